@@ -1,2 +1,97 @@
 // Index performance benchmarks
-// TODO: Implement benchmarks using criterion
+
+use std::path::Path;
+
+use codegraph::config::Config;
+use codegraph::indexer::Indexer;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use tempfile::TempDir;
+
+const DEFAULT_FILES: usize = 10_000;
+const FUNCTIONS_PER_FILE: usize = 8;
+
+/// Corpus size, overridable with `CODEGRAPH_BENCH_FILES` for quick runs
+fn corpus_size() -> usize {
+    std::env::var("CODEGRAPH_BENCH_FILES")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_FILES)
+}
+
+/// Write a synthetic Python corpus where every function calls into the next file
+fn generate_corpus(root: &Path) -> Vec<String> {
+    let files = corpus_size();
+    (0..files)
+        .map(|i| {
+            let dir = root.join(format!("pkg{}", i % 100));
+            std::fs::create_dir_all(&dir).unwrap();
+
+            let mut source = String::new();
+            for f in 0..FUNCTIONS_PER_FILE {
+                source.push_str(&format!(
+                    "def func_{i}_{f}(value):\n    result = func_{next}_{f}(value)\n    return helper_{i}(result)\n\n",
+                    next = (i + 1) % files,
+                ));
+            }
+            source.push_str(&format!("class Model{i}:\n    def run(self):\n        return func_{i}_0(1)\n\n"));
+            source.push_str(&format!("def helper_{i}(value):\n    return value\n"));
+
+            let path = dir.join(format!("module_{}.py", i));
+            std::fs::write(&path, source).unwrap();
+            path.to_string_lossy().to_string()
+        })
+        .collect()
+}
+
+fn bench_full_index(c: &mut Criterion) {
+    let corpus = TempDir::new().unwrap();
+    let files = generate_corpus(corpus.path());
+    let parallel = std::thread::available_parallelism().map_or(4, |n| n.get());
+
+    let mut group = c.benchmark_group(format!("full_index_{}_files", files.len()));
+    group.sample_size(10);
+
+    // One file per transaction on a single thread approximates the old serial loop
+    for (threads, batch_size) in [(1, 1), (parallel, Config::default().indexing.batch_size)] {
+        let mut config = Config::default();
+        config.performance.threads = threads;
+        config.indexing.batch_size = batch_size;
+
+        group.bench_with_input(
+            BenchmarkId::new(format!("threads_{}", threads), format!("batch_{}", batch_size)),
+            &config,
+            |b, config| {
+                b.iter_batched(
+                    || {
+                        let db_dir = TempDir::new().unwrap();
+                        let indexer = Indexer::new(db_dir.path().join("bench.db")).unwrap().with_config(config).unwrap();
+                        (db_dir, indexer)
+                    },
+                    |(_db_dir, indexer)| indexer.sync(&files).unwrap(),
+                    BatchSize::PerIteration,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_unchanged_sync(c: &mut Criterion) {
+    let corpus = TempDir::new().unwrap();
+    let files = generate_corpus(corpus.path());
+
+    let db_dir = TempDir::new().unwrap();
+    let indexer = Indexer::new(db_dir.path().join("bench.db")).unwrap();
+    indexer.sync(&files).unwrap();
+
+    let mut group = c.benchmark_group(format!("unchanged_sync_{}_files", files.len()));
+    group.sample_size(10);
+    group.bench_function("hash_only", |b| {
+        b.iter(|| indexer.sync(&files).unwrap());
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_full_index, bench_unchanged_sync);
+criterion_main!(benches);
@@ -1,2 +1,78 @@
 // Query performance benchmarks
-// TODO: Implement benchmarks using criterion
+
+use std::path::Path;
+
+use codegraph::index::db::SearchFilters;
+use codegraph::indexer::Indexer;
+use codegraph::query::engine::QueryEngine;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tempfile::TempDir;
+
+const DEFAULT_FILES: usize = 2_000;
+
+/// Corpus size, overridable with `CODEGRAPH_BENCH_FILES` for quick runs
+fn corpus_size() -> usize {
+    std::env::var("CODEGRAPH_BENCH_FILES")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_FILES)
+}
+
+/// Write a synthetic Python corpus with camelCase classes and snake_case functions
+fn generate_corpus(root: &Path) -> Vec<String> {
+    let files = corpus_size();
+    (0..files)
+        .map(|i| {
+            let source = format!(
+                "class UserManager{i}:\n    def load_user_record(self, user_id):\n        return fetch_user_{i}(user_id)\n\n\
+                 def fetch_user_{i}(user_id):\n    return session_cache_{i}(user_id)\n\n\
+                 def session_cache_{i}(key):\n    return key\n"
+            );
+            let path = root.join(format!("module_{}.py", i));
+            std::fs::write(&path, source).unwrap();
+            path.to_string_lossy().to_string()
+        })
+        .collect()
+}
+
+fn bench_search(c: &mut Criterion) {
+    let corpus = TempDir::new().unwrap();
+    let files = generate_corpus(corpus.path());
+
+    let db_dir = TempDir::new().unwrap();
+    let indexer = Indexer::new(db_dir.path().join("bench.db")).unwrap();
+    indexer.sync(&files).unwrap();
+    let engine = QueryEngine::new(indexer.db().clone());
+
+    let mut group = c.benchmark_group(format!("search_{}_files", files.len()));
+    let functions = SearchFilters { kind: Some("function".to_string()), ..Default::default() };
+    for (name, query, filters) in [
+        ("exact", "UserManager7", SearchFilters::default()),
+        ("words", "user manager", SearchFilters::default()),
+        ("phrase", "\"load user\"", SearchFilters::default()),
+        ("filtered", "user", functions),
+        ("abbreviation", "usrmgr", SearchFilters::default()),
+    ] {
+        group.bench_with_input(BenchmarkId::new(name, query), &filters, |b, filters| {
+            b.iter(|| engine.search_symbols(query, filters, 20).unwrap());
+        });
+    }
+    group.finish();
+}
+
+fn bench_callers(c: &mut Criterion) {
+    let corpus = TempDir::new().unwrap();
+    let files = generate_corpus(corpus.path());
+
+    let db_dir = TempDir::new().unwrap();
+    let indexer = Indexer::new(db_dir.path().join("bench.db")).unwrap();
+    indexer.sync(&files).unwrap();
+    let engine = QueryEngine::new(indexer.db().clone());
+
+    c.bench_function("find_callers", |b| {
+        b.iter(|| engine.find_callers("fetch_user_7").unwrap());
+    });
+}
+
+criterion_group!(benches, bench_search, bench_callers);
+criterion_main!(benches);
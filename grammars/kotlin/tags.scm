@@ -0,0 +1,36 @@
+; Definitions and references of Kotlin, for the grammar of tree-sitter-kotlin-ng
+;
+; Members of classes, objects and companions are methods and fields, so that
+; Java resolves calls into them. A companion object is a class nested in its
+; class, named `Companion` unless it has a name, as Java sees it. Extension
+; functions are functions with the receiver type in their metadata.
+
+(package_header (qualified_identifier) @package)
+
+(class_declaration "interface" name: (identifier) @name) @definition.interface
+(class_declaration "class" name: (identifier) @name) @definition.class
+(object_declaration name: (identifier) @name) @definition.class
+(companion_object name: (identifier) @name) @definition.class
+((companion_object !name) @definition.class
+  (#set! name "Companion"))
+
+(source_file (function_declaration name: (identifier) @name) @definition.function)
+(source_file (function_declaration [(user_type) (nullable_type) (parenthesized_type)] @receiver . name: (identifier) @name) @definition.function)
+(class_body (function_declaration name: (identifier) @name) @definition.method)
+(class_body (function_declaration [(user_type) (nullable_type) (parenthesized_type)] @receiver . name: (identifier) @name) @definition.method)
+(enum_class_body (function_declaration name: (identifier) @name) @definition.method)
+
+(class_body (property_declaration (variable_declaration (identifier) @name)) @definition.field)
+(class_parameter (identifier) @name) @definition.field
+(enum_entry (identifier) @name) @definition.constant
+
+(call_expression . (identifier) @name) @reference.call
+(call_expression . (navigation_expression (identifier) @name .)) @reference.call
+
+; A superclass is called for its constructor; interfaces are not
+(delegation_specifier (constructor_invocation (user_type (identifier) @name .))) @reference.inheritance
+(delegation_specifier (constructor_invocation (user_type (identifier) @name . (type_arguments)))) @reference.inheritance
+(delegation_specifier (user_type (identifier) @name .)) @reference.implementation
+(delegation_specifier (user_type (identifier) @name . (type_arguments))) @reference.implementation
+
+(function_body) @scope
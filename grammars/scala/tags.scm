@@ -0,0 +1,34 @@
+; Definitions and references of Scala 2 and 3, for the grammar of tree-sitter-scala
+;
+; Objects are classes, so that a companion object's members join its class as
+; they do on the JVM. The methods of an `extension` are functions with the
+; type they extend in their metadata.
+
+(package_clause name: (package_identifier) @package)
+
+(class_definition name: (identifier) @name) @definition.class
+(object_definition name: (identifier) @name) @definition.class
+(trait_definition name: (identifier) @name) @definition.interface
+(enum_definition name: (identifier) @name) @definition.enum
+
+(compilation_unit (function_definition name: (identifier) @name) @definition.function)
+(block (function_definition name: (identifier) @name) @definition.function)
+(template_body (function_definition name: (identifier) @name) @definition.method)
+(template_body (function_declaration name: (identifier) @name) @definition.method)
+(extension_definition
+  parameters: (parameters (parameter type: (_) @receiver))
+  [(function_definition name: (identifier) @name) (function_declaration name: (identifier) @name)] @definition.function)
+
+(template_body (val_definition pattern: (identifier) @name) @definition.field)
+(template_body (var_definition pattern: (identifier) @name) @definition.field)
+(class_parameter name: (identifier) @name) @definition.field
+
+(call_expression function: (identifier) @name) @reference.call
+(call_expression function: (field_expression field: (identifier) @name)) @reference.call
+(instance_expression [(type_identifier) @name (generic_type type: (type_identifier) @name)]) @reference.call
+
+; The first parent is extended, and those after `with` or `,` are mixed in
+(extends_clause . [(type_identifier) @name (generic_type type: (type_identifier) @name)]) @reference.inheritance
+(extends_clause ["with" ","] [(type_identifier) @name (generic_type type: (type_identifier) @name)]) @reference.implementation
+
+(block) @scope
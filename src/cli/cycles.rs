@@ -0,0 +1,62 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::indexer::Indexer;
+use crate::query::cycles::{parse_types, CycleScope, DEPENDENCY_TYPES};
+
+pub async fn find_cycles(project: String, scope: String, types: Option<String>, format: String) -> Result<()> {
+    // Load configuration
+    let config = Config::from_project_dir(&project);
+    let scope = CycleScope::from_str(&scope)?;
+    let types = match types {
+        Some(types) => parse_types(&types)?,
+        None => DEPENDENCY_TYPES.to_vec(),
+    };
+
+    println!("CodeGraph Cycle Detection v0.1.0");
+    println!("Project: {}", project);
+    println!("Scope: {}", scope.as_str());
+    println!("Relationships: {}", types.iter().map(|t| t.as_str()).collect::<Vec<_>>().join(", "));
+    println!("Config: {}", if config.project.name != "unnamed-project" { "loaded" } else { "default" });
+
+    // Initialize indexer
+    let db_path = PathBuf::from(&project).join(".codegraph.db");
+    let indexer = Indexer::new(&db_path)?.with_config(&config)?;
+    let cycles = indexer.query_engine().find_cycles(&types, scope)?;
+
+    if cycles.is_empty() {
+        println!("\n✨ No cycles found");
+        return Ok(());
+    }
+
+    match format.as_str() {
+        "json" => {
+            println!("{}", serde_json::to_string_pretty(&cycles)?);
+        }
+        "text" => {
+            println!("\n🔁 Found {} cycles:", cycles.len());
+            for (i, cycle) in cycles.iter().enumerate() {
+                println!("\n  {}. {} members: {}", i + 1, cycle.members.len(), cycle.members.join(", "));
+                for edge in &cycle.edges {
+                    println!("       {} -> {}  ({}:{} {} {} {})",
+                        edge.from,
+                        edge.to,
+                        edge.via.file,
+                        edge.via.line,
+                        edge.via.from_id,
+                        edge.via.relationship,
+                        edge.via.to_id
+                    );
+                }
+            }
+        }
+        _ => {
+            eprintln!("Unknown format: {}", format);
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::indexer::Indexer;
+use crate::query::deadcode::{group_by_file_and_kind, Confidence, DeadSymbol, EntryPoints};
+
+pub async fn find_dead_code(project: String, min_confidence: String, format: String) -> Result<()> {
+    // Load configuration
+    let config = Config::from_project_dir(&project);
+    let min_confidence = Confidence::from_str(&min_confidence)?;
+
+    println!("CodeGraph Dead Code Report v0.1.0");
+    println!("Project: {}", project);
+    println!("Minimum confidence: {}", min_confidence.as_str());
+    println!("Config: {}", if config.project.name != "unnamed-project" { "loaded" } else { "default" });
+
+    // Initialize indexer
+    let db_path = PathBuf::from(&project).join(".codegraph.db");
+    let indexer = Indexer::new(&db_path)?.with_config(&config)?;
+    let entry_points = EntryPoints::new(&config.deadcode)?;
+
+    let results: Vec<DeadSymbol> = indexer.query_engine()
+        .find_dead_code(&entry_points)?
+        .into_iter()
+        .filter(|symbol| symbol.confidence >= min_confidence)
+        .collect();
+
+    if results.is_empty() {
+        println!("\n✨ No dead code found");
+        return Ok(());
+    }
+
+    match format.as_str() {
+        "json" => {
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        }
+        "text" => {
+            let count = |confidence| results.iter().filter(|r| r.confidence == confidence).count();
+            println!("\n💀 Found {} possibly dead symbols ({} high, {} medium, {} low confidence):",
+                results.len(),
+                count(Confidence::High),
+                count(Confidence::Medium),
+                count(Confidence::Low)
+            );
+
+            for (file, kinds) in group_by_file_and_kind(&results) {
+                println!("\n  {}", file);
+                for (kind, symbols) in kinds {
+                    println!("    {}:", kind);
+                    for symbol in symbols {
+                        println!("      {:>5}  {} [{}] {}",
+                            symbol.line,
+                            symbol.qualified_name,
+                            symbol.confidence.as_str(),
+                            symbol.reason
+                        );
+                    }
+                }
+            }
+        }
+        _ => {
+            eprintln!("Unknown format: {}", format);
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
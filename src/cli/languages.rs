@@ -5,11 +5,17 @@ pub fn list_languages() {
     println!("  - Rust");
     println!("  - Go");
     println!("  - Java");
-    println!("  - Intent");
-    println!("\nPlanned for future:");
+    println!("  - Kotlin/Scala");
     println!("  - TypeScript/JavaScript");
     println!("  - C/C++");
-    println!("  - Kotlin");
+    println!("  - C#");
+    println!("  - Ruby");
+    println!("  - Protocol Buffers");
+    println!("  - SQL");
+    println!("  - Shell, Makefile and Dockerfile");
+    println!("  - Intent");
+    println!("\nOther languages can be added without rebuilding, from a tree-sitter");
+    println!("grammar and queries listed under [[languages.grammars]] in .codegraph.toml.");
     println!("\nTo request a new language, please open an issue:");
     println!("  https://github.com/intent-lang/codegraph/issues");
 }
@@ -4,6 +4,9 @@ pub mod start;
 pub mod serve;
 pub mod index;
 pub mod query;
+pub mod search;
 pub mod impact;
+pub mod deadcode;
+pub mod cycles;
 pub mod stats;
 pub mod languages;
@@ -3,11 +3,20 @@ use std::path::PathBuf;
 
 use crate::config::Config;
 use crate::indexer::Indexer;
-use crate::query::engine::{QueryEngine, QueryResult};
+use crate::query::engine::{route, PathEdge, TransitiveResult};
+
+/// Options that only apply to some query types
+pub struct QueryOptions {
+    /// End of a path query
+    pub to: Option<String>,
+    pub depth: Option<usize>,
+    pub max_paths: usize,
+}
 
 pub async fn query_index(
     query_type: String,
     target: String,
+    options: QueryOptions,
     project: String,
     format: String,
 ) -> Result<()> {
@@ -23,8 +32,30 @@ pub async fn query_index(
 
     // Initialize indexer
     let db_path = PathBuf::from(&project).join(".codegraph.db");
-    let indexer = Indexer::new(&db_path)?;
-    let query_engine = QueryEngine::new(indexer.db().clone());
+    let indexer = Indexer::new(&db_path)?.with_config(&config)?;
+    let query_engine = indexer.query_engine();
+
+    let transitive = match query_type.as_str() {
+        "transitive_callers" => Some(query_engine.transitive_callers(&target, options.depth)?),
+        "transitive_callees" => Some(query_engine.transitive_callees(&target, options.depth)?),
+        "supertypes" => Some(query_engine.supertypes(&target, options.depth)?),
+        "subtypes" => Some(query_engine.subtypes(&target, options.depth)?),
+        _ => None,
+    };
+    if let Some(results) = transitive {
+        print_transitive(&query_type, &target, results, &format)?;
+        return Ok(());
+    }
+
+    if query_type == "path" {
+        let Some(to) = options.to else {
+            eprintln!("The path query needs an end symbol: --to <symbol>");
+            std::process::exit(1);
+        };
+        let paths = query_engine.find_paths(&target, &to, options.depth, options.max_paths)?;
+        print_paths(&target, &to, paths, &format)?;
+        return Ok(());
+    }
 
     // Execute query
     let results = match query_type.as_str() {
@@ -79,3 +110,83 @@ pub async fn query_index(
 
     Ok(())
 }
+
+/// Print transitive results with the depth and route of each hit
+fn print_transitive(query_type: &str, target: &str, results: Vec<TransitiveResult>, format: &str) -> Result<()> {
+    if results.is_empty() {
+        println!("\nNo results found for {} of '{}'", query_type, target);
+        return Ok(());
+    }
+
+    println!("\nFound {} results:", results.len());
+
+    match format {
+        "json" => {
+            let json_results: Vec<serde_json::Value> = results
+                .into_iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "symbol_id": r.symbol_id,
+                        "qualified_name": r.qualified_name,
+                        "file": r.file,
+                        "line": r.line,
+                        "kind": r.kind,
+                        "depth": r.depth,
+                        "path": r.path
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&json_results)?);
+        }
+        "text" => {
+            for result in results {
+                println!("  [{}] {}:{} - {} ({})",
+                    result.depth,
+                    result.file,
+                    result.line,
+                    result.qualified_name,
+                    result.kind
+                );
+                if result.depth > 1 {
+                    println!("      via {}", result.route().join(" -> "));
+                }
+            }
+        }
+        _ => {
+            eprintln!("Unknown format: {}", format);
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print path query results, one line per hop
+fn print_paths(from: &str, to: &str, paths: Vec<Vec<PathEdge>>, format: &str) -> Result<()> {
+    if paths.is_empty() {
+        println!("\nNo paths found from '{}' to '{}'", from, to);
+        return Ok(());
+    }
+
+    println!("\nFound {} paths:", paths.len());
+
+    match format {
+        "json" => {
+            println!("{}", serde_json::to_string_pretty(&paths)?);
+        }
+        "text" => {
+            for (i, path) in paths.iter().enumerate() {
+                println!("  {}. {} ({} hops)", i + 1, route(path).join(" -> "), path.len());
+                for edge in path {
+                    println!("       {}:{} {} {} {}", edge.file, edge.line, edge.from_id, edge.relationship, edge.to_id);
+                }
+            }
+        }
+        _ => {
+            eprintln!("Unknown format: {}", format);
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
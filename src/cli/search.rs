@@ -0,0 +1,49 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::index::db::SearchFilters;
+use crate::indexer::Indexer;
+
+pub async fn search_symbols(query: String, filters: SearchFilters, limit: usize, project: String, format: String) -> Result<()> {
+    // Load configuration
+    let config = Config::from_project_dir(&project);
+
+    // Initialize indexer
+    let db_path = PathBuf::from(&project).join(".codegraph.db");
+    let indexer = Indexer::new(&db_path)?.with_config(&config)?;
+
+    let results = indexer.query_engine().search_symbols(&query, &filters, limit)?;
+
+    match format.as_str() {
+        "json" => {
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        }
+        "text" => {
+            if results.is_empty() {
+                println!("No symbols found matching '{}'", query);
+                return Ok(());
+            }
+
+            println!("Found {} symbols matching '{}':", results.len(), query);
+            for result in &results {
+                println!("  {}:{} - {} ({}, {})",
+                    result.file,
+                    result.line,
+                    result.qualified_name,
+                    result.kind,
+                    result.language
+                );
+                if let Some(signature) = &result.signature {
+                    println!("      {}", signature);
+                }
+            }
+        }
+        _ => {
+            eprintln!("Unknown format: {}", format);
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
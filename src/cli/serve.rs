@@ -1,5 +1,6 @@
 use anyhow::Result;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tracing::info;
 
 use crate::config::Config;
@@ -16,7 +17,7 @@ pub async fn serve_stdio(project: String) -> Result<()> {
 
     // Initialize indexer
     let db_path = PathBuf::from(&project).join(".codegraph.db");
-    let indexer = Indexer::new(&db_path)?;
+    let indexer = Indexer::new(&db_path)?.with_config(&config)?;
 
     // Check if index exists
     let stats = indexer.get_stats()?;
@@ -25,7 +26,7 @@ pub async fn serve_stdio(project: String) -> Result<()> {
     }
 
     // Start MCP server
-    let server = McpServer::new(indexer);
+    let server = McpServer::new(Arc::new(indexer));
     server.run().await?;
 
     Ok(())
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::config::Config;
 use crate::indexer::Indexer;
@@ -25,7 +25,7 @@ pub async fn show_stats(project: String, verbose: bool) -> Result<()> {
     println!("  Total relationships: {}", stats.total_relationships);
 
     // Calculate index size
-    let db_size = get_db_size(&db_path)?;
+    let db_size = get_db_size(indexer.db().path())?;
     println!("  Index size: {:.2} MB", db_size);
 
     if verbose {
@@ -62,7 +62,7 @@ pub async fn show_stats(project: String, verbose: bool) -> Result<()> {
     Ok(())
 }
 
-fn get_db_size(db_path: &PathBuf) -> Result<f64> {
+fn get_db_size(db_path: &Path) -> Result<f64> {
     let metadata = std::fs::metadata(db_path)?;
     let size_bytes = metadata.len() as f64;
     let size_mb = size_bytes / (1024.0 * 1024.0);
@@ -1,7 +1,13 @@
 // Configuration management for CodeGraph
 
 use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
+use std::path::Path;
+
+/// Languages with a built-in parser
+pub const BUILTIN_LANGUAGES: &[&str] = &[
+    "c", "cpp", "csharp", "dockerfile", "go", "intent", "java", "javascript", "kotlin",
+    "makefile", "protobuf", "python", "ruby", "rust", "scala", "shell", "sql", "typescript",
+];
 
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +19,12 @@ pub struct Config {
     pub performance: PerformanceConfig,
     pub logging: LoggingConfig,
     pub mcp: McpConfig,
+    #[serde(default)]
+    pub deadcode: DeadCodeConfig,
+    #[serde(default)]
+    pub c: CConfig,
+    #[serde(default)]
+    pub sql: SqlConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +36,33 @@ pub struct ProjectConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LanguagesConfig {
     pub enabled: Vec<String>,
+    /// Languages whose grammar is loaded at runtime
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grammars: Vec<GrammarConfig>,
+}
+
+/// A language defined by a tree-sitter grammar and `.scm` queries, so that
+/// indexing it needs no rebuild of codegraph
+///
+/// Relative paths are resolved against the directory of the configuration file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrammarConfig {
+    /// Language name, as listed in `enabled`
+    pub name: String,
+    /// Extensions of the language's files, without the dot
+    pub extensions: Vec<String>,
+    /// Grammar compiled to a shared library (`.so`, `.dylib`, `.dll`) or to `.wasm`
+    pub library: String,
+    /// Language function a shared library exports, `tree_sitter_<name>` by
+    /// default, or the language name a WASM module was built with
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// Query files whose captures mark definitions, references and scopes
+    pub queries: Vec<String>,
+    /// Other languages whose definitions references may resolve to, such as
+    /// `java` for another JVM language
+    #[serde(default)]
+    pub resolve_in: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +99,72 @@ pub struct McpConfig {
     pub port: u16,
 }
 
+/// Symbols that dead code detection never reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeadCodeConfig {
+    /// Globs over symbol names or qualified names
+    pub entry_points: Vec<String>,
+    /// Test files, with the same glob semantics as `[indexing]`
+    pub test_files: Vec<String>,
+    /// Languages whose public symbols are exported API
+    pub public_api_languages: Vec<String>,
+}
+
+/// Include resolution for C and C++, on top of any `compile_commands.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CConfig {
+    /// Directories searched for `#include`; relative ones are tried under
+    /// every ancestor of the including file
+    pub include_paths: Vec<String>,
+    /// Macros for `#if` around includes, as `NAME` or `NAME=VALUE`
+    pub defines: Vec<String>,
+}
+
+/// SQL found outside `.sql` files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SqlConfig {
+    /// Link the tables named by SQL in Python, Go and Java string literals
+    pub scan_literals: bool,
+}
+
+impl Default for DeadCodeConfig {
+    fn default() -> Self {
+        Self {
+            entry_points: vec![
+                "main".to_string(),
+                "__*__".to_string(),
+                "init".to_string(),
+                "setUp".to_string(),
+                "tearDown".to_string(),
+                "test_*".to_string(),
+                "Test*".to_string(),
+                "Benchmark*".to_string(),
+                "Example*".to_string(),
+                "Fuzz*".to_string(),
+                "tests::*".to_string(),
+                "*::tests::*".to_string(),
+            ],
+            test_files: vec![
+                "test_*.py".to_string(),
+                "*_test.py".to_string(),
+                "conftest.py".to_string(),
+                "*_test.go".to_string(),
+                "tests/".to_string(),
+                "test/".to_string(),
+            ],
+            public_api_languages: vec![
+                "rust".to_string(),
+                "go".to_string(),
+                "java".to_string(),
+                "csharp".to_string(),
+            ],
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -73,8 +178,22 @@ impl Default for Config {
                     "rust".to_string(),
                     "go".to_string(),
                     "java".to_string(),
+                    "kotlin".to_string(),
+                    "scala".to_string(),
+                    "typescript".to_string(),
+                    "javascript".to_string(),
+                    "c".to_string(),
+                    "cpp".to_string(),
+                    "csharp".to_string(),
+                    "ruby".to_string(),
+                    "protobuf".to_string(),
+                    "sql".to_string(),
+                    "shell".to_string(),
+                    "makefile".to_string(),
+                    "dockerfile".to_string(),
                     "intent".to_string(),
                 ],
+                grammars: vec![],
             },
             indexing: IndexingConfig {
                 exclude: vec![
@@ -107,6 +226,9 @@ impl Default for Config {
                 transport: "stdio".to_string(),
                 port: 3000,
             },
+            deadcode: DeadCodeConfig::default(),
+            c: CConfig::default(),
+            sql: SqlConfig::default(),
         }
     }
 }
@@ -114,8 +236,11 @@ impl Default for Config {
 impl Config {
     /// Load configuration from a TOML file
     pub fn from_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
-        let content = std::fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+        let content = std::fs::read_to_string(&path)?;
+        let mut config: Config = toml::from_str(&content)?;
+        if let Some(directory) = path.as_ref().parent() {
+            config.resolve_paths(directory);
+        }
         config.validate()?;
         Ok(config)
     }
@@ -138,62 +263,36 @@ impl Config {
         }
     }
 
-    /// Check if a file path should be indexed based on include/exclude patterns
+    /// Check if a path relative to the project root passes the include/exclude patterns
+    ///
+    /// Builds the matcher on every call; use `ProjectScanner` for whole projects.
     pub fn should_index_file(&self, file_path: &str) -> bool {
-        let path = Path::new(file_path);
-
-        // Check exclude patterns first
-        for pattern in &self.indexing.exclude {
-            if self.matches_pattern(file_path, pattern) {
-                return false;
-            }
-        }
+        crate::indexer::scanner::PathFilter::new(&self.indexing)
+            .is_ok_and(|filter| filter.is_included(Path::new(file_path)))
+    }
 
-        // If include patterns are specified, file must match at least one
-        if !self.indexing.include.is_empty() {
-            for pattern in &self.indexing.include {
-                if self.matches_pattern(file_path, pattern) {
-                    return true;
-                }
-            }
-            return false; // Include patterns specified but none matched
+    /// Make the grammar paths relative to the configuration file absolute
+    fn resolve_paths(&mut self, directory: &Path) {
+        let resolve = |path: &mut String| *path = directory.join(&*path).to_string_lossy().to_string();
+        for grammar in &mut self.languages.grammars {
+            resolve(&mut grammar.library);
+            grammar.queries.iter_mut().for_each(resolve);
         }
-
-        // No include patterns, and not excluded, so index it
-        true
     }
 
-    /// Simple pattern matching (supports glob-style patterns)
-    fn matches_pattern(&self, file_path: &str, pattern: &str) -> bool {
-        // Simple implementation - could be enhanced with proper glob matching
-        if pattern.ends_with('/') {
-            // Directory pattern
-            file_path.starts_with(pattern) || file_path.contains(&format!("/{}", pattern.trim_end_matches('/')))
-        } else if pattern.starts_with("*.") {
-            // File pattern like *.test.*
-            let pattern_part = &pattern[2..]; // Remove *.
-            file_path.contains(pattern_part)
-        } else if pattern.contains("**") {
-            // Recursive pattern - simplified for **/__tests__/**
-            if pattern == "**/__tests__/**" {
-                file_path.contains("/__tests__/")
-            } else {
-                false
-            }
-        } else {
-            // Exact match or prefix
-            file_path.contains(pattern)
-        }
+    /// Languages with a parser: the built-in ones and those with a grammar
+    pub fn supported_languages(&self) -> Vec<String> {
+        BUILTIN_LANGUAGES.iter()
+            .map(|language| language.to_string())
+            .chain(self.languages.grammars.iter().map(|grammar| grammar.name.clone()))
+            .collect()
     }
 
     /// Get enabled languages, filtered by what's actually supported
     pub fn get_enabled_languages(&self) -> Vec<String> {
-        let supported = vec![
-            "python", "rust", "go", "java", "intent"
-        ];
-
+        let supported = self.supported_languages();
         self.languages.enabled.iter()
-            .filter(|lang| supported.contains(&lang.as_str()))
+            .filter(|lang| supported.contains(lang))
             .cloned()
             .collect()
     }
@@ -206,9 +305,28 @@ impl Config {
         }
 
         // Validate languages
-        let supported_languages = ["python", "rust", "go", "java", "intent"];
+        for (index, grammar) in self.languages.grammars.iter().enumerate() {
+            if grammar.name.is_empty() {
+                return Err(anyhow::anyhow!("Grammar name cannot be empty"));
+            }
+            if BUILTIN_LANGUAGES.contains(&grammar.name.as_str()) || self.languages.grammars[..index].iter().any(|other| other.name == grammar.name) {
+                return Err(anyhow::anyhow!("Grammar {} is already defined", grammar.name));
+            }
+            if grammar.extensions.is_empty() {
+                return Err(anyhow::anyhow!("Grammar {} needs at least one extension", grammar.name));
+            }
+            if grammar.queries.is_empty() {
+                return Err(anyhow::anyhow!("Grammar {} needs at least one query file", grammar.name));
+            }
+        }
+        let supported = self.supported_languages();
+        for grammar in &self.languages.grammars {
+            if let Some(language) = grammar.resolve_in.iter().find(|language| !supported.contains(language)) {
+                return Err(anyhow::anyhow!("Grammar {} resolves in unsupported language {}", grammar.name, language));
+            }
+        }
         for lang in &self.languages.enabled {
-            if !supported_languages.contains(&lang.as_str()) {
+            if !supported.contains(lang) {
                 return Err(anyhow::anyhow!("Unsupported language: {}", lang));
             }
         }
@@ -256,6 +374,9 @@ impl Config {
             return Err(anyhow::anyhow!("MCP port must be greater than 0"));
         }
 
+        // Validate dead code patterns
+        crate::query::deadcode::EntryPoints::new(&self.deadcode)?;
+
         Ok(())
     }
 }
@@ -294,18 +415,24 @@ mod tests {
 
     #[test]
     fn test_pattern_matching() {
-        let config = Config::default();
+        let matches_pattern = |file_path: &str, pattern: &str| {
+            let mut config = Config::default();
+            config.indexing.exclude = vec![pattern.to_string()];
+            !config.should_index_file(file_path)
+        };
 
         // Directory patterns
-        assert!(config.matches_pattern("target/debug/file", "target/"));
-        assert!(config.matches_pattern("src/target/file", "target/"));
+        assert!(matches_pattern("target/debug/file", "target/"));
+        assert!(matches_pattern("src/target/file", "target/"));
 
         // Extension patterns
-        assert!(config.matches_pattern("test.py", "*.py"));
-        assert!(!config.matches_pattern("test.rs", "*.py"));
+        assert!(matches_pattern("test.py", "*.py"));
+        assert!(!matches_pattern("test.rs", "*.py"));
 
         // Recursive patterns
-        assert!(config.matches_pattern("src/__tests__/test.py", "**/__tests__/**"));
+        assert!(matches_pattern("src/__tests__/test.py", "**/__tests__/**"));
+        assert!(matches_pattern("a/b/__tests__/c/test.py", "**/__tests__/**"));
+        assert!(!matches_pattern("src/tests/test.py", "**/__tests__/**"));
     }
 
     #[test]
@@ -323,6 +450,43 @@ mod tests {
         // Test invalid language
         config.languages.enabled = vec!["invalid_lang".to_string()];
         assert!(config.validate().is_err());
+        config.languages.enabled = vec!["typescript".to_string(), "javascript".to_string()];
+        assert!(config.validate().is_ok());
+        config.languages.enabled = vec!["c".to_string(), "cpp".to_string()];
+        assert!(config.validate().is_ok());
+        config.languages.enabled = vec!["java".to_string(), "kotlin".to_string(), "scala".to_string()];
+        assert!(config.validate().is_ok());
+        config.languages.enabled = vec!["csharp".to_string(), "ruby".to_string()];
+        assert!(config.validate().is_ok());
+        config.languages.enabled = vec!["protobuf".to_string(), "sql".to_string()];
+        assert!(config.validate().is_ok());
+        config.languages.enabled = vec!["shell".to_string(), "makefile".to_string(), "dockerfile".to_string()];
+        assert!(config.validate().is_ok());
+        config.languages.enabled = vec!["python".to_string()];
+
+        // Test grammars loaded at runtime
+        let lua = GrammarConfig {
+            name: "lua".to_string(),
+            extensions: vec!["lua".to_string()],
+            library: "grammars/lua.so".to_string(),
+            symbol: None,
+            queries: vec!["queries/lua/tags.scm".to_string()],
+            resolve_in: vec![],
+        };
+        config.languages.enabled = vec!["lua".to_string()];
+        assert!(config.validate().is_err());
+        config.languages.grammars = vec![lua.clone()];
+        assert!(config.validate().is_ok());
+        assert_eq!(config.get_enabled_languages(), vec!["lua"]);
+        config.languages.grammars = vec![GrammarConfig { name: "python".to_string(), ..lua.clone() }];
+        assert!(config.validate().is_err());
+        config.languages.grammars = vec![GrammarConfig { queries: vec![], ..lua.clone() }];
+        assert!(config.validate().is_err());
+        config.languages.grammars = vec![GrammarConfig { resolve_in: vec!["c".to_string()], ..lua.clone() }];
+        assert!(config.validate().is_ok());
+        config.languages.grammars = vec![GrammarConfig { resolve_in: vec!["moonscript".to_string()], ..lua }];
+        assert!(config.validate().is_err());
+        config.languages.grammars = vec![];
         config.languages.enabled = vec!["python".to_string()];
 
         // Test invalid batch size
@@ -339,5 +503,43 @@ mod tests {
         config.mcp.transport = "invalid".to_string();
         assert!(config.validate().is_err());
         config.mcp.transport = "stdio".to_string();
+
+        // Test invalid entry point glob
+        config.deadcode.entry_points = vec!["[main".to_string()];
+        assert!(config.validate().is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_deadcode_section_is_optional() {
+        let mut toml = toml::to_string(&Config::default()).unwrap();
+        let start = toml.find("[deadcode]").unwrap();
+        toml.truncate(start);
+
+        let config: Config = toml::from_str(&toml).unwrap();
+        assert!(config.deadcode.entry_points.contains(&"main".to_string()));
+    }
+
+    #[test]
+    fn test_grammar_paths_are_relative_to_the_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".codegraph.toml");
+        let mut toml = toml::to_string(&Config::default()).unwrap();
+        toml.push_str(concat!(
+            "\n[[languages.grammars]]\n",
+            "name = \"lua\"\n",
+            "extensions = [\"lua\"]\n",
+            "library = \"grammars/lua.so\"\n",
+            "queries = [\"grammars/lua/tags.scm\", \"/opt/queries/locals.scm\"]\n",
+        ));
+        std::fs::write(&path, toml).unwrap();
+
+        let config = Config::from_file(&path).unwrap();
+        let grammar = &config.languages.grammars[0];
+        assert_eq!(Path::new(&grammar.library), dir.path().join("grammars/lua.so"));
+        assert_eq!(grammar.queries, vec![
+            dir.path().join("grammars/lua/tags.scm").to_string_lossy().to_string(),
+            "/opt/queries/locals.scm".to_string(),
+        ]);
+        assert!(config.supported_languages().contains(&"lua".to_string()));
+    }
+}
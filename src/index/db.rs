@@ -1,13 +1,17 @@
 use anyhow::{Context, Result};
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{params, Connection, OptionalExtension, Row};
+use rusqlite::{named_params, params, Connection, OptionalExtension, Row};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{debug, info};
 
 use super::schema::init_schema;
+use super::search::search_terms;
+use super::UNRESOLVED_PREFIX;
 
 /// Type alias for connection pool
 pub type ConnectionPool = Pool<SqliteConnectionManager>;
@@ -56,6 +60,33 @@ impl From<&super::Symbol> for Symbol {
     }
 }
 
+impl From<&Symbol> for super::Symbol {
+    fn from(symbol: &Symbol) -> Self {
+        Self {
+            id: symbol.id.clone(),
+            kind: symbol.kind.into(),
+            name: symbol.name.clone(),
+            qualified_name: symbol.qualified_name.clone(),
+            location: super::Location {
+                file: symbol.file.clone(),
+                line: symbol.line as u32,
+                column: symbol.column as u32,
+                end_line: symbol.end_line as u32,
+                end_column: symbol.end_column as u32,
+            },
+            signature: symbol.signature.clone(),
+            type_info: symbol.type_.clone(),
+            visibility: symbol.visibility.into(),
+            language: symbol.language.clone(),
+            metadata: symbol.metadata.as_deref()
+                .and_then(|m| serde_json::from_str(m).ok())
+                .unwrap_or(serde_json::Value::Null),
+            content_hash: symbol.content_hash.clone(),
+            last_indexed: symbol.last_indexed as i64,
+        }
+    }
+}
+
 impl From<super::SymbolKind> for SymbolKind {
     fn from(kind: super::SymbolKind) -> Self {
         match kind {
@@ -73,6 +104,23 @@ impl From<super::SymbolKind> for SymbolKind {
     }
 }
 
+impl From<SymbolKind> for super::SymbolKind {
+    fn from(kind: SymbolKind) -> Self {
+        match kind {
+            SymbolKind::Function => Self::Function,
+            SymbolKind::Type => Self::Type,
+            SymbolKind::Variable => Self::Variable,
+            SymbolKind::Context => Self::Context,
+            SymbolKind::Module => Self::Module,
+            SymbolKind::Class => Self::Class,
+            SymbolKind::Method => Self::Method,
+            SymbolKind::Field => Self::Field,
+            SymbolKind::Parameter => Self::Parameter,
+            SymbolKind::Import => Self::Import,
+        }
+    }
+}
+
 /// Symbol kinds
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -104,8 +152,12 @@ impl SymbolKind {
             SymbolKind::Import => "import",
         }
     }
+}
+
+impl FromStr for SymbolKind {
+    type Err = anyhow::Error;
 
-    pub fn from_str(s: &str) -> Result<Self> {
+    fn from_str(s: &str) -> Result<Self> {
         match s {
             "function" => Ok(SymbolKind::Function),
             "type" => Ok(SymbolKind::Type),
@@ -132,6 +184,16 @@ impl From<super::Visibility> for Visibility {
     }
 }
 
+impl From<Visibility> for super::Visibility {
+    fn from(vis: Visibility) -> Self {
+        match vis {
+            Visibility::Public => Self::Public,
+            Visibility::Private => Self::Private,
+            Visibility::Internal => Self::Internal,
+        }
+    }
+}
+
 /// Symbol visibility
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -149,8 +211,12 @@ impl Visibility {
             Visibility::Internal => "internal",
         }
     }
+}
+
+impl FromStr for Visibility {
+    type Err = anyhow::Error;
 
-    pub fn from_str(s: &str) -> Result<Self> {
+    fn from_str(s: &str) -> Result<Self> {
         match s {
             "public" => Ok(Visibility::Public),
             "private" => Ok(Visibility::Private),
@@ -224,8 +290,12 @@ impl RelationshipType {
             RelationshipType::Contains => "contains",
         }
     }
+}
+
+impl FromStr for RelationshipType {
+    type Err = anyhow::Error;
 
-    pub fn from_str(s: &str) -> Result<Self> {
+    fn from_str(s: &str) -> Result<Self> {
         match s {
             "calls" => Ok(RelationshipType::Calls),
             "references" => Ok(RelationshipType::References),
@@ -260,7 +330,10 @@ impl IndexDatabase {
         }
 
         // Create connection manager
-        let manager = SqliteConnectionManager::file(&db_path);
+        // WAL lets the MCP server keep reading while the watcher writes
+        let manager = SqliteConnectionManager::file(&db_path).with_init(|conn| {
+            conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL; PRAGMA busy_timeout = 5000;")
+        });
 
         // Create connection pool
         let pool = Pool::builder()
@@ -277,41 +350,24 @@ impl IndexDatabase {
         Ok(Self { pool, db_path })
     }
 
+    /// Path of the database file
+    pub fn path(&self) -> &Path {
+        &self.db_path
+    }
+
     /// Get a connection from the pool
     pub fn get_conn(&self) -> Result<r2d2::PooledConnection<SqliteConnectionManager>> {
         self.pool.get().context("Failed to get connection from pool")
     }
 
     /// Insert a symbol
+    #[cfg(test)]
     pub fn insert_symbol(&self, symbol: &Symbol) -> Result<()> {
         let conn = self.get_conn()?;
 
         debug!("Inserting symbol: {}", symbol.qualified_name);
 
-        conn.execute(
-            "INSERT OR REPLACE INTO symbols (
-                id, kind, name, qualified_name, file, line, column, end_line, end_column,
-                signature, type, visibility, language, metadata, content_hash, last_indexed
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
-            params![
-                symbol.id,
-                symbol.kind.as_str(),
-                symbol.name,
-                symbol.qualified_name,
-                symbol.file,
-                symbol.line as i64,
-                symbol.column as i64,
-                symbol.end_line as i64,
-                symbol.end_column as i64,
-                symbol.signature,
-                symbol.type_,
-                symbol.visibility.as_str(),
-                symbol.language,
-                symbol.metadata,
-                symbol.content_hash,
-                symbol.last_indexed as i64,
-            ],
-        )?;
+        insert_symbol_row(&conn, symbol)?;
 
         Ok(())
     }
@@ -327,7 +383,7 @@ impl IndexDatabase {
         )?;
 
         let symbol = stmt
-            .query_row([id], |row| Ok(row_to_symbol(row)?))
+            .query_row([id], row_to_symbol)
             .optional()?;
 
         Ok(symbol)
@@ -344,7 +400,24 @@ impl IndexDatabase {
         )?;
 
         let symbols = stmt
-            .query_map([qualified_name], |row| Ok(row_to_symbol(row)?))?
+            .query_map([qualified_name], row_to_symbol)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(symbols)
+    }
+
+    /// Load every symbol in the index
+    pub fn all_symbols(&self) -> Result<Vec<Symbol>> {
+        let conn = self.get_conn()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, kind, name, qualified_name, file, line, column, end_line, end_column,
+                    signature, type, visibility, language, metadata, content_hash, last_indexed
+             FROM symbols",
+        )?;
+
+        let symbols = stmt
+            .query_map([], row_to_symbol)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(symbols)
@@ -361,41 +434,333 @@ impl IndexDatabase {
         )?;
 
         let symbols = stmt
-            .query_map([file], |row| Ok(row_to_symbol(row)?))?
+            .query_map([file], row_to_symbol)?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(symbols)
     }
 
-    /// Delete symbols by file
-    pub fn delete_symbols_by_file(&self, file: &str) -> Result<()> {
+    /// Full-text search over symbol names, ranked by BM25
+    ///
+    /// `match_expression` is an FTS5 query as built by
+    /// [`super::search::match_expression`]. Scores are negated BM25 ranks, so
+    /// higher is better, plus [`EXACT_NAME_BONUS`] for symbols named exactly
+    /// `exact_name`.
+    pub fn search_symbols(
+        &self,
+        match_expression: &str,
+        exact_name: &str,
+        filters: &SearchFilters,
+        limit: usize,
+    ) -> Result<Vec<(Symbol, f64)>> {
+        let conn = self.get_conn()?;
+
+        let sql = format!(
+            "SELECT s.id, s.kind, s.name, s.qualified_name, s.file, s.line, s.column, s.end_line, s.end_column,
+                    s.signature, s.type, s.visibility, s.language, s.metadata, s.content_hash, s.last_indexed,
+                    (s.name = :exact COLLATE NOCASE) * {} - bm25(symbols_fts, 10.0, 5.0, 2.0, 1.0) AS score
+             FROM symbols_fts JOIN symbols s ON s.rowid = symbols_fts.rowid
+             WHERE symbols_fts MATCH :query AND {}
+             ORDER BY score DESC
+             LIMIT :limit",
+            EXACT_NAME_BONUS,
+            SEARCH_FILTER_SQL,
+        );
+        let mut stmt = conn.prepare(&sql)?;
+
+        let results = stmt
+            .query_map(
+                named_params! {
+                    ":query": match_expression,
+                    ":exact": exact_name,
+                    ":limit": limit as i64,
+                    ":kind": filters.kind,
+                    ":language": filters.language,
+                    ":file": filters.file,
+                    ":visibility": filters.visibility,
+                    ":decorator": filters.decorator,
+                },
+                |row| Ok((row_to_symbol(row)?, row.get::<_, f64>(16)?)),
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(results)
+    }
+
+    /// Shortest symbols whose name holds the characters of an abbreviation in
+    /// order, starting with its first, as candidates for abbreviation matching
+    pub fn find_symbols_by_abbreviation(&self, abbreviation: &str, filters: &SearchFilters, limit: usize) -> Result<Vec<Symbol>> {
         let conn = self.get_conn()?;
-        conn.execute("DELETE FROM symbols WHERE file = ?1", [file])?;
+
+        // `usrmgr` becomes `u%s%r%m%g%r%`; LIKE ignores ASCII case
+        let pattern: String = abbreviation.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(|c| [c, '%'])
+            .collect();
+        let sql = format!(
+            "SELECT s.id, s.kind, s.name, s.qualified_name, s.file, s.line, s.column, s.end_line, s.end_column,
+                    s.signature, s.type, s.visibility, s.language, s.metadata, s.content_hash, s.last_indexed
+             FROM symbols s
+             WHERE s.name LIKE :pattern AND {}
+             ORDER BY length(replace(s.name, '_', '')), s.qualified_name
+             LIMIT :limit",
+            SEARCH_FILTER_SQL,
+        );
+        let mut stmt = conn.prepare(&sql)?;
+
+        let symbols = stmt
+            .query_map(
+                named_params! {
+                    ":pattern": pattern,
+                    ":kind": filters.kind,
+                    ":language": filters.language,
+                    ":file": filters.file,
+                    ":visibility": filters.visibility,
+                    ":decorator": filters.decorator,
+                    ":limit": limit as i64,
+                },
+                row_to_symbol,
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(symbols)
+    }
+
+    /// Replace everything indexed for a file in a single transaction.
+    ///
+    /// Drops the file's previous symbols together with every relationship
+    /// that starts or ends at one of them, inserts the new parse and updates
+    /// the `files` row. Readers never observe a half-updated file.
+    pub fn replace_file(
+        &self,
+        file_path: &str,
+        language: &str,
+        content_hash: &str,
+        symbols: &[Symbol],
+    ) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+        replace_file_rows(&tx, file_path, language, content_hash, symbols)?;
+        tx.commit()?;
+
+        debug!("Replaced {} symbols for {}", symbols.len(), file_path);
         Ok(())
     }
 
+    /// Remove a file and everything derived from it in a single transaction
+    pub fn remove_file(&self, file_path: &str) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+
+        remove_file_rows(&tx, file_path)?;
+        tx.commit()?;
+
+        debug!("Removed {} from index", file_path);
+        Ok(())
+    }
+
+    /// Get the content hash recorded for every indexed file, keyed by path
+    pub fn file_hashes(&self) -> Result<HashMap<String, String>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare("SELECT path, content_hash FROM files")?;
+
+        let hashes = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<HashMap<_, _>, _>>()?;
+
+        Ok(hashes)
+    }
+
     /// Insert a relationship
+    #[cfg(test)]
     pub fn insert_relationship(&self, rel: &Relationship) -> Result<()> {
         let conn = self.get_conn()?;
 
         debug!("Inserting relationship: {} -> {}", rel.from_id, rel.to_id);
 
-        conn.execute(
-            "INSERT INTO relationships (from_id, to_id, type, file, line, metadata)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                rel.from_id,
-                rel.to_id,
-                rel.type_.as_str(),
-                rel.file,
-                rel.line as i64,
-                rel.metadata,
-            ],
+        insert_relationship_row(&conn, rel)?;
+
+        Ok(())
+    }
+
+    /// Symbols of the given kinds that no relationship of the given types
+    /// points at, other than from the symbol itself
+    pub fn find_unreferenced_symbols(&self, kinds: &[SymbolKind], types: &[RelationshipType]) -> Result<Vec<Symbol>> {
+        let conn = self.get_conn()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, kind, name, qualified_name, file, line, column, end_line, end_column,
+                    signature, type, visibility, language, metadata, content_hash, last_indexed
+             FROM symbols s
+             WHERE kind IN (SELECT value FROM json_each(:kinds))
+               AND NOT EXISTS (
+                   SELECT 1 FROM relationships r
+                   WHERE r.to_id = s.id AND r.from_id != s.id
+                     AND r.type IN (SELECT value FROM json_each(:types))
+                     -- An import edge only stands for a dependency between modules
+                     AND json_extract(r.metadata, '$.import') IS NULL
+               )
+             ORDER BY file, kind, line, qualified_name",
+        )?;
+
+        let symbols = stmt
+            .query_map(
+                named_params! {
+                    ":kinds": serde_json::to_string(&kinds.iter().map(|k| k.as_str()).collect::<Vec<_>>())?,
+                    ":types": serde_json::to_string(&types.iter().map(|t| t.as_str()).collect::<Vec<_>>())?,
+                },
+                row_to_symbol,
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(symbols)
+    }
+
+    /// Every relationship of the given types with the files of both ends
+    pub fn find_relationships_with_files(&self, types: &[RelationshipType]) -> Result<Vec<(Relationship, String, String)>> {
+        let conn = self.get_conn()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT r.from_id, r.to_id, r.type, r.file, r.line, r.metadata, f.file, t.file
+             FROM relationships r
+             JOIN symbols f ON f.id = r.from_id
+             JOIN symbols t ON t.id = r.to_id
+             WHERE r.type IN (SELECT value FROM json_each(?1))
+             ORDER BY r.file, r.line",
         )?;
 
+        let types = serde_json::to_string(&types.iter().map(|t| t.as_str()).collect::<Vec<_>>())?;
+        let relationships = stmt
+            .query_map([types], |row| Ok((row_to_relationship(row)?, row.get(6)?, row.get(7)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(relationships)
+    }
+
+    /// Names of every reference that matched no symbol
+    pub fn unresolved_names(&self) -> Result<HashSet<String>> {
+        let conn = self.get_conn()?;
+
+        let mut stmt = conn.prepare("SELECT DISTINCT name FROM unresolved_references")?;
+        let names = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<HashSet<_>, _>>()?;
+
+        Ok(names)
+    }
+
+    /// Follow relationships of the given types breadth-first from a set of symbols
+    ///
+    /// Returns every relationship crossed within `max_depth` hops together with
+    /// the depth it was crossed at. A symbol reachable along several routes
+    /// appears once per route and depth, so callers pick the shallowest. Links
+    /// from generated code, such as gRPC stubs, to their definitions are skipped.
+    pub fn traverse(
+        &self,
+        start_ids: &[String],
+        types: &[RelationshipType],
+        direction: Direction,
+        max_depth: usize,
+    ) -> Result<Vec<TraversalStep>> {
+        let conn = self.get_conn()?;
+
+        let (current, next) = match direction {
+            Direction::Incoming => ("to_id", "from_id"),
+            Direction::Outgoing => ("from_id", "to_id"),
+        };
+        let sql = format!(
+            "WITH RECURSIVE walk(id, depth, via, type, file, line) AS (
+                 SELECT value, 0, NULL, NULL, NULL, NULL FROM json_each(:start)
+                 UNION
+                 SELECT r.{next}, w.depth + 1, w.id, r.type, r.file, r.line
+                 FROM walk w JOIN relationships r ON r.{current} = w.id
+                 WHERE w.depth < :max_depth AND r.type IN (SELECT value FROM json_each(:types))
+                   AND json_extract(r.metadata, '$.generated') IS NULL
+             )
+             SELECT id, depth, via, type, file, line FROM walk WHERE depth > 0
+             ORDER BY depth, id, via, file, line",
+        );
+        let mut stmt = conn.prepare(&sql)?;
+
+        let steps = stmt
+            .query_map(
+                named_params! {
+                    ":start": serde_json::to_string(start_ids)?,
+                    ":types": serde_json::to_string(&types.iter().map(|t| t.as_str()).collect::<Vec<_>>())?,
+                    ":max_depth": max_depth as i64,
+                },
+                |row| {
+                    Ok(TraversalStep {
+                        id: row.get(0)?,
+                        depth: row.get::<_, i64>(1)? as usize,
+                        via: row.get(2)?,
+                        type_: RelationshipType::from_str(&row.get::<_, String>(3)?).unwrap(),
+                        file: row.get(4)?,
+                        line: row.get::<_, i64>(5)? as usize,
+                    })
+                },
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(steps)
+    }
+
+    /// Replace the relationships sourced from a file in a single transaction
+    ///
+    /// Relationships pointing at an unresolved placeholder are stored in
+    /// `unresolved_references` instead.
+    pub fn replace_file_relationships(&self, file_path: &str, relationships: &[Relationship]) -> Result<()> {
+        let mut conn = self.get_conn()?;
+        let tx = conn.transaction()?;
+
+        replace_relationship_rows(&tx, file_path, relationships)?;
+        tx.commit()?;
         Ok(())
     }
 
+    /// Start a writer that groups many file updates into shared transactions
+    pub fn batch_writer(&self, batch_size: usize) -> Result<BatchWriter> {
+        Ok(BatchWriter {
+            conn: self.get_conn()?,
+            batch_size: batch_size.max(1),
+            pending: 0,
+            in_transaction: false,
+        })
+    }
+
+    /// Find files, other than `exclude_file`, with relationships pointing at any of `to_ids`
+    pub fn find_files_referencing(&self, exclude_file: &str, to_ids: &[String]) -> Result<Vec<String>> {
+        self.find_files_matching(
+            "SELECT DISTINCT file FROM relationships WHERE to_id = ?1 AND file != ?2",
+            exclude_file,
+            to_ids,
+        )
+    }
+
+    /// Find files, other than `exclude_file`, with unresolved references to any of `names`
+    pub fn find_files_with_unresolved(&self, exclude_file: &str, names: &[String]) -> Result<Vec<String>> {
+        self.find_files_matching(
+            "SELECT DISTINCT file FROM unresolved_references WHERE name = ?1 AND file != ?2",
+            exclude_file,
+            names,
+        )
+    }
+
+    fn find_files_matching(&self, sql: &str, exclude_file: &str, keys: &[String]) -> Result<Vec<String>> {
+        let conn = self.get_conn()?;
+        let mut stmt = conn.prepare(sql)?;
+
+        let mut files = std::collections::BTreeSet::new();
+        for key in keys {
+            let rows = stmt.query_map(params![key, exclude_file], |row| row.get::<_, String>(0))?;
+            for file in rows {
+                files.insert(file?);
+            }
+        }
+
+        Ok(files.into_iter().collect())
+    }
+
     /// Find relationships from a symbol
     pub fn find_relationships_from(&self, from_id: &str, type_: Option<RelationshipType>) -> Result<Vec<Relationship>> {
         let conn = self.get_conn()?;
@@ -406,7 +771,7 @@ impl IndexDatabase {
                  FROM relationships WHERE from_id = ?1 AND type = ?2",
             )?;
 
-            let result = stmt.query_map(params![from_id, type_.as_str()], |row| Ok(row_to_relationship(row)?))?
+            let result = stmt.query_map(params![from_id, type_.as_str()], row_to_relationship)?
                 .collect::<std::result::Result<Vec<_>, _>>()?;
             result
         } else {
@@ -415,7 +780,7 @@ impl IndexDatabase {
                  FROM relationships WHERE from_id = ?1",
             )?;
 
-            let result = stmt.query_map([from_id], |row| Ok(row_to_relationship(row)?))?
+            let result = stmt.query_map([from_id], row_to_relationship)?
                 .collect::<std::result::Result<Vec<_>, _>>()?;
             result
         };
@@ -433,7 +798,7 @@ impl IndexDatabase {
                  FROM relationships WHERE to_id = ?1 AND type = ?2",
             )?;
 
-            let result = stmt.query_map(params![to_id, type_.as_str()], |row| Ok(row_to_relationship(row)?))?
+            let result = stmt.query_map(params![to_id, type_.as_str()], row_to_relationship)?
                 .collect::<std::result::Result<Vec<_>, _>>()?;
             result
         } else {
@@ -442,7 +807,7 @@ impl IndexDatabase {
                  FROM relationships WHERE to_id = ?1",
             )?;
 
-            let result = stmt.query_map([to_id], |row| Ok(row_to_relationship(row)?))?
+            let result = stmt.query_map([to_id], row_to_relationship)?
                 .collect::<std::result::Result<Vec<_>, _>>()?;
             result
         };
@@ -479,30 +844,64 @@ impl IndexDatabase {
         })
     }
 
-    /// Update file indexing metadata
-    pub fn update_file_indexed(&self, file_path: &str, language: &str, content_hash: String, symbol_count: i64) -> Result<()> {
-        let conn = self.get_conn()?;
-        let now = now();
-
-        conn.execute(
-            "INSERT OR REPLACE INTO files (path, language, content_hash, last_indexed, symbol_count, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![file_path, language, content_hash, now, symbol_count, now],
-        )?;
-
-        Ok(())
-    }
-
     /// Clear all data (for testing)
     pub fn clear(&self) -> Result<()> {
         let conn = self.get_conn()?;
+        conn.execute("DELETE FROM unresolved_references", [])?;
         conn.execute("DELETE FROM relationships", [])?;
+        conn.execute("DELETE FROM symbols_fts", [])?;
         conn.execute("DELETE FROM symbols", [])?;
         conn.execute("DELETE FROM files", [])?;
         Ok(())
     }
 }
 
+/// Direction in which relationships are followed from a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From `to_id` back to `from_id`, e.g. towards callers
+    Incoming,
+    /// From `from_id` on to `to_id`, e.g. towards callees
+    Outgoing,
+}
+
+/// A relationship crossed during a traversal
+#[derive(Debug, Clone)]
+pub struct TraversalStep {
+    /// Symbol reached
+    pub id: String,
+    pub depth: usize,
+    /// Symbol the step was taken from, one level closer to the start
+    pub via: String,
+    pub type_: RelationshipType,
+    pub file: String,
+    pub line: usize,
+}
+
+/// Filters applied to symbol searches before ranking and limiting
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    pub kind: Option<String>,
+    pub language: Option<String>,
+    /// Glob over the file path, or a substring of it
+    pub file: Option<String>,
+    pub visibility: Option<String>,
+    /// Decorator recorded in the metadata, with or without its arguments
+    pub decorator: Option<String>,
+}
+
+/// Score added to search results whose name equals the query
+pub const EXACT_NAME_BONUS: f64 = 100.0;
+
+/// WHERE clause for [`SearchFilters`] over the symbols table aliased as `s`
+const SEARCH_FILTER_SQL: &str = "(:kind IS NULL OR s.kind = :kind)
+    AND (:language IS NULL OR s.language = :language)
+    AND (:file IS NULL OR s.file GLOB :file OR instr(s.file, :file) > 0)
+    AND (:visibility IS NULL OR s.visibility = :visibility)
+    AND (:decorator IS NULL OR EXISTS (
+        SELECT 1 FROM json_each(s.metadata, '$.decorators') d
+        WHERE d.value = :decorator OR substr(d.value, 1, length(:decorator) + 1) = :decorator || '('))";
+
 /// Index statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexStats {
@@ -511,6 +910,222 @@ pub struct IndexStats {
     pub total_relationships: usize,
 }
 
+/// Writer that commits every `batch_size` rows instead of once per file
+///
+/// A file's rows always land in the same transaction, so readers still never
+/// observe a half-updated file. Uncommitted rows are rolled back if the
+/// writer is dropped without calling [`BatchWriter::finish`].
+pub struct BatchWriter {
+    conn: r2d2::PooledConnection<SqliteConnectionManager>,
+    batch_size: usize,
+    pending: usize,
+    in_transaction: bool,
+}
+
+impl BatchWriter {
+    /// Replace everything indexed for a file
+    pub fn replace_file(&mut self, file_path: &str, language: &str, content_hash: &str, symbols: &[Symbol]) -> Result<()> {
+        self.begin()?;
+        replace_file_rows(&self.conn, file_path, language, content_hash, symbols)?;
+        self.written(symbols.len() + 1)
+    }
+
+    /// Remove a file and everything derived from it
+    pub fn remove_file(&mut self, file_path: &str) -> Result<()> {
+        self.begin()?;
+        remove_file_rows(&self.conn, file_path)?;
+        self.written(1)
+    }
+
+    /// Replace the relationships sourced from a file
+    pub fn replace_file_relationships(&mut self, file_path: &str, relationships: &[Relationship]) -> Result<()> {
+        self.begin()?;
+        replace_relationship_rows(&self.conn, file_path, relationships)?;
+        self.written(relationships.len().max(1))
+    }
+
+    /// Commit whatever is still pending
+    pub fn finish(mut self) -> Result<()> {
+        if self.in_transaction {
+            self.in_transaction = false;
+            self.conn.execute_batch("COMMIT")?;
+        }
+        Ok(())
+    }
+
+    fn begin(&mut self) -> Result<()> {
+        if !self.in_transaction {
+            self.conn.execute_batch("BEGIN IMMEDIATE")?;
+            self.in_transaction = true;
+        }
+        Ok(())
+    }
+
+    fn written(&mut self, rows: usize) -> Result<()> {
+        self.pending += rows;
+        if self.pending >= self.batch_size {
+            self.conn.execute_batch("COMMIT")?;
+            self.in_transaction = false;
+            self.pending = 0;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for BatchWriter {
+    fn drop(&mut self) {
+        if self.in_transaction {
+            let _ = self.conn.execute_batch("ROLLBACK");
+        }
+    }
+}
+
+/// Insert or replace a symbol row on an existing connection or transaction
+fn insert_symbol_row(conn: &Connection, symbol: &Symbol) -> rusqlite::Result<usize> {
+    // REPLACE deletes the previous row under a new rowid, drop its search entry first
+    conn.prepare_cached(
+        "DELETE FROM symbols_fts WHERE rowid IN (SELECT rowid FROM symbols WHERE id = ?1)",
+    )?.execute([&symbol.id])?;
+
+    let inserted = conn.prepare_cached(
+        "INSERT OR REPLACE INTO symbols (
+            id, kind, name, qualified_name, file, line, column, end_line, end_column,
+            signature, type, visibility, language, metadata, content_hash, last_indexed
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+    )?.execute(
+        params![
+            symbol.id,
+            symbol.kind.as_str(),
+            symbol.name,
+            symbol.qualified_name,
+            symbol.file,
+            symbol.line as i64,
+            symbol.column as i64,
+            symbol.end_line as i64,
+            symbol.end_column as i64,
+            symbol.signature,
+            symbol.type_,
+            symbol.visibility.as_str(),
+            symbol.language,
+            symbol.metadata,
+            symbol.content_hash,
+            symbol.last_indexed as i64,
+        ],
+    )?;
+
+    conn.prepare_cached(
+        "INSERT INTO symbols_fts (rowid, name, terms, qualified_name, signature)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?.execute(
+        params![
+            conn.last_insert_rowid(),
+            symbol.name,
+            search_terms(&symbol.name, &symbol.qualified_name),
+            symbol.qualified_name,
+            symbol.signature,
+        ],
+    )?;
+
+    Ok(inserted)
+}
+
+/// Insert a relationship row on an existing connection or transaction
+fn insert_relationship_row(conn: &Connection, rel: &Relationship) -> rusqlite::Result<usize> {
+    conn.prepare_cached(
+        "INSERT INTO relationships (from_id, to_id, type, file, line, metadata)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?.execute(
+        params![
+            rel.from_id,
+            rel.to_id,
+            rel.type_.as_str(),
+            rel.file,
+            rel.line as i64,
+            rel.metadata,
+        ],
+    )
+}
+
+/// Insert an unresolved reference row on an existing connection or transaction
+fn insert_unresolved_row(conn: &Connection, rel: &Relationship, name: &str) -> rusqlite::Result<usize> {
+    conn.prepare_cached(
+        "INSERT INTO unresolved_references (from_id, name, type, file, line, metadata)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?.execute(
+        params![
+            rel.from_id,
+            name,
+            rel.type_.as_str(),
+            rel.file,
+            rel.line as i64,
+            rel.metadata,
+        ],
+    )
+}
+
+/// Delete a file's symbols and every relationship touching them
+fn delete_file_rows(conn: &Connection, file_path: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM unresolved_references WHERE file = ?1", [file_path])?;
+    // Separate statements so that each one can use its own index
+    conn.execute("DELETE FROM relationships WHERE file = ?1", [file_path])?;
+    conn.execute(
+        "DELETE FROM relationships WHERE from_id IN (SELECT id FROM symbols WHERE file = ?1)",
+        [file_path],
+    )?;
+    conn.execute(
+        "DELETE FROM relationships WHERE to_id IN (SELECT id FROM symbols WHERE file = ?1)",
+        [file_path],
+    )?;
+    conn.execute(
+        "DELETE FROM symbols_fts WHERE rowid IN (SELECT rowid FROM symbols WHERE file = ?1)",
+        [file_path],
+    )?;
+    conn.execute("DELETE FROM symbols WHERE file = ?1", [file_path])?;
+    Ok(())
+}
+
+fn replace_file_rows(
+    conn: &Connection,
+    file_path: &str,
+    language: &str,
+    content_hash: &str,
+    symbols: &[Symbol],
+) -> rusqlite::Result<()> {
+    delete_file_rows(conn, file_path)?;
+    for symbol in symbols {
+        insert_symbol_row(conn, symbol)?;
+    }
+
+    let now = now();
+    conn.prepare_cached(
+        "INSERT OR REPLACE INTO files (path, language, content_hash, last_indexed, symbol_count, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?.execute(
+        params![file_path, language, content_hash, now, symbols.len() as i64, now],
+    )?;
+
+    Ok(())
+}
+
+fn remove_file_rows(conn: &Connection, file_path: &str) -> rusqlite::Result<()> {
+    delete_file_rows(conn, file_path)?;
+    conn.execute("DELETE FROM files WHERE path = ?1", [file_path])?;
+    Ok(())
+}
+
+/// Relationships pointing at an unresolved placeholder go to `unresolved_references`
+fn replace_relationship_rows(conn: &Connection, file_path: &str, relationships: &[Relationship]) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM relationships WHERE file = ?1", [file_path])?;
+    conn.execute("DELETE FROM unresolved_references WHERE file = ?1", [file_path])?;
+    for rel in relationships {
+        match rel.to_id.strip_prefix(UNRESOLVED_PREFIX) {
+            Some(name) => insert_unresolved_row(conn, rel, name)?,
+            None => insert_relationship_row(conn, rel)?,
+        };
+    }
+    Ok(())
+}
+
 /// Convert database row to Symbol
 fn row_to_symbol(row: &Row) -> rusqlite::Result<Symbol> {
     let kind_str: String = row.get(1)?;
@@ -564,6 +1179,7 @@ mod tests {
     use tempfile::tempdir;
 
     #[test]
+    #[allow(unused_variables)]
     fn test_create_database() {
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("test.db");
@@ -572,6 +1188,15 @@ mod tests {
         assert!(db_path.exists());
     }
 
+    #[test]
+    fn test_database_path() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let db = IndexDatabase::new(&db_path).unwrap();
+        assert_eq!(db.path(), db_path);
+    }
+
     #[test]
     fn test_insert_and_get_symbol() {
         let dir = tempdir().unwrap();
@@ -668,6 +1293,83 @@ mod tests {
         assert_eq!(rels[0].to_id, "hello");
     }
 
+    fn test_symbol(id: &str, file: &str) -> Symbol {
+        Symbol {
+            id: id.to_string(),
+            kind: SymbolKind::Function,
+            name: id.to_string(),
+            qualified_name: id.to_string(),
+            file: file.to_string(),
+            line: 1,
+            column: 0,
+            end_line: 1,
+            end_column: 0,
+            signature: None,
+            type_: None,
+            visibility: Visibility::Public,
+            language: "python".to_string(),
+            metadata: None,
+            content_hash: "".to_string(),
+            last_indexed: now(),
+        }
+    }
+
+    fn test_call(from_id: &str, to_id: &str, file: &str) -> Relationship {
+        Relationship {
+            from_id: from_id.to_string(),
+            to_id: to_id.to_string(),
+            type_: RelationshipType::Calls,
+            file: file.to_string(),
+            line: 1,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_replace_file_drops_stale_rows() {
+        let dir = tempdir().unwrap();
+        let db = IndexDatabase::new(dir.path().join("test.db")).unwrap();
+
+        db.replace_file("a.py", "python", "h1", &[test_symbol("a:old", "a.py"), test_symbol("a:kept", "a.py")]).unwrap();
+        db.replace_file("b.py", "python", "h1", &[test_symbol("b:caller", "b.py")]).unwrap();
+        db.replace_file_relationships("a.py", &[test_call("a:kept", "a:old", "a.py")]).unwrap();
+        db.replace_file_relationships("b.py", &[test_call("b:caller", "a:old", "b.py")]).unwrap();
+
+        // Re-index a.py without `old`: its symbol and all edges touching it disappear
+        db.replace_file("a.py", "python", "h2", &[test_symbol("a:kept", "a.py")]).unwrap();
+
+        assert!(db.get_symbol("a:old").unwrap().is_none());
+        assert!(db.get_symbol("a:kept").unwrap().is_some());
+        assert!(db.find_relationships_to("a:old", None).unwrap().is_empty());
+        assert!(db.find_relationships_from("a:kept", None).unwrap().is_empty());
+
+        let stats = db.get_stats().unwrap();
+        assert_eq!(stats.total_symbols, 2);
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(stats.total_relationships, 0);
+        assert_eq!(db.file_hashes().unwrap()["a.py"], "h2");
+
+        // Removing b.py purges its symbols and file row
+        db.remove_file("b.py").unwrap();
+        let stats = db.get_stats().unwrap();
+        assert_eq!(stats.total_symbols, 1);
+        assert_eq!(stats.total_files, 1);
+        assert!(!db.file_hashes().unwrap().contains_key("b.py"));
+    }
+
+    #[test]
+    fn test_replace_file_relationships_is_idempotent() {
+        let dir = tempdir().unwrap();
+        let db = IndexDatabase::new(dir.path().join("test.db")).unwrap();
+
+        db.replace_file("a.py", "python", "h1", &[test_symbol("a:f", "a.py"), test_symbol("a:g", "a.py")]).unwrap();
+        for _ in 0..3 {
+            db.replace_file_relationships("a.py", &[test_call("a:f", "a:g", "a.py")]).unwrap();
+        }
+
+        assert_eq!(db.find_relationships_from("a:f", None).unwrap().len(), 1);
+    }
+
     #[test]
     fn test_stats() {
         let dir = tempdir().unwrap();
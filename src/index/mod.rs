@@ -2,6 +2,7 @@
 
 pub mod schema;
 pub mod db;
+pub mod search;
 
 /// A code symbol (function, type, variable, etc.)
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -53,6 +54,42 @@ pub struct Location {
     pub end_column: u32,
 }
 
+/// Source range found by a hand-written parser, with zero-based lines like tree-sitter's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub line: u32,
+    pub column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+impl Span {
+    /// Range of a tree-sitter node
+    pub fn of(node: tree_sitter::Node) -> Self {
+        Self {
+            line: node.start_position().row as u32,
+            column: node.start_position().column as u32,
+            end_line: node.end_position().row as u32,
+            end_column: node.end_position().column as u32,
+        }
+    }
+
+    /// Range from the start of this one to the end of another
+    pub fn to(self, end: Span) -> Self {
+        Self { end_line: end.end_line, end_column: end.end_column, ..self }
+    }
+
+    pub fn location(self, file_path: &str) -> Location {
+        Location {
+            file: file_path.to_string(),
+            line: self.line,
+            column: self.column,
+            end_line: self.end_line,
+            end_column: self.end_column,
+        }
+    }
+}
+
 /// Relationship between symbols
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Relationship {
@@ -63,6 +100,44 @@ pub struct Relationship {
     pub metadata: serde_json::Value,
 }
 
+/// Prefix of `to_id` for references whose target could not be resolved
+pub const UNRESOLVED_PREFIX: &str = "unresolved:";
+
+/// Placeholder target id for an unresolved reference to `name`
+pub fn unresolved_id(name: &str) -> String {
+    format!("{}{}", UNRESOLVED_PREFIX, name)
+}
+
+impl Relationship {
+    /// Reference from `from_id` to a callee expression that matched no known symbol
+    ///
+    /// The target is keyed by the last path segment of the expression so that
+    /// a symbol with that name appearing later can be matched back to it.
+    pub fn unresolved(from_id: String, callee: &str, kind: RelationshipKind, location: Location) -> Self {
+        let name = callee
+            .rsplit(['.', ':'])
+            .find(|segment| !segment.is_empty())
+            .unwrap_or(callee)
+            .trim();
+
+        Self {
+            from_id,
+            to_id: unresolved_id(name),
+            kind,
+            location,
+            metadata: serde_json::json!({
+                "unresolved": true,
+                "callee": callee
+            }),
+        }
+    }
+
+    /// Whether the target of this relationship is an unresolved placeholder
+    pub fn is_unresolved(&self) -> bool {
+        self.to_id.starts_with(UNRESOLVED_PREFIX)
+    }
+}
+
 /// Relationship kinds
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum RelationshipKind {
@@ -76,11 +151,135 @@ pub enum RelationshipKind {
     Imports,
 }
 
+/// Global symbol lookup shared by every file during relationship extraction
+///
+/// Built once per indexing run so resolving a call never scans every symbol.
+pub struct SymbolTable<'a> {
+    by_qualified_name: std::collections::HashMap<&'a str, &'a Symbol>,
+    by_name: std::collections::HashMap<&'a str, Vec<&'a Symbol>>,
+    by_file: std::collections::HashMap<&'a str, Vec<&'a Symbol>>,
+    by_parent: std::collections::HashMap<&'a str, Vec<&'a Symbol>>,
+}
+
+/// Qualified name of the enclosing symbol, e.g. `pkg.Type` for `pkg.Type.method`
+///
+/// Segments are separated by `.`, `::` or the `#` of a Ruby instance method
+/// such as `Shop::Order#save`; a parameter list is not split, so
+/// `pkg.Type.get(java.util.List).x` has the parent `pkg.Type.get(java.util.List)`.
+pub fn parent_name(qualified_name: &str) -> Option<&str> {
+    // A trailing parameter list belongs to the last segment
+    let head = match qualified_name.strip_suffix(')') {
+        Some(_) => {
+            let mut depth = 0usize;
+            let open = qualified_name.char_indices().rev().find(|&(_, c)| {
+                match c {
+                    ')' => depth += 1,
+                    '(' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            });
+            open.map_or(qualified_name, |(index, _)| &qualified_name[..index])
+        }
+        None => qualified_name,
+    };
+
+    let mut depth = 0usize;
+    let mut separator = None;
+    for (index, c) in head.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            '.' if depth == 0 => separator = Some(index),
+            ':' if depth == 0 && head[index + 1..].starts_with(':') => separator = Some(index),
+            // Not the `#` of a private field such as `Counter.#count`
+            '#' if depth == 0 && index > 0 && !head[..index].ends_with(['.', ':']) => separator = Some(index),
+            _ => {}
+        }
+    }
+    separator.map(|index| &head[..index])
+}
+
+impl<'a> SymbolTable<'a> {
+    pub fn new(symbols: &'a [Symbol]) -> Self {
+        let mut by_qualified_name = std::collections::HashMap::with_capacity(symbols.len());
+        let mut by_name: std::collections::HashMap<&str, Vec<&Symbol>> = std::collections::HashMap::new();
+        let mut by_file: std::collections::HashMap<&str, Vec<&Symbol>> = std::collections::HashMap::new();
+        let mut by_parent: std::collections::HashMap<&str, Vec<&Symbol>> = std::collections::HashMap::new();
+        for symbol in symbols {
+            by_qualified_name.insert(symbol.qualified_name.as_str(), symbol);
+            by_name.entry(symbol.name.as_str()).or_default().push(symbol);
+            by_file.entry(symbol.location.file.as_str()).or_default().push(symbol);
+            if let Some(parent) = parent_name(&symbol.qualified_name) {
+                by_parent.entry(parent).or_default().push(symbol);
+            }
+        }
+
+        Self { by_qualified_name, by_name, by_file, by_parent }
+    }
+
+    /// Look up a symbol by its qualified name
+    pub fn get(&self, qualified_name: &str) -> Option<&'a Symbol> {
+        self.by_qualified_name.get(qualified_name).copied()
+    }
+
+    /// All symbols with the given short name, in indexing order
+    pub fn named(&self, name: &str) -> &[&'a Symbol] {
+        self.by_name.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// All symbols defined in a file, in indexing order
+    pub fn in_file(&self, file: &str) -> &[&'a Symbol] {
+        self.by_file.get(file).map_or(&[], Vec::as_slice)
+    }
+
+    /// Symbols directly nested in the one with the given qualified name
+    pub fn children(&self, qualified_name: &str) -> &[&'a Symbol] {
+        self.by_parent.get(qualified_name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether any symbol is defined in the file
+    pub fn has_file(&self, file: &str) -> bool {
+        self.by_file.contains_key(file)
+    }
+
+    /// Files with at least one symbol, in no particular order
+    pub fn files(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.by_file.keys().copied()
+    }
+}
+
+/// Relationships of a parsed file, resolved once the symbols of every file are known
+pub type PendingRelationships<'a> = Box<dyn Fn(&SymbolTable) -> anyhow::Result<Vec<Relationship>> + Send + Sync + 'a>;
+
 /// Parser trait for different languages
 pub trait Parser {
-    fn can_parse(&self, file_path: &str) -> bool;
+    /// Extensions of the files the parser handles, without the dot
+    fn extensions(&self) -> Vec<&str>;
+
+    fn can_parse(&self, file_path: &str) -> bool {
+        std::path::Path::new(file_path).extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| self.extensions().contains(&extension))
+    }
+
     fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)>;
-    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &std::collections::HashMap<&str, &Symbol>) -> anyhow::Result<Vec<Relationship>>;
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, symbols: &SymbolTable) -> anyhow::Result<Vec<Relationship>>;
+
+    /// Parse a file for indexing: its symbols now, and its relationships later
+    /// from the same parse, so the file is not parsed again to resolve them
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)>
+    where
+        Self: Sync,
+    {
+        let (symbols, _) = self.parse(&content, file_path)?;
+        let file_path = file_path.to_string();
+        Ok((symbols, Box::new(move |symbols| self.extract_relationships_with_global_context(&content, &file_path, symbols))))
+    }
+
+    /// Forget what was read from outside the parsed files, such as `go.mod`,
+    /// which may have changed since the last batch of files
+    fn clear_caches(&self) {}
 }
 
 // TODO: Implement index storage
@@ -1,8 +1,10 @@
-use rusqlite::{Connection, Result};
+use rusqlite::{params, Connection, Result};
 use tracing::{info, debug};
 
+use super::search::search_terms;
+
 /// SQLite schema version
-pub const SCHEMA_VERSION: i32 = 1;
+pub const SCHEMA_VERSION: i32 = 4;
 
 /// Initialize the database schema
 pub fn init_schema(conn: &Connection) -> Result<()> {
@@ -42,6 +44,9 @@ fn apply_migrations(conn: &Connection, from_version: i32) -> Result<()> {
         info!("Applying migration v{}", version);
         match version {
             1 => create_v1_schema(conn)?,
+            2 => create_v2_schema(conn)?,
+            3 => create_v3_schema(conn)?,
+            4 => create_v4_schema(conn)?,
             _ => unreachable!("Unknown schema version: {}", version),
         }
 
@@ -238,7 +243,106 @@ fn create_v1_schema(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Create v2 schema (unresolved references)
+fn create_v2_schema(conn: &Connection) -> Result<()> {
+    info!("Creating v2 schema tables");
+
+    // Unresolved references - calls whose target matched no known symbol,
+    // kept so they can be re-resolved when a matching symbol is indexed
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS unresolved_references (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            from_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            type TEXT NOT NULL,
+            file TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            metadata TEXT,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (from_id) REFERENCES symbols(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_unresolved_name
+         ON unresolved_references(name)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_unresolved_file
+         ON unresolved_references(file)",
+        [],
+    )?;
+
+    info!("v2 schema created successfully");
+
+    Ok(())
+}
+
+/// Create v3 schema (per-file relationship lookups)
+fn create_v3_schema(conn: &Connection) -> Result<()> {
+    info!("Creating v3 schema indexes");
+
+    // Re-indexing replaces relationships by source file
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_relationships_file
+         ON relationships(file)",
+        [],
+    )?;
+
+    info!("v3 schema created successfully");
+
+    Ok(())
+}
+
+/// Create v4 schema (ranked symbol search)
+fn create_v4_schema(conn: &Connection) -> Result<()> {
+    info!("Creating v4 search index");
+
+    // The v1 table mirrored symbols through triggers, but REPLACE never fired
+    // the delete trigger and the split identifier words cannot be computed in
+    // SQL. The new table is written alongside symbols, keyed by symbols.rowid.
+    conn.execute("DROP TRIGGER IF EXISTS symbols_fts_update", [])?;
+    conn.execute("DROP TRIGGER IF EXISTS symbols_fts_delete", [])?;
+    conn.execute("DROP TRIGGER IF EXISTS symbols_fts_insert", [])?;
+    conn.execute("DROP TABLE IF EXISTS symbols_fts", [])?;
+
+    conn.execute(
+        "CREATE VIRTUAL TABLE symbols_fts USING fts5(
+            name,
+            terms,
+            qualified_name,
+            signature,
+            tokenize = 'unicode61 remove_diacritics 2',
+            prefix = '2 3'
+        )",
+        [],
+    )?;
+
+    // Backfill from symbols indexed before the upgrade
+    let mut select = conn.prepare("SELECT rowid, name, qualified_name, signature FROM symbols")?;
+    let mut insert = conn.prepare(
+        "INSERT INTO symbols_fts (rowid, name, terms, qualified_name, signature)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    let mut rows = select.query([])?;
+    while let Some(row) = rows.next()? {
+        let rowid: i64 = row.get(0)?;
+        let name: String = row.get(1)?;
+        let qualified_name: String = row.get(2)?;
+        let signature: Option<String> = row.get(3)?;
+        insert.execute(params![rowid, name, search_terms(&name, &qualified_name), qualified_name, signature])?;
+    }
+
+    info!("v4 schema created successfully");
+
+    Ok(())
+}
+
 /// Drop all tables (for testing/rebuilding)
+#[cfg(test)]
 pub fn drop_schema(conn: &Connection) -> Result<()> {
     info!("Dropping all schema tables");
 
@@ -249,6 +353,7 @@ pub fn drop_schema(conn: &Connection) -> Result<()> {
     conn.execute("DROP TRIGGER IF EXISTS symbols_fts_delete", [])?;
     conn.execute("DROP TRIGGER IF EXISTS symbols_fts_insert", [])?;
     conn.execute("DROP TABLE IF EXISTS symbols_fts", [])?;
+    conn.execute("DROP TABLE IF EXISTS unresolved_references", [])?;
     conn.execute("DROP TABLE IF EXISTS relationships", [])?;
     conn.execute("DROP TABLE IF EXISTS symbols", [])?;
 
@@ -278,6 +383,7 @@ mod tests {
         assert!(tables.contains(&"relationships".to_string()));
         assert!(tables.contains(&"files".to_string()));
         assert!(tables.contains(&"schema_version".to_string()));
+        assert!(tables.contains(&"unresolved_references".to_string()));
     }
 
     #[test]
@@ -305,7 +411,7 @@ mod tests {
         // Verify tables are gone
         let count: i32 = conn
             .query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table'",
+                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%'",
                 [],
                 |row| row.get(0),
             )
@@ -314,6 +420,34 @@ mod tests {
         assert_eq!(count, 0);
     }
 
+    #[test]
+    fn test_v4_backfills_search_index() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE schema_version (version INTEGER PRIMARY KEY)", []).unwrap();
+        create_v1_schema(&conn).unwrap();
+        create_v2_schema(&conn).unwrap();
+        create_v3_schema(&conn).unwrap();
+        conn.execute("INSERT INTO schema_version (version) VALUES (1), (2), (3)", []).unwrap();
+        conn.execute(
+            "INSERT INTO symbols (id, kind, name, qualified_name, file, line, column, end_line, end_column,
+                                  language, content_hash, last_indexed)
+             VALUES ('a.py:UserManager', 'class', 'UserManager', 'UserManager', 'a.py', 1, 0, 2, 0, 'python', 'h', 0)",
+            [],
+        )
+        .unwrap();
+
+        init_schema(&conn).unwrap();
+
+        let matches: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM symbols_fts WHERE symbols_fts MATCH 'terms:manager'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(matches, 1);
+    }
+
     #[test]
     fn test_idempotent_init() {
         let conn = Connection::open_in_memory().unwrap();
@@ -330,7 +464,7 @@ mod tests {
             )
             .unwrap();
 
-        // Should only have one version record
-        assert_eq!(version, 1);
+        // Should only have one record per migration
+        assert_eq!(version, SCHEMA_VERSION);
     }
 }
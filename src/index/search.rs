@@ -0,0 +1,142 @@
+// Search terms for the full-text symbol index
+
+/// Split an identifier into lowercase words at camelCase and snake_case boundaries
+///
+/// `HTTPServer_init2` becomes `http`, `server`, `init2`.
+pub fn split_identifier(identifier: &str) -> Vec<String> {
+    let mut words = Vec::new();
+
+    for part in identifier.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, current) = (chars[i - 1], chars[i]);
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            // fooBar, foo2Bar and the end of an acronym as in HTTPServer
+            let boundary = current.is_uppercase()
+                && (prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower));
+            if boundary {
+                words.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        if start < chars.len() {
+            words.push(chars[start..].iter().collect::<String>().to_lowercase());
+        }
+    }
+
+    words
+}
+
+/// Text stored in the `terms` column of `symbols_fts` for a symbol
+///
+/// Holds the split words of the qualified name in order, so phrase queries
+/// match adjacent words, followed by the name with its separators removed.
+pub fn search_terms(name: &str, qualified_name: &str) -> String {
+    let mut terms = split_identifier(qualified_name);
+    let name_words = split_identifier(name);
+    if name_words.len() > 1 {
+        terms.push(name_words.concat());
+    }
+    terms.join(" ")
+}
+
+/// Build an FTS5 MATCH expression from a user query
+///
+/// Words are split like identifiers and matched as prefixes, so `user man`
+/// and `UserMan` both find `UserManager`. Double-quoted text is matched as a
+/// phrase, as a prefix phrase when followed by `*`. Returns `None` when the
+/// query holds no searchable words.
+pub fn match_expression(query: &str) -> Option<String> {
+    let mut clauses = Vec::new();
+
+    for (i, segment) in query.split('"').enumerate() {
+        if i % 2 == 1 {
+            // Inside quotes
+            let words = split_identifier(segment);
+            if words.is_empty() {
+                continue;
+            }
+            let rest = query.split('"').nth(i + 1).unwrap_or("");
+            let prefix = if rest.starts_with('*') { " *" } else { "" };
+            clauses.push(format!("\"{}\"{}", words.join(" "), prefix));
+        } else {
+            for word in split_identifier(segment) {
+                clauses.push(format!("\"{}\" *", word));
+            }
+        }
+    }
+
+    if clauses.is_empty() {
+        None
+    } else {
+        Some(clauses.join(" AND "))
+    }
+}
+
+/// Score a name that contains every character of an abbreviation in order
+///
+/// Used for queries such as `usrmgr` that full-text search cannot match.
+/// The first characters must agree; shorter names score higher, up to 1.0.
+pub fn abbreviation_score(abbreviation: &str, name: &str) -> Option<f64> {
+    let abbreviation: Vec<char> = abbreviation.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let name: Vec<char> = name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    if abbreviation.is_empty() || name.first() != abbreviation.first() {
+        return None;
+    }
+
+    let mut remaining = abbreviation.iter().peekable();
+    for c in &name {
+        if remaining.peek() == Some(&c) {
+            remaining.next();
+        }
+    }
+
+    remaining.peek().is_none().then(|| abbreviation.len() as f64 / name.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_identifier() {
+        assert_eq!(split_identifier("UserManager"), vec!["user", "manager"]);
+        assert_eq!(split_identifier("get_user_by_id"), vec!["get", "user", "by", "id"]);
+        assert_eq!(split_identifier("HTTPServer"), vec!["http", "server"]);
+        assert_eq!(split_identifier("parseJSON2Xml"), vec!["parse", "json2", "xml"]);
+        assert_eq!(split_identifier("app.models::UserManager"), vec!["app", "models", "user", "manager"]);
+        assert!(split_identifier("__").is_empty());
+    }
+
+    #[test]
+    fn test_search_terms() {
+        assert_eq!(search_terms("UserManager", "app.UserManager"), "app user manager usermanager");
+        assert_eq!(search_terms("run", "Service::run"), "service run");
+    }
+
+    #[test]
+    fn test_match_expression() {
+        assert_eq!(match_expression("user manager").unwrap(), "\"user\" * AND \"manager\" *");
+        assert_eq!(match_expression("UserMan").unwrap(), "\"user\" * AND \"man\" *");
+        assert_eq!(match_expression("\"user manager\"").unwrap(), "\"user manager\"");
+        assert_eq!(match_expression("\"get user\"* repo").unwrap(), "\"get user\" * AND \"repo\" *");
+        assert!(match_expression("  ** ").is_none());
+    }
+
+    #[test]
+    fn test_abbreviation_score() {
+        assert!(abbreviation_score("usrmgr", "UserManager").is_some());
+        assert!(abbreviation_score("usrmgr", "user_manager").is_some());
+        assert!(abbreviation_score("usrmgr", "AuthUserManager").is_none());
+        assert!(abbreviation_score("mgrusr", "UserManager").is_none());
+        assert!(abbreviation_score("um", "UserManager").unwrap() < abbreviation_score("um", "UserMap").unwrap());
+    }
+}
@@ -0,0 +1,808 @@
+// C and C++ include, preprocessor and name resolution
+
+use std::cell::{OnceCell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Symbol, SymbolKind, SymbolTable};
+
+use super::typescript::normalize;
+
+/// Maximum number of base classes, typedefs and nested includes followed to resolve one name
+const MAX_DEPTH: usize = 8;
+
+/// Extensions of C sources and headers
+pub const C_EXTENSIONS: &[&str] = &["c", "h"];
+
+/// Extensions of C++ sources and headers
+pub const CPP_EXTENSIONS: &[&str] = &["cc", "cpp", "cxx", "c++", "hh", "hpp", "hxx", "h++", "ipp", "tpp", "inl"];
+
+/// Extensions of headers, whose declarations are linked to definitions elsewhere
+const HEADER_EXTENSIONS: &[&str] = &["h", "hh", "hpp", "hxx", "h++", "ipp", "tpp", "inl"];
+
+/// Class templates whose first argument is the type members are looked up on
+const SMART_POINTERS: &[&str] = &["unique_ptr", "shared_ptr", "weak_ptr", "auto_ptr"];
+
+/// Include directories and macros configured in the `[c]` section
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Directories searched for `#include`; relative ones are tried under
+    /// every ancestor of the including file
+    pub include_paths: Vec<PathBuf>,
+    /// Macros defined for every file, as `NAME` or `NAME=VALUE`
+    pub defines: Vec<String>,
+}
+
+/// `#include` directive, kept in the metadata of a file's `Module` symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Include {
+    /// Path between the quotes or angle brackets
+    pub path: String,
+    /// Whether the path was written `<...>`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub system: bool,
+}
+
+/// Include directories and macros in effect for one file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Flags {
+    /// `-iquote` directories, searched for `#include "..."` only
+    pub quote: Vec<PathBuf>,
+    /// `-I`, `-isystem` and `-idirafter` directories
+    pub include: Vec<PathBuf>,
+    pub defines: HashMap<String, String>,
+}
+
+impl Flags {
+    /// Flags of a compiler command line, relative directories taken from `directory`
+    pub fn from_arguments(arguments: &[String], directory: &Path) -> Self {
+        const OPTIONS: &[&str] = &["-iquote", "-isystem", "-idirafter", "-I", "-D", "-U"];
+
+        let mut flags = Self::default();
+        let mut index = 0;
+        while index < arguments.len() {
+            let argument = arguments[index].as_str();
+            index += 1;
+            let Some((option, value)) = OPTIONS.iter().find_map(|option| Some((*option, argument.strip_prefix(option)?))) else {
+                continue;
+            };
+            // `-I dir` as well as `-Idir`
+            let value = match value {
+                "" => match arguments.get(index) {
+                    Some(value) => {
+                        index += 1;
+                        value.as_str()
+                    }
+                    None => break,
+                },
+                value => value,
+            };
+
+            match option {
+                "-D" => flags.define(value),
+                "-U" => {
+                    flags.defines.remove(value);
+                }
+                "-iquote" => flags.quote.push(normalize(&directory.join(value))),
+                _ => flags.include.push(normalize(&directory.join(value))),
+            }
+        }
+        flags
+    }
+
+    /// Define a macro written `NAME` or `NAME=VALUE`, as `-D` does
+    pub fn define(&mut self, definition: &str) {
+        let (name, value) = definition.split_once('=').unwrap_or((definition, "1"));
+        self.defines.insert(name.trim().to_string(), value.trim().to_string());
+    }
+
+    /// Add the directories and macros of `other` that aren't already set
+    fn merge(&mut self, other: &Flags) {
+        for directory in &other.quote {
+            if !self.quote.contains(directory) {
+                self.quote.push(directory.clone());
+            }
+        }
+        for directory in &other.include {
+            if !self.include.contains(directory) {
+                self.include.push(directory.clone());
+            }
+        }
+        for (name, value) in &other.defines {
+            self.defines.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+    }
+}
+
+/// Entry of a `compile_commands.json`
+#[derive(Deserialize)]
+struct CompileCommand {
+    directory: String,
+    file: String,
+    #[serde(default)]
+    arguments: Vec<String>,
+    #[serde(default)]
+    command: Option<String>,
+}
+
+/// Flags of the files listed in a `compile_commands.json`
+#[derive(Debug, Default)]
+pub struct CompileDatabase {
+    /// Flags by canonical source path
+    files: HashMap<PathBuf, Flags>,
+    /// Every entry's flags merged, for headers and files the database doesn't list
+    common: Flags,
+}
+
+impl CompileDatabase {
+    /// Nearest database in `directory` or one of its parents, or in their `build` directories
+    pub fn find(directory: &Path) -> Option<Self> {
+        directory.ancestors()
+            .flat_map(|dir| [dir.join("compile_commands.json"), dir.join("build").join("compile_commands.json")])
+            .find(|path| path.is_file())
+            .and_then(|path| Self::load(&path))
+    }
+
+    fn load(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let commands: Vec<CompileCommand> = serde_json::from_str(&content).ok()?;
+
+        let mut database = Self::default();
+        for command in commands {
+            let directory = PathBuf::from(&command.directory);
+            let arguments = match command.command {
+                Some(line) if command.arguments.is_empty() => split_command(&line),
+                _ => command.arguments,
+            };
+            let flags = Flags::from_arguments(&arguments, &directory);
+            database.common.merge(&flags);
+            database.files.insert(canonical(&directory.join(&command.file)), flags);
+        }
+        Some(database)
+    }
+
+    /// Flags the database gives a file
+    pub fn flags(&self, file: &Path) -> &Flags {
+        self.files.get(&canonical(file)).unwrap_or(&self.common)
+    }
+}
+
+/// Walks preprocessor conditionals with a set of macros
+///
+/// Branches whose condition evaluates are taken or skipped as the compiler
+/// would; one that can't be evaluated, such as a call of a function-like
+/// macro, takes every branch. `#define` and `#undef` apply in source order.
+pub struct Preprocessor<'c> {
+    content: &'c str,
+    defines: HashMap<String, String>,
+    /// Take every branch regardless of its condition
+    all_branches: bool,
+}
+
+impl<'c> Preprocessor<'c> {
+    pub fn new(content: &'c str, defines: HashMap<String, String>) -> Self {
+        Self { content, defines, all_branches: false }
+    }
+
+    /// Preprocessor that takes every branch, for what a file may include
+    pub fn all_branches(content: &'c str) -> Self {
+        Self { content, defines: HashMap::new(), all_branches: true }
+    }
+
+    /// `#include` directives in the taken branches, with their nodes
+    pub fn includes<'t>(&mut self, root: tree_sitter::Node<'t>) -> Vec<(Include, tree_sitter::Node<'t>)> {
+        let mut includes = Vec::new();
+        self.walk(root, &mut includes);
+        includes
+    }
+
+    fn walk<'t>(&mut self, node: tree_sitter::Node<'t>, includes: &mut Vec<(Include, tree_sitter::Node<'t>)>) {
+        match node.kind() {
+            "preproc_include" => includes.extend(self.include(node).map(|include| (include, node))),
+            "preproc_def" | "preproc_function_def" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    let value = node.child_by_field_name("value").map_or("", |value| self.text(value).trim());
+                    self.defines.insert(self.text(name).to_string(), value.to_string());
+                }
+            }
+            "preproc_call" => {
+                let directive = node.child_by_field_name("directive").map(|directive| self.text(directive));
+                if let (Some("#undef"), Some(argument)) = (directive, node.child_by_field_name("argument")) {
+                    self.defines.remove(self.text(argument).trim());
+                }
+            }
+            "preproc_ifdef" | "preproc_elifdef" => {
+                let defined = node.child_by_field_name("name").map(|name| self.defines.contains_key(self.text(name)));
+                let negated = node.child(0).is_some_and(|directive| directive.kind().ends_with("ndef"));
+                self.branch(node, defined.map(|defined| defined != negated), includes);
+            }
+            "preproc_if" | "preproc_elif" => {
+                let value = node.child_by_field_name("condition").and_then(|condition| self.evaluate(condition));
+                self.branch(node, value.map(|value| value != 0), includes);
+            }
+            "translation_unit" | "preproc_else" | "declaration_list" | "linkage_specification" | "namespace_definition" => {
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    self.walk(child, includes);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Body or `#else`/`#elif` alternative of a conditional, both when unknown
+    fn branch<'t>(&mut self, node: tree_sitter::Node<'t>, condition: Option<bool>, includes: &mut Vec<(Include, tree_sitter::Node<'t>)>) {
+        let mut body = Vec::new();
+        let mut alternative = None;
+        let mut cursor = node.walk();
+        for (index, child) in node.children(&mut cursor).enumerate() {
+            if !child.is_named() {
+                continue;
+            }
+            match node.field_name_for_child(index as u32) {
+                Some("name") | Some("condition") => {}
+                Some("alternative") => alternative = Some(child),
+                _ => body.push(child),
+            }
+        }
+
+        let condition = if self.all_branches { None } else { condition };
+        if condition != Some(false) {
+            for child in body {
+                self.walk(child, includes);
+            }
+        }
+        if condition != Some(true) {
+            if let Some(alternative) = alternative {
+                self.walk(alternative, includes);
+            }
+        }
+    }
+
+    /// Path of an `#include`, expanding a macro naming the header
+    fn include(&self, node: tree_sitter::Node) -> Option<Include> {
+        let path = node.child_by_field_name("path")?;
+        let text = match path.kind() {
+            "identifier" => self.defines.get(self.text(path))?.trim(),
+            _ => self.text(path),
+        };
+        if let Some(path) = text.strip_prefix('<').and_then(|text| text.strip_suffix('>')) {
+            return Some(Include { path: path.to_string(), system: true });
+        }
+        let path = text.strip_prefix('"').and_then(|text| text.strip_suffix('"'))?;
+        Some(Include { path: path.to_string(), system: false })
+    }
+
+    /// Value of an `#if` expression; undefined macros are 0
+    pub fn evaluate(&self, node: tree_sitter::Node) -> Option<i64> {
+        let operand = |field: &str| node.child_by_field_name(field).and_then(|operand| self.evaluate(operand));
+        let operator = node.child_by_field_name("operator").map(|operator| self.text(operator));
+
+        match node.kind() {
+            "number_literal" => parse_integer(self.text(node)),
+            "true" => Some(1),
+            "false" => Some(0),
+            "identifier" => match self.defines.get(self.text(node)) {
+                Some(value) => parse_integer(value),
+                None => Some(0),
+            },
+            "preproc_defined" => {
+                let name = node.named_child(0)?;
+                Some(self.defines.contains_key(self.text(name)) as i64)
+            }
+            "parenthesized_expression" => self.evaluate(node.named_child(0)?),
+            "unary_expression" => {
+                let value = operand("argument")?;
+                match operator? {
+                    "!" => Some((value == 0) as i64),
+                    "-" => Some(value.wrapping_neg()),
+                    "+" => Some(value),
+                    "~" => Some(!value),
+                    _ => None,
+                }
+            }
+            "binary_expression" => {
+                let (left, right) = (operand("left"), operand("right"));
+                match operator? {
+                    // Short-circuit even when the other side is unknown
+                    "&&" if left == Some(0) || right == Some(0) => Some(0),
+                    "||" if left.is_some_and(|value| value != 0) || right.is_some_and(|value| value != 0) => Some(1),
+                    operator => {
+                        let (left, right) = (left?, right?);
+                        match operator {
+                            "&&" | "||" => Some(1),
+                            "==" => Some((left == right) as i64),
+                            "!=" => Some((left != right) as i64),
+                            "<" => Some((left < right) as i64),
+                            "<=" => Some((left <= right) as i64),
+                            ">" => Some((left > right) as i64),
+                            ">=" => Some((left >= right) as i64),
+                            "+" => Some(left.wrapping_add(right)),
+                            "-" => Some(left.wrapping_sub(right)),
+                            "*" => Some(left.wrapping_mul(right)),
+                            "/" => left.checked_div(right),
+                            "%" => left.checked_rem(right),
+                            "&" => Some(left & right),
+                            "|" => Some(left | right),
+                            "^" => Some(left ^ right),
+                            "<<" => left.checked_shl(u32::try_from(right).ok()?),
+                            ">>" => left.checked_shr(u32::try_from(right).ok()?),
+                            _ => None,
+                        }
+                    }
+                }
+            }
+            "conditional_expression" => match operand("condition")? {
+                0 => operand("alternative"),
+                _ => operand("consequence"),
+            },
+            _ => None,
+        }
+    }
+
+    fn text(&self, node: tree_sitter::Node) -> &'c str {
+        &self.content[node.byte_range()]
+    }
+}
+
+/// Lookup context of one file
+pub struct FileScope {
+    pub file: String,
+    /// The file and the files it includes, directly or not
+    pub visible: HashSet<String>,
+    /// Namespaces named by `using namespace`
+    pub usings: Vec<String>,
+}
+
+/// Resolves includes and C++ names against the symbol table
+///
+/// Quoted includes are searched next to the including file, then in the
+/// `-iquote` and `-I` directories of the nearest `compile_commands.json` and
+/// the configured include paths; a quoted path that matches the end of
+/// exactly one indexed file is a last resort. Names are looked up from the
+/// innermost enclosing namespace or class outwards, members through base
+/// classes. Definitions are preferred over declarations, then symbols of the
+/// file and the headers it includes, and `static` symbols of other files are
+/// never seen.
+pub struct Resolver<'t, 'a> {
+    table: &'t SymbolTable<'a>,
+    options: &'t Options,
+    databases: RefCell<HashMap<PathBuf, Option<Rc<CompileDatabase>>>>,
+    /// Indexed C and C++ files, sorted
+    files: OnceCell<Vec<&'a str>>,
+    /// Indexed C and C++ files by canonical path
+    canonical: OnceCell<HashMap<PathBuf, &'a str>>,
+}
+
+impl<'t, 'a> Resolver<'t, 'a> {
+    pub fn new(table: &'t SymbolTable<'a>, options: &'t Options) -> Self {
+        Self {
+            table,
+            options,
+            databases: RefCell::new(HashMap::new()),
+            files: OnceCell::new(),
+            canonical: OnceCell::new(),
+        }
+    }
+
+    /// Include directories and macros of a file: those of the nearest
+    /// compilation database, then the configured ones
+    pub fn flags(&self, file: &str) -> Flags {
+        let directory = Path::new(file).parent().unwrap_or(Path::new(""));
+        let mut flags = self.database(directory)
+            .map(|database| database.flags(Path::new(file)).clone())
+            .unwrap_or_default();
+        let mut configured = Flags::default();
+        for definition in &self.options.defines {
+            configured.define(definition);
+        }
+        flags.merge(&configured);
+        flags
+    }
+
+    fn database(&self, directory: &Path) -> Option<Rc<CompileDatabase>> {
+        if let Some(database) = self.databases.borrow().get(directory) {
+            return database.clone();
+        }
+        let database = CompileDatabase::find(directory).map(Rc::new);
+        self.databases.borrow_mut().insert(directory.to_path_buf(), database.clone());
+        database
+    }
+
+    /// Indexed file an `#include` in `file` refers to
+    pub fn include(&self, file: &str, include: &Include, flags: &Flags) -> Option<String> {
+        let directory = Path::new(file).parent().unwrap_or(Path::new(""));
+        let header = Path::new(&include.path);
+
+        let mut candidates = Vec::new();
+        if header.is_absolute() {
+            candidates.push(header.to_path_buf());
+        }
+        if !include.system {
+            candidates.push(directory.join(header));
+            candidates.extend(flags.quote.iter().map(|dir| dir.join(header)));
+        }
+        candidates.extend(flags.include.iter().map(|dir| dir.join(header)));
+        for path in &self.options.include_paths {
+            if path.is_absolute() {
+                candidates.push(path.join(header));
+            } else {
+                candidates.extend(directory.ancestors().map(|ancestor| ancestor.join(path).join(header)));
+            }
+        }
+
+        candidates.iter()
+            .find_map(|candidate| self.indexed(&normalize(candidate)))
+            .or_else(|| {
+                if include.system {
+                    return None;
+                }
+                let suffix = format!("/{}", include.path.trim_start_matches("./"));
+                let mut matches = self.files().iter().filter(|file| file.ends_with(&suffix));
+                match (matches.next(), matches.next()) {
+                    (Some(file), None) => Some(file.to_string()),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Indexed path of a file, matched lexically then by canonical path
+    fn indexed(&self, path: &Path) -> Option<String> {
+        let lexical = path.to_string_lossy();
+        if self.file_symbol(&lexical).is_some() {
+            return Some(lexical.to_string());
+        }
+        if !path.is_file() {
+            return None;
+        }
+        let canonical = self.canonical.get_or_init(|| {
+            self.files().iter()
+                .filter_map(|file| Some((std::fs::canonicalize(file).ok()?, *file)))
+                .collect()
+        });
+        canonical.get(&std::fs::canonicalize(path).ok()?).map(|file| file.to_string())
+    }
+
+    fn files(&self) -> &[&'a str] {
+        self.files.get_or_init(|| {
+            let mut files: Vec<&str> = self.table.files()
+                .filter(|file| self.file_symbol(file).is_some())
+                .collect();
+            files.sort_unstable();
+            files
+        })
+    }
+
+    /// Symbol defined in `file` with the given qualified name
+    pub fn symbol(&self, file: &str, qualified_name: &str, accept: &dyn Fn(&Symbol) -> bool) -> Option<&'a Symbol> {
+        self.table.in_file(file)
+            .iter()
+            .find(|symbol| symbol.qualified_name == qualified_name && accept(symbol))
+            .copied()
+    }
+
+    /// `Module` symbol standing for a whole C or C++ file
+    pub fn file_symbol(&self, file: &str) -> Option<&'a Symbol> {
+        self.table.in_file(file).iter().find(|symbol| is_file(symbol)).copied()
+    }
+
+    /// Lookup context of `file`, which directly includes `includes`
+    ///
+    /// Nested includes are read from the `includes` metadata of the headers'
+    /// file symbols, so they are followed across every conditional branch.
+    pub fn file_scope(&self, file: &str, includes: &[String], flags: &Flags, usings: Vec<String>) -> FileScope {
+        let mut visible: HashSet<String> = HashSet::from([file.to_string()]);
+        let mut frontier: Vec<String> = includes.to_vec();
+        for _ in 0..MAX_DEPTH {
+            let mut next = Vec::new();
+            for header in frontier {
+                if !visible.insert(header.clone()) {
+                    continue;
+                }
+                let nested: Vec<Include> = self.file_symbol(&header)
+                    .and_then(|symbol| serde_json::from_value(symbol.metadata["includes"].clone()).ok())
+                    .unwrap_or_default();
+                next.extend(nested.iter().filter_map(|include| self.include(&header, include, flags)));
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next;
+        }
+
+        FileScope { file: file.to_string(), visible, usings }
+    }
+
+    /// Best symbol with the given qualified name accepted by `accept`
+    pub fn qualified(&self, scope: &FileScope, qualified_name: &str, accept: &dyn Fn(&Symbol) -> bool) -> Option<&'a Symbol> {
+        let name = qualified_name.rsplit("::").next().unwrap_or(qualified_name);
+        self.table.named(name)
+            .iter()
+            .filter(|symbol| symbol.qualified_name == qualified_name && is_c_family(symbol) && !is_file(symbol) && accept(symbol))
+            .filter(|symbol| symbol.location.file == scope.file || !is_internal(symbol))
+            .min_by(|a, b| {
+                let key = |symbol: &Symbol| (is_declaration(symbol), !scope.visible.contains(&symbol.location.file));
+                key(a).cmp(&key(b)).then_with(|| a.location.file.cmp(&b.location.file))
+            })
+            .copied()
+    }
+
+    /// Name written `path` inside the namespaces and classes `enclosing`
+    pub fn lookup(&self, scope: &FileScope, enclosing: &[String], path: &[String], accept: &dyn Fn(&Symbol) -> bool) -> Option<&'a Symbol> {
+        // `::name` is looked up in the global namespace only
+        if let Some(("", rest)) = path.split_first().map(|(first, rest)| (first.as_str(), rest)) {
+            return self.qualified(scope, &rest.join("::"), accept);
+        }
+
+        for depth in (0..=enclosing.len()).rev() {
+            let prefix = &enclosing[..depth];
+            if depth > 0 && path.len() == 1 {
+                let member = self.class(scope, &prefix.join("::"))
+                    .and_then(|class| self.member(scope, class, &path[0], accept));
+                if member.is_some() {
+                    return member;
+                }
+            }
+            let name = prefix.iter().chain(path).cloned().collect::<Vec<_>>().join("::");
+            if let Some(symbol) = self.qualified(scope, &name, accept) {
+                return Some(symbol);
+            }
+        }
+
+        scope.usings.iter()
+            .find_map(|namespace| self.qualified(scope, &format!("{}::{}", namespace, path.join("::")), accept))
+    }
+
+    /// Class or struct with the given qualified name
+    pub fn class(&self, scope: &FileScope, qualified_name: &str) -> Option<&'a Symbol> {
+        self.qualified(scope, qualified_name, &|symbol| symbol.kind == SymbolKind::Class)
+    }
+
+    /// Member of a class or, failing that, of its base classes
+    pub fn member(&self, scope: &FileScope, class: &'a Symbol, name: &str, accept: &dyn Fn(&Symbol) -> bool) -> Option<&'a Symbol> {
+        let mut classes = vec![class];
+        let mut index = 0;
+        while index < classes.len() && index < MAX_DEPTH {
+            let current = classes[index];
+            index += 1;
+            if let Some(member) = self.qualified(scope, &format!("{}::{}", current.qualified_name, name), accept) {
+                return Some(member);
+            }
+            let enclosing = enclosing(current);
+            for base in bases(current) {
+                if let Some(base) = self.resolve_type(scope, &enclosing, base) {
+                    if !classes.iter().any(|known| known.id == base.id) {
+                        classes.push(base);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Class a declared type names, following typedefs and smart pointers
+    pub fn resolve_type(&self, scope: &FileScope, enclosing: &[String], type_text: &str) -> Option<&'a Symbol> {
+        let mut enclosing = enclosing.to_vec();
+        let mut type_text = type_text.to_string();
+        for _ in 0..MAX_DEPTH {
+            let name = type_name(&type_text)?;
+            let path: Vec<String> = name.split("::").map(str::to_string).collect();
+            let symbol = self.lookup(scope, &enclosing, &path, &|symbol| matches!(symbol.kind, SymbolKind::Class | SymbolKind::Type))?;
+            if symbol.kind == SymbolKind::Class {
+                return Some(symbol);
+            }
+            type_text = symbol.type_info.clone()?;
+            enclosing = self::enclosing(symbol);
+        }
+        None
+    }
+
+    /// Declarations in other files that a definition defines, those in the
+    /// headers the file includes if there are any
+    pub fn declarations(&self, scope: &FileScope, definition: &Symbol) -> Vec<&'a Symbol> {
+        let candidates: Vec<&Symbol> = self.table.named(&definition.name)
+            .iter()
+            .filter(|symbol| {
+                symbol.qualified_name == definition.qualified_name
+                    && symbol.kind == definition.kind
+                    && symbol.location.file != definition.location.file
+                    && is_c_family(symbol)
+                    && is_declaration(symbol)
+            })
+            .copied()
+            .collect();
+
+        let visible: Vec<&Symbol> = candidates.iter()
+            .filter(|symbol| scope.visible.contains(&symbol.location.file))
+            .copied()
+            .collect();
+        match (visible.is_empty(), is_internal(definition)) {
+            (false, _) => visible,
+            (true, false) => candidates,
+            (true, true) => Vec::new(),
+        }
+    }
+}
+
+pub fn is_c_family(symbol: &Symbol) -> bool {
+    matches!(symbol.language.as_str(), "c" | "cpp")
+}
+
+/// Whether the symbol is the `Module` standing for a file
+pub fn is_file(symbol: &Symbol) -> bool {
+    symbol.kind == SymbolKind::Module && symbol.metadata["file"].as_bool().unwrap_or(false)
+}
+
+/// Prototype, `extern` variable or in-class member function declaration
+pub fn is_declaration(symbol: &Symbol) -> bool {
+    symbol.metadata["declaration"].as_bool().unwrap_or(false)
+}
+
+/// `static` function or variable, with internal linkage
+pub fn is_internal(symbol: &Symbol) -> bool {
+    matches!(symbol.kind, SymbolKind::Function | SymbolKind::Variable) && symbol.metadata["static"].as_bool().unwrap_or(false)
+}
+
+pub fn is_header(file_path: &str) -> bool {
+    Path::new(file_path).extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| HEADER_EXTENSIONS.contains(&extension))
+}
+
+/// Whether a `.h` file uses C++ constructs, to pick the grammar it is parsed with
+pub fn looks_like_cpp(content: &str) -> bool {
+    content.lines().any(|line| {
+        let line = line.trim_start();
+        ["namespace ", "class ", "template<", "template <", "public:", "private:", "protected:", "using "]
+            .iter()
+            .any(|marker| line.starts_with(marker))
+    })
+}
+
+/// Base classes recorded on a class
+pub fn bases(class: &Symbol) -> impl Iterator<Item = &str> {
+    class.metadata["bases"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|base| base.as_str())
+}
+
+/// Namespaces and classes enclosing a symbol, outermost first
+pub fn enclosing(symbol: &Symbol) -> Vec<String> {
+    let mut segments: Vec<String> = symbol.qualified_name.split("::").map(str::to_string).collect();
+    segments.pop();
+    segments
+}
+
+/// Class a declared type names, e.g. `geo::Shape` for `const geo::Shape&`
+///
+/// Qualifiers, pointers, references and template arguments are dropped,
+/// except that a smart pointer names its pointee. Primitive types have none.
+pub fn type_name(type_text: &str) -> Option<String> {
+    let (base, arguments) = match type_text.split_once('<') {
+        Some((base, rest)) => (base, rest.rsplit_once('>').map(|(arguments, _)| arguments)),
+        None => (type_text, None),
+    };
+
+    let words: Vec<&str> = base.split(|c: char| c.is_whitespace() || c == '*' || c == '&')
+        .filter(|word| !word.is_empty())
+        .filter(|word| !matches!(*word, "const" | "volatile" | "struct" | "class" | "union" | "enum" | "typename" | "static" | "mutable" | "constexpr" | "inline"))
+        .collect();
+    let [name] = words.as_slice() else {
+        // `unsigned long` and friends
+        return None;
+    };
+
+    let last = name.rsplit("::").next().unwrap_or(name);
+    if SMART_POINTERS.contains(&last) {
+        let first_argument = arguments?.split(',').next()?;
+        return type_name(first_argument);
+    }
+    let primitive = matches!(last, "void" | "bool" | "char" | "short" | "int" | "long" | "float" | "double" | "auto" | "size_t" | "wchar_t");
+    (!primitive).then(|| name.to_string())
+}
+
+/// Integer literal with C suffixes and prefixes
+fn parse_integer(text: &str) -> Option<i64> {
+    let text = text.trim().trim_end_matches(['u', 'U', 'l', 'L']).replace('\'', "");
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = text.strip_prefix("0b").or_else(|| text.strip_prefix("0B")) {
+        i64::from_str_radix(binary, 2).ok()
+    } else if text.len() > 1 && text.starts_with('0') {
+        i64::from_str_radix(&text[1..], 8).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+/// Split a `command` line as a POSIX shell would, honouring quotes and backslashes
+fn split_command(command: &str) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut current = String::new();
+    let mut in_argument = false;
+    let mut quote = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some('"') | None, '\\') => {
+                current.extend(chars.next());
+                in_argument = true;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_argument = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_argument {
+                    arguments.push(std::mem::take(&mut current));
+                    in_argument = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_argument = true;
+            }
+        }
+    }
+    if in_argument {
+        arguments.push(current);
+    }
+    arguments
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| normalize(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn includes(content: &str, defines: &[(&str, &str)]) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_c::LANGUAGE.into()).unwrap();
+        let tree = parser.parse(content, None).unwrap();
+        let defines = defines.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+        Preprocessor::new(content, defines)
+            .includes(tree.root_node())
+            .into_iter()
+            .map(|(include, _)| include.path)
+            .collect()
+    }
+
+    #[test]
+    fn test_preprocessor_takes_evaluated_branches() {
+        let content = "#ifndef GUARD\n#define GUARD\n#include <stdio.h>\n#if defined(FAST) && !defined(SLOW)\n#include \"fast.h\"\n#elif VERSION >= 2\n#include \"v2.h\"\n#else\n#include \"slow.h\"\n#endif\n#ifdef UNKNOWN_FN\n#include CONFIG\n#endif\n#endif\n";
+        assert_eq!(includes(content, &[]), vec!["stdio.h", "slow.h"]);
+        assert_eq!(includes(content, &[("FAST", "1")]), vec!["stdio.h", "fast.h"]);
+        assert_eq!(includes(content, &[("VERSION", "3"), ("UNKNOWN_FN", ""), ("CONFIG", "\"config.h\"")]), vec!["stdio.h", "v2.h", "config.h"]);
+        // A function-like macro can't be evaluated, so both branches count
+        assert_eq!(includes("#if CHECK(1)\n#include \"a.h\"\n#else\n#include \"b.h\"\n#endif\n", &[]), vec!["a.h", "b.h"]);
+    }
+
+    #[test]
+    fn test_compile_command_flags() {
+        let arguments = split_command(r#"cc -Iinclude -I /opt/lib -iquote src -DNAME="\"x y\"" -DDEBUG -UDEBUG -c main.c"#);
+        let flags = Flags::from_arguments(&arguments, Path::new("/build"));
+        assert_eq!(flags.include, vec![PathBuf::from("/build/include"), PathBuf::from("/opt/lib")]);
+        assert_eq!(flags.quote, vec![PathBuf::from("/build/src")]);
+        assert_eq!(flags.defines.get("NAME").map(String::as_str), Some("\"x y\""));
+        assert!(!flags.defines.contains_key("DEBUG"));
+    }
+
+    #[test]
+    fn test_type_name() {
+        assert_eq!(type_name("const geo::Shape&").as_deref(), Some("geo::Shape"));
+        assert_eq!(type_name("struct Point *").as_deref(), Some("Point"));
+        assert_eq!(type_name("std::unique_ptr<Point>").as_deref(), Some("Point"));
+        assert_eq!(type_name("std::vector<Point>").as_deref(), Some("std::vector"));
+        assert_eq!(type_name("unsigned long"), None);
+        assert_eq!(type_name("int"), None);
+    }
+}
@@ -0,0 +1,393 @@
+// C# namespaces, using directives, partial types and overload resolution
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Symbol, SymbolKind, SymbolTable};
+
+/// Maximum number of base types followed to find one member
+const MAX_DEPTH: usize = 8;
+
+/// Keywords of built-in types, which never resolve to an indexed symbol
+const PREDEFINED_TYPES: &[&str] = &[
+    "bool", "byte", "char", "decimal", "double", "dynamic", "float", "int", "long", "nint", "nuint",
+    "object", "sbyte", "short", "string", "uint", "ulong", "ushort", "var", "void",
+];
+
+/// Name bound by a using directive, kept in the metadata of `Import` symbols
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsingDirective {
+    /// Alias of `using Alias = Path;`, none for namespace and static usings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// Imported namespace, type of a static using, or aliased name
+    pub path: String,
+    /// Whether this is a `using static` of a type's static members
+    #[serde(rename = "static", default)]
+    pub is_static: bool,
+}
+
+/// Type as it appears in qualified names: generic arguments, nullability,
+/// whitespace and the `global::` alias removed, so that
+/// `global::System.Collections.Generic.List<int>?` becomes `System.Collections.Generic.List`
+pub fn erase(type_text: &str) -> String {
+    let mut erased = String::with_capacity(type_text.len());
+    let mut depth = 0usize;
+    for c in type_text.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            '?' => {}
+            c if depth == 0 && !c.is_whitespace() => erased.push(c),
+            _ => {}
+        }
+    }
+    match erased.strip_prefix("global::") {
+        Some(stripped) => stripped.to_string(),
+        None => erased,
+    }
+}
+
+/// Using directives of one C# file
+///
+/// Directives apply to the whole file, including those written inside a
+/// namespace block; `global using` directives are treated like local ones.
+#[derive(Debug, Default)]
+pub struct FileScope {
+    namespaces: Vec<String>,
+    aliases: HashMap<String, String>,
+    statics: Vec<String>,
+}
+
+impl FileScope {
+    pub fn new(directives: impl IntoIterator<Item = UsingDirective>) -> Self {
+        let mut scope = Self::default();
+        for directive in directives {
+            match (directive.alias, directive.is_static) {
+                (Some(alias), _) => { scope.aliases.insert(alias, directive.path); }
+                (None, true) => scope.statics.push(directive.path),
+                (None, false) => scope.namespaces.push(directive.path),
+            }
+        }
+        scope
+    }
+
+    /// Directives recorded on the file's `Import` symbols
+    pub fn from_symbols<'s>(symbols: impl IntoIterator<Item = &'s Symbol>) -> Self {
+        let directives: Vec<UsingDirective> = symbols.into_iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Import)
+            .filter_map(|symbol| serde_json::from_value(symbol.metadata["using"].clone()).ok())
+            .collect();
+        Self::new(directives)
+    }
+
+    /// Qualified names a type written in this file may refer to, most likely
+    /// first, `namespace` being the namespace of the use and `enclosing` the
+    /// types around it, innermost last
+    ///
+    /// Nested types of the enclosing types shadow using aliases, which shadow
+    /// the members of the enclosing namespaces, innermost first, which shadow
+    /// the namespaces imported by using directives.
+    pub fn type_candidates(&self, type_text: &str, namespace: &str, enclosing: &[String]) -> Vec<String> {
+        let erased = erase(type_text);
+        let name = erased.split('[').next().unwrap_or_default();
+        if name.is_empty() || PREDEFINED_TYPES.contains(&name) {
+            return Vec::new();
+        }
+
+        let (head, rest) = match name.split_once('.') {
+            Some((head, rest)) => (head, Some(rest)),
+            None => (name, None),
+        };
+        let with_rest = |base: String| match rest {
+            Some(rest) => format!("{}.{}", base, rest),
+            None => base,
+        };
+
+        let mut candidates = Vec::new();
+        for class in enclosing.iter().rev() {
+            candidates.push(with_rest(format!("{}.{}", class, head)));
+        }
+        if let Some(aliased) = self.aliases.get(head) {
+            candidates.push(with_rest(aliased.clone()));
+        }
+        let mut namespace = Some(namespace).filter(|namespace| !namespace.is_empty());
+        while let Some(current) = namespace {
+            candidates.push(format!("{}.{}", current, name));
+            namespace = current.rsplit_once('.').map(|(parent, _)| parent);
+        }
+        candidates.push(name.to_string());
+        for imported in &self.namespaces {
+            candidates.push(with_rest(format!("{}.{}", imported, head)));
+        }
+
+        let mut seen = HashSet::new();
+        candidates.retain(|candidate| seen.insert(candidate.clone()));
+        candidates
+    }
+}
+
+/// Static type of an argument expression, when it can be read off the syntax
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentType {
+    Known(String),
+    Unknown,
+}
+
+/// Resolves C# types and members against the symbol table
+///
+/// The parts of a partial type share its qualified name, so their members
+/// are found together under it and their base lists are merged. Members are
+/// searched in the type, then its base class and interfaces. Overloads are
+/// chosen by arity, then by the argument types that are known.
+pub struct Resolver<'t, 'a> {
+    table: &'t SymbolTable<'a>,
+    scope: FileScope,
+    /// Declarations of this file by start position, so that overloads sharing
+    /// a qualified name are told apart
+    declared: HashMap<(u32, u32), &'a Symbol>,
+}
+
+impl<'t, 'a> Resolver<'t, 'a> {
+    pub fn new(table: &'t SymbolTable<'a>, file: &str) -> Self {
+        let symbols = table.in_file(file);
+        let mut declared = HashMap::new();
+        for &symbol in symbols {
+            if symbol.kind != SymbolKind::Import {
+                declared.entry((symbol.location.line, symbol.location.column)).or_insert(symbol);
+            }
+        }
+
+        Self {
+            table,
+            scope: FileScope::from_symbols(symbols.iter().copied()),
+            declared,
+        }
+    }
+
+    pub fn scope(&self) -> &FileScope {
+        &self.scope
+    }
+
+    /// Symbol declared in this file at the given position
+    pub fn declared_at(&self, line: u32, column: u32) -> Option<&'a Symbol> {
+        self.declared.get(&(line, column)).copied()
+    }
+
+    /// Every part of the C# type with the given qualified name
+    pub fn parts(&self, qualified_name: &str) -> Vec<&'a Symbol> {
+        let name = qualified_name.rsplit('.').next().unwrap_or(qualified_name);
+        self.table.named(name)
+            .iter()
+            .filter(|symbol| symbol.qualified_name == qualified_name && symbol.language == "csharp" && is_type(symbol))
+            .copied()
+            .collect()
+    }
+
+    /// C# type with the given qualified name, its first part if partial
+    pub fn find_type(&self, qualified_name: &str) -> Option<&'a Symbol> {
+        self.parts(qualified_name).into_iter().next()
+    }
+
+    /// First candidate that is an indexed type
+    pub fn first_type(&self, candidates: &[String]) -> Option<&'a Symbol> {
+        candidates.iter().find_map(|candidate| self.find_type(candidate))
+    }
+
+    /// Type written in this file inside `namespace` and the `enclosing` types
+    pub fn resolve_type(&self, type_text: &str, namespace: &str, enclosing: &[String]) -> Option<&'a Symbol> {
+        self.first_type(&self.scope.type_candidates(type_text, namespace, enclosing))
+    }
+
+    /// Static type of a field, property or method result, from the candidates
+    /// recorded when its file was parsed
+    pub fn type_of(&self, symbol: &Symbol) -> Option<&'a Symbol> {
+        let candidates: Vec<String> = serde_json::from_value(symbol.metadata["types"].clone()).unwrap_or_default();
+        self.first_type(&candidates)
+    }
+
+    /// Base class and interfaces of a type, over all of its parts
+    pub fn bases(&self, ty: &Symbol) -> Vec<&'a Symbol> {
+        let mut seen = HashSet::new();
+        self.parts(&ty.qualified_name)
+            .into_iter()
+            .flat_map(|part| base_candidates(part).map(|(_, candidates)| candidates).collect::<Vec<_>>())
+            .filter_map(|candidates| self.first_type(&candidates))
+            .filter(|base| seen.insert(base.qualified_name.as_str()))
+            .collect()
+    }
+
+    /// Base class of a class or record, the one base that is not an interface
+    pub fn base_class(&self, ty: &Symbol) -> Option<&'a Symbol> {
+        self.bases(ty).into_iter().find(|base| !is_interface(base))
+    }
+
+    /// `ty` followed by its bases, breadth first
+    fn hierarchy(&self, ty: &'a Symbol) -> Vec<&'a Symbol> {
+        let mut hierarchy = vec![ty];
+        let mut seen: HashSet<&str> = HashSet::from([ty.qualified_name.as_str()]);
+        let mut level = vec![ty];
+        for _ in 0..MAX_DEPTH {
+            let mut next = Vec::new();
+            for ty in level {
+                for base in self.bases(ty) {
+                    if seen.insert(base.qualified_name.as_str()) {
+                        hierarchy.push(base);
+                        next.push(base);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            level = next;
+        }
+        hierarchy
+    }
+
+    /// Field, property or event of a type or one of its bases
+    pub fn member(&self, ty: &'a Symbol, name: &str) -> Option<&'a Symbol> {
+        self.hierarchy(ty).into_iter().find_map(|ty| {
+            self.table.children(&ty.qualified_name)
+                .iter()
+                .find(|member| member.kind == SymbolKind::Field && member.name == name)
+                .copied()
+        })
+    }
+
+    /// Method of a type or its bases that an invocation with these arguments
+    /// calls; overrides shadow the methods they override
+    pub fn method(&self, ty: &'a Symbol, name: &str, arguments: &[ArgumentType]) -> Option<&'a Symbol> {
+        let mut overloads: Vec<&'a Symbol> = Vec::new();
+        let mut signatures = HashSet::new();
+        for ty in self.hierarchy(ty) {
+            for member in self.table.children(&ty.qualified_name) {
+                let is_method = member.kind == SymbolKind::Method && member.name == name && !is_constructor(member);
+                if is_method && signatures.insert(parameter_types(member)) {
+                    overloads.push(member);
+                }
+            }
+        }
+        select_overload(overloads, arguments)
+    }
+
+    /// Constructor of a type that `new` with these arguments calls
+    pub fn constructor(&self, ty: &'a Symbol, arguments: &[ArgumentType]) -> Option<&'a Symbol> {
+        let constructors: Vec<&'a Symbol> = self.table.children(&ty.qualified_name)
+            .iter()
+            .filter(|member| is_constructor(member))
+            .copied()
+            .collect();
+        select_overload(constructors, arguments)
+    }
+
+    /// Method called without a receiver: a member of an enclosing type,
+    /// innermost first, or of a type imported with `using static`
+    pub fn unqualified_method(&self, namespace: &str, enclosing: &[String], name: &str, arguments: &[ArgumentType]) -> Option<&'a Symbol> {
+        let statics = self.scope.statics.iter().filter_map(|path| self.resolve_type(path, namespace, &[]));
+        enclosing.iter()
+            .rev()
+            .filter_map(|class| self.find_type(class))
+            .chain(statics)
+            .find_map(|ty| self.method(ty, name, arguments))
+    }
+}
+
+pub fn is_type(symbol: &Symbol) -> bool {
+    matches!(symbol.kind, SymbolKind::Class | SymbolKind::Type)
+}
+
+pub fn is_interface(symbol: &Symbol) -> bool {
+    symbol.metadata["interface"].as_bool().unwrap_or(false)
+}
+
+pub fn is_constructor(symbol: &Symbol) -> bool {
+    symbol.metadata["constructor"].as_bool().unwrap_or(false)
+}
+
+/// Written base types of one part of a type with the qualified names each may refer to
+pub fn base_candidates(ty: &Symbol) -> impl Iterator<Item = (String, Vec<String>)> + '_ {
+    ty.metadata["bases"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|base| (
+            base["name"].as_str().unwrap_or_default().to_string(),
+            serde_json::from_value::<Vec<String>>(base["candidates"].clone()).unwrap_or_default(),
+        ))
+}
+
+/// Whether an unresolved base named `name` is an interface by the `IName` convention
+pub fn looks_like_interface(name: &str) -> bool {
+    let name = name.rsplit('.').next().unwrap_or(name);
+    let mut chars = name.chars();
+    chars.next() == Some('I') && chars.next().is_some_and(|c| c.is_ascii_uppercase())
+}
+
+fn parameter_types(method: &Symbol) -> Vec<String> {
+    serde_json::from_value(method.metadata["parameters"].clone()).unwrap_or_default()
+}
+
+/// Pick the overload whose arity fits the arguments, optional and `params`
+/// parameters included, preferring the one whose parameter types match the
+/// most known argument types
+fn select_overload<'a>(overloads: Vec<&'a Symbol>, arguments: &[ArgumentType]) -> Option<&'a Symbol> {
+    let mut best: Option<(&'a Symbol, usize)> = None;
+    for overload in overloads {
+        let parameters = parameter_types(overload);
+        let required = overload.metadata["required"].as_u64().map_or(parameters.len(), |n| n as usize);
+        let variadic = overload.metadata["variadic"].as_bool().unwrap_or(false);
+        if arguments.len() < required || (arguments.len() > parameters.len() && !variadic) {
+            continue;
+        }
+
+        let score = arguments.iter()
+            .zip(&parameters)
+            .filter(|(argument, parameter)| match argument {
+                ArgumentType::Known(known) => simple_name(known) == simple_name(parameter),
+                ArgumentType::Unknown => false,
+            })
+            .count();
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((overload, score));
+        }
+    }
+    best.map(|(overload, _)| overload)
+}
+
+fn simple_name(type_name: &str) -> &str {
+    type_name.rsplit('.').next().unwrap_or(type_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_type_candidates() {
+        let scope = FileScope::new(vec![
+            UsingDirective { alias: None, path: "Acme.Models".to_string(), is_static: false },
+            UsingDirective { alias: Some("Svc".to_string()), path: "Acme.Services".to_string(), is_static: false },
+            UsingDirective { alias: None, path: "System.Math".to_string(), is_static: true },
+        ]);
+        let enclosing = strings(&["Acme.Orders.OrderService"]);
+
+        assert_eq!(erase("global::System.Collections.Generic.List<int>?"), "System.Collections.Generic.List");
+        assert_eq!(scope.type_candidates("Order?", "Acme.Orders", &enclosing), strings(&[
+            "Acme.Orders.OrderService.Order",
+            "Acme.Orders.Order",
+            "Acme.Order",
+            "Order",
+            "Acme.Models.Order",
+        ]));
+        assert_eq!(scope.type_candidates("Svc.Mailer", "", &[]), strings(&["Acme.Services.Mailer", "Svc.Mailer", "Acme.Models.Svc.Mailer"]));
+        assert!(scope.type_candidates("string[]", "Acme.Orders", &enclosing).is_empty());
+        assert!(looks_like_interface("Acme.IOrderService"));
+        assert!(!looks_like_interface("Item"));
+    }
+}
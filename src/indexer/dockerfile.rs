@@ -0,0 +1,351 @@
+// Dockerfile parser: build stages, the stages they copy from and the shell
+// commands they run
+//
+//   stage       := "FROM" flag* image ("AS" name)? instruction*
+//   instruction := KEYWORD flag* arguments
+//   flag        := "--" name ("=" value)?
+//   heredoc     := "<<" "-"? WORD NEWLINE ... WORD
+//
+// An instruction continues onto the next line after the escape character,
+// `\` unless an `# escape=` directive says otherwise.
+
+use crate::index::Span;
+
+/// File names of Dockerfiles without an extension or suffix
+pub const FILE_NAMES: &[&str] = &["Dockerfile", "Containerfile"];
+
+#[derive(Debug, Default)]
+pub struct File {
+    pub stages: Vec<Stage>,
+}
+
+impl File {
+    /// Stage an image name or `--from` value refers to, by name or index,
+    /// among the stages before `before`
+    pub fn stage(&self, reference: &str, before: usize) -> Option<&Stage> {
+        let stages = &self.stages[..before.min(self.stages.len())];
+        match reference.parse::<usize>() {
+            Ok(index) => stages.get(index),
+            Err(_) => stages.iter().find(|stage| stage.name.as_deref().is_some_and(|name| name.eq_ignore_ascii_case(reference))),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Stage {
+    /// `AS` name
+    pub name: Option<String>,
+    /// Position among the `FROM` instructions, which `--from=0` refers to
+    pub index: usize,
+    /// Image or earlier stage the stage starts from
+    pub base: String,
+    pub platform: Option<String>,
+    /// `#` comment lines directly above `FROM`
+    pub doc: Option<String>,
+    pub copies: Vec<CopyFrom>,
+    pub scripts: Vec<Script>,
+    pub span: Span,
+}
+
+impl Stage {
+    /// Qualified name of the stage symbol, `stage-N` for unnamed stages
+    pub fn qualified_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| format!("stage-{}", self.index))
+    }
+
+    pub fn signature(&self) -> String {
+        match &self.name {
+            Some(name) => format!("FROM {} AS {}", self.base, name),
+            None => format!("FROM {}", self.base),
+        }
+    }
+}
+
+/// `COPY --from` or `ADD --from`
+#[derive(Debug)]
+pub struct CopyFrom {
+    pub from: String,
+    pub sources: Vec<String>,
+    pub span: Span,
+}
+
+/// Shell commands of a `RUN`, `CMD` or `ENTRYPOINT` instruction
+#[derive(Debug)]
+pub struct Script {
+    pub instruction: String,
+    /// Script whose first line is `first_line` of the Dockerfile, with the
+    /// instruction and its flags replaced by spaces
+    pub text: String,
+    pub first_line: u32,
+    pub span: Span,
+}
+
+/// Whether a file is a Dockerfile: `Dockerfile`, `Dockerfile.dev`,
+/// `api.Dockerfile` or a `Containerfile`
+pub fn is_dockerfile(file_path: &str) -> bool {
+    let name = file_path.rsplit(['/', '\\']).next().unwrap_or(file_path);
+    FILE_NAMES.iter().any(|file_name| {
+        name == *file_name
+            || name.strip_prefix(file_name).is_some_and(|rest| rest.starts_with('.'))
+            || name.strip_suffix(file_name).is_some_and(|rest| rest.ends_with('.'))
+    }) || name.ends_with(".dockerfile")
+}
+
+pub fn parse(source: &str) -> File {
+    let lines: Vec<&str> = source.lines().collect();
+    let escape = escape_character(&lines);
+    let mut file = File::default();
+    let mut comments: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let trimmed = lines[i].trim();
+        if trimmed.is_empty() {
+            comments.clear();
+            i += 1;
+            continue;
+        }
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            comments.push(comment.trim().to_string());
+            i += 1;
+            continue;
+        }
+        let doc = (!comments.is_empty()).then(|| comments.join("\n"));
+        comments.clear();
+
+        // Continuation lines, where comment lines do not count
+        let start = i;
+        let mut text = vec![lines[i]];
+        let mut continued = lines[i].trim_end().ends_with(escape);
+        while continued && i + 1 < lines.len() {
+            i += 1;
+            if lines[i].trim_start().starts_with('#') {
+                text.push("");
+            } else {
+                text.push(lines[i]);
+                continued = lines[i].trim_end().ends_with(escape);
+            }
+        }
+        let mut end = i;
+        i += 1;
+
+        let joined: String = text.iter()
+            .map(|line| line.trim_end().strip_suffix(escape).unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut words = joined.split_whitespace();
+        let keyword = words.next().unwrap_or_default().to_ascii_uppercase();
+        let (flags, arguments) = flags(words.collect());
+
+        // Heredocs follow the instruction line
+        let mut heredocs = Vec::new();
+        for delimiter in arguments.iter().filter_map(|argument| heredoc_delimiter(argument)) {
+            // An unterminated heredoc ran to the end of the file, leaving none for the rest
+            if i >= lines.len() {
+                break;
+            }
+            let body_start = i;
+            while i < lines.len() && lines[i].trim() != delimiter {
+                i += 1;
+            }
+            heredocs.push((body_start, lines[body_start..i.min(lines.len())].join("\n")));
+            end = i.min(lines.len() - 1);
+            i += 1;
+        }
+        let span = Span { line: start as u32, column: 0, end_line: end as u32, end_column: lines[end].len() as u32 };
+
+        if keyword == "FROM" {
+            let mut arguments = arguments.into_iter();
+            let base = arguments.next().unwrap_or_default().to_string();
+            let name = match (arguments.next(), arguments.next()) {
+                (Some(keyword), Some(name)) if keyword.eq_ignore_ascii_case("AS") => Some(name.to_string()),
+                _ => None,
+            };
+            let platform = flags.iter().find_map(|(flag, value)| (*flag == "platform").then(|| value.to_string()));
+            file.stages.push(Stage { name, index: file.stages.len(), base, platform, doc, span, ..Stage::default() });
+            continue;
+        }
+        // Instructions before the first `FROM`, such as `ARG`, belong to no stage
+        let Some(stage) = file.stages.last_mut() else {
+            continue;
+        };
+        stage.span.end_line = span.end_line;
+        stage.span.end_column = span.end_column;
+
+        match keyword.as_str() {
+            "COPY" | "ADD" => {
+                if let Some((_, from)) = flags.iter().find(|(flag, _)| *flag == "from") {
+                    let sources = match arguments.split_last() {
+                        Some((_, sources)) => sources.iter().map(|source| source.trim_matches(['"', '[', ']', ',']).to_string()).collect(),
+                        None => Vec::new(),
+                    };
+                    stage.copies.push(CopyFrom { from: from.to_string(), sources, span });
+                }
+            }
+            "RUN" | "CMD" | "ENTRYPOINT" => {
+                let instruction = keyword.clone();
+                if !heredocs.is_empty() {
+                    for (first_line, text) in heredocs {
+                        stage.scripts.push(Script { instruction: instruction.clone(), text, first_line: first_line as u32, span });
+                    }
+                    continue;
+                }
+                let exec_form = joined.find('[').filter(|&at| joined[..at].split_whitespace().all(|word| word.starts_with('-') || word.eq_ignore_ascii_case(&keyword)));
+                let text = match exec_form.and_then(|at| serde_json::from_str::<Vec<String>>(&joined[at..]).ok()) {
+                    // `["sh", "-c", "..."]` runs the command line it is given
+                    Some(words) => match words.iter().position(|word| word == "-c") {
+                        Some(flag) if flag + 1 < words.len() => words[flag + 1].clone(),
+                        _ => words.join(" "),
+                    },
+                    None => shell_form(&text, &keyword, escape),
+                };
+                stage.scripts.push(Script { instruction, text, first_line: start as u32, span });
+            }
+            _ => {}
+        }
+    }
+    file
+}
+
+/// The character of an `# escape=` parser directive at the top of the file
+fn escape_character(lines: &[&str]) -> char {
+    lines.iter()
+        .map_while(|line| line.trim().strip_prefix('#'))
+        .find_map(|directive| {
+            let (name, value) = directive.split_once('=')?;
+            (name.trim().eq_ignore_ascii_case("escape")).then(|| value.trim().chars().next()).flatten()
+        })
+        .unwrap_or('\\')
+}
+
+/// `--name=value` flags before the arguments of an instruction
+fn flags(words: Vec<&str>) -> (Vec<(&str, &str)>, Vec<&str>) {
+    let count = words.iter().take_while(|word| word.starts_with("--")).count();
+    let flags = words[..count].iter()
+        .map(|word| word[2..].split_once('=').unwrap_or((&word[2..], "")))
+        .collect();
+    (flags, words[count..].to_vec())
+}
+
+/// Delimiter of a heredoc argument such as `<<EOF`, `<<-"EOF"` or `<<'EOF'`
+fn heredoc_delimiter(argument: &str) -> Option<&str> {
+    let delimiter = argument.strip_prefix("<<")?;
+    let delimiter = delimiter.strip_prefix('-').unwrap_or(delimiter).trim_matches(['"', '\'']);
+    (!delimiter.is_empty() && delimiter.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')).then_some(delimiter)
+}
+
+/// Lines of a shell-form instruction as a script, with the keyword and its
+/// flags blanked out so that columns stay put
+fn shell_form(lines: &[&str], keyword: &str, escape: char) -> String {
+    let mut script = Vec::with_capacity(lines.len());
+    for (index, line) in lines.iter().enumerate() {
+        let mut line = line.to_string();
+        if index == 0 {
+            let indent = line.len() - line.trim_start().len();
+            let mut prefix = indent + keyword.len();
+            while let Some(flag) = line[prefix..].trim_start().strip_prefix("--") {
+                let flag_start = line.len() - flag.len() - 2;
+                prefix = flag_start + 2 + flag.find(char::is_whitespace).unwrap_or(flag.len());
+            }
+            line.replace_range(..prefix, &" ".repeat(prefix));
+        }
+        if escape != '\\' && line.trim_end().ends_with(escape) {
+            let at = line.trim_end().len() - escape.len_utf8();
+            line.replace_range(at.., "\\");
+        }
+        script.push(line);
+    }
+    script.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dockerfile() {
+        let file = parse(concat!(
+            "ARG RUST_VERSION=1.80\n",
+            "# Compile the binaries\n",
+            "FROM --platform=$BUILDPLATFORM rust:${RUST_VERSION} AS builder\n",
+            "COPY . /src\n",
+            "RUN --mount=type=cache,target=/root/.cargo \\\n",
+            "    # fetch first\n",
+            "    ./scripts/build.sh --release\n",
+            "\n",
+            "FROM builder as test\n",
+            "RUN <<EOF\n",
+            "make -C /src check\n",
+            "EOF\n",
+            "\n",
+            "FROM debian:bookworm-slim\n",
+            "COPY --from=builder /src/target/release/app /usr/bin/app\n",
+            "COPY --from=0 /src/config.toml /etc/app/\n",
+            "ENTRYPOINT [\"/usr/bin/app\", \"serve\"]\n",
+            "CMD [\"sh\", \"-c\", \"./entrypoint.sh\"]\n",
+        ));
+
+        let stages: Vec<(String, &str, Option<&str>, u32, u32)> = file.stages.iter()
+            .map(|stage| (stage.qualified_name(), stage.base.as_str(), stage.doc.as_deref(), stage.span.line, stage.span.end_line))
+            .collect();
+        assert_eq!(stages, vec![
+            ("builder".to_string(), "rust:${RUST_VERSION}", Some("Compile the binaries"), 2, 6),
+            ("test".to_string(), "builder", None, 8, 11),
+            ("stage-2".to_string(), "debian:bookworm-slim", None, 13, 17),
+        ]);
+        assert_eq!(file.stages[0].platform.as_deref(), Some("$BUILDPLATFORM"));
+        assert_eq!(file.stage("builder", 1).map(|stage| stage.index), Some(0));
+        assert!(file.stage("test", 1).is_none());
+
+        let builder = &file.stages[0];
+        assert_eq!(builder.scripts.len(), 1);
+        assert_eq!(builder.scripts[0].first_line, 4);
+        assert_eq!(builder.scripts[0].text, concat!(
+            "                                           \\\n",
+            "\n",
+            "    ./scripts/build.sh --release",
+        ));
+
+        let test = &file.stages[1];
+        assert_eq!((test.scripts[0].first_line, test.scripts[0].text.as_str()), (10, "make -C /src check"));
+
+        let runtime = &file.stages[2];
+        let copies: Vec<(&str, Vec<&str>, u32)> = runtime.copies.iter()
+            .map(|copy| (copy.from.as_str(), copy.sources.iter().map(String::as_str).collect(), copy.span.line))
+            .collect();
+        assert_eq!(copies, vec![
+            ("builder", vec!["/src/target/release/app"], 14),
+            ("0", vec!["/src/config.toml"], 15),
+        ]);
+        let scripts: Vec<(&str, &str)> = runtime.scripts.iter()
+            .map(|script| (script.instruction.as_str(), script.text.as_str()))
+            .collect();
+        assert_eq!(scripts, vec![("ENTRYPOINT", "/usr/bin/app serve"), ("CMD", "./entrypoint.sh")]);
+    }
+
+    #[test]
+    fn test_parse_heredocs() {
+        let file = parse("FROM alpine\nRUN <<A <<B\necho one\nA\necho two\nB\nRUN echo three\n");
+        let scripts: Vec<(u32, &str)> = file.stages[0].scripts.iter()
+            .map(|script| (script.first_line, script.text.as_str()))
+            .collect();
+        assert_eq!(scripts, vec![(2, "echo one"), (4, "echo two"), (6, "    echo three")]);
+
+        let file = parse("FROM alpine\nRUN <<A <<B\necho hi");
+        let scripts: Vec<(u32, &str)> = file.stages[0].scripts.iter()
+            .map(|script| (script.first_line, script.text.as_str()))
+            .collect();
+        assert_eq!(scripts, vec![(2, "echo hi")]);
+        assert_eq!(file.stages[0].span.end_line, 2);
+    }
+
+    #[test]
+    fn test_is_dockerfile() {
+        assert!(is_dockerfile("deploy/Dockerfile"));
+        assert!(is_dockerfile("Dockerfile.dev"));
+        assert!(is_dockerfile("images/api.Dockerfile"));
+        assert!(is_dockerfile("Containerfile"));
+        assert!(!is_dockerfile("src/dockerfile.rs"));
+        assert!(!is_dockerfile("MyDockerfile"));
+    }
+}
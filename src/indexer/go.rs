@@ -0,0 +1,465 @@
+// Go packages, imports and method sets
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::index::{parent_name, Symbol, SymbolKind, SymbolTable};
+
+/// Maximum number of embedded types followed to build one method set
+const MAX_DEPTH: usize = 8;
+
+/// Predeclared types, which never resolve to an indexed symbol
+const BUILTIN_TYPES: &[&str] = &[
+    "any", "bool", "byte", "comparable", "complex64", "complex128", "error", "float32", "float64",
+    "int", "int8", "int16", "int32", "int64", "rune", "string",
+    "uint", "uint8", "uint16", "uint32", "uint64", "uintptr",
+];
+
+/// Predeclared functions, whose calls are not recorded
+const BUILTIN_FUNCTIONS: &[&str] = &[
+    "append", "cap", "clear", "close", "complex", "copy", "delete", "imag", "len", "make",
+    "max", "min", "new", "panic", "print", "println", "real", "recover",
+];
+
+/// Name bound by an import spec, kept in the metadata of `Import` symbols
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportBinding {
+    /// Local package name, or `.` for a dot import
+    pub alias: String,
+    /// Import path, e.g. `github.com/acme/app/store`
+    pub path: String,
+}
+
+/// Directory holding the nearest `go.mod` of a file and the module path it declares
+pub fn find_module(file: &str) -> Option<(PathBuf, String)> {
+    for dir in Path::new(file).ancestors().skip(1) {
+        let Ok(go_mod) = std::fs::read_to_string(dir.join("go.mod")) else {
+            continue;
+        };
+        let module = go_mod.lines()
+            .find_map(|line| line.trim().strip_prefix("module "))
+            .map(|path| path.trim().trim_matches('"').to_string())?;
+        return Some((dir.to_path_buf(), module));
+    }
+    None
+}
+
+/// Nearest `go.mod` of each directory, looked up once for all the files of
+/// a package until cleared for the next batch of files
+#[derive(Default)]
+pub struct Modules {
+    directories: Mutex<HashMap<PathBuf, Option<(PathBuf, String)>>>,
+}
+
+impl Modules {
+    /// Directory holding the nearest `go.mod` of a file and the module path it declares
+    pub fn find(&self, file: &str) -> Option<(PathBuf, String)> {
+        let directory = Path::new(file).parent().unwrap_or(Path::new(""));
+        if let Some(module) = self.directories.lock().get(directory) {
+            return module.clone();
+        }
+        let module = find_module(file);
+        self.directories.lock().insert(directory.to_path_buf(), module.clone());
+        module
+    }
+
+    pub fn clear(&self) {
+        self.directories.lock().clear();
+    }
+
+    /// Import path of the package a file belongs to
+    ///
+    /// The path is derived from the module path in `go.mod` and the file's
+    /// directory; external test packages get a `_test` suffix. Files outside a
+    /// module fall back to the name in their package clause.
+    pub fn package_path(&self, file: &str, package_name: &str) -> String {
+        let Some((root, module)) = self.find(file) else {
+            return package_name.to_string();
+        };
+
+        let dir = Path::new(file).parent().unwrap_or(Path::new(""));
+        let mut path = module;
+        if let Ok(relative) = dir.strip_prefix(&root) {
+            for component in relative.components() {
+                path.push('/');
+                path.push_str(&component.as_os_str().to_string_lossy());
+            }
+        }
+        if package_name.ends_with("_test") {
+            path.push_str("_test");
+        }
+        path
+    }
+}
+
+/// Package name an import is referred to by when it has no explicit name
+///
+/// This is the last path element, skipping a major version element such as
+/// `v2` and a `.vN` suffix as in `gopkg.in/yaml.v3`.
+pub fn default_alias(path: &str) -> String {
+    let mut segments = path.rsplit('/');
+    let last = segments.next().unwrap_or(path);
+    let is_version = |s: &str| s.len() > 1 && s.starts_with('v') && s[1..].chars().all(|c| c.is_ascii_digit());
+    let name = match segments.next() {
+        Some(previous) if is_version(last) => previous,
+        _ => last,
+    };
+    match name.rsplit_once('.') {
+        Some((stem, version)) if is_version(version) => stem.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+pub fn is_builtin_function(name: &str) -> bool {
+    BUILTIN_FUNCTIONS.contains(&name)
+}
+
+/// Signature with package qualifiers dropped, so that `func(r io.Reader)` in
+/// one package matches `func(r Reader)` in another
+pub fn signature_key(signature: &str) -> String {
+    let mut key = String::with_capacity(signature.len());
+    let mut word = String::new();
+    for c in signature.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+        if c != '.' || word.is_empty() {
+            key.push_str(&word);
+            if !c.is_whitespace() {
+                key.push(c);
+            }
+        }
+        word.clear();
+    }
+    key.push_str(&word);
+    key
+}
+
+/// Named type a type expression refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeRef {
+    /// Qualified name, e.g. `github.com/acme/app/store.Store`
+    pub name: String,
+    pub pointer: bool,
+}
+
+/// Methods by name
+pub type MethodSet<'a> = BTreeMap<String, &'a Symbol>;
+
+/// Package and imports of one Go file
+#[derive(Debug, Default)]
+pub struct FileScope {
+    package: String,
+    imports: HashMap<String, String>,
+    dot_imports: Vec<String>,
+}
+
+impl FileScope {
+    pub fn new(package: String, bindings: impl IntoIterator<Item = ImportBinding>) -> Self {
+        let mut scope = Self { package, ..Self::default() };
+        for binding in bindings {
+            if binding.alias == "." {
+                scope.dot_imports.push(binding.path);
+            } else {
+                scope.imports.insert(binding.alias, binding.path);
+            }
+        }
+        scope
+    }
+
+    /// Bindings recorded on the file's `Import` symbols
+    pub fn from_symbols<'s>(package: String, symbols: impl IntoIterator<Item = &'s Symbol>) -> Self {
+        Self::new(package, symbols.into_iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Import)
+            .flat_map(|symbol| serde_json::from_value::<Vec<ImportBinding>>(symbol.metadata["bindings"].clone()).unwrap_or_default()))
+    }
+
+    /// Import path of the package
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// Import path bound to a package name
+    pub fn import(&self, alias: &str) -> Option<&str> {
+        self.imports.get(alias).map(String::as_str)
+    }
+
+    /// Named type of a type node, e.g. `*store.Item` or `List[int]`
+    ///
+    /// Composite types such as slices and maps have no methods and yield `None`.
+    pub fn type_ref(&self, node: tree_sitter::Node, content: &str) -> Option<TypeRef> {
+        let text = |node: tree_sitter::Node| content[node.byte_range()].to_string();
+        match node.kind() {
+            "type_identifier" => {
+                let name = text(node);
+                (!BUILTIN_TYPES.contains(&name.as_str())).then(|| TypeRef {
+                    name: format!("{}.{}", self.package, name),
+                    pointer: false,
+                })
+            }
+            "qualified_type" => {
+                let package = text(node.child_by_field_name("package")?);
+                let name = text(node.child_by_field_name("name")?);
+                Some(TypeRef {
+                    name: format!("{}.{}", self.import(&package)?, name),
+                    pointer: false,
+                })
+            }
+            "pointer_type" => {
+                let inner = self.type_ref(node.named_child(0)?, content)?;
+                Some(TypeRef { pointer: true, ..inner })
+            }
+            "generic_type" => self.type_ref(node.child_by_field_name("type")?, content),
+            "parenthesized_type" => self.type_ref(node.named_child(0)?, content),
+            _ => None,
+        }
+    }
+
+    /// Qualified names a package-level name written in this file may refer to
+    fn candidates(&self, name: &str) -> impl Iterator<Item = String> + '_ {
+        let name = name.to_string();
+        std::iter::once(self.package.as_str())
+            .chain(self.dot_imports.iter().map(String::as_str))
+            .map(move |package| format!("{}.{}", package, name))
+    }
+}
+
+/// Resolves Go names against the symbol table, package by package
+///
+/// Package-level names resolve within the file's package and its dot imports,
+/// `pkg.Name` through the file's imports, and selectors on typed values
+/// through the method set or fields of the value's type, embedded types
+/// included.
+pub struct Resolver<'t, 'a> {
+    table: &'t SymbolTable<'a>,
+    scope: FileScope,
+    local: HashMap<&'a str, &'a Symbol>,
+    method_sets: RefCell<HashMap<(String, bool), Rc<MethodSet<'a>>>>,
+}
+
+impl<'t, 'a> Resolver<'t, 'a> {
+    pub fn new(table: &'t SymbolTable<'a>, file: &str, package: String) -> Self {
+        let symbols = table.in_file(file);
+        let mut local = HashMap::new();
+        for &symbol in symbols {
+            if symbol.kind != SymbolKind::Import {
+                local.entry(symbol.qualified_name.as_str()).or_insert(symbol);
+            }
+        }
+
+        Self {
+            table,
+            scope: FileScope::from_symbols(package, symbols.iter().copied()),
+            local,
+            method_sets: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn scope(&self) -> &FileScope {
+        &self.scope
+    }
+
+    /// Symbol defined in this file with the given qualified name
+    pub fn symbol(&self, qualified_name: &str) -> Option<&'a Symbol> {
+        self.local.get(qualified_name).copied()
+    }
+
+    /// Go symbol with the given qualified name
+    pub fn find(&self, qualified_name: &str) -> Option<&'a Symbol> {
+        if let Some(symbol) = self.symbol(qualified_name) {
+            return Some(symbol);
+        }
+
+        let name = qualified_name.rsplit('.').next().unwrap_or(qualified_name);
+        self.table.named(name)
+            .iter()
+            .find(|symbol| symbol.qualified_name == qualified_name && symbol.language == "go")
+            .copied()
+    }
+
+    /// Package-level name used without a qualifier
+    pub fn package_member(&self, name: &str) -> Option<&'a Symbol> {
+        self.scope.candidates(name).find_map(|candidate| self.find(&candidate))
+    }
+
+    /// `pkg.Name`, `pkg` being an import of this file
+    pub fn imported(&self, alias: &str, name: &str) -> Option<&'a Symbol> {
+        self.find(&format!("{}.{}", self.scope.import(alias)?, name))
+    }
+
+    /// Method or field selected on a value of the named type
+    pub fn member(&self, type_name: &str, name: &str) -> Option<&'a Symbol> {
+        if let Some(method) = self.method_set(type_name, true).get(name) {
+            return Some(method);
+        }
+        self.field(type_name, name, 0)
+    }
+
+    /// Named type of a field, variable or function result
+    pub fn type_of(&self, symbol: &Symbol) -> Option<String> {
+        symbol.metadata["type"].as_str().map(str::to_string)
+    }
+
+    fn field(&self, type_name: &str, name: &str, depth: usize) -> Option<&'a Symbol> {
+        let ty = self.underlying(type_name, 0)?;
+        let fields = self.table.children(&ty.qualified_name);
+        if let Some(field) = fields.iter().find(|f| f.kind == SymbolKind::Field && f.name == name) {
+            return Some(field);
+        }
+        if depth >= MAX_DEPTH {
+            return None;
+        }
+        embedded(ty).find_map(|(embedded, _)| self.field(&embedded, name, depth + 1))
+    }
+
+    /// Type declaration a name refers to, following aliases
+    fn underlying(&self, type_name: &str, depth: usize) -> Option<&'a Symbol> {
+        let ty = self.find(type_name)?;
+        match ty.metadata["alias"].as_str() {
+            Some(target) if depth < MAX_DEPTH => self.underlying(target, depth + 1),
+            _ => Some(ty),
+        }
+    }
+
+    /// Methods callable on a value of the named type, or on a pointer to it,
+    /// promoted methods of embedded types included
+    pub fn method_set(&self, type_name: &str, pointer: bool) -> Rc<MethodSet<'a>> {
+        self.method_set_at(type_name, pointer, 0)
+    }
+
+    fn method_set_at(&self, type_name: &str, pointer: bool, depth: usize) -> Rc<MethodSet<'a>> {
+        let key = (type_name.to_string(), pointer);
+        if let Some(methods) = self.method_sets.borrow().get(&key) {
+            return methods.clone();
+        }
+
+        let mut methods = BTreeMap::new();
+        if let Some(ty) = self.underlying(type_name, 0) {
+            for method in self.table.children(&ty.qualified_name) {
+                let pointer_receiver = method.metadata["pointer"].as_bool().unwrap_or(false);
+                if method.kind == SymbolKind::Method && method.language == "go" && (pointer || !pointer_receiver) {
+                    methods.insert(method.name.clone(), *method);
+                }
+            }
+
+            // Methods at a shallower depth shadow promoted ones
+            if depth < MAX_DEPTH {
+                for (embedded, embedded_pointer) in embedded(ty) {
+                    let promoted = self.method_set_at(&embedded, pointer || embedded_pointer, depth + 1);
+                    for (name, method) in promoted.iter() {
+                        methods.entry(name.clone()).or_insert(*method);
+                    }
+                }
+            }
+        }
+
+        let methods = Rc::new(methods);
+        self.method_sets.borrow_mut().insert(key, methods.clone());
+        methods
+    }
+
+    /// Interfaces in the index whose method set a named type satisfies
+    ///
+    /// Candidates are the interfaces declaring a method with the name of one
+    /// of the type's methods. Each one is returned with whether only the
+    /// pointer type satisfies it.
+    pub fn implemented_interfaces(&self, ty: &Symbol) -> Vec<(&'a Symbol, bool)> {
+        if is_interface(ty) {
+            return Vec::new();
+        }
+
+        let pointer_methods = self.method_set(&ty.qualified_name, true);
+        let value_methods = self.method_set(&ty.qualified_name, false);
+
+        let mut candidates = BTreeMap::new();
+        for name in pointer_methods.keys() {
+            for method in self.table.named(name) {
+                if method.language != "go" || !method.metadata["interface"].as_bool().unwrap_or(false) {
+                    continue;
+                }
+                let Some(interface) = parent_name(&method.qualified_name).and_then(|parent| self.find(parent)) else {
+                    continue;
+                };
+                candidates.entry(interface.qualified_name.as_str()).or_insert(interface);
+            }
+        }
+
+        candidates.into_values()
+            .filter(|interface| interface.qualified_name != ty.qualified_name && !interface.metadata["constraint"].as_bool().unwrap_or(false))
+            .filter_map(|interface| {
+                let required = self.method_set(&interface.qualified_name, true);
+                if required.is_empty() || !satisfies(&pointer_methods, &required) {
+                    return None;
+                }
+                Some((interface, !satisfies(&value_methods, &required)))
+            })
+            .collect()
+    }
+}
+
+pub fn is_interface(symbol: &Symbol) -> bool {
+    symbol.metadata["kind"] == "interface"
+}
+
+/// Embedded types of a struct or interface, with whether each is a pointer
+fn embedded(ty: &Symbol) -> impl Iterator<Item = (String, bool)> + '_ {
+    ty.metadata["embedded"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|embedded| {
+            let name = embedded["type"].as_str()?.to_string();
+            Some((name, embedded["pointer"].as_bool().unwrap_or(false)))
+        })
+}
+
+/// Whether every required method is present with a matching signature
+fn satisfies(methods: &MethodSet, required: &MethodSet) -> bool {
+    required.iter().all(|(name, wanted)| {
+        methods.get(name).is_some_and(|method| match (&method.type_info, &wanted.type_info) {
+            (Some(have), Some(want)) => signature_key(have) == signature_key(want),
+            _ => true,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_package_path_and_aliases() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("internal/store")).unwrap();
+        std::fs::write(dir.path().join("go.mod"), "module github.com/acme/app\n\ngo 1.22\n").unwrap();
+        let file = |relative: &str| dir.path().join(relative).to_string_lossy().to_string();
+        let modules = Modules::default();
+        let package_path = |file: &str, package_name: &str| modules.package_path(file, package_name);
+
+        assert_eq!(package_path(&file("main.go"), "main"), "github.com/acme/app");
+        assert_eq!(package_path(&file("internal/store/store.go"), "store"), "github.com/acme/app/internal/store");
+        assert_eq!(package_path(&file("internal/store/store_test.go"), "store_test"), "github.com/acme/app/internal/store_test");
+        assert_eq!(package_path("/nonexistent/x.go", "x"), "x");
+        // A renamed module is seen once the cache is cleared
+        std::fs::write(dir.path().join("go.mod"), "module github.com/acme/shop\n").unwrap();
+        modules.clear();
+        assert_eq!(package_path(&file("internal/store/cache.go"), "store"), "github.com/acme/shop/internal/store");
+
+        assert_eq!(default_alias("github.com/acme/app/store"), "store");
+        assert_eq!(default_alias("github.com/jackc/pgx/v5"), "pgx");
+        assert_eq!(default_alias("gopkg.in/yaml.v3"), "yaml");
+    }
+
+    #[test]
+    fn test_signature_key() {
+        assert_eq!(signature_key("(io.Reader, []*store.Item) (int, error)"), "(Reader,[]*Item)(int,error)");
+        assert_eq!(signature_key("(...string) error"), "(...string)error");
+    }
+}
@@ -0,0 +1,443 @@
+// Languages defined at runtime by a tree-sitter grammar, loaded from a
+// shared library or a WASM module, and `.scm` queries over it, and the
+// built-in Kotlin and Scala, whose grammars are linked in and whose queries
+// are in `grammars/`
+//
+// The queries use the capture names of tree-sitter's tags queries:
+//
+//   @definition.<kind>   a definition, such as `@definition.function`
+//   @reference.<kind>    a reference, such as `@reference.call`
+//   @name                the name of the definition or reference of the match
+//   @doc                 comments documenting the definition of the match
+//   @scope               a node whose definitions are local to it, such as a block
+//   @package             the name of a package the file's definitions belong to
+//   @receiver            the type an extension function of the match is defined on
+//
+// A definition without a name node, such as Kotlin's unnamed companion
+// object, takes the name set by `(#set! name "Companion")` in its pattern.
+//
+// Definitions nest inside definitions by their ranges, which gives qualified
+// names such as `Stack.push`, and a reference resolves to the definition of
+// its name in the innermost scope around it that declares one. The `@package`
+// names of a file, joined by `.`, prefix its qualified names, which gives the
+// `package.Class.member` names of the Java parser to JVM languages.
+
+use std::path::Path;
+
+use anyhow::Context;
+use tree_sitter::{Language, Node, Query, QueryCursor, StreamingIterator};
+
+use crate::config::GrammarConfig;
+use crate::index::Span;
+
+#[derive(Debug, Default)]
+pub struct File {
+    pub definitions: Vec<Definition>,
+    pub references: Vec<Reference>,
+}
+
+#[derive(Debug)]
+pub struct Definition {
+    /// Capture suffix, such as `function` for `@definition.function`
+    pub kind: String,
+    pub name: String,
+    /// Names of the enclosing definitions and this one, joined by `.`
+    pub qualified_name: String,
+    /// First line of the definition
+    pub signature: String,
+    pub doc: Option<String>,
+    /// Type an extension function is defined on
+    pub receiver: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct Reference {
+    /// Capture suffix, such as `call` for `@reference.call`
+    pub kind: String,
+    pub name: String,
+    /// Innermost definition around the reference
+    pub enclosing: Option<usize>,
+    /// Definition in the same file the name resolves to
+    pub target: Option<usize>,
+    pub span: Span,
+}
+
+/// Grammar and queries of a language from `[[languages.grammars]]`
+pub struct Grammar {
+    pub name: String,
+    pub extensions: Vec<String>,
+    /// Other languages whose definitions the references of this one may resolve to
+    pub resolve_in: Vec<String>,
+    language: Language,
+    query: Query,
+    /// Shared library holding the language, which must stay loaded while it is used
+    _library: Option<libloading::Library>,
+    /// Engine of a WASM language; every parse runs it in a store of its own
+    #[cfg(feature = "wasm")]
+    engine: Option<tree_sitter::wasmtime::Engine>,
+}
+
+/// A definition matched by the queries
+struct Matched<'tree> {
+    kind: String,
+    node: Node<'tree>,
+    name: Node<'tree>,
+    /// Name from `(#set! name ...)`, used instead of the text of `name`
+    set_name: Option<String>,
+    doc: Vec<Node<'tree>>,
+    receiver: Option<Node<'tree>>,
+}
+
+impl Grammar {
+    /// Grammar over a language that is already loaded, with the source of its queries
+    pub fn new(name: &str, extensions: &[String], language: Language, queries: &str) -> anyhow::Result<Self> {
+        let query = Query::new(&language, queries)
+            .map_err(|error| anyhow::anyhow!("Invalid query for {} at line {}: {}", name, error.row + 1, error.message))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            extensions: extensions.to_vec(),
+            resolve_in: Vec::new(),
+            language,
+            query,
+            _library: None,
+            #[cfg(feature = "wasm")]
+            engine: None,
+        })
+    }
+
+    /// Kotlin, over tree-sitter-kotlin-ng, resolving into Java and Scala
+    pub fn kotlin() -> Self {
+        let queries = include_str!("../../grammars/kotlin/tags.scm");
+        Self::builtin("kotlin", &["kt", "kts"], tree_sitter_kotlin_ng::LANGUAGE.into(), queries, &["java", "scala"])
+    }
+
+    /// Scala 2 and 3, over tree-sitter-scala, resolving into Java and Kotlin
+    pub fn scala() -> Self {
+        let queries = include_str!("../../grammars/scala/tags.scm");
+        Self::builtin("scala", &["scala", "sc"], tree_sitter_scala::LANGUAGE.into(), queries, &["java", "kotlin"])
+    }
+
+    fn builtin(name: &str, extensions: &[&str], language: Language, queries: &str, resolve_in: &[&str]) -> Self {
+        let extensions: Vec<String> = extensions.iter().map(|extension| extension.to_string()).collect();
+        let mut grammar = Self::new(name, &extensions, language, queries)
+            .unwrap_or_else(|error| panic!("Bundled queries are invalid: {}", error));
+        grammar.resolve_in = resolve_in.iter().map(|language| language.to_string()).collect();
+        grammar
+    }
+
+    /// Load the grammar library and query files a configuration names
+    pub fn load(config: &GrammarConfig) -> anyhow::Result<Self> {
+        let mut queries = String::new();
+        for file in &config.queries {
+            let source = std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read query file {}", file))?;
+            queries.push_str(&source);
+            queries.push('\n');
+        }
+
+        let library = Path::new(&config.library);
+        if library.extension().is_some_and(|extension| extension == "wasm") {
+            return Self::load_wasm(config, library, &queries);
+        }
+
+        let symbol = config.symbol.clone().unwrap_or_else(|| format!("tree_sitter_{}", config.name.replace('-', "_")));
+        // SAFETY: loading a grammar runs its initializers and calls its
+        // language function, which is as trusted as the configuration naming it
+        let (library, language) = unsafe {
+            let library = libloading::Library::new(library)
+                .with_context(|| format!("Failed to load grammar library {}", library.display()))?;
+            let function: libloading::Symbol<unsafe extern "C" fn() -> *const tree_sitter::ffi::TSLanguage> = library
+                .get(symbol.as_bytes())
+                .with_context(|| format!("Grammar library {} does not export {}", config.library, symbol))?;
+            let language = Language::from_raw(function());
+            (library, language)
+        };
+
+        let version = language.abi_version();
+        if !(tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION).contains(&version) {
+            return Err(anyhow::anyhow!(
+                "Grammar {} was generated for ABI version {}, but codegraph supports versions {} to {}",
+                config.library, version, tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION, tree_sitter::LANGUAGE_VERSION
+            ));
+        }
+
+        let mut grammar = Self::new(&config.name, &config.extensions, language, &queries)?;
+        grammar.resolve_in = config.resolve_in.clone();
+        grammar._library = Some(library);
+        Ok(grammar)
+    }
+
+    #[cfg(feature = "wasm")]
+    fn load_wasm(config: &GrammarConfig, library: &Path, queries: &str) -> anyhow::Result<Self> {
+        let bytes = std::fs::read(library)
+            .with_context(|| format!("Failed to read grammar module {}", library.display()))?;
+        let engine = tree_sitter::wasmtime::Engine::default();
+        let mut store = tree_sitter::WasmStore::new(&engine)?;
+        let name = config.symbol.as_deref().unwrap_or(&config.name);
+        let language = store.load_language(name, &bytes)
+            .with_context(|| format!("Failed to load grammar module {}", library.display()))?;
+
+        let mut grammar = Self::new(&config.name, &config.extensions, language, queries)?;
+        grammar.resolve_in = config.resolve_in.clone();
+        grammar.engine = Some(engine);
+        Ok(grammar)
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    fn load_wasm(_config: &GrammarConfig, library: &Path, _queries: &str) -> anyhow::Result<Self> {
+        Err(anyhow::anyhow!(
+            "Cannot load {}: codegraph was built without the `wasm` feature",
+            library.display()
+        ))
+    }
+
+    /// Whether a file has one of the grammar's extensions
+    pub fn handles(&self, file_path: &str) -> bool {
+        Path::new(file_path).extension()
+            .is_some_and(|extension| self.extensions.iter().any(|known| extension == known.trim_start_matches('.')))
+    }
+
+    pub fn parse(&self, source: &str) -> anyhow::Result<File> {
+        let mut parser = tree_sitter::Parser::new();
+        #[cfg(feature = "wasm")]
+        if let Some(engine) = &self.engine {
+            parser.set_wasm_store(tree_sitter::WasmStore::new(engine)?)?;
+        }
+        parser.set_language(&self.language)?;
+        let tree = parser.parse(source, None)
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse {} source", self.name))?;
+
+        Ok(self.extract(tree.root_node(), source))
+    }
+
+    fn extract(&self, root: Node, source: &str) -> File {
+        let capture_names = self.query.capture_names();
+        let mut definitions: Vec<Matched> = Vec::new();
+        let mut references: Vec<(String, Node, Node)> = Vec::new();
+        let mut scopes = vec![root.byte_range()];
+        let mut packages: Vec<Node> = Vec::new();
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&self.query, root, source.as_bytes());
+        while let Some(found) = matches.next() {
+            let (mut role, mut name, mut doc, mut receiver) = (None, None, Vec::new(), None);
+            for capture in found.captures {
+                let capture_name = capture_names[capture.index as usize];
+                if let Some(kind) = capture_name.strip_prefix("definition.") {
+                    role = Some((true, kind, capture.node));
+                } else if let Some(kind) = capture_name.strip_prefix("reference.") {
+                    role = Some((false, kind, capture.node));
+                } else {
+                    match capture_name {
+                        "name" => name = Some(capture.node),
+                        "doc" => doc.push(capture.node),
+                        "receiver" => receiver = Some(capture.node),
+                        "scope" | "local.scope" => scopes.push(capture.node.byte_range()),
+                        "package" if !packages.contains(&capture.node) => packages.push(capture.node),
+                        _ => {}
+                    }
+                }
+            }
+
+            match role {
+                // A definition matched by several patterns counts once, with
+                // the receiver of any pattern that captures it
+                Some((true, kind, node)) => {
+                    let name = name.unwrap_or(node);
+                    match definitions.iter_mut().find(|other| other.node == node && other.name == name) {
+                        Some(other) => other.receiver = other.receiver.or(receiver),
+                        None => definitions.push(Matched {
+                            kind: kind.to_string(),
+                            node,
+                            name,
+                            set_name: self.query.property_settings(found.pattern_index).iter()
+                                .find(|property| &*property.key == "name")
+                                .and_then(|property| property.value.as_deref().map(str::to_string)),
+                            doc,
+                            receiver,
+                        }),
+                    }
+                }
+                // A reference without `@name` is named by its whole node
+                Some((false, kind, node)) => references.push((kind.to_string(), node, name.unwrap_or(node))),
+                None => {}
+            }
+        }
+
+        // Outer definitions first, so that each one's parent is already named
+        definitions.sort_by_key(|definition| (definition.node.start_byte(), std::cmp::Reverse(definition.node.end_byte())));
+        let text = |node: Node| source[node.byte_range()].to_string();
+        packages.sort_by_key(|node| node.start_byte());
+        let package = packages.iter().map(|node| text(*node)).collect::<Vec<_>>().join(".");
+        let innermost = |ranges: &mut dyn Iterator<Item = (usize, std::ops::Range<usize>)>, node: Node| {
+            ranges
+                .filter(|(_, range)| range.start <= node.start_byte() && node.end_byte() <= range.end)
+                .min_by_key(|(_, range)| range.end - range.start)
+                .map(|(index, _)| index)
+        };
+
+        let mut file = File::default();
+        for (index, definition) in definitions.iter().enumerate() {
+            let parent = innermost(&mut definitions[..index].iter().map(|other| other.node.byte_range()).enumerate(), definition.node);
+            let name = definition.set_name.clone().unwrap_or_else(|| text(definition.name));
+            let qualified_name = match parent {
+                Some(parent) => format!("{}.{}", file.definitions[parent].qualified_name, name),
+                None if package.is_empty() => name.clone(),
+                None => format!("{}.{}", package, name),
+            };
+            let doc: Vec<String> = definition.doc.iter().map(|comment| strip_comment(&text(*comment))).collect();
+            file.definitions.push(Definition {
+                kind: definition.kind.clone(),
+                name,
+                qualified_name,
+                signature: text(definition.node).lines().next().unwrap_or_default().trim().to_string(),
+                doc: (!doc.is_empty()).then(|| doc.join("\n")),
+                receiver: definition.receiver.map(text),
+                span: Span::of(definition.node),
+            });
+        }
+
+        // Definitions and `@scope` nodes both hold declarations; a definition
+        // is declared in the innermost one around it other than itself
+        let regions: Vec<std::ops::Range<usize>> = scopes.into_iter()
+            .chain(definitions.iter().map(|definition| definition.node.byte_range()))
+            .collect();
+        let declared_in: Vec<Option<usize>> = definitions.iter()
+            .map(|definition| {
+                let mut candidates = regions.iter().cloned().enumerate().filter(|(_, region)| *region != definition.node.byte_range());
+                innermost(&mut candidates, definition.node)
+            })
+            .collect();
+
+        for (kind, node, name) in references {
+            // The name of a definition is not a reference to it
+            if definitions.iter().any(|definition| definition.name == name) {
+                continue;
+            }
+            let name_text = text(name);
+            let mut around: Vec<usize> = (0..regions.len())
+                .filter(|&region| regions[region].start <= node.start_byte() && node.end_byte() <= regions[region].end)
+                .collect();
+            around.sort_by_key(|&region| regions[region].end - regions[region].start);
+            let target = around.iter().find_map(|&region| {
+                (0..definitions.len()).find(|&definition| declared_in[definition] == Some(region) && file.definitions[definition].name == name_text)
+            });
+
+            file.references.push(Reference {
+                kind,
+                enclosing: innermost(&mut definitions.iter().map(|definition| definition.node.byte_range()).enumerate(), node),
+                name: name_text,
+                target,
+                span: Span::of(node),
+            });
+        }
+        file
+    }
+}
+
+/// Comment text without markers such as `//`, `#`, `--`, `;` and `/* */`
+fn strip_comment(comment: &str) -> String {
+    let comment = comment.trim().trim_start_matches("/**").trim_start_matches("/*").trim_end_matches("*/");
+    comment.lines()
+        .map(|line| line.trim().trim_start_matches(['/', '#', '-', ';', '*']).trim())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUERIES: &str = r#"
+        (class_definition name: (identifier) @name) @definition.class
+        ((comment)* @doc . (function_definition name: (identifier) @name) @definition.function)
+        (call function: [(identifier) @name (attribute attribute: (identifier) @name)]) @reference.call
+        (block) @scope
+    "#;
+
+    #[test]
+    fn test_definitions_and_references_from_queries() {
+        let grammar = Grammar::new("pythonish", &["pyi".to_string()], tree_sitter_python::LANGUAGE.into(), QUERIES).unwrap();
+        assert!(grammar.handles("stubs/os.pyi"));
+        assert!(!grammar.handles("os.py"));
+
+        let file = grammar.parse(concat!(
+            "class Stack:\n",
+            "    def push(self, item):\n",
+            "        helper()\n",
+            "        self.grow()\n",
+            "\n",
+            "    def grow(self):\n",
+            "        def helper():\n",
+            "            pass\n",
+            "        helper()\n",
+            "\n",
+            "# Module level helper\n",
+            "def helper():\n",
+            "    print()\n",
+        )).unwrap();
+
+        let definitions: Vec<(&str, &str, u32)> = file.definitions.iter()
+            .map(|definition| (definition.kind.as_str(), definition.qualified_name.as_str(), definition.span.line))
+            .collect();
+        assert_eq!(definitions, vec![
+            ("class", "Stack", 0),
+            ("function", "Stack.push", 1),
+            ("function", "Stack.grow", 5),
+            ("function", "Stack.grow.helper", 6),
+            ("function", "helper", 11),
+        ]);
+        assert_eq!(file.definitions[1].signature, "def push(self, item):");
+        assert_eq!(file.definitions[4].doc.as_deref(), Some("Module level helper"));
+
+        // Each call resolves in the innermost scope that declares its name
+        let references: Vec<(&str, Option<&str>, Option<&str>)> = file.references.iter()
+            .map(|reference| (
+                reference.name.as_str(),
+                reference.enclosing.map(|index| file.definitions[index].qualified_name.as_str()),
+                reference.target.map(|index| file.definitions[index].qualified_name.as_str()),
+            ))
+            .collect();
+        assert_eq!(references, vec![
+            ("helper", Some("Stack.push"), Some("helper")),
+            ("grow", Some("Stack.push"), Some("Stack.grow")),
+            ("helper", Some("Stack.grow"), Some("Stack.grow.helper")),
+            ("print", Some("helper"), None),
+        ]);
+    }
+
+    #[test]
+    fn test_package_prefixes_qualified_names() {
+        let queries = r#"
+            (package_declaration (scoped_identifier) @package)
+            (class_declaration name: (identifier) @name) @definition.class
+            (method_declaration name: (identifier) @name) @definition.method
+        "#;
+        let grammar = Grammar::new("javaish", &["jv".to_string()], tree_sitter_java::LANGUAGE.into(), queries).unwrap();
+        let file = grammar.parse("package com.shop.orders;\n\nclass Order {\n    void describe() {}\n}\n").unwrap();
+
+        let names: Vec<&str> = file.definitions.iter().map(|definition| definition.qualified_name.as_str()).collect();
+        assert_eq!(names, vec!["com.shop.orders.Order", "com.shop.orders.Order.describe"]);
+    }
+
+    #[test]
+    fn test_load_reports_missing_files() {
+        let config = GrammarConfig {
+            name: "lua".to_string(),
+            extensions: vec!["lua".to_string()],
+            library: "/nonexistent/lua.so".to_string(),
+            symbol: None,
+            queries: vec![],
+            resolve_in: vec![],
+        };
+        let error = Grammar::load(&config).err().unwrap();
+        assert!(error.to_string().contains("Failed to load grammar library /nonexistent/lua.so"));
+
+        let error = Grammar::new("pythonish", &[], tree_sitter_python::LANGUAGE.into(), "(no_such_node) @name").err().unwrap();
+        assert!(error.to_string().starts_with("Invalid query for pythonish at line 1"));
+    }
+}
@@ -0,0 +1,698 @@
+// Intent lexer and recursive-descent parser
+//
+// Grammar, with `{ ... }` bodies of functions and handlers scanned for calls:
+//
+//   file     := (context | function)*
+//   context  := "context" IDENT clause* "{" member* "}"
+//   clause   := "persist" STRING | "extends" IDENT ("," IDENT)* | "depends" "[" IDENT ("," IDENT)* "]"
+//   member   := field | function | handler
+//   field    := IDENT ":" type ("=" expression)?
+//   function := "fn" IDENT "(" (IDENT ":" type ("," IDENT ":" type)*)? ")" ("->" type)? block
+//   handler  := "on" IDENT block
+//   type     := IDENT ("." IDENT)* ("<" type ("," type)* ">")? "?"?
+
+use std::fmt;
+
+use crate::index::Span;
+
+/// Words that look like calls when followed by `(` but are not
+const KEYWORDS: &[&str] = &["if", "else", "while", "for", "in", "match", "return", "let", "var", "fn", "on", "context", "and", "or", "not"];
+
+/// Functions every Intent program can call without declaring them
+const BUILTINS: &[&str] = &["print", "println", "assert", "len"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident,
+    String,
+    Number,
+    /// Punctuation, `->` being the only one longer than a character
+    Punct,
+    /// `///` comment, attached to the next declaration
+    Doc,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+    span: Span,
+}
+
+/// Syntax error the parser recovered from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.span.line + 1, self.span.column + 1, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct File {
+    pub contexts: Vec<Context>,
+    /// Functions declared outside any context
+    pub functions: Vec<Function>,
+    pub errors: Vec<SyntaxError>,
+}
+
+#[derive(Debug, Default)]
+pub struct Context {
+    pub name: String,
+    pub doc: Option<String>,
+    /// Storage URI of a `persist` clause
+    pub persist: Option<String>,
+    pub extends: Vec<String>,
+    pub depends: Vec<String>,
+    pub fields: Vec<Field>,
+    pub functions: Vec<Function>,
+    pub handlers: Vec<Handler>,
+    pub span: Span,
+}
+
+impl Context {
+    /// Declaration without the body, e.g. `context App extends Base depends [Db]`
+    pub fn signature(&self) -> String {
+        let mut signature = format!("context {}", self.name);
+        if let Some(persist) = &self.persist {
+            signature.push_str(&format!(" persist {:?}", persist));
+        }
+        if !self.extends.is_empty() {
+            signature.push_str(&format!(" extends {}", self.extends.join(", ")));
+        }
+        if !self.depends.is_empty() {
+            signature.push_str(&format!(" depends [{}]", self.depends.join(", ")));
+        }
+        signature
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Field {
+    pub name: String,
+    pub doc: Option<String>,
+    pub type_name: String,
+    /// Source text of the default value
+    pub default: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct Parameter {
+    pub name: String,
+    pub type_name: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Function {
+    pub name: String,
+    pub doc: Option<String>,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Option<String>,
+    pub calls: Vec<Call>,
+    pub span: Span,
+}
+
+impl Function {
+    pub fn signature(&self) -> String {
+        let parameters: Vec<String> = self.parameters.iter()
+            .map(|parameter| format!("{}: {}", parameter.name, parameter.type_name))
+            .collect();
+        match &self.return_type {
+            Some(return_type) => format!("fn {}({}) -> {}", self.name, parameters.join(", "), return_type),
+            None => format!("fn {}({})", self.name, parameters.join(", ")),
+        }
+    }
+}
+
+/// `on <event> { ... }` handler of a context
+#[derive(Debug, Default)]
+pub struct Handler {
+    pub event: String,
+    pub doc: Option<String>,
+    pub calls: Vec<Call>,
+    pub span: Span,
+}
+
+impl Handler {
+    /// Name of the handler symbol, which cannot clash with a function name
+    pub fn name(&self) -> String {
+        format!("on:{}", self.event)
+    }
+}
+
+/// Call found in a function or handler body
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    /// Dotted path before the called name, e.g. `db` for `db.query(...)`
+    pub receiver: Option<String>,
+    pub name: String,
+    pub span: Span,
+}
+
+impl Call {
+    /// Text of the callee as written
+    pub fn callee(&self) -> String {
+        match &self.receiver {
+            Some(receiver) => format!("{}.{}", receiver, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+pub fn is_builtin(name: &str) -> bool {
+    BUILTINS.contains(&name)
+}
+
+/// Parse an Intent file, recovering from syntax errors at the next declaration
+pub fn parse(source: &str) -> File {
+    let (tokens, errors) = tokenize(source);
+    let mut parser = Parser { source, tokens, position: 0, errors };
+    let file = parser.file();
+    File { errors: parser.errors, ..file }
+}
+
+fn tokenize(source: &str) -> (Vec<Token>, Vec<SyntaxError>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    let bytes = source.as_bytes();
+    let (mut line, mut line_start) = (0u32, 0usize);
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let (start_line, start_column) = (line, (start - line_start) as u32);
+        let c = bytes[i];
+
+        let kind = match c {
+            b'\n' => {
+                i += 1;
+                line += 1;
+                line_start = i;
+                continue;
+            }
+            c if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                if source[start..i].starts_with("///") {
+                    TokenKind::Doc
+                } else {
+                    continue;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !bytes[i..].starts_with(b"*/") {
+                    if bytes[i] == b'\n' {
+                        line += 1;
+                        line_start = i + 1;
+                    }
+                    i += 1;
+                }
+                i = (i + 2).min(bytes.len());
+                continue;
+            }
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                if bytes.get(i) == Some(&b'"') {
+                    i += 1;
+                } else {
+                    errors.push(SyntaxError {
+                        message: "unterminated string".to_string(),
+                        span: Span { line: start_line, column: start_column, end_line: line, end_column: (i - line_start) as u32 },
+                    });
+                }
+                i = i.min(bytes.len());
+                TokenKind::String
+            }
+            c if c.is_ascii_digit() => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.' || bytes[i] == b'_') {
+                    i += 1;
+                }
+                TokenKind::Number
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' || !c.is_ascii() => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || !bytes[i].is_ascii()) {
+                    i += 1;
+                }
+                TokenKind::Ident
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'>') => {
+                i += 2;
+                TokenKind::Punct
+            }
+            _ => {
+                i += 1;
+                TokenKind::Punct
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            start,
+            end: i,
+            span: Span { line: start_line, column: start_column, end_line: line, end_column: (i - line_start) as u32 },
+        });
+    }
+
+    (tokens, errors)
+}
+
+struct Parser<'s> {
+    source: &'s str,
+    tokens: Vec<Token>,
+    position: usize,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'s> Parser<'s> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.position + offset)
+    }
+
+    fn text(&self, token: &Token) -> &'s str {
+        &self.source[token.start..token.end]
+    }
+
+    /// Whether the next token is this identifier or punctuation
+    fn at(&self, text: &str) -> bool {
+        self.peek().is_some_and(|token| token.kind != TokenKind::String && self.text(token) == text)
+    }
+
+    fn at_kind(&self, offset: usize, kind: TokenKind) -> bool {
+        self.peek_at(offset).is_some_and(|token| token.kind == kind)
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self.at(text) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&mut self, message: impl Into<String>) {
+        let span = self.peek()
+            .or_else(|| self.tokens.last())
+            .map(|token| token.span)
+            .unwrap_or_default();
+        self.errors.push(SyntaxError { message: message.into(), span });
+    }
+
+    fn expect(&mut self, text: &str) -> bool {
+        if self.eat(text) {
+            return true;
+        }
+        let found = self.peek().map_or("end of file".to_string(), |token| format!("`{}`", self.text(token)));
+        self.error(format!("expected `{}`, found {}", text, found));
+        false
+    }
+
+    fn ident(&mut self, what: &str) -> Option<Token> {
+        if self.at_kind(0, TokenKind::Ident) {
+            return self.bump();
+        }
+        self.error(format!("expected {}", what));
+        None
+    }
+
+    /// Consecutive `///` comments, joined without their markers
+    fn doc(&mut self) -> Option<String> {
+        let mut lines = Vec::new();
+        while self.at_kind(0, TokenKind::Doc) {
+            let token = self.bump()?;
+            lines.push(self.text(&token).trim_start_matches('/').trim().to_string());
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    fn file(&mut self) -> File {
+        let mut file = File::default();
+        while self.peek().is_some() {
+            let doc = self.doc();
+            if self.at("context") {
+                if let Some(mut context) = self.context() {
+                    context.doc = doc;
+                    file.contexts.push(context);
+                }
+            } else if self.at("fn") {
+                if let Some(mut function) = self.function() {
+                    function.doc = doc;
+                    file.functions.push(function);
+                }
+            } else if self.peek().is_some() {
+                self.error("expected `context` or `fn`");
+                self.recover(&["context", "fn"]);
+            }
+        }
+        file
+    }
+
+    /// Skip to the next token starting a declaration, stepping over
+    /// balanced brackets so that the body of a broken one is skipped too
+    fn recover(&mut self, starts: &[&str]) {
+        self.position += 1;
+        let mut depth = 0usize;
+        while let Some(token) = self.peek() {
+            let text = self.text(token);
+            match text {
+                "{" | "(" | "[" => depth += 1,
+                "}" | ")" | "]" if depth == 0 => return,
+                "}" | ")" | "]" => depth -= 1,
+                _ if depth == 0 && token.kind == TokenKind::Ident && starts.contains(&text) => return,
+                _ => {}
+            }
+            self.position += 1;
+        }
+    }
+
+    /// Skip a broken context member: fields start with any identifier, so
+    /// resume at the next line, or at `fn`, `on` or the closing brace
+    fn recover_member(&mut self) {
+        let line = self.peek().map(|token| token.span.line);
+        self.position += 1;
+        let mut depth = 0usize;
+        while let Some(token) = self.peek() {
+            let text = self.text(token);
+            if depth == 0 && (matches!(text, "}" | "fn" | "on") || Some(token.span.line) != line) {
+                return;
+            }
+            match text {
+                "{" | "(" | "[" => depth += 1,
+                "}" | ")" | "]" => depth -= 1,
+                _ => {}
+            }
+            self.position += 1;
+        }
+    }
+
+    fn context(&mut self) -> Option<Context> {
+        let start = self.bump()?.span;
+        let name = self.ident("context name")?;
+        let mut context = Context { name: self.text(&name).to_string(), ..Context::default() };
+
+        loop {
+            if self.eat("persist") {
+                match self.peek().filter(|token| token.kind == TokenKind::String).cloned() {
+                    Some(token) => {
+                        self.position += 1;
+                        context.persist = Some(self.text(&token).trim_matches('"').to_string());
+                    }
+                    None => self.error("expected storage URI after `persist`"),
+                }
+            } else if self.eat("extends") {
+                context.extends = self.names(None);
+            } else if self.eat("depends") {
+                if self.expect("[") {
+                    context.depends = self.names(Some("]"));
+                    self.expect("]");
+                }
+            } else {
+                break;
+            }
+        }
+
+        if !self.expect("{") {
+            self.recover(&["context", "fn"]);
+            return None;
+        }
+        // A `context` keyword means the closing brace is missing
+        while self.peek().is_some() && !self.at("}") && !self.at("context") {
+            let doc = self.doc();
+            if self.at("fn") {
+                if let Some(mut function) = self.function() {
+                    function.doc = doc;
+                    context.functions.push(function);
+                }
+            } else if self.at("on") && self.at_kind(1, TokenKind::Ident) {
+                if let Some(mut handler) = self.handler() {
+                    handler.doc = doc;
+                    context.handlers.push(handler);
+                }
+            } else if self.at_kind(0, TokenKind::Ident) && self.peek_at(1).is_some_and(|token| self.text(token) == ":") {
+                if let Some(mut field) = self.field() {
+                    field.doc = doc;
+                    context.fields.push(field);
+                }
+            } else if !self.at("}") && !self.at("context") && self.peek().is_some() {
+                self.error("expected field, `fn` or `on`");
+                self.recover_member();
+            }
+        }
+        let end = self.peek().map(|token| token.span).unwrap_or(start);
+        self.expect("}");
+        context.span = start.to(end);
+        Some(context)
+    }
+
+    /// Comma-separated identifiers, up to `close` if given
+    fn names(&mut self, close: Option<&str>) -> Vec<String> {
+        let mut names = Vec::new();
+        if close.is_some_and(|close| self.at(close)) {
+            return names;
+        }
+        while let Some(token) = self.ident("context name") {
+            names.push(self.text(&token).to_string());
+            if !self.eat(",") {
+                break;
+            }
+        }
+        names
+    }
+
+    fn field(&mut self) -> Option<Field> {
+        let name = self.bump()?;
+        self.expect(":");
+        let type_name = self.type_name()?;
+        let mut field = Field { name: self.text(&name).to_string(), type_name, span: name.span, ..Field::default() };
+
+        if self.eat("=") {
+            // The value runs to the end of the line, or of its brackets
+            let first = self.position;
+            let mut depth = 0usize;
+            let mut last_line = self.tokens[first - 1].span.end_line;
+            while let Some(token) = self.peek() {
+                let text = self.text(token);
+                if depth == 0 && (token.span.line != last_line || text == "}") {
+                    break;
+                }
+                match text {
+                    "{" | "(" | "[" => depth += 1,
+                    "}" | ")" | "]" => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                last_line = token.span.end_line;
+                self.position += 1;
+            }
+            if self.position > first {
+                let (start, end) = (&self.tokens[first], &self.tokens[self.position - 1]);
+                field.default = Some(self.source[start.start..end.end].to_string());
+                field.span = field.span.to(end.span);
+            } else {
+                self.error("expected default value after `=`");
+            }
+        }
+        Some(field)
+    }
+
+    fn type_name(&mut self) -> Option<String> {
+        let first = self.ident("type")?;
+        let mut name = self.text(&first).to_string();
+        while self.at(".") && self.at_kind(1, TokenKind::Ident) {
+            self.position += 1;
+            let segment = self.bump()?;
+            name = format!("{}.{}", name, self.text(&segment));
+        }
+        if self.eat("<") {
+            let mut arguments = Vec::new();
+            loop {
+                arguments.push(self.type_name()?);
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect(">");
+            name = format!("{}<{}>", name, arguments.join(", "));
+        }
+        if self.eat("?") {
+            name.push('?');
+        }
+        Some(name)
+    }
+
+    fn function(&mut self) -> Option<Function> {
+        let start = self.bump()?.span;
+        let name = self.ident("function name")?;
+        let mut function = Function { name: self.text(&name).to_string(), ..Function::default() };
+
+        if self.expect("(") {
+            while self.at_kind(0, TokenKind::Ident) {
+                let parameter = self.bump()?;
+                self.expect(":");
+                let type_name = self.type_name()?;
+                function.parameters.push(Parameter { name: self.text(&parameter).to_string(), type_name });
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect(")");
+        }
+        if self.eat("->") {
+            function.return_type = self.type_name();
+        }
+
+        let (calls, end) = self.block()?;
+        function.calls = calls;
+        function.span = start.to(end);
+        Some(function)
+    }
+
+    fn handler(&mut self) -> Option<Handler> {
+        let start = self.bump()?.span;
+        let event = self.bump()?;
+        let (calls, end) = self.block()?;
+        Some(Handler { event: self.text(&event).to_string(), calls, span: start.to(end), ..Handler::default() })
+    }
+
+    /// Balanced `{ ... }` body and the calls in it: an identifier followed
+    /// by `(`, with the dotted path before it as the receiver
+    fn block(&mut self) -> Option<(Vec<Call>, Span)> {
+        if !self.at("{") {
+            self.error("expected `{`");
+            return None;
+        }
+
+        let mut calls = Vec::new();
+        let mut depth = 0usize;
+        while let Some(token) = self.bump() {
+            let text = self.text(&token);
+            match text {
+                "{" => depth += 1,
+                "}" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some((calls, token.span));
+                    }
+                }
+                _ if token.kind == TokenKind::Ident && self.at("(") && !KEYWORDS.contains(&text) => {
+                    let index = self.position - 1;
+                    let declared = index > 0 && self.text(&self.tokens[index - 1]) == "fn";
+                    if !declared {
+                        calls.push(self.call(index));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.error("unclosed `{`");
+        None
+    }
+
+    fn call(&self, index: usize) -> Call {
+        let name = &self.tokens[index];
+        let mut first = index;
+        while first >= 2
+            && self.text(&self.tokens[first - 1]) == "."
+            && self.tokens[first - 2].kind == TokenKind::Ident
+        {
+            first -= 2;
+        }
+
+        let receiver = (first < index).then(|| {
+            self.tokens[first..index - 1].iter()
+                .filter(|token| token.kind == TokenKind::Ident)
+                .map(|token| self.text(token))
+                .collect::<Vec<_>>()
+                .join(".")
+        });
+        Call {
+            receiver,
+            name: self.text(name).to_string(),
+            span: self.tokens[first].span.to(name.span),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_contexts() {
+        let file = parse(concat!(
+            "/// Sessions\n",
+            "context Session persist \"file://auto\" extends Base, Audited depends [Db] {\n",
+            "  user: String\n",
+            "  prefs: Map<String, List<Int>> = {\n    \"a\": 1\n  }\n",
+            "  fn login(name: String, db: Db) -> Bool {\n",
+            "    db.conn.open(name)\n",
+            "    if (check(name)) { log(\"ok\") }\n",
+            "    return true\n",
+            "  }\n",
+            "  on start {\n    login(\"admin\")\n  }\n",
+            "}\n",
+            "fn main() { Session.login(\"x\") }\n",
+        ));
+
+        assert!(file.errors.is_empty(), "{:?}", file.errors);
+        let session = &file.contexts[0];
+        assert_eq!(session.doc.as_deref(), Some("Sessions"));
+        assert_eq!(session.signature(), "context Session persist \"file://auto\" extends Base, Audited depends [Db]");
+        assert_eq!(session.fields[1].type_name, "Map<String, List<Int>>");
+        assert_eq!(session.fields[1].default.as_deref(), Some("{\n    \"a\": 1\n  }"));
+        assert_eq!(session.fields[1].span.end_line, 5);
+
+        let login = &session.functions[0];
+        assert_eq!(login.signature(), "fn login(name: String, db: Db) -> Bool");
+        let callees: Vec<String> = login.calls.iter().map(Call::callee).collect();
+        assert_eq!(callees, vec!["db.conn.open", "check", "log"]);
+        assert_eq!(login.span.line, 6);
+        assert_eq!(login.span.end_line, 10);
+
+        assert_eq!(session.handlers[0].name(), "on:start");
+        assert_eq!(session.handlers[0].calls[0].name, "login");
+        assert_eq!(file.functions[0].calls[0].callee(), "Session.login");
+    }
+
+    #[test]
+    fn test_recovers_from_syntax_errors() {
+        let file = parse(concat!(
+            "context Broken {\n",
+            "  = oops\n",
+            "  name: String\n",
+            "  fn run( {\n",
+            "}\n",
+            "context Ok {\n  fn go() { run() }\n}\n",
+        ));
+
+        assert!(!file.errors.is_empty());
+        assert_eq!(file.contexts.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["Broken", "Ok"]);
+        assert_eq!(file.contexts[0].fields[0].name, "name");
+        assert_eq!(file.contexts[1].functions[0].calls[0].name, "run");
+    }
+}
@@ -0,0 +1,389 @@
+// Java packages, imports, type hierarchy and overload resolution
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Symbol, SymbolKind, SymbolTable};
+
+/// Maximum number of supertypes followed to find one member
+const MAX_DEPTH: usize = 8;
+
+/// Types without members, which never resolve to an indexed symbol
+const PRIMITIVE_TYPES: &[&str] = &["boolean", "byte", "char", "double", "float", "int", "long", "short", "void", "var"];
+
+/// Languages compiled to the JVM, whose types share one namespace
+pub const JVM_LANGUAGES: &[&str] = &["java", "kotlin", "scala"];
+
+
+/// Name bound by an import declaration, kept in the metadata of `Import` symbols
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportBinding {
+    /// Simple name, or `*` for an on-demand import
+    pub alias: String,
+    /// Imported type, package, or class of a static import
+    pub path: String,
+    /// Whether this is a static import of a member or of all members
+    #[serde(rename = "static", default)]
+    pub is_static: bool,
+}
+
+/// Type as it appears in an overload id: generic arguments and whitespace
+/// removed, so that `Map<String, List<T>>` becomes `Map`
+pub fn erase(type_text: &str) -> String {
+    let mut erased = String::with_capacity(type_text.len());
+    let mut depth = 0usize;
+    for c in type_text.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            c if depth == 0 && !c.is_whitespace() => erased.push(c),
+            _ => {}
+        }
+    }
+    erased
+}
+
+/// Package, imports and declared types of one Java file
+#[derive(Debug, Default)]
+pub struct FileScope {
+    package: String,
+    imports: HashMap<String, String>,
+    on_demand: Vec<String>,
+    static_imports: HashMap<String, String>,
+    static_on_demand: Vec<String>,
+    /// Qualified names of the types declared in the file, nested ones included
+    declared: HashSet<String>,
+}
+
+impl FileScope {
+    pub fn new(package: String, bindings: impl IntoIterator<Item = ImportBinding>, declared: HashSet<String>) -> Self {
+        let mut scope = Self { package, declared, ..Self::default() };
+        for binding in bindings {
+            match (binding.is_static, binding.alias == "*") {
+                (false, false) => { scope.imports.insert(binding.alias, binding.path); }
+                (false, true) => scope.on_demand.push(binding.path),
+                (true, false) => { scope.static_imports.insert(binding.alias, binding.path); }
+                (true, true) => scope.static_on_demand.push(binding.path),
+            }
+        }
+        scope
+    }
+
+    /// Bindings recorded on the file's `Import` symbols
+    pub fn from_symbols<'s>(package: String, symbols: impl IntoIterator<Item = &'s Symbol>, declared: HashSet<String>) -> Self {
+        let bindings: Vec<ImportBinding> = symbols.into_iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Import)
+            .flat_map(|symbol| serde_json::from_value::<Vec<ImportBinding>>(symbol.metadata["bindings"].clone()).unwrap_or_default())
+            .collect();
+        Self::new(package, bindings, declared)
+    }
+
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// Qualified name of a top-level or nested type declared in `package`
+    pub fn qualify(&self, name: &str) -> String {
+        if self.package.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.package, name)
+        }
+    }
+
+    /// Qualified names a type written in this file may refer to, most likely
+    /// first, `enclosing` being the classes around the use, innermost last
+    ///
+    /// Member types declared in the file shadow single-type imports, which
+    /// shadow types of the same package, which shadow on-demand imports.
+    pub fn type_candidates(&self, type_text: &str, enclosing: &[String]) -> Vec<String> {
+        let erased = erase(type_text);
+        let name = erased.trim_end_matches("...").trim_end_matches("[]");
+        if name.is_empty() || PRIMITIVE_TYPES.contains(&name) {
+            return Vec::new();
+        }
+
+        let (head, rest) = match name.split_once('.') {
+            Some((head, rest)) => (head, Some(rest)),
+            None => (name, None),
+        };
+        let with_rest = |base: String| match rest {
+            Some(rest) => format!("{}.{}", base, rest),
+            None => base,
+        };
+
+        let mut candidates = Vec::new();
+        for class in enclosing.iter().rev() {
+            let member = format!("{}.{}", class, head);
+            if self.declared.contains(&member) {
+                candidates.push(with_rest(member));
+            }
+        }
+        if let Some(imported) = self.imports.get(head) {
+            candidates.push(with_rest(imported.clone()));
+        }
+        candidates.push(with_rest(self.qualify(head)));
+        for package in &self.on_demand {
+            candidates.push(with_rest(format!("{}.{}", package, head)));
+        }
+        // A fully qualified name
+        if rest.is_some() {
+            candidates.push(name.to_string());
+        }
+
+        let mut seen = HashSet::new();
+        candidates.retain(|candidate| seen.insert(candidate.clone()));
+        candidates
+    }
+
+    /// Classes a method called without a receiver may be statically imported from
+    fn static_candidates<'s>(&'s self, name: &'s str) -> impl Iterator<Item = String> + 's {
+        self.static_imports.get(name)
+            .and_then(|path| path.rsplit_once('.').map(|(class, _)| class.to_string()))
+            .into_iter()
+            .chain(self.static_on_demand.iter().cloned())
+    }
+}
+
+/// Static type of an argument expression, when it can be read off the syntax
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentType {
+    Known(String),
+    Unknown,
+}
+
+/// Resolves Java types and members against the symbol table
+///
+/// Types resolve through the file's declarations and imports. Members are
+/// searched in the type, then its superclass and interfaces. Overloads are
+/// chosen by arity, then by the argument types that are known.
+pub struct Resolver<'t, 'a> {
+    table: &'t SymbolTable<'a>,
+    scope: FileScope,
+    local: HashMap<&'a str, &'a Symbol>,
+}
+
+impl<'t, 'a> Resolver<'t, 'a> {
+    pub fn new(table: &'t SymbolTable<'a>, file: &str, package: String, declared: HashSet<String>) -> Self {
+        let symbols = table.in_file(file);
+        let mut local = HashMap::new();
+        for &symbol in symbols {
+            if symbol.kind != SymbolKind::Import {
+                local.entry(symbol.qualified_name.as_str()).or_insert(symbol);
+            }
+        }
+
+        Self {
+            table,
+            scope: FileScope::from_symbols(package, symbols.iter().copied(), declared),
+            local,
+        }
+    }
+
+    pub fn scope(&self) -> &FileScope {
+        &self.scope
+    }
+
+    /// Symbol defined in this file with the given qualified name
+    pub fn symbol(&self, qualified_name: &str) -> Option<&'a Symbol> {
+        self.local.get(qualified_name).copied()
+    }
+
+    /// JVM type declaration with the given qualified name
+    pub fn find_type(&self, qualified_name: &str) -> Option<&'a Symbol> {
+        if let Some(symbol) = self.symbol(qualified_name).filter(|s| is_type(s)) {
+            return Some(symbol);
+        }
+
+        let name = qualified_name.rsplit('.').next().unwrap_or(qualified_name);
+        self.table.named(name)
+            .iter()
+            .find(|symbol| symbol.qualified_name == qualified_name && JVM_LANGUAGES.contains(&symbol.language.as_str()) && is_type(symbol))
+            .copied()
+    }
+
+    /// First candidate that is an indexed type
+    pub fn first_type(&self, candidates: &[String]) -> Option<&'a Symbol> {
+        candidates.iter().find_map(|candidate| self.find_type(candidate))
+    }
+
+    /// Type written in this file inside the `enclosing` classes
+    pub fn resolve_type(&self, type_text: &str, enclosing: &[String]) -> Option<&'a Symbol> {
+        self.first_type(&self.scope.type_candidates(type_text, enclosing))
+    }
+
+    /// Static type of a field, variable or method result, from the candidates
+    /// recorded when its file was parsed
+    pub fn type_of(&self, symbol: &Symbol) -> Option<&'a Symbol> {
+        let candidates: Vec<String> = serde_json::from_value(symbol.metadata["types"].clone()).unwrap_or_default();
+        self.first_type(&candidates)
+    }
+
+    /// Superclass and interfaces of a type
+    pub fn supertypes(&self, ty: &Symbol) -> Vec<&'a Symbol> {
+        supertype_candidates(ty)
+            .filter_map(|(_, candidates)| self.first_type(&candidates))
+            .collect()
+    }
+
+    /// `ty` followed by its supertypes, breadth first
+    fn hierarchy(&self, ty: &'a Symbol) -> Vec<&'a Symbol> {
+        let mut hierarchy = vec![ty];
+        let mut seen: HashSet<&str> = HashSet::from([ty.id.as_str()]);
+        let mut level = vec![ty];
+        for _ in 0..MAX_DEPTH {
+            let mut next = Vec::new();
+            for ty in level {
+                for supertype in self.supertypes(ty) {
+                    if seen.insert(supertype.id.as_str()) {
+                        hierarchy.push(supertype);
+                        next.push(supertype);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            level = next;
+        }
+        hierarchy
+    }
+
+    /// Field of a type or one of its supertypes
+    pub fn field(&self, ty: &'a Symbol, name: &str) -> Option<&'a Symbol> {
+        self.hierarchy(ty).into_iter().find_map(|ty| {
+            self.table.children(&ty.qualified_name)
+                .iter()
+                .find(|member| member.kind == SymbolKind::Field && member.name == name)
+                .copied()
+        })
+    }
+
+    /// Method of a type or its supertypes that an invocation with these
+    /// arguments calls; overrides shadow the methods they override
+    pub fn method(&self, ty: &'a Symbol, name: &str, arguments: &[ArgumentType]) -> Option<&'a Symbol> {
+        let mut overloads: Vec<&'a Symbol> = Vec::new();
+        let mut signatures = HashSet::new();
+        for ty in self.hierarchy(ty) {
+            for member in self.table.children(&ty.qualified_name) {
+                let is_method = member.kind == SymbolKind::Method && member.name == name && !is_constructor(member);
+                if is_method && signatures.insert(parameter_types(member)) {
+                    overloads.push(member);
+                }
+            }
+        }
+        select_overload(overloads, arguments)
+    }
+
+    /// Constructor of a type that `new` with these arguments calls
+    pub fn constructor(&self, ty: &'a Symbol, arguments: &[ArgumentType]) -> Option<&'a Symbol> {
+        let constructors: Vec<&'a Symbol> = self.table.children(&ty.qualified_name)
+            .iter()
+            .filter(|member| is_constructor(member))
+            .copied()
+            .collect();
+        select_overload(constructors, arguments)
+    }
+
+    /// Method called without a receiver: a member of an enclosing class,
+    /// innermost first, or a static import
+    pub fn unqualified_method(&self, enclosing: &[String], name: &str, arguments: &[ArgumentType]) -> Option<&'a Symbol> {
+        enclosing.iter()
+            .rev()
+            .filter_map(|class| self.find_type(class))
+            .chain(self.scope.static_candidates(name).filter_map(|class| self.find_type(&class)))
+            .find_map(|ty| self.method(ty, name, arguments))
+    }
+}
+
+pub fn is_type(symbol: &Symbol) -> bool {
+    matches!(symbol.kind, SymbolKind::Class | SymbolKind::Type)
+}
+
+pub fn is_constructor(symbol: &Symbol) -> bool {
+    symbol.metadata["constructor"].as_bool().unwrap_or(false)
+}
+
+/// Written supertypes of a type with their candidate qualified names; the
+/// first element tells whether the type extends rather than implements it
+pub fn supertype_candidates(ty: &Symbol) -> impl Iterator<Item = (bool, Vec<String>)> + '_ {
+    let list = |key: &'static str, extends: bool| {
+        ty.metadata[key]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(move |supertype| (extends, serde_json::from_value::<Vec<String>>(supertype["candidates"].clone()).unwrap_or_default()))
+    };
+    list("extends", true).chain(list("implements", false))
+}
+
+fn parameter_types(method: &Symbol) -> Option<Vec<String>> {
+    serde_json::from_value(method.metadata["parameters"].clone()).ok()
+}
+
+/// Pick the overload whose arity fits the arguments, preferring the one
+/// whose parameter types match the most known argument types
+///
+/// Methods of a language loaded from a grammar have no parameter types and
+/// fit any arguments.
+fn select_overload<'a>(overloads: Vec<&'a Symbol>, arguments: &[ArgumentType]) -> Option<&'a Symbol> {
+    let mut best: Option<(&'a Symbol, usize)> = None;
+    for overload in overloads {
+        let parameters = parameter_types(overload);
+        let fits = match &parameters {
+            Some(parameters) if parameters.last().is_some_and(|p| p.ends_with("...")) => arguments.len() + 1 >= parameters.len(),
+            Some(parameters) => arguments.len() == parameters.len(),
+            None => true,
+        };
+        if !fits {
+            continue;
+        }
+        let parameters = parameters.unwrap_or_default();
+
+        let score = arguments.iter()
+            .zip(&parameters)
+            .filter(|(argument, parameter)| match argument {
+                ArgumentType::Known(known) => simple_name(known) == simple_name(parameter),
+                ArgumentType::Unknown => false,
+            })
+            .count();
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((overload, score));
+        }
+    }
+    best.map(|(overload, _)| overload)
+}
+
+fn simple_name(type_name: &str) -> &str {
+    let type_name = type_name.trim_end_matches("...");
+    type_name.rsplit('.').next().unwrap_or(type_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_type_candidates() {
+        let scope = FileScope::new(
+            "com.acme.store".to_string(),
+            vec![
+                ImportBinding { alias: "List".to_string(), path: "java.util.List".to_string(), is_static: false },
+                ImportBinding { alias: "*".to_string(), path: "com.acme.model".to_string(), is_static: false },
+            ],
+            HashSet::from(["com.acme.store.Store".to_string(), "com.acme.store.Store.Entry".to_string()]),
+        );
+        let enclosing = strings(&["com.acme.store.Store"]);
+
+        assert_eq!(erase("Map<String, List<T>>"), "Map");
+        assert_eq!(scope.type_candidates("List<Entry>", &enclosing), strings(&["java.util.List", "com.acme.store.List", "com.acme.model.List"]));
+        assert_eq!(scope.type_candidates("Entry[]", &enclosing), strings(&["com.acme.store.Store.Entry", "com.acme.store.Entry", "com.acme.model.Entry"]));
+        assert_eq!(scope.type_candidates("Store.Entry", &[]), strings(&["com.acme.store.Store.Entry", "com.acme.model.Store.Entry", "Store.Entry"]));
+        assert!(scope.type_candidates("int", &enclosing).is_empty());
+    }
+}
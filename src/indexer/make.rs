@@ -0,0 +1,447 @@
+// Makefile parser: rules, variables, includes and recipes
+//
+// Lines are joined at trailing backslashes, and a line starting with a tab
+// belongs to the recipe of the rule above it:
+//
+//   rule     := targets (":" | "::") prerequisites ("|" prerequisites)? (";" command)? recipe*
+//   recipe   := TAB command
+//   variable := ("export" | "override" | "private")? NAME ("=" | ":=" | "::=" | "?=" | "+=" | "!=") value
+//             | "define" NAME ... "endef"
+//   include  := ("include" | "-include" | "sinclude") path+
+//
+// Conditionals are read through, so rules and variables in both branches count.
+
+use std::collections::HashMap;
+
+use crate::index::Span;
+
+/// File names `make` reads by default
+pub const FILE_NAMES: &[&str] = &["Makefile", "makefile", "GNUmakefile"];
+
+/// Extensions of makefiles meant to be included
+pub const EXTENSIONS: &[&str] = &["mk", "make"];
+
+/// Directives that may appear inside a recipe without ending it
+const CONDITIONALS: &[&str] = &["ifeq", "ifneq", "ifdef", "ifndef", "else", "endif"];
+
+#[derive(Debug, Default)]
+pub struct File {
+    pub rules: Vec<Rule>,
+    pub variables: Vec<Variable>,
+    pub includes: Vec<Include>,
+    /// Prerequisites of `.PHONY`
+    pub phony: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Rule {
+    pub targets: Vec<String>,
+    /// Normal and order-only prerequisites, with the variables defined
+    /// before the rule expanded
+    pub prerequisites: Vec<String>,
+    /// `#` comment lines directly above the rule, or a `## ...` comment after it
+    pub doc: Option<String>,
+    /// Recipe as a shell script whose lines are those of the makefile from
+    /// `span.line`, with make syntax such as `@` and `$(MAKE)` replaced
+    pub recipe: String,
+    /// Variables used by the prerequisites and the recipe
+    pub variables: Vec<String>,
+    pub double_colon: bool,
+    pub span: Span,
+}
+
+impl Rule {
+    pub fn signature(&self, target: &str) -> String {
+        let colon = if self.double_colon { "::" } else { ":" };
+        match self.prerequisites.as_slice() {
+            [] => format!("{}{}", target, colon),
+            prerequisites => format!("{}{} {}", target, colon, prerequisites.join(" ")),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Variable {
+    pub name: String,
+    /// `=`, `:=`, `?=` and so on, or `define`
+    pub operator: String,
+    pub value: String,
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct Include {
+    pub path: String,
+    /// `-include` and `sinclude` ignore missing files
+    pub optional: bool,
+    pub span: Span,
+}
+
+/// Whether a file is a makefile, by name or extension
+pub fn is_makefile(file_path: &str) -> bool {
+    let name = file_path.rsplit(['/', '\\']).next().unwrap_or(file_path);
+    FILE_NAMES.contains(&name)
+        || name.rsplit_once('.').is_some_and(|(stem, extension)| !stem.is_empty() && EXTENSIONS.contains(&extension))
+}
+
+pub fn parse(source: &str) -> File {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut file = File::default();
+    // Values of the variables defined so far, for expanding prerequisites
+    let mut values: HashMap<String, String> = HashMap::new();
+    // Leading comment lines waiting for the next rule or variable
+    let mut comments: Vec<String> = Vec::new();
+    // Index of the rule whose recipe the following tab-indented lines belong to
+    let mut current: Option<usize> = None;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let start = i;
+        while lines[i].ends_with('\\') && i + 1 < lines.len() {
+            i += 1;
+        }
+        let end = i;
+        i += 1;
+
+        if let Some(rule) = current.filter(|_| lines[start].starts_with('\t')).map(|index| &mut file.rules[index]) {
+            for (line, text) in lines.iter().enumerate().take(end + 1).skip(start) {
+                rule.variables.extend(variable_refs(text));
+                append_recipe(rule, line as u32, text);
+            }
+            rule.span.end_line = end as u32;
+            rule.span.end_column = lines[end].len() as u32;
+            continue;
+        }
+
+        let logical: String = lines[start..=end].iter()
+            .map(|line| line.strip_suffix('\\').unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let trimmed = logical.trim();
+        if trimmed.is_empty() {
+            comments.clear();
+            continue;
+        }
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            comments.push(comment.trim_start_matches('#').trim().to_string());
+            continue;
+        }
+        let doc = (!comments.is_empty()).then(|| comments.join("\n"));
+        comments.clear();
+
+        let (content, trailing) = strip_comment(&logical);
+        let content = content.trim();
+        let directive = content.split_whitespace().next().unwrap_or_default();
+        if CONDITIONALS.contains(&directive) {
+            continue;
+        }
+        current = None;
+        let span = Span { line: start as u32, column: 0, end_line: end as u32, end_column: lines[end].len() as u32 };
+
+        match directive {
+            "include" | "-include" | "sinclude" => {
+                let paths = expand(content[directive.len()..].trim(), &values, 0);
+                file.includes.extend(paths.split_whitespace().map(|path| Include {
+                    path: path.to_string(),
+                    optional: directive != "include",
+                    span,
+                }));
+                continue;
+            }
+            "define" => {
+                let header = content["define".len()..].trim();
+                let name = header.trim_end_matches(['=', ':', '?', '+', '!']).trim().to_string();
+                let body_start = i;
+                while i < lines.len() && lines[i].trim() != "endef" {
+                    i += 1;
+                }
+                let value = lines[body_start..i].join("\n");
+                let end = i.min(lines.len() - 1);
+                i += 1;
+                values.insert(name.clone(), value.clone());
+                file.variables.push(Variable {
+                    name,
+                    operator: "define".to_string(),
+                    value,
+                    doc,
+                    span: Span { end_line: end as u32, end_column: lines[end].len() as u32, ..span },
+                });
+                continue;
+            }
+            _ => {}
+        }
+
+        let content = ["export ", "override ", "private "].iter()
+            .fold(content, |content, keyword| content.strip_prefix(keyword).map_or(content, str::trim_start));
+        match separator(content) {
+            Some(Separator::Assignment(at, operator)) => {
+                let name = content[..at].trim().to_string();
+                let value = content[at + operator.len()..].trim().to_string();
+                let expanded = if operator == "=" { value.clone() } else { expand(&value, &values, 0) };
+                match operator {
+                    "+=" => values.entry(name.clone()).or_default().push_str(&format!(" {}", expanded)),
+                    "?=" => {
+                        values.entry(name.clone()).or_insert(expanded);
+                    }
+                    _ => {
+                        values.insert(name.clone(), expanded);
+                    }
+                }
+                file.variables.push(Variable { name, operator: operator.to_string(), value, doc, span });
+            }
+            Some(Separator::Rule(at, double_colon)) => {
+                let targets: Vec<String> = expand(&content[..at], &values, 0).split_whitespace().map(str::to_string).collect();
+                let rest = &content[at + if double_colon { 2 } else { 1 }..];
+                let (rest, command) = match rest.split_once(';') {
+                    Some((rest, command)) => (rest, Some(command)),
+                    None => (rest, None),
+                };
+                // `target: VARIABLE = value` sets a variable for the target
+                if matches!(separator(rest), Some(Separator::Assignment(..))) {
+                    continue;
+                }
+                let prerequisites: Vec<String> = expand(&rest.replace('|', " "), &values, 0)
+                    .split_whitespace()
+                    .filter(|prerequisite| !prerequisite.contains('$'))
+                    .map(str::to_string)
+                    .collect();
+
+                if targets.iter().any(|target| target == ".PHONY") {
+                    file.phony.extend(prerequisites);
+                    continue;
+                }
+                // Other special targets such as `.SUFFIXES`, and old-fashioned suffix rules
+                if targets.iter().all(|target| target.starts_with('.') && !target.contains('/')) {
+                    continue;
+                }
+
+                let doc = doc.or_else(|| trailing.and_then(|comment| comment.strip_prefix("##")).map(|comment| comment.trim_start_matches('#').trim().to_string()));
+                let mut rule = Rule {
+                    targets,
+                    prerequisites,
+                    doc,
+                    variables: variable_refs(rest),
+                    double_colon,
+                    span,
+                    ..Rule::default()
+                };
+                if let Some(command) = command {
+                    rule.variables.extend(variable_refs(command));
+                    append_recipe(&mut rule, start as u32, &format!("\t{}", command));
+                }
+                current = Some(file.rules.len());
+                file.rules.push(rule);
+            }
+            None => {}
+        }
+    }
+
+    for rule in &mut file.rules {
+        rule.variables.sort();
+        rule.variables.dedup();
+    }
+    file
+}
+
+enum Separator {
+    /// Offset and operator of a variable assignment
+    Assignment(usize, &'static str),
+    /// Offset of the colon of a rule, and whether it is doubled
+    Rule(usize, bool),
+}
+
+/// The first `=` or `:` outside `$(...)`, which tells assignments from rules
+fn separator(content: &str) -> Option<Separator> {
+    let bytes = content.as_bytes();
+    let mut depth = 0usize;
+    for (i, &c) in bytes.iter().enumerate() {
+        match c {
+            b'(' | b'{' => depth += 1,
+            b')' | b'}' => depth = depth.saturating_sub(1),
+            b'=' if depth == 0 => {
+                let operator = ["::=", ":=", "?=", "+=", "!="].into_iter()
+                    .find(|operator| content[..=i].ends_with(operator))
+                    .unwrap_or("=");
+                return Some(Separator::Assignment(i + 1 - operator.len(), operator));
+            }
+            b':' if depth == 0 => {
+                let rest = &content[i + 1..];
+                if rest.starts_with('=') || rest.starts_with(":=") {
+                    continue;
+                }
+                // A drive letter such as `C:\`
+                if rest.starts_with('\\') {
+                    continue;
+                }
+                return Some(Separator::Rule(i, rest.starts_with(':')));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Text before a `#` comment that is not escaped, and the comment
+fn strip_comment(line: &str) -> (&str, Option<&str>) {
+    let bytes = line.as_bytes();
+    for (i, &c) in bytes.iter().enumerate() {
+        if c == b'#' && (i == 0 || bytes[i - 1] != b'\\') {
+            return (&line[..i], Some(&line[i..]));
+        }
+    }
+    (line, None)
+}
+
+/// Replace `$(NAME)` and `${NAME}` with the values defined so far
+fn expand(text: &str, values: &HashMap<String, String>, depth: usize) -> String {
+    if depth > 8 || !text.contains('$') {
+        return text.to_string();
+    }
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(position) = rest.find('$') {
+        expanded.push_str(&rest[..position]);
+        let after = &rest[position + 1..];
+        let close = match after.chars().next() {
+            Some('(') => Some(')'),
+            Some('{') => Some('}'),
+            _ => None,
+        };
+        let name = close.and_then(|close| after[1..].find(close).map(|end| &after[1..=end]));
+        match name.and_then(|name| values.get(name).map(|value| (name, value))) {
+            Some((name, value)) => {
+                expanded.push_str(&expand(value, values, depth + 1));
+                rest = &after[name.len() + 2..];
+            }
+            None => {
+                expanded.push('$');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Names of the variables `$(NAME)`, `${NAME}` and `$(NAME:a=b)` refer to
+fn variable_refs(text: &str) -> Vec<String> {
+    let bytes = text.as_bytes();
+    let mut names = Vec::new();
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        if bytes[i] != b'$' {
+            i += 1;
+            continue;
+        }
+        // `$$` escapes a dollar for the shell
+        if bytes[i + 1] == b'$' {
+            i += 2;
+            continue;
+        }
+        if matches!(bytes[i + 1], b'(' | b'{') {
+            let start = i + 2;
+            let end = (start..bytes.len())
+                .find(|&j| !(bytes[j].is_ascii_alphanumeric() || matches!(bytes[j], b'_' | b'-' | b'.')))
+                .unwrap_or(bytes.len());
+            if end > start && bytes.get(end).is_some_and(|&c| matches!(c, b')' | b'}' | b':')) {
+                names.push(text[start..end].to_string());
+            }
+        }
+        i += 1;
+    }
+    names
+}
+
+/// Add a recipe line at `line` of the makefile, padding with empty lines so
+/// that lines of the script match those of the makefile
+fn append_recipe(rule: &mut Rule, line: u32, text: &str) {
+    let offset = line.saturating_sub(rule.span.line) as usize;
+    let lines = rule.recipe.matches('\n').count();
+    for _ in lines..offset {
+        rule.recipe.push('\n');
+    }
+
+    // The tab and the `@`, `-` and `+` prefixes become spaces so that columns stay put
+    let text = text.strip_prefix('\t').map_or_else(|| text.to_string(), |rest| format!(" {}", rest));
+    let indent = text.len() - text.trim_start().len();
+    let prefixes = text[indent..].len() - text[indent..].trim_start_matches(['@', '-', '+']).len();
+    let text = format!("{}{}", " ".repeat(indent + prefixes), &text[indent + prefixes..]);
+    let text = text.replace("$(MAKE)", "make   ").replace("${MAKE}", "make   ").replace("$$", " $");
+    rule.recipe.push_str(&text);
+    rule.recipe.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_makefile() {
+        let file = parse(concat!(
+            "include common.mk\n",
+            "-include local.mk\n",
+            "BIN := bin\n",
+            "SOURCES = main.c \\\n",
+            "    util.c\n",
+            "\n",
+            ".PHONY: all clean\n",
+            "\n",
+            "# Build everything\n",
+            "all: $(BIN)/app docs | $(BIN)\n",
+            "\n",
+            "$(BIN)/app: $(SOURCES)\n",
+            "\t@mkdir -p $(BIN)\n",
+            "ifdef DEBUG\n",
+            "\t$(CC) -g -o $@ $^\n",
+            "endif\n",
+            "\t./scripts/sign.sh $@ && echo $$HOME\n",
+            "\n",
+            "docs: ## Render the docs\n",
+            "\t$(MAKE) -C docs html\n",
+            "release: VERSION = 1.0\n",
+            "clean:: ; rm -rf $(BIN)\n",
+        ));
+
+        assert_eq!(file.includes.iter().map(|include| (include.path.as_str(), include.optional)).collect::<Vec<_>>(), vec![
+            ("common.mk", false),
+            ("local.mk", true),
+        ]);
+        let variables: Vec<(&str, &str, &str)> = file.variables.iter()
+            .map(|variable| (variable.name.as_str(), variable.operator.as_str(), variable.value.as_str()))
+            .collect();
+        assert_eq!(variables, vec![("BIN", ":=", "bin"), ("SOURCES", "=", "main.c      util.c")]);
+        assert_eq!(file.phony, vec!["all", "clean"]);
+
+        let rules: Vec<(Vec<&str>, Vec<&str>)> = file.rules.iter()
+            .map(|rule| (
+                rule.targets.iter().map(String::as_str).collect(),
+                rule.prerequisites.iter().map(String::as_str).collect(),
+            ))
+            .collect();
+        assert_eq!(rules, vec![
+            (vec!["all"], vec!["bin/app", "docs", "bin"]),
+            (vec!["bin/app"], vec!["main.c", "util.c"]),
+            (vec!["docs"], vec![]),
+            (vec!["clean"], vec![]),
+        ]);
+
+        let all = &file.rules[0];
+        assert_eq!((all.doc.as_deref(), all.span.line, all.variables.clone()), (Some("Build everything"), 9, vec!["BIN".to_string()]));
+        let app = &file.rules[1];
+        assert_eq!((app.span.line, app.span.end_line), (11, 16));
+        assert_eq!(app.variables, vec!["BIN", "CC", "SOURCES"]);
+        assert_eq!(app.recipe, concat!(
+            "\n",
+            "  mkdir -p $(BIN)\n",
+            "\n",
+            " $(CC) -g -o $@ $^\n",
+            "\n",
+            " ./scripts/sign.sh $@ && echo  $HOME\n",
+        ));
+        assert_eq!(file.rules[2].doc.as_deref(), Some("Render the docs"));
+        assert_eq!(file.rules[2].recipe, "\n make    -C docs html\n");
+        assert!(file.rules[3].double_colon);
+        assert_eq!(file.rules[3].recipe, "  rm -rf $(BIN)\n");
+    }
+}
@@ -2,96 +2,318 @@
 
 pub mod watcher;
 pub mod parser;
+pub mod c;
+pub mod csharp;
+pub mod dockerfile;
+pub mod go;
+pub mod grammar;
+pub mod intent;
+pub mod java;
+pub mod make;
+pub mod pipeline;
+pub mod protobuf;
+pub mod python;
+pub mod rust;
+pub mod registry;
+pub mod ruby;
+pub mod scanner;
+pub mod shell;
+pub mod sql;
+pub mod typescript;
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashSet};
 use std::path::Path;
-use crate::index::{Parser, Symbol, Relationship};
+use tracing::warn;
+use crate::config::Config;
+use crate::index::{Parser, PendingRelationships, Relationship, Symbol, SymbolTable};
 use crate::index::db::IndexDatabase;
+use crate::query::engine::QueryEngine;
+use registry::ParserRegistry;
+
+/// Outcome of an incremental update
+#[derive(Debug, Default)]
+pub struct IndexUpdate {
+    /// Files skipped because their content hash matched the index
+    pub unchanged: usize,
+    /// Previously indexed files whose content changed
+    pub modified: usize,
+    /// Files indexed for the first time
+    pub added: usize,
+    /// Files purged from the index
+    pub removed: usize,
+    /// Symbols indexed from the changed files
+    pub symbols: usize,
+    /// Symbols dropped along with the removed files
+    pub removed_symbols: usize,
+    /// Files skipped because they could not be read or parsed
+    pub failed: usize,
+    /// Resolved relationships extracted from the changed files
+    pub relationships: usize,
+    /// Other files whose relationships were re-resolved
+    pub dependents: Vec<String>,
+}
+
+/// Hash recorded in `files.content_hash` to detect changed files
+pub fn content_hash(content: &str) -> String {
+    blake3::hash(content.as_bytes()).to_string()
+}
+
+/// A file read and parsed by a phase 1 worker
+struct ParsedFile<'a> {
+    path: String,
+    content_hash: String,
+    symbols: Vec<Symbol>,
+    /// Resolved in phase 2 from the phase 1 parse
+    relationships: PendingRelationships<'a>,
+}
 
 /// The main indexer that coordinates parsing and storage
 pub struct Indexer {
-    parsers: HashMap<String, Box<dyn Parser + Send + Sync>>,
+    registry: ParserRegistry,
     db: IndexDatabase,
+    config: Config,
 }
 
 impl Indexer {
     pub fn new(db_path: impl AsRef<Path>) -> anyhow::Result<Self> {
-        let mut parsers = HashMap::new();
+        let registry = ParserRegistry::builtin();
+        let db = IndexDatabase::new(db_path)?;
 
-        // Register parsers
-        parsers.insert("python".to_string(), Box::new(parser::PythonParser::new()) as Box<dyn Parser + Send + Sync>);
-        parsers.insert("rust".to_string(), Box::new(parser::RustParser::new()) as Box<dyn Parser + Send + Sync>);
-        parsers.insert("go".to_string(), Box::new(parser::GoParser::new()) as Box<dyn Parser + Send + Sync>);
-        parsers.insert("java".to_string(), Box::new(parser::JavaParser::new()) as Box<dyn Parser + Send + Sync>);
-        parsers.insert("intent".to_string(), Box::new(parser::IntentParser::new()) as Box<dyn Parser + Send + Sync>);
+        Ok(Self {
+            registry,
+            db,
+            config: Config::default(),
+        })
+    }
 
-        let db = IndexDatabase::new(db_path)?;
+    /// Use the project configuration for the worker pool, write batches,
+    /// queries and the parsers, including grammars loaded at runtime
+    pub fn with_config(mut self, config: &Config) -> anyhow::Result<Self> {
+        self.registry = ParserRegistry::from_config(config)?;
+        self.config = config.clone();
+        Ok(self)
+    }
 
-        Ok(Self { parsers, db })
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Query engine over this index, bounded by the configured depth
+    pub fn query_engine(&self) -> QueryEngine {
+        QueryEngine::new(self.db.clone()).with_max_depth(self.config.query.max_depth)
     }
 
     pub fn can_index_file(&self, file_path: &str) -> bool {
-        self.parsers.values().any(|p| p.can_parse(file_path))
+        self.registry.parser_for(file_path).is_some()
     }
 
     pub fn get_parser_for_file(&self, file_path: &str) -> Option<&(dyn Parser + Send + Sync)> {
-        self.parsers.values()
-            .find(|p| p.can_parse(file_path))
-            .map(|p| p.as_ref())
+        self.registry.parser_for(file_path)
     }
 
-    pub async fn index_file(&self, file_path: &str, content: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
-        let parser = self.get_parser_for_file(file_path)
-            .ok_or_else(|| anyhow::anyhow!("No parser available for file: {}", file_path))?;
+    pub fn registry(&self) -> &ParserRegistry {
+        &self.registry
+    }
+
+    /// Re-index a file if it changed and re-resolve the files that depend on it
+    pub fn update_file(&self, file_path: &str) -> anyhow::Result<IndexUpdate> {
+        self.update_files(&[file_path.to_string()], &[])
+    }
 
-        let (symbols, _) = parser.parse(content, file_path)?;
+    /// Remove a file and all of its symbols and relationships from the index
+    ///
+    /// Files that referenced the removed symbols are re-resolved so their
+    /// edges become unresolved instead of silently disappearing.
+    pub fn remove_file(&self, file_path: &str) -> anyhow::Result<IndexUpdate> {
+        self.update_files(&[], &[file_path.to_string()])
+    }
+
+    /// Reconcile the files found by a project walk against the index
+    ///
+    /// Indexed files missing from the walk are purged along with their symbols.
+    pub fn sync(&self, files: &[String]) -> anyhow::Result<IndexUpdate> {
+        let walked: HashSet<&str> = files.iter().map(String::as_str).collect();
+        let removed: Vec<String> = self.db.file_hashes()?
+            .into_keys()
+            .filter(|path| !walked.contains(path.as_str()))
+            .collect();
 
-        // Store symbols in database
-        for symbol in &symbols {
-            let db_symbol = symbol.into();
-            self.db.insert_symbol(&db_symbol)?;
+        self.update_files(files, &removed)
+    }
+
+    /// Re-index a batch of files, purge removed ones and re-resolve the files
+    /// that depend on either
+    ///
+    /// Files are read and parsed on a pool of `performance.threads` workers
+    /// while a single writer commits every `indexing.batch_size` rows. Files
+    /// whose content hash matches the index are skipped. Dependents are files
+    /// with edges into a touched file's previous symbols and files with
+    /// unresolved references matching one of its new symbol names. A file that
+    /// cannot be read or parsed is logged and skipped. Changed files are parsed
+    /// once, and their relationships are resolved in phase 2 from that parse.
+    ///
+    /// Parsing and SQLite block, so async callers run this on a blocking thread.
+    pub fn update_files(&self, files: &[String], removed: &[String]) -> anyhow::Result<IndexUpdate> {
+        let known = self.db.file_hashes()?;
+        let mut update = IndexUpdate { removed: removed.len(), ..Default::default() };
+        let mut dependents = BTreeSet::new();
+        let mut changed = Vec::new();
+        self.registry.clear_caches();
+
+        // Phase 1: purge removed files, then parse and store symbols of changed ones.
+        // Edges into the old symbols are dropped by the replace, so collect them first.
+        let mut writer = self.db.batch_writer(self.config.indexing.batch_size)?;
+        for file_path in removed {
+            let previous_ids = self.previous_ids(file_path)?;
+            update.removed_symbols += previous_ids.len();
+            dependents.extend(self.db.find_files_referencing(file_path, &previous_ids)?);
+            writer.remove_file(file_path)?;
         }
 
-        // Update file metadata
-        let content_hash = blake3::hash(content.as_bytes()).to_string();
-        let language = if file_path.ends_with(".py") {
-            "python"
-        } else if file_path.ends_with(".rs") {
-            "rust"
-        } else if file_path.ends_with(".go") {
-            "go"
-        } else if file_path.ends_with(".java") {
-            "java"
-        } else if file_path.ends_with(".intent") {
-            "intent"
-        } else {
-            "unknown"
-        };
-        self.db.update_file_indexed(file_path, language, content_hash, symbols.len() as i64)?;
+        pipeline::run(
+            files,
+            self.config.performance.threads,
+            |file_path| (file_path.clone(), self.parse_file(file_path, known.get(file_path))),
+            |(file_path, parsed)| {
+                let file = match parsed {
+                    Ok(Some(file)) => file,
+                    Ok(None) => {
+                        update.unchanged += 1;
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        warn!("Failed to index {}: {}", file_path, e);
+                        update.failed += 1;
+                        return Ok(());
+                    }
+                };
+
+                if known.contains_key(&file.path) {
+                    update.modified += 1;
+                    let previous_ids = self.previous_ids(&file.path)?;
+                    dependents.extend(self.db.find_files_referencing(&file.path, &previous_ids)?);
+                } else {
+                    update.added += 1;
+                }
+
+                let db_symbols: Vec<crate::index::db::Symbol> = file.symbols.iter().map(Into::into).collect();
+                let language = self.registry.language_for(&file.path).unwrap_or("unknown");
+                writer.replace_file(&file.path, language, &file.content_hash, &db_symbols)?;
+                update.symbols += file.symbols.len();
 
-        // Return symbols but no relationships yet - we'll extract them later with global context
-        Ok((symbols, Vec::new()))
+                // Nothing can be waiting on the new names in an empty index
+                if !known.is_empty() {
+                    let names: Vec<String> = file.symbols.into_iter().map(|s| s.name).collect();
+                    dependents.extend(self.db.find_files_with_unresolved(&file.path, &names)?);
+                }
+
+                changed.push((file.path, file.relationships));
+                Ok(())
+            },
+        )?;
+        writer.finish()?;
+
+        // Touched files are handled directly rather than as dependents
+        for file_path in changed.iter().map(|(path, _)| path).chain(removed) {
+            dependents.remove(file_path);
+        }
+        update.dependents = dependents.into_iter().collect();
+
+        if changed.is_empty() && update.dependents.is_empty() {
+            return Ok(update);
+        }
+
+        // Phase 2: resolve relationships of changed files and their dependents
+        // against the global symbol set
+        let all_symbols = self.load_symbols()?;
+        let global_symbol_map = SymbolTable::new(&all_symbols);
+
+        let targets: Vec<(&str, Option<&PendingRelationships>)> = changed.iter()
+            .map(|(path, relationships)| (path.as_str(), Some(relationships)))
+            .chain(update.dependents.iter().map(|path| (path.as_str(), None)))
+            .collect();
+
+        let mut writer = self.db.batch_writer(self.config.indexing.batch_size)?;
+        let mut relationships = 0;
+        pipeline::run(
+            &targets,
+            self.config.performance.threads,
+            |&(file_path, relationships)| {
+                // Dependents were not parsed in phase 1 and are read back from disk
+                let result = match relationships {
+                    Some(relationships) => relationships(&global_symbol_map),
+                    None => std::fs::read_to_string(file_path)
+                        .map_err(Into::into)
+                        .and_then(|content| self.resolve_file(file_path, &content, &global_symbol_map)),
+                };
+                (file_path, relationships.is_none(), result)
+            },
+            |(file_path, is_dependent, result)| {
+                match result {
+                    Ok(file_relationships) => {
+                        if !is_dependent {
+                            relationships += file_relationships.iter()
+                                .filter(|r| !r.is_unresolved())
+                                .count();
+                        }
+                        let db_relationships: Vec<crate::index::db::Relationship> = file_relationships.iter().map(Into::into).collect();
+                        writer.replace_file_relationships(file_path, &db_relationships)?;
+                    }
+                    Err(e) if is_dependent => warn!("Failed to re-resolve {}: {}", file_path, e),
+                    Err(e) => return Err(e),
+                }
+                Ok(())
+            },
+        )?;
+        writer.finish()?;
+        update.relationships = relationships;
+
+        Ok(update)
     }
 
-    pub async fn extract_relationships(&self, file_path: &str, content: &str, all_symbols: &[Symbol]) -> anyhow::Result<Vec<Relationship>> {
+    /// Read, hash and parse a file, or `None` if its hash matches `known_hash`
+    fn parse_file(&self, file_path: &str, known_hash: Option<&String>) -> anyhow::Result<Option<ParsedFile<'_>>> {
+        let content = std::fs::read_to_string(file_path)?;
+        let content_hash = content_hash(&content);
+        if known_hash == Some(&content_hash) {
+            return Ok(None);
+        }
+
         let parser = self.get_parser_for_file(file_path)
             .ok_or_else(|| anyhow::anyhow!("No parser available for file: {}", file_path))?;
+        let (symbols, relationships) = parser.parse_pending(content, file_path)?;
 
-        // Create a global symbol map
-        let global_symbol_map: std::collections::HashMap<&str, &Symbol> = all_symbols.iter()
-            .map(|s| (s.qualified_name.as_str(), s))
-            .collect();
+        Ok(Some(ParsedFile {
+            path: file_path.to_string(),
+            content_hash,
+            symbols,
+            relationships,
+        }))
+    }
 
-        // Extract relationships using the global context
-        let relationships = parser.extract_relationships_with_global_context(content, file_path, &global_symbol_map)?;
+    /// Parse a file that was not changed and extract its relationships using the global symbol map
+    fn resolve_file(
+        &self,
+        file_path: &str,
+        content: &str,
+        global_symbol_map: &SymbolTable,
+    ) -> anyhow::Result<Vec<Relationship>> {
+        let parser = self.get_parser_for_file(file_path)
+            .ok_or_else(|| anyhow::anyhow!("No parser available for file: {}", file_path))?;
 
-        // Store relationships in database
-        for relationship in &relationships {
-            let db_relationship = relationship.into();
-            self.db.insert_relationship(&db_relationship)?;
-        }
+        parser.extract_relationships_with_global_context(content, file_path, global_symbol_map)
+    }
+
+    fn previous_ids(&self, file_path: &str) -> anyhow::Result<Vec<String>> {
+        Ok(self.db.find_symbols_by_file(file_path)?
+            .into_iter()
+            .map(|s| s.id)
+            .collect())
+    }
 
-        Ok(relationships)
+    /// Load the global symbol set from the database
+    pub fn load_symbols(&self) -> anyhow::Result<Vec<Symbol>> {
+        Ok(self.db.all_symbols()?.iter().map(Into::into).collect())
     }
 
     pub fn get_stats(&self) -> anyhow::Result<crate::index::db::IndexStats> {
@@ -108,3 +330,1327 @@ impl Indexer {
 // - Language parsers
 // - Symbol extraction
 // - Incremental updates
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::db::RelationshipType;
+    use crate::query::cycles::CycleScope;
+    use tempfile::tempdir;
+
+    fn write(path: &Path, content: &str) -> String {
+        std::fs::write(path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_update_file_re_resolves_dependents() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+
+        let lib = write(&dir.path().join("lib.py"), "def helper():\n    pass\n");
+        let app = write(&dir.path().join("app.py"), "from lib import helper\n\ndef run():\n    helper()\n");
+
+        indexer.update_file(&lib).unwrap();
+        indexer.update_file(&app).unwrap();
+
+        let run_id = format!("{}:run", app);
+        let callees = |indexer: &Indexer| {
+            indexer.db().find_relationships_from(&run_id, Some(RelationshipType::Calls)).unwrap()
+        };
+        let unresolved = |indexer: &Indexer| -> Vec<String> {
+            let conn = indexer.db().get_conn().unwrap();
+            let mut stmt = conn.prepare("SELECT name FROM unresolved_references WHERE from_id = ?1").unwrap();
+            stmt.query_map([&run_id], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+        };
+        assert_eq!(callees(&indexer)[0].to_id, format!("{}:helper", lib));
+        assert!(unresolved(&indexer).is_empty());
+
+        // Renaming the target leaves the caller with an unresolved edge
+        write(Path::new(&lib), "def renamed():\n    pass\n");
+        let update = indexer.update_file(&lib).unwrap();
+        assert_eq!(update.dependents, vec![app.clone()]);
+        assert!(callees(&indexer).is_empty());
+        assert_eq!(unresolved(&indexer), vec!["helper"]);
+
+        // Restoring it resolves the dangling call again
+        write(Path::new(&lib), "def helper():\n    pass\n");
+        let update = indexer.update_file(&lib).unwrap();
+        assert_eq!(update.dependents, vec![app.clone()]);
+        assert_eq!(callees(&indexer)[0].to_id, format!("{}:helper", lib));
+        assert!(unresolved(&indexer).is_empty());
+
+        // Deleting the file makes the call unresolved once more
+        std::fs::remove_file(&lib).unwrap();
+        indexer.remove_file(&lib).unwrap();
+        assert!(callees(&indexer).is_empty());
+        assert_eq!(unresolved(&indexer), vec!["helper"]);
+    }
+
+    #[test]
+    fn test_sync_skips_unchanged_files() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::default();
+        config.performance.threads = 3;
+        config.indexing.batch_size = 1;
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap().with_config(&config).unwrap();
+
+        let files: Vec<String> = (0..8)
+            .map(|i| {
+                let next = (i + 1) % 8;
+                let source = format!("from m{} import f{}\n\ndef f{}():\n    f{}()\n", next, next, i, next);
+                write(&dir.path().join(format!("m{}.py", i)), &source)
+            })
+            .collect();
+
+        let update = indexer.sync(&files).unwrap();
+        assert_eq!((update.added, update.unchanged), (8, 0));
+        // A function and an import per file
+        assert_eq!(update.symbols, 16);
+        // A call and an import edge per file
+        assert_eq!(update.relationships, 16);
+
+        // Only the edited file is parsed again; the deleted one is purged
+        write(Path::new(&files[0]), "from m1 import f1\n\ndef f0():\n    pass\n");
+        std::fs::remove_file(&files[7]).unwrap();
+        let update = indexer.sync(&files[..7]).unwrap();
+        assert_eq!((update.unchanged, update.modified, update.added, update.removed), (6, 1, 0, 1));
+        assert_eq!(update.removed_symbols, 2);
+        assert_eq!(update.dependents, vec![files[6].clone()]);
+
+        let stats = indexer.get_stats().unwrap();
+        assert_eq!(stats.total_files, 7);
+        // m6 no longer reaches m7 and f0 no longer calls f1
+        assert_eq!(stats.total_relationships, 11);
+    }
+
+    #[test]
+    fn test_sync_skips_unreadable_files() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+
+        let good = write(&dir.path().join("good.py"), "def f():\n    pass\n");
+        let binary = dir.path().join("binary.py");
+        std::fs::write(&binary, [0xff, 0xfe, 0x00]).unwrap();
+        let missing = dir.path().join("missing.py").to_string_lossy().to_string();
+
+        let update = indexer.sync(&[binary.to_string_lossy().to_string(), good.clone(), missing]).unwrap();
+        assert_eq!((update.added, update.failed), (1, 2));
+        assert!(indexer.db().get_symbol(&format!("{}:f", good)).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_python_hierarchy_and_decorators() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+
+        let base = write(&dir.path().join("base.py"), "class Base:\n    pass\n");
+        let models = write(&dir.path().join("models.py"), concat!(
+            "from base import Base\n\n",
+            "class User(Base):\n",
+            "    @property\n",
+            "    def name(self) -> str:\n        return ''\n\n",
+            "    async def save(self, force: bool = False, *args) -> None:\n        pass\n\n",
+            "class Admin(User, Exception):\n    pass\n\n",
+            "@app.route('/users')\n",
+            "def users():\n    pass\n",
+        ));
+        indexer.sync(&[base.clone(), models.clone()]).unwrap();
+
+        let save = indexer.db().get_symbol(&format!("{}:User.save", models)).unwrap().unwrap();
+        assert_eq!(save.kind, crate::index::db::SymbolKind::Method);
+        assert_eq!(save.signature.as_deref(), Some("async def save(self, force: bool = False, *args) -> None"));
+        assert_eq!(save.type_.as_deref(), Some("(self, force: bool = False, *args) -> None"));
+
+        let engine = indexer.query_engine();
+        let supertypes: Vec<(String, usize)> = engine.supertypes("Admin", None).unwrap()
+            .into_iter()
+            .map(|r| (r.qualified_name, r.depth))
+            .collect();
+        assert_eq!(supertypes, vec![("User".to_string(), 1), ("Base".to_string(), 2)]);
+        assert_eq!(engine.subtypes("Base", None).unwrap().len(), 2);
+
+        let decorated = |query: &str, decorator: &str| -> Vec<String> {
+            let filters = crate::index::db::SearchFilters { decorator: Some(decorator.to_string()), ..Default::default() };
+            engine.search_symbols(query, &filters, 10).unwrap().into_iter().map(|r| r.qualified_name).collect()
+        };
+        assert_eq!(decorated("user", "property"), vec!["User.name"]);
+        assert_eq!(decorated("users", "app.route"), vec!["users"]);
+        assert!(decorated("users", "app").is_empty());
+    }
+
+    #[test]
+    fn test_rust_modules_traits_and_uses() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+        std::fs::create_dir_all(dir.path().join("src/api")).unwrap();
+
+        let lib = write(&dir.path().join("src/lib.rs"), "pub mod store;\npub mod api;\n\npub trait Named {\n    fn name(&self) -> String;\n\n    fn describe(&self) -> String {\n        self.name()\n    }\n}\n");
+        let store = write(&dir.path().join("src/store.rs"), concat!(
+            "use crate::Named;\n\n",
+            "pub struct Store;\n\n",
+            "impl Store {\n    pub fn open() -> Self {\n        Store\n    }\n\n    pub fn get(&self) {\n        self.check();\n    }\n\n    fn check(&self) {}\n}\n\n",
+            "impl Named for Store {\n    fn name(&self) -> String {\n        self.describe()\n    }\n}\n",
+        ));
+        let api = write(&dir.path().join("src/api/mod.rs"), concat!(
+            "use crate::store::Store as Db;\n\n",
+            "pub fn handle() {\n    let db = Db::open();\n    db.get();\n    helper();\n}\n\n",
+            "fn helper() {}\n",
+        ));
+        indexer.sync(&[lib.clone(), store.clone(), api.clone()]).unwrap();
+
+        let db = indexer.db();
+        let open = db.get_symbol(&format!("{}:crate::store::Store::open", store)).unwrap().unwrap();
+        assert_eq!(open.kind, crate::index::db::SymbolKind::Method);
+        assert!(db.get_symbol(&format!("{}:crate::api", lib)).unwrap().is_some());
+
+        let targets = |from: &str, type_: RelationshipType| -> Vec<String> {
+            db.find_relationships_from(from, Some(type_)).unwrap().into_iter().map(|r| r.to_id).collect()
+        };
+        assert_eq!(targets(&format!("{}:crate::api::handle", api), RelationshipType::Calls), vec![
+            format!("{}:crate::store::Store::open", store),
+            format!("{}:crate::api::helper", api),
+        ]);
+        assert_eq!(targets(&format!("{}:crate::store::Store::get", store), RelationshipType::Calls),
+            vec![format!("{}:crate::store::Store::check", store)]);
+        // A provided method of the implemented trait
+        assert_eq!(targets(&format!("{}:crate::store::Store::name", store), RelationshipType::Calls),
+            vec![format!("{}:crate::Named::describe", lib)]);
+        assert_eq!(targets(&format!("{}:crate::store::Store", store), RelationshipType::Implements),
+            vec![format!("{}:crate::Named", lib)]);
+    }
+
+    #[test]
+    fn test_go_packages_method_sets_and_interfaces() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+        std::fs::create_dir_all(dir.path().join("store")).unwrap();
+        std::fs::create_dir_all(dir.path().join("api")).unwrap();
+        write(&dir.path().join("go.mod"), "module example.com/app\n\ngo 1.22\n");
+
+        let store = write(&dir.path().join("store/store.go"), concat!(
+            "package store\n\n",
+            "type Base struct{}\n\n",
+            "func (b *Base) Close() error { return nil }\n\n",
+            "type Store struct {\n\t*Base\n\titems map[string]int\n}\n\n",
+            "func New() *Store { return &Store{} }\n\n",
+            "func (s *Store) Get(key string) (int, error) {\n\ts.check()\n\treturn len(s.items), nil\n}\n\n",
+            "func (s Store) check() {}\n",
+        ));
+        let api = write(&dir.path().join("api/api.go"), concat!(
+            "package api\n\n",
+            "import (\n\tst \"example.com/app/store\"\n)\n\n",
+            "type Getter interface {\n\tGet(key string) (int, error)\n}\n\n",
+            "type Closer interface {\n\tClose() error\n}\n\n",
+            "type GetCloser interface {\n\tGetter\n\tCloser\n}\n\n",
+            "type Handler struct {\n\tstore *st.Store\n}\n\n",
+            "func (h *Handler) Serve() {\n\th.store.Get(\"a\")\n\ts := st.New()\n\ts.Close()\n\thelper()\n}\n\n",
+            "func helper() {}\n",
+        ));
+        indexer.sync(&[store.clone(), api.clone()]).unwrap();
+
+        let db = indexer.db();
+        let id = |file: &str, name: &str| format!("{}:example.com/app/{}", file, name);
+        let targets = |from: &str, type_: RelationshipType| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(type_)).unwrap().into_iter().map(|r| r.to_id).collect();
+            targets.sort();
+            targets
+        };
+
+        let get = db.get_symbol(&id(&store, "store.Store.Get")).unwrap().unwrap();
+        assert_eq!(get.kind, crate::index::db::SymbolKind::Method);
+        assert_eq!(get.signature.as_deref(), Some("func (s *Store) Get(key string) (int, error)"));
+
+        // Calls through an import alias, a field, a local and a promoted method
+        let mut expected = vec![
+            id(&store, "store.New"),
+            id(&store, "store.Store.Get"),
+            id(&store, "store.Base.Close"),
+            id(&api, "api.helper"),
+        ];
+        expected.sort();
+        assert_eq!(targets(&id(&api, "api.Handler.Serve"), RelationshipType::Calls), expected);
+        assert_eq!(targets(&id(&store, "store.Store.Get"), RelationshipType::Calls), vec![id(&store, "store.Store.check")]);
+
+        // Only `*Store` has `Get` and the promoted `Close`
+        assert_eq!(targets(&id(&store, "store.Store"), RelationshipType::Implements), vec![
+            id(&api, "api.Closer"),
+            id(&api, "api.Getter"),
+        ]);
+        let implements = db.find_relationships_from(&id(&store, "store.Store"), Some(RelationshipType::Implements)).unwrap();
+        assert!(implements.iter().all(|r| r.metadata.as_deref().is_some_and(|m| m.contains("\"pointer\":true"))));
+        assert_eq!(targets(&id(&store, "store.Base"), RelationshipType::Implements), vec![id(&api, "api.Closer")]);
+        assert!(targets(&id(&api, "api.Handler"), RelationshipType::Implements).is_empty());
+        assert_eq!(targets(&id(&api, "api.GetCloser"), RelationshipType::Extends), vec![
+            id(&api, "api.Closer"),
+            id(&api, "api.Getter"),
+        ]);
+    }
+
+    #[test]
+    fn test_sync_sees_a_renamed_go_module() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+        write(&dir.path().join("go.mod"), "module example.com/app\n");
+        let main = write(&dir.path().join("main.go"), "package main\n\nfunc run() {}\n");
+        indexer.sync(std::slice::from_ref(&main)).unwrap();
+        assert!(indexer.db().get_symbol(&format!("{}:example.com/app.run", main)).unwrap().is_some());
+
+        write(&dir.path().join("go.mod"), "module example.com/shop\n");
+        write(Path::new(&main), "package main\n\nfunc run() {}\n\nfunc stop() {}\n");
+        indexer.sync(std::slice::from_ref(&main)).unwrap();
+        assert!(indexer.db().get_symbol(&format!("{}:example.com/shop.run", main)).unwrap().is_some());
+        assert!(indexer.db().get_symbol(&format!("{}:example.com/app.run", main)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_import_cycles_without_calls() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+        for package in ["py", "a", "b"] {
+            std::fs::create_dir_all(dir.path().join(package)).unwrap();
+        }
+        write(&dir.path().join("go.mod"), "module example.com/app\n\ngo 1.22\n");
+
+        let files = vec![
+            write(&dir.path().join("py/a.py"), "import b\n\ndef f():\n    pass\n"),
+            write(&dir.path().join("py/b.py"), "from a import f\n\ndef g():\n    pass\n"),
+            write(&dir.path().join("a/a.go"), "package a\n\nimport _ \"example.com/app/b\"\n\nfunc A() {}\n"),
+            write(&dir.path().join("b/b.go"), "package b\n\nimport _ \"example.com/app/a\"\n\nfunc B() {}\n"),
+        ];
+        indexer.sync(&files).unwrap();
+
+        let engine = indexer.query_engine();
+        let members = |scope| -> Vec<Vec<String>> {
+            engine.find_cycles(&[RelationshipType::DependsOn], scope).unwrap()
+                .into_iter()
+                .map(|cycle| cycle.members)
+                .collect()
+        };
+        let mut expected = vec![vec![files[2].clone(), files[3].clone()], vec![files[0].clone(), files[1].clone()]];
+        expected.sort();
+        let mut cycles = members(CycleScope::File);
+        cycles.sort();
+        assert_eq!(cycles, expected);
+        // Both Python modules live in one package
+        let package = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        assert_eq!(members(CycleScope::Package), vec![vec![package("a"), package("b")]]);
+
+        // Importing `f` does not keep it alive
+        let entry_points = crate::query::deadcode::EntryPoints::new(&Config::default().deadcode).unwrap();
+        let dead = engine.find_dead_code(&entry_points).unwrap();
+        assert!(dead.iter().any(|symbol| symbol.symbol_id == format!("{}:f", files[0])));
+    }
+
+    #[test]
+    fn test_java_packages_overloads_and_hierarchy() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+        std::fs::create_dir_all(dir.path().join("com/acme/model")).unwrap();
+        std::fs::create_dir_all(dir.path().join("com/acme/store")).unwrap();
+
+        let model = write(&dir.path().join("com/acme/model/Model.java"), concat!(
+            "package com.acme.model;\n\n",
+            "public interface Named {\n    String name();\n}\n\n",
+            "abstract class Entity implements Named {\n    public String name() { return \"\"; }\n}\n\n",
+            "enum Color { RED, GREEN }\n\n",
+            "record Point(int x, int y) {}\n",
+        ));
+        let store = write(&dir.path().join("com/acme/store/Store.java"), concat!(
+            "package com.acme.store;\n\n",
+            "import com.acme.model.*;\n",
+            "import java.util.List;\n\n",
+            "@Deprecated\n",
+            "public class Store extends Base implements Named {\n",
+            "    private Cache cache;\n\n",
+            "    public Store() { super(); }\n\n",
+            "    public String name() { return \"store\"; }\n\n",
+            "    public Item get(String key) { return cache.lookup(key); }\n\n",
+            "    public Item get(String key, int version) { return null; }\n\n",
+            "    public void run(List<String> keys) {\n",
+            "        Item item = get(\"a\", 1);\n",
+            "        item.touch();\n",
+            "        this.get(\"b\").touch();\n",
+            "        Point p = new Point(1, 2);\n",
+            "        p.x();\n",
+            "        close();\n",
+            "    }\n",
+            "}\n\n",
+            "class Base {\n    Base() {}\n    void close() {}\n}\n\n",
+            "class Cache {\n    Item lookup(String key) { return null; }\n    Item lookup(int index) { return null; }\n}\n\n",
+            "class Item {\n    void touch() {}\n}\n",
+        ));
+        indexer.sync(&[model.clone(), store.clone()]).unwrap();
+
+        let db = indexer.db();
+        let id = |file: &str, name: &str| format!("{}:com.acme.{}", file, name);
+        let targets = |from: &str, type_: RelationshipType| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(type_)).unwrap().into_iter().map(|r| r.to_id).collect();
+            targets.sort();
+            targets
+        };
+
+        // Overloads get distinct ids
+        let get = db.get_symbol(&id(&store, "store.Store.get(String, int)")).unwrap().unwrap();
+        assert_eq!(get.signature.as_deref(), Some("Item get(String key, int version)"));
+        assert!(db.get_symbol(&id(&store, "store.Store.get(String)")).unwrap().is_some());
+        assert!(db.get_symbol(&id(&model, "model.Color.RED")).unwrap().is_some());
+        assert!(db.get_symbol(&id(&model, "model.Point.x()")).unwrap().is_some());
+
+        // Calls by receiver type and arity, through locals, fields, `this` and the superclass
+        let mut expected = vec![
+            id(&store, "store.Store.get(String, int)"),
+            id(&store, "store.Store.get(String)"),
+            id(&store, "store.Item.touch()"),
+            id(&store, "store.Item.touch()"),
+            id(&store, "store.Base.close()"),
+            id(&model, "model.Point.x()"),
+        ];
+        expected.sort();
+        assert_eq!(targets(&id(&store, "store.Store.run(List)"), RelationshipType::Calls), expected);
+        assert_eq!(targets(&id(&store, "store.Store.get(String)"), RelationshipType::Calls), vec![id(&store, "store.Cache.lookup(String)")]);
+        assert_eq!(targets(&id(&store, "store.Store.Store()"), RelationshipType::Calls), vec![id(&store, "store.Base.Base()")]);
+        assert_eq!(targets(&id(&store, "store.Store.run(List)"), RelationshipType::References), vec![id(&model, "model.Point")]);
+
+        // Supertypes resolve through the on-demand import
+        assert_eq!(targets(&id(&store, "store.Store"), RelationshipType::Extends), vec![id(&store, "store.Base")]);
+        assert_eq!(targets(&id(&store, "store.Store"), RelationshipType::Implements), vec![id(&model, "model.Named")]);
+        assert_eq!(targets(&id(&model, "model.Entity"), RelationshipType::Implements), vec![id(&model, "model.Named")]);
+    }
+
+    #[test]
+    fn test_intent_contexts_clauses_and_calls() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+
+        let session = write(&dir.path().join("session.intent"), concat!(
+            "context UserSession persist \"file://auto\" {\n",
+            "  user_id: String\n\n",
+            "  fn login(user: String) -> Bool {\n    user_id = user\n    return true\n  }\n\n",
+            "  fn query(sql: String) {}\n\n",
+            "  on start {\n    println(\"started {user_id}\")\n    login(\"guest\")\n  }\n",
+            "}\n",
+        ));
+        let app = write(&dir.path().join("app.intent"), concat!(
+            "context Database {\n",
+            "  fn query(sql: String) -> List<String> {\n    return []\n  }\n",
+            "}\n\n",
+            "context App extends UserSession depends [Database, Cache] {\n",
+            "  db: Database\n\n",
+            "  fn run() {\n    login(\"admin\")\n    db.query(\"select 1\")\n    missing()\n  }\n",
+            "}\n",
+        ));
+        indexer.sync(&[session.clone(), app.clone()]).unwrap();
+
+        let db = indexer.db();
+        let targets = |from: &str, type_: RelationshipType| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(type_)).unwrap().into_iter().map(|r| r.to_id).collect();
+            targets.sort();
+            targets
+        };
+
+        // Members are nested under their context
+        let query = db.get_symbol(&format!("{}:Database.query", app)).unwrap().unwrap();
+        assert_eq!(query.signature.as_deref(), Some("fn query(sql: String) -> List<String>"));
+        assert!(db.get_symbol(&format!("{}:UserSession.query", session)).unwrap().is_some());
+        let context = db.get_symbol(&format!("{}:UserSession", session)).unwrap().unwrap();
+        assert!(context.metadata.as_deref().is_some_and(|m| m.contains("\"persist\":\"file://auto\"")));
+
+        let unresolved = |from: &str| -> Vec<String> {
+            let conn = db.get_conn().unwrap();
+            let mut stmt = conn.prepare("SELECT name FROM unresolved_references WHERE from_id = ?1 ORDER BY name").unwrap();
+            stmt.query_map([from], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+        };
+
+        let app_id = format!("{}:App", app);
+        assert_eq!(targets(&app_id, RelationshipType::Extends), vec![format!("{}:UserSession", session)]);
+        assert_eq!(targets(&app_id, RelationshipType::DependsOn), vec![format!("{}:Database", app)]);
+        assert_eq!(unresolved(&app_id), vec!["Cache"]);
+
+        // Inherited functions and calls through a field's context type
+        assert_eq!(targets(&format!("{}:App.run", app), RelationshipType::Calls), vec![
+            format!("{}:Database.query", app),
+            format!("{}:UserSession.login", session),
+        ]);
+        assert_eq!(unresolved(&format!("{}:App.run", app)), vec!["missing"]);
+        assert_eq!(targets(&format!("{}:UserSession.on:start", session), RelationshipType::Calls), vec![format!("{}:UserSession.login", session)]);
+    }
+
+    #[test]
+    fn test_typescript_modules_and_heritage() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+        std::fs::create_dir_all(dir.path().join("src/models")).unwrap();
+        std::fs::create_dir_all(dir.path().join("src/app")).unwrap();
+        write(&dir.path().join("tsconfig.json"), "{\n  // Aliases\n  \"compilerOptions\": { \"baseUrl\": \".\", \"paths\": { \"@models/*\": [\"src/models/*\"] } }\n}\n");
+
+        let base = write(&dir.path().join("src/models/base.ts"), concat!(
+            "export interface Entity {\n  id: string;\n}\n\n",
+            "export interface Named extends Entity {\n  name(): string;\n}\n\n",
+            "export abstract class Model implements Entity {\n  id = \"\";\n  save(): void {}\n}\n",
+        ));
+        let user = write(&dir.path().join("src/models/user.ts"), concat!(
+            "import { Model, Named } from './base';\n\n",
+            "export enum Role { Admin, Guest = \"guest\" }\n\n",
+            "export type UserId = string;\n\n",
+            "export class User extends Model implements Named {\n  constructor(private role: Role) { super(); }\n  name(): string { return \"\"; }\n}\n",
+        ));
+        let service = write(&dir.path().join("src/app/service.ts"), concat!(
+            "import { User } from '@models/user';\n",
+            "import * as base from '../models/base.js';\n\n",
+            "export const format = (user: User): string => user.name();\n\n",
+            "export default class Service {\n",
+            "  private current: User = new User(0);\n\n",
+            "  run(model: base.Model) {\n",
+            "    this.current.save();\n",
+            "    model.save();\n",
+            "    format(this.current);\n",
+            "    missing();\n",
+            "  }\n",
+            "}\n",
+        ));
+        let main = write(&dir.path().join("src/app/main.js"), concat!(
+            "import Service from './service';\n\n",
+            "export function main() {\n  const service = new Service();\n  service.run();\n}\n",
+        ));
+        indexer.sync(&[base.clone(), user.clone(), service.clone(), main.clone()]).unwrap();
+
+        let db = indexer.db();
+        let targets = |from: &str, type_: RelationshipType| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(type_)).unwrap().into_iter().map(|r| r.to_id).collect();
+            targets.sort();
+            targets
+        };
+
+        let format = db.get_symbol(&format!("{}:format", service)).unwrap().unwrap();
+        assert_eq!(format.kind, crate::index::db::SymbolKind::Function);
+        assert_eq!(format.signature.as_deref(), Some("format(user: User): string"));
+        assert!(db.get_symbol(&format!("{}:Role.Guest", user)).unwrap().is_some());
+        assert!(db.get_symbol(&format!("{}:UserId", user)).unwrap().is_some());
+        assert_eq!(db.get_symbol(&format!("{}:main", main)).unwrap().unwrap().language, "javascript");
+
+        // Heritage resolved through named imports
+        let user_class = format!("{}:User", user);
+        assert_eq!(targets(&user_class, RelationshipType::Extends), vec![format!("{}:Model", base)]);
+        assert_eq!(targets(&user_class, RelationshipType::Implements), vec![format!("{}:Named", base)]);
+        assert_eq!(targets(&format!("{}:Named", base), RelationshipType::Extends), vec![format!("{}:Entity", base)]);
+        assert_eq!(targets(&format!("{}:User.constructor", user), RelationshipType::Calls), vec![format!("{}:Model", base)]);
+
+        // Calls through tsconfig paths, a namespace import, typed fields and parameters
+        let run = format!("{}:Service.run", service);
+        let mut expected = vec![
+            format!("{}:Model.save", base),
+            format!("{}:Model.save", base),
+            format!("{}:format", service),
+        ];
+        expected.sort();
+        assert_eq!(targets(&run, RelationshipType::Calls), expected);
+        let conn = db.get_conn().unwrap();
+        let unresolved: String = conn.query_row("SELECT name FROM unresolved_references WHERE from_id = ?1", [&run], |row| row.get(0)).unwrap();
+        assert_eq!(unresolved, "missing");
+        assert_eq!(targets(&format!("{}:format", service), RelationshipType::Calls), vec![format!("{}:User.name", user)]);
+
+        // A default import from JavaScript, and `new` of a class without a constructor
+        assert_eq!(targets(&format!("{}:main", main), RelationshipType::Calls), vec![format!("{}:Service.run", service)]);
+        assert_eq!(targets(&format!("{}:main", main), RelationshipType::References), vec![format!("{}:Service", service)]);
+    }
+
+    #[test]
+    fn test_c_and_cpp_includes_and_definitions() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::default();
+        config.c.include_paths = vec!["third_party".to_string()];
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap().with_config(&config).unwrap();
+        for directory in ["include/geo", "src", "build", "third_party"] {
+            std::fs::create_dir_all(dir.path().join(directory)).unwrap();
+        }
+        write(&dir.path().join("build/compile_commands.json"), &serde_json::json!([{
+            "directory": dir.path().join("build"),
+            "file": "../src/main.cpp",
+            "arguments": ["c++", "-I../include", "-DUSE_FAST", "-c", "../src/main.cpp"]
+        }]).to_string());
+
+        let shape_hpp = write(&dir.path().join("include/geo/shape.hpp"), concat!(
+            "#pragma once\n",
+            "namespace geo {\n",
+            "class Base {\npublic:\n    virtual void describe();\n};\n\n",
+            "class Shape : public Base {\npublic:\n    Shape(int sides);\n    double area() const;\n    static Shape* make();\nprivate:\n    int sides;\n};\n",
+            "}\n",
+        ));
+        let shape_cpp = write(&dir.path().join("src/shape.cpp"), concat!(
+            "#include \"geo/shape.hpp\"\n\n",
+            "namespace geo {\n",
+            "Shape::Shape(int sides) : sides(sides) {}\n",
+            "double Shape::area() const { return sides; }\n",
+            "Shape* Shape::make() { return new Shape(3); }\n",
+            "}\n",
+        ));
+        let util_h = write(&dir.path().join("src/util.h"), concat!(
+            "#ifndef UTIL_H\n#define UTIL_H\n",
+            "#ifdef USE_FAST\n#include \"fast.h\"\n#else\n#include \"slow.h\"\n#endif\n\n",
+            "typedef struct { int x; int y; } Point;\n",
+            "int add(int a, int b);\n",
+            "#endif\n",
+        ));
+        let fast_h = write(&dir.path().join("src/fast.h"), "#define FAST 1\n");
+        let slow_h = write(&dir.path().join("src/slow.h"), "#define SLOW 1\n");
+        let util_c = write(&dir.path().join("src/util.c"), concat!(
+            "#include \"util.h\"\n\n",
+            "static int twice(int a) { return a * 2; }\n",
+            "int add(int a, int b) { return twice(a) + b; }\n",
+        ));
+        let lib_h = write(&dir.path().join("third_party/lib.h"), "void lib_init(void);\n");
+        let main_cpp = write(&dir.path().join("src/main.cpp"), concat!(
+            "#include \"util.h\"\n",
+            "#include <geo/shape.hpp>\n",
+            "#include <lib.h>\n",
+            "#include <vector>\n\n",
+            "int main() {\n",
+            "    geo::Shape* shape = geo::Shape::make();\n",
+            "    shape->area();\n",
+            "    shape->describe();\n",
+            "    add(1, 2);\n",
+            "    missing();\n",
+            "    return 0;\n",
+            "}\n",
+        ));
+        let files = [&shape_hpp, &shape_cpp, &util_h, &fast_h, &slow_h, &util_c, &lib_h, &main_cpp];
+        indexer.sync(&files.map(|file| file.clone())).unwrap();
+
+        let db = indexer.db();
+        let targets = |from: &str, type_: RelationshipType| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(type_)).unwrap().into_iter().map(|r| r.to_id).collect();
+            targets.sort();
+            targets
+        };
+
+        let area = db.get_symbol(&format!("{}:geo::Shape::area", shape_hpp)).unwrap().unwrap();
+        assert_eq!(area.kind, crate::index::db::SymbolKind::Method);
+        let metadata: serde_json::Value = serde_json::from_str(area.metadata.as_deref().unwrap()).unwrap();
+        assert_eq!(metadata["declaration"], true);
+        assert_eq!(db.get_symbol(&format!("{}:Point", util_h)).unwrap().unwrap().kind, crate::index::db::SymbolKind::Class);
+        assert_eq!(db.get_symbol(&format!("{}:add", util_h)).unwrap().unwrap().language, "c");
+        assert_eq!(db.get_symbol(&format!("{}:main", main_cpp)).unwrap().unwrap().language, "cpp");
+
+        // Includes through compile_commands.json, its defines and the configured include path
+        assert_eq!(targets(&format!("{}:main.cpp", main_cpp), RelationshipType::DependsOn), vec![
+            format!("{}:shape.hpp", shape_hpp),
+            format!("{}:util.h", util_h),
+            format!("{}:lib.h", lib_h),
+        ]);
+        assert_eq!(targets(&format!("{}:util.h", util_h), RelationshipType::DependsOn), vec![format!("{}:fast.h", fast_h)]);
+
+        // Definitions linked to the declarations in headers
+        assert_eq!(targets(&format!("{}:geo::Shape::area", shape_cpp), RelationshipType::Defines), vec![area.id.clone()]);
+        assert_eq!(targets(&format!("{}:add", util_c), RelationshipType::Defines), vec![format!("{}:add", util_h)]);
+        assert!(targets(&format!("{}:twice", util_c), RelationshipType::Defines).is_empty());
+        assert_eq!(targets(&format!("{}:geo::Shape", shape_hpp), RelationshipType::Extends), vec![format!("{}:geo::Base", shape_hpp)]);
+
+        // Calls prefer definitions, and follow a pointer's type into base classes
+        let mut expected = vec![
+            format!("{}:add", util_c),
+            format!("{}:geo::Base::describe", shape_hpp),
+            format!("{}:geo::Shape::area", shape_cpp),
+            format!("{}:geo::Shape::make", shape_cpp),
+        ];
+        expected.sort();
+        assert_eq!(targets(&format!("{}:main", main_cpp), RelationshipType::Calls), expected);
+        assert_eq!(targets(&format!("{}:geo::Shape::make", shape_cpp), RelationshipType::Calls), vec![format!("{}:geo::Shape::Shape", shape_cpp)]);
+        assert_eq!(targets(&format!("{}:add", util_c), RelationshipType::Calls), vec![format!("{}:twice", util_c)]);
+        let conn = db.get_conn().unwrap();
+        let unresolved: String = conn.query_row("SELECT name FROM unresolved_references WHERE from_id = ?1", [format!("{}:main", main_cpp)], |row| row.get(0)).unwrap();
+        assert_eq!(unresolved, "missing");
+    }
+
+    #[test]
+    fn test_csharp_namespaces_partial_classes_and_usings() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+
+        let models = write(&dir.path().join("Models.cs"), concat!(
+            "namespace Acme.Models\n{\n",
+            "    public interface IEntity { int Id { get; } }\n\n",
+            "    public abstract class Entity : IEntity\n    {\n",
+            "        public int Id { get; set; }\n",
+            "        public void Touch() {}\n",
+            "    }\n\n",
+            "    public record Item(string Name, decimal Price) : IEntity\n    {\n",
+            "        public int Id => 0;\n",
+            "        public void Touch() {}\n",
+            "    }\n",
+            "}\n",
+        ));
+        let service = write(&dir.path().join("OrderService.cs"), concat!(
+            "using System;\n",
+            "using Acme.Models;\n",
+            "using Repo = Acme.Data.Repository;\n\n",
+            "namespace Acme.Orders;\n\n",
+            "public partial class OrderService : Entity, IDisposable\n{\n",
+            "    private readonly Repo _repo = new Repo();\n",
+            "    public event EventHandler Changed;\n\n",
+            "    public OrderService() : base() {}\n\n",
+            "    public Item Find(int id) => _repo.Load(id);\n",
+            "    public Item Find(string name, int limit = 1) => null;\n\n",
+            "    public void Run()\n    {\n",
+            "        var item = Find(\"a\");\n",
+            "        item.Touch();\n",
+            "        this.Find(1);\n",
+            "        Audit();\n",
+            "        Console.WriteLine(item.Name);\n",
+            "    }\n",
+            "}\n",
+        ));
+        let audit = write(&dir.path().join("OrderService.Audit.cs"), concat!(
+            "namespace Acme.Orders\n{\n",
+            "    public partial class OrderService : IAuditable\n    {\n",
+            "        internal void Audit() { Touch(); }\n",
+            "    }\n\n",
+            "    public interface IAuditable : Acme.Models.IEntity {}\n",
+            "}\n",
+        ));
+        let data = write(&dir.path().join("Repository.cs"), concat!(
+            "namespace Acme.Data\n{\n",
+            "    public class Repository\n    {\n",
+            "        public Acme.Models.Item Load(int id) => null;\n",
+            "    }\n",
+            "}\n",
+        ));
+        indexer.sync(&[models.clone(), service.clone(), audit.clone(), data.clone()]).unwrap();
+
+        let db = indexer.db();
+        let id = |file: &str, name: &str| format!("{}:Acme.{}", file, name);
+        let targets = |from: &str, type_: RelationshipType| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(type_)).unwrap().into_iter().map(|r| r.to_id).collect();
+            targets.sort();
+            targets
+        };
+
+        // Namespace.Type.Member, with parameter types only in the ids of overloads
+        let find = db.get_symbol(&id(&service, "Orders.OrderService.Find(string, int)")).unwrap().unwrap();
+        assert_eq!(find.qualified_name, "Acme.Orders.OrderService.Find");
+        assert_eq!(find.signature.as_deref(), Some("Item Find(string name, int limit = 1)"));
+        assert!(db.get_symbol(&id(&service, "Orders.OrderService.Find(int)")).unwrap().is_some());
+        assert!(db.get_symbol(&id(&service, "Orders.OrderService.Changed")).unwrap().is_some());
+        assert!(db.get_symbol(&id(&models, "Models.Item.Name")).unwrap().is_some());
+        assert_eq!(db.get_symbol(&id(&models, "Models")).unwrap().unwrap().kind, crate::index::db::SymbolKind::Module);
+        assert_eq!(db.get_symbol(&id(&audit, "Orders.OrderService.Audit")).unwrap().unwrap().visibility, crate::index::db::Visibility::Internal);
+
+        // Calls by receiver type and arity, across the parts of the partial class
+        let run = id(&service, "Orders.OrderService.Run");
+        let mut expected = vec![
+            id(&service, "Orders.OrderService.Find(string, int)"),
+            id(&service, "Orders.OrderService.Find(int)"),
+            id(&models, "Models.Item.Touch"),
+            id(&audit, "Orders.OrderService.Audit"),
+        ];
+        expected.sort();
+        assert_eq!(targets(&run, RelationshipType::Calls), expected);
+        assert_eq!(targets(&id(&audit, "Orders.OrderService.Audit"), RelationshipType::Calls), vec![id(&models, "Models.Entity.Touch")]);
+        // Through the using alias
+        assert_eq!(targets(&id(&service, "Orders.OrderService.Find(int)"), RelationshipType::Calls), vec![id(&data, "Data.Repository.Load")]);
+        assert_eq!(targets(&id(&service, "Orders.OrderService.OrderService"), RelationshipType::References), vec![id(&models, "Models.Entity")]);
+        let conn = db.get_conn().unwrap();
+        let unresolved: String = conn.query_row("SELECT name FROM unresolved_references WHERE from_id = ?1", [&run], |row| row.get(0)).unwrap();
+        assert_eq!(unresolved, "WriteLine");
+
+        // Base lists split into the base class and interfaces
+        assert_eq!(targets(&id(&service, "Orders.OrderService"), RelationshipType::Extends), vec![id(&models, "Models.Entity")]);
+        assert!(targets(&id(&service, "Orders.OrderService"), RelationshipType::Implements).is_empty());
+        assert_eq!(targets(&id(&audit, "Orders.OrderService"), RelationshipType::Implements), vec![id(&audit, "Orders.IAuditable")]);
+        assert_eq!(targets(&id(&audit, "Orders.IAuditable"), RelationshipType::Extends), vec![id(&models, "Models.IEntity")]);
+        assert_eq!(targets(&id(&models, "Models.Item"), RelationshipType::Implements), vec![id(&models, "Models.IEntity")]);
+        assert_eq!(targets(&id(&models, "Models.Entity"), RelationshipType::Implements), vec![id(&models, "Models.IEntity")]);
+    }
+
+    #[test]
+    fn test_ruby_mixins_and_ancestor_lookup() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+
+        let concern = write(&dir.path().join("auditable.rb"), concat!(
+            "module Shop\n",
+            "  module Auditable\n",
+            "    def audit(message = nil)\n      log\n    end\n\n",
+            "    def log; end\n",
+            "  end\n\n",
+            "  module Finders\n",
+            "    def find(id) = new\n",
+            "  end\n",
+            "end\n",
+        ));
+        let order = write(&dir.path().join("order.rb"), concat!(
+            "module Shop\n",
+            "  class Record\n",
+            "    def save; end\n",
+            "  end\n\n",
+            "  class Order < Record\n",
+            "    include Auditable\n",
+            "    extend Finders\n",
+            "    attr_accessor :total\n\n",
+            "    def initialize(total)\n      @total = total\n      @line = LineItem.new\n    end\n\n",
+            "    def save\n",
+            "      audit\n",
+            "      self.total = 1\n",
+            "      @line.price\n",
+            "      super\n",
+            "    end\n\n",
+            "    def self.create\n      order = Order.find(1)\n      new(2).save\n    end\n\n",
+            "    private\n\n",
+            "    def log; end\n",
+            "  end\n",
+            "end\n",
+        ));
+        let invoice = write(&dir.path().join("invoice.rb"), concat!(
+            "class Shop::Invoice < Shop::Record\n",
+            "  include Shop::Auditable\n",
+            "  def close\n    audit\n    log\n    Shop::LineItem.new.price\n  end\n",
+            "end\n\n",
+            "module Shop\n  class LineItem\n    def price; end\n  end\nend\n\n",
+            "def checkout\n  order = Shop::Order.new(3)\n  order.audit\n  order.total\n  missing\nend\n",
+        ));
+        indexer.sync(&[concern.clone(), order.clone(), invoice.clone()]).unwrap();
+
+        let db = indexer.db();
+        let id = |file: &str, name: &str| format!("{}:{}", file, name);
+        let targets = |from: &str, type_: RelationshipType| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(type_)).unwrap().into_iter().map(|r| r.to_id).collect();
+            targets.sort();
+            targets
+        };
+
+        // Instance methods use `#`, singleton methods `.`
+        let create = db.get_symbol(&id(&order, "Shop::Order.create")).unwrap().unwrap();
+        assert_eq!(create.signature.as_deref(), Some("def self.create"));
+        let total = db.get_symbol(&id(&order, "Shop::Order#total")).unwrap().unwrap();
+        assert_eq!(total.kind, crate::index::db::SymbolKind::Field);
+        assert_eq!(db.get_symbol(&id(&order, "Shop::Order#log")).unwrap().unwrap().visibility, crate::index::db::Visibility::Private);
+        assert_eq!(db.get_symbol(&id(&invoice, "checkout")).unwrap().unwrap().kind, crate::index::db::SymbolKind::Function);
+
+        // Mixins and superclasses
+        assert_eq!(targets(&id(&order, "Shop::Order"), RelationshipType::Extends), vec![id(&order, "Shop::Record")]);
+        assert_eq!(targets(&id(&order, "Shop::Order"), RelationshipType::Implements), vec![
+            id(&concern, "Shop::Auditable"),
+            id(&concern, "Shop::Finders"),
+        ]);
+        assert_eq!(targets(&id(&invoice, "Shop::Invoice"), RelationshipType::Implements), vec![id(&concern, "Shop::Auditable")]);
+
+        // Calls walk the ancestor chain: the class before its mixins, mixins before the superclass
+        let mut expected = vec![
+            id(&concern, "Shop::Auditable#audit"),
+            id(&order, "Shop::Order#total"),
+            id(&invoice, "Shop::LineItem#price"),
+            id(&order, "Shop::Record#save"),
+        ];
+        expected.sort();
+        assert_eq!(targets(&id(&order, "Shop::Order#save"), RelationshipType::Calls), expected);
+        let mut expected = vec![
+            id(&concern, "Shop::Auditable#audit"),
+            id(&concern, "Shop::Auditable#log"),
+            id(&invoice, "Shop::LineItem#price"),
+        ];
+        expected.sort();
+        assert_eq!(targets(&id(&invoice, "Shop::Invoice#close"), RelationshipType::Calls), expected);
+        let mut expected = vec![
+            id(&concern, "Shop::Finders#find"),
+            id(&order, "Shop::Order#initialize"),
+            id(&order, "Shop::Order#save"),
+        ];
+        expected.sort();
+        assert_eq!(targets(&id(&order, "Shop::Order.create"), RelationshipType::Calls), expected);
+
+        // Callers of a concern's method include every including class
+        let mut callers: Vec<String> = db.find_relationships_to(&id(&concern, "Shop::Auditable#audit"), Some(RelationshipType::Calls))
+            .unwrap()
+            .into_iter()
+            .map(|r| r.from_id)
+            .collect();
+        callers.sort();
+        let mut expected = vec![id(&order, "Shop::Order#save"), id(&invoice, "Shop::Invoice#close"), id(&invoice, "checkout")];
+        expected.sort();
+        assert_eq!(callers, expected);
+
+        let conn = db.get_conn().unwrap();
+        let unresolved: String = conn.query_row("SELECT name FROM unresolved_references WHERE from_id = ?1", [id(&invoice, "checkout")], |row| row.get(0)).unwrap();
+        assert_eq!(unresolved, "missing");
+    }
+
+    #[test]
+    fn test_kotlin_and_scala_join_the_jvm_graph() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+
+        let data = write(&dir.path().join("Store.java"), concat!(
+            "package com.shop.data;\n\n",
+            "public class Store {\n",
+            "    public Object get(long id) { return null; }\n",
+            "}\n",
+        ));
+        let orders = write(&dir.path().join("Orders.kt"), concat!(
+            "package com.shop.orders\n\n",
+            "import com.shop.data.Store\n\n",
+            "open class Entity\n\n",
+            "interface Auditable {\n",
+            "    fun audit()\n",
+            "}\n\n",
+            "class Order(val id: Long) : Entity(), Auditable {\n",
+            "    override fun audit() {}\n\n",
+            "    companion object {\n",
+            "        fun create(id: Long): Order = Order(id)\n",
+            "    }\n",
+            "}\n\n",
+            "object Registry {\n",
+            "    fun register(order: Order) = order.audit()\n",
+            "}\n\n",
+            "class OrderRepository(private val store: Store) {\n",
+            "    fun find(id: Long): Order {\n",
+            "        store.get(id)\n",
+            "        return missing(id)\n",
+            "    }\n",
+            "}\n\n",
+            "fun Order.describe(): String = \"Order\"\n",
+        ));
+        let billing = write(&dir.path().join("Billing.scala"), concat!(
+            "package com.shop.billing\n\n",
+            "import com.shop.orders.{Auditable, Order}\n\n",
+            "trait Ledger\n\n",
+            "class Invoice(val order: Order) extends Ledger with Auditable {\n",
+            "  def audit(): Unit = ()\n",
+            "}\n\n",
+            "object Invoice {\n",
+            "  def issue(id: Long): Invoice = new Invoice(Order.create(id))\n",
+            "}\n\n",
+            "extension (invoice: Invoice)\n",
+            "  def summary: String = invoice.order.describe()\n",
+        ));
+        let app = write(&dir.path().join("Checkout.java"), concat!(
+            "package com.shop.app;\n\n",
+            "import com.shop.orders.OrderRepository;\n\n",
+            "public class Checkout {\n",
+            "    public void run(OrderRepository repository) {\n",
+            "        repository.find(1L);\n",
+            "    }\n",
+            "}\n",
+        ));
+        indexer.sync(&[data.clone(), orders.clone(), billing.clone(), app.clone()]).unwrap();
+
+        let db = indexer.db();
+        let id = |file: &str, name: &str| format!("{}:{}", file, name);
+        let targets = |from: &str, type_: RelationshipType| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(type_)).unwrap().into_iter().map(|r| r.to_id).collect();
+            targets.sort();
+            targets
+        };
+        let symbol = |file: &str, name: &str| db.get_symbol(&id(file, name)).unwrap().unwrap();
+        let receiver = |symbol: &crate::index::db::Symbol| -> serde_json::Value {
+            serde_json::from_str::<serde_json::Value>(symbol.metadata.as_deref().unwrap()).unwrap()["receiver"].clone()
+        };
+
+        // Classes and objects, with a companion object nested in its class as Java sees it
+        assert_eq!(symbol(&orders, "com.shop.orders.Order").language, "kotlin");
+        assert_eq!(symbol(&orders, "com.shop.orders.Registry").kind, crate::index::db::SymbolKind::Class);
+        assert_eq!(symbol(&orders, "com.shop.orders.Order.Companion").kind, crate::index::db::SymbolKind::Class);
+        assert_eq!(symbol(&orders, "com.shop.orders.Order.Companion.create").kind, crate::index::db::SymbolKind::Method);
+        // A Scala companion object's members join its class
+        assert_eq!(symbol(&billing, "com.shop.billing.Invoice.issue").kind, crate::index::db::SymbolKind::Method);
+
+        // Extension functions keep the type they extend
+        let describe = symbol(&orders, "com.shop.orders.describe");
+        assert_eq!((describe.kind, receiver(&describe)), (crate::index::db::SymbolKind::Function, serde_json::json!("Order")));
+        assert_eq!(receiver(&symbol(&billing, "com.shop.billing.summary")), serde_json::json!("Invoice"));
+        assert_eq!(receiver(&symbol(&orders, "com.shop.orders.Order.audit")), serde_json::Value::Null);
+
+        // Inheritance within and across the JVM languages
+        assert_eq!(targets(&id(&orders, "com.shop.orders.Order"), RelationshipType::Extends), vec![id(&orders, "com.shop.orders.Entity")]);
+        assert_eq!(targets(&id(&orders, "com.shop.orders.Order"), RelationshipType::Implements), vec![id(&orders, "com.shop.orders.Auditable")]);
+        assert_eq!(targets(&id(&billing, "com.shop.billing.Invoice"), RelationshipType::Extends), vec![id(&billing, "com.shop.billing.Ledger")]);
+        assert_eq!(targets(&id(&billing, "com.shop.billing.Invoice"), RelationshipType::Implements), vec![id(&orders, "com.shop.orders.Auditable")]);
+
+        // Calls from Kotlin and Scala into each other and Java, and from Java into Kotlin
+        assert_eq!(targets(&id(&orders, "com.shop.orders.Order.Companion.create"), RelationshipType::Calls), vec![id(&orders, "com.shop.orders.Order")]);
+        assert_eq!(targets(&id(&orders, "com.shop.orders.Registry.register"), RelationshipType::Calls), vec![id(&orders, "com.shop.orders.Order.audit")]);
+        assert_eq!(targets(&id(&orders, "com.shop.orders.OrderRepository.find"), RelationshipType::Calls), vec![id(&data, "com.shop.data.Store.get(long)")]);
+        let mut expected = vec![id(&billing, "com.shop.billing.Invoice"), id(&orders, "com.shop.orders.Order.Companion.create")];
+        expected.sort();
+        assert_eq!(targets(&id(&billing, "com.shop.billing.Invoice.issue"), RelationshipType::Calls), expected);
+        assert_eq!(targets(&id(&billing, "com.shop.billing.summary"), RelationshipType::Calls), vec![id(&orders, "com.shop.orders.describe")]);
+        assert_eq!(targets(&id(&app, "com.shop.app.Checkout.run(OrderRepository)"), RelationshipType::Calls), vec![id(&orders, "com.shop.orders.OrderRepository.find")]);
+
+        let conn = db.get_conn().unwrap();
+        let unresolved: String = conn.query_row("SELECT name FROM unresolved_references WHERE from_id = ?1", [id(&orders, "com.shop.orders.OrderRepository.find")], |row| row.get(0)).unwrap();
+        assert_eq!(unresolved, "missing");
+    }
+
+    #[test]
+    fn test_protobuf_definitions_and_generated_stubs() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+        for path in ["proto", "gen/orders", "client", "java", "py"] {
+            std::fs::create_dir_all(dir.path().join(path)).unwrap();
+        }
+        write(&dir.path().join("go.mod"), "module example.com/shop\n\ngo 1.22\n");
+
+        let common = write(&dir.path().join("proto/common.proto"), concat!(
+            "syntax = \"proto3\";\n",
+            "package shop.common;\n\n",
+            "message Money {\n  int64 cents = 1;\n}\n",
+        ));
+        let orders = write(&dir.path().join("proto/orders.proto"), concat!(
+            "syntax = \"proto3\";\n",
+            "package shop.orders;\n\n",
+            "import \"common.proto\";\n",
+            "import \"google/protobuf/empty.proto\";\n\n",
+            "option java_package = \"com.shop.orders.grpc\";\n",
+            "option java_multiple_files = true;\n\n",
+            "message Order {\n",
+            "  string id = 1;\n",
+            "  shop.common.Money total = 2;\n",
+            "  Status status = 3;\n",
+            "  repeated Line lines = 4;\n",
+            "  enum Status { PENDING = 0; PAID = 1; }\n",
+            "  message Line { string sku = 1; }\n",
+            "}\n\n",
+            "message GetOrderRequest { string id = 1; }\n\n",
+            "service OrderService {\n",
+            "  rpc GetOrder(GetOrderRequest) returns (Order);\n",
+            "  rpc WatchOrders(GetOrderRequest) returns (stream Order) {}\n",
+            "}\n",
+        ));
+        let go_messages = write(&dir.path().join("gen/orders/orders.pb.go"), concat!(
+            "// Code generated by protoc-gen-go. DO NOT EDIT.\n",
+            "// source: orders.proto\n\n",
+            "package orders\n\n",
+            "type Order struct {\n\tId string\n}\n\n",
+            "func (x *Order) GetId() string { return x.Id }\n\n",
+            "type Order_Line struct {\n\tSku string\n}\n\n",
+            "type GetOrderRequest struct {\n\tId string\n}\n",
+        ));
+        let go_grpc = write(&dir.path().join("gen/orders/orders_grpc.pb.go"), concat!(
+            "// Code generated by protoc-gen-go-grpc. DO NOT EDIT.\n\n",
+            "package orders\n\n",
+            "type OrderServiceClient interface {\n",
+            "\tGetOrder(in *GetOrderRequest) (*Order, error)\n",
+            "}\n\n",
+            "type orderServiceClient struct{}\n\n",
+            "func NewOrderServiceClient() OrderServiceClient { return &orderServiceClient{} }\n\n",
+            "func (c *orderServiceClient) GetOrder(in *GetOrderRequest) (*Order, error) { return nil, nil }\n",
+        ));
+        let go_client = write(&dir.path().join("client/main.go"), concat!(
+            "package main\n\n",
+            "import (\n\torders \"example.com/shop/gen/orders\"\n)\n\n",
+            "func fetch() {\n",
+            "\tclient := orders.NewOrderServiceClient()\n",
+            "\tclient.GetOrder(&orders.GetOrderRequest{Id: \"1\"})\n",
+            "}\n",
+        ));
+        let java_grpc = write(&dir.path().join("java/OrderServiceGrpc.java"), concat!(
+            "package com.shop.orders.grpc;\n\n",
+            "@javax.annotation.Generated(\n",
+            "    value = \"by gRPC proto compiler (version 1.60.0)\",\n",
+            "    comments = \"Source: orders.proto\")\n",
+            "public final class OrderServiceGrpc {\n",
+            "  public static OrderServiceBlockingStub newBlockingStub() { return null; }\n\n",
+            "  public static final class OrderServiceBlockingStub {\n",
+            "    public Order getOrder(GetOrderRequest request) { return null; }\n",
+            "  }\n",
+            "}\n",
+        ));
+        let java_client = write(&dir.path().join("java/Client.java"), concat!(
+            "package com.shop.app;\n\n",
+            "import com.shop.orders.grpc.OrderServiceGrpc;\n\n",
+            "public class Client {\n",
+            "  public void load() {\n",
+            "    OrderServiceGrpc.OrderServiceBlockingStub stub = OrderServiceGrpc.newBlockingStub();\n",
+            "    stub.getOrder(null);\n",
+            "  }\n",
+            "}\n",
+        ));
+        let python_grpc = write(&dir.path().join("py/orders_pb2_grpc.py"), concat!(
+            "# Generated by the gRPC Python protocol compiler plugin. DO NOT EDIT!\n",
+            "import grpc\n\n",
+            "class OrderServiceServicer(object):\n",
+            "    def GetOrder(self, request, context):\n",
+            "        raise NotImplementedError()\n",
+        ));
+        indexer.sync(&[
+            common.clone(), orders.clone(), go_messages.clone(), go_grpc.clone(), go_client.clone(),
+            java_grpc.clone(), java_client.clone(), python_grpc.clone(),
+        ]).unwrap();
+
+        let db = indexer.db();
+        let id = |file: &str, name: &str| format!("{}:{}", file, name);
+        let targets = |from: &str, type_: RelationshipType| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(type_)).unwrap().into_iter().map(|r| r.to_id).collect();
+            targets.sort();
+            targets
+        };
+
+        let watch = db.get_symbol(&id(&orders, "shop.orders.OrderService.WatchOrders")).unwrap().unwrap();
+        assert_eq!(watch.kind, crate::index::db::SymbolKind::Method);
+        let metadata: serde_json::Value = serde_json::from_str(watch.metadata.as_deref().unwrap()).unwrap();
+        assert_eq!((metadata["definition"].as_str(), metadata["server_streaming"].as_bool()), (Some("rpc"), Some(true)));
+        assert_eq!(db.get_symbol(&id(&orders, "shop.orders.Order.Status.PAID")).unwrap().unwrap().language, "protobuf");
+
+        // Imports between `.proto` files, and field and rpc types resolved scope by scope
+        assert_eq!(targets(&id(&orders, "orders.proto"), RelationshipType::References), vec![id(&common, "common.proto")]);
+        assert_eq!(targets(&id(&orders, "shop.orders.Order.total"), RelationshipType::References), vec![id(&common, "shop.common.Money")]);
+        assert_eq!(targets(&id(&orders, "shop.orders.Order.status"), RelationshipType::References), vec![id(&orders, "shop.orders.Order.Status")]);
+        let mut expected = vec![id(&orders, "shop.orders.GetOrderRequest"), id(&orders, "shop.orders.Order")];
+        expected.sort();
+        assert_eq!(targets(&id(&orders, "shop.orders.OrderService.GetOrder"), RelationshipType::References), expected);
+
+        // Generated Go, Java and Python code is linked back to its definitions
+        let get_order = id(&orders, "shop.orders.OrderService.GetOrder");
+        let go = |file: &str, name: &str| id(file, &format!("example.com/shop/gen/orders.{}", name));
+        assert_eq!(targets(&go(&go_messages, "Order_Line"), RelationshipType::Implements), vec![id(&orders, "shop.orders.Order.Line")]);
+        assert!(targets(&go(&go_messages, "Order.GetId"), RelationshipType::Implements).is_empty());
+        assert_eq!(targets(&go(&go_grpc, "OrderServiceClient.GetOrder"), RelationshipType::Implements), vec![get_order.clone()]);
+        assert_eq!(targets(&go(&go_grpc, "orderServiceClient.GetOrder"), RelationshipType::Implements), vec![get_order.clone()]);
+        let stub = id(&java_grpc, "com.shop.orders.grpc.OrderServiceGrpc.OrderServiceBlockingStub.getOrder(GetOrderRequest)");
+        assert_eq!(targets(&stub, RelationshipType::Implements), vec![get_order.clone()]);
+        assert_eq!(targets(&id(&python_grpc, "OrderServiceServicer.GetOrder"), RelationshipType::Implements), vec![get_order.clone()]);
+
+        // The callers of an rpc are the call sites of its stubs in every language
+        let mut callers: Vec<String> = indexer.query_engine().find_callers("shop.orders.OrderService.GetOrder").unwrap()
+            .into_iter()
+            .map(|caller| caller.symbol_id)
+            .collect();
+        callers.sort();
+        let mut expected = vec![id(&go_client, "example.com/shop/client.fetch"), id(&java_client, "com.shop.app.Client.load()")];
+        expected.sort();
+        assert_eq!(callers, expected);
+
+        // The links to generated code are not part of the type hierarchy
+        let engine = indexer.query_engine();
+        assert!(engine.subtypes("shop.orders.OrderService.GetOrder", None).unwrap().is_empty());
+        assert!(engine.subtypes("shop.orders.Order.Line", None).unwrap().is_empty());
+        assert!(engine.supertypes("example.com/shop/gen/orders.Order_Line", None).unwrap().is_empty());
+
+        // An import of a file outside the index stays unresolved under its file name
+        let conn = db.get_conn().unwrap();
+        let unresolved: String = conn.query_row("SELECT name FROM unresolved_references WHERE from_id = ?1", [id(&orders, "orders.proto")], |row| row.get(0)).unwrap();
+        assert_eq!(unresolved, "empty.proto");
+    }
+
+    #[test]
+    fn test_sql_schema_and_literal_tables() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::default();
+        config.sql.scan_literals = true;
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap().with_config(&config).unwrap();
+        for directory in ["migrations", "app", "store", "java"] {
+            std::fs::create_dir_all(dir.path().join(directory)).unwrap();
+        }
+        write(&dir.path().join("go.mod"), "module example.com/app\n\ngo 1.22\n");
+
+        let init = write(&dir.path().join("migrations/001_init.sql"), concat!(
+            "CREATE TABLE orgs (id serial PRIMARY KEY, name text NOT NULL);\n\n",
+            "-- People who can sign in\n",
+            "CREATE TABLE Users (\n",
+            "    id serial PRIMARY KEY,\n",
+            "    email varchar(255) NOT NULL,\n",
+            "    org_id integer REFERENCES orgs (id)\n",
+            ");\n",
+            "CREATE UNIQUE INDEX users_email_idx ON users (email);\n",
+        ));
+        let views = write(&dir.path().join("migrations/002_views.sql"), concat!(
+            "ALTER TABLE users ADD COLUMN last_seen timestamptz;\n\n",
+            "CREATE VIEW org_members AS\n",
+            "    SELECT o.name, u.email FROM orgs o JOIN users u ON u.org_id = o.id;\n\n",
+            "CREATE FUNCTION forget(p_id integer) RETURNS void AS $$\n",
+            "    DELETE FROM sessions WHERE user_id = p_id;\n",
+            "    UPDATE users SET email = '' WHERE id = p_id;\n",
+            "$$ LANGUAGE sql;\n",
+        ));
+        let python = write(&dir.path().join("app/repo.py"), concat!(
+            "def find_user(db, email):\n",
+            "    return db.execute(\"SELECT id FROM users \" \"WHERE email = %s\", (email,))\n\n",
+            "def greet(name):\n",
+            "    return f\"Select a plan from the list, {name}\"\n",
+        ));
+        let go = write(&dir.path().join("store/store.go"), concat!(
+            "package store\n\n",
+            "func CountMembers(db DB) int {\n",
+            "\treturn db.QueryRow(`SELECT count(*)\n\t\tFROM org_members`)\n",
+            "}\n",
+        ));
+        let java = write(&dir.path().join("java/UserDao.java"), concat!(
+            "package com.app;\n\n",
+            "public class UserDao {\n",
+            "    private static final String INSERT = \"INSERT INTO users (email) VALUES (?)\";\n\n",
+            "    public void purge() {\n",
+            "        run(\"TRUNCATE TABLE sessions\");\n",
+            "    }\n",
+            "}\n",
+        ));
+        indexer.sync(&[init.clone(), views.clone(), python.clone(), go.clone(), java.clone()]).unwrap();
+
+        let db = indexer.db();
+        let id = |file: &str, name: &str| format!("{}:{}", file, name);
+        let targets = |from: &str| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(RelationshipType::References)).unwrap()
+                .into_iter()
+                .map(|r| r.to_id)
+                .collect();
+            targets.sort();
+            targets
+        };
+
+        let users = db.get_symbol(&id(&init, "users")).unwrap().unwrap();
+        assert_eq!((users.kind, users.language.as_str()), (crate::index::db::SymbolKind::Class, "sql"));
+        let metadata: serde_json::Value = serde_json::from_str(users.metadata.as_deref().unwrap()).unwrap();
+        assert_eq!(metadata["docstring"], "People who can sign in");
+        assert_eq!(db.get_symbol(&id(&init, "users.email")).unwrap().unwrap().type_.as_deref(), Some("varchar(255)"));
+        assert_eq!(db.get_symbol(&id(&views, "users.last_seen")).unwrap().unwrap().kind, crate::index::db::SymbolKind::Field);
+        assert_eq!(db.get_symbol(&id(&views, "org_members")).unwrap().unwrap().kind, crate::index::db::SymbolKind::Type);
+        assert_eq!(db.get_symbol(&id(&views, "forget")).unwrap().unwrap().signature.as_deref(), Some("forget(p_id integer) RETURNS void"));
+
+        // Foreign keys, indexes, views and functions reference tables across files
+        assert_eq!(targets(&id(&init, "users.org_id")), vec![id(&init, "orgs")]);
+        assert_eq!(targets(&id(&init, "users.users_email_idx")), vec![id(&init, "users")]);
+        assert_eq!(targets(&id(&views, "org_members")), vec![id(&init, "orgs"), id(&init, "users")]);
+        assert_eq!(targets(&id(&views, "forget")), vec![id(&init, "users")]);
+        let conn = db.get_conn().unwrap();
+        let unresolved: String = conn.query_row("SELECT name FROM unresolved_references WHERE from_id = ?1", [id(&views, "forget")], |row| row.get(0)).unwrap();
+        assert_eq!(unresolved, "sessions");
+        let forget = db.find_relationships_from(&id(&views, "forget"), Some(RelationshipType::References)).unwrap();
+        let update = forget.iter().find(|r| r.to_id == id(&init, "users")).unwrap();
+        let metadata: serde_json::Value = serde_json::from_str(update.metadata.as_deref().unwrap()).unwrap();
+        assert_eq!((metadata["access"].as_str(), update.line), (Some("write"), 7));
+
+        // Tables named by SQL in Python, Go and Java string literals
+        assert_eq!(targets(&id(&python, "find_user")), vec![id(&init, "users")]);
+        assert!(targets(&id(&python, "greet")).is_empty());
+        assert_eq!(targets(&id(&go, "example.com/app/store.CountMembers")), vec![id(&views, "org_members")]);
+        assert_eq!(targets(&id(&java, "com.app.UserDao.INSERT")), vec![id(&init, "users")]);
+
+        let mut referrers: Vec<String> = indexer.query_engine().find_references("users").unwrap()
+            .into_iter()
+            .map(|result| result.symbol_id)
+            .collect();
+        referrers.sort();
+        referrers.dedup();
+        let mut expected = vec![
+            id(&init, "users.users_email_idx"),
+            id(&views, "002_views.sql"),
+            id(&views, "org_members"),
+            id(&views, "forget"),
+            id(&python, "find_user"),
+            id(&java, "com.app.UserDao.INSERT"),
+        ];
+        expected.sort();
+        assert_eq!(referrers, expected);
+
+        // A table indexed later resolves the literals that named it
+        let sessions = write(&dir.path().join("migrations/003_sessions.sql"), "CREATE TABLE sessions (user_id integer);\n");
+        let update = indexer.update_file(&sessions).unwrap();
+        assert!(update.dependents.contains(&java));
+        assert_eq!(targets(&id(&java, "com.app.UserDao.purge()")), vec![id(&sessions, "sessions")]);
+    }
+
+    #[test]
+    fn test_build_graph_of_makefiles_scripts_and_dockerfiles() {
+        let dir = tempdir().unwrap();
+        let indexer = Indexer::new(dir.path().join("test.db")).unwrap();
+        for directory in ["build", "docs", "scripts"] {
+            std::fs::create_dir_all(dir.path().join(directory)).unwrap();
+        }
+
+        let makefile = write(&dir.path().join("Makefile"), concat!(
+            "include build/common.mk\n",
+            "\n",
+            ".PHONY: all build deploy\n",
+            "\n",
+            "# Build and ship\n",
+            "all: build deploy\n",
+            "\n",
+            "build: gen\n",
+            "\t./scripts/build.sh $(MODE)\n",
+            "\n",
+            "deploy: build\n",
+            "\t@$(MAKE) -C docs html\n",
+            "\tsh scripts/deploy.sh\n",
+        ));
+        let common = write(&dir.path().join("build/common.mk"), "MODE := release\n\ngen:\n\t./scripts/gen.sh\n");
+        let docs = write(&dir.path().join("docs/Makefile"), "html:\n\tsphinx-build . _build\n");
+        let lib = write(&dir.path().join("scripts/lib.sh"), "# Print a log line\nlog() { echo \"$@\"; }\n");
+        let build = write(&dir.path().join("scripts/build.sh"), concat!(
+            "#!/bin/sh\n",
+            ". \"$(dirname \"$0\")/lib.sh\"\n",
+            "\n",
+            "main() {\n",
+            "  log building\n",
+            "}\n",
+            "main \"$@\"\n",
+        ));
+        let gen = write(&dir.path().join("scripts/gen.sh"), "#!/bin/sh\necho generated\n");
+        let deploy = write(&dir.path().join("scripts/deploy.sh"), "#!/bin/bash\nsource ./lib.sh\nmake -C ../docs html\n./missing.sh\n");
+        let dockerfile = write(&dir.path().join("Dockerfile"), concat!(
+            "FROM rust:1.80 AS builder\n",
+            "COPY . /src\n",
+            "RUN make build\n",
+            "\n",
+            "FROM builder AS test\n",
+            "RUN ./scripts/build.sh --check\n",
+            "\n",
+            "FROM debian:bookworm-slim\n",
+            "COPY --from=builder /src/target/release/app /usr/bin/app\n",
+        ));
+        let files = [makefile.clone(), common.clone(), docs.clone(), lib.clone(), build.clone(), gen.clone(), deploy.clone(), dockerfile.clone()];
+        indexer.sync(&files).unwrap();
+
+        let db = indexer.db();
+        let id = |file: &str, name: &str| format!("{}:{}", file, name);
+        let targets = |from: &str, kind: RelationshipType| -> Vec<String> {
+            let mut targets: Vec<String> = db.find_relationships_from(from, Some(kind)).unwrap()
+                .into_iter()
+                .map(|r| r.to_id)
+                .collect();
+            targets.sort();
+            targets
+        };
+
+        let all = db.get_symbol(&id(&makefile, "all")).unwrap().unwrap();
+        assert_eq!((all.kind, all.language.as_str()), (crate::index::db::SymbolKind::Function, "makefile"));
+        let metadata: serde_json::Value = serde_json::from_str(all.metadata.as_deref().unwrap()).unwrap();
+        assert_eq!((metadata["phony"].as_bool(), metadata["default"].as_bool()), (Some(true), Some(true)));
+        assert_eq!(metadata["docstring"], "Build and ship");
+        assert_eq!(db.get_symbol(&id(&common, "MODE")).unwrap().unwrap().signature.as_deref(), Some("MODE := release"));
+
+        // Prerequisites, recipes and includes
+        assert_eq!(targets(&id(&makefile, "all"), RelationshipType::Calls), vec![id(&makefile, "build"), id(&makefile, "deploy")]);
+        assert_eq!(targets(&id(&makefile, "build"), RelationshipType::Calls), vec![id(&common, "gen"), id(&build, "build.sh")]);
+        assert_eq!(targets(&id(&makefile, "build"), RelationshipType::References), vec![id(&common, "MODE")]);
+        assert_eq!(targets(&id(&makefile, "deploy"), RelationshipType::Calls), vec![id(&makefile, "build"), id(&docs, "html"), id(&deploy, "deploy.sh")]);
+        assert_eq!(targets(&id(&makefile, "Makefile"), RelationshipType::DependsOn), vec![id(&common, "common.mk")]);
+        // Run from the repository root, so found by its path suffix
+        assert_eq!(targets(&id(&common, "gen"), RelationshipType::Calls), vec![id(&gen, "gen.sh")]);
+
+        // Scripts source each other, call the functions they source and run `make`
+        assert_eq!(targets(&id(&build, "build.sh"), RelationshipType::DependsOn), vec![id(&lib, "lib.sh")]);
+        assert_eq!(targets(&id(&build, "main"), RelationshipType::Calls), vec![id(&lib, "log")]);
+        assert_eq!(targets(&id(&deploy, "deploy.sh"), RelationshipType::Calls), vec![id(&docs, "html")]);
+        let conn = db.get_conn().unwrap();
+        let unresolved: String = conn.query_row("SELECT name FROM unresolved_references WHERE from_id = ?1", [id(&deploy, "deploy.sh")], |row| row.get(0)).unwrap();
+        assert_eq!(unresolved, "missing.sh");
+
+        // Stages build on and copy from earlier ones and run targets and scripts
+        assert_eq!(targets(&id(&dockerfile, "test"), RelationshipType::Extends), vec![id(&dockerfile, "builder")]);
+        assert_eq!(targets(&id(&dockerfile, "stage-2"), RelationshipType::Calls), vec![id(&dockerfile, "builder")]);
+        assert_eq!(targets(&id(&dockerfile, "builder"), RelationshipType::Calls), vec![id(&makefile, "build")]);
+        assert_eq!(targets(&id(&dockerfile, "test"), RelationshipType::Calls), vec![id(&build, "build.sh")]);
+
+        let mut callers: Vec<String> = indexer.query_engine().find_callers("build").unwrap()
+            .into_iter()
+            .map(|result| result.symbol_id)
+            .collect();
+        callers.sort();
+        let mut expected = vec![id(&makefile, "all"), id(&makefile, "deploy"), id(&dockerfile, "builder")];
+        expected.sort();
+        assert_eq!(callers, expected);
+        let callees: Vec<String> = indexer.query_engine().find_callees("build.sh").unwrap()
+            .into_iter()
+            .map(|result| result.symbol_id)
+            .collect();
+        assert_eq!(callees, vec![id(&build, "main")]);
+    }
+}
@@ -1,28 +1,116 @@
 // Language parsers
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
 use tree_sitter::{Parser as TreeParser, Tree};
 
-use crate::index::{Location, Parser, Relationship, RelationshipKind, Symbol, SymbolKind, Visibility};
+use crate::config::GrammarConfig;
+use crate::index::{parent_name, unresolved_id, Location, PendingRelationships, Relationship, RelationshipKind, Span, Symbol, SymbolKind, SymbolTable, Visibility};
+
+use super::python::{class_bases, ImportBinding, Resolver};
+use super::c;
+use super::csharp;
+use super::dockerfile;
+use super::go;
+use super::grammar;
+use super::intent;
+use super::java;
+use super::make;
+use super::protobuf;
+use super::ruby;
+use super::rust;
+use super::shell;
+use super::sql;
+use super::typescript;
 
 /// Python parser using tree-sitter
-pub struct PythonParser;
+#[derive(Default)]
+pub struct PythonParser {
+    /// Link the tables named by SQL in string literals
+    sql_literals: bool,
+}
 
 // Rust parser using tree-sitter
+#[derive(Default)]
 pub struct RustParser;
 
 // Go parser using tree-sitter
-pub struct GoParser;
+#[derive(Default)]
+pub struct GoParser {
+    /// Link the tables named by SQL in string literals
+    sql_literals: bool,
+    modules: go::Modules,
+}
 
 // Java parser using tree-sitter
-pub struct JavaParser;
+#[derive(Default)]
+pub struct JavaParser {
+    /// Link the tables named by SQL in string literals
+    sql_literals: bool,
+}
+
+// TypeScript, TSX and JavaScript parser using tree-sitter
+#[derive(Default)]
+pub struct TypeScriptParser {
+    /// Index `.js` files as JavaScript rather than `.ts` files as TypeScript
+    javascript: bool,
+}
+
+// C and C++ parser using tree-sitter
+#[derive(Default)]
+pub struct CParser {
+    cpp: bool,
+    options: c::Options,
+}
+
+// C# parser using tree-sitter
+#[derive(Default)]
+pub struct CSharpParser;
+
+// Ruby parser using tree-sitter
+#[derive(Default)]
+pub struct RubyParser;
+
+// Protocol Buffers parser over the hand-written parser in `protobuf.rs`
+#[derive(Default)]
+pub struct ProtobufParser;
+
+// SQL parser over the hand-written parser in `sql.rs`
+#[derive(Default)]
+pub struct SqlParser;
+
+// Shell script parser over tree-sitter-bash, whose invocation resolution the
+// Makefile and Dockerfile parsers share for recipes and `RUN` instructions
+#[derive(Default)]
+pub struct ShellParser;
+
+// Makefile parser over the hand-written parser in `make.rs`
+#[derive(Default)]
+pub struct MakefileParser;
+
+// Dockerfile parser over the hand-written parser in `dockerfile.rs`
+#[derive(Default)]
+pub struct DockerfileParser;
+
+// Parser for a language from `[[languages.grammars]]`, or for Kotlin and
+// Scala, over the grammar and queries of `grammar.rs`
+pub struct GrammarParser {
+    grammar: grammar::Grammar,
+}
 
-// Intent parser (basic implementation)
+// Intent parser over the recursive-descent parser in `intent.rs`
+#[derive(Default)]
 pub struct IntentParser;
 
 impl PythonParser {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Scan string literals for SQL and link the tables they name
+    pub fn with_sql_literals(mut self, enabled: bool) -> Self {
+        self.sql_literals = enabled;
+        self
     }
 
     fn parse_tree(&self, content: &str) -> anyhow::Result<Tree> {
@@ -43,7 +131,24 @@ impl PythonParser {
         let mut cursor = root.walk();
         self.walk_tree(&mut cursor, content, file_path, &mut symbols, Vec::new());
 
-        symbols
+        // Repeated imports of a module share one symbol
+        let mut merged: Vec<Symbol> = Vec::with_capacity(symbols.len());
+        let mut imports = std::collections::HashMap::new();
+        for symbol in symbols {
+            if symbol.kind == SymbolKind::Import {
+                if let Some(&index) = imports.get(&symbol.id) {
+                    let existing: &mut Symbol = &mut merged[index];
+                    if let (Some(bindings), Some(more)) = (existing.metadata["bindings"].as_array_mut(), symbol.metadata["bindings"].as_array()) {
+                        bindings.extend(more.iter().cloned());
+                    }
+                    continue;
+                }
+                imports.insert(symbol.id.clone(), merged.len());
+            }
+            merged.push(symbol);
+        }
+
+        merged
     }
 
     fn walk_tree(
@@ -73,9 +178,7 @@ impl PythonParser {
                 }
             }
             "import_statement" | "import_from_statement" => {
-                if let Some(symbol) = self.extract_import(node, content, file_path, &scope_stack) {
-                    symbols.push(symbol);
-                }
+                symbols.extend(self.extract_imports(node, content, file_path));
             }
             _ => {}
         }
@@ -89,10 +192,10 @@ impl PythonParser {
                 }
             }
 
-            self.walk_tree(cursor, content, file_path, symbols, new_scope);
+            self.walk_tree(cursor, content, file_path, symbols, new_scope.clone());
 
             while cursor.goto_next_sibling() {
-                self.walk_tree(cursor, content, file_path, symbols, scope_stack.clone());
+                self.walk_tree(cursor, content, file_path, symbols, new_scope.clone());
             }
 
             cursor.goto_parent();
@@ -111,34 +214,52 @@ impl PythonParser {
 
         let location = self.node_location(node, file_path);
 
-        // Extract parameters
-        let parameters_node = node.child_by_field_name("parameters");
+        // Extract parameters as written, annotations and defaults included
         let mut parameters = Vec::new();
-        if let Some(params) = parameters_node {
+        let mut parameter_list = Vec::new();
+        if let Some(params) = node.child_by_field_name("parameters") {
             let mut cursor = params.walk();
-            for child in params.children(&mut cursor) {
-                if child.kind() == "identifier" {
-                    if let Some(param_name) = self.get_node_text(Some(child), content) {
-                        parameters.push(param_name);
-                    }
+            for child in params.named_children(&mut cursor) {
+                if child.kind() == "comment" {
+                    continue;
+                }
+                let Some(text) = self.get_node_text(Some(child), content) else { continue };
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                let param_name = text.split([':', '=']).next().unwrap_or(&text).trim().trim_start_matches('*');
+                if !param_name.is_empty() && !matches!(child.kind(), "keyword_separator" | "positional_separator") {
+                    parameters.push(param_name.to_string());
                 }
+                parameter_list.push(text);
             }
         }
 
-        let signature = format!("def {}({})", name, parameters.join(", "));
+        let return_type = self.get_node_text(node.child_by_field_name("return_type"), content);
+        let type_info = match &return_type {
+            Some(return_type) => format!("({}) -> {}", parameter_list.join(", "), return_type),
+            None => format!("({})", parameter_list.join(", ")),
+        };
+        let is_async = node.child(0).is_some_and(|child| child.kind() == "async");
+        let signature = format!("{}def {}{}", if is_async { "async " } else { "" }, name, type_info);
+
+        // Functions defined directly in a class body are methods
+        let kind = match self.enclosing_definition(node) {
+            Some(parent) if parent.kind() == "class_definition" => SymbolKind::Method,
+            _ => SymbolKind::Function,
+        };
 
         Some(Symbol {
             id: format!("{}:{}", file_path, qualified_name),
-            kind: SymbolKind::Function,
+            kind,
             name,
             qualified_name,
             location,
             signature: Some(signature),
-            type_info: None,
+            type_info: Some(type_info),
             visibility: Visibility::Public, // Python doesn't have visibility
             language: "python".to_string(),
             metadata: serde_json::json!({
-                "parameters": parameters
+                "parameters": parameters,
+                "decorators": self.decorators(node, content)
             }),
             content_hash: "".to_string(), // TODO: calculate
             last_indexed: chrono::Utc::now().timestamp(),
@@ -157,6 +278,19 @@ impl PythonParser {
 
         let location = self.node_location(node, file_path);
 
+        // Base class expressions, resolved later against the module's imports
+        let mut bases = Vec::new();
+        if let Some(superclasses) = node.child_by_field_name("superclasses") {
+            let mut cursor = superclasses.walk();
+            for child in superclasses.named_children(&mut cursor) {
+                if let "identifier" | "attribute" | "subscript" = child.kind() {
+                    if let Some(base) = self.get_node_text(Some(child), content) {
+                        bases.push(base);
+                    }
+                }
+            }
+        }
+
         Some(Symbol {
             id: format!("{}:{}", file_path, qualified_name),
             kind: SymbolKind::Class,
@@ -167,7 +301,10 @@ impl PythonParser {
             type_info: None,
             visibility: Visibility::Public,
             language: "python".to_string(),
-            metadata: serde_json::json!({}),
+            metadata: serde_json::json!({
+                "bases": bases,
+                "decorators": self.decorators(node, content)
+            }),
             content_hash: "".to_string(),
             last_indexed: chrono::Utc::now().timestamp(),
         })
@@ -197,7 +334,8 @@ impl PythonParser {
             qualified_name,
             location,
             signature: None,
-            type_info: None,
+            // Annotation of `x: int = 1`
+            type_info: self.get_node_text(node.child_by_field_name("type"), content),
             visibility: Visibility::Public,
             language: "python".to_string(),
             metadata: serde_json::json!({}),
@@ -206,47 +344,131 @@ impl PythonParser {
         })
     }
 
-    fn extract_import(&self, node: tree_sitter::Node, content: &str, file_path: &str, _scope_stack: &[String]) -> Option<Symbol> {
-        // For now, just extract the module name
-        let name = if node.kind() == "import_statement" {
-            node.child_by_field_name("name")?
-                .child(0)? // dotted_name
-                .child(0)? // identifier
-        } else { // import_from_statement
-            node.child_by_field_name("module")?
+    /// Decorators of a definition without the `@`, e.g. `app.route("/users")`
+    fn decorators(&self, node: tree_sitter::Node, content: &str) -> Vec<String> {
+        let Some(parent) = node.parent().filter(|p| p.kind() == "decorated_definition") else {
+            return Vec::new();
         };
 
-        let module_name = self.get_node_text(Some(name), content)?;
+        let mut cursor = parent.walk();
+        parent.named_children(&mut cursor)
+            .filter(|child| child.kind() == "decorator")
+            .filter_map(|decorator| self.get_node_text(Some(decorator), content))
+            .map(|text| text.trim_start_matches('@').split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    }
 
-        let location = self.node_location(node, file_path);
+    /// Nearest class or function definition around a node
+    fn enclosing_definition<'t>(&self, node: tree_sitter::Node<'t>) -> Option<tree_sitter::Node<'t>> {
+        let mut current = node.parent();
+        while let Some(parent) = current {
+            if let "class_definition" | "function_definition" = parent.kind() {
+                return Some(parent);
+            }
+            current = parent.parent();
+        }
+        None
+    }
 
-        Some(Symbol {
-            id: format!("{}:import:{}", file_path, module_name),
-            kind: SymbolKind::Import,
-            name: module_name.clone(),
-            qualified_name: module_name,
-            location,
-            signature: None,
-            type_info: None,
-            visibility: Visibility::Public,
-            language: "python".to_string(),
-            metadata: serde_json::json!({}),
-            content_hash: "".to_string(),
-            last_indexed: chrono::Utc::now().timestamp(),
-        })
+    /// One `Import` symbol per imported module, holding the names it binds
+    fn extract_imports(&self, node: tree_sitter::Node, content: &str, file_path: &str) -> Vec<Symbol> {
+        let mut imports: Vec<(String, Vec<ImportBinding>)> = Vec::new();
+        let mut cursor = node.walk();
+
+        if node.kind() == "import_statement" {
+            // `import a.b` binds `a`, `import a.b as c` binds the module itself
+            for name in node.children_by_field_name("name", &mut cursor) {
+                let (module, alias) = match name.kind() {
+                    "aliased_import" => (
+                        self.get_node_text(name.child_by_field_name("name"), content),
+                        self.get_node_text(name.child_by_field_name("alias"), content),
+                    ),
+                    _ => (self.get_node_text(Some(name), content), None),
+                };
+                let Some(module) = module else { continue };
+                let binding = match alias {
+                    Some(alias) => ImportBinding { alias, module: module.clone(), level: 0, symbol: None },
+                    None => {
+                        let package = module.split('.').next().unwrap_or(&module).to_string();
+                        ImportBinding { alias: package.clone(), module: package, level: 0, symbol: None }
+                    }
+                };
+                imports.push((module, vec![binding]));
+            }
+        } else {
+            let Some(module_node) = node.child_by_field_name("module_name") else {
+                return Vec::new();
+            };
+            let Some(module_name) = self.get_node_text(Some(module_node), content) else {
+                return Vec::new();
+            };
+            let level = module_name.chars().take_while(|&c| c == '.').count();
+            let module = module_name[level..].to_string();
+
+            let mut bindings = Vec::new();
+            for name in node.children_by_field_name("name", &mut cursor) {
+                let (symbol, alias) = match name.kind() {
+                    "aliased_import" => (
+                        self.get_node_text(name.child_by_field_name("name"), content),
+                        self.get_node_text(name.child_by_field_name("alias"), content),
+                    ),
+                    _ => (self.get_node_text(Some(name), content), None),
+                };
+                let Some(symbol) = symbol else { continue };
+                bindings.push(ImportBinding {
+                    alias: alias.unwrap_or_else(|| symbol.clone()),
+                    module: module.clone(),
+                    level,
+                    symbol: Some(symbol),
+                });
+            }
+
+            let mut cursor = node.walk();
+            if node.children(&mut cursor).any(|child| child.kind() == "wildcard_import") {
+                bindings.push(ImportBinding { alias: "*".to_string(), module: module.clone(), level, symbol: None });
+            }
+            imports.push((module_name, bindings));
+        }
+
+        let location = self.node_location(node, file_path);
+        imports.into_iter()
+            .map(|(module_name, bindings)| Symbol {
+                id: format!("{}:import:{}", file_path, module_name),
+                kind: SymbolKind::Import,
+                name: module_name.clone(),
+                qualified_name: module_name,
+                location: location.clone(),
+                signature: None,
+                type_info: None,
+                visibility: Visibility::Public,
+                language: "python".to_string(),
+                metadata: serde_json::json!({
+                    "bindings": bindings
+                }),
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            })
+            .collect()
     }
 
     fn extract_relationships(&self, tree: &Tree, content: &str, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(tree, content, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, tree: &Tree, content: &str, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
         let mut relationships = Vec::new();
         let root = tree.root_node();
-
-        // Create a map of qualified names to symbol IDs for lookup
-        let symbol_map: HashMap<&str, &Symbol> = symbols.iter()
-            .map(|s| (s.qualified_name.as_str(), s))
-            .collect();
+        let resolver = Resolver::new(symbol_map);
 
         let mut cursor = root.walk();
-        self.extract_relationships_from_tree(&mut cursor, content, file_path, &symbol_map, &mut relationships, Vec::new());
+        self.extract_relationships_from_tree(&mut cursor, content, file_path, &resolver, &mut relationships, Vec::new());
+        relationships.extend(self.import_relationships(file_path, &resolver, symbol_map));
+        relationships.extend(ProtobufParser::stub_relationships(content, file_path, symbol_map));
+        if self.sql_literals {
+            relationships.extend(SqlParser::literal_relationships(root, content, file_path, &["string", "concatenated_string"], symbol_map));
+        }
 
         relationships
     }
@@ -256,7 +478,7 @@ impl PythonParser {
         cursor: &mut tree_sitter::TreeCursor,
         content: &str,
         file_path: &str,
-        symbol_map: &HashMap<&str, &Symbol>,
+        resolver: &Resolver,
         relationships: &mut Vec<Relationship>,
         context_stack: Vec<String>,
     ) {
@@ -264,15 +486,18 @@ impl PythonParser {
 
         match node.kind() {
             "call" => {
-                if let Some(rel) = self.extract_call_relationship(node, content, file_path, symbol_map, &context_stack) {
+                if let Some(rel) = self.extract_call_relationship(node, content, file_path, resolver, &context_stack) {
                     relationships.push(rel);
                 }
             }
             "attribute" => {
-                if let Some(rel) = self.extract_attribute_relationship(node, content, file_path, symbol_map, &context_stack) {
+                if let Some(rel) = self.extract_attribute_relationship(node, content, file_path, resolver, &context_stack) {
                     relationships.push(rel);
                 }
             }
+            "class_definition" => {
+                relationships.extend(self.extract_base_relationships(node, content, file_path, resolver, &context_stack));
+            }
             _ => {}
         }
 
@@ -285,16 +510,10 @@ impl PythonParser {
                 }
             }
 
-            self.extract_relationships_from_tree(cursor, content, file_path, symbol_map, relationships, new_context);
+            self.extract_relationships_from_tree(cursor, content, file_path, resolver, relationships, new_context.clone());
 
             while cursor.goto_next_sibling() {
-                let mut sibling_context = context_stack.clone();
-                if let "class_definition" | "function_definition" = cursor.node().kind() {
-                    if let Some(name) = self.get_node_text(cursor.node().child_by_field_name("name"), content) {
-                        sibling_context.push(name);
-                    }
-                }
-                self.extract_relationships_from_tree(cursor, content, file_path, symbol_map, relationships, sibling_context);
+                self.extract_relationships_from_tree(cursor, content, file_path, resolver, relationships, new_context.clone());
             }
 
             cursor.goto_parent();
@@ -306,51 +525,65 @@ impl PythonParser {
         node: tree_sitter::Node,
         content: &str,
         file_path: &str,
-        symbol_map: &HashMap<&str, &Symbol>,
+        resolver: &Resolver,
         context_stack: &[String],
     ) -> Option<Relationship> {
         let function_node = node.child_by_field_name("function")?;
+        let path = self.reference_path(function_node, content)?;
 
-        let (function_name, is_method_call) = if function_node.kind() == "attribute" {
-            // Handle method calls like obj.method()
-            let attribute_node = function_node.child_by_field_name("attribute")?;
-            let method_name = self.get_node_text(Some(attribute_node), content)?;
-            (method_name, true)
-        } else {
-            // Handle direct function calls
-            let function_name = self.get_node_text(Some(function_node), content)?;
-            (function_name, false)
+        // Only create relationship if we have a valid calling context
+        if context_stack.is_empty() {
+            return None;
+        }
+        let caller_symbol = resolver.symbol(file_path, &context_stack.join("."))?;
+        let location = self.node_location(node, file_path);
+
+        match resolver.resolve(file_path, context_stack, &path) {
+            Some(called_symbol) => Some(Relationship {
+                from_id: caller_symbol.id.clone(),
+                to_id: called_symbol.id.clone(),
+                kind: RelationshipKind::Calls,
+                location,
+                metadata: serde_json::json!({}),
+            }),
+            // Record the call so it can be re-resolved once a matching symbol appears
+            None => Some(Relationship::unresolved(caller_symbol.id.clone(), &path.join("."), RelationshipKind::Calls, location)),
+        }
+    }
+
+    /// `Extends` from a class to each of its bases
+    fn extract_base_relationships(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &Resolver,
+        context_stack: &[String],
+    ) -> Vec<Relationship> {
+        let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) else {
+            return Vec::new();
+        };
+        let qualified_name = context_stack.iter().chain([&name]).cloned().collect::<Vec<_>>().join(".");
+        let Some(class) = resolver.symbol(file_path, &qualified_name) else {
+            return Vec::new();
         };
 
-        let called_symbol = if is_method_call {
-            // For method calls, look for any method with this name
-            symbol_map.values()
-                .find(|s| s.kind == SymbolKind::Method && s.name == function_name)
-        } else {
-            // For direct calls, look for functions or classes
-            symbol_map.values()
-                .find(|s| (s.kind == SymbolKind::Function || s.kind == SymbolKind::Class) &&
-                          (s.qualified_name == function_name || s.qualified_name.ends_with(&format!(".{}", function_name))))
-        };
-
-        if let Some(called_symbol) = called_symbol {
-            // Only create relationship if we have a valid calling context
-            if !context_stack.is_empty() {
-                let caller_qualified_name = context_stack.join(".");
-                if let Some(caller_symbol) = symbol_map.get(caller_qualified_name.as_str()) {
-                    let location = self.node_location(node, file_path);
-
-                    return Some(Relationship {
-                        from_id: caller_symbol.id.clone(),
-                        to_id: called_symbol.id.clone(),
-                        kind: RelationshipKind::Calls,
-                        location,
-                        metadata: serde_json::json!({}),
-                    });
+        let location = self.node_location(node, file_path);
+        class_bases(class)
+            .map(|base| match resolver.resolve_base(class, base) {
+                Some(base_class) => Relationship {
+                    from_id: class.id.clone(),
+                    to_id: base_class.id.clone(),
+                    kind: RelationshipKind::Extends,
+                    location: location.clone(),
+                    metadata: serde_json::json!({}),
+                },
+                None => {
+                    let base = base.split('[').next().unwrap_or(base).trim();
+                    Relationship::unresolved(class.id.clone(), base, RelationshipKind::Extends, location.clone())
                 }
-            }
-        }
-        None
+            })
+            .collect()
     }
 
     fn extract_attribute_relationship(
@@ -358,35 +591,47 @@ impl PythonParser {
         node: tree_sitter::Node,
         content: &str,
         file_path: &str,
-        symbol_map: &HashMap<&str, &Symbol>,
+        resolver: &Resolver,
         context_stack: &[String],
     ) -> Option<Relationship> {
-        let object_node = node.child_by_field_name("object")?;
-        let attribute_node = node.child_by_field_name("attribute")?;
-
-        let object_name = self.get_node_text(Some(object_node), content)?;
-        let attribute_name = self.get_node_text(Some(attribute_node), content)?;
-
-        let qualified_name = format!("{}.{}", object_name, attribute_name);
+        // The callee of a call is already recorded as a call
+        if node.parent().and_then(|p| p.child_by_field_name("function")) == Some(node) {
+            return None;
+        }
+        let path = self.reference_path(node, content)?;
 
-        if let Some(referenced_symbol) = symbol_map.get(qualified_name.as_str()) {
-            // Only create relationship if we have a valid calling context
-            if !context_stack.is_empty() {
-                let caller_qualified_name = context_stack.join(".");
-                if let Some(caller_symbol) = symbol_map.get(caller_qualified_name.as_str()) {
-                    let location = self.node_location(node, file_path);
+        // Only create relationship if we have a valid calling context
+        if context_stack.is_empty() {
+            return None;
+        }
+        let caller_symbol = resolver.symbol(file_path, &context_stack.join("."))?;
+        let referenced_symbol = resolver.resolve(file_path, context_stack, &path)?;
+
+        Some(Relationship {
+            from_id: caller_symbol.id.clone(),
+            to_id: referenced_symbol.id.clone(),
+            kind: RelationshipKind::References,
+            location: self.node_location(node, file_path),
+            metadata: serde_json::json!({}),
+        })
+    }
 
-                    return Some(Relationship {
-                        from_id: caller_symbol.id.clone(),
-                        to_id: referenced_symbol.id.clone(),
-                        kind: RelationshipKind::References,
-                        location,
-                        metadata: serde_json::json!({}),
-                    });
-                }
+    /// Segments of a name or attribute chain such as `pkg.mod.func`, with
+    /// `super()` kept as one segment; `None` for other expressions
+    fn reference_path(&self, node: tree_sitter::Node, content: &str) -> Option<Vec<String>> {
+        match node.kind() {
+            "identifier" => Some(vec![self.get_node_text(Some(node), content)?]),
+            "attribute" => {
+                let mut path = self.reference_path(node.child_by_field_name("object")?, content)?;
+                path.push(self.get_node_text(node.child_by_field_name("attribute"), content)?);
+                Some(path)
+            }
+            "call" => {
+                let function = self.get_node_text(node.child_by_field_name("function"), content)?;
+                (function == "super").then(|| vec!["super()".to_string()])
             }
+            _ => None,
         }
-        None
     }
 
     fn get_node_text(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
@@ -406,11 +651,27 @@ impl PythonParser {
         }
     }
 
+    /// `DependsOn` edges from each import to the modules and names it imports
+    fn import_relationships(&self, file_path: &str, resolver: &Resolver, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        symbol_map.in_file(file_path)
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Import)
+            .flat_map(|import| {
+                resolver.import_targets(import).into_iter().map(|target| Relationship {
+                    from_id: import.id.clone(),
+                    to_id: target.id.clone(),
+                    kind: RelationshipKind::DependsOn,
+                    location: import.location.clone(),
+                    metadata: serde_json::json!({ "import": true }),
+                })
+            })
+            .collect()
+    }
 }
 
 impl crate::index::Parser for PythonParser {
-    fn can_parse(&self, file_path: &str) -> bool {
-        file_path.ends_with(".py")
+    fn extensions(&self) -> Vec<&str> {
+        vec!["py"]
     }
 
     fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
@@ -421,15 +682,42 @@ impl crate::index::Parser for PythonParser {
         Ok((symbols, relationships))
     }
 
-    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &std::collections::HashMap<&str, &Symbol>) -> anyhow::Result<Vec<Relationship>> {
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
         let tree = self.parse_tree(content)?;
-        let mut relationships = Vec::new();
-        let root = tree.root_node();
+        Ok(self.extract_relationships_with_table(&tree, content, file_path, global_symbol_map))
+    }
 
-        let mut cursor = root.walk();
-        self.extract_relationships_from_tree(&mut cursor, content, file_path, global_symbol_map, &mut relationships, Vec::new());
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let tree = self.parse_tree(&content)?;
+        let symbols = self.extract_symbols(&tree, &content, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&tree, &content, &file_path, symbols)))))
+    }
+}
+
+/// Where a Rust item is declared
+#[derive(Debug, Clone)]
+struct RustContext {
+    /// Qualified name segments of the enclosing item, starting with `crate`
+    scope: Vec<String>,
+    /// Enclosing module
+    module: Vec<String>,
+    /// Type of the enclosing impl or trait, for `Self` and `self`
+    self_type: Option<Vec<String>>,
+    /// Trait of the enclosing impl, for its provided methods
+    trait_path: Option<Vec<String>>,
+}
 
-        Ok(relationships)
+impl RustContext {
+    fn new(file_path: &str) -> Self {
+        let (_, module) = rust::module_path(file_path);
+        Self {
+            scope: module.clone(),
+            module,
+            self_type: None,
+            trait_path: None,
+        }
     }
 }
 
@@ -449,12 +737,17 @@ impl RustParser {
     }
 
     fn extract_symbols(&self, tree: &Tree, content: &str, file_path: &str) -> Vec<Symbol> {
-        let mut symbols = Vec::new();
         let root = tree.root_node();
+        let context = RustContext::new(file_path);
+
+        // `use` declarations first, impl types are resolved through them
+        let mut symbols = Vec::new();
+        self.extract_uses(root, content, file_path, &context, &mut symbols);
+        let uses = rust::UseScope::from_symbols(&symbols);
 
         // Walk the tree to find symbols
         let mut cursor = root.walk();
-        self.walk_tree(&mut cursor, content, file_path, &mut symbols, Vec::new());
+        self.walk_tree(&mut cursor, content, file_path, &mut symbols, context, &uses);
 
         symbols
     }
@@ -465,43 +758,45 @@ impl RustParser {
         content: &str,
         file_path: &str,
         symbols: &mut Vec<Symbol>,
-        scope_stack: Vec<String>,
+        context: RustContext,
+        uses: &rust::UseScope,
     ) {
         let node = cursor.node();
+        let scope_stack = &context.scope;
 
         match node.kind() {
-            "function_item" => {
-                if let Some(symbol) = self.extract_function(node, content, file_path, &scope_stack) {
+            "function_item" | "function_signature_item" => {
+                if let Some(symbol) = self.extract_function(node, content, file_path, &context) {
                     symbols.push(symbol);
                 }
             }
             "struct_item" => {
-                if let Some(symbol) = self.extract_struct(node, content, file_path, &scope_stack) {
+                if let Some(symbol) = self.extract_struct(node, content, file_path, scope_stack) {
                     symbols.push(symbol);
                 }
             }
             "enum_item" => {
-                if let Some(symbol) = self.extract_enum(node, content, file_path, &scope_stack) {
+                if let Some(symbol) = self.extract_enum(node, content, file_path, scope_stack) {
                     symbols.push(symbol);
                 }
             }
-            "impl_item" => {
-                if let Some(symbol) = self.extract_impl(node, content, file_path, &scope_stack) {
+            "trait_item" => {
+                if let Some(symbol) = self.extract_trait(node, content, file_path, scope_stack) {
                     symbols.push(symbol);
                 }
             }
-            "trait_item" => {
-                if let Some(symbol) = self.extract_trait(node, content, file_path, &scope_stack) {
+            "mod_item" => {
+                if let Some(symbol) = self.extract_mod(node, content, file_path, scope_stack) {
                     symbols.push(symbol);
                 }
             }
             "const_item" => {
-                if let Some(symbol) = self.extract_const(node, content, file_path, &scope_stack) {
+                if let Some(symbol) = self.extract_const(node, content, file_path, scope_stack) {
                     symbols.push(symbol);
                 }
             }
             "static_item" => {
-                if let Some(symbol) = self.extract_static(node, content, file_path, &scope_stack) {
+                if let Some(symbol) = self.extract_static(node, content, file_path, scope_stack) {
                     symbols.push(symbol);
                 }
             }
@@ -510,26 +805,115 @@ impl RustParser {
 
         // Recurse into children
         if cursor.goto_first_child() {
-            let mut new_scope = scope_stack.clone();
-            if let "impl_item" | "function_item" = node.kind() {
-                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
-                    new_scope.push(name);
-                }
-            }
+            let new_context = self.enter(node, content, &context, uses);
 
-            self.walk_tree(cursor, content, file_path, symbols, new_scope);
+            self.walk_tree(cursor, content, file_path, symbols, new_context.clone(), uses);
 
             while cursor.goto_next_sibling() {
-                self.walk_tree(cursor, content, file_path, symbols, scope_stack.clone());
+                self.walk_tree(cursor, content, file_path, symbols, new_context.clone(), uses);
             }
 
             cursor.goto_parent();
         }
     }
 
-    fn extract_function(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
+    /// Context of the children of `node`
+    fn enter(&self, node: tree_sitter::Node, content: &str, context: &RustContext, uses: &rust::UseScope) -> RustContext {
+        let mut new_context = context.clone();
+        match node.kind() {
+            "mod_item" => {
+                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
+                    new_context.scope.push(name);
+                    new_context.module = new_context.scope.clone();
+                    new_context.self_type = None;
+                    new_context.trait_path = None;
+                }
+            }
+            "impl_item" => {
+                // Methods are qualified by the path of their type, wherever it is declared
+                if let Some(self_type) = self.type_path(node.child_by_field_name("type"), content, context, uses) {
+                    new_context.scope = self_type.clone();
+                    new_context.self_type = Some(self_type);
+                    new_context.trait_path = self.type_path(node.child_by_field_name("trait"), content, context, uses);
+                }
+            }
+            "trait_item" => {
+                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
+                    new_context.scope.push(name);
+                    new_context.self_type = Some(new_context.scope.clone());
+                    new_context.trait_path = None;
+                }
+            }
+            "function_item" => {
+                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
+                    new_context.scope.push(name);
+                }
+            }
+            _ => {}
+        }
+        new_context
+    }
+
+    /// Absolute path of a type named in an impl, as far as the file's `use`
+    /// declarations tell, e.g. `crate::index::db::IndexDatabase`
+    fn type_path(&self, node: Option<tree_sitter::Node>, content: &str, context: &RustContext, uses: &rust::UseScope) -> Option<Vec<String>> {
+        let segments = rust::path_segments(&self.get_node_text(node, content)?)?;
+        uses.candidates(&context.module, &segments, context.self_type.as_deref())
+            .into_iter()
+            .next()
+    }
+
+    /// One `Import` symbol per `use` declaration, holding the absolute paths it binds
+    fn extract_uses(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &RustContext, symbols: &mut Vec<Symbol>) {
+        if node.kind() == "use_declaration" {
+            let Some(argument) = node.child_by_field_name("argument") else { return };
+            let Some(text) = self.get_node_text(Some(argument), content) else { return };
+            let text = text.split_whitespace().collect::<String>();
+
+            let bindings: Vec<rust::UseBinding> = rust::use_bindings(argument, content)
+                .into_iter()
+                .filter_map(|binding| {
+                    let path = rust::path_segments(&binding.path)?;
+                    let path = rust::absolute_path(&context.module, &path)?;
+                    Some(rust::UseBinding { alias: binding.alias, path: path.join("::") })
+                })
+                .collect();
+
+            symbols.push(Symbol {
+                id: format!("{}:import:{}", file_path, text),
+                kind: SymbolKind::Import,
+                name: text.clone(),
+                qualified_name: text,
+                location: self.node_location(node, file_path),
+                signature: None,
+                type_info: None,
+                visibility: Visibility::Public,
+                language: "rust".to_string(),
+                metadata: serde_json::json!({
+                    "bindings": bindings
+                }),
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            });
+            return;
+        }
+
+        // Only inline modules change the module `self` and `super` refer to
+        let context = if node.kind() == "mod_item" {
+            self.enter(node, content, context, &rust::UseScope::default())
+        } else {
+            context.clone()
+        };
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.extract_uses(child, content, file_path, &context, symbols);
+        }
+    }
+
+    fn extract_function(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &RustContext) -> Option<Symbol> {
         let name_node = node.child_by_field_name("name")?;
         let name = self.get_node_text(Some(name_node), content)?;
+        let scope_stack = &context.scope;
 
         let qualified_name = if scope_stack.is_empty() {
             name.clone()
@@ -557,9 +941,17 @@ impl RustParser {
 
         let signature = format!("fn {}({})", name, parameters.join(", "));
 
+        // Items of an impl or trait body are methods
+        let in_body = node.parent().is_some_and(|parent| parent.kind() == "declaration_list");
+        let kind = if in_body && context.self_type.is_some() {
+            SymbolKind::Method
+        } else {
+            SymbolKind::Function
+        };
+
         Some(Symbol {
             id: format!("{}:{}", file_path, qualified_name),
-            kind: SymbolKind::Function,
+            kind,
             name,
             qualified_name,
             location,
@@ -631,37 +1023,35 @@ impl RustParser {
         })
     }
 
-    fn extract_impl(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
-        let type_node = node.child_by_field_name("type")?;
-        let type_name = self.get_node_text(Some(type_node), content)?;
+    fn extract_trait(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
+        let name_node = node.child_by_field_name("name")?;
+        let name = self.get_node_text(Some(name_node), content)?;
 
         let qualified_name = if scope_stack.is_empty() {
-            format!("impl {}", type_name)
+            name.clone()
         } else {
-            format!("{}::impl {}", scope_stack.join("::"), type_name)
+            format!("{}::{}", scope_stack.join("::"), name)
         };
 
         let location = self.node_location(node, file_path);
 
         Some(Symbol {
             id: format!("{}:{}", file_path, qualified_name),
-            kind: SymbolKind::Context,
-            name: format!("impl {}", type_name),
+            kind: SymbolKind::Type,
+            name,
             qualified_name,
             location,
             signature: None,
             type_info: None,
             visibility: Visibility::Public,
             language: "rust".to_string(),
-            metadata: serde_json::json!({
-                "implements": type_name
-            }),
+            metadata: serde_json::json!({}),
             content_hash: "".to_string(),
             last_indexed: chrono::Utc::now().timestamp(),
         })
     }
 
-    fn extract_trait(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
+    fn extract_mod(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
         let name_node = node.child_by_field_name("name")?;
         let name = self.get_node_text(Some(name_node), content)?;
 
@@ -675,7 +1065,7 @@ impl RustParser {
 
         Some(Symbol {
             id: format!("{}:{}", file_path, qualified_name),
-            kind: SymbolKind::Type,
+            kind: SymbolKind::Module,
             name,
             qualified_name,
             location,
@@ -683,7 +1073,10 @@ impl RustParser {
             type_info: None,
             visibility: Visibility::Public,
             language: "rust".to_string(),
-            metadata: serde_json::json!({}),
+            metadata: serde_json::json!({
+                // `mod name;` declares a module kept in its own file
+                "inline": node.child_by_field_name("body").is_some()
+            }),
             content_hash: "".to_string(),
             last_indexed: chrono::Utc::now().timestamp(),
         })
@@ -746,16 +1139,18 @@ impl RustParser {
     }
 
     fn extract_relationships(&self, tree: &Tree, content: &str, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(tree, content, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, tree: &Tree, content: &str, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
         let mut relationships = Vec::new();
         let root = tree.root_node();
-
-        // Create a map of qualified names to symbol IDs for lookup
-        let symbol_map: HashMap<&str, &Symbol> = symbols.iter()
-            .map(|s| (s.qualified_name.as_str(), s))
-            .collect();
+        let resolver = rust::Resolver::new(symbol_map, file_path);
 
         let mut cursor = root.walk();
-        self.extract_relationships_from_tree(&mut cursor, content, file_path, &symbol_map, &mut relationships, Vec::new());
+        self.extract_relationships_from_tree(&mut cursor, content, file_path, &resolver, &mut relationships, RustContext::new(file_path));
 
         relationships
     }
@@ -765,15 +1160,20 @@ impl RustParser {
         cursor: &mut tree_sitter::TreeCursor,
         content: &str,
         file_path: &str,
-        symbol_map: &HashMap<&str, &Symbol>,
+        resolver: &rust::Resolver,
         relationships: &mut Vec<Relationship>,
-        context_stack: Vec<String>,
+        context: RustContext,
     ) {
         let node = cursor.node();
 
         match node.kind() {
             "call_expression" => {
-                if let Some(rel) = self.extract_call_relationship(node, content, file_path, symbol_map, &context_stack) {
+                if let Some(rel) = self.extract_call_relationship(node, content, file_path, resolver, &context) {
+                    relationships.push(rel);
+                }
+            }
+            "impl_item" => {
+                if let Some(rel) = self.extract_implements_relationship(node, content, file_path, resolver, &context) {
                     relationships.push(rel);
                 }
             }
@@ -782,70 +1182,111 @@ impl RustParser {
 
         // Recurse
         if cursor.goto_first_child() {
-            let mut new_context = context_stack.clone();
-            if let "function_item" = node.kind() {
-                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
-                    new_context.push(name);
-                }
-            }
+            let new_context = self.enter(node, content, &context, resolver.uses());
 
-            self.extract_relationships_from_tree(cursor, content, file_path, symbol_map, relationships, new_context);
+            self.extract_relationships_from_tree(cursor, content, file_path, resolver, relationships, new_context.clone());
 
             while cursor.goto_next_sibling() {
-                let mut sibling_context = context_stack.clone();
-                if let "function_item" = cursor.node().kind() {
-                    if let Some(name) = self.get_node_text(cursor.node().child_by_field_name("name"), content) {
-                        sibling_context.push(name);
-                    }
-                }
-                self.extract_relationships_from_tree(cursor, content, file_path, symbol_map, relationships, sibling_context);
+                self.extract_relationships_from_tree(cursor, content, file_path, resolver, relationships, new_context.clone());
             }
 
             cursor.goto_parent();
         }
     }
 
-    fn extract_call_relationship(
+    /// `Implements` from the type of an `impl Trait for Type` to the trait
+    fn extract_implements_relationship(
         &self,
         node: tree_sitter::Node,
         content: &str,
         file_path: &str,
-        symbol_map: &HashMap<&str, &Symbol>,
-        context_stack: &[String],
+        resolver: &rust::Resolver,
+        context: &RustContext,
     ) -> Option<Relationship> {
-        let function_node = node.child_by_field_name("function")?;
-        let function_name = self.get_node_text(Some(function_node), content)?;
-
-        // Find the symbol being called
-        let called_symbol = symbol_map.values()
-            .find(|s| s.qualified_name == function_name || s.qualified_name.ends_with(&format!("::{}", function_name)));
+        let trait_text = self.get_node_text(node.child_by_field_name("trait"), content)?;
+        let trait_path = rust::path_segments(&trait_text)?;
+        let type_path = rust::path_segments(&self.get_node_text(node.child_by_field_name("type"), content)?)?;
 
-        if let Some(called_symbol) = called_symbol {
-            // Only create relationship if we have a valid calling context
-            if !context_stack.is_empty() {
-                let caller_qualified_name = context_stack.join("::");
-                if let Some(caller_symbol) = symbol_map.get(caller_qualified_name.as_str()) {
-                    let location = self.node_location(node, file_path);
+        // Impls of foreign types have nothing to attach the edge to
+        let implementor = resolver.resolve(&context.module, &type_path, None)?;
+        let location = self.node_location(node, file_path);
 
-                    return Some(Relationship {
-                        from_id: caller_symbol.id.clone(),
-                        to_id: called_symbol.id.clone(),
-                        kind: RelationshipKind::Calls,
-                        location,
-                        metadata: serde_json::json!({}),
-                    });
-                }
-            }
+        match resolver.resolve(&context.module, &trait_path, None) {
+            Some(implemented) => Some(Relationship {
+                from_id: implementor.id.clone(),
+                to_id: implemented.id.clone(),
+                kind: RelationshipKind::Implements,
+                location,
+                metadata: serde_json::json!({}),
+            }),
+            None => Some(Relationship::unresolved(implementor.id.clone(), &trait_path.join("::"), RelationshipKind::Implements, location)),
         }
-        None
-    }
-
-    fn get_node_text(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
-        node.map(|n| content[n.byte_range()].to_string())
     }
 
-    fn node_location(&self, node: tree_sitter::Node, file_path: &str) -> Location {
-        let start = node.start_position();
+    fn extract_call_relationship(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &rust::Resolver,
+        context: &RustContext,
+    ) -> Option<Relationship> {
+        let mut function_node = node.child_by_field_name("function")?;
+        // `parse::<T>()` calls `parse`
+        if function_node.kind() == "generic_function" {
+            function_node = function_node.child_by_field_name("function")?;
+        }
+        let function_name = self.get_node_text(Some(function_node), content)?;
+
+        let called_symbol = match function_node.kind() {
+            "identifier" => resolver.symbol(&format!("{}::{}", context.scope.join("::"), function_name))
+                .or_else(|| resolver.resolve(&context.module, std::slice::from_ref(&function_name), context.self_type.as_deref())),
+            "scoped_identifier" => rust::path_segments(&function_name)
+                .and_then(|path| resolver.resolve(&context.module, &path, context.self_type.as_deref())),
+            "field_expression" => {
+                // Only the type of `self` is known without type inference
+                let receiver = self.get_node_text(function_node.child_by_field_name("value"), content)?;
+                let method = self.get_node_text(function_node.child_by_field_name("field"), content)?;
+                if receiver == "self" {
+                    let method_path = ["Self".to_string(), method.clone()];
+                    resolver.resolve(&context.module, &method_path, context.self_type.as_deref())
+                        .or_else(|| {
+                            // A provided method of the implemented trait
+                            let trait_path = context.trait_path.as_ref()?;
+                            resolver.find(&format!("{}::{}", trait_path.join("::"), method))
+                        })
+                } else {
+                    None
+                }
+            }
+            _ => return None,
+        };
+
+        // Only create relationship if we have a valid calling context
+        let caller_qualified_name = context.scope.join("::");
+        let caller_symbol = resolver.symbol(&caller_qualified_name)
+            .filter(|symbol| matches!(symbol.kind, SymbolKind::Function | SymbolKind::Method))?;
+        let location = self.node_location(node, file_path);
+
+        match called_symbol {
+            Some(called_symbol) => Some(Relationship {
+                from_id: caller_symbol.id.clone(),
+                to_id: called_symbol.id.clone(),
+                kind: RelationshipKind::Calls,
+                location,
+                metadata: serde_json::json!({}),
+            }),
+            // Record the call so it can be re-resolved once a matching symbol appears
+            None => Some(Relationship::unresolved(caller_symbol.id.clone(), &function_name, RelationshipKind::Calls, location)),
+        }
+    }
+
+    fn get_node_text(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
+        node.map(|n| content[n.byte_range()].to_string())
+    }
+
+    fn node_location(&self, node: tree_sitter::Node, file_path: &str) -> Location {
+        let start = node.start_position();
         let end = node.end_position();
 
         Location {
@@ -859,8 +1300,8 @@ impl RustParser {
 }
 
 impl crate::index::Parser for RustParser {
-    fn can_parse(&self, file_path: &str) -> bool {
-        file_path.ends_with(".rs")
+    fn extensions(&self) -> Vec<&str> {
+        vec!["rs"]
     }
 
     fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
@@ -871,21 +1312,36 @@ impl crate::index::Parser for RustParser {
         Ok((symbols, relationships))
     }
 
-    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &std::collections::HashMap<&str, &Symbol>) -> anyhow::Result<Vec<Relationship>> {
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
         let tree = self.parse_tree(content)?;
-        let mut relationships = Vec::new();
-        let root = tree.root_node();
+        Ok(self.extract_relationships_with_table(&tree, content, file_path, global_symbol_map))
+    }
 
-        let mut cursor = root.walk();
-        self.extract_relationships_from_tree(&mut cursor, content, file_path, global_symbol_map, &mut relationships, Vec::new());
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let tree = self.parse_tree(&content)?;
+        let symbols = self.extract_symbols(&tree, &content, file_path);
+        let file_path = file_path.to_string();
 
-        Ok(relationships)
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&tree, &content, &file_path, symbols)))))
     }
 }
 
+/// Function or method whose body is being walked for relationships
+struct GoFunction<'a> {
+    symbol: &'a Symbol,
+    /// Named types of parameters and local variables
+    locals: HashMap<String, String>,
+}
+
 impl GoParser {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Scan string literals for SQL and link the tables they name
+    pub fn with_sql_literals(mut self, enabled: bool) -> Self {
+        self.sql_literals = enabled;
+        self
     }
 
     fn parse_tree(&self, content: &str) -> anyhow::Result<Tree> {
@@ -898,13 +1354,27 @@ impl GoParser {
         Ok(tree)
     }
 
+    /// Import path of the file's package
+    fn package(&self, root: tree_sitter::Node, content: &str, file_path: &str) -> String {
+        let mut cursor = root.walk();
+        let name = root.named_children(&mut cursor)
+            .find(|child| child.kind() == "package_clause")
+            .and_then(|clause| clause.named_child(0))
+            .and_then(|name| self.get_node_text(Some(name), content))
+            .unwrap_or_default();
+        self.modules.package_path(file_path, &name)
+    }
+
     fn extract_symbols(&self, tree: &Tree, content: &str, file_path: &str) -> Vec<Symbol> {
-        let mut symbols = Vec::new();
         let root = tree.root_node();
 
+        // Imports first, types are resolved through them
+        let mut symbols = self.extract_imports(root, content, file_path);
+        let scope = go::FileScope::from_symbols(self.package(root, content, file_path), &symbols);
+
         // Walk the tree to find symbols
         let mut cursor = root.walk();
-        self.walk_tree(&mut cursor, content, file_path, &mut symbols, Vec::new());
+        self.walk_tree(&mut cursor, content, file_path, &mut symbols, vec![scope.package().to_string()], &scope);
 
         symbols
     }
@@ -916,31 +1386,30 @@ impl GoParser {
         file_path: &str,
         symbols: &mut Vec<Symbol>,
         scope_stack: Vec<String>,
+        scope: &go::FileScope,
     ) {
         let node = cursor.node();
 
         match node.kind() {
             "function_declaration" => {
-                if let Some(symbol) = self.extract_function(node, content, file_path, &scope_stack) {
+                if let Some(symbol) = self.extract_function(node, content, file_path, &scope_stack, scope) {
                     symbols.push(symbol);
                 }
             }
             "method_declaration" => {
-                if let Some(symbol) = self.extract_method(node, content, file_path, &scope_stack) {
+                if let Some(symbol) = self.extract_method(node, content, file_path, &scope_stack, scope) {
                     symbols.push(symbol);
                 }
             }
-            "type_declaration" => {
-                if let Some(symbol) = self.extract_type(node, content, file_path, &scope_stack) {
-                    symbols.push(symbol);
-                }
+            "type_spec" | "type_alias" => {
+                symbols.extend(self.extract_type(node, content, file_path, &scope_stack, scope));
             }
             "const_declaration" => {
                 let const_symbols = self.extract_const_declaration(node, content, file_path, &scope_stack);
                 symbols.extend(const_symbols);
             }
             "var_declaration" => {
-                let var_symbols = self.extract_var_declaration(node, content, file_path, &scope_stack);
+                let var_symbols = self.extract_var_declaration(node, content, file_path, &scope_stack, scope);
                 symbols.extend(var_symbols);
             }
             _ => {}
@@ -948,24 +1417,163 @@ impl GoParser {
 
         // Recurse into children
         if cursor.goto_first_child() {
-            let mut new_scope = scope_stack.clone();
-            if let "function_declaration" | "method_declaration" = node.kind() {
-                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
-                    new_scope.push(name);
-                }
-            }
+            let new_scope = self.enter(node, content, &scope_stack);
 
-            self.walk_tree(cursor, content, file_path, symbols, new_scope);
+            self.walk_tree(cursor, content, file_path, symbols, new_scope.clone(), scope);
 
             while cursor.goto_next_sibling() {
-                self.walk_tree(cursor, content, file_path, symbols, scope_stack.clone());
+                self.walk_tree(cursor, content, file_path, symbols, new_scope.clone(), scope);
             }
 
             cursor.goto_parent();
         }
     }
 
-    fn extract_function(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
+    /// Scope of the children of a node: functions push their name, methods
+    /// their receiver type and name
+    fn enter(&self, node: tree_sitter::Node, content: &str, scope_stack: &[String]) -> Vec<String> {
+        let mut new_scope = scope_stack.to_vec();
+        if let "function_declaration" | "method_declaration" = node.kind() {
+            if node.kind() == "method_declaration" {
+                if let Some(receiver) = self.receiver_type(node, content) {
+                    new_scope.push(receiver);
+                }
+            }
+            if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
+                new_scope.push(name);
+            }
+        }
+        new_scope
+    }
+
+    fn extract_imports(&self, root: tree_sitter::Node, content: &str, file_path: &str) -> Vec<Symbol> {
+        let mut specs = Vec::new();
+        let mut cursor = root.walk();
+        for declaration in root.named_children(&mut cursor).filter(|child| child.kind() == "import_declaration") {
+            let mut declaration_cursor = declaration.walk();
+            for child in declaration.named_children(&mut declaration_cursor) {
+                match child.kind() {
+                    "import_spec" => specs.push(child),
+                    "import_spec_list" => {
+                        let mut list_cursor = child.walk();
+                        specs.extend(child.named_children(&mut list_cursor).filter(|spec| spec.kind() == "import_spec"));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        specs.into_iter()
+            .filter_map(|spec| {
+                let path = self.get_node_text(spec.child_by_field_name("path"), content)?;
+                let path = path.trim_matches(|c| c == '"' || c == '`').to_string();
+
+                // A blank import binds nothing but is still a dependency
+                let bindings = match spec.child_by_field_name("name") {
+                    Some(name) if name.kind() == "blank_identifier" => Vec::new(),
+                    Some(name) if name.kind() == "dot" => vec![go::ImportBinding { alias: ".".to_string(), path: path.clone() }],
+                    Some(name) => vec![go::ImportBinding { alias: self.get_node_text(Some(name), content)?, path: path.clone() }],
+                    None => vec![go::ImportBinding { alias: go::default_alias(&path), path: path.clone() }],
+                };
+
+                Some(Symbol {
+                    id: format!("{}:import:{}", file_path, path),
+                    kind: SymbolKind::Import,
+                    name: path.clone(),
+                    qualified_name: path,
+                    location: self.node_location(spec, file_path),
+                    signature: None,
+                    type_info: None,
+                    visibility: Visibility::Public,
+                    language: "go".to_string(),
+                    metadata: serde_json::json!({
+                        "bindings": bindings
+                    }),
+                    content_hash: "".to_string(),
+                    last_indexed: chrono::Utc::now().timestamp(),
+                })
+            })
+            .collect()
+    }
+
+    /// Parameter names and types of a parameter list, one type per name
+    fn parameters(&self, node: Option<tree_sitter::Node>, content: &str) -> (Vec<String>, Vec<String>) {
+        let mut names = Vec::new();
+        let mut types = Vec::new();
+        let Some(node) = node else {
+            return (names, types);
+        };
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            let Some(type_text) = self.get_node_text(child.child_by_field_name("type"), content) else {
+                continue;
+            };
+            let type_text = type_text.split_whitespace().collect::<Vec<_>>().join(" ");
+            let type_text = match child.kind() {
+                "variadic_parameter_declaration" => format!("...{}", type_text),
+                _ => type_text,
+            };
+
+            let mut name_cursor = child.walk();
+            let declared: Vec<String> = child.children_by_field_name("name", &mut name_cursor)
+                .filter_map(|name| self.get_node_text(Some(name), content))
+                .collect();
+            if declared.is_empty() {
+                types.push(type_text);
+                continue;
+            }
+            for name in declared {
+                names.push(name);
+                types.push(type_text.clone());
+            }
+        }
+
+        (names, types)
+    }
+
+    /// Parameter names and the type written without names, e.g. `(string, ...int) (Item, error)`
+    fn function_type(&self, node: tree_sitter::Node, content: &str) -> (Vec<String>, String) {
+        let (names, types) = self.parameters(node.child_by_field_name("parameters"), content);
+        let mut type_info = format!("({})", types.join(", "));
+
+        match node.child_by_field_name("result") {
+            Some(result) if result.kind() == "parameter_list" => {
+                let (_, results) = self.parameters(Some(result), content);
+                match results.as_slice() {
+                    [] => {}
+                    [single] => type_info = format!("{} {}", type_info, single),
+                    results => type_info = format!("{} ({})", type_info, results.join(", ")),
+                }
+            }
+            Some(result) => {
+                if let Some(result) = self.get_node_text(Some(result), content) {
+                    type_info = format!("{} {}", type_info, result);
+                }
+            }
+            None => {}
+        }
+
+        (names, type_info)
+    }
+
+    /// Named type of the first result, which the value of a call has
+    fn result_type(&self, node: tree_sitter::Node, content: &str, scope: &go::FileScope) -> Option<String> {
+        let result = node.child_by_field_name("result")?;
+        let type_node = match result.kind() {
+            "parameter_list" => result.named_child(0)?.child_by_field_name("type")?,
+            _ => result,
+        };
+        scope.type_ref(type_node, content).map(|ty| ty.name)
+    }
+
+    /// Declaration text up to the body, e.g. `func (s *Store) Get(key string) error`
+    fn declaration_signature(&self, node: tree_sitter::Node, content: &str) -> String {
+        let end = node.child_by_field_name("body").map_or(node.end_byte(), |body| body.start_byte());
+        content[node.start_byte()..end].split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    fn extract_function(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String], scope: &go::FileScope) -> Option<Symbol> {
         let name_node = node.child_by_field_name("name")?;
         let name = self.get_node_text(Some(name_node), content)?;
 
@@ -976,24 +1584,7 @@ impl GoParser {
         };
 
         let location = self.node_location(node, file_path);
-
-        // Extract parameters
-        let parameters_node = node.child_by_field_name("parameters");
-        let mut parameters = Vec::new();
-        if let Some(params) = parameters_node {
-            let mut cursor = params.walk();
-            for child in params.children(&mut cursor) {
-                if child.kind() == "parameter_declaration" {
-                    if let Some(param_node) = child.child_by_field_name("name") {
-                        if let Some(param_name) = self.get_node_text(Some(param_node), content) {
-                            parameters.push(param_name);
-                        }
-                    }
-                }
-            }
-        }
-
-        let signature = format!("func {}({})", name, parameters.join(", "));
+        let (parameters, type_info) = self.function_type(node, content);
 
         Some(Symbol {
             id: format!("{}:{}", file_path, qualified_name),
@@ -1001,51 +1592,48 @@ impl GoParser {
             name,
             qualified_name,
             location,
-            signature: Some(signature),
-            type_info: None,
+            signature: Some(self.declaration_signature(node, content)),
+            type_info: Some(type_info),
             visibility: Visibility::Public, // Go has package-level visibility
             language: "go".to_string(),
             metadata: serde_json::json!({
-                "parameters": parameters
+                "parameters": parameters,
+                "type": self.result_type(node, content, scope)
             }),
             content_hash: "".to_string(),
             last_indexed: chrono::Utc::now().timestamp(),
         })
     }
 
-    fn extract_method(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
+    /// Receiver type node of a method, without the pointer
+    fn receiver_type_node<'t>(&self, node: tree_sitter::Node<'t>) -> Option<(tree_sitter::Node<'t>, bool)> {
+        let receiver = node.child_by_field_name("receiver")?.named_child(0)?;
+        let mut type_node = receiver.child_by_field_name("type")?;
+        let pointer = type_node.kind() == "pointer_type";
+        if pointer {
+            type_node = type_node.named_child(0)?;
+        }
+        if type_node.kind() == "generic_type" {
+            type_node = type_node.child_by_field_name("type")?;
+        }
+        Some((type_node, pointer))
+    }
+
+    fn receiver_type(&self, node: tree_sitter::Node, content: &str) -> Option<String> {
+        self.get_node_text(Some(self.receiver_type_node(node)?.0), content)
+    }
+
+    fn extract_method(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String], scope: &go::FileScope) -> Option<Symbol> {
         let name_node = node.child_by_field_name("name")?;
         let name = self.get_node_text(Some(name_node), content)?;
 
-        // Get receiver type
-        let receiver_node = node.child_by_field_name("receiver")?;
+        // Methods are qualified by their receiver type, `pkg.Type.Method`
+        let (receiver_node, pointer) = self.receiver_type_node(node)?;
         let receiver_type = self.get_node_text(Some(receiver_node), content)?;
 
-        let qualified_name = if scope_stack.is_empty() {
-            format!("{}::{}", receiver_type, name)
-        } else {
-            format!("{}.{}::{}", scope_stack.join("."), receiver_type, name)
-        };
-
+        let qualified_name = format!("{}.{}.{}", scope_stack.join("."), receiver_type, name);
         let location = self.node_location(node, file_path);
-
-        // Extract parameters
-        let parameters_node = node.child_by_field_name("parameters");
-        let mut parameters = Vec::new();
-        if let Some(params) = parameters_node {
-            let mut cursor = params.walk();
-            for child in params.children(&mut cursor) {
-                if child.kind() == "parameter_declaration" {
-                    if let Some(param_node) = child.child_by_field_name("name") {
-                        if let Some(param_name) = self.get_node_text(Some(param_node), content) {
-                            parameters.push(param_name);
-                        }
-                    }
-                }
-            }
-        }
-
-        let signature = format!("func ({} {}) {}({})", receiver_node, receiver_type, name, parameters.join(", "));
+        let (parameters, type_info) = self.function_type(node, content);
 
         Some(Symbol {
             id: format!("{}:{}", file_path, qualified_name),
@@ -1053,30 +1641,28 @@ impl GoParser {
             name,
             qualified_name,
             location,
-            signature: Some(signature),
-            type_info: Some(receiver_type.clone()),
+            signature: Some(self.declaration_signature(node, content)),
+            type_info: Some(type_info),
             visibility: Visibility::Public,
             language: "go".to_string(),
             metadata: serde_json::json!({
                 "receiver": receiver_type,
-                "parameters": parameters
+                "pointer": pointer,
+                "parameters": parameters,
+                "type": self.result_type(node, content, scope)
             }),
             content_hash: "".to_string(),
             last_indexed: chrono::Utc::now().timestamp(),
         })
     }
 
-    fn extract_type(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
-        let name_node = node.child_by_field_name("name")?;
-        let name = self.get_node_text(Some(name_node), content)?;
-
-        let type_node = node.child_by_field_name("type")?;
-        let type_kind = if type_node.kind() == "struct_type" {
-            SymbolKind::Class // Go structs are similar to classes
-        } else if type_node.kind() == "interface_type" {
-            SymbolKind::Type
-        } else {
-            SymbolKind::Type
+    /// A type declaration, plus the fields of a struct and the methods of an interface
+    fn extract_type(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String], scope: &go::FileScope) -> Vec<Symbol> {
+        let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) else {
+            return Vec::new();
+        };
+        let Some(type_node) = node.child_by_field_name("type") else {
+            return Vec::new();
         };
 
         let qualified_name = if scope_stack.is_empty() {
@@ -1085,9 +1671,36 @@ impl GoParser {
             format!("{}.{}", scope_stack.join("."), name)
         };
 
+        let mut members = Vec::new();
+        let mut embedded = Vec::new();
+        let mut metadata = serde_json::Map::new();
+        let (type_kind, kind) = match type_node.kind() {
+            _ if node.kind() == "type_alias" => {
+                metadata.insert("alias".to_string(), scope.type_ref(type_node, content).map(|ty| ty.name).into());
+                (SymbolKind::Type, "alias")
+            }
+            "struct_type" => {
+                members = self.extract_fields(type_node, content, file_path, &qualified_name, scope, &mut embedded);
+                (SymbolKind::Class, "struct") // Go structs are similar to classes
+            }
+            "interface_type" => {
+                let mut constraint = false;
+                members = self.extract_interface_methods(type_node, content, file_path, &qualified_name, scope, &mut embedded, &mut constraint);
+                if constraint {
+                    metadata.insert("constraint".to_string(), true.into());
+                }
+                (SymbolKind::Type, "interface")
+            }
+            _ => (SymbolKind::Type, "defined"),
+        };
+        metadata.insert("kind".to_string(), kind.into());
+        if !embedded.is_empty() {
+            metadata.insert("embedded".to_string(), embedded.into());
+        }
+
         let location = self.node_location(node, file_path);
 
-        Some(Symbol {
+        let mut symbols = vec![Symbol {
             id: format!("{}:{}", file_path, qualified_name),
             kind: type_kind,
             name,
@@ -1097,10 +1710,128 @@ impl GoParser {
             type_info: None,
             visibility: Visibility::Public,
             language: "go".to_string(),
-            metadata: serde_json::json!({}),
+            metadata: serde_json::Value::Object(metadata),
             content_hash: "".to_string(),
             last_indexed: chrono::Utc::now().timestamp(),
-        })
+        }];
+        symbols.extend(members);
+        symbols
+    }
+
+    fn extract_fields(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        type_name: &str,
+        scope: &go::FileScope,
+        embedded: &mut Vec<serde_json::Value>,
+    ) -> Vec<Symbol> {
+        let mut fields = Vec::new();
+        let Some(list) = node.named_child(0) else {
+            return fields;
+        };
+
+        let mut cursor = list.walk();
+        for declaration in list.named_children(&mut cursor).filter(|child| child.kind() == "field_declaration") {
+            let Some(type_node) = declaration.child_by_field_name("type") else { continue };
+            let type_ref = scope.type_ref(type_node, content);
+            let type_text = self.get_node_text(Some(type_node), content);
+
+            let mut name_cursor = declaration.walk();
+            let mut names: Vec<String> = declaration.children_by_field_name("name", &mut name_cursor)
+                .filter_map(|name| self.get_node_text(Some(name), content))
+                .collect();
+
+            // An embedded field is named after its type
+            let is_embedded = names.is_empty();
+            if is_embedded {
+                let Some(ty) = &type_ref else { continue };
+                embedded.push(serde_json::json!({ "type": ty.name, "pointer": ty.pointer }));
+                names.push(ty.name.rsplit('.').next().unwrap_or(&ty.name).to_string());
+            }
+
+            for name in names {
+                let qualified_name = format!("{}.{}", type_name, name);
+                fields.push(Symbol {
+                    id: format!("{}:{}", file_path, qualified_name),
+                    kind: SymbolKind::Field,
+                    name,
+                    qualified_name,
+                    location: self.node_location(declaration, file_path),
+                    signature: None,
+                    type_info: type_text.clone(),
+                    visibility: Visibility::Public,
+                    language: "go".to_string(),
+                    metadata: serde_json::json!({
+                        "type": type_ref.as_ref().map(|ty| ty.name.clone()),
+                        "embedded": is_embedded
+                    }),
+                    content_hash: "".to_string(),
+                    last_indexed: chrono::Utc::now().timestamp(),
+                });
+            }
+        }
+
+        fields
+    }
+
+    /// Methods of an interface; embedded interfaces are recorded, and any
+    /// other type element makes it a constraint that no value can satisfy
+    #[allow(clippy::too_many_arguments)]
+    fn extract_interface_methods(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        type_name: &str,
+        scope: &go::FileScope,
+        embedded: &mut Vec<serde_json::Value>,
+        constraint: &mut bool,
+    ) -> Vec<Symbol> {
+        let mut methods = Vec::new();
+        let mut cursor = node.walk();
+        for element in node.named_children(&mut cursor) {
+            match element.kind() {
+                "method_elem" => {
+                    let Some(name) = self.get_node_text(element.child_by_field_name("name"), content) else { continue };
+                    let qualified_name = format!("{}.{}", type_name, name);
+                    let (parameters, type_info) = self.function_type(element, content);
+
+                    methods.push(Symbol {
+                        id: format!("{}:{}", file_path, qualified_name),
+                        kind: SymbolKind::Method,
+                        name,
+                        qualified_name,
+                        location: self.node_location(element, file_path),
+                        signature: Some(self.declaration_signature(element, content)),
+                        type_info: Some(type_info),
+                        visibility: Visibility::Public,
+                        language: "go".to_string(),
+                        metadata: serde_json::json!({
+                            "interface": true,
+                            "parameters": parameters,
+                            "type": self.result_type(element, content, scope)
+                        }),
+                        content_hash: "".to_string(),
+                        last_indexed: chrono::Utc::now().timestamp(),
+                    });
+                }
+                "type_elem" => {
+                    let embedded_type = match element.named_child_count() {
+                        1 => element.named_child(0).and_then(|ty| scope.type_ref(ty, content)),
+                        _ => None,
+                    };
+                    match embedded_type {
+                        Some(ty) => embedded.push(serde_json::json!({ "type": ty.name, "pointer": false })),
+                        None => *constraint = true,
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        methods
     }
 
     fn extract_const_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Vec<Symbol> {
@@ -1110,34 +1841,32 @@ impl GoParser {
         for child in node.children(&mut cursor) {
             if child.kind() == "const_spec" {
                 let mut spec_cursor = child.walk();
-                for spec_child in child.children(&mut spec_cursor) {
-                    if spec_child.kind() == "identifier" {
-                        if let Some(name) = self.get_node_text(Some(spec_child), content) {
-                            let qualified_name = if scope_stack.is_empty() {
-                                name.clone()
-                            } else {
-                                format!("{}.{}", scope_stack.join("."), name)
-                            };
+                for spec_child in child.children_by_field_name("name", &mut spec_cursor) {
+                    if let Some(name) = self.get_node_text(Some(spec_child), content) {
+                        let qualified_name = if scope_stack.is_empty() {
+                            name.clone()
+                        } else {
+                            format!("{}.{}", scope_stack.join("."), name)
+                        };
 
-                            let location = self.node_location(spec_child, file_path);
-
-                            symbols.push(Symbol {
-                                id: format!("{}:{}", file_path, qualified_name),
-                                kind: SymbolKind::Variable,
-                                name,
-                                qualified_name,
-                                location,
-                                signature: None,
-                                type_info: None,
-                                visibility: Visibility::Public,
-                                language: "go".to_string(),
-                                metadata: serde_json::json!({
-                                    "const": true
-                                }),
-                                content_hash: "".to_string(),
-                                last_indexed: chrono::Utc::now().timestamp(),
-                            });
-                        }
+                        let location = self.node_location(spec_child, file_path);
+
+                        symbols.push(Symbol {
+                            id: format!("{}:{}", file_path, qualified_name),
+                            kind: SymbolKind::Variable,
+                            name,
+                            qualified_name,
+                            location,
+                            signature: None,
+                            type_info: None,
+                            visibility: Visibility::Public,
+                            language: "go".to_string(),
+                            metadata: serde_json::json!({
+                                "const": true
+                            }),
+                            content_hash: "".to_string(),
+                            last_indexed: chrono::Utc::now().timestamp(),
+                        });
                     }
                 }
             }
@@ -1146,41 +1875,52 @@ impl GoParser {
         symbols
     }
 
-    fn extract_var_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Vec<Symbol> {
+    fn extract_var_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String], scope: &go::FileScope) -> Vec<Symbol> {
         let mut symbols = Vec::new();
 
         let mut cursor = node.walk();
-        for child in node.children(&mut cursor) {
-            if child.kind() == "var_spec" {
-                let mut spec_cursor = child.walk();
-                for spec_child in child.children(&mut spec_cursor) {
-                    if spec_child.kind() == "identifier" {
-                        if let Some(name) = self.get_node_text(Some(spec_child), content) {
-                            let qualified_name = if scope_stack.is_empty() {
-                                name.clone()
-                            } else {
-                                format!("{}.{}", scope_stack.join("."), name)
-                            };
+        for child in node.named_children(&mut cursor) {
+            let specs: Vec<tree_sitter::Node> = match child.kind() {
+                "var_spec" => vec![child],
+                "var_spec_list" => {
+                    let mut list_cursor = child.walk();
+                    child.named_children(&mut list_cursor).filter(|spec| spec.kind() == "var_spec").collect()
+                }
+                _ => continue,
+            };
 
-                            let location = self.node_location(spec_child, file_path);
-
-                            symbols.push(Symbol {
-                                id: format!("{}:{}", file_path, qualified_name),
-                                kind: SymbolKind::Variable,
-                                name,
-                                qualified_name,
-                                location,
-                                signature: None,
-                                type_info: None,
-                                visibility: Visibility::Public,
-                                language: "go".to_string(),
-                                metadata: serde_json::json!({
-                                    "var": true
-                                }),
-                                content_hash: "".to_string(),
-                                last_indexed: chrono::Utc::now().timestamp(),
-                            });
-                        }
+            for spec in specs {
+                let type_node = spec.child_by_field_name("type");
+                let type_ref = type_node.and_then(|ty| scope.type_ref(ty, content));
+
+                let mut spec_cursor = spec.walk();
+                for spec_child in spec.children_by_field_name("name", &mut spec_cursor) {
+                    if let Some(name) = self.get_node_text(Some(spec_child), content) {
+                        let qualified_name = if scope_stack.is_empty() {
+                            name.clone()
+                        } else {
+                            format!("{}.{}", scope_stack.join("."), name)
+                        };
+
+                        let location = self.node_location(spec_child, file_path);
+
+                        symbols.push(Symbol {
+                            id: format!("{}:{}", file_path, qualified_name),
+                            kind: SymbolKind::Variable,
+                            name,
+                            qualified_name,
+                            location,
+                            signature: None,
+                            type_info: self.get_node_text(type_node, content),
+                            visibility: Visibility::Public,
+                            language: "go".to_string(),
+                            metadata: serde_json::json!({
+                                "var": true,
+                                "type": type_ref.as_ref().map(|ty| ty.name.clone())
+                            }),
+                            content_hash: "".to_string(),
+                            last_indexed: chrono::Utc::now().timestamp(),
+                        });
                     }
                 }
             }
@@ -1190,62 +1930,244 @@ impl GoParser {
     }
 
     fn extract_relationships(&self, tree: &Tree, content: &str, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(tree, content, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, tree: &Tree, content: &str, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
         let mut relationships = Vec::new();
         let root = tree.root_node();
-
-        // Create a map of qualified names to symbol IDs for lookup
-        let symbol_map: HashMap<&str, &Symbol> = symbols.iter()
-            .map(|s| (s.qualified_name.as_str(), s))
-            .collect();
-
-        let mut cursor = root.walk();
-        self.extract_relationships_from_tree(&mut cursor, content, file_path, &symbol_map, &mut relationships, Vec::new());
+        let package = self.package(root, content, file_path);
+        let resolver = go::Resolver::new(symbol_map, file_path, package.clone());
+
+        self.extract_relationships_from_tree(root, content, file_path, &resolver, &mut relationships, &[package], None);
+        relationships.extend(self.import_relationships(file_path, symbol_map));
+        relationships.extend(ProtobufParser::stub_relationships(content, file_path, symbol_map));
+        if self.sql_literals {
+            relationships.extend(SqlParser::literal_relationships(root, content, file_path, &["interpreted_string_literal", "raw_string_literal"], symbol_map));
+        }
 
         relationships
     }
 
+    /// `DependsOn` edges from each import spec to a symbol of the imported
+    /// package, which stands for the package in file and package cycles
+    fn import_relationships(&self, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        symbol_map.in_file(file_path)
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Import)
+            .filter_map(|import| {
+                let target = symbol_map.children(&import.qualified_name)
+                    .iter()
+                    .find(|symbol| symbol.language == "go" && symbol.kind != SymbolKind::Import)?;
+                Some(Relationship {
+                    from_id: import.id.clone(),
+                    to_id: target.id.clone(),
+                    kind: RelationshipKind::DependsOn,
+                    location: import.location.clone(),
+                    metadata: serde_json::json!({ "import": true }),
+                })
+            })
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn extract_relationships_from_tree(
         &self,
-        cursor: &mut tree_sitter::TreeCursor,
+        node: tree_sitter::Node,
         content: &str,
         file_path: &str,
-        symbol_map: &HashMap<&str, &Symbol>,
+        resolver: &go::Resolver,
         relationships: &mut Vec<Relationship>,
-        context_stack: Vec<String>,
+        scope_stack: &[String],
+        function: Option<&GoFunction>,
     ) {
-        let node = cursor.node();
-
         match node.kind() {
             "call_expression" => {
-                if let Some(rel) = self.extract_call_relationship(node, content, file_path, symbol_map, &context_stack) {
-                    relationships.push(rel);
+                if let Some(function) = function {
+                    if let Some(rel) = self.extract_call_relationship(node, content, file_path, resolver, function) {
+                        relationships.push(rel);
+                    }
                 }
             }
+            "type_spec" => {
+                relationships.extend(self.extract_type_relationships(node, content, file_path, resolver, scope_stack));
+            }
             _ => {}
         }
 
         // Recurse
-        if cursor.goto_first_child() {
-            let mut new_context = context_stack.clone();
-            if let "function_declaration" | "method_declaration" = node.kind() {
-                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
-                    new_context.push(name);
-                }
-            }
-
-            self.extract_relationships_from_tree(cursor, content, file_path, symbol_map, relationships, new_context);
+        let new_scope = self.enter(node, content, scope_stack);
+        let entered = match node.kind() {
+            "function_declaration" | "method_declaration" => resolver.symbol(&new_scope.join(".")).map(|symbol| GoFunction {
+                symbol,
+                locals: self.locals(node, content, resolver),
+            }),
+            _ => None,
+        };
+        let function = entered.as_ref().or(function);
 
-            while cursor.goto_next_sibling() {
-                let mut sibling_context = context_stack.clone();
-                if let "function_declaration" | "method_declaration" = cursor.node().kind() {
-                    if let Some(name) = self.get_node_text(cursor.node().child_by_field_name("name"), content) {
-                        sibling_context.push(name);
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.extract_relationships_from_tree(child, content, file_path, resolver, relationships, &new_scope, function);
+        }
+    }
+
+    /// `Implements` from a named type to each interface in the index its
+    /// method set satisfies, and `Extends` from an interface to the ones it embeds
+    fn extract_type_relationships(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &go::Resolver,
+        scope_stack: &[String],
+    ) -> Vec<Relationship> {
+        let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) else {
+            return Vec::new();
+        };
+        let Some(ty) = resolver.symbol(&format!("{}.{}", scope_stack.join("."), name)) else {
+            return Vec::new();
+        };
+        let location = self.node_location(node, file_path);
+
+        if go::is_interface(ty) {
+            return ty.metadata["embedded"].as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|embedded| embedded["type"].as_str())
+                .map(|embedded| match resolver.find(embedded) {
+                    Some(parent) => Relationship {
+                        from_id: ty.id.clone(),
+                        to_id: parent.id.clone(),
+                        kind: RelationshipKind::Extends,
+                        location: location.clone(),
+                        metadata: serde_json::json!({}),
+                    },
+                    None => Relationship::unresolved(ty.id.clone(), embedded, RelationshipKind::Extends, location.clone()),
+                })
+                .collect();
+        }
+
+        resolver.implemented_interfaces(ty)
+            .into_iter()
+            .map(|(interface, pointer)| Relationship {
+                from_id: ty.id.clone(),
+                to_id: interface.id.clone(),
+                kind: RelationshipKind::Implements,
+                location: location.clone(),
+                // Only `*T` has the pointer receiver methods
+                metadata: serde_json::json!({
+                    "pointer": pointer
+                }),
+            })
+            .collect()
+    }
+
+    /// Named types of the receiver, parameters and variables of a function,
+    /// in declaration order so that `x := NewStore()` can use earlier ones
+    fn locals(&self, node: tree_sitter::Node, content: &str, resolver: &go::Resolver) -> HashMap<String, String> {
+        let mut locals = HashMap::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            match node.kind() {
+                "parameter_declaration" | "variadic_parameter_declaration" | "var_spec" => {
+                    let type_name = match node.child_by_field_name("type") {
+                        Some(ty) => resolver.scope().type_ref(ty, content).map(|ty| ty.name),
+                        None => node.child_by_field_name("value")
+                            .and_then(|values| values.named_child(0))
+                            .and_then(|value| self.expression_type(value, content, resolver, &locals)),
+                    };
+                    let mut cursor = node.walk();
+                    for name in node.children_by_field_name("name", &mut cursor) {
+                        if let (Some(name), Some(type_name)) = (self.get_node_text(Some(name), content), &type_name) {
+                            locals.insert(name, type_name.clone());
+                        }
+                    }
+                }
+                "short_var_declaration" => {
+                    let left = node.child_by_field_name("left");
+                    let right = node.child_by_field_name("right");
+                    if let (Some(left), Some(right)) = (left, right) {
+                        let mut right_cursor = right.walk();
+                        let values: Vec<_> = right.named_children(&mut right_cursor).collect();
+                        let mut cursor = left.walk();
+                        for (i, name) in left.named_children(&mut cursor).enumerate() {
+                            // `x, err := f()` gives `x` the first result
+                            let value = match values.len() {
+                                1 => values.first().filter(|_| i == 0),
+                                _ => values.get(i),
+                            };
+                            let type_name = value.and_then(|value| self.expression_type(*value, content, resolver, &locals));
+                            if let (Some(name), Some(type_name)) = (self.get_node_text(Some(name), content), type_name) {
+                                locals.insert(name, type_name);
+                            }
+                        }
                     }
                 }
-                self.extract_relationships_from_tree(cursor, content, file_path, symbol_map, relationships, sibling_context);
+                _ => {}
             }
 
-            cursor.goto_parent();
+            let mut cursor = node.walk();
+            let children: Vec<_> = node.named_children(&mut cursor).collect();
+            stack.extend(children.into_iter().rev());
+        }
+        locals
+    }
+
+    /// Named type of an expression, as far as it can be known without type checking
+    fn expression_type(&self, node: tree_sitter::Node, content: &str, resolver: &go::Resolver, locals: &HashMap<String, String>) -> Option<String> {
+        match node.kind() {
+            "identifier" => {
+                let name = self.get_node_text(Some(node), content)?;
+                match locals.get(&name) {
+                    Some(type_name) => Some(type_name.clone()),
+                    None => resolver.type_of(resolver.package_member(&name)?),
+                }
+            }
+            "composite_literal" | "type_assertion_expression" | "type_conversion_expression" => {
+                resolver.scope().type_ref(node.child_by_field_name("type")?, content).map(|ty| ty.name)
+            }
+            "unary_expression" | "parenthesized_expression" => {
+                let operand = node.child_by_field_name("operand").or_else(|| node.named_child(0))?;
+                self.expression_type(operand, content, resolver, locals)
+            }
+            "call_expression" => {
+                let callee = self.callee(node.child_by_field_name("function")?, content, resolver, locals)?;
+                resolver.type_of(callee)
+            }
+            "selector_expression" => resolver.type_of(self.callee(node, content, resolver, locals)?),
+            _ => None,
+        }
+    }
+
+    /// Symbol named by the function expression of a call, or by a selector
+    fn callee<'a>(&self, node: tree_sitter::Node, content: &str, resolver: &go::Resolver<'_, 'a>, locals: &HashMap<String, String>) -> Option<&'a Symbol> {
+        match node.kind() {
+            "identifier" => {
+                let name = self.get_node_text(Some(node), content)?;
+                // A local holding a function value shadows package-level names
+                if locals.contains_key(&name) {
+                    return None;
+                }
+                resolver.package_member(&name)
+            }
+            "selector_expression" => {
+                let operand = node.child_by_field_name("operand")?;
+                let field = self.get_node_text(node.child_by_field_name("field"), content)?;
+
+                if operand.kind() == "identifier" {
+                    let name = self.get_node_text(Some(operand), content)?;
+                    if !locals.contains_key(&name) && resolver.scope().import(&name).is_some() {
+                        return resolver.imported(&name, &field);
+                    }
+                }
+                let type_name = self.expression_type(operand, content, resolver, locals)?;
+                resolver.member(&type_name, &field)
+            }
+            "parenthesized_expression" => self.callee(node.named_child(0)?, content, resolver, locals),
+            _ => None,
         }
     }
 
@@ -1254,34 +2176,29 @@ impl GoParser {
         node: tree_sitter::Node,
         content: &str,
         file_path: &str,
-        symbol_map: &HashMap<&str, &Symbol>,
-        context_stack: &[String],
+        resolver: &go::Resolver,
+        function: &GoFunction,
     ) -> Option<Relationship> {
         let function_node = node.child_by_field_name("function")?;
         let function_name = self.get_node_text(Some(function_node), content)?;
 
-        // Find the symbol being called
-        let called_symbol = symbol_map.values()
-            .find(|s| s.qualified_name == function_name || s.qualified_name.ends_with(&format!(".{}", function_name)));
-
-        if let Some(called_symbol) = called_symbol {
-            // Only create relationship if we have a valid calling context
-            if !context_stack.is_empty() {
-                let caller_qualified_name = context_stack.join(".");
-                if let Some(caller_symbol) = symbol_map.get(caller_qualified_name.as_str()) {
-                    let location = self.node_location(node, file_path);
+        let called_symbol = self.callee(function_node, content, resolver, &function.locals);
+        let location = self.node_location(node, file_path);
 
-                    return Some(Relationship {
-                        from_id: caller_symbol.id.clone(),
-                        to_id: called_symbol.id.clone(),
-                        kind: RelationshipKind::Calls,
-                        location,
-                        metadata: serde_json::json!({}),
-                    });
-                }
-            }
+        match called_symbol {
+            Some(called_symbol) if matches!(called_symbol.kind, SymbolKind::Function | SymbolKind::Method) => Some(Relationship {
+                from_id: function.symbol.id.clone(),
+                to_id: called_symbol.id.clone(),
+                kind: RelationshipKind::Calls,
+                location,
+                metadata: serde_json::json!({}),
+            }),
+            // Conversions such as `Celsius(x)` and calls of function values
+            Some(_) => None,
+            None if function_node.kind() == "identifier" && go::is_builtin_function(&function_name) => None,
+            // Record the call so it can be re-resolved once a matching symbol appears
+            None => Some(Relationship::unresolved(function.symbol.id.clone(), &function_name, RelationshipKind::Calls, location)),
         }
-        None
     }
 
     fn get_node_text(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
@@ -1303,8 +2220,12 @@ impl GoParser {
 }
 
 impl crate::index::Parser for GoParser {
-    fn can_parse(&self, file_path: &str) -> bool {
-        file_path.ends_with(".go")
+    fn extensions(&self) -> Vec<&str> {
+        vec!["go"]
+    }
+
+    fn clear_caches(&self) {
+        self.modules.clear();
     }
 
     fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
@@ -1315,21 +2236,70 @@ impl crate::index::Parser for GoParser {
         Ok((symbols, relationships))
     }
 
-    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &std::collections::HashMap<&str, &Symbol>) -> anyhow::Result<Vec<Relationship>> {
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
         let tree = self.parse_tree(content)?;
-        let mut relationships = Vec::new();
-        let root = tree.root_node();
+        Ok(self.extract_relationships_with_table(&tree, content, file_path, global_symbol_map))
+    }
 
-        let mut cursor = root.walk();
-        self.extract_relationships_from_tree(&mut cursor, content, file_path, global_symbol_map, &mut relationships, Vec::new());
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let tree = self.parse_tree(&content)?;
+        let symbols = self.extract_symbols(&tree, &content, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&tree, &content, &file_path, symbols)))))
+    }
+}
+
+/// Enclosing declarations while walking a Java file
+#[derive(Debug, Clone, Default)]
+struct JavaContext {
+    /// Qualified name segments of the enclosing declaration, starting with the package
+    scope: Vec<String>,
+    /// Qualified names of the enclosing types, innermost last
+    classes: Vec<String>,
+}
+
+impl JavaContext {
+    fn new(package: &str) -> Self {
+        Self {
+            scope: if package.is_empty() { Vec::new() } else { vec![package.to_string()] },
+            classes: Vec::new(),
+        }
+    }
 
-        Ok(relationships)
+    fn qualify(&self, name: &str) -> String {
+        if self.scope.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.scope.join("."), name)
+        }
     }
 }
 
+/// Method or constructor whose body is being walked for relationships
+struct JavaMethod<'a> {
+    symbol: &'a Symbol,
+    /// Erased declared type and resolved type of parameters and locals
+    locals: HashMap<String, (String, Option<&'a Symbol>)>,
+}
+
+const JAVA_TYPE_DECLARATIONS: &[&str] = &[
+    "class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "record_declaration",
+    "annotation_type_declaration",
+];
+
 impl JavaParser {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Scan string literals for SQL and link the tables they name
+    pub fn with_sql_literals(mut self, enabled: bool) -> Self {
+        self.sql_literals = enabled;
+        self
     }
 
     fn parse_tree(&self, content: &str) -> anyhow::Result<Tree> {
@@ -1342,13 +2312,54 @@ impl JavaParser {
         Ok(tree)
     }
 
+    fn package(&self, root: tree_sitter::Node, content: &str) -> String {
+        let mut cursor = root.walk();
+        let Some(declaration) = root.named_children(&mut cursor).find(|child| child.kind() == "package_declaration") else {
+            return String::new();
+        };
+
+        let mut declaration_cursor = declaration.walk();
+        let name = declaration.named_children(&mut declaration_cursor)
+            .find(|child| matches!(child.kind(), "scoped_identifier" | "identifier"));
+        self.get_node_text(name, content)
+            .map(|name| name.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+
+    /// Qualified names of every type declared in the file
+    fn declared_types(&self, node: tree_sitter::Node, content: &str, context: &JavaContext, declared: &mut HashSet<String>) {
+        let context = self.enter(node, content, context);
+        if JAVA_TYPE_DECLARATIONS.contains(&node.kind()) {
+            if let Some(class) = context.classes.last() {
+                declared.insert(class.clone());
+            }
+        }
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.declared_types(child, content, &context, declared);
+        }
+    }
+
+    /// File scope shared by symbol and relationship extraction
+    fn file_scope(&self, root: tree_sitter::Node, content: &str, imports: &[Symbol]) -> (JavaContext, java::FileScope) {
+        let package = self.package(root, content);
+        let context = JavaContext::new(&package);
+        let mut declared = HashSet::new();
+        self.declared_types(root, content, &context, &mut declared);
+        (context, java::FileScope::from_symbols(package, imports, declared))
+    }
+
     fn extract_symbols(&self, tree: &Tree, content: &str, file_path: &str) -> Vec<Symbol> {
-        let mut symbols = Vec::new();
         let root = tree.root_node();
 
+        // Imports first, types are resolved through them
+        let mut symbols = self.extract_imports(root, content, file_path);
+        let (context, scope) = self.file_scope(root, content, &symbols);
+
         // Walk the tree to find symbols
         let mut cursor = root.walk();
-        self.walk_tree(&mut cursor, content, file_path, &mut symbols, Vec::new());
+        self.walk_tree(&mut cursor, content, file_path, &mut symbols, context, &scope);
 
         symbols
     }
@@ -1359,37 +2370,36 @@ impl JavaParser {
         content: &str,
         file_path: &str,
         symbols: &mut Vec<Symbol>,
-        scope_stack: Vec<String>,
+        context: JavaContext,
+        scope: &java::FileScope,
     ) {
         let node = cursor.node();
 
         match node.kind() {
-            "class_declaration" => {
-                if let Some(symbol) = self.extract_class(node, content, file_path, &scope_stack) {
-                    symbols.push(symbol);
-                }
+            kind if JAVA_TYPE_DECLARATIONS.contains(&kind) => {
+                symbols.extend(self.extract_type_declaration(node, content, file_path, &context, scope));
             }
-            "interface_declaration" => {
-                if let Some(symbol) = self.extract_interface(node, content, file_path, &scope_stack) {
+            "method_declaration" | "annotation_type_element_declaration" => {
+                if let Some(symbol) = self.extract_method(node, content, file_path, &context, scope) {
                     symbols.push(symbol);
                 }
             }
-            "method_declaration" => {
-                if let Some(symbol) = self.extract_method(node, content, file_path, &scope_stack) {
+            "constructor_declaration" | "compact_constructor_declaration" => {
+                if let Some(symbol) = self.extract_constructor(node, content, file_path, &context) {
                     symbols.push(symbol);
                 }
             }
-            "constructor_declaration" => {
-                if let Some(symbol) = self.extract_constructor(node, content, file_path, &scope_stack) {
+            "field_declaration" | "constant_declaration" => {
+                let field_symbols = self.extract_field_declaration(node, content, file_path, &context, scope);
+                symbols.extend(field_symbols);
+            }
+            "enum_constant" => {
+                if let Some(symbol) = self.extract_enum_constant(node, content, file_path, &context) {
                     symbols.push(symbol);
                 }
             }
-            "field_declaration" => {
-                let field_symbols = self.extract_field_declaration(node, content, file_path, &scope_stack);
-                symbols.extend(field_symbols);
-            }
             "local_variable_declaration" => {
-                let var_symbols = self.extract_local_variable_declaration(node, content, file_path, &scope_stack);
+                let var_symbols = self.extract_local_variable_declaration(node, content, file_path, &context, scope);
                 symbols.extend(var_symbols);
             }
             _ => {}
@@ -1397,113 +2407,311 @@ impl JavaParser {
 
         // Recurse into children
         if cursor.goto_first_child() {
-            let mut new_scope = scope_stack.clone();
-            if let "class_declaration" | "method_declaration" | "constructor_declaration" = node.kind() {
-                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
-                    new_scope.push(name);
-                }
-            }
+            let new_context = self.enter(node, content, &context);
 
-            self.walk_tree(cursor, content, file_path, symbols, new_scope);
+            self.walk_tree(cursor, content, file_path, symbols, new_context.clone(), scope);
 
             while cursor.goto_next_sibling() {
-                self.walk_tree(cursor, content, file_path, symbols, scope_stack.clone());
+                self.walk_tree(cursor, content, file_path, symbols, new_context.clone(), scope);
             }
 
             cursor.goto_parent();
         }
     }
 
-    fn extract_class(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
-        let name_node = node.child_by_field_name("name")?;
-        let name = self.get_node_text(Some(name_node), content)?;
+    /// Context of the children of a node: types push their name, methods and
+    /// constructors their overload segment, e.g. `get(String, int)`
+    fn enter(&self, node: tree_sitter::Node, content: &str, context: &JavaContext) -> JavaContext {
+        let mut new_context = context.clone();
+        let kind = node.kind();
+        if JAVA_TYPE_DECLARATIONS.contains(&kind) {
+            if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
+                new_context.classes.push(context.qualify(&name));
+                new_context.scope.push(name);
+            }
+        } else if let Some(segment) = self.member_segment(node, content) {
+            new_context.scope.push(segment);
+        }
+        new_context
+    }
 
-        let qualified_name = if scope_stack.is_empty() {
-            name.clone()
-        } else {
-            format!("{}.{}", scope_stack.join("."), name)
+    /// Last qualified name segment of a method or constructor, which includes
+    /// its erased parameter types so that overloads get distinct ids
+    fn member_segment(&self, node: tree_sitter::Node, content: &str) -> Option<String> {
+        let (name, parameters) = match node.kind() {
+            "method_declaration" | "annotation_type_element_declaration" | "constructor_declaration" => (
+                self.get_node_text(node.child_by_field_name("name"), content)?,
+                self.parameter_types(node.child_by_field_name("parameters"), content),
+            ),
+            // A compact constructor takes the record components
+            "compact_constructor_declaration" => {
+                let record = node.parent()?.parent()?;
+                (
+                    self.get_node_text(record.child_by_field_name("name"), content)?,
+                    self.parameter_types(record.child_by_field_name("parameters"), content),
+                )
+            }
+            _ => return None,
         };
+        Some(format!("{}({})", name, parameters.join(", ")))
+    }
 
-        let location = self.node_location(node, file_path);
+    /// Erased types of formal parameters, varargs written as `T...`
+    fn parameter_types(&self, node: Option<tree_sitter::Node>, content: &str) -> Vec<String> {
+        let Some(node) = node else {
+            return Vec::new();
+        };
 
-        Some(Symbol {
-            id: format!("{}:{}", file_path, qualified_name),
-            kind: SymbolKind::Class,
-            name,
-            qualified_name,
-            location,
-            signature: None,
-            type_info: None,
-            visibility: Visibility::Public, // Default visibility in Java
-            language: "java".to_string(),
-            metadata: serde_json::json!({}),
-            content_hash: "".to_string(),
-            last_indexed: chrono::Utc::now().timestamp(),
-        })
+        let mut types = Vec::new();
+        let mut cursor = node.walk();
+        for parameter in node.named_children(&mut cursor) {
+            match parameter.kind() {
+                "formal_parameter" => {
+                    if let Some(type_text) = self.get_node_text(parameter.child_by_field_name("type"), content) {
+                        types.push(java::erase(&type_text));
+                    }
+                }
+                "spread_parameter" => {
+                    let mut parameter_cursor = parameter.walk();
+                    let type_node = parameter.named_children(&mut parameter_cursor)
+                        .find(|child| !matches!(child.kind(), "modifiers" | "variable_declarator"));
+                    if let Some(type_text) = self.get_node_text(type_node, content) {
+                        types.push(format!("{}...", java::erase(&type_text)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        types
     }
 
-    fn extract_interface(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
-        let name_node = node.child_by_field_name("name")?;
-        let name = self.get_node_text(Some(name_node), content)?;
+    /// Names of the annotations on a declaration, with their arguments
+    fn annotations(&self, node: tree_sitter::Node, content: &str) -> Vec<String> {
+        let mut cursor = node.walk();
+        let Some(modifiers) = node.children(&mut cursor).find(|child| child.kind() == "modifiers") else {
+            return Vec::new();
+        };
 
-        let qualified_name = if scope_stack.is_empty() {
-            name.clone()
-        } else {
-            format!("{}.{}", scope_stack.join("."), name)
+        let mut modifier_cursor = modifiers.walk();
+        modifiers.named_children(&mut modifier_cursor)
+            .filter(|child| matches!(child.kind(), "annotation" | "marker_annotation"))
+            .filter_map(|annotation| self.get_node_text(Some(annotation), content))
+            .map(|text| text.trim_start_matches('@').split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    fn extract_imports(&self, root: tree_sitter::Node, content: &str, file_path: &str) -> Vec<Symbol> {
+        let mut cursor = root.walk();
+        root.named_children(&mut cursor)
+            .filter(|child| child.kind() == "import_declaration")
+            .filter_map(|declaration| {
+                let mut declaration_cursor = declaration.walk();
+                let children: Vec<tree_sitter::Node> = declaration.children(&mut declaration_cursor).collect();
+                let is_static = children.iter().any(|child| child.kind() == "static");
+                let on_demand = children.iter().any(|child| child.kind() == "asterisk");
+                let path_node = children.iter().find(|child| matches!(child.kind(), "scoped_identifier" | "identifier"))?;
+                let path: String = self.get_node_text(Some(*path_node), content)?.split_whitespace().collect();
+
+                let alias = if on_demand {
+                    "*".to_string()
+                } else {
+                    path.rsplit('.').next().unwrap_or(&path).to_string()
+                };
+                let written = if on_demand { format!("{}.*", path) } else { path.clone() };
+                let binding = java::ImportBinding { alias, path, is_static };
+
+                Some(Symbol {
+                    id: format!("{}:import:{}", file_path, written),
+                    kind: SymbolKind::Import,
+                    name: written.clone(),
+                    qualified_name: written,
+                    location: self.node_location(declaration, file_path),
+                    signature: None,
+                    type_info: None,
+                    visibility: Visibility::Public,
+                    language: "java".to_string(),
+                    metadata: serde_json::json!({
+                        "bindings": [binding]
+                    }),
+                    content_hash: "".to_string(),
+                    last_indexed: chrono::Utc::now().timestamp(),
+                })
+            })
+            .collect()
+    }
+
+    /// Written supertypes in an `extends` or `implements` clause, with the
+    /// qualified names each may refer to
+    fn supertypes(&self, clause: Option<tree_sitter::Node>, content: &str, context: &JavaContext, scope: &java::FileScope) -> Vec<serde_json::Value> {
+        let Some(clause) = clause else {
+            return Vec::new();
         };
 
-        let location = self.node_location(node, file_path);
+        // `superclass` holds the type directly, the others a `type_list`
+        let mut cursor = clause.walk();
+        let types: Vec<tree_sitter::Node> = match clause.named_children(&mut cursor).find(|child| child.kind() == "type_list") {
+            Some(list) => {
+                let mut list_cursor = list.walk();
+                list.named_children(&mut list_cursor).collect()
+            }
+            None => clause.named_child(0).into_iter().collect(),
+        };
 
-        Some(Symbol {
+        types.into_iter()
+            .filter_map(|ty| self.get_node_text(Some(ty), content))
+            .map(|name| serde_json::json!({
+                "name": java::erase(&name),
+                "candidates": scope.type_candidates(&name, &context.classes)
+            }))
+            .collect()
+    }
+
+    /// A class, interface, enum, record or annotation type, plus the
+    /// components of a record with their accessors
+    fn extract_type_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &JavaContext, scope: &java::FileScope) -> Vec<Symbol> {
+        let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) else {
+            return Vec::new();
+        };
+        let qualified_name = context.qualify(&name);
+
+        let mut cursor = node.walk();
+        let extends_interfaces = node.children(&mut cursor).find(|child| child.kind() == "extends_interfaces");
+        let (kind, flag, extends, implements) = match node.kind() {
+            "interface_declaration" => (SymbolKind::Type, "interface", self.supertypes(extends_interfaces, content, context, scope), Vec::new()),
+            "annotation_type_declaration" => (SymbolKind::Type, "annotation", Vec::new(), Vec::new()),
+            other => (
+                SymbolKind::Class,
+                match other {
+                    "enum_declaration" => "enum",
+                    "record_declaration" => "record",
+                    _ => "class",
+                },
+                self.supertypes(node.child_by_field_name("superclass"), content, context, scope),
+                self.supertypes(node.child_by_field_name("interfaces"), content, context, scope),
+            ),
+        };
+
+        let mut metadata = serde_json::json!({
+            "extends": extends,
+            "implements": implements,
+            "decorators": self.annotations(node, content)
+        });
+        if flag != "class" {
+            metadata[flag] = true.into();
+        }
+
+        let mut symbols = vec![Symbol {
             id: format!("{}:{}", file_path, qualified_name),
-            kind: SymbolKind::Type,
+            kind,
             name,
-            qualified_name,
-            location,
+            qualified_name: qualified_name.clone(),
+            location: self.node_location(node, file_path),
             signature: None,
             type_info: None,
-            visibility: Visibility::Public,
+            visibility: Visibility::Public, // Default visibility in Java
             language: "java".to_string(),
-            metadata: serde_json::json!({
-                "interface": true
-            }),
+            metadata,
             content_hash: "".to_string(),
             last_indexed: chrono::Utc::now().timestamp(),
-        })
-    }
+        }];
 
-    fn extract_method(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
-        let name_node = node.child_by_field_name("name")?;
-        let name = self.get_node_text(Some(name_node), content)?;
-
-        // Get class name from scope
-        let class_name = scope_stack.last().unwrap_or(&"Unknown".to_string()).clone();
+        if node.kind() == "record_declaration" {
+            symbols.extend(self.extract_record_components(node, content, file_path, &qualified_name, context, scope));
+        }
+        symbols
+    }
 
-        let qualified_name = if scope_stack.is_empty() {
-            name.clone()
-        } else {
-            format!("{}.{}", scope_stack.join("."), name)
+    /// Fields of a record and the accessors the compiler generates for them
+    fn extract_record_components(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        record: &str,
+        context: &JavaContext,
+        scope: &java::FileScope,
+    ) -> Vec<Symbol> {
+        let Some(parameters) = node.child_by_field_name("parameters") else {
+            return Vec::new();
         };
+        let record_context = self.enter(node, content, context);
+
+        // Accessors written out in the body replace the generated ones
+        let mut declared = HashSet::new();
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor).filter(|m| m.kind() == "method_declaration") {
+                if let Some(segment) = self.member_segment(member, content) {
+                    declared.insert(segment);
+                }
+            }
+        }
 
-        let location = self.node_location(node, file_path);
+        let mut symbols = Vec::new();
+        let mut cursor = parameters.walk();
+        for component in parameters.named_children(&mut cursor).filter(|p| p.kind() == "formal_parameter") {
+            let Some(name) = self.get_node_text(component.child_by_field_name("name"), content) else { continue };
+            let Some(type_text) = self.get_node_text(component.child_by_field_name("type"), content) else { continue };
+            let types = scope.type_candidates(&type_text, &record_context.classes);
+            let location = self.node_location(component, file_path);
+
+            let field_name = format!("{}.{}", record, name);
+            symbols.push(Symbol {
+                id: format!("{}:{}", file_path, field_name),
+                kind: SymbolKind::Field,
+                name: name.clone(),
+                qualified_name: field_name,
+                location: location.clone(),
+                signature: None,
+                type_info: Some(type_text.clone()),
+                visibility: Visibility::Public,
+                language: "java".to_string(),
+                metadata: serde_json::json!({
+                    "field": true,
+                    "types": types
+                }),
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            });
 
-        // Extract parameters
-        let parameters_node = node.child_by_field_name("parameters");
-        let mut parameters = Vec::new();
-        if let Some(params) = parameters_node {
-            let mut cursor = params.walk();
-            for child in params.children(&mut cursor) {
-                if child.kind() == "formal_parameter" {
-                    if let Some(type_node) = child.child_by_field_name("type") {
-                        if let Some(param_type) = self.get_node_text(Some(type_node), content) {
-                            parameters.push(param_type);
-                        }
-                    }
-                }
+            let segment = format!("{}()", name);
+            if declared.contains(&segment) {
+                continue;
             }
+            let accessor_name = format!("{}.{}", record, segment);
+            symbols.push(Symbol {
+                id: format!("{}:{}", file_path, accessor_name),
+                kind: SymbolKind::Method,
+                name: name.clone(),
+                qualified_name: accessor_name,
+                location,
+                signature: Some(format!("{} {}()", type_text, name)),
+                type_info: Some(type_text),
+                visibility: Visibility::Public,
+                language: "java".to_string(),
+                metadata: serde_json::json!({
+                    "accessor": true,
+                    "parameters": Vec::<String>::new(),
+                    "types": types
+                }),
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            });
         }
+        symbols
+    }
+
+    fn extract_method(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &JavaContext, scope: &java::FileScope) -> Option<Symbol> {
+        let name_node = node.child_by_field_name("name")?;
+        let name = self.get_node_text(Some(name_node), content)?;
+        let segment = self.member_segment(node, content)?;
+        let qualified_name = context.qualify(&segment);
 
-        let signature = format!("{} {}({})", class_name, name, parameters.join(", "));
+        let location = self.node_location(node, file_path);
+        let parameters = self.parameter_types(node.child_by_field_name("parameters"), content);
+        let return_type = self.get_node_text(node.child_by_field_name("type"), content)?;
+        let parameters_text = self.get_node_text(node.child_by_field_name("parameters"), content).unwrap_or_else(|| "()".to_string());
+        let signature = format!("{} {}{}", return_type, name, parameters_text).split_whitespace().collect::<Vec<_>>().join(" ");
 
         Some(Symbol {
             id: format!("{}:{}", file_path, qualified_name),
@@ -1512,52 +2720,36 @@ impl JavaParser {
             qualified_name,
             location,
             signature: Some(signature),
-            type_info: Some(class_name),
+            type_info: Some(return_type.clone()),
             visibility: Visibility::Public,
             language: "java".to_string(),
             metadata: serde_json::json!({
-                "parameters": parameters
+                "parameters": parameters,
+                "types": scope.type_candidates(&return_type, &context.classes),
+                "decorators": self.annotations(node, content)
             }),
             content_hash: "".to_string(),
             last_indexed: chrono::Utc::now().timestamp(),
         })
     }
 
-    fn extract_constructor(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Option<Symbol> {
-        // Get class name from scope
-        let class_name = scope_stack.last().unwrap_or(&"Unknown".to_string()).clone();
-        let name = class_name.clone(); // Constructor name is the class name
-
-        let qualified_name = if scope_stack.is_empty() {
-            name.clone()
-        } else {
-            format!("{}.{}", scope_stack.join("."), name)
-        };
+    fn extract_constructor(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &JavaContext) -> Option<Symbol> {
+        // Constructors are named after their class
+        let class_name = context.scope.last()?.clone();
+        let segment = self.member_segment(node, content)?;
+        let qualified_name = context.qualify(&segment);
 
         let location = self.node_location(node, file_path);
-
-        // Extract parameters
-        let parameters_node = node.child_by_field_name("parameters");
-        let mut parameters = Vec::new();
-        if let Some(params) = parameters_node {
-            let mut cursor = params.walk();
-            for child in params.children(&mut cursor) {
-                if child.kind() == "formal_parameter" {
-                    if let Some(type_node) = child.child_by_field_name("type") {
-                        if let Some(param_type) = self.get_node_text(Some(type_node), content) {
-                            parameters.push(param_type);
-                        }
-                    }
-                }
-            }
-        }
-
+        let parameters = match node.kind() {
+            "compact_constructor_declaration" => self.parameter_types(node.parent()?.parent()?.child_by_field_name("parameters"), content),
+            _ => self.parameter_types(node.child_by_field_name("parameters"), content),
+        };
         let signature = format!("{}({})", class_name, parameters.join(", "));
 
         Some(Symbol {
             id: format!("{}:{}", file_path, qualified_name),
             kind: SymbolKind::Method,
-            name,
+            name: class_name.clone(),
             qualified_name,
             location,
             signature: Some(signature),
@@ -1566,27 +2758,25 @@ impl JavaParser {
             language: "java".to_string(),
             metadata: serde_json::json!({
                 "constructor": true,
-                "parameters": parameters
+                "parameters": parameters,
+                "decorators": self.annotations(node, content)
             }),
             content_hash: "".to_string(),
             last_indexed: chrono::Utc::now().timestamp(),
         })
     }
 
-    fn extract_field_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Vec<Symbol> {
+    fn extract_field_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &JavaContext, scope: &java::FileScope) -> Vec<Symbol> {
         let mut symbols = Vec::new();
+        let type_text = self.get_node_text(node.child_by_field_name("type"), content);
+        let types = type_text.as_deref().map(|ty| scope.type_candidates(ty, &context.classes)).unwrap_or_default();
 
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
             if child.kind() == "variable_declarator" {
                 if let Some(name_node) = child.child_by_field_name("name") {
                     if let Some(name) = self.get_node_text(Some(name_node), content) {
-                        let qualified_name = if scope_stack.is_empty() {
-                            name.clone()
-                        } else {
-                            format!("{}.{}", scope_stack.join("."), name)
-                        };
-
+                        let qualified_name = context.qualify(&name);
                         let location = self.node_location(child, file_path);
 
                         symbols.push(Symbol {
@@ -1596,11 +2786,13 @@ impl JavaParser {
                             qualified_name,
                             location,
                             signature: None,
-                            type_info: None,
+                            type_info: type_text.clone(),
                             visibility: Visibility::Public,
                             language: "java".to_string(),
                             metadata: serde_json::json!({
-                                "field": true
+                                "field": true,
+                                "types": types,
+                                "decorators": self.annotations(node, content)
                             }),
                             content_hash: "".to_string(),
                             last_indexed: chrono::Utc::now().timestamp(),
@@ -1613,20 +2805,43 @@ impl JavaParser {
         symbols
     }
 
-    fn extract_local_variable_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope_stack: &[String]) -> Vec<Symbol> {
+    /// Enum constants are fields of the enum's own type
+    fn extract_enum_constant(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &JavaContext) -> Option<Symbol> {
+        let name = self.get_node_text(node.child_by_field_name("name"), content)?;
+        let enum_name = context.classes.last()?.clone();
+        let qualified_name = context.qualify(&name);
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Field,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: None,
+            type_info: context.scope.last().cloned(),
+            visibility: Visibility::Public,
+            language: "java".to_string(),
+            metadata: serde_json::json!({
+                "field": true,
+                "enum_constant": true,
+                "types": [enum_name]
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    fn extract_local_variable_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &JavaContext, scope: &java::FileScope) -> Vec<Symbol> {
         let mut symbols = Vec::new();
+        let type_text = self.get_node_text(node.child_by_field_name("type"), content);
+        let types = type_text.as_deref().map(|ty| scope.type_candidates(ty, &context.classes)).unwrap_or_default();
 
         let mut cursor = node.walk();
         for child in node.children(&mut cursor) {
             if child.kind() == "variable_declarator" {
                 if let Some(name_node) = child.child_by_field_name("name") {
                     if let Some(name) = self.get_node_text(Some(name_node), content) {
-                        let qualified_name = if scope_stack.is_empty() {
-                            name.clone()
-                        } else {
-                            format!("{}.{}", scope_stack.join("."), name)
-                        };
-
+                        let qualified_name = context.qualify(&name);
                         let location = self.node_location(child, file_path);
 
                         symbols.push(Symbol {
@@ -1636,11 +2851,12 @@ impl JavaParser {
                             qualified_name,
                             location,
                             signature: None,
-                            type_info: None,
+                            type_info: type_text.clone(),
                             visibility: Visibility::Public,
                             language: "java".to_string(),
                             metadata: serde_json::json!({
-                                "local": true
+                                "local": true,
+                                "types": types
                             }),
                             content_hash: "".to_string(),
                             last_indexed: chrono::Utc::now().timestamp(),
@@ -1654,62 +2870,310 @@ impl JavaParser {
     }
 
     fn extract_relationships(&self, tree: &Tree, content: &str, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(tree, content, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, tree: &Tree, content: &str, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
         let mut relationships = Vec::new();
         let root = tree.root_node();
 
-        // Create a map of qualified names to symbol IDs for lookup
-        let symbol_map: HashMap<&str, &Symbol> = symbols.iter()
-            .map(|s| (s.qualified_name.as_str(), s))
-            .collect();
+        let package = self.package(root, content);
+        let context = JavaContext::new(&package);
+        let mut declared = HashSet::new();
+        self.declared_types(root, content, &context, &mut declared);
+        let resolver = java::Resolver::new(symbol_map, file_path, package, declared);
 
-        let mut cursor = root.walk();
-        self.extract_relationships_from_tree(&mut cursor, content, file_path, &symbol_map, &mut relationships, Vec::new());
+        self.extract_relationships_from_tree(root, content, file_path, &resolver, &mut relationships, &context, None);
+        relationships.extend(ProtobufParser::stub_relationships(content, file_path, symbol_map));
+        if self.sql_literals {
+            relationships.extend(SqlParser::literal_relationships(root, content, file_path, &["string_literal"], symbol_map));
+        }
 
         relationships
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn extract_relationships_from_tree(
         &self,
-        cursor: &mut tree_sitter::TreeCursor,
+        node: tree_sitter::Node,
         content: &str,
         file_path: &str,
-        symbol_map: &HashMap<&str, &Symbol>,
+        resolver: &java::Resolver,
         relationships: &mut Vec<Relationship>,
-        context_stack: Vec<String>,
+        context: &JavaContext,
+        method: Option<&JavaMethod>,
     ) {
-        let node = cursor.node();
-
-        match node.kind() {
-            "method_invocation" => {
-                if let Some(rel) = self.extract_method_invocation(node, content, file_path, symbol_map, &context_stack) {
-                    relationships.push(rel);
+        let kind = node.kind();
+        if JAVA_TYPE_DECLARATIONS.contains(&kind) {
+            relationships.extend(self.extract_supertype_relationships(node, content, file_path, resolver, context));
+        } else if let Some(method) = method {
+            let rel = match kind {
+                "method_invocation" => self.extract_method_invocation(node, content, file_path, resolver, context, method),
+                "object_creation_expression" | "explicit_constructor_invocation" => {
+                    self.extract_constructor_call(node, content, file_path, resolver, context, method)
                 }
-            }
-            _ => {}
+                _ => None,
+            };
+            relationships.extend(rel);
         }
 
         // Recurse
-        if cursor.goto_first_child() {
-            let mut new_context = context_stack.clone();
-            if let "method_declaration" | "constructor_declaration" = node.kind() {
-                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
-                    new_context.push(name);
+        let new_context = self.enter(node, content, context);
+        let entered = match kind {
+            "method_declaration" | "constructor_declaration" | "compact_constructor_declaration" => {
+                resolver.symbol(&new_context.scope.join(".")).map(|symbol| JavaMethod {
+                    symbol,
+                    locals: self.locals(node, content, resolver, &new_context),
+                })
+            }
+            _ => None,
+        };
+        // A nested or local class starts a new scope
+        let method = match kind {
+            kind if JAVA_TYPE_DECLARATIONS.contains(&kind) => None,
+            _ => entered.as_ref().or(method),
+        };
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.extract_relationships_from_tree(child, content, file_path, resolver, relationships, &new_context, method);
+        }
+    }
+
+    /// `Extends` and `Implements` edges of a type declaration
+    fn extract_supertype_relationships(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &java::Resolver,
+        context: &JavaContext,
+    ) -> Vec<Relationship> {
+        let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) else {
+            return Vec::new();
+        };
+        let Some(ty) = resolver.symbol(&context.qualify(&name)) else {
+            return Vec::new();
+        };
+        let location = self.node_location(node, file_path);
+
+        let written = |key: &str| -> Vec<String> {
+            ty.metadata[key].as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|supertype| supertype["name"].as_str().map(str::to_string))
+                .collect()
+        };
+        let names: Vec<String> = written("extends").into_iter().chain(written("implements")).collect();
+
+        java::supertype_candidates(ty)
+            .zip(names)
+            .map(|((extends, candidates), name)| {
+                let kind = if extends { RelationshipKind::Extends } else { RelationshipKind::Implements };
+                match resolver.first_type(&candidates) {
+                    Some(supertype) => Relationship {
+                        from_id: ty.id.clone(),
+                        to_id: supertype.id.clone(),
+                        kind,
+                        location: location.clone(),
+                        metadata: serde_json::json!({}),
+                    },
+                    None => Relationship::unresolved(ty.id.clone(), &name, kind, location.clone()),
                 }
+            })
+            .collect()
+    }
+
+    /// Declared types of the parameters and locals of a method, in
+    /// declaration order so that `var` can use earlier ones
+    fn locals<'a>(&self, node: tree_sitter::Node, content: &str, resolver: &java::Resolver<'_, 'a>, context: &JavaContext) -> HashMap<String, (String, Option<&'a Symbol>)> {
+        let mut locals = HashMap::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            // Local and anonymous classes have their own members
+            if node.kind() == "class_body" || JAVA_TYPE_DECLARATIONS.contains(&node.kind()) {
+                continue;
             }
 
-            self.extract_relationships_from_tree(cursor, content, file_path, symbol_map, relationships, new_context);
+            let type_node = match node.kind() {
+                "formal_parameter" | "catch_formal_parameter" | "local_variable_declaration" | "enhanced_for_statement" | "resource" => {
+                    node.child_by_field_name("type")
+                }
+                "spread_parameter" => {
+                    let mut cursor = node.walk();
+                    let type_node = node.named_children(&mut cursor).find(|child| !matches!(child.kind(), "modifiers" | "variable_declarator"));
+                    type_node
+                }
+                _ => None,
+            };
 
-            while cursor.goto_next_sibling() {
-                let mut sibling_context = context_stack.clone();
-                if let "method_declaration" | "constructor_declaration" = cursor.node().kind() {
-                    if let Some(name) = self.get_node_text(cursor.node().child_by_field_name("name"), content) {
-                        sibling_context.push(name);
+            if let Some(type_node) = type_node {
+                let type_text = self.get_node_text(Some(type_node), content).unwrap_or_default();
+                let mut names = Vec::new();
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    match child.kind() {
+                        "variable_declarator" => names.push((child.child_by_field_name("name"), child.child_by_field_name("value"))),
+                        "identifier" if Some(child) == node.child_by_field_name("name") => names.push((Some(child), node.child_by_field_name("value"))),
+                        _ => {}
+                    }
+                }
+                if node.kind() == "catch_formal_parameter" || node.kind() == "formal_parameter" || node.kind() == "resource" {
+                    names = vec![(node.child_by_field_name("name"), node.child_by_field_name("value"))];
+                }
+
+                for (name, value) in names {
+                    let Some(name) = self.get_node_text(name, content) else { continue };
+                    let local = if type_text == "var" {
+                        let ty = value.and_then(|value| self.expression_type(value, content, resolver, context, &locals));
+                        (ty.map(|ty| ty.name.clone()).unwrap_or_default(), ty)
+                    } else {
+                        (java::erase(&type_text), resolver.resolve_type(&type_text, &context.classes))
+                    };
+                    locals.insert(name, local);
+                }
+            } else if node.kind() == "lambda_expression" {
+                // Untyped lambda parameters shadow fields of the same name
+                if let Some(parameters) = node.child_by_field_name("parameters") {
+                    let mut cursor = parameters.walk();
+                    let mut names: Vec<tree_sitter::Node> = parameters.named_children(&mut cursor).filter(|p| p.kind() == "identifier").collect();
+                    if parameters.kind() == "identifier" {
+                        names.push(parameters);
+                    }
+                    for name in names {
+                        if let Some(name) = self.get_node_text(Some(name), content) {
+                            locals.entry(name).or_insert((String::new(), None));
+                        }
                     }
                 }
-                self.extract_relationships_from_tree(cursor, content, file_path, symbol_map, relationships, sibling_context);
             }
 
-            cursor.goto_parent();
+            let mut cursor = node.walk();
+            let children: Vec<_> = node.named_children(&mut cursor).collect();
+            stack.extend(children.into_iter().rev());
+        }
+        locals
+    }
+
+    /// Static type of an expression, as far as declarations tell without type checking
+    fn expression_type<'a>(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        resolver: &java::Resolver<'_, 'a>,
+        context: &JavaContext,
+        locals: &HashMap<String, (String, Option<&'a Symbol>)>,
+    ) -> Option<&'a Symbol> {
+        match node.kind() {
+            "identifier" => {
+                let name = self.get_node_text(Some(node), content)?;
+                if let Some((_, ty)) = locals.get(&name) {
+                    return *ty;
+                }
+                // A field of an enclosing class, or a type for a static access
+                context.classes.iter()
+                    .rev()
+                    .filter_map(|class| resolver.find_type(class))
+                    .find_map(|class| resolver.field(class, &name))
+                    .and_then(|field| resolver.type_of(field))
+                    .or_else(|| resolver.resolve_type(&name, &context.classes))
+            }
+            "this" => context.classes.last().and_then(|class| resolver.find_type(class)),
+            "super" => {
+                let class = resolver.find_type(context.classes.last()?)?;
+                java::supertype_candidates(class)
+                    .find(|(extends, _)| *extends)
+                    .and_then(|(_, candidates)| resolver.first_type(&candidates))
+            }
+            "field_access" => {
+                let field = self.get_node_text(node.child_by_field_name("field"), content)?;
+                let field = self.expression_type(node.child_by_field_name("object")?, content, resolver, context, locals)
+                    .and_then(|ty| resolver.field(ty, &field))
+                    .and_then(|field| resolver.type_of(field));
+                // A qualified type name such as `Outer.Inner` or `Service.Companion`
+                field.or_else(|| resolver.resolve_type(&self.get_node_text(Some(node), content)?, &context.classes))
+            }
+            "method_invocation" => {
+                let method = self.invocation_target(node, content, resolver, context, locals)?;
+                resolver.type_of(method)
+            }
+            "object_creation_expression" | "cast_expression" => {
+                resolver.resolve_type(&self.get_node_text(node.child_by_field_name("type"), content)?, &context.classes)
+            }
+            "parenthesized_expression" => self.expression_type(node.named_child(0)?, content, resolver, context, locals),
+            _ => None,
+        }
+    }
+
+    /// Static types of call arguments that can be told from literals and declarations
+    fn argument_types<'a>(
+        &self,
+        node: Option<tree_sitter::Node>,
+        content: &str,
+        resolver: &java::Resolver<'_, 'a>,
+        context: &JavaContext,
+        locals: &HashMap<String, (String, Option<&'a Symbol>)>,
+    ) -> Vec<java::ArgumentType> {
+        let Some(node) = node else {
+            return Vec::new();
+        };
+
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .filter(|argument| !matches!(argument.kind(), "line_comment" | "block_comment"))
+            .map(|argument| {
+                let literal = match argument.kind() {
+                    "string_literal" | "text_block" => Some("String"),
+                    "decimal_integer_literal" | "hex_integer_literal" | "octal_integer_literal" | "binary_integer_literal" => {
+                        let text = &content[argument.byte_range()];
+                        Some(if text.ends_with(['l', 'L']) { "long" } else { "int" })
+                    }
+                    "decimal_floating_point_literal" | "hex_floating_point_literal" => {
+                        let text = &content[argument.byte_range()];
+                        Some(if text.ends_with(['f', 'F']) { "float" } else { "double" })
+                    }
+                    "true" | "false" => Some("boolean"),
+                    "character_literal" => Some("char"),
+                    _ => None,
+                };
+                if let Some(literal) = literal {
+                    return java::ArgumentType::Known(literal.to_string());
+                }
+
+                if argument.kind() == "identifier" {
+                    let name = &content[argument.byte_range()];
+                    if let Some((declared, _)) = locals.get(name).filter(|(declared, _)| !declared.is_empty()) {
+                        return java::ArgumentType::Known(declared.clone());
+                    }
+                }
+                match self.expression_type(argument, content, resolver, context, locals) {
+                    Some(ty) => java::ArgumentType::Known(ty.name.clone()),
+                    None => java::ArgumentType::Unknown,
+                }
+            })
+            .collect()
+    }
+
+    /// Method a `method_invocation` calls, from the static type of its receiver
+    fn invocation_target<'a>(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        resolver: &java::Resolver<'_, 'a>,
+        context: &JavaContext,
+        locals: &HashMap<String, (String, Option<&'a Symbol>)>,
+    ) -> Option<&'a Symbol> {
+        let name = self.get_node_text(node.child_by_field_name("name"), content)?;
+        let arguments = self.argument_types(node.child_by_field_name("arguments"), content, resolver, context, locals);
+
+        match node.child_by_field_name("object") {
+            Some(object) => {
+                let ty = self.expression_type(object, content, resolver, context, locals)?;
+                resolver.method(ty, &name, &arguments)
+            }
+            None => resolver.unqualified_method(&context.classes, &name, &arguments),
         }
     }
 
@@ -1718,34 +3182,60 @@ impl JavaParser {
         node: tree_sitter::Node,
         content: &str,
         file_path: &str,
-        symbol_map: &HashMap<&str, &Symbol>,
-        context_stack: &[String],
+        resolver: &java::Resolver,
+        context: &JavaContext,
+        method: &JavaMethod,
     ) -> Option<Relationship> {
         let name_node = node.child_by_field_name("name")?;
         let method_name = self.get_node_text(Some(name_node), content)?;
 
-        // Look for the method in any class
-        let called_symbol = symbol_map.values()
-            .find(|s| s.kind == SymbolKind::Method && s.name == method_name);
+        let called_symbol = self.invocation_target(node, content, resolver, context, &method.locals);
+        let location = self.node_location(node, file_path);
 
-        if let Some(called_symbol) = called_symbol {
-            // Only create relationship if we have a valid calling context
-            if !context_stack.is_empty() {
-                let caller_qualified_name = context_stack.join(".");
-                if let Some(caller_symbol) = symbol_map.get(caller_qualified_name.as_str()) {
-                    let location = self.node_location(node, file_path);
+        match called_symbol {
+            Some(called_symbol) => Some(Relationship {
+                from_id: method.symbol.id.clone(),
+                to_id: called_symbol.id.clone(),
+                kind: RelationshipKind::Calls,
+                location,
+                metadata: serde_json::json!({}),
+            }),
+            // Record the call so it can be re-resolved once a matching symbol appears
+            None => Some(Relationship::unresolved(method.symbol.id.clone(), &method_name, RelationshipKind::Calls, location)),
+        }
+    }
 
-                    return Some(Relationship {
-                        from_id: caller_symbol.id.clone(),
-                        to_id: called_symbol.id.clone(),
-                        kind: RelationshipKind::Calls,
-                        location,
-                        metadata: serde_json::json!({}),
-                    });
-                }
+    /// `new T(...)`, `this(...)` and `super(...)` call a constructor, or
+    /// reference the class when it declares none
+    fn extract_constructor_call(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &java::Resolver,
+        context: &JavaContext,
+        method: &JavaMethod,
+    ) -> Option<Relationship> {
+        let class = match node.kind() {
+            "explicit_constructor_invocation" => {
+                let constructor = node.child_by_field_name("constructor")?;
+                self.expression_type(constructor, content, resolver, context, &method.locals)?
             }
-        }
-        None
+            _ => resolver.resolve_type(&self.get_node_text(node.child_by_field_name("type"), content)?, &context.classes)?,
+        };
+        let arguments = self.argument_types(node.child_by_field_name("arguments"), content, resolver, context, &method.locals);
+
+        let (to_id, kind) = match resolver.constructor(class, &arguments) {
+            Some(constructor) => (constructor.id.clone(), RelationshipKind::Calls),
+            None => (class.id.clone(), RelationshipKind::References),
+        };
+        Some(Relationship {
+            from_id: method.symbol.id.clone(),
+            to_id,
+            kind,
+            location: self.node_location(node, file_path),
+            metadata: serde_json::json!({}),
+        })
     }
 
     fn get_node_text(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
@@ -1766,243 +3256,5453 @@ impl JavaParser {
     }
 }
 
-impl IntentParser {
+const TYPESCRIPT_FUNCTIONS: &[&str] = &["function_expression", "arrow_function", "generator_function"];
+
+impl TypeScriptParser {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 
-    fn extract_symbols(&self, content: &str, file_path: &str) -> Vec<Symbol> {
-        let mut symbols = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+    /// Parser for `.js`, `.jsx`, `.mjs` and `.cjs` files
+    pub fn javascript() -> Self {
+        Self { javascript: true }
+    }
 
-        for (line_num, line) in lines.iter().enumerate() {
-            let line = line.trim();
+    fn language(&self) -> &'static str {
+        if self.javascript { "javascript" } else { "typescript" }
+    }
 
-            // Parse context declarations
-            if line.starts_with("context ") {
-                if let Some(context_symbol) = self.extract_context_declaration(line, file_path, line_num + 1) {
-                    symbols.push(context_symbol);
-                }
+    fn parse_tree(&self, content: &str, file_path: &str) -> anyhow::Result<Tree> {
+        let mut parser = TreeParser::new();
+        // The TSX grammar also covers JavaScript and JSX, but reads `<T>x` casts as elements
+        let language = if [".ts", ".mts", ".cts"].iter().any(|ext| file_path.ends_with(ext)) {
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT
+        } else {
+            tree_sitter_typescript::LANGUAGE_TSX
+        };
+        parser.set_language(&language.into())?;
+
+        let tree = parser.parse(content, None)
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse {} code", self.language()))?;
+
+        Ok(tree)
+    }
+
+    fn extract_symbols(&self, tree: &Tree, content: &str, file_path: &str) -> Vec<Symbol> {
+        let root = tree.root_node();
+        let mut symbols = self.extract_imports(root, content, file_path);
+        self.walk_tree(root, content, file_path, &mut symbols, &[]);
+
+        // Overload signatures and accessor pairs share the implementation's id
+        let mut seen = HashSet::new();
+        symbols.retain(|symbol| seen.insert(symbol.id.clone()));
+        symbols
+    }
+
+    fn walk_tree(&self, node: tree_sitter::Node, content: &str, file_path: &str, symbols: &mut Vec<Symbol>, scope: &[String]) {
+        match node.kind() {
+            "function_declaration" | "generator_function_declaration" | "function_signature" => {
+                symbols.extend(self.extract_function(node, node, content, file_path, scope, SymbolKind::Function));
             }
-            // Parse field declarations (inside contexts)
-            else if line.contains(": ") && !line.starts_with("fn ") && !line.starts_with("//") && !line.starts_with("///") {
-                if let Some(field_symbol) = self.extract_field_declaration(line, file_path, line_num + 1) {
-                    symbols.push(field_symbol);
-                }
+            "class_declaration" | "abstract_class_declaration" | "interface_declaration" => {
+                symbols.extend(self.extract_class(node, node, content, file_path, scope));
+            }
+            "method_definition" | "abstract_method_signature" | "method_signature" => {
+                symbols.extend(self.extract_function(node, node, content, file_path, scope, SymbolKind::Method));
+                symbols.extend(self.extract_parameter_properties(node, content, file_path, scope));
+            }
+            "public_field_definition" | "property_signature" => {
+                symbols.extend(self.extract_field(node, content, file_path, scope));
+            }
+            "type_alias_declaration" => {
+                symbols.extend(self.extract_type_alias(node, content, file_path, scope));
+            }
+            "enum_declaration" => {
+                symbols.extend(self.extract_enum(node, content, file_path, scope));
             }
-            // Parse method declarations
-            else if line.starts_with("fn ") {
-                if let Some(method_symbol) = self.extract_method_declaration(line, file_path, line_num + 1) {
-                    symbols.push(method_symbol);
+            "internal_module" | "module" => {
+                symbols.extend(self.extract_namespace(node, content, file_path, scope));
+            }
+            "variable_declarator" => {
+                symbols.extend(self.extract_variable(node, content, file_path, scope));
+            }
+            // Anonymous `export default function () {}` and `export default class {}`
+            "function_expression" | "arrow_function" | "class" if self.is_default_export(node) => {
+                match node.kind() {
+                    "class" => symbols.extend(self.extract_class(node, node, content, file_path, scope)),
+                    _ => symbols.extend(self.extract_function(node, node, content, file_path, scope, SymbolKind::Function)),
                 }
             }
+            _ => {}
         }
 
-        symbols
-    }
-
-    fn extract_context_declaration(&self, line: &str, file_path: &str, line_num: usize) -> Option<Symbol> {
-        // Match: context <Name> [persist "..."] [extends <Base>] [depends [...]] {
-        let re = regex::Regex::new(r"context\s+(\w+)").ok()?;
-        if let Some(captures) = re.captures(line) {
-            let name = captures.get(1)?.as_str();
+        let mut new_scope = scope.to_vec();
+        new_scope.extend(self.scope_segment(node, content));
 
-            let location = Location {
-                file: file_path.to_string(),
-                line: line_num as u32,
-                column: line.find("context").unwrap_or(0) as u32,
-                end_line: line_num as u32,
-                end_column: (line.find("context").unwrap_or(0) + line.len()) as u32,
-            };
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.walk_tree(child, content, file_path, symbols, &new_scope);
+        }
+    }
 
-            Some(Symbol {
-                id: format!("{}:{}", file_path, name),
-                kind: SymbolKind::Context,
-                name: name.to_string(),
-                qualified_name: name.to_string(),
-                location,
-                signature: Some(format!("context {}", name)),
-                type_info: None,
-                visibility: Visibility::Public,
-                language: "intent".to_string(),
-                metadata: serde_json::json!({
-                    "context": true
-                }),
-                content_hash: "".to_string(),
-                last_indexed: chrono::Utc::now().timestamp(),
-            })
-        } else {
-            None
+    /// Name a node adds to the qualified names of the declarations inside it
+    fn scope_segment(&self, node: tree_sitter::Node, content: &str) -> Option<String> {
+        match node.kind() {
+            "function_declaration" | "generator_function_declaration" | "class_declaration" | "abstract_class_declaration"
+            | "interface_declaration" | "method_definition" | "internal_module" | "module" | "enum_declaration" => {
+                self.declaration_name(node, content)
+            }
+            // Functions and classes bound to a name
+            "variable_declarator" | "public_field_definition" => {
+                let value = node.child_by_field_name("value")?;
+                let named = TYPESCRIPT_FUNCTIONS.contains(&value.kind()) || value.kind() == "class";
+                named.then(|| self.get_node_text(node.child_by_field_name("name"), content)).flatten()
+            }
+            "function_expression" | "arrow_function" | "class" if self.is_default_export(node) => Some("default".to_string()),
+            _ => None,
         }
     }
 
-    fn extract_field_declaration(&self, line: &str, file_path: &str, line_num: usize) -> Option<Symbol> {
-        // Match: <name>: <Type> [= <default>]
-        let re = regex::Regex::new(r"(\w+)\s*:\s*([^=\s]+)").ok()?;
-        if let Some(captures) = re.captures(line) {
-            let name = captures.get(1)?.as_str();
-            let type_info = captures.get(2)?.as_str();
+    fn declaration_name(&self, node: tree_sitter::Node, content: &str) -> Option<String> {
+        let name = self.get_node_text(node.child_by_field_name("name"), content)
+            .map(|name| name.trim_matches(['"', '\'']).to_string());
+        name.or_else(|| self.is_default_export(node).then(|| "default".to_string()))
+    }
 
-            let location = Location {
-                file: file_path.to_string(),
-                line: line_num as u32,
-                column: 0,
-                end_line: line_num as u32,
-                end_column: line.len() as u32,
-            };
+    fn is_default_export(&self, node: tree_sitter::Node) -> bool {
+        node.parent().is_some_and(|parent| {
+            let mut cursor = parent.walk();
+            let is_default = parent.kind() == "export_statement" && parent.children(&mut cursor).any(|child| child.kind() == "default");
+            is_default
+        })
+    }
 
-            Some(Symbol {
-                id: format!("{}:{}", file_path, name),
-                kind: SymbolKind::Field,
-                name: name.to_string(),
-                qualified_name: name.to_string(),
-                location,
-                signature: Some(format!("{}: {}", name, type_info)),
-                type_info: Some(type_info.to_string()),
-                visibility: Visibility::Public,
-                language: "intent".to_string(),
-                metadata: serde_json::json!({
-                    "field": true
-                }),
-                content_hash: "".to_string(),
-                last_indexed: chrono::Utc::now().timestamp(),
-            })
-        } else {
-            None
+    /// `exported` and `default` flags of a declaration, from its `export` statement
+    fn export_flags(&self, declaration: tree_sitter::Node) -> (bool, bool) {
+        let statement = match declaration.kind() {
+            "variable_declarator" => declaration.parent().and_then(|list| list.parent()),
+            _ => declaration.parent(),
+        };
+        match statement {
+            Some(statement) if statement.kind() == "export_statement" => (true, self.is_default_export(declaration)),
+            _ => (false, false),
         }
     }
 
-    fn extract_method_declaration(&self, line: &str, file_path: &str, line_num: usize) -> Option<Symbol> {
-        // Match: fn <name>(<params>) [-> <return_type>]
-        let re = regex::Regex::new(r"fn\s+(\w+)\s*\(([^)]*)\)(?:\s*->\s*(\w+))?").ok()?;
-        if let Some(captures) = re.captures(line) {
-            let name = captures.get(1)?.as_str();
-            let params = captures.get(2).map_or("", |m| m.as_str());
-            let return_type = captures.get(3).map_or("void", |m| m.as_str());
+    fn qualify(&self, scope: &[String], name: &str) -> String {
+        if scope.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", scope.join("."), name)
+        }
+    }
 
-            let signature = if return_type == "void" {
-                format!("fn {}({})", name, params)
-            } else {
-                format!("fn {}({}) -> {}", name, params, return_type)
-            };
+    /// `import` declarations and re-exports, one `Import` symbol per module
+    /// specifier and one for the file's local export lists
+    fn extract_imports(&self, root: tree_sitter::Node, content: &str, file_path: &str) -> Vec<Symbol> {
+        let mut modules: Vec<(Option<String>, tree_sitter::Node, Vec<typescript::ImportBinding>)> = Vec::new();
 
-            let location = Location {
-                file: file_path.to_string(),
-                line: line_num as u32,
-                column: line.find("fn").unwrap_or(0) as u32,
-                end_line: line_num as u32,
-                end_column: (line.find("fn").unwrap_or(0) + line.len()) as u32,
+        let mut cursor = root.walk();
+        for statement in root.named_children(&mut cursor) {
+            let is_export = match statement.kind() {
+                "import_statement" => false,
+                "export_statement" => true,
+                _ => continue,
             };
+            let source = statement.child_by_field_name("source")
+                .and_then(|source| self.get_node_text(Some(source), content))
+                .map(|source| source.trim_matches(['"', '\'', '`']).to_string());
+            let bindings = self.import_bindings(statement, content, source.as_deref(), is_export);
+            if is_export && source.is_none() && bindings.is_empty() {
+                continue;
+            }
 
-            Some(Symbol {
-                id: format!("{}:{}", file_path, name),
-                kind: SymbolKind::Function,
-                name: name.to_string(),
-                qualified_name: name.to_string(),
-                location,
-                signature: Some(signature),
-                type_info: Some(return_type.to_string()),
-                visibility: Visibility::Public,
-                language: "intent".to_string(),
-                metadata: serde_json::json!({
-                    "function": true,
-                    "parameters": params
-                }),
-                content_hash: "".to_string(),
-                last_indexed: chrono::Utc::now().timestamp(),
-            })
-        } else {
-            None
+            match modules.iter_mut().find(|(known, _, _)| *known == source) {
+                Some((_, _, known)) => known.extend(bindings),
+                None => modules.push((source, statement, bindings)),
+            }
         }
-    }
 
-    fn extract_relationships(&self, _content: &str, _file_path: &str, _symbols: &[Symbol]) -> Vec<Relationship> {
-        // For now, skip relationship extraction for Intent files
-        // TODO: Implement proper relationship extraction with context tracking
-        Vec::new()
+        modules.into_iter()
+            .map(|(source, statement, bindings)| {
+                let (id, name) = match &source {
+                    Some(source) => (format!("{}:import:{}", file_path, source), source.clone()),
+                    None => (format!("{}:export", file_path), "export".to_string()),
+                };
+                Symbol {
+                    id,
+                    kind: SymbolKind::Import,
+                    name: name.clone(),
+                    qualified_name: name,
+                    location: self.node_location(statement, file_path),
+                    signature: None,
+                    type_info: None,
+                    visibility: Visibility::Public,
+                    language: self.language().to_string(),
+                    metadata: serde_json::json!({
+                        "bindings": bindings
+                    }),
+                    content_hash: "".to_string(),
+                    last_indexed: chrono::Utc::now().timestamp(),
+                }
+            })
+            .collect()
     }
 
-    fn extract_method_calls(&self, line: &str, file_path: &str, line_num: usize, symbol_map: &HashMap<&str, &Symbol>) -> Option<Vec<Relationship>> {
-        let mut relationships = Vec::new();
-
-        // Simple regex to find method calls: word( or word.word(
-        let re = regex::Regex::new(r"(\w+(?:\.\w+)*)\s*\(").ok()?;
-
-        for capture in re.captures_iter(line) {
-            if let Some(method_ref) = capture.get(1) {
-                let method_name = method_ref.as_str();
-
-                // Try to find the method in our symbols
-                if let Some(called_symbol) = symbol_map.get(method_name) {
-                    // For now, we don't track the caller context in this simple parser
-                    // In a real implementation, we'd need to track the current context/method
-                    let location = Location {
-                        file: file_path.to_string(),
-                        line: line_num as u32,
-                        column: method_ref.start() as u32,
-                        end_line: line_num as u32,
-                        end_column: method_ref.end() as u32,
-                    };
-
-                    relationships.push(Relationship {
-                        from_id: format!("{}:unknown_caller", file_path), // Placeholder
-                        to_id: called_symbol.id.clone(),
-                        kind: RelationshipKind::Calls,
-                        location,
-                        metadata: serde_json::json!({}),
-                    });
+    fn import_bindings(&self, statement: tree_sitter::Node, content: &str, source: Option<&str>, export: bool) -> Vec<typescript::ImportBinding> {
+        let binding = |alias: String, name: Option<String>| typescript::ImportBinding {
+            alias,
+            source: source.map(str::to_string),
+            name,
+            export,
+        };
+        let mut bindings = Vec::new();
+
+        let mut cursor = statement.walk();
+        for child in statement.children(&mut cursor) {
+            match child.kind() {
+                "import_clause" => {
+                    let mut clause_cursor = child.walk();
+                    for part in child.named_children(&mut clause_cursor) {
+                        match part.kind() {
+                            // Default import
+                            "identifier" => bindings.extend(self.get_node_text(Some(part), content).map(|alias| binding(alias, Some("default".to_string())))),
+                            "namespace_import" => {
+                                let mut namespace_cursor = part.walk();
+                                let alias = part.named_children(&mut namespace_cursor).find(|n| n.kind() == "identifier");
+                                bindings.extend(self.get_node_text(alias, content).map(|alias| binding(alias, None)));
+                            }
+                            "named_imports" => bindings.extend(self.specifiers(part, content, "import_specifier").map(|(name, alias)| binding(alias, Some(name)))),
+                            _ => {}
+                        }
+                    }
+                }
+                "export_clause" => {
+                    bindings.extend(self.specifiers(child, content, "export_specifier").map(|(name, alias)| binding(alias, Some(name))));
+                }
+                // `export * as ns from`
+                "namespace_export" => {
+                    let mut namespace_cursor = child.walk();
+                    let alias = child.named_children(&mut namespace_cursor).last();
+                    bindings.extend(self.get_node_text(alias, content).map(|alias| binding(alias.trim_matches(['"', '\'']).to_string(), None)));
                 }
+                // `export * from`, the namespaced form being a `namespace_export`
+                "*" if export => bindings.push(binding("*".to_string(), None)),
+                _ => {}
             }
         }
 
-        if relationships.is_empty() {
-            None
-        } else {
-            Some(relationships)
+        // `export default foo;` re-exports a local name
+        if export && source.is_none() && self.is_default_value(statement) {
+            let value = statement.child_by_field_name("value").filter(|value| value.kind() == "identifier");
+            bindings.extend(self.get_node_text(value, content).map(|name| binding("default".to_string(), Some(name))));
         }
+        bindings
     }
-}
 
-impl crate::index::Parser for JavaParser {
-    fn can_parse(&self, file_path: &str) -> bool {
-        file_path.ends_with(".java")
+    fn is_default_value(&self, statement: tree_sitter::Node) -> bool {
+        let mut cursor = statement.walk();
+        let is_default = statement.children(&mut cursor).any(|child| child.kind() == "default");
+        is_default
     }
 
-    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
-        let tree = self.parse_tree(content)?;
-        let symbols = self.extract_symbols(&tree, content, file_path);
-        let relationships = self.extract_relationships(&tree, content, file_path, &symbols);
+    /// `(name, alias)` pairs of import or export specifiers
+    fn specifiers<'n>(&self, node: tree_sitter::Node<'n>, content: &'n str, kind: &'n str) -> impl Iterator<Item = (String, String)> + 'n {
+        let mut cursor = node.walk();
+        let specifiers: Vec<tree_sitter::Node<'n>> = node.named_children(&mut cursor).filter(|child| child.kind() == kind).collect();
+        specifiers.into_iter().filter_map(move |specifier| {
+            let name = content[specifier.child_by_field_name("name")?.byte_range()].trim_matches(['"', '\'']).to_string();
+            let alias = specifier.child_by_field_name("alias")
+                .map(|alias| content[alias.byte_range()].trim_matches(['"', '\'']).to_string())
+                .unwrap_or_else(|| name.clone());
+            Some((name, alias))
+        })
+    }
+
+    /// Text of a type annotation without the colon
+    fn annotation(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
+        let node = node?;
+        let text = self.get_node_text(Some(node), content)?;
+        let text = match node.kind() {
+            "type_annotation" => text.trim_start_matches(':').trim().to_string(),
+            _ => text,
+        };
+        Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    /// Declared type of a variable or field: its annotation, or the class it is constructed from
+    fn declared_type(&self, node: tree_sitter::Node, content: &str) -> Option<String> {
+        self.annotation(node.child_by_field_name("type"), content).or_else(|| {
+            let value = node.child_by_field_name("value").filter(|value| value.kind() == "new_expression")?;
+            self.get_node_text(value.child_by_field_name("constructor"), content)
+        })
+    }
+
+    /// Parameter names with their annotated types
+    fn parameters(&self, node: Option<tree_sitter::Node>, content: &str) -> Vec<serde_json::Value> {
+        let Some(node) = node else {
+            return Vec::new();
+        };
+        // `x => x` has a bare identifier for its parameter
+        if node.kind() == "identifier" {
+            return vec![serde_json::json!({ "name": content[node.byte_range()].to_string() })];
+        }
+
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .filter(|parameter| matches!(parameter.kind(), "required_parameter" | "optional_parameter" | "identifier" | "assignment_pattern" | "rest_pattern"))
+            .filter_map(|parameter| {
+                let pattern = match parameter.kind() {
+                    "identifier" => Some(parameter),
+                    "assignment_pattern" => parameter.child_by_field_name("left"),
+                    _ => parameter.child_by_field_name("pattern"),
+                }?;
+                let name = self.get_node_text(Some(pattern), content)?.trim_start_matches("...").to_string();
+                Some(match self.annotation(parameter.child_by_field_name("type"), content) {
+                    Some(type_name) => serde_json::json!({ "name": name, "type": type_name }),
+                    None => serde_json::json!({ "name": name }),
+                })
+            })
+            .collect()
+    }
+
+    /// Declaration text up to the body, whitespace collapsed
+    fn signature(&self, node: tree_sitter::Node, content: &str) -> Option<String> {
+        let end = node.child_by_field_name("body").map_or(node.end_byte(), |body| body.start_byte());
+        let text = content[node.start_byte()..end].trim_end().trim_end_matches(['=', '>', ';']).trim_end();
+        Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    /// Function or method; `declaration` is the node carrying the name, which
+    /// for `const f = () => {}` is the declarator around `function`
+    fn extract_function(
+        &self,
+        declaration: tree_sitter::Node,
+        function: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        scope: &[String],
+        kind: SymbolKind,
+    ) -> Option<Symbol> {
+        let name = self.declaration_name(declaration, content)?;
+        let qualified_name = self.qualify(scope, &name);
+        let (exported, default) = self.export_flags(declaration);
+
+        let signature = if declaration.id() == function.id() {
+            self.signature(function, content)?
+        } else {
+            let parameters = self.get_node_text(function.child_by_field_name("parameters").or_else(|| function.child_by_field_name("parameter")), content).unwrap_or_default();
+            let return_type = self.get_node_text(function.child_by_field_name("return_type"), content).unwrap_or_default();
+            format!("{}{}{}", name, parameters, return_type).split_whitespace().collect::<Vec<_>>().join(" ")
+        };
+        let parameters = self.parameters(function.child_by_field_name("parameters").or_else(|| function.child_by_field_name("parameter")), content);
+        let return_type = self.annotation(function.child_by_field_name("return_type"), content);
+        let is_async = {
+            let mut cursor = function.walk();
+            let is_async = function.children(&mut cursor).any(|child| child.kind() == "async");
+            is_async
+        };
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind,
+            name,
+            qualified_name,
+            location: self.node_location(declaration, file_path),
+            signature: Some(signature),
+            type_info: return_type,
+            visibility: self.member_visibility(declaration, content),
+            language: self.language().to_string(),
+            metadata: serde_json::json!({
+                "parameters": parameters,
+                "exported": exported,
+                "default": default,
+                "async": is_async,
+                "arrow": function.kind() == "arrow_function"
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// `private`, `protected` and `#private` members are not part of the public surface
+    fn member_visibility(&self, node: tree_sitter::Node, content: &str) -> Visibility {
+        let mut cursor = node.walk();
+        let modifier = node.children(&mut cursor)
+            .find(|child| child.kind() == "accessibility_modifier")
+            .and_then(|modifier| self.get_node_text(Some(modifier), content));
+        let private_name = node.child_by_field_name("name").is_some_and(|name| name.kind() == "private_property_identifier");
+        match modifier.as_deref() {
+            Some("private") => Visibility::Private,
+            Some("protected") => Visibility::Internal,
+            _ if private_name => Visibility::Private,
+            _ => Visibility::Public,
+        }
+    }
+
+    /// Class or interface, with its heritage clauses
+    fn extract_class(&self, declaration: tree_sitter::Node, class: tree_sitter::Node, content: &str, file_path: &str, scope: &[String]) -> Option<Symbol> {
+        let name = self.declaration_name(declaration, content)?;
+        let qualified_name = self.qualify(scope, &name);
+        let (exported, default) = self.export_flags(declaration);
+        let is_interface = class.kind() == "interface_declaration";
+
+        let mut extends = Vec::new();
+        let mut implements = Vec::new();
+        let mut cursor = class.walk();
+        for child in class.children(&mut cursor) {
+            let clauses = match child.kind() {
+                "class_heritage" => {
+                    let mut heritage_cursor = child.walk();
+                    child.named_children(&mut heritage_cursor).collect()
+                }
+                "extends_type_clause" => vec![child],
+                _ => continue,
+            };
+            for clause in clauses {
+                let target = if clause.kind() == "implements_clause" { &mut implements } else { &mut extends };
+                let mut clause_cursor = clause.walk();
+                for base in clause.named_children(&mut clause_cursor) {
+                    if base.kind() == "type_arguments" {
+                        continue;
+                    }
+                    if let Some(text) = self.get_node_text(Some(base), content) {
+                        let text: String = text.split_whitespace().collect();
+                        target.push(text.split('<').next().unwrap_or(&text).to_string());
+                    }
+                }
+            }
+        }
+
+        let mut metadata = serde_json::json!({
+            "extends": extends,
+            "implements": implements,
+            "exported": exported,
+            "default": default
+        });
+        if is_interface {
+            metadata["interface"] = true.into();
+        }
+        if class.kind() == "abstract_class_declaration" {
+            metadata["abstract"] = true.into();
+        }
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: if is_interface { SymbolKind::Type } else { SymbolKind::Class },
+            name,
+            qualified_name,
+            location: self.node_location(declaration, file_path),
+            signature: self.signature(class, content),
+            type_info: None,
+            visibility: Visibility::Public,
+            language: self.language().to_string(),
+            metadata,
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Class field or interface property; one holding a function is a method
+    fn extract_field(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope: &[String]) -> Option<Symbol> {
+        if let Some(value) = node.child_by_field_name("value").filter(|value| TYPESCRIPT_FUNCTIONS.contains(&value.kind())) {
+            return self.extract_function(node, value, content, file_path, scope, SymbolKind::Method);
+        }
+
+        let name = self.get_node_text(node.child_by_field_name("name"), content)?;
+        let qualified_name = self.qualify(scope, &name);
+        let type_name = self.declared_type(node, content);
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Field,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: self.get_node_text(Some(node), content).map(|text| text.split_whitespace().collect::<Vec<_>>().join(" ")),
+            type_info: type_name.clone(),
+            visibility: self.member_visibility(node, content),
+            language: self.language().to_string(),
+            metadata: serde_json::json!({
+                "field": true,
+                "type": type_name
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// `constructor(private db: Db)` declares a field `db`
+    fn extract_parameter_properties(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope: &[String]) -> Vec<Symbol> {
+        let is_constructor = self.get_node_text(node.child_by_field_name("name"), content).as_deref() == Some("constructor");
+        let Some(parameters) = node.child_by_field_name("parameters").filter(|_| is_constructor) else {
+            return Vec::new();
+        };
+
+        let mut cursor = parameters.walk();
+        parameters.named_children(&mut cursor)
+            .filter(|parameter| {
+                let mut parameter_cursor = parameter.walk();
+                let is_property = parameter.children(&mut parameter_cursor).any(|child| matches!(child.kind(), "accessibility_modifier" | "readonly" | "override_modifier"));
+                is_property
+            })
+            .filter_map(|parameter| {
+                let name = self.get_node_text(parameter.child_by_field_name("pattern"), content)?;
+                let qualified_name = self.qualify(scope, &name);
+                let type_name = self.annotation(parameter.child_by_field_name("type"), content);
+                Some(Symbol {
+                    id: format!("{}:{}", file_path, qualified_name),
+                    kind: SymbolKind::Field,
+                    name,
+                    qualified_name,
+                    location: self.node_location(parameter, file_path),
+                    signature: self.get_node_text(Some(parameter), content),
+                    type_info: type_name.clone(),
+                    visibility: self.member_visibility(parameter, content),
+                    language: self.language().to_string(),
+                    metadata: serde_json::json!({
+                        "field": true,
+                        "type": type_name
+                    }),
+                    content_hash: "".to_string(),
+                    last_indexed: chrono::Utc::now().timestamp(),
+                })
+            })
+            .collect()
+    }
+
+    fn extract_type_alias(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope: &[String]) -> Option<Symbol> {
+        let name = self.get_node_text(node.child_by_field_name("name"), content)?;
+        let qualified_name = self.qualify(scope, &name);
+        let (exported, default) = self.export_flags(node);
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Type,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: self.get_node_text(Some(node), content).map(|text| text.split_whitespace().collect::<Vec<_>>().join(" ")),
+            type_info: self.annotation(node.child_by_field_name("value"), content),
+            visibility: Visibility::Public,
+            language: self.language().to_string(),
+            metadata: serde_json::json!({
+                "alias": true,
+                "exported": exported,
+                "default": default
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Enum and its members
+    fn extract_enum(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope: &[String]) -> Vec<Symbol> {
+        let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) else {
+            return Vec::new();
+        };
+        let qualified_name = self.qualify(scope, &name);
+        let (exported, default) = self.export_flags(node);
+
+        let mut symbols = vec![Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Type,
+            name,
+            qualified_name: qualified_name.clone(),
+            location: self.node_location(node, file_path),
+            signature: self.signature(node, content),
+            type_info: None,
+            visibility: Visibility::Public,
+            language: self.language().to_string(),
+            metadata: serde_json::json!({
+                "enum": true,
+                "exported": exported,
+                "default": default
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        }];
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                let name_node = match member.kind() {
+                    "enum_assignment" => member.child_by_field_name("name"),
+                    "property_identifier" | "string" => Some(member),
+                    _ => None,
+                };
+                let Some(member_name) = self.get_node_text(name_node, content) else { continue };
+                let member_name = member_name.trim_matches(['"', '\'']).to_string();
+                let member_qualified_name = format!("{}.{}", qualified_name, member_name);
+                symbols.push(Symbol {
+                    id: format!("{}:{}", file_path, member_qualified_name),
+                    kind: SymbolKind::Field,
+                    name: member_name,
+                    qualified_name: member_qualified_name,
+                    location: self.node_location(member, file_path),
+                    signature: self.get_node_text(Some(member), content),
+                    type_info: None,
+                    visibility: Visibility::Public,
+                    language: self.language().to_string(),
+                    metadata: serde_json::json!({
+                        "field": true,
+                        "enum_member": true
+                    }),
+                    content_hash: "".to_string(),
+                    last_indexed: chrono::Utc::now().timestamp(),
+                });
+            }
+        }
+        symbols
+    }
+
+    /// `namespace N {}` or `declare module "m" {}`
+    fn extract_namespace(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope: &[String]) -> Option<Symbol> {
+        let name = self.declaration_name(node, content)?;
+        let qualified_name = self.qualify(scope, &name);
+        let (exported, default) = self.export_flags(node);
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Module,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: self.signature(node, content),
+            type_info: None,
+            visibility: Visibility::Public,
+            language: self.language().to_string(),
+            metadata: serde_json::json!({
+                "namespace": true,
+                "exported": exported,
+                "default": default
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Variable, or the function or class expression bound to it
+    fn extract_variable(&self, node: tree_sitter::Node, content: &str, file_path: &str, scope: &[String]) -> Option<Symbol> {
+        let name_node = node.child_by_field_name("name").filter(|name| name.kind() == "identifier")?;
+        let value = node.child_by_field_name("value");
+        match value {
+            Some(value) if TYPESCRIPT_FUNCTIONS.contains(&value.kind()) => {
+                return self.extract_function(node, value, content, file_path, scope, SymbolKind::Function);
+            }
+            Some(value) if value.kind() == "class" => return self.extract_class(node, value, content, file_path, scope),
+            _ => {}
+        }
+
+        let name = self.get_node_text(Some(name_node), content)?;
+        let qualified_name = self.qualify(scope, &name);
+        let (exported, default) = self.export_flags(node);
+        let type_name = self.declared_type(node, content);
+        let declaration_kind = node.parent()
+            .and_then(|declaration| declaration.child_by_field_name("kind").or_else(|| declaration.child(0)))
+            .and_then(|kind| self.get_node_text(Some(kind), content));
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Variable,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: declaration_kind.as_ref().and_then(|kind| {
+                let declarator = self.get_node_text(Some(node), content)?;
+                let declarator = declarator.split_whitespace().collect::<Vec<_>>().join(" ");
+                Some(format!("{} {}", kind, declarator.chars().take(120).collect::<String>()))
+            }),
+            type_info: type_name.clone(),
+            visibility: Visibility::Public,
+            language: self.language().to_string(),
+            metadata: serde_json::json!({
+                "type": type_name,
+                "constant": declaration_kind.as_deref() == Some("const"),
+                "exported": exported,
+                "default": default
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    fn extract_relationships(&self, tree: &Tree, content: &str, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(tree, content, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, tree: &Tree, content: &str, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let mut relationships = Vec::new();
+        let resolver = typescript::Resolver::new(symbol_map);
+        self.extract_relationships_from_tree(tree.root_node(), content, file_path, &resolver, &mut relationships, &[]);
+        relationships
+    }
+
+    fn extract_relationships_from_tree(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &typescript::Resolver,
+        relationships: &mut Vec<Relationship>,
+        scope: &[String],
+    ) {
+        match node.kind() {
+            "call_expression" | "new_expression" => {
+                relationships.extend(self.extract_call_relationship(node, content, file_path, resolver, scope));
+            }
+            "class_declaration" | "abstract_class_declaration" | "interface_declaration" | "class" | "variable_declarator" => {
+                relationships.extend(self.extract_heritage_relationships(node, content, file_path, resolver, scope));
+            }
+            _ => {}
+        }
+
+        let mut new_scope = scope.to_vec();
+        new_scope.extend(self.scope_segment(node, content));
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.extract_relationships_from_tree(child, content, file_path, resolver, relationships, &new_scope);
+        }
+    }
+
+    /// Member expression as path segments, e.g. `this.api.get`
+    fn reference_path(&self, node: tree_sitter::Node, content: &str) -> Option<Vec<String>> {
+        match node.kind() {
+            "identifier" | "this" | "super" => Some(vec![self.get_node_text(Some(node), content)?]),
+            "member_expression" => {
+                let mut path = self.reference_path(node.child_by_field_name("object")?, content)?;
+                path.push(self.get_node_text(node.child_by_field_name("property"), content)?);
+                Some(path)
+            }
+            "parenthesized_expression" | "non_null_expression" => self.reference_path(node.named_child(0)?, content),
+            _ => None,
+        }
+    }
+
+    /// Call of a function or method, or `new` of a class: its constructor is
+    /// called, or the class referenced when it declares none
+    fn extract_call_relationship(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &typescript::Resolver,
+        scope: &[String],
+    ) -> Option<Relationship> {
+        let is_new = node.kind() == "new_expression";
+        let callee = node.child_by_field_name(if is_new { "constructor" } else { "function" })?;
+        let path = self.reference_path(callee, content)?;
+
+        // Only functions and methods call anything
+        let caller_symbol = resolver.symbol(file_path, &scope.join("."))
+            .filter(|symbol| matches!(symbol.kind, SymbolKind::Function | SymbolKind::Method))?;
+        let location = self.node_location(node, file_path);
+
+        let target = resolver.resolve(file_path, scope, &path);
+        let (target, kind) = match target {
+            Some(class) if is_new && typescript::is_type(class) => {
+                let mut constructor_path = path.clone();
+                constructor_path.push("constructor".to_string());
+                match resolver.resolve(file_path, scope, &constructor_path) {
+                    Some(constructor) => (Some(constructor), RelationshipKind::Calls),
+                    None => (Some(class), RelationshipKind::References),
+                }
+            }
+            target => (target, RelationshipKind::Calls),
+        };
+
+        match target {
+            Some(called_symbol) => Some(Relationship {
+                from_id: caller_symbol.id.clone(),
+                to_id: called_symbol.id.clone(),
+                kind,
+                location,
+                metadata: serde_json::json!({}),
+            }),
+            // Record the call so it can be re-resolved once a matching symbol appears
+            None => Some(Relationship::unresolved(caller_symbol.id.clone(), &path.join("."), kind, location)),
+        }
+    }
+
+    /// `Extends` and `Implements` edges of a class or interface
+    fn extract_heritage_relationships(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &typescript::Resolver,
+        scope: &[String],
+    ) -> Vec<Relationship> {
+        let name = match node.kind() {
+            "variable_declarator" if node.child_by_field_name("value").is_some_and(|value| value.kind() == "class") => {
+                self.get_node_text(node.child_by_field_name("name"), content)
+            }
+            "variable_declarator" => None,
+            // Bound classes were handled with their declarator
+            "class" if !self.is_default_export(node) => None,
+            _ => self.declaration_name(node, content),
+        };
+        let Some(class) = name.and_then(|name| resolver.symbol(file_path, &self.qualify(scope, &name))) else {
+            return Vec::new();
+        };
+        let location = self.node_location(node, file_path);
+
+        let clauses = [("extends", RelationshipKind::Extends), ("implements", RelationshipKind::Implements)];
+        clauses.into_iter()
+            .flat_map(|(clause, kind)| typescript::heritage(class, clause).map(move |base| (base, kind.clone())))
+            .map(|(base, kind)| match resolver.resolve_base(class, base) {
+                Some(base) => Relationship {
+                    from_id: class.id.clone(),
+                    to_id: base.id.clone(),
+                    kind,
+                    location: location.clone(),
+                    metadata: serde_json::json!({}),
+                },
+                None => Relationship::unresolved(class.id.clone(), base, kind, location.clone()),
+            })
+            .collect()
+    }
+
+    fn get_node_text(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
+        node.map(|n| content[n.byte_range()].to_string())
+    }
+
+    fn node_location(&self, node: tree_sitter::Node, file_path: &str) -> Location {
+        let start = node.start_position();
+        let end = node.end_position();
+
+        Location {
+            file: file_path.to_string(),
+            line: start.row as u32,
+            column: start.column as u32,
+            end_line: end.row as u32,
+            end_column: end.column as u32,
+        }
+    }
+}
+
+/// File-wide facts needed while walking a C or C++ file
+struct CContext {
+    /// `c` or `cpp`, depending on the grammar the file is parsed with
+    language: &'static str,
+    /// Names of the namespaces the file opens, to tell `ns::f` from `Class::f` defined out of line
+    namespaces: HashSet<String>,
+}
+
+/// Function body being walked for relationships
+struct CFunction<'a> {
+    symbol: &'a Symbol,
+    /// Namespaces and classes the function is declared in, outermost first
+    enclosing: Vec<String>,
+    class: Option<&'a Symbol>,
+    /// Declared types of parameters and locals
+    locals: HashMap<String, String>,
+}
+
+impl CParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parser for C++ sources and headers
+    pub fn cpp() -> Self {
+        Self { cpp: true, ..Self::default() }
+    }
+
+    /// Search these include directories and define these macros for every file
+    pub fn with_options(mut self, options: c::Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// C++ files, and `.h` headers that use C++ constructs, get the C++ grammar
+    fn is_cpp(&self, file_path: &str, content: &str) -> bool {
+        self.cpp || (file_path.ends_with(".h") && c::looks_like_cpp(content))
+    }
+
+    fn parse_tree(&self, content: &str, cpp: bool) -> anyhow::Result<Tree> {
+        let mut parser = TreeParser::new();
+        let language = if cpp { tree_sitter_cpp::LANGUAGE } else { tree_sitter_c::LANGUAGE };
+        parser.set_language(&language.into())?;
+
+        let tree = parser.parse(content, None)
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse {} code", if cpp { "C++" } else { "C" }))?;
+
+        Ok(tree)
+    }
+
+    fn context(&self, root: tree_sitter::Node, content: &str, cpp: bool) -> CContext {
+        let mut namespaces = HashSet::new();
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            if node.kind() == "namespace_definition" {
+                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
+                    namespaces.extend(name.split("::").map(|segment| segment.trim().to_string()));
+                }
+            }
+            if node.kind() != "compound_statement" {
+                let mut cursor = node.walk();
+                stack.extend(node.named_children(&mut cursor));
+            }
+        }
+
+        CContext {
+            language: if cpp { "cpp" } else { "c" },
+            namespaces,
+        }
+    }
+
+    fn extract_symbols(&self, tree: &Tree, content: &str, file_path: &str, context: &CContext) -> Vec<Symbol> {
+        let root = tree.root_node();
+        let mut symbols = vec![self.file_symbol(root, content, file_path, context)];
+        self.walk_tree(root, content, file_path, context, &mut symbols, &[]);
+
+        // Repeated declarations share an id; the definition wins over a prototype
+        let mut unique: Vec<Symbol> = Vec::with_capacity(symbols.len());
+        let mut positions = HashMap::new();
+        for symbol in symbols {
+            match positions.get(&symbol.id) {
+                Some(&position) => {
+                    if c::is_declaration(&unique[position]) && !c::is_declaration(&symbol) {
+                        unique[position] = symbol;
+                    }
+                }
+                None => {
+                    positions.insert(symbol.id.clone(), unique.len());
+                    unique.push(symbol);
+                }
+            }
+        }
+        unique
+    }
+
+    /// `Module` standing for the whole file, which `#include` edges connect
+    fn file_symbol(&self, root: tree_sitter::Node, content: &str, file_path: &str, context: &CContext) -> Symbol {
+        let name = std::path::Path::new(file_path).file_name()
+            .map_or_else(|| file_path.to_string(), |name| name.to_string_lossy().to_string());
+        let includes: Vec<c::Include> = c::Preprocessor::all_branches(content)
+            .includes(root)
+            .into_iter()
+            .map(|(include, _)| include)
+            .collect();
+
+        Symbol {
+            id: format!("{}:{}", file_path, name),
+            kind: SymbolKind::Module,
+            name: name.clone(),
+            qualified_name: name,
+            location: self.node_location(root, file_path),
+            signature: None,
+            type_info: None,
+            visibility: Visibility::Public,
+            language: context.language.to_string(),
+            metadata: serde_json::json!({
+                "file": true,
+                "header": c::is_header(file_path),
+                "includes": includes
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn walk_tree(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CContext, symbols: &mut Vec<Symbol>, scope: &[String]) {
+        match node.kind() {
+            "function_definition" => {
+                if let Some(declarator) = node.child_by_field_name("declarator") {
+                    symbols.extend(self.extract_function(node, declarator, content, file_path, context, scope));
+                }
+            }
+            "declaration" | "field_declaration" => {
+                symbols.extend(self.extract_declaration(node, content, file_path, context, scope));
+            }
+            "struct_specifier" | "class_specifier" | "union_specifier" if node.child_by_field_name("body").is_some() => {
+                symbols.extend(self.extract_class(node, content, file_path, context, scope));
+            }
+            "enum_specifier" if node.child_by_field_name("body").is_some() => {
+                symbols.extend(self.extract_enum(node, content, file_path, context, scope));
+            }
+            "type_definition" | "alias_declaration" => {
+                symbols.extend(self.extract_typedefs(node, content, file_path, context, scope));
+            }
+            "namespace_definition" => {
+                symbols.extend(self.extract_namespace(node, content, file_path, context, scope));
+            }
+            // Locals and local classes belong to the function
+            "compound_statement" => return,
+            _ => {}
+        }
+
+        let mut new_scope = scope.to_vec();
+        new_scope.extend(self.scope_segments(node, content));
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.walk_tree(child, content, file_path, context, symbols, &new_scope);
+        }
+    }
+
+    /// Names a namespace or class adds to the qualified names of its members
+    fn scope_segments(&self, node: tree_sitter::Node, content: &str) -> Vec<String> {
+        match node.kind() {
+            "namespace_definition" => self.get_node_text(node.child_by_field_name("name"), content)
+                .map(|name| name.split("::").map(|segment| segment.trim().to_string()).collect())
+                .unwrap_or_default(),
+            "struct_specifier" | "class_specifier" | "union_specifier" if node.child_by_field_name("body").is_some() => {
+                self.class_name(node, content).into_iter().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Name of a struct, class, union or enum; `typedef struct { ... } Name`
+    /// names an anonymous one after the typedef
+    fn class_name(&self, node: tree_sitter::Node, content: &str) -> Option<String> {
+        match node.child_by_field_name("name") {
+            Some(name) => self.name_segments(name, content)?.pop(),
+            None => {
+                let typedef = node.parent().filter(|parent| parent.kind() == "type_definition")?;
+                let (name, _) = self.declarator_name(typedef.child_by_field_name("declarator")?)?;
+                self.get_node_text(Some(name), content)
+            }
+        }
+    }
+
+    /// Nearest ancestor that isn't a preprocessor conditional, template or `extern "C"` block
+    fn declaration_parent<'t>(&self, node: tree_sitter::Node<'t>) -> Option<tree_sitter::Node<'t>> {
+        let mut parent = node.parent();
+        while let Some(current) = parent {
+            if !(current.kind().starts_with("preproc_") || matches!(current.kind(), "template_declaration" | "linkage_specification")) {
+                break;
+            }
+            parent = current.parent();
+        }
+        parent
+    }
+
+    fn in_class(&self, node: tree_sitter::Node) -> bool {
+        self.declaration_parent(node).is_some_and(|parent| parent.kind() == "field_declaration_list")
+    }
+
+    fn in_anonymous_namespace(&self, node: tree_sitter::Node) -> bool {
+        let mut parent = node.parent();
+        while let Some(current) = parent {
+            if current.kind() == "namespace_definition" && current.child_by_field_name("name").is_none() {
+                return true;
+            }
+            parent = current.parent();
+        }
+        false
+    }
+
+    /// Function declarator under pointer and reference declarators, with the
+    /// `*` and `&` they add to the return type; none for a function pointer
+    fn function_declarator<'t>(&self, declarator: tree_sitter::Node<'t>) -> Option<(tree_sitter::Node<'t>, String)> {
+        let mut node = declarator;
+        let mut suffix = String::new();
+        loop {
+            match node.kind() {
+                "function_declarator" => {
+                    // `(*callback)(int)` declares a pointer
+                    let inner = node.child_by_field_name("declarator")?;
+                    return (inner.kind() != "parenthesized_declarator").then_some((node, suffix));
+                }
+                "pointer_declarator" => suffix.push('*'),
+                "reference_declarator" => suffix.push('&'),
+                "attributed_declarator" => {}
+                _ => return None,
+            }
+            node = node.child_by_field_name("declarator").or_else(|| node.named_child(0))?;
+        }
+    }
+
+    /// Name node of a declarator, with the `*`, `&` and `[]` it adds to the declared type
+    fn declarator_name<'t>(&self, declarator: tree_sitter::Node<'t>) -> Option<(tree_sitter::Node<'t>, String)> {
+        let mut node = declarator;
+        let mut suffix = String::new();
+        loop {
+            match node.kind() {
+                "identifier" | "field_identifier" | "type_identifier" | "qualified_identifier" | "destructor_name" | "operator_name" => {
+                    return Some((node, suffix));
+                }
+                "pointer_declarator" => suffix.push('*'),
+                "reference_declarator" => suffix.push('&'),
+                "array_declarator" => suffix.push_str("[]"),
+                "init_declarator" | "function_declarator" | "parenthesized_declarator" | "attributed_declarator" => {}
+                _ => return None,
+            }
+            node = node.child_by_field_name("declarator").or_else(|| node.named_child(0))?;
+        }
+    }
+
+    /// Segments of a possibly qualified name, template arguments dropped;
+    /// `::name` starts with an empty segment
+    fn name_segments(&self, node: tree_sitter::Node, content: &str) -> Option<Vec<String>> {
+        match node.kind() {
+            "qualified_identifier" => {
+                let mut segments = match node.child_by_field_name("scope") {
+                    Some(scope) => self.name_segments(scope, content)?,
+                    None => vec![String::new()],
+                };
+                segments.extend(self.name_segments(node.child_by_field_name("name")?, content)?);
+                Some(segments)
+            }
+            "template_function" | "template_type" | "template_method" => self.name_segments(node.child_by_field_name("name")?, content),
+            _ => Some(vec![self.normalized_text(node, content)]),
+        }
+    }
+
+    fn qualify(&self, scope: &[String], name: &str) -> String {
+        if scope.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}::{}", scope.join("::"), name)
+        }
+    }
+
+    /// Function definition, or a prototype or member function declaration
+    fn extract_function(
+        &self,
+        node: tree_sitter::Node,
+        declarator: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        context: &CContext,
+        scope: &[String],
+    ) -> Option<Symbol> {
+        let (function, suffix) = self.function_declarator(declarator)?;
+        let mut segments: Vec<String> = self.name_segments(function.child_by_field_name("declarator")?, content)?
+            .into_iter()
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let name = segments.pop()?;
+
+        // `Class::method` defined out of line, unless the qualifier is a namespace
+        let in_class = self.in_class(node);
+        let is_method = in_class || segments.last().is_some_and(|qualifier| !context.namespaces.contains(qualifier));
+        let mut path = scope.to_vec();
+        path.extend(segments);
+        let qualified_name = self.qualify(&path, &name);
+
+        let is_static = self.has_specifier(node, content, "static");
+        let visibility = if in_class {
+            self.member_visibility(node, content)
+        } else if is_static || self.in_anonymous_namespace(node) {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        };
+        let class_name = path.last().filter(|_| is_method);
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: if is_method { SymbolKind::Method } else { SymbolKind::Function },
+            name: name.clone(),
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: self.signature(node, content),
+            type_info: node.child_by_field_name("type").map(|type_node| format!("{}{}", self.normalized_text(type_node, content), suffix)),
+            visibility,
+            language: context.language.to_string(),
+            metadata: serde_json::json!({
+                "declaration": node.kind() != "function_definition",
+                "parameters": self.parameters(function.child_by_field_name("parameters"), content),
+                "static": is_static,
+                "virtual": self.has_specifier(node, content, "virtual"),
+                "template": self.is_template(node),
+                "constructor": class_name == Some(&name),
+                "destructor": name.starts_with('~')
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Names and declared types of a parameter list
+    fn parameters(&self, node: Option<tree_sitter::Node>, content: &str) -> Vec<serde_json::Value> {
+        let Some(node) = node else {
+            return Vec::new();
+        };
+
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .filter(|parameter| matches!(parameter.kind(), "parameter_declaration" | "optional_parameter_declaration" | "variadic_parameter_declaration"))
+            .filter_map(|parameter| {
+                let type_name = self.get_node_text(parameter.child_by_field_name("type"), content)?;
+                let declarator = parameter.child_by_field_name("declarator").and_then(|declarator| self.declarator_name(declarator));
+                match declarator {
+                    Some((name, suffix)) => Some(serde_json::json!({
+                        "name": self.normalized_text(name, content),
+                        "type": format!("{}{}", type_name.split_whitespace().collect::<Vec<_>>().join(" "), suffix)
+                    })),
+                    // `f(void)` takes no parameters
+                    None if type_name == "void" => None,
+                    None => Some(serde_json::json!({ "type": type_name })),
+                }
+            })
+            .collect()
+    }
+
+    /// Functions, fields and variables declared at namespace or class scope
+    fn extract_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CContext, scope: &[String]) -> Vec<Symbol> {
+        let at_scope = self.declaration_parent(node)
+            .is_some_and(|parent| matches!(parent.kind(), "translation_unit" | "declaration_list" | "field_declaration_list"));
+        if !at_scope {
+            return Vec::new();
+        }
+
+        let mut cursor = node.walk();
+        let declarators: Vec<tree_sitter::Node> = node.children_by_field_name("declarator", &mut cursor).collect();
+        declarators.into_iter()
+            .filter_map(|declarator| match self.function_declarator(declarator) {
+                Some(_) => self.extract_function(node, declarator, content, file_path, context, scope),
+                None => self.extract_variable(node, declarator, content, file_path, context, scope),
+            })
+            .collect()
+    }
+
+    /// Class field, or a variable at namespace scope; `int Class::count = 0;`
+    /// defines a static field
+    fn extract_variable(
+        &self,
+        node: tree_sitter::Node,
+        declarator: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        context: &CContext,
+        scope: &[String],
+    ) -> Option<Symbol> {
+        let (name_node, suffix) = self.declarator_name(declarator)?;
+        let mut segments: Vec<String> = self.name_segments(name_node, content)?
+            .into_iter()
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let name = segments.pop()?;
+        let is_field = self.in_class(node) || !segments.is_empty();
+        let mut path = scope.to_vec();
+        path.extend(segments);
+        let qualified_name = self.qualify(&path, &name);
+
+        let type_name = node.child_by_field_name("type").map(|type_node| format!("{}{}", self.normalized_text(type_node, content), suffix));
+        let is_static = self.has_specifier(node, content, "static");
+        let visibility = if self.in_class(node) {
+            self.member_visibility(node, content)
+        } else if is_static || self.in_anonymous_namespace(node) {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        };
+        let is_declaration = self.has_specifier(node, content, "extern") && declarator.kind() != "init_declarator";
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: if is_field { SymbolKind::Field } else { SymbolKind::Variable },
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: self.signature(node, content),
+            type_info: type_name.clone(),
+            visibility,
+            language: context.language.to_string(),
+            metadata: serde_json::json!({
+                "type": type_name,
+                "declaration": is_declaration,
+                "static": is_static,
+                "constant": self.has_specifier(node, content, "const") || self.has_specifier(node, content, "constexpr")
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Struct, class or union with a body, and its base classes
+    fn extract_class(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CContext, scope: &[String]) -> Option<Symbol> {
+        let name = self.class_name(node, content)?;
+        let qualified_name = self.qualify(scope, &name);
+
+        let mut bases = Vec::new();
+        let mut cursor = node.walk();
+        if let Some(clause) = node.children(&mut cursor).find(|child| child.kind() == "base_class_clause") {
+            let mut clause_cursor = clause.walk();
+            bases.extend(clause.named_children(&mut clause_cursor)
+                .filter(|base| base.kind() != "access_specifier")
+                .map(|base| content[base.byte_range()].split_whitespace().collect::<String>()));
+        }
+
+        // A nested class is a member of the enclosing one
+        let visibility = match node.parent().filter(|parent| parent.kind() == "field_declaration") {
+            Some(member) if self.in_class(member) => self.member_visibility(member, content),
+            _ => Visibility::Public,
+        };
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Class,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: self.signature(node, content),
+            type_info: None,
+            visibility,
+            language: context.language.to_string(),
+            metadata: serde_json::json!({
+                "struct": node.kind() == "struct_specifier",
+                "union": node.kind() == "union_specifier",
+                "bases": bases,
+                "template": self.is_template(node)
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Enum and its enumerators
+    fn extract_enum(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CContext, scope: &[String]) -> Vec<Symbol> {
+        let Some(name) = self.class_name(node, content) else {
+            return Vec::new();
+        };
+        let qualified_name = self.qualify(scope, &name);
+        let scoped = {
+            let mut cursor = node.walk();
+            let scoped = node.children(&mut cursor).any(|child| matches!(child.kind(), "class" | "struct"));
+            scoped
+        };
+
+        let mut symbols = vec![Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Type,
+            name,
+            qualified_name: qualified_name.clone(),
+            location: self.node_location(node, file_path),
+            signature: self.signature(node, content),
+            type_info: None,
+            visibility: Visibility::Public,
+            language: context.language.to_string(),
+            metadata: serde_json::json!({
+                "enum": true,
+                "scoped": scoped
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        }];
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for enumerator in body.named_children(&mut cursor).filter(|child| child.kind() == "enumerator") {
+                let Some(member_name) = self.get_node_text(enumerator.child_by_field_name("name"), content) else { continue };
+                let member_qualified_name = format!("{}::{}", qualified_name, member_name);
+                symbols.push(Symbol {
+                    id: format!("{}:{}", file_path, member_qualified_name),
+                    kind: SymbolKind::Field,
+                    name: member_name,
+                    qualified_name: member_qualified_name,
+                    location: self.node_location(enumerator, file_path),
+                    signature: Some(self.normalized_text(enumerator, content)),
+                    type_info: None,
+                    visibility: Visibility::Public,
+                    language: context.language.to_string(),
+                    metadata: serde_json::json!({
+                        "field": true,
+                        "enum_member": true
+                    }),
+                    content_hash: "".to_string(),
+                    last_indexed: chrono::Utc::now().timestamp(),
+                });
+            }
+        }
+        symbols
+    }
+
+    /// `typedef` names and `using` aliases; `typedef struct Point { ... } Point`
+    /// and `typedef struct { ... } Point` are the struct itself
+    fn extract_typedefs(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CContext, scope: &[String]) -> Vec<Symbol> {
+        let type_node = node.child_by_field_name("type");
+        let tagged = type_node.filter(|type_node| type_node.kind().ends_with("_specifier") && type_node.kind() != "sized_type_specifier");
+        let tag = tagged.and_then(|tagged| self.class_name(tagged, content));
+        let has_body = tagged.is_some_and(|tagged| tagged.child_by_field_name("body").is_some());
+        let aliased = match (tagged, &tag) {
+            (Some(tagged), Some(tag)) => {
+                let keyword = tagged.child(0).map_or("struct", |keyword| &content[keyword.byte_range()]);
+                format!("{} {}", keyword, tag)
+            }
+            _ => type_node.map(|type_node| self.normalized_text(type_node, content)).unwrap_or_default(),
+        };
+
+        let names: Vec<(String, String)> = if node.kind() == "alias_declaration" {
+            self.get_node_text(node.child_by_field_name("name"), content).into_iter().map(|name| (name, String::new())).collect()
+        } else {
+            let mut cursor = node.walk();
+            let declarators: Vec<tree_sitter::Node> = node.children_by_field_name("declarator", &mut cursor).collect();
+            declarators.into_iter()
+                .filter_map(|declarator| self.declarator_name(declarator))
+                .map(|(name, suffix)| (self.normalized_text(name, content), suffix))
+                .collect()
+        };
+
+        names.into_iter()
+            .filter(|(name, suffix)| !(has_body && suffix.is_empty() && tag.as_ref() == Some(name)))
+            .map(|(name, suffix)| {
+                let qualified_name = self.qualify(scope, &name);
+                let type_name = format!("{}{}", aliased, suffix);
+                let signature = match node.kind() {
+                    "alias_declaration" => self.signature(node, content),
+                    _ => Some(format!("typedef {} {}", type_name, name)),
+                };
+                Symbol {
+                    id: format!("{}:{}", file_path, qualified_name),
+                    kind: SymbolKind::Type,
+                    name,
+                    qualified_name,
+                    location: self.node_location(node, file_path),
+                    signature,
+                    type_info: Some(type_name),
+                    visibility: Visibility::Public,
+                    language: context.language.to_string(),
+                    metadata: serde_json::json!({
+                        "typedef": true,
+                        "template": self.is_template(node)
+                    }),
+                    content_hash: "".to_string(),
+                    last_indexed: chrono::Utc::now().timestamp(),
+                }
+            })
+            .collect()
+    }
+
+    /// `namespace a {}` or `namespace a::b {}`; anonymous namespaces add no symbol
+    fn extract_namespace(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CContext, scope: &[String]) -> Option<Symbol> {
+        let mut path = scope.to_vec();
+        path.extend(self.scope_segments(node, content));
+        let name = path.pop().filter(|_| node.child_by_field_name("name").is_some())?;
+        let qualified_name = self.qualify(&path, &name);
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Module,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: self.signature(node, content),
+            type_info: None,
+            visibility: Visibility::Public,
+            language: context.language.to_string(),
+            metadata: serde_json::json!({
+                "namespace": true
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Access of a class member: the nearest preceding access specifier, else
+    /// private in a `class` and public in a `struct` or `union`
+    fn member_visibility(&self, node: tree_sitter::Node, content: &str) -> Visibility {
+        let member = node.parent().filter(|parent| parent.kind() == "template_declaration").unwrap_or(node);
+        let mut sibling = member.prev_named_sibling();
+        while let Some(current) = sibling {
+            if current.kind() == "access_specifier" {
+                return match content[current.byte_range()].trim_end_matches(':').trim() {
+                    "private" => Visibility::Private,
+                    "protected" => Visibility::Internal,
+                    _ => Visibility::Public,
+                };
+            }
+            sibling = current.prev_named_sibling();
+        }
+
+        let in_class = member.parent()
+            .and_then(|body| body.parent())
+            .is_some_and(|class| class.kind() == "class_specifier");
+        if in_class { Visibility::Private } else { Visibility::Public }
+    }
+
+    /// Whether a declaration has a storage class, qualifier or `virtual` written `word`
+    fn has_specifier(&self, node: tree_sitter::Node, content: &str, word: &str) -> bool {
+        let mut cursor = node.walk();
+        let found = node.children(&mut cursor).any(|child| {
+            matches!(child.kind(), "storage_class_specifier" | "type_qualifier" | "virtual") && &content[child.byte_range()] == word
+        });
+        found
+    }
+
+    fn is_template(&self, node: tree_sitter::Node) -> bool {
+        node.parent().is_some_and(|parent| parent.kind() == "template_declaration")
+    }
+
+    /// Declaration text up to the body, whitespace collapsed
+    fn signature(&self, node: tree_sitter::Node, content: &str) -> Option<String> {
+        let end = node.child_by_field_name("body").map_or(node.end_byte(), |body| body.start_byte());
+        let text = content[node.start_byte()..end].trim_end().trim_end_matches(';').trim_end();
+        Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    fn normalized_text(&self, node: tree_sitter::Node, content: &str) -> String {
+        content[node.byte_range()].split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// Namespaces named by `using namespace` at file scope
+    fn using_namespaces(&self, root: tree_sitter::Node, content: &str) -> Vec<String> {
+        let mut cursor = root.walk();
+        root.named_children(&mut cursor)
+            .filter(|node| node.kind() == "using_declaration")
+            .filter(|node| {
+                let mut cursor = node.walk();
+                let is_namespace = node.children(&mut cursor).any(|child| child.kind() == "namespace");
+                is_namespace
+            })
+            .filter_map(|node| Some(self.name_segments(node.named_child(0)?, content)?.join("::")))
+            .collect()
+    }
+
+    fn extract_relationships(&self, tree: &Tree, content: &str, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(tree, content, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, tree: &Tree, content: &str, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let root = tree.root_node();
+        let resolver = c::Resolver::new(symbol_map, &self.options);
+        let flags = resolver.flags(file_path);
+        let mut relationships = Vec::new();
+
+        // Includes in the branches the macros select depend on the included file
+        let file = resolver.file_symbol(file_path);
+        let mut includes: Vec<String> = Vec::new();
+        for (include, node) in c::Preprocessor::new(content, flags.defines.clone()).includes(root) {
+            let Some(header) = resolver.include(file_path, &include, &flags) else {
+                continue;
+            };
+            if includes.contains(&header) {
+                continue;
+            }
+            if let (Some(from), Some(to)) = (file, resolver.file_symbol(&header)) {
+                relationships.push(Relationship {
+                    from_id: from.id.clone(),
+                    to_id: to.id.clone(),
+                    kind: RelationshipKind::DependsOn,
+                    location: self.node_location(node, file_path),
+                    metadata: serde_json::json!({
+                        "include": include.path
+                    }),
+                });
+            }
+            includes.push(header);
+        }
+
+        let scope = resolver.file_scope(file_path, &includes, &flags, self.using_namespaces(root, content));
+        self.extract_relationships_from_tree(root, content, file_path, &resolver, &scope, &mut relationships, &[], None);
+        relationships
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn extract_relationships_from_tree<'a>(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &c::Resolver<'_, 'a>,
+        scope: &c::FileScope,
+        relationships: &mut Vec<Relationship>,
+        enclosing: &[String],
+        function: Option<&CFunction<'a>>,
+    ) {
+        match node.kind() {
+            "function_definition" if function.is_none() => {
+                let Some(function) = self.function_context(node, content, file_path, resolver, scope, enclosing) else {
+                    return;
+                };
+                let location = self.node_location(node, file_path);
+                relationships.extend(resolver.declarations(scope, function.symbol).into_iter().map(|declaration| Relationship {
+                    from_id: function.symbol.id.clone(),
+                    to_id: declaration.id.clone(),
+                    kind: RelationshipKind::Defines,
+                    location: location.clone(),
+                    metadata: serde_json::json!({}),
+                }));
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.extract_relationships_from_tree(body, content, file_path, resolver, scope, relationships, &function.enclosing, Some(&function));
+                }
+                return;
+            }
+            "call_expression" | "new_expression" => {
+                if let Some(function) = function {
+                    relationships.extend(self.extract_call_relationship(node, content, file_path, resolver, scope, function));
+                }
+            }
+            "struct_specifier" | "class_specifier" if function.is_none() && node.child_by_field_name("body").is_some() => {
+                relationships.extend(self.extract_base_relationships(node, content, file_path, resolver, scope, enclosing));
+            }
+            _ => {}
+        }
+
+        let mut new_scope = enclosing.to_vec();
+        if function.is_none() {
+            new_scope.extend(self.scope_segments(node, content));
+        }
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.extract_relationships_from_tree(child, content, file_path, resolver, scope, relationships, &new_scope, function);
+        }
+    }
+
+    /// Defined function with the declared types of its parameters and locals
+    fn function_context<'a>(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &c::Resolver<'_, 'a>,
+        scope: &c::FileScope,
+        enclosing: &[String],
+    ) -> Option<CFunction<'a>> {
+        let (function, _) = self.function_declarator(node.child_by_field_name("declarator")?)?;
+        let mut segments: Vec<String> = self.name_segments(function.child_by_field_name("declarator")?, content)?
+            .into_iter()
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let name = segments.pop()?;
+        let mut path = enclosing.to_vec();
+        path.extend(segments);
+
+        let symbol = resolver.symbol(file_path, &self.qualify(&path, &name), &|symbol| {
+            matches!(symbol.kind, SymbolKind::Function | SymbolKind::Method) && !c::is_declaration(symbol)
+        })?;
+        let class = if symbol.kind == SymbolKind::Method { resolver.class(scope, &path.join("::")) } else { None };
+
+        let mut locals = HashMap::new();
+        for parameter in symbol.metadata["parameters"].as_array().into_iter().flatten() {
+            if let (Some(name), Some(type_name)) = (parameter["name"].as_str(), parameter["type"].as_str()) {
+                locals.insert(name.to_string(), type_name.to_string());
+            }
+        }
+        if let Some(body) = node.child_by_field_name("body") {
+            self.local_types(body, content, &mut locals);
+        }
+
+        Some(CFunction { symbol, enclosing: path, class, locals })
+    }
+
+    /// Declared types of the locals in a function body; `auto p = new T` is a `T*`
+    fn local_types(&self, node: tree_sitter::Node, content: &str, locals: &mut HashMap<String, String>) {
+        if node.kind() == "declaration" {
+            let type_name = self.get_node_text(node.child_by_field_name("type"), content).unwrap_or_default();
+            let mut cursor = node.walk();
+            for declarator in node.children_by_field_name("declarator", &mut cursor) {
+                let Some((name, suffix)) = self.declarator_name(declarator) else { continue };
+                let created = declarator.child_by_field_name("value")
+                    .filter(|value| value.kind() == "new_expression" && type_name == "auto")
+                    .and_then(|value| self.get_node_text(value.child_by_field_name("type"), content));
+                let type_name = match created {
+                    Some(created) => format!("{}*", created),
+                    None => format!("{}{}", type_name, suffix),
+                };
+                locals.insert(self.normalized_text(name, content), type_name);
+            }
+        }
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.local_types(child, content, locals);
+        }
+    }
+
+    /// Call of a function or method, or `new` of a class: its constructor is
+    /// called, or the class referenced when it declares none
+    fn extract_call_relationship<'a>(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &c::Resolver<'_, 'a>,
+        scope: &c::FileScope,
+        function: &CFunction<'a>,
+    ) -> Option<Relationship> {
+        let (target, name) = match node.kind() {
+            "new_expression" => {
+                let type_text = self.get_node_text(node.child_by_field_name("type"), content)?;
+                let class = resolver.resolve_type(scope, &function.enclosing, &type_text);
+                (class, c::type_name(&type_text)?)
+            }
+            _ => self.callee(node.child_by_field_name("function")?, content, resolver, scope, function)?,
+        };
+
+        // Constructing a class calls its constructor
+        let (target, kind) = match target {
+            Some(class) if class.kind == SymbolKind::Class => {
+                let constructor = resolver.qualified(scope, &format!("{}::{}", class.qualified_name, class.name), &|symbol| symbol.kind == SymbolKind::Method);
+                match constructor {
+                    Some(constructor) => (Some(constructor), RelationshipKind::Calls),
+                    None => (Some(class), RelationshipKind::References),
+                }
+            }
+            target => (target, if node.kind() == "new_expression" { RelationshipKind::References } else { RelationshipKind::Calls }),
+        };
+
+        let location = self.node_location(node, file_path);
+        match target {
+            Some(target) => Some(Relationship {
+                from_id: function.symbol.id.clone(),
+                to_id: target.id.clone(),
+                kind,
+                location,
+                metadata: serde_json::json!({}),
+            }),
+            // Record the call so it can be re-resolved once a matching symbol appears
+            None => Some(Relationship::unresolved(function.symbol.id.clone(), &name, kind, location)),
+        }
+    }
+
+    /// Function, method or class a call expression's callee names, with the
+    /// name recorded when it doesn't resolve; none for calls through pointers
+    fn callee<'a>(
+        &self,
+        callee: tree_sitter::Node,
+        content: &str,
+        resolver: &c::Resolver<'_, 'a>,
+        scope: &c::FileScope,
+        function: &CFunction<'a>,
+    ) -> Option<(Option<&'a Symbol>, String)> {
+        let callable = |symbol: &Symbol| matches!(symbol.kind, SymbolKind::Function | SymbolKind::Method);
+        match callee.kind() {
+            "field_expression" => {
+                let method = self.name_segments(callee.child_by_field_name("field")?, content)?.pop()?;
+                let target = self.expression_type(callee.child_by_field_name("argument")?, content, resolver, scope, function)
+                    .and_then(|class| resolver.member(scope, class, &method, &callable));
+                Some((target, method))
+            }
+            "identifier" | "qualified_identifier" | "template_function" => {
+                let path = self.name_segments(callee, content)?;
+                if path.len() == 1 && function.locals.contains_key(&path[0]) {
+                    return None;
+                }
+                let target = resolver.lookup(scope, &function.enclosing, &path, &|symbol| callable(symbol) || symbol.kind == SymbolKind::Class);
+                Some((target, path.join("::")))
+            }
+            _ => None,
+        }
+    }
+
+    /// Class an expression evaluates to, or points to
+    fn expression_type<'a>(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        resolver: &c::Resolver<'_, 'a>,
+        scope: &c::FileScope,
+        function: &CFunction<'a>,
+    ) -> Option<&'a Symbol> {
+        let declared_type = |symbol: &'a Symbol| resolver.resolve_type(scope, &c::enclosing(symbol), symbol.type_info.as_deref()?);
+        match node.kind() {
+            "this" => function.class,
+            "identifier" | "qualified_identifier" => {
+                let path = self.name_segments(node, content)?;
+                if let Some(type_name) = function.locals.get(&path[0]).filter(|_| path.len() == 1) {
+                    return resolver.resolve_type(scope, &function.enclosing, type_name);
+                }
+                let variable = resolver.lookup(scope, &function.enclosing, &path, &|symbol| {
+                    matches!(symbol.kind, SymbolKind::Field | SymbolKind::Variable)
+                })?;
+                declared_type(variable)
+            }
+            "field_expression" => {
+                let owner = self.expression_type(node.child_by_field_name("argument")?, content, resolver, scope, function)?;
+                let field = self.name_segments(node.child_by_field_name("field")?, content)?.pop()?;
+                declared_type(resolver.member(scope, owner, &field, &|symbol| symbol.kind == SymbolKind::Field)?)
+            }
+            "call_expression" => {
+                let (target, _) = self.callee(node.child_by_field_name("function")?, content, resolver, scope, function)?;
+                let target = target?;
+                if target.kind == SymbolKind::Class { Some(target) } else { declared_type(target) }
+            }
+            "new_expression" => {
+                let type_text = self.get_node_text(node.child_by_field_name("type"), content)?;
+                resolver.resolve_type(scope, &function.enclosing, &type_text)
+            }
+            "pointer_expression" | "subscript_expression" => {
+                self.expression_type(node.child_by_field_name("argument")?, content, resolver, scope, function)
+            }
+            "parenthesized_expression" => self.expression_type(node.named_child(0)?, content, resolver, scope, function),
+            _ => None,
+        }
+    }
+
+    /// `Extends` edges of a class to its base classes
+    fn extract_base_relationships(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &c::Resolver,
+        scope: &c::FileScope,
+        enclosing: &[String],
+    ) -> Vec<Relationship> {
+        let class = self.class_name(node, content)
+            .and_then(|name| resolver.symbol(file_path, &self.qualify(enclosing, &name), &|symbol| symbol.kind == SymbolKind::Class));
+        let Some(class) = class else {
+            return Vec::new();
+        };
+        let location = self.node_location(node, file_path);
+
+        c::bases(class)
+            .map(|base| match resolver.resolve_type(scope, enclosing, base) {
+                Some(target) => Relationship {
+                    from_id: class.id.clone(),
+                    to_id: target.id.clone(),
+                    kind: RelationshipKind::Extends,
+                    location: location.clone(),
+                    metadata: serde_json::json!({}),
+                },
+                None => Relationship::unresolved(class.id.clone(), &c::type_name(base).unwrap_or_else(|| base.to_string()), RelationshipKind::Extends, location.clone()),
+            })
+            .collect()
+    }
+
+    fn get_node_text(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
+        node.map(|n| content[n.byte_range()].to_string())
+    }
+
+    fn node_location(&self, node: tree_sitter::Node, file_path: &str) -> Location {
+        let start = node.start_position();
+        let end = node.end_position();
+
+        Location {
+            file: file_path.to_string(),
+            line: start.row as u32,
+            column: start.column as u32,
+            end_line: end.row as u32,
+            end_column: end.column as u32,
+        }
+    }
+}
+
+/// Enclosing declarations while walking a C# file
+#[derive(Debug, Clone, Default)]
+struct CSharpContext {
+    /// Segments of the enclosing namespace
+    namespace: Vec<String>,
+    /// Qualified names of the enclosing types, innermost last
+    classes: Vec<String>,
+}
+
+impl CSharpContext {
+    fn namespace(&self) -> String {
+        self.namespace.join(".")
+    }
+
+    /// Qualified name of a declaration in the innermost type or namespace
+    fn qualify(&self, name: &str) -> String {
+        match self.classes.last() {
+            Some(class) => format!("{}.{}", class, name),
+            None if self.namespace.is_empty() => name.to_string(),
+            None => format!("{}.{}", self.namespace(), name),
+        }
+    }
+}
+
+/// Method, constructor, property or event whose body is being walked for relationships
+struct CSharpMember<'a> {
+    symbol: &'a Symbol,
+    /// Erased declared type and resolved type of parameters and locals
+    locals: HashMap<String, (String, Option<&'a Symbol>)>,
+}
+
+const CSHARP_TYPE_DECLARATIONS: &[&str] = &[
+    "class_declaration",
+    "struct_declaration",
+    "interface_declaration",
+    "record_declaration",
+    "enum_declaration",
+];
+
+impl CSharpParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn parse_tree(&self, content: &str) -> anyhow::Result<Tree> {
+        let mut parser = TreeParser::new();
+        parser.set_language(&tree_sitter_c_sharp::LANGUAGE.into())?;
+
+        let tree = parser.parse(content, None)
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse C# code"))?;
+
+        Ok(tree)
+    }
+
+    fn extract_symbols(&self, tree: &Tree, content: &str, file_path: &str) -> Vec<Symbol> {
+        let root = tree.root_node();
+
+        // Usings first, types are resolved through them
+        let mut symbols = Vec::new();
+        self.extract_usings(root, content, file_path, &mut symbols);
+        let scope = csharp::FileScope::from_symbols(&symbols);
+
+        // Walk the tree to find symbols
+        self.walk_tree(root, content, file_path, &mut symbols, &CSharpContext::default(), &scope);
+        self.disambiguate_overloads(&mut symbols, file_path);
+
+        symbols
+    }
+
+    /// Overloads share their qualified name, `Namespace.Type.Member`, so
+    /// their ids get the parameter types, e.g. `Acme.Store.Get(string, int)`;
+    /// other duplicates, such as a namespace opened twice, keep the first
+    fn disambiguate_overloads(&self, symbols: &mut Vec<Symbol>, file_path: &str) {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for symbol in symbols.iter().filter(|symbol| symbol.kind == SymbolKind::Method) {
+            *counts.entry(symbol.id.clone()).or_default() += 1;
+        }
+        for symbol in symbols.iter_mut() {
+            if symbol.kind == SymbolKind::Method && counts.get(&symbol.id).is_some_and(|&count| count > 1) {
+                let parameters: Vec<String> = serde_json::from_value(symbol.metadata["parameters"].clone()).unwrap_or_default();
+                symbol.id = format!("{}:{}({})", file_path, symbol.qualified_name, parameters.join(", "));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        symbols.retain(|symbol| seen.insert(symbol.id.clone()));
+    }
+
+    fn walk_tree(&self, node: tree_sitter::Node, content: &str, file_path: &str, symbols: &mut Vec<Symbol>, context: &CSharpContext, scope: &csharp::FileScope) {
+        match node.kind() {
+            "namespace_declaration" | "file_scoped_namespace_declaration" => {
+                symbols.extend(self.extract_namespace(node, content, file_path, context));
+            }
+            kind if CSHARP_TYPE_DECLARATIONS.contains(&kind) => {
+                symbols.extend(self.extract_type_declaration(node, content, file_path, context, scope));
+            }
+            "delegate_declaration" => {
+                symbols.extend(self.extract_delegate(node, content, file_path, context, scope));
+            }
+            "method_declaration" | "constructor_declaration" => {
+                symbols.extend(self.extract_method(node, content, file_path, context, scope));
+            }
+            "property_declaration" | "event_declaration" => {
+                symbols.extend(self.extract_property(node, content, file_path, context, scope));
+            }
+            "field_declaration" | "event_field_declaration" => {
+                symbols.extend(self.extract_field_declaration(node, content, file_path, context, scope));
+            }
+            "enum_member_declaration" => {
+                symbols.extend(self.extract_enum_member(node, content, file_path, context));
+            }
+            // Bodies declare no members
+            "block" | "arrow_expression_clause" | "accessor_list" => return,
+            _ => {}
+        }
+
+        let context = self.enter(node, content, context);
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.walk_tree(child, content, file_path, symbols, &context, scope);
+        }
+    }
+
+    /// Context of the children of a node: namespaces push their segments and
+    /// types their qualified name
+    fn enter(&self, node: tree_sitter::Node, content: &str, context: &CSharpContext) -> CSharpContext {
+        let mut new_context = context.clone();
+        let kind = node.kind();
+        let namespace = match kind {
+            // A file-scoped namespace covers every declaration of the file
+            "compilation_unit" => {
+                let mut cursor = node.walk();
+                let declaration = node.named_children(&mut cursor).find(|child| child.kind() == "file_scoped_namespace_declaration");
+                declaration.and_then(|declaration| self.namespace_name(declaration, content))
+            }
+            "namespace_declaration" => self.namespace_name(node, content),
+            _ => None,
+        };
+
+        if let Some(namespace) = namespace {
+            new_context.namespace.extend(namespace.split('.').map(str::to_string));
+        } else if CSHARP_TYPE_DECLARATIONS.contains(&kind) {
+            if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
+                new_context.classes.push(context.qualify(&name));
+            }
+        }
+        new_context
+    }
+
+    fn namespace_name(&self, node: tree_sitter::Node, content: &str) -> Option<String> {
+        self.get_node_text(node.child_by_field_name("name"), content)
+            .map(|name| name.split_whitespace().collect())
+    }
+
+    /// Using directives at the top of the file and in namespace bodies
+    fn extract_usings(&self, node: tree_sitter::Node, content: &str, file_path: &str, symbols: &mut Vec<Symbol>) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "using_directive" => symbols.extend(self.extract_using(child, content, file_path)),
+                "namespace_declaration" | "declaration_list" => self.extract_usings(child, content, file_path, symbols),
+                _ => {}
+            }
+        }
+    }
+
+    fn extract_using(&self, node: tree_sitter::Node, content: &str, file_path: &str) -> Option<Symbol> {
+        let mut cursor = node.walk();
+        let children: Vec<tree_sitter::Node> = node.children(&mut cursor).collect();
+        let is_static = children.iter().any(|child| child.kind() == "static");
+        let global = children.iter().any(|child| child.kind() == "global");
+        let alias_node = node.child_by_field_name("name");
+        let path_node = children.iter().rev().find(|child| child.is_named() && Some(**child) != alias_node)?;
+        let path = csharp::erase(&self.get_node_text(Some(*path_node), content)?);
+        let alias = self.get_node_text(alias_node, content);
+
+        let written = match &alias {
+            Some(alias) => format!("{} = {}", alias, path),
+            None => path.clone(),
+        };
+        let directive = csharp::UsingDirective { alias, path, is_static };
+
+        Some(Symbol {
+            id: format!("{}:using:{}", file_path, written),
+            kind: SymbolKind::Import,
+            name: written.clone(),
+            qualified_name: written,
+            location: self.node_location(node, file_path),
+            signature: None,
+            type_info: None,
+            visibility: Visibility::Public,
+            language: "csharp".to_string(),
+            metadata: serde_json::json!({
+                "using": directive,
+                "global": global
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    fn modifiers(&self, node: tree_sitter::Node, content: &str) -> Vec<String> {
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .filter(|child| child.kind() == "modifier")
+            .filter_map(|modifier| self.get_node_text(Some(modifier), content))
+            .collect()
+    }
+
+    /// Declared accessibility, or the default for where the declaration
+    /// appears: interface and enum members are public, other members
+    /// private, and types outside a type internal
+    fn visibility(&self, node: tree_sitter::Node, modifiers: &[String]) -> Visibility {
+        let has = |modifier: &str| modifiers.iter().any(|m| m == modifier);
+        if has("public") {
+            return Visibility::Public;
+        }
+        if has("internal") {
+            return Visibility::Internal;
+        }
+        if has("private") || has("protected") {
+            return Visibility::Private;
+        }
+
+        match node.parent().and_then(|list| list.parent()).map(|container| container.kind()) {
+            Some("interface_declaration" | "enum_declaration") => Visibility::Public,
+            Some(kind) if CSHARP_TYPE_DECLARATIONS.contains(&kind) => Visibility::Private,
+            _ => Visibility::Internal,
+        }
+    }
+
+    /// Attributes on a declaration, with their arguments
+    fn attributes(&self, node: tree_sitter::Node, content: &str) -> Vec<String> {
+        let mut cursor = node.walk();
+        let lists: Vec<tree_sitter::Node> = node.named_children(&mut cursor).filter(|child| child.kind() == "attribute_list").collect();
+        lists.into_iter()
+            .flat_map(|list| {
+                let mut list_cursor = list.walk();
+                let attributes: Vec<tree_sitter::Node> = list.named_children(&mut list_cursor).filter(|child| child.kind() == "attribute").collect();
+                attributes
+            })
+            .filter_map(|attribute| self.get_node_text(Some(attribute), content))
+            .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    /// Written base types of a declaration, with the qualified names each may
+    /// refer to; they resolve outside the declaration itself
+    fn bases(&self, node: tree_sitter::Node, content: &str, context: &CSharpContext, scope: &csharp::FileScope) -> Vec<serde_json::Value> {
+        let mut cursor = node.walk();
+        let Some(list) = node.named_children(&mut cursor).find(|child| child.kind() == "base_list") else {
+            return Vec::new();
+        };
+
+        let namespace = context.namespace();
+        let mut list_cursor = list.walk();
+        let bases: Vec<tree_sitter::Node> = list.named_children(&mut list_cursor).filter(|child| child.kind() != "argument_list").collect();
+        bases.into_iter()
+            // `record R(int X) : Base(X)` passes arguments to the base
+            .map(|base| match base.kind() {
+                "primary_constructor_base_type" => base.child_by_field_name("type").or_else(|| base.named_child(0)).unwrap_or(base),
+                _ => base,
+            })
+            .filter_map(|base| self.get_node_text(Some(base), content))
+            .map(|name| serde_json::json!({
+                "name": csharp::erase(&name),
+                "candidates": scope.type_candidates(&name, &namespace, &context.classes)
+            }))
+            .collect()
+    }
+
+    fn extract_namespace(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CSharpContext) -> Option<Symbol> {
+        let name = self.namespace_name(node, content)?;
+        let qualified_name = match node.kind() {
+            // The context of the file already includes its file-scoped namespace
+            "file_scoped_namespace_declaration" => name.clone(),
+            _ if context.namespace.is_empty() => name.clone(),
+            _ => format!("{}.{}", context.namespace(), name),
+        };
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Module,
+            name: name.rsplit('.').next().unwrap_or(&name).to_string(),
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: None,
+            type_info: None,
+            visibility: Visibility::Public,
+            language: "csharp".to_string(),
+            metadata: serde_json::json!({
+                "namespace": true
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// A class, struct, interface, record or enum, plus the properties of a
+    /// record's primary constructor; each part of a partial type is one symbol
+    fn extract_type_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CSharpContext, scope: &csharp::FileScope) -> Vec<Symbol> {
+        let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) else {
+            return Vec::new();
+        };
+        let qualified_name = context.qualify(&name);
+        let modifiers = self.modifiers(node, content);
+
+        let (kind, flag) = match node.kind() {
+            "interface_declaration" => (SymbolKind::Type, "interface"),
+            "struct_declaration" => (SymbolKind::Class, "struct"),
+            "record_declaration" => (SymbolKind::Class, "record"),
+            "enum_declaration" => (SymbolKind::Class, "enum"),
+            _ => (SymbolKind::Class, "class"),
+        };
+        let mut metadata = serde_json::json!({
+            "bases": self.bases(node, content, context, scope),
+            "partial": modifiers.iter().any(|m| m == "partial"),
+            "static": modifiers.iter().any(|m| m == "static"),
+            "decorators": self.attributes(node, content)
+        });
+        if flag != "class" {
+            metadata[flag] = true.into();
+        }
+        // `record struct` declares a value type
+        let mut cursor = node.walk();
+        if node.kind() == "record_declaration" && node.children(&mut cursor).any(|child| child.kind() == "struct") {
+            metadata["struct"] = true.into();
+        }
+
+        let mut symbols = vec![Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind,
+            name,
+            qualified_name: qualified_name.clone(),
+            location: self.node_location(node, file_path),
+            signature: None,
+            type_info: None,
+            visibility: self.visibility(node, &modifiers),
+            language: "csharp".to_string(),
+            metadata,
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        }];
+
+        if node.kind() == "record_declaration" {
+            symbols.extend(self.extract_record_parameters(node, content, file_path, &qualified_name, context, scope));
+        }
+        symbols
+    }
+
+    /// Properties the compiler generates for a record's primary constructor
+    fn extract_record_parameters(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        record: &str,
+        context: &CSharpContext,
+        scope: &csharp::FileScope,
+    ) -> Vec<Symbol> {
+        let mut cursor = node.walk();
+        let Some(parameters) = node.named_children(&mut cursor).find(|child| child.kind() == "parameter_list") else {
+            return Vec::new();
+        };
+        let record_context = self.enter(node, content, context);
+        let namespace = record_context.namespace();
+
+        // Properties written out in the body replace the generated ones
+        let mut declared = HashSet::new();
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut body_cursor = body.walk();
+            for member in body.named_children(&mut body_cursor).filter(|m| m.kind() == "property_declaration") {
+                declared.extend(self.get_node_text(member.child_by_field_name("name"), content));
+            }
+        }
+
+        let mut symbols = Vec::new();
+        let mut parameter_cursor = parameters.walk();
+        for parameter in parameters.named_children(&mut parameter_cursor).filter(|p| p.kind() == "parameter") {
+            let Some(name) = self.get_node_text(parameter.child_by_field_name("name"), content) else { continue };
+            let Some(type_text) = self.get_node_text(parameter.child_by_field_name("type"), content) else { continue };
+            if declared.contains(&name) {
+                continue;
+            }
+
+            let qualified_name = format!("{}.{}", record, name);
+            symbols.push(Symbol {
+                id: format!("{}:{}", file_path, qualified_name),
+                kind: SymbolKind::Field,
+                name,
+                qualified_name,
+                location: self.node_location(parameter, file_path),
+                signature: None,
+                type_info: Some(type_text.clone()),
+                visibility: Visibility::Public,
+                language: "csharp".to_string(),
+                metadata: serde_json::json!({
+                    "property": true,
+                    "types": scope.type_candidates(&type_text, &namespace, &record_context.classes)
+                }),
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            });
+        }
+        symbols
+    }
+
+    fn extract_delegate(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CSharpContext, scope: &csharp::FileScope) -> Option<Symbol> {
+        let name = self.get_node_text(node.child_by_field_name("name"), content)?;
+        let qualified_name = context.qualify(&name);
+        let return_type = self.get_node_text(node.child_by_field_name("type"), content)?;
+        let parameters_text = self.get_node_text(node.child_by_field_name("parameters"), content).unwrap_or_else(|| "()".to_string());
+        let (parameters, _, _) = self.parameters(node.child_by_field_name("parameters"), content);
+        let modifiers = self.modifiers(node, content);
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Type,
+            name: name.clone(),
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: Some(format!("delegate {} {}{}", return_type, name, parameters_text).split_whitespace().collect::<Vec<_>>().join(" ")),
+            type_info: Some(return_type.clone()),
+            visibility: self.visibility(node, &modifiers),
+            language: "csharp".to_string(),
+            metadata: serde_json::json!({
+                "delegate": true,
+                "parameters": parameters,
+                "types": scope.type_candidates(&return_type, &context.namespace(), &context.classes)
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Erased parameter types, how many of them are required, and whether
+    /// the last one is a `params` array
+    fn parameters(&self, node: Option<tree_sitter::Node>, content: &str) -> (Vec<String>, usize, bool) {
+        let Some(node) = node else {
+            return (Vec::new(), 0, false);
+        };
+
+        let mut types = Vec::new();
+        let mut required = 0;
+        let mut variadic = false;
+        let mut cursor = node.walk();
+        for (index, child) in node.children(&mut cursor).enumerate() {
+            if child.kind() == "parameter" {
+                let type_text = self.get_node_text(child.child_by_field_name("type"), content).unwrap_or_default();
+                types.push(csharp::erase(&type_text));
+
+                // A default value makes the parameter optional
+                let mut parameter_cursor = child.walk();
+                let optional = child.named_children(&mut parameter_cursor).any(|part| {
+                    !matches!(part.kind(), "attribute_list" | "modifier")
+                        && Some(part) != child.child_by_field_name("type")
+                        && Some(part) != child.child_by_field_name("name")
+                });
+                if !optional {
+                    required = types.len();
+                }
+            } else if node.field_name_for_child(index as u32) == Some("type") {
+                // `params T[] name` is written directly in the list
+                types.push(csharp::erase(&content[child.byte_range()]));
+                variadic = true;
+            }
+        }
+        (types, required, variadic)
+    }
+
+    fn extract_method(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CSharpContext, scope: &csharp::FileScope) -> Option<Symbol> {
+        let name = self.get_node_text(node.child_by_field_name("name"), content)?;
+        let qualified_name = context.qualify(&name);
+        let modifiers = self.modifiers(node, content);
+        let parameters_node = node.child_by_field_name("parameters");
+        let (parameters, required, variadic) = self.parameters(parameters_node, content);
+        let parameters_text = self.get_node_text(parameters_node, content).unwrap_or_else(|| "()".to_string());
+
+        let constructor = node.kind() == "constructor_declaration";
+        let return_type = self.get_node_text(node.child_by_field_name("returns"), content);
+        let signature = match &return_type {
+            Some(return_type) => format!("{} {}{}", return_type, name, parameters_text),
+            None => format!("{}{}", name, parameters_text),
+        };
+
+        let mut metadata = serde_json::json!({
+            "parameters": parameters,
+            "required": required,
+            "variadic": variadic,
+            "static": modifiers.iter().any(|m| m == "static"),
+            "decorators": self.attributes(node, content)
+        });
+        if constructor {
+            metadata["constructor"] = true.into();
+        }
+        if let Some(return_type) = &return_type {
+            metadata["types"] = scope.type_candidates(return_type, &context.namespace(), &context.classes).into();
+        }
+        // `this` on the first parameter declares an extension method
+        let first = parameters_node.and_then(|list| {
+            let mut cursor = list.walk();
+            let first = list.named_children(&mut cursor).find(|child| child.kind() == "parameter");
+            first
+        });
+        if first.is_some_and(|parameter| self.modifiers(parameter, content).iter().any(|m| m == "this")) {
+            metadata["extension"] = true.into();
+        }
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Method,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: Some(signature.split_whitespace().collect::<Vec<_>>().join(" ")),
+            type_info: return_type,
+            visibility: self.visibility(node, &modifiers),
+            language: "csharp".to_string(),
+            metadata,
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// A property, or an event with `add` and `remove` accessors
+    fn extract_property(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CSharpContext, scope: &csharp::FileScope) -> Option<Symbol> {
+        let name = self.get_node_text(node.child_by_field_name("name"), content)?;
+        let qualified_name = context.qualify(&name);
+        let type_text = self.get_node_text(node.child_by_field_name("type"), content)?;
+        let modifiers = self.modifiers(node, content);
+        let flag = if node.kind() == "event_declaration" { "event" } else { "property" };
+
+        let mut metadata = serde_json::json!({
+            "types": scope.type_candidates(&type_text, &context.namespace(), &context.classes),
+            "static": modifiers.iter().any(|m| m == "static"),
+            "decorators": self.attributes(node, content)
+        });
+        metadata[flag] = true.into();
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Field,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: None,
+            type_info: Some(type_text),
+            visibility: self.visibility(node, &modifiers),
+            language: "csharp".to_string(),
+            metadata,
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Fields, and events declared like fields
+    fn extract_field_declaration(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CSharpContext, scope: &csharp::FileScope) -> Vec<Symbol> {
+        let mut cursor = node.walk();
+        let Some(declaration) = node.named_children(&mut cursor).find(|child| child.kind() == "variable_declaration") else {
+            return Vec::new();
+        };
+        let type_text = self.get_node_text(declaration.child_by_field_name("type"), content);
+        let types = type_text.as_deref()
+            .map(|ty| scope.type_candidates(ty, &context.namespace(), &context.classes))
+            .unwrap_or_default();
+        let modifiers = self.modifiers(node, content);
+        let flag = if node.kind() == "event_field_declaration" { "event" } else { "field" };
+
+        let mut symbols = Vec::new();
+        let mut declaration_cursor = declaration.walk();
+        for declarator in declaration.named_children(&mut declaration_cursor).filter(|child| child.kind() == "variable_declarator") {
+            let Some(name) = self.get_node_text(declarator.child_by_field_name("name"), content) else { continue };
+            let qualified_name = context.qualify(&name);
+
+            let mut metadata = serde_json::json!({
+                "types": types,
+                "static": modifiers.iter().any(|m| m == "static" || m == "const"),
+                "const": modifiers.iter().any(|m| m == "const"),
+                "decorators": self.attributes(node, content)
+            });
+            metadata[flag] = true.into();
+
+            symbols.push(Symbol {
+                id: format!("{}:{}", file_path, qualified_name),
+                kind: SymbolKind::Field,
+                name,
+                qualified_name,
+                location: self.node_location(declarator, file_path),
+                signature: None,
+                type_info: type_text.clone(),
+                visibility: self.visibility(node, &modifiers),
+                language: "csharp".to_string(),
+                metadata,
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            });
+        }
+        symbols
+    }
+
+    /// Enum members are constants of the enum's own type
+    fn extract_enum_member(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &CSharpContext) -> Option<Symbol> {
+        let name = self.get_node_text(node.child_by_field_name("name"), content)?;
+        let enum_name = context.classes.last()?.clone();
+        let qualified_name = context.qualify(&name);
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: SymbolKind::Field,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: None,
+            type_info: enum_name.rsplit('.').next().map(str::to_string),
+            visibility: Visibility::Public,
+            language: "csharp".to_string(),
+            metadata: serde_json::json!({
+                "field": true,
+                "enum_constant": true,
+                "types": [enum_name]
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    fn extract_relationships(&self, tree: &Tree, content: &str, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(tree, content, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, tree: &Tree, content: &str, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let mut relationships = Vec::new();
+        let resolver = csharp::Resolver::new(symbol_map, file_path);
+
+        self.extract_relationships_from_tree(tree.root_node(), content, file_path, &resolver, &mut relationships, &CSharpContext::default(), None);
+
+        relationships
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn extract_relationships_from_tree(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &csharp::Resolver,
+        relationships: &mut Vec<Relationship>,
+        context: &CSharpContext,
+        member: Option<&CSharpMember>,
+    ) {
+        let kind = node.kind();
+        if CSHARP_TYPE_DECLARATIONS.contains(&kind) {
+            relationships.extend(self.extract_base_relationships(node, file_path, resolver));
+        } else if let Some(member) = member {
+            let rel = match kind {
+                "invocation_expression" => self.extract_invocation(node, content, file_path, resolver, context, member),
+                "object_creation_expression" | "constructor_initializer" => {
+                    self.extract_constructor_call(node, content, file_path, resolver, context, member)
+                }
+                _ => None,
+            };
+            relationships.extend(rel);
+        }
+
+        // Recurse
+        let new_context = self.enter(node, content, context);
+        let entered = match kind {
+            "method_declaration" | "constructor_declaration" | "property_declaration" | "event_declaration" => {
+                self.declared(node, resolver).map(|symbol| CSharpMember {
+                    symbol,
+                    locals: self.locals(node, content, resolver, &new_context),
+                })
+            }
+            _ => None,
+        };
+        // A nested type starts a new scope
+        let member = match kind {
+            kind if CSHARP_TYPE_DECLARATIONS.contains(&kind) => None,
+            _ => entered.as_ref().or(member),
+        };
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.extract_relationships_from_tree(child, content, file_path, resolver, relationships, &new_context, member);
+        }
+    }
+
+    /// Symbol extracted from a declaration node of this file
+    fn declared<'a>(&self, node: tree_sitter::Node, resolver: &csharp::Resolver<'_, 'a>) -> Option<&'a Symbol> {
+        let start = node.start_position();
+        resolver.declared_at(start.row as u32, start.column as u32)
+    }
+
+    /// `Extends` and `Implements` edges of one part of a type declaration
+    ///
+    /// A base list does not tell the base class from interfaces: a resolved
+    /// base is one or the other, an unresolved one is taken for the base class
+    /// when it comes first and its name does not follow the `IName` convention.
+    fn extract_base_relationships(&self, node: tree_sitter::Node, file_path: &str, resolver: &csharp::Resolver) -> Vec<Relationship> {
+        let Some(ty) = self.declared(node, resolver).filter(|symbol| csharp::is_type(symbol)) else {
+            return Vec::new();
+        };
+        let location = self.node_location(node, file_path);
+        let flag = |key: &str| ty.metadata[key].as_bool().unwrap_or(false);
+        let interface = csharp::is_interface(ty);
+        let value_type = flag("struct") || flag("enum");
+
+        csharp::base_candidates(ty)
+            // An enum's underlying integral type is not a base
+            .filter(|(_, candidates)| !candidates.is_empty())
+            .enumerate()
+            .map(|(index, (name, candidates))| {
+                let base = resolver.first_type(&candidates);
+                let extends = match base {
+                    // Interfaces extend their base interfaces
+                    _ if interface => true,
+                    _ if value_type => false,
+                    Some(base) => !csharp::is_interface(base),
+                    None => index == 0 && !csharp::looks_like_interface(&name),
+                };
+                let kind = if extends { RelationshipKind::Extends } else { RelationshipKind::Implements };
+                match base {
+                    Some(base) => Relationship {
+                        from_id: ty.id.clone(),
+                        to_id: base.id.clone(),
+                        kind,
+                        location: location.clone(),
+                        metadata: serde_json::json!({}),
+                    },
+                    None => Relationship::unresolved(ty.id.clone(), &name, kind, location.clone()),
+                }
+            })
+            .collect()
+    }
+
+    /// Declared types of the parameters and locals of a member, in
+    /// declaration order so that `var` can use earlier ones
+    fn locals<'a>(&self, node: tree_sitter::Node, content: &str, resolver: &csharp::Resolver<'_, 'a>, context: &CSharpContext) -> HashMap<String, (String, Option<&'a Symbol>)> {
+        let namespace = context.namespace();
+        let mut locals = HashMap::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            // Local types have their own members
+            if CSHARP_TYPE_DECLARATIONS.contains(&node.kind()) {
+                continue;
+            }
+
+            // Declared type, name and initial value of each variable
+            let mut declared: Vec<(Option<tree_sitter::Node>, Option<tree_sitter::Node>, Option<tree_sitter::Node>)> = Vec::new();
+            match node.kind() {
+                "parameter" | "catch_declaration" | "declaration_pattern" | "declaration_expression" => {
+                    declared.push((node.child_by_field_name("type"), node.child_by_field_name("name"), None));
+                }
+                "foreach_statement" => declared.push((node.child_by_field_name("type"), node.child_by_field_name("left"), None)),
+                "variable_declaration" => {
+                    let type_node = node.child_by_field_name("type");
+                    let mut cursor = node.walk();
+                    for declarator in node.named_children(&mut cursor).filter(|child| child.kind() == "variable_declarator") {
+                        let name = declarator.child_by_field_name("name");
+                        let mut declarator_cursor = declarator.walk();
+                        let value = declarator.named_children(&mut declarator_cursor)
+                            .filter(|child| Some(*child) != name && child.kind() != "bracketed_argument_list")
+                            .last();
+                        declared.push((type_node, name, value));
+                    }
+                }
+                // `params T[] name` is written directly in the list
+                "parameter_list" => {
+                    if let Some(name) = node.child_by_field_name("name") {
+                        declared.push((node.child_by_field_name("type"), Some(name), None));
+                    }
+                }
+                // Untyped lambda parameters shadow fields of the same name
+                "implicit_parameter" => {
+                    if let Some(name) = self.get_node_text(Some(node), content) {
+                        locals.entry(name).or_insert((String::new(), None));
+                    }
+                }
+                _ => {}
+            }
+
+            for (type_node, name, value) in declared {
+                let Some(name) = self.get_node_text(name, content) else { continue };
+                let local = match type_node.filter(|type_node| type_node.kind() != "implicit_type") {
+                    Some(type_node) => {
+                        let type_text = self.get_node_text(Some(type_node), content).unwrap_or_default();
+                        (csharp::erase(&type_text), resolver.resolve_type(&type_text, &namespace, &context.classes))
+                    }
+                    None => {
+                        let ty = value.and_then(|value| self.expression_type(value, content, resolver, context, &locals));
+                        (ty.map(|ty| ty.name.clone()).unwrap_or_default(), ty)
+                    }
+                };
+                locals.insert(name, local);
+            }
+
+            let mut cursor = node.walk();
+            let children: Vec<_> = node.named_children(&mut cursor).collect();
+            stack.extend(children.into_iter().rev());
+        }
+        locals
+    }
+
+    /// Name of an identifier or of a generic name without its type arguments
+    fn simple_name(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
+        let node = node?;
+        match node.kind() {
+            "identifier" => self.get_node_text(Some(node), content),
+            "generic_name" => self.get_node_text(node.named_child(0), content),
+            _ => None,
+        }
+    }
+
+    /// Static type of an expression, as far as declarations tell without type checking
+    fn expression_type<'a>(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        resolver: &csharp::Resolver<'_, 'a>,
+        context: &CSharpContext,
+        locals: &HashMap<String, (String, Option<&'a Symbol>)>,
+    ) -> Option<&'a Symbol> {
+        let namespace = context.namespace();
+        match node.kind() {
+            "identifier" => {
+                let name = self.get_node_text(Some(node), content)?;
+                if let Some((_, ty)) = locals.get(&name) {
+                    return *ty;
+                }
+                // A field or property of an enclosing type, or a type for a static access
+                context.classes.iter()
+                    .rev()
+                    .filter_map(|class| resolver.find_type(class))
+                    .find_map(|class| resolver.member(class, &name))
+                    .and_then(|member| resolver.type_of(member))
+                    .or_else(|| resolver.resolve_type(&name, &namespace, &context.classes))
+            }
+            "this" => context.classes.last().and_then(|class| resolver.find_type(class)),
+            "base" => resolver.base_class(resolver.find_type(context.classes.last()?)?),
+            "member_access_expression" => {
+                let name = self.simple_name(node.child_by_field_name("name"), content)?;
+                match self.expression_type(node.child_by_field_name("expression")?, content, resolver, context, locals) {
+                    Some(ty) => resolver.member(ty, &name).and_then(|member| resolver.type_of(member)),
+                    // A qualified type name such as `Outer.Inner` or `Alias.Type`
+                    None => resolver.resolve_type(&self.get_node_text(Some(node), content)?, &namespace, &context.classes),
+                }
+            }
+            "invocation_expression" => {
+                let method = self.invocation_target(node, content, resolver, context, locals)?;
+                resolver.type_of(method)
+            }
+            "object_creation_expression" | "cast_expression" => {
+                resolver.resolve_type(&self.get_node_text(node.child_by_field_name("type"), content)?, &namespace, &context.classes)
+            }
+            "parenthesized_expression" => self.expression_type(node.named_child(0)?, content, resolver, context, locals),
+            _ => None,
+        }
+    }
+
+    /// Static types of call arguments that can be told from literals and declarations
+    fn argument_types<'a>(
+        &self,
+        node: Option<tree_sitter::Node>,
+        content: &str,
+        resolver: &csharp::Resolver<'_, 'a>,
+        context: &CSharpContext,
+        locals: &HashMap<String, (String, Option<&'a Symbol>)>,
+    ) -> Vec<csharp::ArgumentType> {
+        let Some(node) = node else {
+            return Vec::new();
+        };
+
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .filter(|argument| argument.kind() == "argument")
+            .map(|argument| {
+                // The value follows the name of a named argument
+                let Some(value) = argument.named_child(argument.named_child_count().saturating_sub(1)) else {
+                    return csharp::ArgumentType::Unknown;
+                };
+                let text = content[value.byte_range()].to_ascii_lowercase();
+                let literal = match value.kind() {
+                    "string_literal" | "verbatim_string_literal" | "raw_string_literal" | "interpolated_string_expression" => Some("string"),
+                    "integer_literal" if text.ends_with("ul") || text.ends_with("lu") => Some("ulong"),
+                    "integer_literal" if text.ends_with('l') => Some("long"),
+                    "integer_literal" if text.ends_with('u') => Some("uint"),
+                    "integer_literal" => Some("int"),
+                    "real_literal" if text.ends_with('f') => Some("float"),
+                    "real_literal" if text.ends_with('m') => Some("decimal"),
+                    "real_literal" => Some("double"),
+                    "boolean_literal" => Some("bool"),
+                    "character_literal" => Some("char"),
+                    _ => None,
+                };
+                if let Some(literal) = literal {
+                    return csharp::ArgumentType::Known(literal.to_string());
+                }
+
+                if value.kind() == "identifier" {
+                    let name = &content[value.byte_range()];
+                    if let Some((declared, _)) = locals.get(name).filter(|(declared, _)| !declared.is_empty()) {
+                        return csharp::ArgumentType::Known(declared.clone());
+                    }
+                }
+                match self.expression_type(value, content, resolver, context, locals) {
+                    Some(ty) => csharp::ArgumentType::Known(ty.name.clone()),
+                    None => csharp::ArgumentType::Unknown,
+                }
+            })
+            .collect()
+    }
+
+    /// Name of the method an invocation calls, and the receiver it is called on
+    fn invoked<'t>(&self, node: tree_sitter::Node<'t>, content: &str) -> Option<(String, Option<tree_sitter::Node<'t>>)> {
+        let function = node.child_by_field_name("function")?;
+        match function.kind() {
+            "identifier" | "generic_name" => Some((self.simple_name(Some(function), content)?, None)),
+            "member_access_expression" => Some((
+                self.simple_name(function.child_by_field_name("name"), content)?,
+                Some(function.child_by_field_name("expression")?),
+            )),
+            // `receiver?.Method()`
+            "conditional_access_expression" => {
+                let mut cursor = function.walk();
+                let binding = function.named_children(&mut cursor).find(|child| child.kind() == "member_binding_expression")?;
+                Some((
+                    self.simple_name(binding.child_by_field_name("name"), content)?,
+                    Some(function.child_by_field_name("condition")?),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Method an `invocation_expression` calls, from the static type of its receiver
+    fn invocation_target<'a>(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        resolver: &csharp::Resolver<'_, 'a>,
+        context: &CSharpContext,
+        locals: &HashMap<String, (String, Option<&'a Symbol>)>,
+    ) -> Option<&'a Symbol> {
+        let (name, receiver) = self.invoked(node, content)?;
+        let arguments = self.argument_types(node.child_by_field_name("arguments"), content, resolver, context, locals);
+
+        match receiver {
+            Some(receiver) => {
+                let ty = self.expression_type(receiver, content, resolver, context, locals)?;
+                resolver.method(ty, &name, &arguments)
+            }
+            None => resolver.unqualified_method(&context.namespace(), &context.classes, &name, &arguments),
+        }
+    }
+
+    fn extract_invocation(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &csharp::Resolver,
+        context: &CSharpContext,
+        member: &CSharpMember,
+    ) -> Option<Relationship> {
+        let (method_name, _) = self.invoked(node, content)?;
+        // `nameof(x)` is evaluated by the compiler
+        if method_name == "nameof" {
+            return None;
+        }
+
+        let called_symbol = self.invocation_target(node, content, resolver, context, &member.locals);
+        let location = self.node_location(node, file_path);
+
+        match called_symbol {
+            Some(called_symbol) => Some(Relationship {
+                from_id: member.symbol.id.clone(),
+                to_id: called_symbol.id.clone(),
+                kind: RelationshipKind::Calls,
+                location,
+                metadata: serde_json::json!({}),
+            }),
+            // Record the call so it can be re-resolved once a matching symbol appears
+            None => Some(Relationship::unresolved(member.symbol.id.clone(), &method_name, RelationshipKind::Calls, location)),
+        }
+    }
+
+    /// `new T(...)`, `: this(...)` and `: base(...)` call a constructor, or
+    /// reference the class when it declares none
+    fn extract_constructor_call(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &csharp::Resolver,
+        context: &CSharpContext,
+        member: &CSharpMember,
+    ) -> Option<Relationship> {
+        let (class, arguments) = match node.kind() {
+            "constructor_initializer" => {
+                let class = resolver.find_type(context.classes.last()?)?;
+                let mut cursor = node.walk();
+                let children: Vec<tree_sitter::Node> = node.children(&mut cursor).collect();
+                let class = match children.iter().any(|child| child.kind() == "base") {
+                    true => resolver.base_class(class)?,
+                    false => class,
+                };
+                (class, children.into_iter().find(|child| child.kind() == "argument_list"))
+            }
+            _ => (
+                resolver.resolve_type(&self.get_node_text(node.child_by_field_name("type"), content)?, &context.namespace(), &context.classes)?,
+                node.child_by_field_name("arguments"),
+            ),
+        };
+        let arguments = self.argument_types(arguments, content, resolver, context, &member.locals);
+
+        let (to_id, kind) = match resolver.constructor(class, &arguments) {
+            Some(constructor) => (constructor.id.clone(), RelationshipKind::Calls),
+            None => (class.id.clone(), RelationshipKind::References),
+        };
+        Some(Relationship {
+            from_id: member.symbol.id.clone(),
+            to_id,
+            kind,
+            location: self.node_location(node, file_path),
+            metadata: serde_json::json!({}),
+        })
+    }
+
+    fn get_node_text(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
+        node.map(|n| content[n.byte_range()].to_string())
+    }
+
+    fn node_location(&self, node: tree_sitter::Node, file_path: &str) -> Location {
+        let start = node.start_position();
+        let end = node.end_position();
+
+        Location {
+            file: file_path.to_string(),
+            line: start.row as u32,
+            column: start.column as u32,
+            end_line: end.row as u32,
+            end_column: end.column as u32,
+        }
+    }
+}
+
+/// Enclosing declarations while walking a Ruby file
+#[derive(Debug, Clone, Default)]
+struct RubyContext {
+    /// Qualified names of the enclosing classes and modules, innermost last
+    nesting: Vec<String>,
+    /// Inside `class << self`, where methods are singleton methods
+    singleton: bool,
+    /// Set by a bare `private` or `protected` for the methods after it
+    visibility: Option<Visibility>,
+}
+
+impl RubyContext {
+    /// Qualified name of a class or module declared here, `Shop::Order`
+    fn qualify_constant(&self, name: &str) -> String {
+        match (name.strip_prefix("::"), self.nesting.last()) {
+            (Some(absolute), _) => absolute.to_string(),
+            (None, Some(scope)) => format!("{}::{}", scope, name),
+            (None, None) => name.to_string(),
+        }
+    }
+
+    /// Qualified name of a method declared here: `Shop::Order#save` for an
+    /// instance method, `Shop::Order.find` for a singleton method
+    fn qualify_method(&self, name: &str, singleton: bool) -> String {
+        match self.nesting.last() {
+            Some(owner) => format!("{}{}{}", owner, if singleton { "." } else { "#" }, name),
+            None => name.to_string(),
+        }
+    }
+}
+
+/// Receiver and method whose body is being walked for relationships
+#[derive(Default)]
+struct RubyScope<'a> {
+    /// Class or module the code runs in, as `self`
+    owner: Option<ruby::Owner<'a>>,
+    /// Instance variables assigned `Class.new` in the class, with that class
+    ivars: HashMap<String, &'a Symbol>,
+    method: Option<&'a Symbol>,
+    /// Local variables and parameters, with the class assigned to them if known
+    locals: HashMap<String, Option<&'a Symbol>>,
+}
+
+/// Calls in class bodies that declare rather than run code
+const RUBY_DECLARATIONS: &[&str] = &[
+    "attr_accessor", "attr_reader", "attr_writer", "extend", "include", "module_function", "prepend",
+    "private", "private_constant", "protected", "public", "require", "require_relative",
+];
+
+impl RubyParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn parse_tree(&self, content: &str) -> anyhow::Result<Tree> {
+        let mut parser = TreeParser::new();
+        parser.set_language(&tree_sitter_ruby::LANGUAGE.into())?;
+
+        let tree = parser.parse(content, None)
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse Ruby code"))?;
+
+        Ok(tree)
+    }
+
+    fn extract_symbols(&self, tree: &Tree, content: &str, file_path: &str) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        self.walk_tree(tree.root_node(), content, file_path, &mut symbols, &RubyContext::default());
+
+        // A class reopened in the same file keeps its first definition
+        let mut seen = HashSet::new();
+        symbols.retain(|symbol| seen.insert(symbol.id.clone()));
+        symbols
+    }
+
+    fn walk_tree(&self, node: tree_sitter::Node, content: &str, file_path: &str, symbols: &mut Vec<Symbol>, context: &RubyContext) {
+        match node.kind() {
+            "class" | "module" => symbols.extend(self.extract_namespace(node, content, file_path, context)),
+            "method" | "singleton_method" => {
+                symbols.extend(self.extract_method(node, content, file_path, context));
+                // Method bodies declare no members
+                return;
+            }
+            "call" => symbols.extend(self.extract_attributes(node, content, file_path, context)),
+            _ => {}
+        }
+
+        let mut context = self.enter(node, content, context);
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            // A bare `private`, `protected` or `public` applies to the methods after it
+            if child.kind() == "identifier" && node.kind() == "body_statement" {
+                match &content[child.byte_range()] {
+                    "private" | "protected" => context.visibility = Some(Visibility::Private),
+                    "public" => context.visibility = Some(Visibility::Public),
+                    _ => {}
+                }
+            }
+            self.walk_tree(child, content, file_path, symbols, &context);
+        }
+    }
+
+    /// Context of the children of a node: classes and modules push their
+    /// qualified name, `class << self` and `def self.name` switch to singleton methods
+    fn enter(&self, node: tree_sitter::Node, content: &str, context: &RubyContext) -> RubyContext {
+        let mut new_context = context.clone();
+        match node.kind() {
+            "class" | "module" => {
+                if let Some(name) = self.get_node_text(node.child_by_field_name("name"), content) {
+                    new_context.nesting.push(context.qualify_constant(&name));
+                    new_context.singleton = false;
+                    new_context.visibility = None;
+                }
+            }
+            "singleton_class" if node.child_by_field_name("value").is_some_and(|value| value.kind() == "self") => {
+                new_context.singleton = true;
+                new_context.visibility = None;
+            }
+            // The body of `def self.name` runs with the class as `self`
+            "singleton_method" => new_context.singleton = true,
+            _ => {}
+        }
+        new_context
+    }
+
+    /// Name of the method a call invokes
+    fn call_name(&self, node: tree_sitter::Node, content: &str) -> Option<String> {
+        self.get_node_text(node.child_by_field_name("method"), content)
+    }
+
+    /// Arguments of the declaring calls, e.g. `include`, directly in a class or module body
+    fn declaring_calls<'t>(&self, node: tree_sitter::Node<'t>, content: &str, method: &str) -> Vec<tree_sitter::Node<'t>> {
+        let Some(body) = node.child_by_field_name("body") else {
+            return Vec::new();
+        };
+
+        let mut cursor = body.walk();
+        let calls: Vec<tree_sitter::Node> = body.named_children(&mut cursor)
+            .filter(|child| child.kind() == "call" && child.child_by_field_name("receiver").is_none())
+            .filter(|call| self.call_name(*call, content).as_deref() == Some(method))
+            .collect();
+        calls.into_iter()
+            .filter_map(|call| call.child_by_field_name("arguments"))
+            .flat_map(|arguments| {
+                let mut arguments_cursor = arguments.walk();
+                let values: Vec<tree_sitter::Node> = arguments.named_children(&mut arguments_cursor).collect();
+                values
+            })
+            .collect()
+    }
+
+    fn written_constant(&self, node: tree_sitter::Node, content: &str, nesting: &[String]) -> Option<serde_json::Value> {
+        if !matches!(node.kind(), "constant" | "scope_resolution") {
+            return None;
+        }
+        let name: String = self.get_node_text(Some(node), content)?.split_whitespace().collect();
+        Some(serde_json::json!({
+            "name": name,
+            "candidates": ruby::constant_candidates(&name, nesting)
+        }))
+    }
+
+    /// A class or module definition, with its superclass and mixins; a class
+    /// reopened in several files has one symbol per definition
+    fn extract_namespace(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &RubyContext) -> Option<Symbol> {
+        let name_node = node.child_by_field_name("name")?;
+        let written = self.get_node_text(Some(name_node), content)?;
+        let qualified_name = context.qualify_constant(&written);
+        let name = qualified_name.rsplit("::").next().unwrap_or(&qualified_name).to_string();
+
+        // Mixins resolve inside the body, the superclass outside it
+        let inner = self.enter(node, content, context);
+        let superclass = node.child_by_field_name("superclass")
+            .and_then(|superclass| superclass.named_child(0))
+            .and_then(|superclass| self.written_constant(superclass, content, &context.nesting));
+
+        let (kind, mut metadata) = match node.kind() {
+            "module" => (SymbolKind::Module, serde_json::json!({ "module": true })),
+            _ => (SymbolKind::Class, serde_json::json!({ "superclass": superclass })),
+        };
+        for mixin in ruby::MIXINS {
+            let modules: Vec<serde_json::Value> = self.declaring_calls(node, content, mixin)
+                .into_iter()
+                .filter_map(|module| self.written_constant(module, content, &inner.nesting))
+                .collect();
+            metadata[*mixin] = modules.into();
+        }
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind,
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: None,
+            type_info: None,
+            visibility: Visibility::Public,
+            language: "ruby".to_string(),
+            metadata,
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// `def name` and `def self.name`, the latter and any method inside
+    /// `class << self` being a singleton method
+    fn extract_method(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &RubyContext) -> Option<Symbol> {
+        let name = self.get_node_text(node.child_by_field_name("name"), content)?;
+        let singleton = node.kind() == "singleton_method" || context.singleton;
+        let qualified_name = context.qualify_method(&name, singleton);
+
+        let parameters_node = node.child_by_field_name("parameters");
+        let mut parameters = Vec::new();
+        if let Some(list) = parameters_node {
+            let mut cursor = list.walk();
+            for parameter in list.named_children(&mut cursor) {
+                let name = match parameter.kind() {
+                    "identifier" => Some(parameter),
+                    _ => parameter.child_by_field_name("name"),
+                };
+                parameters.extend(self.get_node_text(name, content));
+            }
+        }
+        let prefix = if node.kind() == "singleton_method" { "self." } else { "" };
+        let parameters_text = self.get_node_text(parameters_node, content).unwrap_or_default();
+        let signature = format!("def {}{}{}", prefix, name, parameters_text);
+
+        // `private def name` wraps the definition in a call
+        let wrapper = node.parent()
+            .filter(|arguments| arguments.kind() == "argument_list")
+            .and_then(|arguments| arguments.parent())
+            .and_then(|call| self.call_name(call, content));
+        let visibility = match wrapper.as_deref() {
+            Some("private" | "protected") => Visibility::Private,
+            Some("public") => Visibility::Public,
+            _ => context.visibility.clone().unwrap_or(Visibility::Public),
+        };
+
+        Some(Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind: if context.nesting.is_empty() { SymbolKind::Function } else { SymbolKind::Method },
+            name,
+            qualified_name,
+            location: self.node_location(node, file_path),
+            signature: Some(signature.split_whitespace().collect::<Vec<_>>().join(" ")),
+            type_info: None,
+            visibility,
+            language: "ruby".to_string(),
+            metadata: serde_json::json!({
+                "singleton": singleton,
+                "parameters": parameters
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Fields generated by `attr_reader`, `attr_writer` and `attr_accessor`
+    /// in a class or module body
+    fn extract_attributes(&self, node: tree_sitter::Node, content: &str, file_path: &str, context: &RubyContext) -> Vec<Symbol> {
+        let Some(method) = self.call_name(node, content) else {
+            return Vec::new();
+        };
+        let (reader, writer) = match method.as_str() {
+            "attr_reader" => (true, false),
+            "attr_writer" => (false, true),
+            "attr_accessor" => (true, true),
+            _ => return Vec::new(),
+        };
+        if context.nesting.is_empty() || node.child_by_field_name("receiver").is_some() {
+            return Vec::new();
+        }
+        let Some(arguments) = node.child_by_field_name("arguments") else {
+            return Vec::new();
+        };
+
+        let mut symbols = Vec::new();
+        let mut cursor = arguments.walk();
+        for argument in arguments.named_children(&mut cursor) {
+            let name = match argument.kind() {
+                "simple_symbol" => content[argument.byte_range()].trim_start_matches(':').to_string(),
+                "string" => content[argument.byte_range()].trim_matches(['"', '\'']).to_string(),
+                _ => continue,
+            };
+            let qualified_name = context.qualify_method(&name, context.singleton);
+
+            symbols.push(Symbol {
+                id: format!("{}:{}", file_path, qualified_name),
+                kind: SymbolKind::Field,
+                name,
+                qualified_name,
+                location: self.node_location(argument, file_path),
+                signature: None,
+                type_info: None,
+                visibility: context.visibility.clone().unwrap_or(Visibility::Public),
+                language: "ruby".to_string(),
+                metadata: serde_json::json!({
+                    "attribute": method.trim_start_matches("attr_"),
+                    "reader": reader,
+                    "writer": writer,
+                    "singleton": context.singleton
+                }),
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            });
+        }
+        symbols
+    }
+
+    fn extract_relationships(&self, tree: &Tree, content: &str, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(tree, content, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, tree: &Tree, content: &str, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let mut relationships = Vec::new();
+        let resolver = ruby::Resolver::new(symbol_map, file_path);
+
+        self.extract_relationships_from_tree(tree.root_node(), content, file_path, &resolver, &mut relationships, &RubyContext::default(), &RubyScope::default());
+
+        relationships
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn extract_relationships_from_tree<'a>(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &ruby::Resolver<'_, 'a>,
+        relationships: &mut Vec<Relationship>,
+        context: &RubyContext,
+        scope: &RubyScope<'a>,
+    ) {
+        let kind = node.kind();
+        let new_context = self.enter(node, content, context);
+        let entered = match kind {
+            "class" | "module" => {
+                relationships.extend(self.extract_ancestor_relationships(node, file_path, resolver));
+                new_context.nesting.last().and_then(|name| resolver.find(name)).map(|symbol| RubyScope {
+                    owner: Some(ruby::Owner { symbol, singleton: false }),
+                    ivars: self.instance_variables(node, content, resolver, &new_context),
+                    ..RubyScope::default()
+                })
+            }
+            "singleton_class" if new_context.singleton => scope.owner.map(|owner| RubyScope {
+                owner: Some(ruby::Owner { symbol: owner.symbol, singleton: true }),
+                ..RubyScope::default()
+            }),
+            "method" | "singleton_method" => {
+                let start = node.start_position();
+                resolver.declared_at(start.row as u32, start.column as u32).map(|method| RubyScope {
+                    owner: scope.owner.map(|owner| ruby::Owner { symbol: owner.symbol, singleton: ruby::is_singleton(method) }),
+                    ivars: scope.ivars.clone(),
+                    method: Some(method),
+                    locals: self.locals(node, content, resolver, &new_context),
+                })
+            }
+            _ => None,
+        };
+        let scope = entered.as_ref().unwrap_or(scope);
+
+        if let Some(method) = scope.method {
+            let rel = match kind {
+                "call" => self.extract_call(node, content, file_path, resolver, &new_context, scope, method),
+                // A bare name that is not a local variable calls a method on `self`
+                "identifier" if self.is_bare_call(node, content, scope) => {
+                    let name = &content[node.byte_range()];
+                    Some(self.call_relationship(node, file_path, method, name, self.self_method(resolver, scope, name)))
+                }
+                "super" => {
+                    let target = scope.owner.and_then(|owner| {
+                        let defined_in = resolver.find(context.nesting.last()?)?;
+                        resolver.super_method(owner, defined_in, &method.name)
+                    });
+                    Some(self.call_relationship(node, file_path, method, &method.name, target))
+                }
+                _ => None,
+            };
+            relationships.extend(rel);
+        }
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.extract_relationships_from_tree(child, content, file_path, resolver, relationships, &new_context, scope);
+        }
+    }
+
+    /// `Extends` edge to the superclass and `Implements` edges to mixed-in
+    /// modules, from one definition of a class or module
+    fn extract_ancestor_relationships(&self, node: tree_sitter::Node, file_path: &str, resolver: &ruby::Resolver) -> Vec<Relationship> {
+        let start = node.start_position();
+        let Some(ty) = resolver.declared_at(start.row as u32, start.column as u32).filter(|symbol| ruby::is_namespace(symbol)) else {
+            return Vec::new();
+        };
+        let location = self.node_location(node, file_path);
+
+        let superclass = ruby::written_constants(&ty.metadata["superclass"])
+            .into_iter()
+            .map(|written| (written, RelationshipKind::Extends, serde_json::json!({})));
+        let mixins = ruby::MIXINS.iter().flat_map(|mixin| {
+            ruby::written_constants(&ty.metadata[*mixin])
+                .into_iter()
+                .map(move |written| (written, RelationshipKind::Implements, serde_json::json!({ "mixin": mixin })))
+        });
+
+        superclass.chain(mixins)
+            .map(|((name, candidates), kind, metadata)| match resolver.first(&candidates) {
+                Some(target) => Relationship {
+                    from_id: ty.id.clone(),
+                    to_id: target.id.clone(),
+                    kind,
+                    location: location.clone(),
+                    metadata,
+                },
+                None => Relationship::unresolved(ty.id.clone(), &name, kind, location.clone()),
+            })
+            .collect()
+    }
+
+    /// Class of a `Const.new(...)` expression, or of `new(...)` and
+    /// `self.new(...)` in a singleton method
+    fn instantiated<'a>(&self, node: tree_sitter::Node, content: &str, resolver: &ruby::Resolver<'_, 'a>, context: &RubyContext) -> Option<&'a Symbol> {
+        if node.kind() != "call" || self.call_name(node, content).as_deref() != Some("new") {
+            return None;
+        }
+        match node.child_by_field_name("receiver") {
+            Some(receiver) if matches!(receiver.kind(), "constant" | "scope_resolution") => {
+                resolver.resolve(&self.get_node_text(Some(receiver), content)?, &context.nesting)
+            }
+            Some(receiver) if receiver.kind() != "self" => None,
+            _ if context.singleton => resolver.find(context.nesting.last()?),
+            _ => None,
+        }
+    }
+
+    /// Instance variables assigned `Const.new` anywhere in a class body
+    fn instance_variables<'a>(&self, node: tree_sitter::Node, content: &str, resolver: &ruby::Resolver<'_, 'a>, context: &RubyContext) -> HashMap<String, &'a Symbol> {
+        let mut ivars = HashMap::new();
+        let mut stack = node.child_by_field_name("body").into_iter().collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            // Nested classes have their own instance variables
+            if matches!(node.kind(), "class" | "module" | "singleton_class") {
+                continue;
+            }
+            if node.kind() == "assignment" {
+                let left = node.child_by_field_name("left").filter(|left| left.kind() == "instance_variable");
+                let class = node.child_by_field_name("right").and_then(|right| self.instantiated(right, content, resolver, context));
+                if let (Some(left), Some(class)) = (left, class) {
+                    ivars.entry(content[left.byte_range()].to_string()).or_insert(class);
+                }
+            }
+            let mut cursor = node.walk();
+            stack.extend(node.named_children(&mut cursor));
+        }
+        ivars
+    }
+
+    /// Parameters and assigned local variables of a method, with the class
+    /// of those assigned `Const.new`
+    fn locals<'a>(&self, node: tree_sitter::Node, content: &str, resolver: &ruby::Resolver<'_, 'a>, context: &RubyContext) -> HashMap<String, Option<&'a Symbol>> {
+        let mut locals = HashMap::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            match node.kind() {
+                "method_parameters" | "block_parameters" | "lambda_parameters" | "exception_variable" => {
+                    let mut inner = vec![node];
+                    while let Some(parameter) = inner.pop() {
+                        if parameter.kind() == "identifier" {
+                            locals.entry(content[parameter.byte_range()].to_string()).or_insert(None);
+                        }
+                        let mut cursor = parameter.walk();
+                        inner.extend(parameter.named_children(&mut cursor));
+                    }
+                }
+                "assignment" | "operator_assignment" => {
+                    let class = node.child_by_field_name("right").and_then(|right| self.instantiated(right, content, resolver, context));
+                    let mut targets: Vec<tree_sitter::Node> = node.child_by_field_name("left").into_iter().collect();
+                    while let Some(target) = targets.pop() {
+                        match target.kind() {
+                            "identifier" => {
+                                let entry = locals.entry(content[target.byte_range()].to_string()).or_insert(None);
+                                if class.is_some() {
+                                    *entry = class;
+                                }
+                            }
+                            "left_assignment_list" | "destructured_left_assignment" | "rest_assignment" => {
+                                let mut cursor = target.walk();
+                                targets.extend(target.named_children(&mut cursor));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                "for" => {
+                    if let Some(pattern) = node.child_by_field_name("pattern") {
+                        locals.entry(content[pattern.byte_range()].to_string()).or_insert(None);
+                    }
+                }
+                _ => {}
+            }
+            let mut cursor = node.walk();
+            stack.extend(node.named_children(&mut cursor));
+        }
+        locals
+    }
+
+    /// Whether an identifier is a method call on `self` without arguments
+    fn is_bare_call(&self, node: tree_sitter::Node, content: &str, scope: &RubyScope) -> bool {
+        let name = &content[node.byte_range()];
+        if scope.locals.contains_key(name) || RUBY_DECLARATIONS.contains(&name) {
+            return false;
+        }
+        let Some(parent) = node.parent() else {
+            return false;
+        };
+        let mut cursor = parent.walk();
+        let field = parent.children(&mut cursor)
+            .position(|child| child == node)
+            .and_then(|index| parent.field_name_for_child(index as u32));
+        !matches!(field, Some("method" | "name" | "pattern")) && !matches!(parent.kind(), "method_parameters" | "block_parameters" | "lambda_parameters")
+    }
+
+    /// Method called on `self`, or a top-level method outside any class
+    fn self_method<'a>(&self, resolver: &ruby::Resolver<'_, 'a>, scope: &RubyScope<'a>, name: &str) -> Option<&'a Symbol> {
+        scope.owner
+            .and_then(|owner| resolver.method(owner, name))
+            .or_else(|| resolver.top_level(name))
+    }
+
+    /// Class or module whose methods a receiver expression responds to
+    fn receiver_owner<'a>(&self, node: tree_sitter::Node, content: &str, resolver: &ruby::Resolver<'_, 'a>, context: &RubyContext, scope: &RubyScope<'a>) -> Option<ruby::Owner<'a>> {
+        match node.kind() {
+            "self" => scope.owner,
+            "constant" | "scope_resolution" => {
+                let class = resolver.resolve(&self.get_node_text(Some(node), content)?, &context.nesting)?;
+                Some(ruby::Owner { symbol: class, singleton: true })
+            }
+            "identifier" => {
+                let class = (*scope.locals.get(&content[node.byte_range()])?)?;
+                Some(ruby::Owner { symbol: class, singleton: false })
+            }
+            "instance_variable" => {
+                let class = scope.ivars.get(&content[node.byte_range()])?;
+                Some(ruby::Owner { symbol: class, singleton: false })
+            }
+            "call" => {
+                let class = self.instantiated(node, content, resolver, context)?;
+                Some(ruby::Owner { symbol: class, singleton: false })
+            }
+            "parenthesized_statements" => self.receiver_owner(node.named_child(0)?, content, resolver, context, scope),
+            _ => None,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn extract_call<'a>(
+        &self,
+        node: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        resolver: &ruby::Resolver<'_, 'a>,
+        context: &RubyContext,
+        scope: &RubyScope<'a>,
+        method: &Symbol,
+    ) -> Option<Relationship> {
+        let name = self.call_name(node, content)?;
+
+        // `Const.new` runs `initialize`, or references the class when it defines none
+        if let Some(class) = self.instantiated(node, content, resolver, context) {
+            let initialize = resolver.method(ruby::Owner { symbol: class, singleton: false }, "initialize");
+            return Some(match initialize {
+                Some(initialize) => self.call_relationship(node, file_path, method, &name, Some(initialize)),
+                None => Relationship {
+                    from_id: method.id.clone(),
+                    to_id: class.id.clone(),
+                    kind: RelationshipKind::References,
+                    location: self.node_location(node, file_path),
+                    metadata: serde_json::json!({}),
+                },
+            });
+        }
+
+        let target = match node.child_by_field_name("receiver") {
+            Some(receiver) => self.receiver_owner(receiver, content, resolver, context, scope).and_then(|owner| resolver.method(owner, &name)),
+            None if RUBY_DECLARATIONS.contains(&name.as_str()) => return None,
+            None => self.self_method(resolver, scope, &name),
+        };
+        Some(self.call_relationship(node, file_path, method, &name, target))
+    }
+
+    /// `Calls` edge to a resolved method, or an unresolved call by name
+    fn call_relationship(&self, node: tree_sitter::Node, file_path: &str, method: &Symbol, name: &str, target: Option<&Symbol>) -> Relationship {
+        let location = self.node_location(node, file_path);
+        match target {
+            Some(target) => Relationship {
+                from_id: method.id.clone(),
+                to_id: target.id.clone(),
+                kind: RelationshipKind::Calls,
+                location,
+                metadata: serde_json::json!({}),
+            },
+            // Record the call so it can be re-resolved once a matching symbol appears
+            None => Relationship::unresolved(method.id.clone(), name, RelationshipKind::Calls, location),
+        }
+    }
+
+    fn get_node_text(&self, node: Option<tree_sitter::Node>, content: &str) -> Option<String> {
+        node.map(|n| content[n.byte_range()].to_string())
+    }
+
+    fn node_location(&self, node: tree_sitter::Node, file_path: &str) -> Location {
+        let start = node.start_position();
+        let end = node.end_position();
+
+        Location {
+            file: file_path.to_string(),
+            line: start.row as u32,
+            column: start.column as u32,
+            end_line: end.row as u32,
+            end_column: end.column as u32,
+        }
+    }
+}
+
+impl ProtobufParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn extract_symbols(&self, file: &protobuf::File, file_path: &str) -> Vec<Symbol> {
+        let mut symbols = vec![self.file_symbol(file, file_path)];
+        let prefix = |name: &str| match file.package.as_str() {
+            "" => name.to_string(),
+            package => format!("{}.{}", package, name),
+        };
+
+        for message in &file.messages {
+            self.extract_message(message, &prefix(&message.name), file_path, &mut symbols);
+        }
+        for enumeration in &file.enums {
+            self.extract_enum(enumeration, &prefix(&enumeration.name), file_path, &mut symbols);
+        }
+        for service in &file.services {
+            let qualified_name = prefix(&service.name);
+            symbols.push(self.definition_symbol(
+                SymbolKind::Type,
+                &service.name,
+                qualified_name.clone(),
+                format!("service {}", service.name),
+                service.span,
+                file_path,
+                serde_json::json!({
+                    "definition": protobuf::Definition::Service.as_str(),
+                    "docstring": service.doc
+                }),
+            ));
+
+            for rpc in &service.rpcs {
+                let mut symbol = self.definition_symbol(
+                    SymbolKind::Method,
+                    &rpc.name,
+                    format!("{}.{}", qualified_name, rpc.name),
+                    rpc.signature(),
+                    rpc.span,
+                    file_path,
+                    serde_json::json!({
+                        "definition": protobuf::Definition::Rpc.as_str(),
+                        "request": rpc.request,
+                        "response": rpc.response,
+                        "client_streaming": rpc.client_streaming,
+                        "server_streaming": rpc.server_streaming,
+                        "docstring": rpc.doc
+                    }),
+                );
+                symbol.type_info = Some(rpc.response.clone());
+                symbols.push(symbol);
+            }
+        }
+
+        symbols
+    }
+
+    /// `Module` standing for the whole file, which `import` edges connect
+    fn file_symbol(&self, file: &protobuf::File, file_path: &str) -> Symbol {
+        let name = std::path::Path::new(file_path).file_name()
+            .map_or_else(|| file_path.to_string(), |name| name.to_string_lossy().to_string());
+        let imports: Vec<&str> = file.imports.iter().map(|import| import.path.as_str()).collect();
+        let options: serde_json::Map<String, serde_json::Value> = file.options.iter()
+            .map(|(name, value)| (name.clone(), serde_json::Value::String(value.clone())))
+            .collect();
+
+        Symbol {
+            id: format!("{}:{}", file_path, name),
+            kind: SymbolKind::Module,
+            name: name.clone(),
+            qualified_name: name,
+            location: Location { file: file_path.to_string(), line: 0, column: 0, end_line: 0, end_column: 0 },
+            signature: None,
+            type_info: None,
+            visibility: Visibility::Public,
+            language: "protobuf".to_string(),
+            metadata: serde_json::json!({
+                "file": true,
+                "syntax": file.syntax,
+                "package": file.package,
+                "imports": imports,
+                "options": options,
+                "java_outer_classname": protobuf::java_outer_class(file_path, file)
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn extract_message(&self, message: &protobuf::Message, qualified_name: &str, file_path: &str, symbols: &mut Vec<Symbol>) {
+        symbols.push(self.definition_symbol(
+            SymbolKind::Class,
+            &message.name,
+            qualified_name.to_string(),
+            format!("message {}", message.name),
+            message.span,
+            file_path,
+            serde_json::json!({
+                "definition": protobuf::Definition::Message.as_str(),
+                "docstring": message.doc
+            }),
+        ));
+
+        for field in &message.fields {
+            let mut symbol = self.definition_symbol(
+                SymbolKind::Field,
+                &field.name,
+                format!("{}.{}", qualified_name, field.name),
+                field.signature(),
+                field.span,
+                file_path,
+                serde_json::json!({
+                    "definition": protobuf::Definition::Field.as_str(),
+                    "number": field.number,
+                    "label": field.label,
+                    "key_type": field.key_type,
+                    "oneof": field.oneof,
+                    "docstring": field.doc
+                }),
+            );
+            symbol.type_info = Some(field.type_name.clone());
+            symbols.push(symbol);
+        }
+        for nested in &message.messages {
+            self.extract_message(nested, &format!("{}.{}", qualified_name, nested.name), file_path, symbols);
+        }
+        for enumeration in &message.enums {
+            self.extract_enum(enumeration, &format!("{}.{}", qualified_name, enumeration.name), file_path, symbols);
+        }
+    }
+
+    fn extract_enum(&self, enumeration: &protobuf::Enum, qualified_name: &str, file_path: &str, symbols: &mut Vec<Symbol>) {
+        symbols.push(self.definition_symbol(
+            SymbolKind::Type,
+            &enumeration.name,
+            qualified_name.to_string(),
+            format!("enum {}", enumeration.name),
+            enumeration.span,
+            file_path,
+            serde_json::json!({
+                "definition": protobuf::Definition::Enum.as_str(),
+                "docstring": enumeration.doc
+            }),
+        ));
+
+        // Values are scoped by their enum here, unlike in `protoc`, so that
+        // two enums of a package may share value names
+        for value in &enumeration.values {
+            symbols.push(self.definition_symbol(
+                SymbolKind::Field,
+                &value.name,
+                format!("{}.{}", qualified_name, value.name),
+                format!("{} = {}", value.name, value.number),
+                value.span,
+                file_path,
+                serde_json::json!({
+                    "definition": protobuf::Definition::Value.as_str(),
+                    "number": value.number,
+                    "docstring": value.doc
+                }),
+            ));
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn definition_symbol(
+        &self,
+        kind: SymbolKind,
+        name: &str,
+        qualified_name: String,
+        signature: String,
+        span: Span,
+        file_path: &str,
+        metadata: serde_json::Value,
+    ) -> Symbol {
+        Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind,
+            name: name.to_string(),
+            qualified_name,
+            location: span.location(file_path),
+            signature: Some(signature),
+            type_info: None,
+            visibility: Visibility::Public,
+            language: "protobuf".to_string(),
+            metadata,
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn extract_relationships(&self, file: &protobuf::File, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(file, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, file: &protobuf::File, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let mut relationships = Vec::new();
+
+        if let Some(from) = Self::file_symbol_of(file_path, symbol_map) {
+            for import in &file.imports {
+                let location = import.span.location(file_path);
+                relationships.push(match Self::proto_file(&import.path, file_path, symbol_map) {
+                    Some(target) => Relationship {
+                        from_id: from.id.clone(),
+                        to_id: target.id.clone(),
+                        kind: RelationshipKind::Imports,
+                        location,
+                        metadata: serde_json::json!({
+                            "import": import.path,
+                            "public": import.public
+                        }),
+                    },
+                    None => Self::unresolved_file(from.id.clone(), &import.path, RelationshipKind::Imports, location),
+                });
+            }
+        }
+
+        let scope = |name: &str| match file.package.as_str() {
+            "" => name.to_string(),
+            package => format!("{}.{}", package, name),
+        };
+        for message in &file.messages {
+            self.extract_message_relationships(message, &scope(&message.name), file_path, symbol_map, &mut relationships);
+        }
+        for service in &file.services {
+            let service_name = scope(&service.name);
+            for rpc in &service.rpcs {
+                let from_id = format!("{}:{}.{}", file_path, service_name, rpc.name);
+                for type_name in [&rpc.request, &rpc.response] {
+                    relationships.push(self.type_reference(&from_id, type_name, &service_name, rpc.span, file_path, symbol_map));
+                }
+            }
+        }
+
+        relationships
+    }
+
+    /// `References` from each field of a message, and of its nested
+    /// messages, to the message or enum it holds
+    fn extract_message_relationships(
+        &self,
+        message: &protobuf::Message,
+        qualified_name: &str,
+        file_path: &str,
+        symbol_map: &SymbolTable,
+        relationships: &mut Vec<Relationship>,
+    ) {
+        for field in message.fields.iter().filter(|field| !protobuf::is_scalar(&field.type_name)) {
+            let from_id = format!("{}:{}.{}", file_path, qualified_name, field.name);
+            relationships.push(self.type_reference(&from_id, &field.type_name, qualified_name, field.span, file_path, symbol_map));
+        }
+        for nested in &message.messages {
+            self.extract_message_relationships(nested, &format!("{}.{}", qualified_name, nested.name), file_path, symbol_map, relationships);
+        }
+    }
+
+    fn type_reference(
+        &self,
+        from_id: &str,
+        type_name: &str,
+        scope: &str,
+        span: Span,
+        file_path: &str,
+        symbol_map: &SymbolTable,
+    ) -> Relationship {
+        let location = span.location(file_path);
+        match self.resolve_type(type_name, scope, symbol_map) {
+            Some(target) => Relationship {
+                from_id: from_id.to_string(),
+                to_id: target.id.clone(),
+                kind: RelationshipKind::References,
+                location,
+                metadata: serde_json::json!({}),
+            },
+            // Record the reference so it can be re-resolved once the type is indexed
+            None => Relationship::unresolved(from_id.to_string(), type_name, RelationshipKind::References, location),
+        }
+    }
+
+    /// Message or enum a type name refers to from within `scope`, searching
+    /// the innermost scope first the way `protoc` does
+    fn resolve_type<'a>(&self, type_name: &str, scope: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        let find = |qualified_name: &str| {
+            symbol_map.get(qualified_name).filter(|symbol| {
+                let definition = symbol.metadata["definition"].as_str().and_then(protobuf::Definition::from_name);
+                symbol.language == "protobuf" && matches!(definition, Some(protobuf::Definition::Message | protobuf::Definition::Enum))
+            })
+        };
+        if let Some(absolute) = type_name.strip_prefix('.') {
+            return find(absolute);
+        }
+
+        let mut scope = Some(scope);
+        while let Some(current) = scope {
+            if let Some(symbol) = find(&format!("{}.{}", current, type_name)) {
+                return Some(symbol);
+            }
+            scope = parent_name(current);
+        }
+        find(type_name)
+    }
+
+    /// The `Module` symbol of an indexed `.proto` file
+    fn file_symbol_of<'a>(file_path: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        symbol_map.in_file(file_path)
+            .iter()
+            .find(|symbol| symbol.kind == SymbolKind::Module && symbol.language == "protobuf")
+            .copied()
+    }
+
+    /// File symbol of the indexed `.proto` file an import path names from
+    /// `from`, the one sharing the longest directory prefix when several
+    /// import roots hold a match
+    fn proto_file<'a>(path: &str, from: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        let suffix = format!("/{}", path.trim_start_matches("./"));
+        let shared = |file: &str| file.chars().zip(from.chars()).take_while(|(a, b)| a == b).count();
+        symbol_map.files()
+            .filter(|file| *file == path || file.ends_with(&suffix))
+            .max_by(|a, b| shared(a).cmp(&shared(b)).then_with(|| b.cmp(a)))
+            .and_then(|file| Self::file_symbol_of(file, symbol_map))
+    }
+
+    /// Placeholder for a `.proto` file that is not indexed, keyed by its file
+    /// name so that indexing it has the referencing file re-resolved
+    fn unresolved_file(from_id: String, path: &str, kind: RelationshipKind, location: Location) -> Relationship {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        Relationship {
+            from_id,
+            to_id: unresolved_id(name),
+            kind,
+            location,
+            metadata: serde_json::json!({
+                "unresolved": true,
+                "callee": path
+            }),
+        }
+    }
+
+    /// `Implements` edges from the symbols `protoc` generated into a Go,
+    /// Python or Java file to the `.proto` definitions they stand for
+    ///
+    /// Messages and enums link to the generated types, services to their
+    /// clients, servers and stubs, and rpcs to the methods of those, so that
+    /// the callers of an rpc include the callers of its stubs.
+    pub fn stub_relationships(content: &str, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        if !protobuf::is_generated(content) {
+            return Vec::new();
+        }
+        let Some(source) = protobuf::source(content, file_path) else {
+            return Vec::new();
+        };
+        let generated: Vec<&Symbol> = symbol_map.in_file(file_path)
+            .iter()
+            .filter(|symbol| matches!(symbol.kind, SymbolKind::Class | SymbolKind::Type | SymbolKind::Method | SymbolKind::Function))
+            .copied()
+            .collect();
+
+        let Some(proto) = Self::proto_file(&source, file_path, symbol_map) else {
+            // Link the stubs once the `.proto` file is indexed
+            return generated.first()
+                .map(|symbol| Self::unresolved_file(symbol.id.clone(), &source, RelationshipKind::DependsOn, symbol.location.clone()))
+                .into_iter()
+                .collect();
+        };
+
+        let package = proto.metadata["package"].as_str().unwrap_or_default();
+        let definitions: HashMap<&str, (&Symbol, protobuf::Definition)> = symbol_map.in_file(&proto.location.file)
+            .iter()
+            .filter_map(|symbol| {
+                let definition = protobuf::Definition::from_name(symbol.metadata["definition"].as_str()?)?;
+                let name = match package {
+                    "" => symbol.qualified_name.as_str(),
+                    package => symbol.qualified_name.strip_prefix(package)?.strip_prefix('.')?,
+                };
+                Some((name, (*symbol, definition)))
+            })
+            .collect();
+        let outer_class = proto.metadata["java_outer_classname"].as_str();
+
+        generated.into_iter()
+            .filter_map(|symbol| {
+                let segments = Self::stub_segments(symbol, symbol_map);
+                let name = protobuf::stub_definition(&symbol.language, &segments, outer_class, |name| {
+                    definitions.get(name).map(|(_, definition)| *definition)
+                })?;
+                let (definition, _) = definitions.get(name.as_str())?;
+                Some(Relationship {
+                    from_id: symbol.id.clone(),
+                    to_id: definition.id.clone(),
+                    kind: RelationshipKind::Implements,
+                    location: symbol.location.clone(),
+                    metadata: serde_json::json!({
+                        "generated": true
+                    }),
+                })
+            })
+            .collect()
+    }
+
+    /// Names of the types enclosing a generated symbol in its file, outermost
+    /// first, followed by its own name
+    fn stub_segments<'a>(symbol: &'a Symbol, symbol_map: &SymbolTable<'a>) -> Vec<&'a str> {
+        let mut segments = vec![symbol.name.as_str()];
+        let mut current = symbol;
+        while let Some(parent) = parent_name(&current.qualified_name)
+            .and_then(|name| symbol_map.get(name))
+            .filter(|parent| parent.location.file == symbol.location.file && matches!(parent.kind, SymbolKind::Class | SymbolKind::Type))
+        {
+            segments.insert(0, parent.name.as_str());
+            current = parent;
+        }
+        segments
+    }
+}
+
+impl SqlParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn extract_symbols(&self, file: &sql::File, file_path: &str) -> Vec<Symbol> {
+        let mut symbols = vec![self.file_symbol(file_path)];
+
+        for table in &file.tables {
+            let temporary = if table.temporary { "TEMPORARY " } else { "" };
+            symbols.push(self.definition_symbol(
+                SymbolKind::Class,
+                table.name.clone(),
+                format!("CREATE {}TABLE {}", temporary, table.name),
+                table.span,
+                file_path,
+                serde_json::json!({
+                    "definition": "table",
+                    "temporary": table.temporary,
+                    "docstring": table.doc
+                }),
+            ));
+            self.extract_columns(&table.name, &table.columns, file_path, &mut symbols);
+        }
+        for view in &file.views {
+            let materialized = if view.materialized { "MATERIALIZED " } else { "" };
+            symbols.push(self.definition_symbol(
+                SymbolKind::Type,
+                view.name.clone(),
+                format!("CREATE {}VIEW {}", materialized, view.name),
+                view.span,
+                file_path,
+                serde_json::json!({
+                    "definition": "view",
+                    "materialized": view.materialized,
+                    "docstring": view.doc
+                }),
+            ));
+            self.extract_columns(&view.name, &view.columns, file_path, &mut symbols);
+        }
+        for alteration in &file.alterations {
+            self.extract_columns(&alteration.table, &alteration.columns, file_path, &mut symbols);
+        }
+        for function in &file.functions {
+            let mut symbol = self.definition_symbol(
+                SymbolKind::Function,
+                function.name.clone(),
+                function.signature(),
+                function.span,
+                file_path,
+                serde_json::json!({
+                    "definition": if function.procedure { "procedure" } else { "function" },
+                    "language": function.language,
+                    "docstring": function.doc
+                }),
+            );
+            symbol.type_info = function.returns.clone();
+            symbols.push(symbol);
+        }
+        for index in &file.indexes {
+            symbols.push(self.definition_symbol(
+                SymbolKind::Variable,
+                format!("{}.{}", index.table, index.name),
+                index.signature(),
+                index.span,
+                file_path,
+                serde_json::json!({
+                    "definition": "index",
+                    "unique": index.unique,
+                    "columns": index.columns
+                }),
+            ));
+        }
+
+        // A later definition replaces an earlier one, as when a migration
+        // recreates a view
+        let mut merged: Vec<Symbol> = Vec::with_capacity(symbols.len());
+        let mut positions = HashMap::new();
+        for symbol in symbols {
+            match positions.get(&symbol.id) {
+                Some(&position) => merged[position] = symbol,
+                None => {
+                    positions.insert(symbol.id.clone(), merged.len());
+                    merged.push(symbol);
+                }
+            }
+        }
+        merged
+    }
+
+    /// `Module` standing for the whole file, which holds the references of
+    /// statements outside any definition
+    fn file_symbol(&self, file_path: &str) -> Symbol {
+        let name = std::path::Path::new(file_path).file_name()
+            .map_or_else(|| file_path.to_string(), |name| name.to_string_lossy().to_string());
+
+        Symbol {
+            id: format!("{}:{}", file_path, name),
+            kind: SymbolKind::Module,
+            name: name.clone(),
+            qualified_name: name,
+            location: Location { file: file_path.to_string(), line: 0, column: 0, end_line: 0, end_column: 0 },
+            signature: None,
+            type_info: None,
+            visibility: Visibility::Public,
+            language: "sql".to_string(),
+            metadata: serde_json::json!({
+                "file": true
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn extract_columns(&self, table: &str, columns: &[sql::Column], file_path: &str, symbols: &mut Vec<Symbol>) {
+        for column in columns {
+            let mut symbol = self.definition_symbol(
+                SymbolKind::Field,
+                format!("{}.{}", table, column.name),
+                column.signature.clone(),
+                column.span,
+                file_path,
+                serde_json::json!({
+                    "definition": "column",
+                    "not_null": column.not_null,
+                    "primary_key": column.primary_key,
+                    "references": column.references.as_ref().map(|(table, _)| &table.name),
+                    "docstring": column.doc
+                }),
+            );
+            symbol.type_info = column.type_name.clone();
+            symbols.push(symbol);
+        }
+    }
+
+    fn definition_symbol(
+        &self,
+        kind: SymbolKind,
+        qualified_name: String,
+        signature: String,
+        span: Span,
+        file_path: &str,
+        metadata: serde_json::Value,
+    ) -> Symbol {
+        Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind,
+            name: qualified_name.rsplit('.').next().unwrap_or(&qualified_name).to_string(),
+            qualified_name,
+            location: span.location(file_path),
+            signature: Some(signature),
+            type_info: None,
+            visibility: Visibility::Public,
+            language: "sql".to_string(),
+            metadata,
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn extract_relationships(&self, file: &sql::File, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(file, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, file: &sql::File, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let mut relationships = Vec::new();
+        let id = |qualified_name: &str| format!("{}:{}", file_path, qualified_name);
+        let mut reference = |from_id: String, table: &sql::TableRef, metadata: serde_json::Value| {
+            relationships.push(Self::table_reference(from_id, table, table.span.location(file_path), metadata, symbol_map));
+        };
+
+        let columns = file.tables.iter().map(|table| (&table.name, &table.columns))
+            .chain(file.alterations.iter().map(|alteration| (&alteration.table, &alteration.columns)));
+        for (table, columns) in columns {
+            for column in columns {
+                if let Some((target, target_column)) = &column.references {
+                    let metadata = serde_json::json!({ "foreign_key": true, "column": target_column });
+                    reference(id(&format!("{}.{}", table, column.name)), target, metadata);
+                }
+            }
+        }
+
+        let sources = file.tables.iter().map(|table| (&table.name, &table.sources))
+            .chain(file.views.iter().map(|view| (&view.name, &view.sources)))
+            .chain(file.functions.iter().map(|function| (&function.name, &function.sources)));
+        for (name, sources) in sources {
+            for source in sources {
+                reference(id(name), source, serde_json::json!({}));
+            }
+        }
+
+        for index in &file.indexes {
+            let table = sql::TableRef { name: index.table.clone(), access: sql::Access::Read, span: index.span };
+            reference(id(&format!("{}.{}", index.table, index.name)), &table, serde_json::json!({ "index": true }));
+        }
+
+        let file_id = symbol_map.in_file(file_path).iter()
+            .find(|symbol| symbol.kind == SymbolKind::Module)
+            .map(|symbol| symbol.id.clone());
+        if let Some(file_id) = file_id {
+            for table in &file.references {
+                reference(file_id.clone(), table, serde_json::json!({}));
+            }
+        }
+
+        relationships
+    }
+
+    /// `References` edge to the table or view a name resolves to, or a
+    /// placeholder keyed by its unqualified name until it is indexed
+    fn table_reference(
+        from_id: String,
+        table: &sql::TableRef,
+        location: Location,
+        metadata: serde_json::Value,
+        symbol_map: &SymbolTable,
+    ) -> Relationship {
+        let mut relationship = match Self::resolve_table(&table.name, symbol_map) {
+            Some(target) => Relationship {
+                from_id,
+                to_id: target.id.clone(),
+                kind: RelationshipKind::References,
+                location,
+                metadata: serde_json::json!({}),
+            },
+            None => Relationship::unresolved(from_id, &table.name, RelationshipKind::References, location),
+        };
+        relationship.metadata["access"] = table.access.as_str().into();
+        if let (Some(fields), serde_json::Value::Object(extra)) = (relationship.metadata.as_object_mut(), metadata) {
+            fields.extend(extra);
+        }
+        relationship
+    }
+
+    /// Table or view named `name`; an unqualified name also finds a table
+    /// created in a schema, and a qualified one a table created without
+    fn resolve_table<'a>(name: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        let is_table = |symbol: &&Symbol| {
+            symbol.language == "sql" && matches!(symbol.metadata["definition"].as_str(), Some("table" | "view"))
+        };
+        if let Some(symbol) = symbol_map.get(name).filter(is_table) {
+            return Some(symbol);
+        }
+        let (schema, table) = match name.rsplit_once('.') {
+            Some((schema, table)) => (Some(schema), table),
+            None => (None, name),
+        };
+        symbol_map.named(table).iter()
+            .copied()
+            .filter(is_table)
+            .find(|symbol| schema.is_none() || symbol.qualified_name == table)
+    }
+
+    /// `References` edges to the tables named by SQL in the string literals
+    /// of a Python, Go or Java file, from the innermost symbol around each
+    ///
+    /// `kinds` are the literal node kinds of the grammar; a literal made of
+    /// several, such as Python's implicit concatenation, is read as one.
+    pub fn literal_relationships(
+        root: tree_sitter::Node,
+        content: &str,
+        file_path: &str,
+        kinds: &[&str],
+        symbol_map: &SymbolTable,
+    ) -> Vec<Relationship> {
+        let mut literals = Vec::new();
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            if kinds.contains(&node.kind()) {
+                literals.push(node);
+                continue;
+            }
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+        }
+
+        let symbols = symbol_map.in_file(file_path);
+        let mut relationships = Vec::new();
+        for literal in literals {
+            let mut cursor = literal.walk();
+            let parts: Vec<tree_sitter::Node> = literal.named_children(&mut cursor)
+                .filter(|child| kinds.contains(&child.kind()))
+                .collect();
+            let text: String = match parts.as_slice() {
+                [] => Self::literal_text(&content[literal.byte_range()]).to_string(),
+                parts => parts.iter().map(|part| Self::literal_text(&content[part.byte_range()])).collect(),
+            };
+            let tables = sql::literal_tables(&text);
+            if tables.is_empty() {
+                continue;
+            }
+
+            // The innermost symbol around a literal is the last one to start before it
+            let (line, column) = (literal.start_position().row as u32, literal.start_position().column as u32);
+            let enclosing = symbols.iter()
+                .filter(|symbol| !matches!(symbol.kind, SymbolKind::Import | SymbolKind::Parameter))
+                .filter(|symbol| {
+                    let location = &symbol.location;
+                    (location.line, location.column) <= (line, column) && (line, column) < (location.end_line, location.end_column)
+                })
+                .max_by_key(|symbol| (symbol.location.line, symbol.location.column));
+            let Some(enclosing) = enclosing else {
+                continue;
+            };
+
+            let location = Location {
+                file: file_path.to_string(),
+                line,
+                column,
+                end_line: literal.end_position().row as u32,
+                end_column: literal.end_position().column as u32,
+            };
+            for table in &tables {
+                let metadata = serde_json::json!({ "sql": true });
+                relationships.push(Self::table_reference(enclosing.id.clone(), table, location.clone(), metadata, symbol_map));
+            }
+        }
+        relationships
+    }
+
+    /// Text between the quotes of a string literal, after any prefix such as `f` or `r`
+    fn literal_text(text: &str) -> &str {
+        let text = text.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+        for quote in ["\"\"\"", "'''", "\"", "'", "`"] {
+            if let Some(inner) = text.strip_prefix(quote) {
+                return inner.strip_suffix(quote).unwrap_or(inner);
+            }
+        }
+        text
+    }
+}
+
+impl ShellParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn extract_symbols(&self, script: &shell::Script, file_path: &str) -> Vec<Symbol> {
+        let mut symbols = vec![Self::file_symbol(file_path, "shell")];
+
+        // A function defined twice, as in both branches of an `if`, keeps its first definition
+        let mut seen = HashSet::new();
+        for function in script.functions.iter().filter(|function| seen.insert(function.name.as_str())) {
+            symbols.push(Symbol {
+                id: format!("{}:{}", file_path, function.name),
+                kind: SymbolKind::Function,
+                name: function.name.clone(),
+                qualified_name: function.name.clone(),
+                location: function.span.location(file_path),
+                signature: Some(format!("{}()", function.name)),
+                type_info: None,
+                visibility: Visibility::Public,
+                language: "shell".to_string(),
+                metadata: serde_json::json!({
+                    "docstring": function.doc
+                }),
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            });
+        }
+        symbols
+    }
+
+    /// `Module` standing for a whole script, Makefile or Dockerfile, which
+    /// holds the commands outside any function, target or stage
+    fn file_symbol(file_path: &str, language: &str) -> Symbol {
+        let name = Self::file_name(file_path);
+        Symbol {
+            id: format!("{}:{}", file_path, name),
+            kind: SymbolKind::Module,
+            name: name.clone(),
+            qualified_name: name,
+            location: Location { file: file_path.to_string(), line: 0, column: 0, end_line: 0, end_column: 0 },
+            signature: None,
+            type_info: None,
+            visibility: Visibility::Public,
+            language: language.to_string(),
+            metadata: serde_json::json!({
+                "file": true
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn file_name(file_path: &str) -> String {
+        std::path::Path::new(file_path).file_name()
+            .map_or_else(|| file_path.to_string(), |name| name.to_string_lossy().to_string())
+    }
+
+    fn extract_relationships(&self, script: &shell::Script, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(script, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, script: &shell::Script, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let file_id = format!("{}:{}", file_path, Self::file_name(file_path));
+        // The script and the files it sources, whose functions it can call
+        let mut scope = vec![file_path.to_string()];
+        let mut relationships = Vec::new();
+
+        for command in &script.commands {
+            let Some(invocation) = command.invocation() else {
+                continue;
+            };
+            let from_id = match &command.function {
+                Some(function) => format!("{}:{}", file_path, function),
+                None => file_id.clone(),
+            };
+            let location = command.span.location(file_path);
+
+            match &invocation {
+                shell::Invocation::Call(name) => {
+                    let function = scope.iter().find_map(|file| {
+                        symbol_map.in_file(file).iter()
+                            .find(|symbol| symbol.kind == SymbolKind::Function && symbol.language == "shell" && symbol.name == *name)
+                            .copied()
+                    });
+                    if let Some(function) = function {
+                        relationships.push(Relationship {
+                            from_id,
+                            to_id: function.id.clone(),
+                            kind: RelationshipKind::Calls,
+                            location,
+                            metadata: serde_json::json!({}),
+                        });
+                    }
+                    continue;
+                }
+                shell::Invocation::Source(path) => {
+                    let sourced = shell::script_path(path).and_then(|path| Self::resolve_path(path, file_path, symbol_map));
+                    scope.extend(sourced.map(|symbol| symbol.location.file.clone()));
+                }
+                _ => {}
+            }
+            relationships.extend(Self::invocation_relationships(from_id, &invocation, location, file_path, symbol_map));
+        }
+        relationships
+    }
+
+    /// Edges for a command that sources or runs a script or runs `make`, from
+    /// a script in `file_path` or a Makefile recipe or Dockerfile instruction
+    /// there
+    ///
+    /// `source` depends on the sourced file; running a script calls its file
+    /// symbol and `make` calls the targets it builds, or the default target.
+    fn invocation_relationships(
+        from_id: String,
+        invocation: &shell::Invocation,
+        location: Location,
+        file_path: &str,
+        symbol_map: &SymbolTable,
+    ) -> Vec<Relationship> {
+        match invocation {
+            shell::Invocation::Source(path) => {
+                Self::file_reference(from_id, path, RelationshipKind::DependsOn, location, file_path, symbol_map).into_iter().collect()
+            }
+            shell::Invocation::Script(path) => {
+                Self::file_reference(from_id, path, RelationshipKind::Calls, location, file_path, symbol_map).into_iter().collect()
+            }
+            shell::Invocation::Make { directory, targets } => {
+                let makefile = Self::makefile(directory.as_deref(), file_path, symbol_map);
+                let edge = |target: &Symbol| Relationship {
+                    from_id: from_id.clone(),
+                    to_id: target.id.clone(),
+                    kind: RelationshipKind::Calls,
+                    location: location.clone(),
+                    metadata: serde_json::json!({ "make": true }),
+                };
+                if targets.is_empty() {
+                    return makefile.and_then(|makefile| Self::make_target(makefile, None, symbol_map)).map(edge).into_iter().collect();
+                }
+                targets.iter()
+                    .map(|target| match makefile.and_then(|makefile| Self::make_target(makefile, Some(target), symbol_map)) {
+                        Some(symbol) => edge(symbol),
+                        // Keyed by the whole target name, which may hold dots
+                        None => Relationship {
+                            from_id: from_id.clone(),
+                            to_id: unresolved_id(target),
+                            kind: RelationshipKind::Calls,
+                            location: location.clone(),
+                            metadata: serde_json::json!({
+                                "unresolved": true,
+                                "callee": target,
+                                "make": true
+                            }),
+                        },
+                    })
+                    .collect()
+            }
+            shell::Invocation::Call(_) => Vec::new(),
+        }
+    }
+
+    /// Edge to the file a script path names, or a placeholder keyed by its
+    /// file name until it is indexed; `None` for paths the index cannot
+    /// follow, such as absolute ones
+    fn file_reference(
+        from_id: String,
+        word: &str,
+        kind: RelationshipKind,
+        location: Location,
+        file_path: &str,
+        symbol_map: &SymbolTable,
+    ) -> Option<Relationship> {
+        let path = shell::script_path(word)?;
+        Some(match Self::resolve_path(path, file_path, symbol_map) {
+            Some(target) => Relationship {
+                from_id,
+                to_id: target.id.clone(),
+                kind,
+                location,
+                metadata: serde_json::json!({ "path": word }),
+            },
+            None => ProtobufParser::unresolved_file(from_id, path, kind, location),
+        })
+    }
+
+    /// File symbol of the indexed file a relative path names from `from`:
+    /// the file under `from`'s directory, or else the file ending in the path
+    /// that shares the longest directory prefix with `from`, since scripts are
+    /// often run from the repository root or through a variable
+    fn resolve_path<'a>(path: &str, from: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        if let Some(symbol) = Self::resolve_exact(path, from, symbol_map) {
+            return Some(symbol);
+        }
+        let relative: Vec<&str> = path.split('/').filter(|segment| !matches!(*segment, "" | "." | "..")).collect();
+        let relative = relative.join("/");
+        let suffix = format!("/{}", relative);
+        let shared = |file: &str| file.chars().zip(from.chars()).take_while(|(a, b)| a == b).count();
+        symbol_map.files()
+            .filter(|file| *file == relative || file.ends_with(&suffix))
+            .max_by(|a, b| shared(a).cmp(&shared(b)).then_with(|| b.cmp(a)))
+            .and_then(|file| Self::file_symbol_of(file, symbol_map))
+    }
+
+    /// File symbol of the indexed file at a path relative to `from`'s directory
+    fn resolve_exact<'a>(path: &str, from: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        let directory = std::path::Path::new(from).parent().unwrap_or(std::path::Path::new(""));
+        let file = typescript::normalize(&directory.join(path));
+        Self::file_symbol_of(&file.to_string_lossy(), symbol_map)
+    }
+
+    fn file_symbol_of<'a>(file_path: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        symbol_map.in_file(file_path)
+            .iter()
+            .find(|symbol| symbol.kind == SymbolKind::Module)
+            .copied()
+    }
+
+    /// Indexed Makefile that `make` reads when run from `file_path`: the one
+    /// in the `-C` directory, or else the nearest one up the directory tree
+    fn makefile<'a>(directory: Option<&str>, file_path: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a str> {
+        let base = std::path::Path::new(file_path).parent().unwrap_or(std::path::Path::new(""));
+        let directories: Vec<std::path::PathBuf> = match directory {
+            Some(directory) => vec![typescript::normalize(&base.join(shell::script_path(directory)?))],
+            None => base.ancestors().map(std::path::Path::to_path_buf).collect(),
+        };
+        directories.iter()
+            .flat_map(|directory| make::FILE_NAMES.iter().map(move |name| directory.join(name).to_string_lossy().to_string()))
+            .find_map(|file| symbol_map.in_file(&file).first().map(|symbol| symbol.location.file.as_str()))
+    }
+
+    /// Target of a Makefile, or its default goal for `None`
+    fn make_target<'a>(makefile: &str, target: Option<&str>, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        symbol_map.in_file(makefile)
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Function && symbol.language == "makefile")
+            .find(|symbol| match target {
+                Some(target) => symbol.name == target,
+                None => symbol.metadata["default"] == true,
+            })
+            .copied()
+    }
+}
+
+impl MakefileParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn extract_symbols(&self, file: &make::File, file_path: &str) -> Vec<Symbol> {
+        let mut symbols = vec![ShellParser::file_symbol(file_path, "makefile")];
+        let symbol = |kind, name: &str, span: Span, signature, metadata| Symbol {
+            id: format!("{}:{}", file_path, name),
+            kind,
+            name: name.to_string(),
+            qualified_name: name.to_string(),
+            location: span.location(file_path),
+            signature: Some(signature),
+            type_info: None,
+            visibility: Visibility::Public,
+            language: "makefile".to_string(),
+            metadata,
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        };
+
+        // A bare `make` builds the first target that is not a pattern
+        let default = file.rules.iter().flat_map(|rule| &rule.targets).find(|target| !target.contains('%'));
+        // Rules adding prerequisites to a target, and variables set again or
+        // appended to, keep the first definition
+        let mut seen = HashSet::new();
+        for rule in &file.rules {
+            for target in rule.targets.iter().filter(|target| seen.insert(target.as_str())) {
+                symbols.push(symbol(SymbolKind::Function, target, rule.span, rule.signature(target), serde_json::json!({
+                    "target": true,
+                    "phony": file.phony.contains(target),
+                    "default": default == Some(target),
+                    "double_colon": rule.double_colon,
+                    "docstring": rule.doc
+                })));
+            }
+        }
+        for variable in file.variables.iter().filter(|variable| seen.insert(variable.name.as_str())) {
+            let signature = match variable.operator.as_str() {
+                "define" => format!("define {}", variable.name),
+                operator => format!("{} {} {}", variable.name, operator, variable.value).trim_end().to_string(),
+            };
+            symbols.push(symbol(SymbolKind::Variable, &variable.name, variable.span, signature, serde_json::json!({
+                "operator": variable.operator,
+                "docstring": variable.doc
+            })));
+        }
+        symbols
+    }
+
+    fn extract_relationships(&self, file: &make::File, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(file, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, file: &make::File, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let file_id = format!("{}:{}", file_path, ShellParser::file_name(file_path));
+        let mut relationships = Vec::new();
+        let edge = |from_id: String, target: &Symbol, kind, location, metadata| Relationship {
+            from_id,
+            to_id: target.id.clone(),
+            kind,
+            location,
+            metadata,
+        };
+
+        // The Makefile and the files it includes, whose targets and variables its rules name
+        let mut scope = vec![file_path.to_string()];
+        for include in file.includes.iter().filter(|include| !include.path.contains('$')) {
+            let location = include.span.location(file_path);
+            match ShellParser::resolve_path(&include.path, file_path, symbol_map) {
+                Some(target) => {
+                    scope.push(target.location.file.clone());
+                    relationships.push(edge(file_id.clone(), target, RelationshipKind::DependsOn, location, serde_json::json!({ "include": true })));
+                }
+                // A missing `-include` is the usual case for local overrides
+                None if include.optional => {}
+                None => relationships.push(ProtobufParser::unresolved_file(file_id.clone(), &include.path, RelationshipKind::DependsOn, location)),
+            }
+        }
+        let find = |name: &str, kind: SymbolKind| {
+            scope.iter().find_map(|file| {
+                symbol_map.in_file(file).iter()
+                    .find(|symbol| symbol.kind == kind && symbol.language == "makefile" && symbol.name == name)
+                    .copied()
+            })
+        };
+
+        for rule in &file.rules {
+            let location = rule.span.location(file_path);
+            let script = match rule.recipe.trim() {
+                "" => None,
+                _ => shell::parse(&rule.recipe, rule.span.line).ok(),
+            };
+
+            for target in &rule.targets {
+                let from_id = format!("{}:{}", file_path, target);
+                for prerequisite in &rule.prerequisites {
+                    let metadata = serde_json::json!({ "prerequisite": true });
+                    if let Some(symbol) = find(prerequisite, SymbolKind::Function) {
+                        relationships.push(edge(from_id.clone(), symbol, RelationshipKind::Calls, location.clone(), metadata));
+                    } else if let Some(symbol) = ShellParser::resolve_exact(prerequisite, file_path, symbol_map) {
+                        // A script or other indexed file the target is rebuilt from
+                        relationships.push(edge(from_id.clone(), symbol, RelationshipKind::DependsOn, location.clone(), metadata));
+                    }
+                }
+                for variable in rule.variables.iter().filter_map(|name| find(name, SymbolKind::Variable)) {
+                    relationships.push(edge(from_id.clone(), variable, RelationshipKind::References, location.clone(), serde_json::json!({})));
+                }
+                for command in script.iter().flat_map(|script| &script.commands) {
+                    if let Some(invocation) = command.invocation() {
+                        let location = command.span.location(file_path);
+                        relationships.extend(ShellParser::invocation_relationships(from_id.clone(), &invocation, location, file_path, symbol_map));
+                    }
+                }
+            }
+        }
+        relationships
+    }
+}
+
+impl DockerfileParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn extract_symbols(&self, file: &dockerfile::File, file_path: &str) -> Vec<Symbol> {
+        let mut symbols = vec![ShellParser::file_symbol(file_path, "dockerfile")];
+        for stage in &file.stages {
+            let qualified_name = stage.qualified_name();
+            symbols.push(Symbol {
+                id: format!("{}:{}", file_path, qualified_name),
+                kind: SymbolKind::Class,
+                name: qualified_name.clone(),
+                qualified_name,
+                location: stage.span.location(file_path),
+                signature: Some(stage.signature()),
+                type_info: Some(stage.base.clone()),
+                visibility: Visibility::Public,
+                language: "dockerfile".to_string(),
+                metadata: serde_json::json!({
+                    "stage": stage.index,
+                    "platform": stage.platform,
+                    "docstring": stage.doc
+                }),
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            });
+        }
+        symbols
+    }
+
+    fn extract_relationships(&self, file: &dockerfile::File, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(file, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, file: &dockerfile::File, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let id = |stage: &dockerfile::Stage| format!("{}:{}", file_path, stage.qualified_name());
+        let mut relationships = Vec::new();
+
+        for (index, stage) in file.stages.iter().enumerate() {
+            // `FROM builder` starts from an earlier stage rather than an image
+            if let Some(base) = file.stage(&stage.base, index) {
+                relationships.push(Relationship {
+                    from_id: id(stage),
+                    to_id: id(base),
+                    kind: RelationshipKind::Extends,
+                    location: stage.span.location(file_path),
+                    metadata: serde_json::json!({}),
+                });
+            }
+            for copy in &stage.copies {
+                if let Some(source) = file.stage(&copy.from, index) {
+                    relationships.push(Relationship {
+                        from_id: id(stage),
+                        to_id: id(source),
+                        kind: RelationshipKind::Calls,
+                        location: copy.span.location(file_path),
+                        metadata: serde_json::json!({
+                            "copy_from": true,
+                            "sources": copy.sources
+                        }),
+                    });
+                }
+            }
+            for script in &stage.scripts {
+                let Ok(parsed) = shell::parse(&script.text, script.first_line) else {
+                    continue;
+                };
+                for command in &parsed.commands {
+                    if let Some(invocation) = command.invocation() {
+                        let location = command.span.location(file_path);
+                        relationships.extend(ShellParser::invocation_relationships(id(stage), &invocation, location, file_path, symbol_map));
+                    }
+                }
+            }
+        }
+        relationships
+    }
+}
+
+impl GrammarParser {
+    pub fn new(grammar: grammar::Grammar) -> Self {
+        Self { grammar }
+    }
+
+    pub fn kotlin() -> Self {
+        Self::new(grammar::Grammar::kotlin())
+    }
+
+    pub fn scala() -> Self {
+        Self::new(grammar::Grammar::scala())
+    }
+
+    /// Load the grammar and queries of a `[[languages.grammars]]` entry
+    pub fn load(config: &GrammarConfig) -> anyhow::Result<Self> {
+        Ok(Self::new(grammar::Grammar::load(config)?))
+    }
+
+    fn extract_symbols(&self, file: &grammar::File, file_path: &str) -> Vec<Symbol> {
+        let mut symbols = vec![ShellParser::file_symbol(file_path, &self.grammar.name)];
+
+        // Overloads and redefinitions keep the first definition
+        let mut seen = HashSet::new();
+        for definition in file.definitions.iter().filter(|definition| seen.insert(definition.qualified_name.as_str())) {
+            symbols.push(Symbol {
+                id: format!("{}:{}", file_path, definition.qualified_name),
+                kind: Self::symbol_kind(&definition.kind),
+                name: definition.name.clone(),
+                qualified_name: definition.qualified_name.clone(),
+                location: definition.span.location(file_path),
+                signature: Some(definition.signature.clone()),
+                type_info: None,
+                visibility: Visibility::Public,
+                language: self.grammar.name.clone(),
+                metadata: serde_json::json!({
+                    "definition": definition.kind,
+                    "receiver": definition.receiver,
+                    "docstring": definition.doc
+                }),
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            });
+        }
+        symbols
+    }
+
+    /// Symbol kind for the suffix of a `@definition.<kind>` capture, after
+    /// the kinds tree-sitter's tags queries use
+    fn symbol_kind(kind: &str) -> SymbolKind {
+        match kind {
+            "function" | "macro" => SymbolKind::Function,
+            "method" | "constructor" => SymbolKind::Method,
+            "class" | "struct" => SymbolKind::Class,
+            "interface" | "trait" | "type" | "enum" | "union" => SymbolKind::Type,
+            "module" | "namespace" | "package" => SymbolKind::Module,
+            "field" | "property" => SymbolKind::Field,
+            "parameter" => SymbolKind::Parameter,
+            "import" => SymbolKind::Import,
+            _ => SymbolKind::Variable,
+        }
+    }
+
+    /// Relationship kind for the suffix of a `@reference.<kind>` capture
+    fn relationship_kind(kind: &str) -> RelationshipKind {
+        match kind {
+            "call" | "send" => RelationshipKind::Calls,
+            "implementation" => RelationshipKind::Implements,
+            "extends" | "inheritance" => RelationshipKind::Extends,
+            "import" | "include" => RelationshipKind::Imports,
+            _ => RelationshipKind::References,
+        }
+    }
+
+    fn extract_relationships(&self, file: &grammar::File, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(file, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, file: &grammar::File, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let id = |definition: usize| format!("{}:{}", file_path, file.definitions[definition].qualified_name);
+        let file_id = format!("{}:{}", file_path, ShellParser::file_name(file_path));
+        // Names not defined in the file resolve to a definition in another
+        // file of the language or one it resolves in, the one sharing the
+        // longest directory prefix
+        let shared = |file: &str| file.chars().zip(file_path.chars()).take_while(|(a, b)| a == b).count();
+        let resolves_in = |language: &str| language == self.grammar.name || self.grammar.resolve_in.iter().any(|other| other == language);
+
+        file.references.iter()
+            .map(|reference| {
+                let from_id = reference.enclosing.map_or_else(|| file_id.clone(), id);
+                let kind = Self::relationship_kind(&reference.kind);
+                let location = reference.span.location(file_path);
+                let target = reference.target.map(id).or_else(|| {
+                    symbol_map.named(&reference.name).iter()
+                        .filter(|symbol| resolves_in(&symbol.language) && symbol.kind != SymbolKind::Module)
+                        .max_by_key(|symbol| shared(&symbol.location.file))
+                        .map(|symbol| symbol.id.clone())
+                });
+                match target {
+                    Some(to_id) => Relationship { from_id, to_id, kind, location, metadata: serde_json::json!({}) },
+                    None => Relationship::unresolved(from_id, &reference.name, kind, location),
+                }
+            })
+            .collect()
+    }
+}
+
+impl IntentParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn extract_symbols(&self, file: &intent::File, file_path: &str) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+
+        for context in &file.contexts {
+            symbols.push(Symbol {
+                id: format!("{}:{}", file_path, context.name),
+                kind: SymbolKind::Context,
+                name: context.name.clone(),
+                qualified_name: context.name.clone(),
+                location: context.span.location(file_path),
+                signature: Some(context.signature()),
+                type_info: None,
+                visibility: Visibility::Public,
+                language: "intent".to_string(),
+                metadata: serde_json::json!({
+                    "context": true,
+                    "persist": context.persist,
+                    "extends": context.extends,
+                    "depends": context.depends,
+                    "docstring": context.doc
+                }),
+                content_hash: "".to_string(),
+                last_indexed: chrono::Utc::now().timestamp(),
+            });
+
+            for field in &context.fields {
+                let qualified_name = format!("{}.{}", context.name, field.name);
+                symbols.push(Symbol {
+                    id: format!("{}:{}", file_path, qualified_name),
+                    kind: SymbolKind::Field,
+                    name: field.name.clone(),
+                    qualified_name,
+                    location: field.span.location(file_path),
+                    signature: Some(format!("{}: {}", field.name, field.type_name)),
+                    type_info: Some(field.type_name.clone()),
+                    visibility: Visibility::Public,
+                    language: "intent".to_string(),
+                    metadata: serde_json::json!({
+                        "field": true,
+                        "default": field.default,
+                        "docstring": field.doc
+                    }),
+                    content_hash: "".to_string(),
+                    last_indexed: chrono::Utc::now().timestamp(),
+                });
+            }
+
+            for function in &context.functions {
+                symbols.push(self.function_symbol(function, Some(&context.name), file_path));
+            }
+
+            for handler in &context.handlers {
+                let name = handler.name();
+                let qualified_name = format!("{}.{}", context.name, name);
+                symbols.push(Symbol {
+                    id: format!("{}:{}", file_path, qualified_name),
+                    kind: SymbolKind::Method,
+                    name: name.clone(),
+                    qualified_name,
+                    location: handler.span.location(file_path),
+                    signature: Some(format!("on {}", handler.event)),
+                    type_info: None,
+                    visibility: Visibility::Public,
+                    language: "intent".to_string(),
+                    metadata: serde_json::json!({
+                        "handler": true,
+                        "event": handler.event,
+                        "docstring": handler.doc
+                    }),
+                    content_hash: "".to_string(),
+                    last_indexed: chrono::Utc::now().timestamp(),
+                });
+            }
+        }
+
+        for function in &file.functions {
+            symbols.push(self.function_symbol(function, None, file_path));
+        }
+
+        symbols
+    }
+
+    /// Function of a context, or a free function when `context` is `None`
+    fn function_symbol(&self, function: &intent::Function, context: Option<&str>, file_path: &str) -> Symbol {
+        let (kind, qualified_name) = match context {
+            Some(context) => (SymbolKind::Method, format!("{}.{}", context, function.name)),
+            None => (SymbolKind::Function, function.name.clone()),
+        };
+        let parameters: Vec<serde_json::Value> = function.parameters.iter()
+            .map(|parameter| serde_json::json!({ "name": parameter.name, "type": parameter.type_name }))
+            .collect();
+
+        Symbol {
+            id: format!("{}:{}", file_path, qualified_name),
+            kind,
+            name: function.name.clone(),
+            qualified_name,
+            location: function.span.location(file_path),
+            signature: Some(function.signature()),
+            type_info: function.return_type.clone(),
+            visibility: Visibility::Public,
+            language: "intent".to_string(),
+            metadata: serde_json::json!({
+                "function": true,
+                "parameters": parameters,
+                "docstring": function.doc
+            }),
+            content_hash: "".to_string(),
+            last_indexed: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn extract_relationships(&self, file: &intent::File, file_path: &str, symbols: &[Symbol]) -> Vec<Relationship> {
+        // Create a lookup table over the file's own symbols
+        let symbol_map = SymbolTable::new(symbols);
+        self.extract_relationships_with_table(file, file_path, &symbol_map)
+    }
+
+    fn extract_relationships_with_table(&self, file: &intent::File, file_path: &str, symbol_map: &SymbolTable) -> Vec<Relationship> {
+        let mut relationships = Vec::new();
+
+        for context in &file.contexts {
+            let Some(from) = self.find_context(&context.name, file_path, symbol_map) else {
+                continue;
+            };
+            let location = context.span.location(file_path);
+
+            let clauses = context.extends.iter()
+                .map(|name| (name, RelationshipKind::Extends))
+                .chain(context.depends.iter().map(|name| (name, RelationshipKind::DependsOn)));
+            for (name, kind) in clauses {
+                relationships.push(match self.find_context(name, file_path, symbol_map) {
+                    Some(target) => Relationship {
+                        from_id: from.id.clone(),
+                        to_id: target.id.clone(),
+                        kind,
+                        location: location.clone(),
+                        metadata: serde_json::json!({}),
+                    },
+                    None => Relationship::unresolved(from.id.clone(), name, kind, location.clone()),
+                });
+            }
+
+            let bodies = context.functions.iter()
+                .map(|function| (function.name.clone(), function.parameters.as_slice(), function.calls.as_slice()))
+                .chain(context.handlers.iter().map(|handler| (handler.name(), &[][..], handler.calls.as_slice())));
+            for (name, parameters, calls) in bodies {
+                let caller_id = format!("{}:{}.{}", file_path, context.name, name);
+                for call in calls {
+                    relationships.extend(self.extract_call(call, &caller_id, Some(from), parameters, file_path, symbol_map));
+                }
+            }
+        }
+
+        for function in &file.functions {
+            let caller_id = format!("{}:{}", file_path, function.name);
+            for call in &function.calls {
+                relationships.extend(self.extract_call(call, &caller_id, None, &function.parameters, file_path, symbol_map));
+            }
+        }
+
+        relationships
+    }
+
+    /// Context with this name, preferring the one declared in `file_path`
+    fn find_context<'a>(&self, name: &str, file_path: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        let candidates = symbol_map.named(name)
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Context && symbol.language == "intent");
+        candidates.clone()
+            .find(|symbol| symbol.location.file == file_path)
+            .or_else(|| candidates.min_by(|a, b| a.location.file.cmp(&b.location.file)))
+            .copied()
+    }
+
+    /// `context` followed by the contexts it extends, breadth first
+    fn context_chain<'a>(&self, context: &'a Symbol, file_path: &str, symbol_map: &SymbolTable<'a>) -> Vec<&'a Symbol> {
+        let mut chain = vec![context];
+        let mut index = 0;
+        while index < chain.len() && chain.len() < 16 {
+            let bases: Vec<String> = serde_json::from_value(chain[index].metadata["extends"].clone()).unwrap_or_default();
+            for base in bases {
+                if let Some(base) = self.find_context(&base, file_path, symbol_map) {
+                    if !chain.iter().any(|known| known.id == base.id) {
+                        chain.push(base);
+                    }
+                }
+            }
+            index += 1;
+        }
+        chain
+    }
+
+    /// Field, function or handler of a context or the contexts it extends
+    fn context_member<'a>(&self, context: &'a Symbol, name: &str, file_path: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        self.context_chain(context, file_path, symbol_map)
+            .into_iter()
+            .find_map(|context| {
+                symbol_map.children(&context.qualified_name)
+                    .iter()
+                    .find(|member| member.name == name && member.language == "intent")
+                    .copied()
+            })
+    }
+
+    /// Context a field or parameter type names, ignoring generic arguments
+    fn type_context<'a>(&self, type_name: &str, file_path: &str, symbol_map: &SymbolTable<'a>) -> Option<&'a Symbol> {
+        let name = type_name.split('<').next().unwrap_or(type_name).trim_end_matches('?');
+        self.find_context(name.rsplit('.').next().unwrap_or(name), file_path, symbol_map)
+    }
+
+    /// Resolve a call made in a function of `context`, or in a free function
+    ///
+    /// A bare name is a member of the context chain, then a free function.
+    /// Receiver paths are followed through `self`, parameters, fields and
+    /// context names, each step moving to the context the type names.
+    fn extract_call(
+        &self,
+        call: &intent::Call,
+        caller_id: &str,
+        context: Option<&Symbol>,
+        parameters: &[intent::Parameter],
+        file_path: &str,
+        symbol_map: &SymbolTable,
+    ) -> Option<Relationship> {
+        let target = match &call.receiver {
+            None => context
+                .and_then(|context| self.context_member(context, &call.name, file_path, symbol_map))
+                .filter(|member| member.kind != SymbolKind::Field)
+                .or_else(|| {
+                    let functions = symbol_map.named(&call.name)
+                        .iter()
+                        .filter(|symbol| symbol.kind == SymbolKind::Function && symbol.language == "intent");
+                    functions.clone()
+                        .find(|symbol| symbol.location.file == file_path)
+                        .or_else(|| functions.min_by(|a, b| a.location.file.cmp(&b.location.file)))
+                        .copied()
+                }),
+            Some(receiver) => {
+                let mut segments = receiver.split('.');
+                let first = segments.next().unwrap_or_default();
+                let parameter = parameters.iter().find(|parameter| parameter.name == first);
+                let mut current = match (first, parameter) {
+                    ("self", _) => context,
+                    (_, Some(parameter)) => self.type_context(&parameter.type_name, file_path, symbol_map),
+                    _ => context
+                        .and_then(|context| self.context_member(context, first, file_path, symbol_map))
+                        .filter(|member| member.kind == SymbolKind::Field)
+                        .and_then(|field| self.type_context(field.type_info.as_deref()?, file_path, symbol_map))
+                        .or_else(|| self.find_context(first, file_path, symbol_map)),
+                };
+                for segment in segments {
+                    current = current
+                        .and_then(|context| self.context_member(context, segment, file_path, symbol_map))
+                        .filter(|member| member.kind == SymbolKind::Field)
+                        .and_then(|field| self.type_context(field.type_info.as_deref()?, file_path, symbol_map));
+                }
+                current
+                    .and_then(|context| self.context_member(context, &call.name, file_path, symbol_map))
+                    .filter(|member| member.kind != SymbolKind::Field)
+            }
+        };
+
+        let location = call.span.location(file_path);
+        match target {
+            Some(target) => Some(Relationship {
+                from_id: caller_id.to_string(),
+                to_id: target.id.clone(),
+                kind: RelationshipKind::Calls,
+                location,
+                metadata: serde_json::json!({}),
+            }),
+            None if call.receiver.is_none() && intent::is_builtin(&call.name) => None,
+            // Record the call so it can be re-resolved once a matching symbol appears
+            None => Some(Relationship::unresolved(caller_id.to_string(), &call.callee(), RelationshipKind::Calls, location)),
+        }
+    }
+}
+
+impl crate::index::Parser for JavaParser {
+    fn extensions(&self) -> Vec<&str> {
+        vec!["java"]
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let tree = self.parse_tree(content)?;
+        let symbols = self.extract_symbols(&tree, content, file_path);
+        let relationships = self.extract_relationships(&tree, content, file_path, &symbols);
 
         Ok((symbols, relationships))
     }
 
-    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &std::collections::HashMap<&str, &Symbol>) -> anyhow::Result<Vec<Relationship>> {
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
         let tree = self.parse_tree(content)?;
-        let mut relationships = Vec::new();
-        let root = tree.root_node();
+        Ok(self.extract_relationships_with_table(&tree, content, file_path, global_symbol_map))
+    }
 
-        let mut cursor = root.walk();
-        self.extract_relationships_from_tree(&mut cursor, content, file_path, global_symbol_map, &mut relationships, Vec::new());
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let tree = self.parse_tree(&content)?;
+        let symbols = self.extract_symbols(&tree, &content, file_path);
+        let file_path = file_path.to_string();
 
-        Ok(relationships)
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&tree, &content, &file_path, symbols)))))
     }
 }
 
-impl crate::index::Parser for IntentParser {
+impl crate::index::Parser for TypeScriptParser {
+    fn extensions(&self) -> Vec<&str> {
+        if self.javascript {
+            vec!["js", "jsx", "mjs", "cjs"]
+        } else {
+            vec!["ts", "tsx", "mts", "cts"]
+        }
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let tree = self.parse_tree(content, file_path)?;
+        let symbols = self.extract_symbols(&tree, content, file_path);
+        let relationships = self.extract_relationships(&tree, content, file_path, &symbols);
+
+        Ok((symbols, relationships))
+    }
+
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let tree = self.parse_tree(content, file_path)?;
+        Ok(self.extract_relationships_with_table(&tree, content, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let tree = self.parse_tree(&content, file_path)?;
+        let symbols = self.extract_symbols(&tree, &content, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&tree, &content, &file_path, symbols)))))
+    }
+}
+
+impl crate::index::Parser for CParser {
+    fn extensions(&self) -> Vec<&str> {
+        if self.cpp { c::CPP_EXTENSIONS } else { c::C_EXTENSIONS }.to_vec()
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let cpp = self.is_cpp(file_path, content);
+        let tree = self.parse_tree(content, cpp)?;
+        let context = self.context(tree.root_node(), content, cpp);
+        let symbols = self.extract_symbols(&tree, content, file_path, &context);
+        let relationships = self.extract_relationships(&tree, content, file_path, &symbols);
+
+        Ok((symbols, relationships))
+    }
+
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let tree = self.parse_tree(content, self.is_cpp(file_path, content))?;
+        Ok(self.extract_relationships_with_table(&tree, content, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let cpp = self.is_cpp(file_path, &content);
+        let tree = self.parse_tree(&content, cpp)?;
+        let context = self.context(tree.root_node(), &content, cpp);
+        let symbols = self.extract_symbols(&tree, &content, file_path, &context);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&tree, &content, &file_path, symbols)))))
+    }
+}
+
+impl crate::index::Parser for CSharpParser {
+    fn extensions(&self) -> Vec<&str> {
+        vec!["cs"]
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let tree = self.parse_tree(content)?;
+        let symbols = self.extract_symbols(&tree, content, file_path);
+        let relationships = self.extract_relationships(&tree, content, file_path, &symbols);
+
+        Ok((symbols, relationships))
+    }
+
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let tree = self.parse_tree(content)?;
+        Ok(self.extract_relationships_with_table(&tree, content, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let tree = self.parse_tree(&content)?;
+        let symbols = self.extract_symbols(&tree, &content, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&tree, &content, &file_path, symbols)))))
+    }
+}
+
+impl crate::index::Parser for RubyParser {
+    fn extensions(&self) -> Vec<&str> {
+        vec!["rb", "rake"]
+    }
+
+    fn can_parse(&self, file_path: &str) -> bool {
+        file_path.ends_with(".rb") || file_path.ends_with(".rake") || file_path.ends_with("Rakefile")
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let tree = self.parse_tree(content)?;
+        let symbols = self.extract_symbols(&tree, content, file_path);
+        let relationships = self.extract_relationships(&tree, content, file_path, &symbols);
+
+        Ok((symbols, relationships))
+    }
+
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let tree = self.parse_tree(content)?;
+        Ok(self.extract_relationships_with_table(&tree, content, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let tree = self.parse_tree(&content)?;
+        let symbols = self.extract_symbols(&tree, &content, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&tree, &content, &file_path, symbols)))))
+    }
+}
+
+impl crate::index::Parser for ProtobufParser {
+    fn extensions(&self) -> Vec<&str> {
+        vec!["proto"]
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let file = protobuf::parse(content);
+        if let Some(error) = file.errors.first() {
+            tracing::warn!("{}:{} ({} syntax errors recovered)", file_path, error, file.errors.len());
+        }
+        let symbols = self.extract_symbols(&file, file_path);
+        let relationships = self.extract_relationships(&file, file_path, &symbols);
+
+        Ok((symbols, relationships))
+    }
+
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let file = protobuf::parse(content);
+        Ok(self.extract_relationships_with_table(&file, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let file = protobuf::parse(&content);
+        if let Some(error) = file.errors.first() {
+            tracing::warn!("{}:{} ({} syntax errors recovered)", file_path, error, file.errors.len());
+        }
+        let symbols = self.extract_symbols(&file, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&file, &file_path, symbols)))))
+    }
+}
+
+impl crate::index::Parser for SqlParser {
+    fn extensions(&self) -> Vec<&str> {
+        vec!["sql"]
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let file = sql::parse(content);
+        let symbols = self.extract_symbols(&file, file_path);
+        let relationships = self.extract_relationships(&file, file_path, &symbols);
+
+        Ok((symbols, relationships))
+    }
+
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let file = sql::parse(content);
+        Ok(self.extract_relationships_with_table(&file, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let file = sql::parse(&content);
+        let symbols = self.extract_symbols(&file, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&file, &file_path, symbols)))))
+    }
+}
+
+impl crate::index::Parser for ShellParser {
+    fn extensions(&self) -> Vec<&str> {
+        shell::EXTENSIONS.to_vec()
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let script = shell::parse(content, 0)?;
+        let symbols = self.extract_symbols(&script, file_path);
+        let relationships = self.extract_relationships(&script, file_path, &symbols);
+
+        Ok((symbols, relationships))
+    }
+
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let script = shell::parse(content, 0)?;
+        Ok(self.extract_relationships_with_table(&script, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let script = shell::parse(&content, 0)?;
+        let symbols = self.extract_symbols(&script, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&script, &file_path, symbols)))))
+    }
+}
+
+impl crate::index::Parser for MakefileParser {
+    fn extensions(&self) -> Vec<&str> {
+        make::EXTENSIONS.to_vec()
+    }
+
+    fn can_parse(&self, file_path: &str) -> bool {
+        make::is_makefile(file_path)
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let file = make::parse(content);
+        let symbols = self.extract_symbols(&file, file_path);
+        let relationships = self.extract_relationships(&file, file_path, &symbols);
+
+        Ok((symbols, relationships))
+    }
+
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let file = make::parse(content);
+        Ok(self.extract_relationships_with_table(&file, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let file = make::parse(&content);
+        let symbols = self.extract_symbols(&file, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&file, &file_path, symbols)))))
+    }
+}
+
+impl crate::index::Parser for DockerfileParser {
+    fn extensions(&self) -> Vec<&str> {
+        vec!["dockerfile"]
+    }
+
+    fn can_parse(&self, file_path: &str) -> bool {
+        dockerfile::is_dockerfile(file_path)
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let file = dockerfile::parse(content);
+        let symbols = self.extract_symbols(&file, file_path);
+        let relationships = self.extract_relationships(&file, file_path, &symbols);
+
+        Ok((symbols, relationships))
+    }
+
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let file = dockerfile::parse(content);
+        Ok(self.extract_relationships_with_table(&file, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let file = dockerfile::parse(&content);
+        let symbols = self.extract_symbols(&file, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&file, &file_path, symbols)))))
+    }
+}
+
+impl crate::index::Parser for GrammarParser {
+    fn extensions(&self) -> Vec<&str> {
+        self.grammar.extensions.iter().map(|extension| extension.trim_start_matches('.')).collect()
+    }
+
     fn can_parse(&self, file_path: &str) -> bool {
-        file_path.ends_with(".intent")
+        self.grammar.handles(file_path)
+    }
+
+    fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
+        let file = self.grammar.parse(content)?;
+        let symbols = self.extract_symbols(&file, file_path);
+        let relationships = self.extract_relationships(&file, file_path, &symbols);
+
+        Ok((symbols, relationships))
+    }
+
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let file = self.grammar.parse(content)?;
+        Ok(self.extract_relationships_with_table(&file, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let file = self.grammar.parse(&content)?;
+        let symbols = self.extract_symbols(&file, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&file, &file_path, symbols)))))
+    }
+}
+
+impl crate::index::Parser for IntentParser {
+    fn extensions(&self) -> Vec<&str> {
+        vec!["intent"]
     }
 
     fn parse(&self, content: &str, file_path: &str) -> anyhow::Result<(Vec<Symbol>, Vec<Relationship>)> {
-        let symbols = self.extract_symbols(content, file_path);
-        let relationships = self.extract_relationships(content, file_path, &symbols);
+        let file = intent::parse(content);
+        if let Some(error) = file.errors.first() {
+            tracing::warn!("{}:{} ({} syntax errors recovered)", file_path, error, file.errors.len());
+        }
+        let symbols = self.extract_symbols(&file, file_path);
+        let relationships = self.extract_relationships(&file, file_path, &symbols);
 
         Ok((symbols, relationships))
     }
 
-    fn extract_relationships_with_global_context(&self, _content: &str, _file_path: &str, _global_symbol_map: &std::collections::HashMap<&str, &Symbol>) -> anyhow::Result<Vec<Relationship>> {
-        // For now, skip relationship extraction for Intent files
-        // TODO: Implement proper relationship extraction with context tracking
-        Ok(Vec::new())
+    fn extract_relationships_with_global_context(&self, content: &str, file_path: &str, global_symbol_map: &SymbolTable) -> anyhow::Result<Vec<Relationship>> {
+        let file = intent::parse(content);
+        Ok(self.extract_relationships_with_table(&file, file_path, global_symbol_map))
+    }
+
+    fn parse_pending(&self, content: String, file_path: &str) -> anyhow::Result<(Vec<Symbol>, PendingRelationships<'_>)> {
+        let file = intent::parse(&content);
+        if let Some(error) = file.errors.first() {
+            tracing::warn!("{}:{} ({} syntax errors recovered)", file_path, error, file.errors.len());
+        }
+        let symbols = self.extract_symbols(&file, file_path);
+        let file_path = file_path.to_string();
+
+        Ok((symbols, Box::new(move |symbols| Ok(self.extract_relationships_with_table(&file, &file_path, symbols)))))
     }
 }
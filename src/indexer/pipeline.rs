@@ -0,0 +1,52 @@
+// Worker pool feeding a single writer
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Run `work` over `items` on `threads` worker threads
+///
+/// Results are handed to `sink` on the calling thread as they complete, so the
+/// sink can own the database writer. The first error returned by `sink` stops
+/// the workers and is returned.
+pub fn run<T, R, W, S>(items: &[T], threads: usize, work: W, mut sink: S) -> anyhow::Result<()>
+where
+    T: Sync,
+    R: Send,
+    W: Fn(&T) -> R + Sync,
+    S: FnMut(R) -> anyhow::Result<()>,
+{
+    let threads = threads.clamp(1, items.len().max(1));
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        // Bounded so that a slow writer applies back-pressure to the workers
+        let (tx, rx) = mpsc::sync_channel(threads * 2);
+
+        for _ in 0..threads {
+            let tx = tx.clone();
+            let (next, stop, work) = (&next, &stop, &work);
+            scope.spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let Some(item) = items.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                        break;
+                    };
+                    if tx.send(work(item)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        for result in rx {
+            if let Err(e) = sink(result) {
+                stop.store(true, Ordering::Relaxed);
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    })
+}
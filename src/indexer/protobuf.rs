@@ -0,0 +1,1054 @@
+// Protocol Buffers lexer and recursive-descent parser, and the names `protoc`
+// gives generated code
+//
+// Grammar, options and reserved ranges being skipped as statements:
+//
+//   file    := (syntax | package | import | option | message | enum | service | extend | ";")*
+//   syntax  := ("syntax" | "edition") "=" STRING ";"
+//   package := "package" IDENT ("." IDENT)* ";"
+//   import  := "import" ("public" | "weak")? STRING ";"
+//   message := "message" IDENT "{" (field | map | oneof | message | enum | extend | option | reserved)* "}"
+//   field   := ("optional" | "required" | "repeated")? type IDENT "=" INT ("[" ... "]")? ";"
+//   map     := "map" "<" type "," type ">" IDENT "=" INT ("[" ... "]")? ";"
+//   oneof   := "oneof" IDENT "{" (field | option)* "}"
+//   enum    := "enum" IDENT "{" (IDENT "=" "-"? INT ("[" ... "]")? ";" | option | reserved)* "}"
+//   service := "service" IDENT "{" (rpc | option)* "}"
+//   rpc     := "rpc" IDENT "(" "stream"? type ")" "returns" "(" "stream"? type ")" (";" | "{" option* "}")
+//   type    := "."? IDENT ("." IDENT)*
+
+use std::fmt;
+
+use crate::index::Span;
+
+/// Field types that name no message or enum
+const SCALAR_TYPES: &[&str] = &[
+    "double", "float", "int32", "int64", "uint32", "uint64", "sint32", "sint64",
+    "fixed32", "fixed64", "sfixed32", "sfixed64", "bool", "string", "bytes",
+];
+
+/// Header lines `protoc` and its gRPC plugins write into generated files
+const GENERATED_MARKERS: &[&str] = &[
+    "Generated by the protocol buffer compiler",
+    "Code generated by protoc-gen-go",
+    "Generated by the gRPC Python protocol compiler plugin",
+    "by gRPC proto compiler",
+];
+
+/// File name endings of generated Go and Python code, which drop `.proto`
+const GENERATED_SUFFIXES: &[&str] = &["_grpc.pb.go", ".pb.go", "_pb2_grpc.py", "_pb2.pyi", "_pb2.py"];
+
+/// Affixes gRPC generators add to a service name for clients, servers and stubs
+const SERVICE_PREFIXES: &[&str] = &["Unimplemented", "Unsafe"];
+const SERVICE_SUFFIXES: &[&str] = &[
+    "BlockingV2Stub", "BlockingStub", "FutureStub", "ImplBase", "Servicer", "Client", "Server", "Stub", "Grpc",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident,
+    String,
+    Number,
+    Punct,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+    span: Span,
+    /// `//` comment lines directly above the token, the way `protoc` attaches
+    /// leading comments
+    doc: Option<String>,
+}
+
+/// Syntax error the parser recovered from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.span.line + 1, self.span.column + 1, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct File {
+    /// `proto2`, `proto3` or an edition
+    pub syntax: Option<String>,
+    pub package: String,
+    pub imports: Vec<Import>,
+    /// File options such as `go_package`, values unquoted
+    pub options: Vec<(String, String)>,
+    pub messages: Vec<Message>,
+    pub enums: Vec<Enum>,
+    pub services: Vec<Service>,
+    pub errors: Vec<SyntaxError>,
+}
+
+impl File {
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Import {
+    /// Path relative to an import root, e.g. `google/protobuf/empty.proto`
+    pub path: String,
+    pub public: bool,
+    pub weak: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct Message {
+    pub name: String,
+    pub doc: Option<String>,
+    pub fields: Vec<Field>,
+    pub messages: Vec<Message>,
+    pub enums: Vec<Enum>,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct Field {
+    pub name: String,
+    pub doc: Option<String>,
+    /// `optional`, `required` or `repeated`
+    pub label: Option<String>,
+    /// Value type, or the type named by a `group`
+    pub type_name: String,
+    /// Key type of a `map<K, V>` field
+    pub key_type: Option<String>,
+    pub number: i64,
+    pub oneof: Option<String>,
+    pub span: Span,
+}
+
+impl Field {
+    /// Declaration without options, e.g. `repeated string names = 2`
+    pub fn signature(&self) -> String {
+        let type_name = match &self.key_type {
+            Some(key_type) => format!("map<{}, {}>", key_type, self.type_name),
+            None => self.type_name.clone(),
+        };
+        match &self.label {
+            Some(label) => format!("{} {} {} = {}", label, type_name, self.name, self.number),
+            None => format!("{} {} = {}", type_name, self.name, self.number),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Enum {
+    pub name: String,
+    pub doc: Option<String>,
+    pub values: Vec<EnumValue>,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct EnumValue {
+    pub name: String,
+    pub doc: Option<String>,
+    pub number: i64,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct Service {
+    pub name: String,
+    pub doc: Option<String>,
+    pub rpcs: Vec<Rpc>,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct Rpc {
+    pub name: String,
+    pub doc: Option<String>,
+    pub request: String,
+    pub response: String,
+    pub client_streaming: bool,
+    pub server_streaming: bool,
+    pub span: Span,
+}
+
+impl Rpc {
+    pub fn signature(&self) -> String {
+        let stream = |streaming: bool| if streaming { "stream " } else { "" };
+        format!(
+            "rpc {}({}{}) returns ({}{})",
+            self.name, stream(self.client_streaming), self.request, stream(self.server_streaming), self.response,
+        )
+    }
+}
+
+pub fn is_scalar(type_name: &str) -> bool {
+    SCALAR_TYPES.contains(&type_name)
+}
+
+/// Parse a `.proto` file, recovering from syntax errors at the next statement
+pub fn parse(source: &str) -> File {
+    let tokens = tokenize(source);
+    let mut parser = Parser { source, tokens, position: 0, errors: Vec::new() };
+    let file = parser.file();
+    File { errors: parser.errors, ..file }
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let bytes = source.as_bytes();
+    let (mut line, mut line_start) = (0u32, 0usize);
+    // Leading comment lines waiting for the next token, and the line of the last one
+    let (mut comments, mut comment_line): (Vec<&str>, Option<u32>) = (Vec::new(), None);
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let (start_line, start_column) = (line, (start - line_start) as u32);
+
+        let kind = match bytes[i] {
+            b'\n' => {
+                i += 1;
+                line += 1;
+                line_start = i;
+                continue;
+            }
+            c if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                // A trailing comment documents the token before it, not the next one
+                let trailing = tokens.last().is_some_and(|token| token.span.end_line == start_line);
+                if !trailing {
+                    // A blank line detaches the comments above it
+                    if comment_line.is_some_and(|previous| previous + 1 != start_line) {
+                        comments.clear();
+                    }
+                    comments.push(source[start + 2..i].trim());
+                    comment_line = Some(start_line);
+                }
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !bytes[i..].starts_with(b"*/") {
+                    if bytes[i] == b'\n' {
+                        line += 1;
+                        line_start = i + 1;
+                    }
+                    i += 1;
+                }
+                i = (i + 2).min(bytes.len());
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(bytes.len());
+                TokenKind::String
+            }
+            c if c.is_ascii_digit() => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.') {
+                    i += 1;
+                }
+                TokenKind::Number
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                TokenKind::Ident
+            }
+            _ => {
+                // Keep multi-byte characters whole so that slicing stays on boundaries
+                i += source[i..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Punct
+            }
+        };
+
+        let doc = (comment_line.is_some_and(|previous| previous + 1 == start_line) && !comments.is_empty())
+            .then(|| comments.join("\n"));
+        comments.clear();
+        comment_line = None;
+
+        tokens.push(Token {
+            kind,
+            start,
+            end: i,
+            span: Span { line: start_line, column: start_column, end_line: line, end_column: (i - line_start) as u32 },
+            doc,
+        });
+    }
+
+    tokens
+}
+
+/// Value of an integer literal, decimal, hexadecimal or octal
+fn integer(text: &str) -> Option<i64> {
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        return i64::from_str_radix(hex, 16).ok();
+    }
+    if text.len() > 1 && text.starts_with('0') {
+        return i64::from_str_radix(&text[1..], 8).ok();
+    }
+    text.parse().ok()
+}
+
+fn unquote(text: &str) -> String {
+    text.get(1..text.len().saturating_sub(1)).unwrap_or_default().to_string()
+}
+
+struct Parser<'s> {
+    source: &'s str,
+    tokens: Vec<Token>,
+    position: usize,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'s> Parser<'s> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn text(&self, token: &Token) -> &'s str {
+        &self.source[token.start..token.end]
+    }
+
+    /// Whether the token `offset` ahead is this identifier or punctuation
+    fn at_offset(&self, offset: usize, text: &str) -> bool {
+        self.tokens.get(self.position + offset)
+            .is_some_and(|token| token.kind != TokenKind::String && self.text(token) == text)
+    }
+
+    fn at(&self, text: &str) -> bool {
+        self.at_offset(0, text)
+    }
+
+    fn at_kind(&self, kind: TokenKind) -> bool {
+        self.peek().is_some_and(|token| token.kind == kind)
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self.at(text) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&mut self, message: impl Into<String>) {
+        let span = self.peek()
+            .or_else(|| self.tokens.last())
+            .map(|token| token.span)
+            .unwrap_or_default();
+        self.errors.push(SyntaxError { message: message.into(), span });
+    }
+
+    fn expect(&mut self, text: &str) -> Option<()> {
+        if self.eat(text) {
+            return Some(());
+        }
+        let found = self.peek().map_or("end of file".to_string(), |token| format!("`{}`", self.text(token)));
+        self.error(format!("expected `{}`, found {}", text, found));
+        None
+    }
+
+    fn ident(&mut self, what: &str) -> Option<String> {
+        if self.at_kind(TokenKind::Ident) {
+            let token = self.bump()?;
+            return Some(self.text(&token).to_string());
+        }
+        self.error(format!("expected {}", what));
+        None
+    }
+
+    fn string(&mut self, what: &str) -> Option<String> {
+        if self.at_kind(TokenKind::String) {
+            let token = self.bump()?;
+            return Some(unquote(self.text(&token)));
+        }
+        self.error(format!("expected {}", what));
+        None
+    }
+
+    /// Signed integer, such as a field number or enum value
+    fn integer(&mut self) -> Option<i64> {
+        let negative = self.eat("-");
+        let value = self.peek()
+            .filter(|token| token.kind == TokenKind::Number)
+            .and_then(|token| integer(self.text(token)));
+        match value {
+            Some(value) => {
+                self.position += 1;
+                Some(if negative { -value } else { value })
+            }
+            None => {
+                self.error("expected a number");
+                None
+            }
+        }
+    }
+
+    /// Dotted type name, keeping a leading `.` that makes it fully qualified
+    fn type_name(&mut self) -> Option<String> {
+        let mut name = String::new();
+        if self.eat(".") {
+            name.push('.');
+        }
+        name.push_str(&self.ident("a type")?);
+        while self.at(".") {
+            self.position += 1;
+            name.push('.');
+            name.push_str(&self.ident("a type")?);
+        }
+        Some(name)
+    }
+
+    /// Skip the tokens of a balanced `[ ... ]`, `( ... )` or `{ ... }` group
+    fn skip_group(&mut self) {
+        let mut depth = 0usize;
+        while let Some(token) = self.bump() {
+            match self.text(&token) {
+                "{" | "(" | "[" => depth += 1,
+                "}" | ")" | "]" => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Skip to the end of a statement: past its `;` or its `{ ... }` body,
+    /// stopping before the `}` that closes the enclosing body
+    fn skip_statement(&mut self) {
+        while let Some(token) = self.peek() {
+            match self.text(token) {
+                ";" => {
+                    self.position += 1;
+                    return;
+                }
+                "{" => {
+                    self.skip_group();
+                    return;
+                }
+                "[" | "(" => self.skip_group(),
+                "}" => return,
+                _ => self.position += 1,
+            }
+        }
+    }
+
+    /// `[deprecated = true]` after a field or value
+    fn field_options(&mut self) {
+        if self.at("[") {
+            self.skip_group();
+        }
+    }
+
+    fn file(&mut self) -> File {
+        let mut file = File::default();
+        while let Some(token) = self.peek() {
+            let keyword = self.text(token);
+            let parsed = match keyword {
+                "syntax" | "edition" => self.syntax().map(|syntax| file.syntax = Some(syntax)),
+                "package" => self.package().map(|package| file.package = package),
+                "import" => self.import().map(|import| file.imports.push(import)),
+                "option" => self.option().map(|option| file.options.push(option)),
+                "message" => self.message().map(|message| file.messages.push(message)),
+                "enum" => self.enumeration().map(|enumeration| file.enums.push(enumeration)),
+                "service" => self.service().map(|service| file.services.push(service)),
+                "extend" => {
+                    self.skip_statement();
+                    Some(())
+                }
+                ";" => {
+                    self.position += 1;
+                    Some(())
+                }
+                _ => {
+                    self.error(format!("expected a declaration, found `{}`", keyword));
+                    // A stray `}` ends no statement, so step over it
+                    if keyword == "}" {
+                        self.position += 1;
+                    }
+                    None
+                }
+            };
+            if parsed.is_none() {
+                self.skip_statement();
+            }
+        }
+        file
+    }
+
+    fn syntax(&mut self) -> Option<String> {
+        self.position += 1;
+        self.expect("=")?;
+        let syntax = self.string("a syntax")?;
+        self.expect(";")?;
+        Some(syntax)
+    }
+
+    fn package(&mut self) -> Option<String> {
+        self.position += 1;
+        let package = self.type_name()?;
+        self.expect(";")?;
+        Some(package)
+    }
+
+    fn import(&mut self) -> Option<Import> {
+        let start = self.bump()?.span;
+        let public = self.eat("public");
+        let weak = !public && self.eat("weak");
+        let path = self.string("an import path")?;
+        let end = self.peek().map_or(start, |token| token.span);
+        self.expect(";")?;
+        Some(Import { path, public, weak, span: start.to(end) })
+    }
+
+    /// `option name = value;`, with `(custom).option` names kept as written
+    fn option(&mut self) -> Option<(String, String)> {
+        self.position += 1;
+        let mut name = String::new();
+        while let Some(token) = self.peek().filter(|token| self.text(token) != "=" && self.text(token) != ";") {
+            name.push_str(self.text(token));
+            self.position += 1;
+        }
+        self.expect("=")?;
+        let value = match self.peek() {
+            Some(token) if token.kind == TokenKind::String => unquote(self.text(token)),
+            Some(token) if self.text(token) == "{" => {
+                self.skip_group();
+                return self.expect(";").map(|_| (name, String::new()));
+            }
+            Some(token) => self.text(token).to_string(),
+            None => return None,
+        };
+        self.position += 1;
+        // The rest of a negative number, or adjacent strings
+        while self.peek().is_some_and(|token| self.text(token) != ";" && self.text(token) != "}") {
+            self.position += 1;
+        }
+        self.expect(";")?;
+        Some((name, value))
+    }
+
+    fn message(&mut self) -> Option<Message> {
+        let keyword = self.bump()?;
+        let name = self.ident("a message name")?;
+        let mut message = Message { name, doc: keyword.doc.clone(), ..Default::default() };
+        self.expect("{")?;
+        let end = self.body(&mut message);
+        message.span = keyword.span.to(end);
+        Some(message)
+    }
+
+    /// Members of a message or group up to and including its closing `}`
+    fn body(&mut self, message: &mut Message) -> Span {
+        while let Some(token) = self.peek() {
+            let parsed = match self.text(token) {
+                "}" => {
+                    let end = token.span;
+                    self.position += 1;
+                    return end;
+                }
+                "message" => self.message().map(|nested| message.messages.push(nested)),
+                "enum" => self.enumeration().map(|nested| message.enums.push(nested)),
+                "oneof" => self.oneof(message),
+                "map" if self.at_offset(1, "<") => self.map().map(|field| message.fields.push(field)),
+                "option" => self.option().map(|_| ()),
+                "reserved" | "extensions" | "extend" => {
+                    self.skip_statement();
+                    Some(())
+                }
+                ";" => {
+                    self.position += 1;
+                    Some(())
+                }
+                _ => self.field(message, None),
+            };
+            if parsed.is_none() {
+                self.skip_statement();
+            }
+        }
+        self.error("expected `}`");
+        self.tokens.last().map(|token| token.span).unwrap_or_default()
+    }
+
+    /// Field of `message`, or a `group` which also declares a nested message
+    fn field(&mut self, message: &mut Message, oneof: Option<&str>) -> Option<()> {
+        let first = self.peek()?.clone();
+        let label = match self.text(&first) {
+            "optional" | "required" | "repeated" => {
+                self.position += 1;
+                Some(self.text(&first).to_string())
+            }
+            _ => None,
+        };
+
+        if self.eat("group") {
+            let name = self.ident("a group name")?;
+            self.expect("=")?;
+            let number = self.integer()?;
+            self.field_options();
+            self.expect("{")?;
+            let mut group = Message { name: name.clone(), doc: first.doc.clone(), ..Default::default() };
+            let end = self.body(&mut group);
+            group.span = first.span.to(end);
+            message.fields.push(Field {
+                name: name.to_lowercase(),
+                doc: first.doc,
+                label,
+                type_name: name,
+                key_type: None,
+                number,
+                oneof: oneof.map(str::to_string),
+                span: first.span.to(end),
+            });
+            message.messages.push(group);
+            return Some(());
+        }
+
+        let type_name = self.type_name()?;
+        let name = self.ident("a field name")?;
+        self.expect("=")?;
+        let number = self.integer()?;
+        self.field_options();
+        let end = self.peek().map_or(first.span, |token| token.span);
+        self.expect(";")?;
+        message.fields.push(Field {
+            name,
+            doc: first.doc,
+            label,
+            type_name,
+            key_type: None,
+            number,
+            oneof: oneof.map(str::to_string),
+            span: first.span.to(end),
+        });
+        Some(())
+    }
+
+    fn map(&mut self) -> Option<Field> {
+        let keyword = self.bump()?;
+        self.expect("<")?;
+        let key_type = self.type_name()?;
+        self.expect(",")?;
+        let type_name = self.type_name()?;
+        self.expect(">")?;
+        let name = self.ident("a field name")?;
+        self.expect("=")?;
+        let number = self.integer()?;
+        self.field_options();
+        let end = self.peek().map_or(keyword.span, |token| token.span);
+        self.expect(";")?;
+        Some(Field {
+            name,
+            doc: keyword.doc,
+            label: None,
+            type_name,
+            key_type: Some(key_type),
+            number,
+            oneof: None,
+            span: keyword.span.to(end),
+        })
+    }
+
+    /// `oneof` members, which are fields of the enclosing message
+    fn oneof(&mut self, message: &mut Message) -> Option<()> {
+        self.position += 1;
+        let name = self.ident("a oneof name")?;
+        self.expect("{")?;
+        while let Some(token) = self.peek() {
+            let parsed = match self.text(token) {
+                "}" => break,
+                "option" => self.option().map(|_| ()),
+                ";" => {
+                    self.position += 1;
+                    Some(())
+                }
+                _ => self.field(message, Some(&name)),
+            };
+            if parsed.is_none() {
+                self.skip_statement();
+            }
+        }
+        self.expect("}")
+    }
+
+    fn enumeration(&mut self) -> Option<Enum> {
+        let keyword = self.bump()?;
+        let name = self.ident("an enum name")?;
+        let mut enumeration = Enum { name, doc: keyword.doc.clone(), ..Default::default() };
+        self.expect("{")?;
+        while let Some(token) = self.peek() {
+            let parsed = match self.text(token) {
+                "}" => {
+                    enumeration.span = keyword.span.to(token.span);
+                    self.position += 1;
+                    return Some(enumeration);
+                }
+                "option" => self.option().map(|_| ()),
+                "reserved" => {
+                    self.skip_statement();
+                    Some(())
+                }
+                ";" => {
+                    self.position += 1;
+                    Some(())
+                }
+                _ => self.enum_value().map(|value| enumeration.values.push(value)),
+            };
+            if parsed.is_none() {
+                self.skip_statement();
+            }
+        }
+        self.error("expected `}`");
+        Some(enumeration)
+    }
+
+    fn enum_value(&mut self) -> Option<EnumValue> {
+        let first = self.peek()?.clone();
+        let name = self.ident("an enum value")?;
+        self.expect("=")?;
+        let number = self.integer()?;
+        self.field_options();
+        let end = self.peek().map_or(first.span, |token| token.span);
+        self.expect(";")?;
+        Some(EnumValue { name, doc: first.doc, number, span: first.span.to(end) })
+    }
+
+    fn service(&mut self) -> Option<Service> {
+        let keyword = self.bump()?;
+        let name = self.ident("a service name")?;
+        let mut service = Service { name, doc: keyword.doc.clone(), ..Default::default() };
+        self.expect("{")?;
+        while let Some(token) = self.peek() {
+            let parsed = match self.text(token) {
+                "}" => {
+                    service.span = keyword.span.to(token.span);
+                    self.position += 1;
+                    return Some(service);
+                }
+                "rpc" => self.rpc().map(|rpc| service.rpcs.push(rpc)),
+                "option" => self.option().map(|_| ()),
+                ";" => {
+                    self.position += 1;
+                    Some(())
+                }
+                other => {
+                    let message = format!("expected `rpc`, found `{}`", other);
+                    self.error(message);
+                    None
+                }
+            };
+            if parsed.is_none() {
+                self.skip_statement();
+            }
+        }
+        self.error("expected `}`");
+        Some(service)
+    }
+
+    fn rpc(&mut self) -> Option<Rpc> {
+        let keyword = self.bump()?;
+        let name = self.ident("an rpc name")?;
+        self.expect("(")?;
+        let client_streaming = self.stream();
+        let request = self.type_name()?;
+        self.expect(")")?;
+        self.expect("returns")?;
+        self.expect("(")?;
+        let server_streaming = self.stream();
+        let response = self.type_name()?;
+        let mut end = self.peek().map_or(keyword.span, |token| token.span);
+        self.expect(")")?;
+
+        // Either `;` or a body of options
+        if self.at("{") {
+            self.skip_group();
+            end = self.tokens.get(self.position - 1).map_or(end, |token| token.span);
+            self.eat(";");
+        } else {
+            self.expect(";")?;
+        }
+        Some(Rpc { name, doc: keyword.doc, request, response, client_streaming, server_streaming, span: keyword.span.to(end) })
+    }
+
+    /// `stream` before a request or response type, unless it names the type
+    fn stream(&mut self) -> bool {
+        let keyword = self.at("stream") && !self.at_offset(1, ")") && !self.at_offset(1, ".");
+        if keyword {
+            self.position += 1;
+        }
+        keyword
+    }
+}
+
+/// What a `.proto` symbol declares, kept in its `definition` metadata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Definition {
+    Message,
+    Enum,
+    Service,
+    Rpc,
+    Field,
+    Value,
+}
+
+impl Definition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Definition::Message => "message",
+            Definition::Enum => "enum",
+            Definition::Service => "service",
+            Definition::Rpc => "rpc",
+            Definition::Field => "field",
+            Definition::Value => "value",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "message" => Some(Definition::Message),
+            "enum" => Some(Definition::Enum),
+            "service" => Some(Definition::Service),
+            "rpc" => Some(Definition::Rpc),
+            "field" => Some(Definition::Field),
+            "value" => Some(Definition::Value),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `protoc` or one of its gRPC plugins generated a file
+pub fn is_generated(content: &str) -> bool {
+    content.lines()
+        .take(40)
+        .any(|line| GENERATED_MARKERS.iter().any(|marker| line.contains(marker)))
+}
+
+/// Path of the `.proto` file that generated code came from, relative to an
+/// import root
+///
+/// Read from the `source:` header most generators write, or else derived
+/// from the Go and Python file naming conventions.
+pub fn source(content: &str, file_path: &str) -> Option<String> {
+    let header = content.lines().take(40).find_map(|line| {
+        let index = line.to_ascii_lowercase().find("source: ")?;
+        let path = line[index + "source: ".len()..].trim().trim_end_matches(['"', ')', ';', ',']);
+        path.ends_with(".proto").then(|| path.to_string())
+    });
+    header.or_else(|| {
+        let name = std::path::Path::new(file_path).file_name()?.to_str()?;
+        GENERATED_SUFFIXES.iter()
+            .find_map(|suffix| name.strip_suffix(suffix))
+            .map(|stem| format!("{}.proto", stem))
+    })
+}
+
+/// Class wrapping the generated Java code of a file, `java_outer_classname`
+/// or the camel-cased file name, suffixed when a definition has that name
+pub fn java_outer_class(file_path: &str, file: &File) -> String {
+    if let Some(name) = file.option("java_outer_classname") {
+        return name.to_string();
+    }
+    let stem = std::path::Path::new(file_path).file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    let name = camel_case(stem);
+    let clash = file.messages.iter().map(|message| &message.name)
+        .chain(file.enums.iter().map(|enumeration| &enumeration.name))
+        .chain(file.services.iter().map(|service| &service.name))
+        .any(|definition| *definition == name);
+    if clash { format!("{}OuterClass", name) } else { name }
+}
+
+/// `hello_world` as `HelloWorld`, capitalizing after digits like `protoc`
+fn camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    let mut upper = true;
+    for c in name.chars() {
+        if c == '_' || c == '-' {
+            upper = true;
+            continue;
+        }
+        camel.push(if upper { c.to_ascii_uppercase() } else { c });
+        upper = c.is_ascii_digit();
+    }
+    camel
+}
+
+fn upper_first(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Name of the `.proto` definition, relative to its package, that a symbol
+/// of generated code stands for
+///
+/// `segments` are the names of the symbol's enclosing types followed by its
+/// own, e.g. `["GreeterClient", "SayHello"]`. `kind_of` looks up a name
+/// relative to the package, and `outer_class` is the Java class wrapping the
+/// file's messages.
+pub fn stub_definition(
+    language: &str,
+    segments: &[&str],
+    outer_class: Option<&str>,
+    kind_of: impl Fn(&str) -> Option<Definition>,
+) -> Option<String> {
+    let is = |name: &str, definition: Definition| kind_of(name) == Some(definition);
+    let service_of = |name: &str| -> Option<String> {
+        let bases = std::iter::once(name).chain(SERVICE_PREFIXES.iter().filter_map(|prefix| name.strip_prefix(prefix)));
+        bases
+            .flat_map(|base| std::iter::once(base).chain(SERVICE_SUFFIXES.iter().filter_map(move |suffix| base.strip_suffix(suffix))))
+            .map(upper_first)
+            .find(|candidate| is(candidate, Definition::Service))
+    };
+    // Java lower-cases the first letter of an rpc method
+    let rpc = |service: &str, method: &str| -> Option<String> {
+        [method.to_string(), upper_first(method)]
+            .into_iter()
+            .map(|name| format!("{}.{}", service, name))
+            .find(|name| is(name, Definition::Rpc))
+    };
+
+    let segments = match (outer_class, segments) {
+        (Some(outer), [first, rest @ ..]) if language == "java" && *first == outer && !rest.is_empty() => rest,
+        _ => segments,
+    };
+
+    // Messages and enums, which Go flattens by joining nested names with `_`
+    let path = segments.join(".");
+    let path = if language == "go" { path.replace('_', ".") } else { path };
+    if is(&path, Definition::Message) || is(&path, Definition::Enum) {
+        return Some(path);
+    }
+
+    // Java nests the stubs of a service in a `{Service}Grpc` class
+    if let Some((grpc, rest)) = segments.split_first().filter(|(first, _)| language == "java" && first.ends_with("Grpc")) {
+        let service = service_of(grpc)?;
+        return match rest {
+            [] => Some(service),
+            [stub] if *stub == "AsyncService" || service_of(stub).as_deref() == Some(service.as_str()) => Some(service),
+            [_, method] => rpc(&service, method),
+            _ => None,
+        };
+    }
+
+    // Go and Python name clients, servers and stubs after the service and
+    // their methods after its rpcs
+    match segments {
+        [owner] => service_of(owner),
+        [owner, method] => rpc(&service_of(owner)?, method),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proto() {
+        let file = parse(concat!(
+            "syntax = \"proto3\";\n",
+            "package shop.orders;\n\n",
+            "import public \"common.proto\";\n",
+            "option go_package = \"example.com/shop/orders\";\n\n",
+            "// An order\n",
+            "// placed by a customer\n",
+            "message Order {\n",
+            "  string id = 1; // trailing, not a doc\n",
+            "  map<string, Line> lines = 2 [deprecated = true];\n",
+            "  oneof payment { string card = 3; .shop.common.Money credit = 4; }\n",
+            "  enum Status { option allow_alias = true; PENDING = 0; PAID = 0x1; }\n",
+            "  reserved 5 to 9;\n",
+            "  message Line { repeated int32 skus = 1; }\n",
+            "  int32 = broken;\n",
+            "}\n\n",
+            "service Orders {\n",
+            "  rpc Get(stream GetRequest) returns (stream Order) { option deprecated = true; }\n",
+            "}\n",
+        ));
+
+        assert_eq!(file.syntax.as_deref(), Some("proto3"));
+        assert_eq!(file.package, "shop.orders");
+        assert_eq!((file.imports[0].path.as_str(), file.imports[0].public), ("common.proto", true));
+        assert_eq!(file.option("go_package"), Some("example.com/shop/orders"));
+
+        let order = &file.messages[0];
+        assert_eq!(order.doc.as_deref(), Some("An order\nplaced by a customer"));
+        assert_eq!((order.span.line, order.span.end_line), (8, 16));
+        let fields: Vec<(&str, &str, i64, Option<&str>)> = order.fields.iter()
+            .map(|field| (field.name.as_str(), field.type_name.as_str(), field.number, field.oneof.as_deref()))
+            .collect();
+        assert_eq!(fields, vec![
+            ("id", "string", 1, None),
+            ("lines", "Line", 2, None),
+            ("card", "string", 3, Some("payment")),
+            ("credit", ".shop.common.Money", 4, Some("payment")),
+        ]);
+        assert_eq!(order.fields[1].signature(), "map<string, Line> lines = 2");
+        assert_eq!(order.fields[0].doc, None);
+        let values: Vec<(&str, i64)> = order.enums[0].values.iter().map(|value| (value.name.as_str(), value.number)).collect();
+        assert_eq!(values, vec![("PENDING", 0), ("PAID", 1)]);
+        assert_eq!(order.messages[0].fields[0].signature(), "repeated int32 skus = 1");
+
+        // The broken field is reported and skipped
+        assert_eq!(file.errors.len(), 1);
+        assert_eq!(file.errors[0].span.line, 15);
+
+        let rpc = &file.services[0].rpcs[0];
+        assert_eq!(rpc.signature(), "rpc Get(stream GetRequest) returns (stream Order)");
+        assert_eq!(file.services[0].span.end_line, 20);
+    }
+
+    #[test]
+    fn test_stub_definition() {
+        let kind_of = |name: &str| match name {
+            "Greeter" => Some(Definition::Service),
+            "Greeter.SayHello" => Some(Definition::Rpc),
+            "HelloRequest" | "HelloRequest.Part" => Some(Definition::Message),
+            "Mood" => Some(Definition::Enum),
+            _ => None,
+        };
+        let link = |language: &str, segments: &[&str]| stub_definition(language, segments, Some("HelloProto"), kind_of);
+
+        assert_eq!(link("go", &["GreeterClient", "SayHello"]).as_deref(), Some("Greeter.SayHello"));
+        assert_eq!(link("go", &["greeterClient", "SayHello"]).as_deref(), Some("Greeter.SayHello"));
+        assert_eq!(link("go", &["UnimplementedGreeterServer"]).as_deref(), Some("Greeter"));
+        assert_eq!(link("go", &["HelloRequest_Part"]).as_deref(), Some("HelloRequest.Part"));
+        assert_eq!(link("go", &["HelloRequest", "GetName"]), None);
+        assert_eq!(link("python", &["GreeterServicer", "SayHello"]).as_deref(), Some("Greeter.SayHello"));
+        assert_eq!(link("python", &["GreeterStub", "__init__"]), None);
+        assert_eq!(link("java", &["GreeterGrpc", "GreeterBlockingStub", "sayHello"]).as_deref(), Some("Greeter.SayHello"));
+        assert_eq!(link("java", &["GreeterGrpc", "newBlockingStub"]), None);
+        assert_eq!(link("java", &["HelloProto", "HelloRequest", "Part"]).as_deref(), Some("HelloRequest.Part"));
+        assert_eq!(link("java", &["Mood"]).as_deref(), Some("Mood"));
+
+        assert_eq!(source("// source: api/hello.proto\n", "gen/hello.pb.go").as_deref(), Some("api/hello.proto"));
+        assert_eq!(source("import grpc\n", "gen/hello_pb2_grpc.py").as_deref(), Some("hello.proto"));
+        assert_eq!(camel_case("hello_world2x"), "HelloWorld2X");
+    }
+}
@@ -0,0 +1,375 @@
+// Python module and import resolution
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Symbol, SymbolKind, SymbolTable};
+
+/// Maximum number of imports and base classes followed to resolve one name
+const MAX_DEPTH: usize = 8;
+
+/// Name bound by an import statement, kept in the metadata of `Import` symbols
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportBinding {
+    /// Local name, or `*` for a wildcard import
+    pub alias: String,
+    /// Dotted module path, without the leading dots of a relative import
+    pub module: String,
+    /// Number of leading dots of a relative import
+    #[serde(default)]
+    pub level: usize,
+    /// Name imported from the module by `from ... import`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+}
+
+/// What a name or dotted expression refers to
+#[derive(Clone)]
+enum Target<'a> {
+    /// Candidate module paths without extension, most likely first
+    Module(Vec<PathBuf>),
+    Symbol(&'a Symbol),
+}
+
+/// Top-level view of one Python file
+struct ModuleScope<'a> {
+    file: String,
+    /// Symbols by qualified name, imports excluded
+    symbols: HashMap<&'a str, &'a Symbol>,
+    bindings: HashMap<String, ImportBinding>,
+    wildcards: Vec<ImportBinding>,
+}
+
+/// Resolves Python names against the symbol table the way the interpreter would
+///
+/// Bare names are looked up in the enclosing function scopes, then the module
+/// and its imports. Modules are located from the importing file: relative
+/// imports from its package, absolute ones from its directory and each parent,
+/// nearest first. `self`, `cls` and `super()` resolve against the enclosing
+/// class and its bases.
+pub struct Resolver<'t, 'a> {
+    table: &'t SymbolTable<'a>,
+    scopes: RefCell<HashMap<String, Rc<ModuleScope<'a>>>>,
+}
+
+impl<'t, 'a> Resolver<'t, 'a> {
+    pub fn new(table: &'t SymbolTable<'a>) -> Self {
+        Self {
+            table,
+            scopes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Symbol defined in `file` with the given qualified name
+    pub fn symbol(&self, file: &str, qualified_name: &str) -> Option<&'a Symbol> {
+        self.scope(file).symbols.get(qualified_name).copied()
+    }
+
+    /// Resolve a dotted expression such as `helpers.load` or `self.save`,
+    /// used inside the definition named by `context`
+    ///
+    /// `path` holds the segments of the expression, `super()` being one segment.
+    pub fn resolve(&self, file: &str, context: &[String], path: &[String]) -> Option<&'a Symbol> {
+        let scope = self.scope(file);
+        let (first, rest) = path.split_first()?;
+
+        let (mut target, rest) = match first.as_str() {
+            "self" | "cls" => (Target::Symbol(self.enclosing_class(&scope, context)?), rest),
+            "super()" => {
+                let class = self.enclosing_class(&scope, context)?;
+                let (name, rest) = rest.split_first()?;
+                let member = self.bases(class, 0)
+                    .into_iter()
+                    .find_map(|base| self.class_member(base, name, 1))?;
+                (Target::Symbol(member), rest)
+            }
+            name => (self.lookup_local(&scope, context, name)?, rest),
+        };
+
+        for segment in rest {
+            target = self.member(target, segment, 0)?;
+        }
+
+        match target {
+            Target::Symbol(symbol) => Some(symbol),
+            Target::Module(_) => None,
+        }
+    }
+
+    fn scope(&self, file: &str) -> Rc<ModuleScope<'a>> {
+        if let Some(scope) = self.scopes.borrow().get(file) {
+            return scope.clone();
+        }
+
+        let mut scope = ModuleScope {
+            file: file.to_string(),
+            symbols: HashMap::new(),
+            bindings: HashMap::new(),
+            wildcards: Vec::new(),
+        };
+        for &symbol in self.table.in_file(file) {
+            if symbol.kind != SymbolKind::Import {
+                scope.symbols.entry(symbol.qualified_name.as_str()).or_insert(symbol);
+                continue;
+            }
+            let bindings: Vec<ImportBinding> = serde_json::from_value(symbol.metadata["bindings"].clone())
+                .unwrap_or_default();
+            for binding in bindings {
+                if binding.alias == "*" {
+                    scope.wildcards.push(binding);
+                } else {
+                    scope.bindings.insert(binding.alias.clone(), binding);
+                }
+            }
+        }
+
+        let scope = Rc::new(scope);
+        self.scopes.borrow_mut().insert(file.to_string(), scope.clone());
+        scope
+    }
+
+    /// Look up a bare name from inside a definition: enclosing function scopes
+    /// first, class bodies being skipped as in Python, then the module
+    fn lookup_local(&self, scope: &ModuleScope<'a>, context: &[String], name: &str) -> Option<Target<'a>> {
+        for depth in (1..=context.len()).rev() {
+            let enclosing = context[..depth].join(".");
+            if scope.symbols.get(enclosing.as_str()).is_some_and(|s| s.kind == SymbolKind::Class) {
+                continue;
+            }
+            if let Some(&symbol) = scope.symbols.get(format!("{}.{}", enclosing, name).as_str()) {
+                return Some(Target::Symbol(symbol));
+            }
+        }
+
+        self.lookup_global(scope, name, 0)
+    }
+
+    /// Look up a name at module level: definitions, then imports, then wildcard imports
+    fn lookup_global(&self, scope: &ModuleScope<'a>, name: &str, depth: usize) -> Option<Target<'a>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        if let Some(&symbol) = scope.symbols.get(name) {
+            return Some(Target::Symbol(symbol));
+        }
+        if let Some(binding) = scope.bindings.get(name) {
+            let module = Target::Module(self.module_paths(&scope.file, binding));
+            return match &binding.symbol {
+                Some(symbol) => self.member(module, symbol, depth + 1),
+                None => Some(module),
+            };
+        }
+
+        scope.wildcards.iter().find_map(|binding| {
+            let module = self.module_paths(&scope.file, binding);
+            let file = self.module_file(&module)?;
+            self.lookup_global(&self.scope(&file), name, depth + 1)
+        })
+    }
+
+    /// Attribute `name` of a module or class
+    fn member(&self, target: Target<'a>, name: &str, depth: usize) -> Option<Target<'a>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        match target {
+            Target::Module(paths) => {
+                let defined = self.module_file(&paths)
+                    .and_then(|file| self.lookup_global(&self.scope(&file), name, depth + 1));
+                // Otherwise a submodule, which is only known to exist once something is used from it
+                defined.or_else(|| Some(Target::Module(paths.iter().map(|p| p.join(name)).collect())))
+            }
+            Target::Symbol(symbol) if symbol.kind == SymbolKind::Class => {
+                self.class_member(symbol, name, depth).map(Target::Symbol)
+            }
+            Target::Symbol(_) => None,
+        }
+    }
+
+    /// Member of a class or, following the bases depth first, of its ancestors
+    fn class_member(&self, class: &'a Symbol, name: &str, depth: usize) -> Option<&'a Symbol> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let scope = self.scope(&class.location.file);
+        let qualified_name = format!("{}.{}", class.qualified_name, name);
+        if let Some(&member) = scope.symbols.get(qualified_name.as_str()) {
+            return Some(member);
+        }
+
+        self.bases(class, depth)
+            .into_iter()
+            .find_map(|base| self.class_member(base, name, depth + 1))
+    }
+
+    /// Base classes found in the index, in declaration order
+    fn bases(&self, class: &'a Symbol, depth: usize) -> Vec<&'a Symbol> {
+        class_bases(class)
+            .filter_map(|base| self.resolve_base_at(class, base, depth))
+            .collect()
+    }
+
+    /// Resolve one base class expression of `class`, such as `models.Base`
+    pub fn resolve_base(&self, class: &'a Symbol, base: &str) -> Option<&'a Symbol> {
+        self.resolve_base_at(class, base, 0)
+    }
+
+    fn resolve_base_at(&self, class: &'a Symbol, base: &str, depth: usize) -> Option<&'a Symbol> {
+        let scope = self.scope(&class.location.file);
+        // Drop type arguments, as in `Generic[T]`
+        let base = base.split('[').next().unwrap_or(base).trim();
+        let (first, rest) = base.split_once('.').unwrap_or((base, ""));
+
+        // Classes nested in a class body see their siblings
+        let sibling = class.qualified_name.rsplit_once('.')
+            .and_then(|(parent, _)| scope.symbols.get(format!("{}.{}", parent, first).as_str()))
+            .map(|&symbol| Target::Symbol(symbol));
+        let mut target = sibling.or_else(|| self.lookup_global(&scope, first, depth + 1))?;
+        for segment in rest.split('.').filter(|s| !s.is_empty()) {
+            target = self.member(target, segment, depth + 1)?;
+        }
+
+        match target {
+            Target::Symbol(symbol) if symbol.kind == SymbolKind::Class && !std::ptr::eq(symbol, class) => Some(symbol),
+            _ => None,
+        }
+    }
+
+    fn enclosing_class(&self, scope: &ModuleScope<'a>, context: &[String]) -> Option<&'a Symbol> {
+        (1..=context.len()).rev()
+            .filter_map(|depth| scope.symbols.get(context[..depth].join(".").as_str()).copied())
+            .find(|symbol| symbol.kind == SymbolKind::Class)
+    }
+
+    /// Symbols an `Import` symbol makes its file depend on: the names a
+    /// `from` import binds, or a symbol standing for each module imported whole
+    pub fn import_targets(&self, import: &Symbol) -> Vec<&'a Symbol> {
+        let file = &import.location.file;
+        let bindings: Vec<ImportBinding> = serde_json::from_value(import.metadata["bindings"].clone())
+            .unwrap_or_default();
+
+        let mut targets: Vec<&'a Symbol> = Vec::new();
+        for binding in bindings {
+            let target = match &binding.symbol {
+                Some(symbol) => self.member(Target::Module(self.module_paths(file, &binding)), symbol, 1),
+                // `import a.b` binds `a` but depends on `a.b`
+                None => {
+                    let module = import.qualified_name.trim_start_matches('.').to_string();
+                    Some(Target::Module(self.module_paths(file, &ImportBinding { module, ..binding })))
+                }
+            };
+            let symbol = match target {
+                Some(Target::Symbol(symbol)) => Some(symbol),
+                Some(Target::Module(paths)) => self.module_file(&paths).and_then(|module| {
+                    self.table.in_file(&module).iter().find(|symbol| symbol.kind != SymbolKind::Import).copied()
+                }),
+                None => None,
+            };
+            if let Some(symbol) = symbol.filter(|symbol| symbol.location.file != *file && !targets.iter().any(|t| t.id == symbol.id)) {
+                targets.push(symbol);
+            }
+        }
+        targets
+    }
+
+    /// Candidate paths of an imported module, without extension
+    fn module_paths(&self, file: &str, binding: &ImportBinding) -> Vec<PathBuf> {
+        let directory = Path::new(file).parent().unwrap_or(Path::new(""));
+        let roots: Vec<&Path> = if binding.level > 0 {
+            directory.ancestors().nth(binding.level - 1).into_iter().collect()
+        } else {
+            directory.ancestors().collect()
+        };
+
+        roots.into_iter()
+            .map(|root| {
+                let mut path = root.to_path_buf();
+                path.extend(binding.module.split('.').filter(|s| !s.is_empty()));
+                path
+            })
+            .collect()
+    }
+
+    /// Indexed file of the first candidate that is a module or a package
+    fn module_file(&self, paths: &[PathBuf]) -> Option<String> {
+        paths.iter()
+            .flat_map(|path| {
+                let module = path.file_name().map(|name| path.with_file_name(format!("{}.py", name.to_string_lossy())));
+                module.into_iter().chain([path.join("__init__.py")])
+            })
+            .map(|file| file.to_string_lossy().to_string())
+            .find(|file| self.table.has_file(file))
+    }
+}
+
+/// Base class expressions recorded on a class symbol
+pub fn class_bases(class: &Symbol) -> impl Iterator<Item = &str> {
+    class.metadata["bases"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|base| base.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Parser;
+    use crate::indexer::parser::PythonParser;
+
+    fn parse(files: &[(&str, &str)]) -> Vec<Symbol> {
+        files.iter()
+            .flat_map(|(path, source)| PythonParser::new().parse(source, path).unwrap().0)
+            .collect()
+    }
+
+    fn resolve(table: &SymbolTable, file: &str, context: &[&str], path: &str) -> Option<String> {
+        let context: Vec<String> = context.iter().map(|s| s.to_string()).collect();
+        let path: Vec<String> = path.split('.').map(str::to_string).collect();
+        Resolver::new(table).resolve(file, &context, &path).map(|s| s.id.clone())
+    }
+
+    #[test]
+    fn test_resolves_imports_and_packages() {
+        let symbols = parse(&[
+            ("proj/app/main.py", "import app.util as u\nfrom .models import User as Account\nfrom app.models import *\nimport app.util\n"),
+            ("proj/app/util.py", "def helper():\n    pass\n"),
+            ("proj/app/models.py", "class User:\n    pass\n\ndef load():\n    pass\n"),
+            ("proj/app/__init__.py", "from .util import helper\n"),
+            ("proj/other/util.py", "def helper():\n    pass\n"),
+        ]);
+        let table = SymbolTable::new(&symbols);
+        let main = "proj/app/main.py";
+
+        assert_eq!(resolve(&table, main, &["run"], "u.helper").as_deref(), Some("proj/app/util.py:helper"));
+        assert_eq!(resolve(&table, main, &["run"], "app.util.helper").as_deref(), Some("proj/app/util.py:helper"));
+        assert_eq!(resolve(&table, main, &["run"], "Account").as_deref(), Some("proj/app/models.py:User"));
+        assert_eq!(resolve(&table, main, &["run"], "load").as_deref(), Some("proj/app/models.py:load"));
+        // Re-exported by the package
+        assert_eq!(resolve(&table, main, &["run"], "app.helper").as_deref(), Some("proj/app/util.py:helper"));
+        // Never guessed from another module
+        assert_eq!(resolve(&table, main, &["run"], "helper"), None);
+        assert_eq!(resolve(&table, main, &["run"], "u.missing"), None);
+    }
+
+    #[test]
+    fn test_resolves_self_and_bases() {
+        let symbols = parse(&[
+            ("base.py", "class Base:\n    def save(self):\n        pass\n\n    def close(self):\n        pass\n"),
+            ("models.py", "from base import Base\n\nclass Model(Base):\n    def save(self):\n        super().save()\n        self.close()\n        self.save()\n\nclass Other:\n    def save(self):\n        pass\n"),
+        ]);
+        let table = SymbolTable::new(&symbols);
+        let context = ["Model", "save"];
+
+        assert_eq!(resolve(&table, "models.py", &context, "self.save").as_deref(), Some("models.py:Model.save"));
+        assert_eq!(resolve(&table, "models.py", &context, "self.close").as_deref(), Some("base.py:Base.close"));
+        assert_eq!(resolve(&table, "models.py", &context, "super().save").as_deref(), Some("base.py:Base.save"));
+        assert_eq!(resolve(&table, "models.py", &context, "self.missing"), None);
+        assert_eq!(resolve(&table, "models.py", &[], "self.save"), None);
+    }
+}
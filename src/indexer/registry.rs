@@ -0,0 +1,228 @@
+// Registry of language parsers
+
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+
+use crate::config::Config;
+use crate::index::Parser;
+
+use super::c;
+use super::parser;
+
+/// Language parsers keyed by language name
+pub struct ParserRegistry {
+    parsers: BTreeMap<String, Box<dyn Parser + Send + Sync>>,
+}
+
+impl ParserRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self { parsers: BTreeMap::new() }
+    }
+
+    /// Create a registry with every built-in parser
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register("python", parser::PythonParser::new());
+        registry.register("rust", parser::RustParser::new());
+        registry.register("go", parser::GoParser::new());
+        registry.register("java", parser::JavaParser::new());
+        registry.register("kotlin", parser::GrammarParser::kotlin());
+        registry.register("scala", parser::GrammarParser::scala());
+        registry.register("typescript", parser::TypeScriptParser::new());
+        registry.register("javascript", parser::TypeScriptParser::javascript());
+        registry.register("c", parser::CParser::new());
+        registry.register("cpp", parser::CParser::cpp());
+        registry.register("csharp", parser::CSharpParser::new());
+        registry.register("ruby", parser::RubyParser::new());
+        registry.register("protobuf", parser::ProtobufParser::new());
+        registry.register("sql", parser::SqlParser::new());
+        registry.register("shell", parser::ShellParser::new());
+        registry.register("makefile", parser::MakefileParser::new());
+        registry.register("dockerfile", parser::DockerfileParser::new());
+        registry.register("intent", parser::IntentParser::new());
+        registry
+    }
+
+    /// Create a registry with the built-in parsers set up by the
+    /// configuration, C include resolution and SQL in string literals, and a
+    /// parser for each grammar in `[[languages.grammars]]`
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let mut registry = Self::builtin();
+        let options = c::Options {
+            include_paths: config.c.include_paths.iter().map(std::path::PathBuf::from).collect(),
+            defines: config.c.defines.clone(),
+        };
+        registry.register("c", parser::CParser::new().with_options(options.clone()));
+        registry.register("cpp", parser::CParser::cpp().with_options(options));
+        let scan_literals = config.sql.scan_literals;
+        registry.register("python", parser::PythonParser::new().with_sql_literals(scan_literals));
+        registry.register("go", parser::GoParser::new().with_sql_literals(scan_literals));
+        registry.register("java", parser::JavaParser::new().with_sql_literals(scan_literals));
+
+        // A file goes to the first parser that claims it, so a shared
+        // extension would leave one of the two languages unindexed
+        let grammars = &config.languages.grammars;
+        for (index, grammar) in grammars.iter().enumerate() {
+            for extension in grammar.extensions.iter().map(|extension| extension.trim_start_matches('.')) {
+                let owner = registry.parsers.iter()
+                    .find(|(_, parser)| parser.extensions().contains(&extension))
+                    .map(|(language, _)| language.as_str())
+                    .or_else(|| grammars[..index].iter()
+                        .find(|other| other.extensions.iter().any(|known| known.trim_start_matches('.') == extension))
+                        .map(|other| other.name.as_str()));
+                if let Some(owner) = owner {
+                    anyhow::bail!("Grammar {} claims .{} files, which {} already parses", grammar.name, extension, owner);
+                }
+            }
+        }
+
+        for grammar in grammars {
+            let parser = parser::GrammarParser::load(grammar)
+                .with_context(|| format!("Failed to load the {} grammar", grammar.name))?;
+            registry.register(&grammar.name, parser);
+        }
+        Ok(registry)
+    }
+
+    /// Register a parser for a language, replacing any previous one
+    pub fn register(&mut self, language: &str, parser: impl Parser + Send + Sync + 'static) {
+        self.parsers.insert(language.to_string(), Box::new(parser));
+    }
+
+    /// Clear what every parser cached from outside the files it parsed
+    pub fn clear_caches(&self) {
+        self.parsers.values().for_each(|parser| parser.clear_caches());
+    }
+
+    /// Names of the registered languages
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.parsers.keys().map(String::as_str)
+    }
+
+    /// Language of the parser that handles a file
+    pub fn language_for(&self, file_path: &str) -> Option<&str> {
+        self.parsers.iter()
+            .find(|(_, p)| p.can_parse(file_path))
+            .map(|(language, _)| language.as_str())
+    }
+
+    /// Parser that handles a file
+    pub fn parser_for(&self, file_path: &str) -> Option<&(dyn Parser + Send + Sync)> {
+        self.parsers.values()
+            .find(|p| p.can_parse(file_path))
+            .map(|p| p.as_ref())
+    }
+}
+
+impl Default for ParserRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{GrammarConfig, BUILTIN_LANGUAGES};
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    fn grammar(name: &str, extensions: &[&str], library: &str, queries: &Path) -> GrammarConfig {
+        GrammarConfig {
+            name: name.to_string(),
+            extensions: extensions.iter().map(|extension| extension.to_string()).collect(),
+            library: library.to_string(),
+            symbol: Some("tree_sitter_bash".to_string()),
+            queries: vec![queries.to_string_lossy().to_string()],
+            resolve_in: vec![],
+        }
+    }
+
+    #[test]
+    fn test_builtin_languages_match_the_parsers() {
+        let registry = ParserRegistry::builtin();
+        assert_eq!(registry.languages().collect::<Vec<_>>(), BUILTIN_LANGUAGES);
+        for language in registry.languages() {
+            let parser = &registry.parsers[language];
+            assert!(parser.extensions().iter().all(|extension| parser.can_parse(&format!("src/file.{}", extension))), "{}", language);
+        }
+    }
+
+    #[test]
+    fn test_grammars_cannot_claim_parsed_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        let queries = dir.path().join("tags.scm");
+        let mut config = Config::default();
+
+        // Rejected before the library is loaded
+        config.languages.grammars = vec![grammar("lua", &["lua", ".h"], "missing.so", &queries)];
+        let error = ParserRegistry::from_config(&config).err().unwrap();
+        assert_eq!(error.to_string(), "Grammar lua claims .h files, which c already parses");
+
+        config.languages.grammars = vec![grammar("lua", &["lua"], "missing.so", &queries), grammar("luau", &["luau", "lua"], "missing.so", &queries)];
+        let error = ParserRegistry::from_config(&config).err().unwrap();
+        assert_eq!(error.to_string(), "Grammar luau claims .lua files, which lua already parses");
+
+        config.languages.grammars = vec![grammar("lua", &["lua"], "missing.so", &queries), grammar("luau", &["luau"], "missing.so", &queries)];
+        let error = ParserRegistry::from_config(&config).err().unwrap();
+        assert_eq!(error.to_string(), "Failed to load the lua grammar");
+    }
+
+    /// Shared library of tree-sitter-bash built from its sources in the cargo
+    /// registry, or `None` without a C compiler or the sources at hand
+    fn compile_bash_grammar(dir: &Path) -> Option<PathBuf> {
+        let output = Command::new(env!("CARGO"))
+            .args(["metadata", "--format-version", "1", "--offline"])
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output()
+            .ok()?;
+        let metadata: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+        let package = metadata["packages"].as_array()?
+            .iter()
+            .find(|package| package["name"] == "tree-sitter-bash")?;
+        let source = Path::new(package["manifest_path"].as_str()?).parent()?.join("src");
+
+        let library = dir.join(format!("bash.{}", std::env::consts::DLL_EXTENSION));
+        let status = Command::new("cc")
+            .args(["-shared", "-fPIC", "-I"])
+            .arg(&source)
+            .arg(source.join("parser.c"))
+            .arg(source.join("scanner.c"))
+            .arg("-o")
+            .arg(&library)
+            .status()
+            .ok()?;
+        status.success().then_some(library)
+    }
+
+    #[test]
+    fn test_from_config_loads_a_shared_library_grammar() {
+        let dir = tempfile::tempdir().unwrap();
+        let Some(library) = compile_bash_grammar(dir.path()) else {
+            eprintln!("skipped: no C compiler or tree-sitter-bash sources");
+            return;
+        };
+
+        let queries = dir.path().join("tags.scm");
+        std::fs::write(&queries, concat!(
+            "(function_definition name: (word) @name) @definition.function\n",
+            "(command name: (command_name (word) @name)) @reference.call\n",
+        )).unwrap();
+
+        let mut config = Config::default();
+        config.languages.grammars = vec![grammar("bashlike", &["bashlike"], &library.to_string_lossy(), &queries)];
+        config.validate().unwrap();
+        let registry = ParserRegistry::from_config(&config).unwrap();
+        assert_eq!(registry.language_for("deploy.bashlike"), Some("bashlike"));
+
+        let (symbols, relationships) = registry.parser_for("deploy.bashlike").unwrap()
+            .parse("build() {\n  echo building\n}\n\ndeploy() {\n  build\n}\n", "deploy.bashlike")
+            .unwrap();
+        // The file's module, then each function
+        let names: Vec<&str> = symbols.iter().map(|symbol| symbol.qualified_name.as_str()).collect();
+        assert_eq!(names, vec!["deploy.bashlike", "build", "deploy"]);
+        assert!(relationships.iter().any(|r| r.from_id == "deploy.bashlike:deploy" && r.to_id == "deploy.bashlike:build"), "{:?}", relationships);
+    }
+}
@@ -0,0 +1,250 @@
+// Ruby constant lookup, mixins and method resolution along ancestor chains
+
+use std::collections::{HashMap, HashSet};
+
+use crate::index::{Symbol, SymbolKind, SymbolTable};
+
+/// Maximum nesting of superclasses and mixins followed to build one ancestor chain
+const MAX_DEPTH: usize = 16;
+
+/// Mixin methods called in a class or module body, recorded under the same key in its metadata
+pub const MIXINS: &[&str] = &["include", "extend", "prepend"];
+
+/// Constants a name written inside `nesting` may refer to, most likely first,
+/// `nesting` being the enclosing classes and modules, innermost last
+///
+/// `::Name` is looked up at the top level only; otherwise each enclosing
+/// scope is tried from the inside out, then the top level.
+pub fn constant_candidates(name: &str, nesting: &[String]) -> Vec<String> {
+    let name: String = name.split_whitespace().collect();
+    if let Some(absolute) = name.strip_prefix("::") {
+        return vec![absolute.to_string()];
+    }
+    if name.is_empty() {
+        return Vec::new();
+    }
+
+    let mut candidates: Vec<String> = nesting.iter()
+        .rev()
+        .map(|scope| format!("{}::{}", scope, name))
+        .collect();
+    candidates.push(name);
+
+    let mut seen = HashSet::new();
+    candidates.retain(|candidate| seen.insert(candidate.clone()));
+    candidates
+}
+
+/// Written constant with the qualified names it may refer to, as kept in metadata
+pub fn written_constants(value: &serde_json::Value) -> Vec<(String, Vec<String>)> {
+    let one = |value: &serde_json::Value| (
+        value["name"].as_str().unwrap_or_default().to_string(),
+        serde_json::from_value::<Vec<String>>(value["candidates"].clone()).unwrap_or_default(),
+    );
+    match value {
+        serde_json::Value::Array(values) => values.iter().map(one).collect(),
+        serde_json::Value::Object(_) => vec![one(value)],
+        _ => Vec::new(),
+    }
+}
+
+/// Class or module whose methods are looked up: instance methods, or the
+/// singleton methods of a class object
+#[derive(Debug, Clone, Copy)]
+pub struct Owner<'a> {
+    pub symbol: &'a Symbol,
+    pub singleton: bool,
+}
+
+/// Resolves Ruby constants and methods against the symbol table
+///
+/// Classes and modules are open: every definition of one contributes its
+/// methods, superclass and mixins. Instance methods are looked up along the
+/// ancestor chain Ruby builds, with prepended modules before the class and
+/// included modules after it, then the superclass; singleton methods along
+/// the extended modules and the singleton methods of the class and its
+/// superclasses.
+pub struct Resolver<'t, 'a> {
+    table: &'t SymbolTable<'a>,
+    /// Declarations of this file by start position
+    declared: HashMap<(u32, u32), &'a Symbol>,
+}
+
+impl<'t, 'a> Resolver<'t, 'a> {
+    pub fn new(table: &'t SymbolTable<'a>, file: &str) -> Self {
+        let mut declared = HashMap::new();
+        for &symbol in table.in_file(file) {
+            declared.entry((symbol.location.line, symbol.location.column)).or_insert(symbol);
+        }
+        Self { table, declared }
+    }
+
+    /// Symbol declared in this file at the given position
+    pub fn declared_at(&self, line: u32, column: u32) -> Option<&'a Symbol> {
+        self.declared.get(&(line, column)).copied()
+    }
+
+    /// Every definition of the Ruby class or module with the given qualified name
+    pub fn parts(&self, qualified_name: &str) -> Vec<&'a Symbol> {
+        let name = qualified_name.rsplit("::").next().unwrap_or(qualified_name);
+        self.table.named(name)
+            .iter()
+            .filter(|symbol| symbol.qualified_name == qualified_name && symbol.language == "ruby" && is_namespace(symbol))
+            .copied()
+            .collect()
+    }
+
+    /// Ruby class or module with the given qualified name
+    pub fn find(&self, qualified_name: &str) -> Option<&'a Symbol> {
+        self.parts(qualified_name).into_iter().next()
+    }
+
+    /// First candidate that is an indexed class or module
+    pub fn first(&self, candidates: &[String]) -> Option<&'a Symbol> {
+        candidates.iter().find_map(|candidate| self.find(candidate))
+    }
+
+    /// Constant written inside `nesting`
+    pub fn resolve(&self, name: &str, nesting: &[String]) -> Option<&'a Symbol> {
+        self.first(&constant_candidates(name, nesting))
+    }
+
+    /// Superclass of a class, from whichever of its definitions names one
+    pub fn superclass(&self, ty: &Symbol) -> Option<&'a Symbol> {
+        self.parts(&ty.qualified_name)
+            .into_iter()
+            .flat_map(|part| written_constants(&part.metadata["superclass"]))
+            .find_map(|(_, candidates)| self.first(&candidates))
+    }
+
+    /// Modules mixed into a class or module with `include`, `extend` or
+    /// `prepend`, the last one mixed in first, as Ruby searches them
+    pub fn mixins(&self, ty: &Symbol, mixin: &str) -> Vec<&'a Symbol> {
+        let mut modules: Vec<&'a Symbol> = self.parts(&ty.qualified_name)
+            .into_iter()
+            .flat_map(|part| written_constants(&part.metadata[mixin]))
+            .filter_map(|(_, candidates)| self.first(&candidates))
+            .collect();
+        modules.reverse();
+        modules
+    }
+
+    /// Instance method lookup order of a class or module
+    pub fn ancestors(&self, ty: &'a Symbol) -> Vec<&'a Symbol> {
+        let mut ancestors = Vec::new();
+        let mut seen = HashSet::new();
+        self.collect_ancestors(ty, &mut ancestors, &mut seen, 0);
+        ancestors
+    }
+
+    fn collect_ancestors(&self, ty: &'a Symbol, ancestors: &mut Vec<&'a Symbol>, seen: &mut HashSet<&'a str>, depth: usize) {
+        if depth > MAX_DEPTH || !seen.insert(ty.qualified_name.as_str()) {
+            return;
+        }
+        for module in self.mixins(ty, "prepend") {
+            self.collect_ancestors(module, ancestors, seen, depth + 1);
+        }
+        ancestors.push(ty);
+        for module in self.mixins(ty, "include") {
+            self.collect_ancestors(module, ancestors, seen, depth + 1);
+        }
+        if let Some(superclass) = self.superclass(ty) {
+            self.collect_ancestors(superclass, ancestors, seen, depth + 1);
+        }
+    }
+
+    /// Method lookup order of a receiver: the ancestors of a class or module
+    /// for its instances, or for the class object its extended modules and
+    /// singleton methods, then those of its superclasses
+    pub fn lookup_order(&self, owner: Owner<'a>) -> Vec<Owner<'a>> {
+        if !owner.singleton {
+            return self.ancestors(owner.symbol).into_iter().map(|symbol| Owner { symbol, singleton: false }).collect();
+        }
+
+        let mut order = Vec::new();
+        let mut seen = HashSet::new();
+        let mut current = Some(owner.symbol);
+        while let Some(class) = current.filter(|class| seen.insert(class.qualified_name.as_str()) && seen.len() <= MAX_DEPTH) {
+            order.push(Owner { symbol: class, singleton: true });
+            for module in self.mixins(class, "extend") {
+                order.extend(self.ancestors(module).into_iter().map(|symbol| Owner { symbol, singleton: false }));
+            }
+            current = self.superclass(class);
+        }
+        order
+    }
+
+    /// Method or attribute defined directly in one owner
+    fn own_method(&self, owner: Owner<'a>, name: &str) -> Option<&'a Symbol> {
+        self.table.children(&owner.symbol.qualified_name)
+            .iter()
+            .find(|member| is_singleton(member) == owner.singleton && responds_to(member, name))
+            .copied()
+    }
+
+    /// Method a call named `name` on a receiver of `owner` runs
+    pub fn method(&self, owner: Owner<'a>, name: &str) -> Option<&'a Symbol> {
+        self.lookup_order(owner).into_iter().find_map(|owner| self.own_method(owner, name))
+    }
+
+    /// Method `super` calls from the method `name` defined in `owner`, looked
+    /// up from `receiver`, the class the call runs in
+    pub fn super_method(&self, receiver: Owner<'a>, owner: &Symbol, name: &str) -> Option<&'a Symbol> {
+        self.lookup_order(receiver)
+            .into_iter()
+            .skip_while(|ancestor| ancestor.symbol.qualified_name != owner.qualified_name)
+            .skip(1)
+            .find_map(|ancestor| self.own_method(ancestor, name))
+    }
+
+    /// Method defined at the top level of any Ruby file
+    pub fn top_level(&self, name: &str) -> Option<&'a Symbol> {
+        self.table.named(name)
+            .iter()
+            .find(|symbol| symbol.language == "ruby" && symbol.kind == SymbolKind::Function && symbol.qualified_name == name)
+            .copied()
+    }
+}
+
+pub fn is_namespace(symbol: &Symbol) -> bool {
+    matches!(symbol.kind, SymbolKind::Class | SymbolKind::Module)
+}
+
+pub fn is_singleton(symbol: &Symbol) -> bool {
+    symbol.metadata["singleton"].as_bool().unwrap_or(false)
+}
+
+/// Whether a method or generated attribute answers a call named `name`,
+/// an attribute writer being called as `name=`
+fn responds_to(member: &Symbol, name: &str) -> bool {
+    match member.kind {
+        SymbolKind::Method | SymbolKind::Function => member.name == name,
+        SymbolKind::Field => {
+            let reader = member.metadata["reader"].as_bool().unwrap_or(false);
+            let writer = member.metadata["writer"].as_bool().unwrap_or(false);
+            (reader && member.name == name) || (writer && name.strip_suffix('=') == Some(member.name.as_str()))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_constant_candidates() {
+        let nesting = strings(&["Shop", "Shop::Billing"]);
+
+        assert_eq!(constant_candidates("Order", &nesting), strings(&["Shop::Billing::Order", "Shop::Order", "Order"]));
+        assert_eq!(constant_candidates("Base::Record", &nesting), strings(&["Shop::Billing::Base::Record", "Shop::Base::Record", "Base::Record"]));
+        assert_eq!(constant_candidates("::Order", &nesting), strings(&["Order"]));
+        assert_eq!(written_constants(&serde_json::json!([{ "name": "Auditable", "candidates": ["Shop::Auditable"] }])), vec![
+            ("Auditable".to_string(), strings(&["Shop::Auditable"])),
+        ]);
+    }
+}
@@ -0,0 +1,344 @@
+// Rust module paths and `use` resolution
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Symbol, SymbolKind, SymbolTable};
+
+/// Name bound by a `use` declaration, kept in the metadata of `Import` symbols
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UseBinding {
+    /// Local name, or `*` for a glob import
+    pub alias: String,
+    /// Path with `self` and `super` resolved, e.g. `crate::index::db::IndexDatabase`
+    pub path: String,
+}
+
+/// Directory of the crate a file belongs to and the file's module path
+///
+/// Files under a `src/` directory follow the module layout, so
+/// `src/index/db.rs` and `src/index/db/mod.rs` are `crate::index::db` and
+/// `src/lib.rs` is `crate`. Any other file, such as an integration test, is
+/// the root of its own crate.
+pub fn module_path(file: &str) -> (PathBuf, Vec<String>) {
+    let path = Path::new(file);
+    let components: Vec<Component> = path.components().collect();
+    let src = components.iter().rposition(|c| c.as_os_str() == "src");
+
+    let mut module = vec!["crate".to_string()];
+    let Some(src) = src else {
+        return (path.parent().unwrap_or(Path::new("")).to_path_buf(), module);
+    };
+
+    let root: PathBuf = components[..=src].iter().collect();
+    let rest = &components[src + 1..];
+    for (i, component) in rest.iter().enumerate() {
+        let name = component.as_os_str().to_string_lossy();
+        if i + 1 < rest.len() {
+            module.push(name.to_string());
+            continue;
+        }
+        let stem = name.strip_suffix(".rs").unwrap_or(&name);
+        let is_root = i == 0 && (stem == "lib" || stem == "main");
+        if !is_root && stem != "mod" {
+            module.push(stem.to_string());
+        }
+    }
+
+    (root, module)
+}
+
+/// Segments of a path expression without generic arguments, so that
+/// `Vec::<u8>::new` becomes `Vec`, `new`
+///
+/// Returns `None` for qualified paths such as `<T as Trait>::f`.
+pub fn path_segments(text: &str) -> Option<Vec<String>> {
+    if text.trim_start().starts_with('<') {
+        return None;
+    }
+
+    // A reference type such as `&'a mut Foo` names the path after its `&`, lifetime and `mut`
+    let mut text = text.trim_start();
+    while let Some(rest) = text.strip_prefix('&') {
+        text = rest.trim_start();
+        if let Some(lifetime) = text.strip_prefix('\'') {
+            text = lifetime.trim_start_matches(|c: char| c.is_alphanumeric() || c == '_').trim_start();
+        }
+        if let Some(rest) = text.strip_prefix("mut").filter(|rest| rest.starts_with(char::is_whitespace)) {
+            text = rest.trim_start();
+        }
+    }
+
+    let mut stripped = String::with_capacity(text.len());
+    let mut depth = 0usize;
+    for c in text.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            c if depth == 0 && !c.is_whitespace() => stripped.push(c),
+            _ => {}
+        }
+    }
+
+    let segments: Vec<String> = stripped.split("::")
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!segments.is_empty()).then_some(segments)
+}
+
+/// Bindings introduced by the argument of a `use` declaration
+pub fn use_bindings(node: tree_sitter::Node, content: &str) -> Vec<UseBinding> {
+    let mut bindings = Vec::new();
+    collect_use(node, content, &[], &mut bindings);
+    bindings
+}
+
+fn collect_use(node: tree_sitter::Node, content: &str, prefix: &[String], bindings: &mut Vec<UseBinding>) {
+    let text = |node: tree_sitter::Node| content[node.byte_range()].to_string();
+    let join = |path: Option<tree_sitter::Node>| -> Vec<String> {
+        let mut segments = prefix.to_vec();
+        segments.extend(path.and_then(|p| path_segments(&text(p))).unwrap_or_default());
+        segments
+    };
+
+    match node.kind() {
+        "use_as_clause" => {
+            let Some(alias) = node.child_by_field_name("alias") else { return };
+            bindings.push(UseBinding { alias: text(alias), path: join(node.child_by_field_name("path")).join("::") });
+        }
+        "scoped_use_list" => {
+            let prefix = join(node.child_by_field_name("path"));
+            if let Some(list) = node.child_by_field_name("list") {
+                collect_use(list, content, &prefix, bindings);
+            }
+        }
+        "use_list" => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                collect_use(child, content, prefix, bindings);
+            }
+        }
+        "use_wildcard" => {
+            let path = join(node.named_child(0));
+            bindings.push(UseBinding { alias: "*".to_string(), path: path.join("::") });
+        }
+        "identifier" | "scoped_identifier" | "crate" | "self" | "super" => {
+            let mut path = join(Some(node));
+            // `use a::b::{self}` binds `b`
+            if path.last().is_some_and(|s| s == "self") && path.len() > 1 {
+                path.pop();
+            }
+            if let Some(alias) = path.last().cloned() {
+                bindings.push(UseBinding { alias, path: path.join("::") });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Resolve the `crate`, `self` and `super` prefixes of a path written in
+/// `module`; other paths are returned unchanged
+pub fn absolute_path(module: &[String], path: &[String]) -> Option<Vec<String>> {
+    let head = path.first()?;
+    match head.as_str() {
+        "self" => Some(module.iter().chain(&path[1..]).cloned().collect()),
+        "super" => {
+            let supers = path.iter().take_while(|s| *s == "super").count();
+            let parent = module.len().checked_sub(supers).filter(|&len| len > 0)?;
+            Some(module[..parent].iter().chain(&path[supers..]).cloned().collect())
+        }
+        _ => Some(path.to_vec()),
+    }
+}
+
+/// `use` bindings of a file, by alias, plus its glob imports
+#[derive(Debug, Default)]
+pub struct UseScope {
+    bindings: HashMap<String, Vec<String>>,
+    globs: Vec<Vec<String>>,
+}
+
+impl UseScope {
+    pub fn new(bindings: impl IntoIterator<Item = UseBinding>) -> Self {
+        let mut scope = Self::default();
+        for binding in bindings {
+            let Some(path) = path_segments(&binding.path) else { continue };
+            if binding.alias == "*" {
+                scope.globs.push(path);
+            } else {
+                scope.bindings.insert(binding.alias, path);
+            }
+        }
+        scope
+    }
+
+    /// Bindings recorded on the file's `Import` symbols
+    pub fn from_symbols<'s>(symbols: impl IntoIterator<Item = &'s Symbol>) -> Self {
+        Self::new(symbols.into_iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Import)
+            .flat_map(|symbol| serde_json::from_value::<Vec<UseBinding>>(symbol.metadata["bindings"].clone()).unwrap_or_default()))
+    }
+
+    /// Absolute paths a path written in `module` may refer to, most likely first
+    ///
+    /// `self_type` is the type of the enclosing impl or trait, for `Self`.
+    pub fn candidates(&self, module: &[String], path: &[String], self_type: Option<&[String]>) -> Vec<Vec<String>> {
+        let Some((head, rest)) = path.split_first() else {
+            return Vec::new();
+        };
+        let with = |base: &[String], rest: &[String]| -> Vec<String> {
+            base.iter().chain(rest).cloned().collect()
+        };
+
+        match head.as_str() {
+            "crate" | "self" | "super" => absolute_path(module, path).into_iter().collect(),
+            "Self" => self_type.map(|ty| vec![with(ty, rest)]).unwrap_or_default(),
+            _ => {
+                if let Some(bound) = self.bindings.get(head) {
+                    return self.relative_candidates(module, bound)
+                        .into_iter()
+                        .map(|candidate| with(&candidate, rest))
+                        .collect();
+                }
+
+                let mut candidates = self.relative_candidates(module, path);
+                for glob in &self.globs {
+                    for base in self.relative_candidates(module, glob) {
+                        candidates.push(with(&base, path));
+                    }
+                }
+                candidates
+            }
+        }
+    }
+
+    /// Candidates for a path that does not start with a `use` alias: a child
+    /// of the current module or, as in the 2015 edition, of the crate root
+    fn relative_candidates(&self, module: &[String], path: &[String]) -> Vec<Vec<String>> {
+        match path.first().map(String::as_str) {
+            Some("crate" | "self" | "super") => absolute_path(module, path).into_iter().collect(),
+            Some(_) => {
+                let mut candidates = vec![module.iter().chain(path).cloned().collect()];
+                if module.len() > 1 {
+                    candidates.push(module[..1].iter().chain(path).cloned().collect());
+                }
+                candidates
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Resolves Rust paths from one file against the symbols of its crate
+pub struct Resolver<'t, 'a> {
+    table: &'t SymbolTable<'a>,
+    root: PathBuf,
+    uses: UseScope,
+    local: HashMap<&'a str, &'a Symbol>,
+}
+
+impl<'t, 'a> Resolver<'t, 'a> {
+    pub fn new(table: &'t SymbolTable<'a>, file: &str) -> Self {
+        let symbols = table.in_file(file);
+        let mut local = HashMap::new();
+        for &symbol in symbols {
+            if symbol.kind != SymbolKind::Import {
+                local.entry(symbol.qualified_name.as_str()).or_insert(symbol);
+            }
+        }
+
+        Self {
+            table,
+            root: module_path(file).0,
+            uses: UseScope::from_symbols(symbols.iter().copied()),
+            local,
+        }
+    }
+
+    /// `use` bindings of the file
+    pub fn uses(&self) -> &UseScope {
+        &self.uses
+    }
+
+    /// Symbol defined in this file with the given qualified name
+    pub fn symbol(&self, qualified_name: &str) -> Option<&'a Symbol> {
+        self.local.get(qualified_name).copied()
+    }
+
+    /// Resolve a path written in `module`, `self_type` being the enclosing impl or trait
+    pub fn resolve(&self, module: &[String], path: &[String], self_type: Option<&[String]>) -> Option<&'a Symbol> {
+        self.uses.candidates(module, path, self_type)
+            .into_iter()
+            .find_map(|candidate| self.find(&candidate.join("::")))
+    }
+
+    /// Symbol of this crate with the given qualified name
+    pub fn find(&self, qualified_name: &str) -> Option<&'a Symbol> {
+        if let Some(symbol) = self.symbol(qualified_name) {
+            return Some(symbol);
+        }
+
+        let name = qualified_name.rsplit("::").next().unwrap_or(qualified_name);
+        self.table.named(name)
+            .iter()
+            .find(|symbol| {
+                symbol.qualified_name == qualified_name
+                    && symbol.language == "rust"
+                    && Path::new(&symbol.location.file).starts_with(&self.root)
+            })
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Parser;
+    use crate::indexer::parser::RustParser;
+
+    fn strings(path: &[&str]) -> Vec<String> {
+        path.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path("proj/src/lib.rs"), (PathBuf::from("proj/src"), strings(&["crate"])));
+        assert_eq!(module_path("proj/src/index/db.rs").1, strings(&["crate", "index", "db"]));
+        assert_eq!(module_path("proj/src/index/mod.rs").1, strings(&["crate", "index"]));
+        assert_eq!(module_path("proj/tests/api.rs"), (PathBuf::from("proj/tests"), strings(&["crate"])));
+    }
+
+    #[test]
+    fn test_path_segments() {
+        assert_eq!(path_segments("Vec::<u8>::new"), Some(strings(&["Vec", "new"])));
+        assert_eq!(path_segments("HashMap<K, V>"), Some(strings(&["HashMap"])));
+        assert_eq!(path_segments("<T as Default>::default"), None);
+        assert_eq!(path_segments("mutation::apply"), Some(strings(&["mutation", "apply"])));
+        assert_eq!(path_segments("std::sync::Mutex"), Some(strings(&["std", "sync", "Mutex"])));
+        assert_eq!(path_segments("mutex::lock"), Some(strings(&["mutex", "lock"])));
+        assert_eq!(path_segments("&'a mut Stack<T>"), Some(strings(&["Stack"])));
+        assert_eq!(path_segments("&mut_state::Store"), Some(strings(&["mut_state", "Store"])));
+    }
+
+    #[test]
+    fn test_use_candidates() {
+        let symbols = RustParser::new()
+            .parse("use super::db::{self, Store as Db};\nuse crate::util::*;\n", "src/index/scan.rs")
+            .unwrap()
+            .0;
+        let uses = UseScope::from_symbols(&symbols);
+        let module = strings(&["crate", "index", "scan"]);
+
+        assert_eq!(uses.candidates(&module, &strings(&["Db", "open"]), None), vec![strings(&["crate", "index", "db", "Store", "open"])]);
+        assert_eq!(uses.candidates(&module, &strings(&["db", "open"]), None), vec![strings(&["crate", "index", "db", "open"])]);
+        assert_eq!(uses.candidates(&module, &strings(&["Self", "new"]), Some(&strings(&["crate", "Foo"]))), vec![strings(&["crate", "Foo", "new"])]);
+        assert_eq!(uses.candidates(&module, &strings(&["helper"]), None), vec![
+            strings(&["crate", "index", "scan", "helper"]),
+            strings(&["crate", "helper"]),
+            strings(&["crate", "util", "helper"]),
+        ]);
+    }
+}
@@ -0,0 +1,322 @@
+// Project file discovery shared by indexing, startup and the watcher
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use tracing::warn;
+
+use crate::config::{Config, IndexingConfig};
+use crate::indexer::registry::ParserRegistry;
+
+/// Directories holding third-party code that is never indexed
+const VENDORED_DIRS: &[&str] = &[
+    "node_modules",
+    "bower_components",
+    "vendor",
+    "third_party",
+    "target",
+    "__pycache__",
+    "site-packages",
+    "venv",
+];
+
+/// Ignore files honored at every level of the project, highest precedence first
+const IGNORE_FILES: &[&str] = &[".ignore", ".gitignore"];
+
+/// Include/exclude patterns from `[indexing]`, matched with gitignore glob semantics
+///
+/// A pattern without a slash matches at any depth, a trailing slash only
+/// matches directories and `**` spans any number of directories.
+pub struct PathFilter {
+    include: Option<Gitignore>,
+    exclude: Gitignore,
+}
+
+impl PathFilter {
+    pub fn new(indexing: &IndexingConfig) -> Result<Self> {
+        let include = if indexing.include.is_empty() {
+            None
+        } else {
+            Some(build_matcher(&indexing.include)?)
+        };
+
+        Ok(Self {
+            include,
+            exclude: build_matcher(&indexing.exclude)?,
+        })
+    }
+
+    /// Check a path relative to the project root
+    pub fn is_included(&self, relative_path: &Path) -> bool {
+        if self.exclude.matched_path_or_any_parents(relative_path, false).is_ignore() {
+            return false;
+        }
+
+        match &self.include {
+            Some(include) => include.matched_path_or_any_parents(relative_path, false).is_ignore(),
+            None => true,
+        }
+    }
+}
+
+/// Compile gitignore-style patterns rooted at the project
+pub(crate) fn build_matcher(patterns: &[String]) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new("");
+    for pattern in patterns {
+        builder.add_line(None, pattern)?;
+    }
+    Ok(builder.build()?)
+}
+
+/// Files found by a scan, grouped by language
+#[derive(Debug, Default)]
+pub struct ScanResult {
+    pub by_language: BTreeMap<String, Vec<String>>,
+    /// Files that are excluded, unsupported or in a disabled language
+    pub skipped: usize,
+}
+
+impl ScanResult {
+    /// All indexable files, grouped by language
+    pub fn files(&self) -> Vec<String> {
+        self.by_language.values().flatten().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_language.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Walks a project and decides which files are indexed
+///
+/// Paths are reported as `root.join(relative)`, so the project argument
+/// decides whether they are relative or absolute. Paths coming from
+/// elsewhere, such as watcher events, are normalized to the same form.
+pub struct ProjectScanner {
+    root: PathBuf,
+    canonical_root: PathBuf,
+    filter: PathFilter,
+    languages: Vec<String>,
+}
+
+impl ProjectScanner {
+    pub fn new(root: impl AsRef<Path>, config: &Config) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let canonical_root = root.canonicalize().unwrap_or_else(|_| root.clone());
+
+        Ok(Self {
+            root,
+            canonical_root,
+            filter: PathFilter::new(&config.indexing)?,
+            languages: config.get_enabled_languages(),
+        })
+    }
+
+    /// Restrict the scan to the given languages
+    pub fn with_languages(mut self, languages: Vec<String>) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// Walk the project and group the indexable files by language
+    pub fn scan(&self, registry: &ParserRegistry) -> ScanResult {
+        let mut result = ScanResult::default();
+
+        let walker = WalkBuilder::new(&self.root)
+            .hidden(true)
+            .ignore(true)
+            .git_ignore(true)
+            .git_exclude(true)
+            .git_global(false)
+            .parents(true)
+            .require_git(false)
+            .filter_entry(|entry| {
+                // Never descend into vendored directories
+                !(entry.depth() > 0
+                    && entry.file_type().is_some_and(|t| t.is_dir())
+                    && entry.file_name().to_str().is_some_and(is_vendored))
+            })
+            .build();
+
+        for entry in walker {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!("Failed to scan: {}", e);
+                    continue;
+                }
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+
+            match self.classify(entry.path(), registry) {
+                Some(language) => {
+                    let path = entry.path().to_string_lossy().to_string();
+                    result.by_language.entry(language.to_string()).or_default().push(path);
+                }
+                None => result.skipped += 1,
+            }
+        }
+
+        for files in result.by_language.values_mut() {
+            files.sort();
+        }
+
+        result
+    }
+
+    /// Normalize a path from outside the walk, such as a watcher event,
+    /// to the form the walk reports, or `None` if it is not indexed
+    ///
+    /// The path does not need to exist, so deletions can be matched too.
+    pub fn accept(&self, path: &Path, registry: &ParserRegistry) -> Option<PathBuf> {
+        let relative = self.relative(path)?;
+        if relative.components().any(|c| match c {
+            Component::Normal(name) => name.to_str().is_some_and(|n| n.starts_with('.') || is_vendored(n)),
+            _ => true,
+        }) {
+            return None;
+        }
+
+        let path = self.root.join(&relative);
+        if path.is_dir() || self.is_ignored(&relative) {
+            return None;
+        }
+
+        self.classify(&path, registry)?;
+        Some(path)
+    }
+
+    /// Language of a walked file, if it passes the filter and is enabled
+    fn classify<'r>(&self, path: &Path, registry: &'r ParserRegistry) -> Option<&'r str> {
+        let relative = self.relative(path)?;
+        if !self.filter.is_included(&relative) {
+            return None;
+        }
+
+        let language = registry.language_for(&path.to_string_lossy())?;
+        self.languages.iter().any(|l| l == language).then_some(language)
+    }
+
+    fn relative(&self, path: &Path) -> Option<PathBuf> {
+        path.strip_prefix(&self.root)
+            .or_else(|_| path.strip_prefix(&self.canonical_root))
+            .ok()
+            .map(Path::to_path_buf)
+    }
+
+    /// Check the ignore files between the root and a path, deepest first
+    fn is_ignored(&self, relative: &Path) -> bool {
+        let dirs: Vec<&Path> = relative.ancestors().skip(1).collect();
+        for dir in dirs {
+            let dir_path = self.root.join(dir);
+            for name in IGNORE_FILES {
+                let ignore_file = dir_path.join(name);
+                if !ignore_file.is_file() {
+                    continue;
+                }
+
+                let mut builder = GitignoreBuilder::new(&dir_path);
+                if let Some(e) = builder.add(&ignore_file) {
+                    warn!("Failed to read {}: {}", ignore_file.display(), e);
+                }
+                let Ok(matcher) = builder.build() else {
+                    continue;
+                };
+
+                let within = relative.strip_prefix(dir).unwrap_or(relative);
+                let matched = matcher.matched_path_or_any_parents(within, false);
+                if !matched.is_none() {
+                    return matched.is_ignore();
+                }
+            }
+        }
+
+        false
+    }
+}
+
+fn is_vendored(name: &str) -> bool {
+    VENDORED_DIRS.contains(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn touch(root: &Path, relative: &str) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+
+    #[test]
+    fn test_scan_honors_ignore_files_and_policy() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for file in [
+            "app.py",
+            "lib/util.rs",
+            "lib/generated/schema.rs",
+            "lib/generated/keep.rs",
+            "scratch.go",
+            "pkg/notes.txt",
+            "pkg/__tests__/case.py",
+            ".hidden/secret.py",
+            "node_modules/dep/index.py",
+            "vendor/github.com/x/y.go",
+        ] {
+            touch(root, file);
+        }
+        std::fs::write(root.join(".gitignore"), "scratch.go\n").unwrap();
+        std::fs::write(root.join("lib/.gitignore"), "generated/*\n!generated/keep.rs\n").unwrap();
+
+        let registry = ParserRegistry::builtin();
+        let scanner = ProjectScanner::new(root, &Config::default()).unwrap();
+        let result = scanner.scan(&registry);
+
+        let relative = |files: &Vec<String>| -> Vec<String> {
+            files.iter()
+                .map(|f| Path::new(f).strip_prefix(root).unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+        assert_eq!(relative(&result.by_language["python"]), vec!["app.py"]);
+        assert_eq!(relative(&result.by_language["rust"]), vec!["lib/generated/keep.rs", "lib/util.rs"]);
+        assert!(!result.by_language.contains_key("go"));
+
+        // The watcher applies the same rules to single paths
+        assert!(scanner.accept(&root.join("lib/util.rs"), &registry).is_some());
+        assert!(scanner.accept(&root.join("lib/generated/keep.rs"), &registry).is_some());
+        assert!(scanner.accept(&root.join("lib/generated/schema.rs"), &registry).is_none());
+        assert!(scanner.accept(&root.join("scratch.go"), &registry).is_none());
+        assert!(scanner.accept(&root.join("node_modules/dep/index.py"), &registry).is_none());
+        assert!(scanner.accept(&root.join(".hidden/secret.py"), &registry).is_none());
+        assert!(scanner.accept(&root.join("deleted.py"), &registry).is_some());
+    }
+
+    #[test]
+    fn test_path_filter_glob_semantics() {
+        let mut indexing = Config::default().indexing;
+        indexing.exclude = vec!["build/".to_string(), "**/fixtures/**".to_string(), "*_pb2.py".to_string()];
+        indexing.include = vec!["src/**/*.py".to_string(), "tools/".to_string()];
+        let filter = PathFilter::new(&indexing).unwrap();
+
+        assert!(filter.is_included(Path::new("src/app.py")));
+        assert!(filter.is_included(Path::new("src/deep/nested/app.py")));
+        assert!(filter.is_included(Path::new("tools/gen/main.go")));
+        assert!(!filter.is_included(Path::new("src/app.rs")));
+        assert!(!filter.is_included(Path::new("lib/app.py")));
+        assert!(!filter.is_included(Path::new("src/build/app.py")));
+        assert!(!filter.is_included(Path::new("src/a/fixtures/b/app.py")));
+        assert!(!filter.is_included(Path::new("src/api/service_pb2.py")));
+    }
+}
@@ -0,0 +1,263 @@
+// Shell script analysis: functions, the commands they run and what those
+// commands invoke, shared with the Makefile and Dockerfile parsers for
+// recipes and `RUN` instructions
+
+use tree_sitter::Node;
+
+use crate::index::Span;
+
+/// Extensions of shell scripts
+pub const EXTENSIONS: &[&str] = &["sh", "bash", "zsh", "ksh"];
+
+/// Shells that run the script given as their first operand
+const SHELLS: &[&str] = &["bash", "sh", "zsh", "dash", "ksh"];
+
+/// Commands that run the rest of their command line
+const WRAPPERS: &[&str] = &["sudo", "env", "exec", "time", "nohup", "nice", "command", "builtin"];
+
+#[derive(Debug, Default)]
+pub struct Script {
+    pub functions: Vec<Function>,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    /// `#` comment lines directly above the function
+    pub doc: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub struct Command {
+    /// Command name and arguments, quotes removed
+    pub words: Vec<String>,
+    /// Function the command runs in, if any
+    pub function: Option<String>,
+    pub span: Span,
+}
+
+/// What a command runs, as far as the index is concerned
+#[derive(Debug, Clone, PartialEq)]
+pub enum Invocation {
+    /// `source path` or `. path`
+    Source(String),
+    /// `./path`, `dir/path.sh` or `bash path`
+    Script(String),
+    /// `make` or `$(MAKE)`, with its `-C` directory and targets
+    Make { directory: Option<String>, targets: Vec<String> },
+    /// Any other command, which may be a function
+    Call(String),
+}
+
+impl Command {
+    pub fn invocation(&self) -> Option<Invocation> {
+        let mut words = self.words.iter().map(String::as_str);
+        let mut name = words.next()?;
+        // `sudo -E env A=1 ./run.sh` runs `./run.sh`
+        while WRAPPERS.contains(&name) || name.starts_with('-') || is_assignment(name) {
+            name = words.next()?;
+        }
+
+        let invocation = match name {
+            "source" | "." => Invocation::Source(words.next()?.to_string()),
+            shell if SHELLS.contains(&shell) => {
+                let mut operands = words.skip_while(|word| word.starts_with('-') && *word != "-c");
+                match operands.next()? {
+                    // `bash -c '...'` runs a command line, not a file
+                    "-c" => return None,
+                    path => Invocation::Script(path.to_string()),
+                }
+            }
+            "make" | "gmake" => {
+                let (mut directory, mut targets) = (None, Vec::new());
+                while let Some(word) = words.next() {
+                    if word == "-C" || word == "--directory" {
+                        directory = words.next().map(str::to_string);
+                    } else if let Some(path) = word.strip_prefix("--directory=").or_else(|| word.strip_prefix("-C").filter(|path| !path.is_empty())) {
+                        directory = Some(path.to_string());
+                    } else if matches!(word, "-f" | "--file" | "-I" | "-j" | "-l" | "-o" | "-W") {
+                        words.next();
+                    } else if !word.starts_with('-') && !is_assignment(word) {
+                        targets.push(word.to_string());
+                    }
+                }
+                Invocation::Make { directory, targets }
+            }
+            path if is_path(path) => Invocation::Script(path.to_string()),
+            name => Invocation::Call(name.to_string()),
+        };
+        Some(invocation)
+    }
+}
+
+/// `NAME=value` before a command
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+}
+
+/// Whether a command name runs a file rather than something on `PATH`
+fn is_path(name: &str) -> bool {
+    name.contains('/')
+        || name.rsplit_once('.').is_some_and(|(stem, extension)| !stem.is_empty() && EXTENSIONS.contains(&extension))
+}
+
+/// Path of a script as written, without the expansions it starts with, such
+/// as `$(dirname "$0")/` or `${ROOT}/`; `None` for absolute paths and paths
+/// that are expansions through and through
+pub fn script_path(word: &str) -> Option<&str> {
+    let bytes = word.as_bytes();
+    let (mut i, mut end) = (0, 0);
+    while i < bytes.len() {
+        if bytes[i] != b'$' {
+            i += 1;
+            continue;
+        }
+        i += 1;
+        match bytes.get(i) {
+            Some(&open @ (b'(' | b'{')) => {
+                let close = if open == b'(' { b')' } else { b'}' };
+                let mut depth = 0;
+                while i < bytes.len() {
+                    if bytes[i] == open {
+                        depth += 1;
+                    } else if bytes[i] == close {
+                        depth -= 1;
+                        if depth == 0 {
+                            i += 1;
+                            break;
+                        }
+                    }
+                    i += 1;
+                }
+            }
+            _ => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+            }
+        }
+        end = i;
+    }
+
+    if end == 0 && word.starts_with('/') {
+        return None;
+    }
+    let path = word[end..].trim_start_matches('/');
+    (!path.is_empty() && !path.contains('$')).then_some(path)
+}
+
+/// Functions and commands of a script whose first line is `first_line` of
+/// the file it was taken from
+pub fn parse(source: &str, first_line: u32) -> anyhow::Result<Script> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_bash::LANGUAGE.into())?;
+    let tree = parser.parse(source, None)
+        .ok_or_else(|| anyhow::anyhow!("Failed to parse shell script"))?;
+
+    let mut script = Script::default();
+    walk(tree.root_node(), source, first_line, None, &mut script);
+    Ok(script)
+}
+
+fn walk(node: Node, source: &str, first_line: u32, function: Option<&str>, script: &mut Script) {
+    let text = |node: Node| source[node.byte_range()].to_string();
+    match node.kind() {
+        "function_definition" => {
+            if let Some(name) = node.child_by_field_name("name").map(text) {
+                script.functions.push(Function { doc: doc(node, source), span: span(node, first_line), name: name.clone() });
+                if let Some(body) = node.child_by_field_name("body") {
+                    walk(body, source, first_line, Some(&name), script);
+                }
+                return;
+            }
+        }
+        "command" => {
+            let mut cursor = node.walk();
+            let words: Vec<String> = node.child_by_field_name("name").into_iter()
+                .chain(node.children_by_field_name("argument", &mut cursor))
+                .map(|word| text(word).replace(['"', '\''], ""))
+                .collect();
+            if !words.is_empty() {
+                script.commands.push(Command { words, function: function.map(str::to_string), span: span(node, first_line) });
+            }
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk(child, source, first_line, function, script);
+    }
+}
+
+/// `#` comment lines directly above a node, without the shebang
+fn doc(node: Node, source: &str) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut line = node.start_position().row;
+    let mut previous = node.prev_sibling();
+    while let Some(comment) = previous.filter(|previous| previous.kind() == "comment" && previous.end_position().row + 1 == line) {
+        let text = &source[comment.byte_range()];
+        if text.starts_with("#!") {
+            break;
+        }
+        lines.push(text.trim_start_matches('#').trim());
+        line = comment.start_position().row;
+        previous = comment.prev_sibling();
+    }
+    lines.reverse();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Range of a node of a script whose first line is `first_line` of its file
+fn span(node: Node, first_line: u32) -> Span {
+    let span = Span::of(node);
+    Span { line: span.line + first_line, end_line: span.end_line + first_line, ..span }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_functions_and_invocations() {
+        let script = parse(concat!(
+            "#!/bin/bash\n",
+            "# Print a message\n",
+            "log() { echo \"$1\"; }\n",
+            "source \"$(dirname \"$0\")/lib.sh\"\n",
+            "function build {\n",
+            "  log hi | tee out.txt\n",
+            "  sudo -E env MODE=fast ./scripts/deploy.sh --now && bash -x \"$ROOT/tools/gen.sh\" a\n",
+            "  make -C sub all DEBUG=1\n",
+            "}\n",
+        ), 10).unwrap();
+
+        let functions: Vec<(&str, Option<&str>, u32)> = script.functions.iter()
+            .map(|function| (function.name.as_str(), function.doc.as_deref(), function.span.line))
+            .collect();
+        assert_eq!(functions, vec![("log", Some("Print a message"), 12), ("build", None, 14)]);
+
+        let invocations: Vec<(Option<&str>, Invocation)> = script.commands.iter()
+            .filter_map(|command| Some((command.function.as_deref(), command.invocation()?)))
+            .filter(|(_, invocation)| !matches!(invocation, Invocation::Call(name) if name == "echo" || name == "tee" || name == "dirname"))
+            .collect();
+        assert_eq!(invocations, vec![
+            (None, Invocation::Source("$(dirname $0)/lib.sh".to_string())),
+            (Some("build"), Invocation::Call("log".to_string())),
+            (Some("build"), Invocation::Script("./scripts/deploy.sh".to_string())),
+            (Some("build"), Invocation::Script("$ROOT/tools/gen.sh".to_string())),
+            (Some("build"), Invocation::Make { directory: Some("sub".to_string()), targets: vec!["all".to_string()] }),
+        ]);
+    }
+
+    #[test]
+    fn test_script_path() {
+        assert_eq!(script_path("$(dirname $0)/lib.sh"), Some("lib.sh"));
+        assert_eq!(script_path("${BASH_SOURCE%/*}/../common/env.sh"), Some("../common/env.sh"));
+        assert_eq!(script_path("./scripts/deploy.sh"), Some("./scripts/deploy.sh"));
+        assert_eq!(script_path("/usr/local/bin/entrypoint.sh"), None);
+        assert_eq!(script_path("$SCRIPT"), None);
+    }
+}
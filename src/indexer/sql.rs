@@ -0,0 +1,1192 @@
+// SQL lexer and statement parser for schema definitions, and a scanner for
+// the tables that queries read and write
+//
+// Dialects differ too much for one grammar, so the source is split into
+// statements at `;` (or the MySQL `DELIMITER`), the definitions below are
+// parsed, and every other statement is only scanned for the tables it touches:
+//
+//   table    := CREATE (OR REPLACE)? modifier* TABLE (IF NOT EXISTS)? name ("(" element ("," element)* ")")? (AS query)?
+//   element  := column | (CONSTRAINT IDENT)? (PRIMARY KEY | FOREIGN KEY | UNIQUE | KEY | INDEX | CHECK) ...
+//   column   := IDENT type (NOT NULL | PRIMARY KEY | REFERENCES name ("(" IDENT ")")? | ...)*
+//   view     := CREATE (OR REPLACE)? modifier* VIEW (IF NOT EXISTS)? name ("(" IDENT ("," IDENT)* ")")? ... AS query
+//   function := CREATE (OR REPLACE)? (FUNCTION | PROCEDURE) name "(" ... ")" (RETURNS type)? ... (AS STRING | BEGIN ... END | RETURN ...)
+//   index    := CREATE UNIQUE? INDEX CONCURRENTLY? (IF NOT EXISTS)? IDENT? ON ONLY? name (USING IDENT)? "(" ... ")"
+//   alter    := ALTER TABLE (IF EXISTS)? ONLY? name action ("," action)*
+//   action   := ADD COLUMN? (IF NOT EXISTS)? column | ...
+//   name     := IDENT ("." IDENT)*
+//
+// Unquoted identifiers are folded to lower case the way PostgreSQL and SQLite
+// compare them, so `Users` in a query finds `CREATE TABLE users`.
+
+use std::collections::HashSet;
+
+use crate::index::Span;
+
+/// Keywords that cannot name a table or stand for its alias
+const RESERVED: &[&str] = &[
+    "as", "cross", "except", "fetch", "for", "from", "full", "group", "having", "inner", "intersect", "into",
+    "join", "lateral", "left", "limit", "natural", "offset", "on", "only", "order", "outer", "returning",
+    "right", "select", "set", "straight_join", "union", "using", "values", "when", "where", "window", "with",
+];
+
+/// Keywords that end the type of a column definition
+const COLUMN_CONSTRAINTS: &[&str] = &[
+    "not", "null", "default", "primary", "references", "unique", "check", "constraint", "generated",
+    "collate", "auto_increment", "autoincrement", "comment", "identity", "on", "as",
+];
+
+/// Keywords that end the `RETURNS` clause of a function
+const FUNCTION_CLAUSES: &[&str] = &[
+    "language", "as", "begin", "return", "immutable", "stable", "volatile", "strict", "security", "called",
+    "cost", "rows", "parallel", "set", "leakproof", "deterministic", "no", "reads", "modifies", "contains",
+    "comment", "not", "window", "support", "transform",
+];
+
+/// Verbs a string literal holding a SQL statement starts with
+const QUERY_VERBS: &[&str] = &[
+    "select", "insert", "update", "delete", "with", "merge", "replace", "upsert", "truncate", "create", "alter",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Word,
+    /// `"name"`, `` `name` `` or `[name]`
+    Quoted,
+    /// `'text'` or `$tag$text$tag$`
+    String,
+    Number,
+    Punct,
+    /// `;`, or the delimiter set by `DELIMITER`
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+    span: Span,
+    /// `--` comment lines directly above the token
+    doc: Option<String>,
+}
+
+/// Whether a table is read or written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Access::Read => "read",
+            Access::Write => "write",
+        }
+    }
+}
+
+/// Table named by a statement, e.g. after `FROM`, `JOIN` or `INSERT INTO`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRef {
+    /// Name as written, folded, with any schema, e.g. `public.users`
+    pub name: String,
+    pub access: Access,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct File {
+    pub tables: Vec<Table>,
+    pub views: Vec<View>,
+    pub functions: Vec<Function>,
+    pub indexes: Vec<Index>,
+    /// `ALTER TABLE ... ADD COLUMN`, for tables that may be created elsewhere
+    pub alterations: Vec<Alteration>,
+    /// Tables touched by statements other than definitions, such as data
+    /// migrations and `ALTER TABLE`
+    pub references: Vec<TableRef>,
+}
+
+#[derive(Debug, Default)]
+pub struct Table {
+    pub name: String,
+    pub doc: Option<String>,
+    pub columns: Vec<Column>,
+    pub temporary: bool,
+    /// Tables read by `CREATE TABLE ... AS`
+    pub sources: Vec<TableRef>,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct Column {
+    pub name: String,
+    pub doc: Option<String>,
+    /// Declared type; `None` for the columns of a view
+    pub type_name: Option<String>,
+    pub not_null: bool,
+    pub primary_key: bool,
+    /// Table, and column if given, of a `REFERENCES` or `FOREIGN KEY` constraint
+    pub references: Option<(TableRef, Option<String>)>,
+    /// Definition with whitespace collapsed, e.g. `email text NOT NULL`
+    pub signature: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct View {
+    pub name: String,
+    pub doc: Option<String>,
+    /// Columns listed after the view name
+    pub columns: Vec<Column>,
+    pub materialized: bool,
+    pub sources: Vec<TableRef>,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct Function {
+    pub name: String,
+    pub doc: Option<String>,
+    pub procedure: bool,
+    /// Parameter list without parentheses, whitespace collapsed
+    pub parameters: String,
+    pub returns: Option<String>,
+    pub language: Option<String>,
+    /// Tables read and written by the body
+    pub sources: Vec<TableRef>,
+    pub span: Span,
+}
+
+impl Function {
+    pub fn signature(&self) -> String {
+        match &self.returns {
+            Some(returns) => format!("{}({}) RETURNS {}", self.name, self.parameters, returns),
+            None => format!("{}({})", self.name, self.parameters),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Index {
+    /// Declared name, or the one PostgreSQL derives for an unnamed index
+    pub name: String,
+    pub table: String,
+    /// Indexed columns; expressions are left out
+    pub columns: Vec<String>,
+    pub unique: bool,
+    pub span: Span,
+}
+
+impl Index {
+    pub fn signature(&self) -> String {
+        let unique = if self.unique { "UNIQUE " } else { "" };
+        format!("{}INDEX {} ON {} ({})", unique, self.name, self.table, self.columns.join(", "))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Alteration {
+    pub table: String,
+    pub columns: Vec<Column>,
+    pub span: Span,
+}
+
+/// Parse a SQL script; statements that cannot be parsed are scanned for tables
+pub fn parse(source: &str) -> File {
+    let tokens = tokenize(source, 0..source.len(), 0, 0);
+    let mut file = File::default();
+    let mut start = 0;
+    while start < tokens.len() {
+        let end = statement_end(source, &tokens, start);
+        let mut statement = Statement { source, tokens: &tokens[start..end], position: 0 };
+        statement.parse(&mut file);
+        start = end + 1;
+    }
+    file
+}
+
+/// Tables touched by the SQL in a string literal, or nothing when the
+/// literal does not start like a statement
+pub fn literal_tables(text: &str) -> Vec<TableRef> {
+    let tokens = tokenize(text, 0..text.len(), 0, 0);
+    let verb = tokens.iter()
+        .find(|token| !(token.kind == TokenKind::Punct && token.text(text) == "("))
+        .and_then(|token| token.word(text));
+    match verb {
+        Some(verb) if QUERY_VERBS.contains(&verb.as_str()) => table_refs(text, &tokens),
+        _ => Vec::new(),
+    }
+}
+
+/// Tokens of `source[range]`, which starts on `line` at byte `line_start`
+fn tokenize(source: &str, range: std::ops::Range<usize>, mut line: u32, mut line_start: usize) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let bytes = &source.as_bytes()[..range.end];
+    // Leading comment lines waiting for the next token, and the line of the last one
+    let (mut comments, mut comment_line): (Vec<&str>, Option<u32>) = (Vec::new(), None);
+    let mut delimiter: Option<&str> = None;
+    let mut i = range.start;
+
+    while i < bytes.len() {
+        let start = i;
+        let (start_line, start_column) = (line, (start - line_start) as u32);
+        let word_continues = |c: u8| c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c >= 0x80;
+
+        let kind = match bytes[i] {
+            _ if delimiter.is_some_and(|delimiter| bytes[i..].starts_with(delimiter.as_bytes())) => {
+                i += delimiter.map_or(1, str::len);
+                TokenKind::End
+            }
+            b'\n' => {
+                i += 1;
+                line += 1;
+                line_start = i;
+                continue;
+            }
+            c if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                // A trailing comment documents the token before it, not the next one
+                let trailing = tokens.last().is_some_and(|token| token.span.end_line == start_line);
+                if !trailing {
+                    // A blank line detaches the comments above it
+                    if comment_line.is_some_and(|previous| previous + 1 != start_line) {
+                        comments.clear();
+                    }
+                    comments.push(source[start + 2..i].trim());
+                    comment_line = Some(start_line);
+                }
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !bytes[i..].starts_with(b"*/") {
+                    if bytes[i] == b'\n' {
+                        line += 1;
+                        line_start = i + 1;
+                    }
+                    i += 1;
+                }
+                i = (i + 2).min(bytes.len());
+                continue;
+            }
+            b';' => {
+                i += 1;
+                TokenKind::End
+            }
+            quote @ (b'\'' | b'"' | b'`') => {
+                i = quoted(bytes, i + 1, quote, &mut line, &mut line_start);
+                if quote == b'\'' { TokenKind::String } else { TokenKind::Quoted }
+            }
+            // `[name]` quotes an identifier in SQL Server, but subscripts the array right before it
+            b'[' if bytes.get(i + 1).is_some_and(|&c| c.is_ascii_alphabetic() || c == b'_')
+                && !tokens.last().is_some_and(|token| token.end == i && token.text(source) != ".") =>
+            {
+                while i < bytes.len() && bytes[i] != b']' && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                i = (i + 1).min(bytes.len());
+                TokenKind::Quoted
+            }
+            b'$' => {
+                let tag_end = (i + 1..bytes.len()).find(|&j| !word_continues(bytes[j]) || bytes[j] == b'$');
+                match tag_end {
+                    Some(tag_end) if bytes[tag_end] == b'$' && !bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                        let tag = &source[i..=tag_end];
+                        i = tag_end + 1;
+                        let close = source[i..bytes.len()].find(tag).map_or(bytes.len(), |offset| i + offset + tag.len());
+                        for (j, _) in bytes[i..close].iter().enumerate().filter(|(_, &c)| c == b'\n') {
+                            line += 1;
+                            line_start = i + j + 1;
+                        }
+                        i = close;
+                        TokenKind::String
+                    }
+                    // A positional parameter such as `$1`
+                    _ => {
+                        i += 1;
+                        while i < bytes.len() && bytes[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                        TokenKind::Punct
+                    }
+                }
+            }
+            c if c.is_ascii_digit() => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.') {
+                    i += 1;
+                }
+                TokenKind::Number
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' || c >= 0x80 => {
+                let at_delimiter = |i: usize| delimiter.is_some_and(|delimiter| bytes[i..].starts_with(delimiter.as_bytes()));
+                while i < bytes.len() && word_continues(bytes[i]) && !at_delimiter(i) {
+                    i += 1;
+                }
+                // Prefixed strings such as `E'\n'` and `N'text'`
+                if i - start == 1 && bytes.get(i) == Some(&b'\'') && b"eEnNbBxXuU".contains(&c) {
+                    i = quoted(bytes, i + 1, b'\'', &mut line, &mut line_start);
+                    TokenKind::String
+                } else if source[start..i].eq_ignore_ascii_case("delimiter")
+                    && tokens.last().is_none_or(|token| token.span.end_line < start_line)
+                {
+                    // `DELIMITER $$` ends the statements that follow with `$$` instead of `;`
+                    let end = source[i..bytes.len()].find('\n').map_or(bytes.len(), |offset| i + offset);
+                    delimiter = Some(source[i..end].trim()).filter(|delimiter| !delimiter.is_empty() && *delimiter != ";");
+                    i = end;
+                    continue;
+                } else {
+                    TokenKind::Word
+                }
+            }
+            _ => {
+                i += source[i..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Punct
+            }
+        };
+
+        let doc = (comment_line.is_some_and(|previous| previous + 1 == start_line) && !comments.is_empty())
+            .then(|| comments.join("\n"));
+        comments.clear();
+        comment_line = None;
+
+        tokens.push(Token {
+            kind,
+            start,
+            end: i,
+            span: Span { line: start_line, column: start_column, end_line: line, end_column: (i - line_start) as u32 },
+            doc,
+        });
+    }
+
+    tokens
+}
+
+/// End of a quoted string or identifier opened just before `i`, where a
+/// doubled quote stands for itself
+fn quoted(bytes: &[u8], mut i: usize, quote: u8, line: &mut u32, line_start: &mut usize) -> usize {
+    while i < bytes.len() {
+        if bytes[i] == quote {
+            if bytes.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        if bytes[i] == b'\n' {
+            *line += 1;
+            *line_start = i + 1;
+        }
+        i += 1;
+    }
+    i
+}
+
+impl Token {
+    fn text<'s>(&self, source: &'s str) -> &'s str {
+        &source[self.start..self.end]
+    }
+
+    /// Keyword or unquoted identifier, in lower case
+    fn word(&self, source: &str) -> Option<String> {
+        (self.kind == TokenKind::Word).then(|| self.text(source).to_ascii_lowercase())
+    }
+
+    fn is(&self, source: &str, text: &str) -> bool {
+        match self.kind {
+            TokenKind::Word => self.text(source).eq_ignore_ascii_case(text),
+            TokenKind::Punct => self.text(source) == text,
+            _ => false,
+        }
+    }
+
+    /// Identifier, folded unless quoted; keywords that cannot name a table are not
+    fn identifier(&self, source: &str) -> Option<String> {
+        let text = self.text(source);
+        match self.kind {
+            TokenKind::Word => Some(text.to_ascii_lowercase()).filter(|word| !RESERVED.contains(&word.as_str())),
+            TokenKind::Quoted if text.len() >= 2 => {
+                let quote = &text[..1];
+                let inner = &text[1..text.len() - 1];
+                Some(if quote == "[" { inner.to_string() } else { inner.replace(&quote.repeat(2), quote) })
+            }
+            _ => None,
+        }
+    }
+
+    /// Range of the text inside a string literal, with the line it starts on
+    /// and the offset of that line, for tokenizing a function body in place
+    fn string_body(&self, source: &str) -> Option<(std::ops::Range<usize>, u32, usize)> {
+        if self.kind != TokenKind::String {
+            return None;
+        }
+        let text = self.text(source);
+        let (open, close) = match text.strip_prefix('$') {
+            Some(rest) => {
+                let tag = rest.find('$')? + 2;
+                (tag, if text.len() >= 2 * tag { text.len() - tag } else { text.len() })
+            }
+            None => {
+                let open = text.find('\'')? + 1;
+                (open, if text.len() > open && text.ends_with('\'') { text.len() - 1 } else { text.len() })
+            }
+        };
+        let line_start = self.start - self.span.column as usize;
+        Some((self.start + open..self.start + close, self.span.line, line_start))
+    }
+}
+
+/// Index of the token ending the statement from `start`, skipping the `;`
+/// inside `BEGIN ... END` bodies and `CASE ... END` expressions
+fn statement_end(source: &str, tokens: &[Token], start: usize) -> usize {
+    let (mut parens, mut blocks) = (0usize, 0usize);
+    let mut i = start;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let next = tokens.get(i + 1);
+        let next_is = |texts: &[&str]| next.is_some_and(|next| texts.iter().any(|text| next.is(source, text)));
+        match token.kind {
+            TokenKind::End if parens == 0 && blocks == 0 => return i,
+            TokenKind::Punct if token.is(source, "(") => parens += 1,
+            TokenKind::Punct if token.is(source, ")") => parens = parens.saturating_sub(1),
+            // `BEGIN;` and `BEGIN TRANSACTION` start a transaction, not a block
+            TokenKind::Word if token.is(source, "begin")
+                && !next.is_none_or(|next| next.kind == TokenKind::End)
+                && !next_is(&["transaction", "work", "deferred", "immediate", "exclusive"]) => blocks += 1,
+            TokenKind::Word if token.is(source, "case") => blocks += 1,
+            TokenKind::Word if token.is(source, "end") && !next_is(&["if", "loop", "while", "repeat", "for"]) => {
+                blocks = blocks.saturating_sub(1);
+                if next_is(&["case"]) {
+                    i += 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    tokens.len()
+}
+
+/// Tables named in a statement, or in a function body: after `FROM`,
+/// `JOIN`, `INSERT INTO`, `UPDATE ... SET`, `TRUNCATE` and `REFERENCES`,
+/// leaving out common table expressions and table functions
+fn table_refs(source: &str, tokens: &[Token]) -> Vec<TableRef> {
+    let mut refs = Vec::new();
+    // Names bound by `WITH name AS (...)`
+    let mut ctes = HashSet::new();
+    // Whether each open parenthesis holds a query rather than arguments, innermost last
+    let mut queries = vec![true];
+    // Verb of the innermost statement, for what `INTO` and `FROM` mean
+    let mut verb = String::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|i| &tokens[i]);
+        let previous_is = |text: &str| previous.is_some_and(|previous| previous.is(source, text));
+        if token.kind == TokenKind::End {
+            verb.clear();
+            continue;
+        }
+        if token.is(source, "(") {
+            let query = tokens.get(i + 1).is_some_and(|next| ["select", "with", "values"].iter().any(|text| next.is(source, text)));
+            queries.push(query);
+            continue;
+        }
+        if token.is(source, ")") {
+            if queries.len() > 1 {
+                queries.pop();
+            }
+            continue;
+        }
+
+        // `WITH name AS (` or `, name (columns) AS (`
+        if previous.is_some_and(|previous| ["with", "recursive", ","].iter().any(|text| previous.is(source, text))) {
+            if let Some(name) = token.identifier(source) {
+                let mut j = i + 1;
+                if tokens.get(j).is_some_and(|next| next.is(source, "(")) {
+                    j = matching(source, tokens, j) + 1;
+                }
+                if tokens.get(j).is_some_and(|next| next.is(source, "as")) {
+                    // `AS (`, `AS MATERIALIZED (` or `AS NOT MATERIALIZED (`, unlike a column alias
+                    let body = tokens[j + 1..].iter().find(|next| !next.is(source, "not") && !next.is(source, "materialized"));
+                    if body.is_some_and(|body| body.is(source, "(")) {
+                        ctes.insert(name);
+                    }
+                }
+            }
+        }
+
+        let Some(word) = token.word(source) else {
+            continue;
+        };
+        let query = *queries.last().unwrap_or(&true);
+        let mut push = |found: Vec<(String, Span)>, access: Access| {
+            refs.extend(found.into_iter()
+                .filter(|(name, _)| !ctes.contains(name))
+                .map(|(name, span)| TableRef { name, access, span }));
+        };
+        match word.as_str() {
+            "select" | "insert" | "update" | "delete" | "merge" | "replace" | "upsert"
+                if !tokens.get(i + 1).is_some_and(|next| next.is(source, "(")) =>
+            {
+                // Not `ON UPDATE`, `FOR UPDATE`, `DO UPDATE` or `INSERT OR UPDATE`
+                if ["on", "for", "do", "or"].iter().any(|text| previous_is(text)) {
+                    continue;
+                }
+                verb = word.clone();
+                if word == "update" {
+                    let (found, next) = names(source, tokens, i + 1, false, false);
+                    if tokens.get(next).is_some_and(|next| next.is(source, "set")) {
+                        push(found, Access::Write);
+                    }
+                }
+            }
+            "from" if query && !previous_is("distinct") => {
+                let access = if previous_is("delete") { Access::Write } else { Access::Read };
+                push(names(source, tokens, i + 1, true, true).0, access);
+            }
+            "join" => push(names(source, tokens, i + 1, false, true).0, Access::Read),
+            "using" if matches!(verb.as_str(), "delete" | "merge") => push(names(source, tokens, i + 1, true, true).0, Access::Read),
+            "into" if matches!(verb.as_str(), "insert" | "replace" | "merge" | "upsert") => {
+                push(names(source, tokens, i + 1, false, false).0, Access::Write);
+            }
+            "truncate" => {
+                let start = if tokens.get(i + 1).is_some_and(|next| next.is(source, "table")) { i + 2 } else { i + 1 };
+                push(names(source, tokens, start, true, false).0, Access::Write);
+            }
+            "references" => push(names(source, tokens, i + 1, false, false).0, Access::Read),
+            _ => {}
+        }
+    }
+
+    refs
+}
+
+/// Table names from `start`, each with an optional alias, and the index
+/// after them
+///
+/// A `list` continues after commas. Where a table can be a function call,
+/// a name followed by `(` is one and not included; elsewhere the `(` opens
+/// a column list.
+fn names(source: &str, tokens: &[Token], start: usize, list: bool, calls: bool) -> (Vec<(String, Span)>, usize) {
+    let mut found = Vec::new();
+    let mut i = start;
+    loop {
+        if tokens.get(i).is_some_and(|token| token.is(source, "only")) {
+            i += 1;
+        }
+        let Some((name, span, next)) = qualified_name(source, tokens, i) else {
+            break;
+        };
+        if calls && tokens.get(next).is_some_and(|token| token.is(source, "(")) {
+            break;
+        }
+        found.push((name, span));
+        i = next;
+
+        // Alias
+        if tokens.get(i).is_some_and(|token| token.is(source, "as")) {
+            i += 1;
+        }
+        if tokens.get(i).is_some_and(|token| token.identifier(source).is_some()) {
+            i += 1;
+        }
+
+        if !(list && tokens.get(i).is_some_and(|token| token.is(source, ","))) {
+            break;
+        }
+        i += 1;
+    }
+    (found, i)
+}
+
+/// `IDENT ("." IDENT)*` at `start`, with its span and the index after it
+fn qualified_name(source: &str, tokens: &[Token], start: usize) -> Option<(String, Span, usize)> {
+    let first = tokens.get(start)?;
+    let mut name = first.identifier(source)?;
+    let mut span = first.span;
+    let mut i = start + 1;
+    while tokens.get(i).is_some_and(|token| token.is(source, ".")) {
+        let Some(segment) = tokens.get(i + 1).and_then(|token| token.identifier(source)) else {
+            break;
+        };
+        name = format!("{}.{}", name, segment);
+        span = span.to(tokens[i + 1].span);
+        i += 2;
+    }
+    Some((name, span, i))
+}
+
+/// Index of the `)` closing the `(` at `open`, or the last token
+fn matching(source: &str, tokens: &[Token], open: usize) -> usize {
+    let mut depth = 0usize;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token.is(source, "(") {
+            depth += 1;
+        } else if token.is(source, ")") {
+            depth -= 1;
+            if depth == 0 {
+                return i;
+            }
+        }
+    }
+    tokens.len().saturating_sub(1)
+}
+
+/// Source text with runs of whitespace collapsed to one space
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Cursor over the tokens of one statement, or of one element of a list
+struct Statement<'s, 't> {
+    source: &'s str,
+    tokens: &'t [Token],
+    position: usize,
+}
+
+impl<'s, 't> Statement<'s, 't> {
+    fn peek(&self) -> Option<&'t Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn at(&self, text: &str) -> bool {
+        self.peek().is_some_and(|token| token.is(self.source, text))
+    }
+
+    fn at_any(&self, texts: &[&str]) -> bool {
+        texts.iter().any(|text| self.at(text))
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self.at(text) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume a sequence of keywords only if all of them follow
+    fn eat_all(&mut self, texts: &[&str]) -> bool {
+        let matched = texts.iter().enumerate().all(|(offset, text)| {
+            self.tokens.get(self.position + offset).is_some_and(|token| token.is(self.source, text))
+        });
+        if matched {
+            self.position += texts.len();
+        }
+        matched
+    }
+
+    fn name(&mut self) -> Option<(String, Span)> {
+        let (name, span, next) = qualified_name(self.source, self.tokens, self.position)?;
+        self.position = next;
+        Some((name, span))
+    }
+
+    fn identifier(&mut self) -> Option<String> {
+        let identifier = self.peek()?.identifier(self.source)?;
+        self.position += 1;
+        Some(identifier)
+    }
+
+    fn rest(&self) -> &'t [Token] {
+        &self.tokens[self.position.min(self.tokens.len())..]
+    }
+
+    /// Span of the whole statement
+    fn span(&self) -> Span {
+        match (self.tokens.first(), self.tokens.last()) {
+            (Some(first), Some(last)) => first.span.to(last.span),
+            _ => Span::default(),
+        }
+    }
+
+    fn doc(&self) -> Option<String> {
+        self.tokens.first().and_then(|token| token.doc.clone())
+    }
+
+    /// Source text of `tokens[range]`, whitespace collapsed
+    fn text(&self, range: std::ops::Range<usize>) -> String {
+        match (self.tokens.get(range.start), range.end.checked_sub(1).and_then(|end| self.tokens.get(end))) {
+            (Some(first), Some(last)) if range.start < range.end => collapse(&self.source[first.start..last.end]),
+            _ => String::new(),
+        }
+    }
+
+    /// Token ranges of the comma-separated elements of the parenthesized
+    /// list at the cursor, which is consumed
+    fn list(&mut self) -> Vec<std::ops::Range<usize>> {
+        let mut elements = Vec::new();
+        if !self.at("(") {
+            return elements;
+        }
+        let close = matching(self.source, self.tokens, self.position);
+        let (mut depth, mut start) = (0usize, self.position + 1);
+        for i in self.position + 1..close {
+            let token = &self.tokens[i];
+            if token.is(self.source, "(") {
+                depth += 1;
+            } else if token.is(self.source, ")") {
+                depth = depth.saturating_sub(1);
+            } else if depth == 0 && token.is(self.source, ",") {
+                elements.push(start..i);
+                start = i + 1;
+            }
+        }
+        if start < close {
+            elements.push(start..close);
+        }
+        self.position = close + 1;
+        elements
+    }
+
+    /// Cursor over `tokens[range]`
+    fn element(&self, range: std::ops::Range<usize>) -> Statement<'s, 't> {
+        Statement { source: self.source, tokens: &self.tokens[range], position: 0 }
+    }
+
+    fn parse(&mut self, file: &mut File) {
+        if self.at("create") {
+            self.create(file);
+        } else if self.at("alter") && self.tokens.get(1).is_some_and(|token| token.is(self.source, "table")) {
+            self.alter(file);
+        } else {
+            file.references.extend(self.scan(self.tokens, self.at("do")));
+        }
+    }
+
+    /// Tables touched by `tokens`, and by the SQL in their strings when
+    /// those are bodies, as in `DO $$ ... $$`
+    fn scan(&self, tokens: &[Token], bodies: bool) -> Vec<TableRef> {
+        let mut refs = table_refs(self.source, tokens);
+        if bodies {
+            for (range, line, line_start) in tokens.iter().filter_map(|token| token.string_body(self.source)) {
+                let body = tokenize(self.source, range, line, line_start);
+                refs.extend(table_refs(self.source, &body));
+            }
+        }
+        refs
+    }
+
+    /// `CREATE` of a table, view, function, procedure or index; other
+    /// objects are only scanned
+    fn create(&mut self, file: &mut File) {
+        self.eat("create");
+        self.eat_all(&["or", "replace"]);
+        let (mut temporary, mut materialized, mut unique) = (false, false, false);
+        // Modifiers such as `TEMPORARY`, `MATERIALIZED` and MySQL's `DEFINER = ...`
+        while let Some(token) = self.peek() {
+            match token.word(self.source).as_deref() {
+                Some("table") => return self.table(file, temporary),
+                Some("view") => return self.view(file, materialized),
+                Some(kind @ ("function" | "procedure")) => return self.function(file, kind == "procedure"),
+                Some("index") => return self.index(file, unique),
+                Some("temp" | "temporary") => temporary = true,
+                Some("materialized") => materialized = true,
+                Some("unique") => unique = true,
+                _ if token.is(self.source, "(") || token.kind == TokenKind::String => break,
+                _ => {}
+            }
+            self.position += 1;
+        }
+        file.references.extend(self.scan(self.tokens, false));
+    }
+
+    fn table(&mut self, file: &mut File, temporary: bool) {
+        self.eat("table");
+        self.eat_all(&["if", "not", "exists"]);
+        let Some((name, _)) = self.name() else {
+            return;
+        };
+        let mut table = Table { name, doc: self.doc(), temporary, span: self.span(), ..Table::default() };
+
+        for range in self.list() {
+            let mut element = self.element(range);
+            element.table_element(&mut table, file);
+        }
+        table.sources = self.scan(self.rest(), false);
+        file.tables.push(table);
+    }
+
+    /// Column or table constraint inside `CREATE TABLE (...)`
+    fn table_element(&mut self, table: &mut Table, file: &mut File) {
+        let constraint = if self.eat("constraint") { self.identifier() } else { None };
+        let columns = |statement: &mut Statement| -> Vec<String> {
+            statement.list().into_iter()
+                .filter_map(|range| statement.tokens.get(range.start).and_then(|token| token.identifier(statement.source)))
+                .collect()
+        };
+
+        if self.eat_all(&["primary", "key"]) {
+            for name in columns(self) {
+                if let Some(column) = table.columns.iter_mut().find(|column| column.name == name) {
+                    column.primary_key = true;
+                    column.not_null = true;
+                }
+            }
+        } else if self.eat_all(&["foreign", "key"]) {
+            let names = columns(self);
+            if !self.eat("references") {
+                return;
+            }
+            let Some((target, span)) = self.name() else {
+                return;
+            };
+            let targets = columns(self);
+            for (i, name) in names.iter().enumerate() {
+                if let Some(column) = table.columns.iter_mut().find(|column| &column.name == name) {
+                    let reference = TableRef { name: target.clone(), access: Access::Read, span };
+                    column.references = Some((reference, targets.get(i).cloned()));
+                }
+            }
+        } else if self.at_any(&["unique", "key", "index", "fulltext", "spatial"]) {
+            // `UNIQUE (a)`, and MySQL's `UNIQUE KEY name (a)` and `KEY name (a)`
+            let unique = self.eat("unique");
+            self.eat("fulltext");
+            self.eat("spatial");
+            let _ = self.eat("key") || self.eat("index");
+            let name = if self.at("(") { constraint } else { self.identifier().or(constraint) };
+            let columns = columns(self);
+            if let Some(name) = name {
+                file.indexes.push(Index { name, table: table.name.clone(), columns, unique, span: self.span() });
+            }
+        } else if constraint.is_none() && !self.at_any(&["check", "exclude", "like", "period"]) {
+            if let Some(column) = self.column() {
+                table.columns.push(column);
+            }
+        }
+    }
+
+    /// Column definition: a name, a type and constraints
+    fn column(&mut self) -> Option<Column> {
+        let start = self.position;
+        let doc = self.peek()?.doc.clone();
+        let name = self.identifier()?;
+        let type_start = self.position;
+        while let Some(token) = self.peek() {
+            if token.word(self.source).is_some_and(|word| COLUMN_CONSTRAINTS.contains(&word.as_str())) {
+                break;
+            }
+            if token.is(self.source, "(") {
+                self.position = matching(self.source, self.tokens, self.position);
+            }
+            self.position += 1;
+        }
+        let type_name = Some(self.text(type_start..self.position.min(self.tokens.len()))).filter(|text| !text.is_empty());
+
+        let mut column = Column {
+            name,
+            doc,
+            type_name,
+            signature: self.text(start..self.tokens.len()),
+            span: self.element(start..self.tokens.len()).span(),
+            ..Column::default()
+        };
+        while self.peek().is_some() {
+            if self.eat_all(&["not", "null"]) {
+                column.not_null = true;
+            } else if self.eat_all(&["primary", "key"]) {
+                column.primary_key = true;
+                column.not_null = true;
+            } else if self.eat("references") {
+                if let Some((target, span)) = self.name() {
+                    let target_column = self.list().first()
+                        .and_then(|range| self.tokens.get(range.start))
+                        .and_then(|token| token.identifier(self.source));
+                    column.references = Some((TableRef { name: target, access: Access::Read, span }, target_column));
+                }
+            } else {
+                self.position += 1;
+            }
+        }
+        Some(column)
+    }
+
+    fn view(&mut self, file: &mut File, materialized: bool) {
+        self.eat("view");
+        self.eat_all(&["if", "not", "exists"]);
+        let Some((name, _)) = self.name() else {
+            return;
+        };
+        let columns = self.list().into_iter()
+            .filter_map(|range| {
+                let token = self.tokens.get(range.start)?;
+                Some(Column {
+                    name: token.identifier(self.source)?,
+                    doc: token.doc.clone(),
+                    signature: self.text(range),
+                    span: token.span,
+                    ..Column::default()
+                })
+            })
+            .collect();
+
+        // Options such as `WITH (security_barrier)` come before the query
+        while self.peek().is_some() && !self.at("as") {
+            if self.at("(") {
+                self.position = matching(self.source, self.tokens, self.position);
+            }
+            self.position += 1;
+        }
+        let sources = self.scan(self.rest(), false);
+        file.views.push(View { name, doc: self.doc(), columns, materialized, sources, span: self.span() });
+    }
+
+    fn function(&mut self, file: &mut File, procedure: bool) {
+        self.position += 1;
+        self.eat_all(&["if", "not", "exists"]);
+        let Some((name, _)) = self.name() else {
+            return;
+        };
+        let parameters = match self.list().as_slice() {
+            [] => String::new(),
+            elements => self.text(elements[0].start..elements[elements.len() - 1].end),
+        };
+
+        let (mut returns, mut language) = (None, None);
+        let body = self.rest();
+        while let Some(token) = self.peek() {
+            if self.eat("returns") {
+                let start = self.position;
+                while self.peek().is_some_and(|token| !token.word(self.source).is_some_and(|word| FUNCTION_CLAUSES.contains(&word.as_str()))) {
+                    if self.at("(") {
+                        self.position = matching(self.source, self.tokens, self.position);
+                    }
+                    self.position += 1;
+                }
+                returns = Some(self.text(start..self.position.min(self.tokens.len()))).filter(|text| !text.is_empty());
+            } else if self.eat("language") {
+                language = self.peek().map(|token| match token.kind {
+                    TokenKind::String => token.text(self.source).trim_matches('\'').to_ascii_lowercase(),
+                    _ => token.text(self.source).to_ascii_lowercase(),
+                });
+                self.position += 1;
+            } else if token.is(self.source, "begin") || token.is(self.source, "return") {
+                break;
+            } else {
+                self.position += 1;
+            }
+        }
+
+        // Bodies in other languages, such as `plpython3u` or `c`, hold no SQL
+        let sql = language.as_deref().is_none_or(|language| matches!(language, "sql" | "plpgsql"));
+        let sources = if sql { self.scan(body, true) } else { Vec::new() };
+        file.functions.push(Function {
+            name,
+            doc: self.doc(),
+            procedure,
+            parameters,
+            returns,
+            language,
+            sources,
+            span: self.span(),
+        });
+    }
+
+    fn index(&mut self, file: &mut File, unique: bool) {
+        self.eat("index");
+        self.eat("concurrently");
+        self.eat_all(&["if", "not", "exists"]);
+        let name = if self.at("on") { None } else { self.name().map(|(name, _)| name) };
+        if !self.eat("on") {
+            return;
+        }
+        self.eat("only");
+        let Some((table, _)) = self.name() else {
+            return;
+        };
+        if self.eat("using") {
+            self.position += 1;
+        }
+        let columns: Vec<String> = self.list().into_iter()
+            .filter(|range| !self.tokens.get(range.start + 1).is_some_and(|token| token.is(self.source, "(")))
+            .filter_map(|range| self.tokens.get(range.start).and_then(|token| token.identifier(self.source)))
+            .collect();
+
+        // PostgreSQL names an unnamed index after its table and columns
+        let name = name.unwrap_or_else(|| {
+            let table = table.rsplit('.').next().unwrap_or(&table);
+            format!("{}_{}_idx", table, columns.join("_"))
+        });
+        let name = name.rsplit('.').next().unwrap_or(&name).to_string();
+        file.indexes.push(Index { name, table, columns, unique, span: self.span() });
+    }
+
+    /// `ALTER TABLE`, whose added columns are kept; the table counts as written
+    fn alter(&mut self, file: &mut File) {
+        self.eat_all(&["alter", "table"]);
+        self.eat_all(&["if", "exists"]);
+        self.eat("only");
+        let Some((table, span)) = self.name() else {
+            return;
+        };
+        file.references.push(TableRef { name: table.clone(), access: Access::Write, span });
+
+        let mut alteration = Alteration { table, span: self.span(), ..Alteration::default() };
+        let (mut start, mut depth) = (self.position, 0usize);
+        for i in self.position..=self.tokens.len() {
+            let token = self.tokens.get(i);
+            match token {
+                Some(token) if token.is(self.source, "(") => depth += 1,
+                Some(token) if token.is(self.source, ")") => depth = depth.saturating_sub(1),
+                Some(token) if !(depth == 0 && token.is(self.source, ",")) => {}
+                _ => {
+                    let mut action = self.element(start..i);
+                    let added = action.eat("add") && !action.at_any(&["constraint", "primary", "foreign", "unique", "check", "index", "key"]);
+                    if added {
+                        action.eat("column");
+                        action.eat_all(&["if", "not", "exists"]);
+                        alteration.columns.extend(action.column());
+                    } else {
+                        file.references.extend(self.scan(action.tokens, false));
+                    }
+                    start = i + 1;
+                }
+            }
+        }
+        if !alteration.columns.is_empty() {
+            file.alterations.push(alteration);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn refs(refs: &[TableRef]) -> Vec<(&str, Access)> {
+        refs.iter().map(|table| (table.name.as_str(), table.access)).collect()
+    }
+
+    #[test]
+    fn test_parse_schema() {
+        let source = r#"-- Accounts that can sign in
+CREATE TABLE IF NOT EXISTS public.Users (
+    id serial PRIMARY KEY,
+    -- Unique, lower-cased
+    email varchar(255) NOT NULL,
+    org_id integer REFERENCES orgs (id) ON DELETE CASCADE,
+    "Display Name" text DEFAULT '',
+    CONSTRAINT users_email_key UNIQUE (email)
+);
+
+CREATE TABLE memberships (
+    user_id int,
+    team_id int,
+    PRIMARY KEY (user_id, team_id),
+    FOREIGN KEY (team_id) REFERENCES teams(id)
+);
+
+CREATE UNIQUE INDEX CONCURRENTLY ON public.users USING btree (email, lower(email));
+
+CREATE OR REPLACE VIEW active_users (id, email) AS
+    WITH recent AS (SELECT user_id FROM sessions WHERE created_at > now() - interval '1 day')
+    SELECT u.id, u.email FROM users u JOIN recent r ON r.user_id = u.id
+    WHERE EXTRACT(YEAR FROM u.created_at) > 2020;
+
+CREATE FUNCTION deactivate(p_id integer) RETURNS SETOF users AS $$
+BEGIN
+    UPDATE users SET active = false WHERE id = p_id;
+    INSERT INTO audit_log (user_id) VALUES (p_id);
+    RETURN QUERY SELECT * FROM users WHERE id = p_id;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS last_seen timestamptz, DROP COLUMN legacy;
+DELETE FROM sessions USING users WHERE sessions.user_id = users.id;
+"#;
+        let file = parse(source);
+
+        let names: Vec<&str> = file.tables.iter().map(|table| table.name.as_str()).collect();
+        assert_eq!(names, vec!["public.users", "memberships"]);
+        let users = &file.tables[0];
+        assert_eq!(users.doc.as_deref(), Some("Accounts that can sign in"));
+        assert_eq!((users.span.line, users.span.end_line), (1, 8));
+        let columns: Vec<(&str, Option<&str>, bool, bool)> = users.columns.iter()
+            .map(|column| (column.name.as_str(), column.type_name.as_deref(), column.not_null, column.primary_key))
+            .collect();
+        assert_eq!(columns, vec![
+            ("id", Some("serial"), true, true),
+            ("email", Some("varchar(255)"), true, false),
+            ("org_id", Some("integer"), false, false),
+            ("Display Name", Some("text"), false, false),
+        ]);
+        assert_eq!(users.columns[1].doc.as_deref(), Some("Unique, lower-cased"));
+        assert_eq!(users.columns[1].signature, "email varchar(255) NOT NULL");
+        let (target, column) = users.columns[2].references.as_ref().unwrap();
+        assert_eq!((target.name.as_str(), column.as_deref()), ("orgs", Some("id")));
+
+        let memberships = &file.tables[1];
+        assert!(memberships.columns.iter().all(|column| column.primary_key));
+        assert_eq!(memberships.columns[1].references.as_ref().map(|(table, _)| table.name.as_str()), Some("teams"));
+
+        let indexes: Vec<String> = file.indexes.iter().map(Index::signature).collect();
+        assert_eq!(indexes, vec![
+            "UNIQUE INDEX users_email_key ON public.users (email)",
+            "UNIQUE INDEX users_email_idx ON public.users (email)",
+        ]);
+
+        let view = &file.views[0];
+        assert_eq!(view.name, "active_users");
+        assert_eq!(view.columns.iter().map(|column| column.name.as_str()).collect::<Vec<_>>(), vec!["id", "email"]);
+        assert_eq!(refs(&view.sources), vec![("sessions", Access::Read), ("users", Access::Read)]);
+
+        let function = &file.functions[0];
+        assert_eq!(function.signature(), "deactivate(p_id integer) RETURNS SETOF users");
+        assert_eq!(function.language.as_deref(), Some("plpgsql"));
+        assert_eq!(refs(&function.sources), vec![
+            ("users", Access::Write),
+            ("audit_log", Access::Write),
+            ("users", Access::Read),
+        ]);
+        // Body tables point into the dollar-quoted string
+        assert_eq!(function.sources[0].span.line, 26);
+
+        assert_eq!(file.alterations.len(), 1);
+        assert_eq!(file.alterations[0].columns[0].signature, "last_seen timestamptz");
+        assert_eq!(refs(&file.references), vec![
+            ("users", Access::Write),
+            ("sessions", Access::Write),
+            ("users", Access::Read),
+        ]);
+    }
+
+    #[test]
+    fn test_literal_tables() {
+        let tables = |text: &str| literal_tables(text).into_iter().map(|table| table.name).collect::<Vec<_>>();
+
+        assert_eq!(tables("SELECT u.id FROM Users u, orgs WHERE u.org_id = orgs.id"), vec!["users", "orgs"]);
+        assert_eq!(tables("insert into audit_log (user_id) values (%s)"), vec!["audit_log"]);
+        assert_eq!(tables("UPDATE `users` SET name = ? WHERE id = ?"), vec!["users"]);
+        assert_eq!(tables("(SELECT * FROM [dbo].[Orders]) UNION SELECT * FROM generate_series(1, 3)"), vec!["dbo.Orders"]);
+        assert_eq!(tables("WITH x AS (SELECT 1 FROM a) SELECT * FROM x"), vec!["a"]);
+        assert_eq!(tables("SELECT * FROM {table}"), Vec::<String>::new());
+        assert_eq!(tables("Could not load from the cache"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_delimiters_and_blocks() {
+        let file = parse(concat!(
+            "DELIMITER $$\n",
+            "CREATE PROCEDURE touch(IN p INT)\n",
+            "BEGIN\n",
+            "  IF p > 0 THEN\n",
+            "    UPDATE counters SET n = CASE WHEN n > 9 THEN 0 ELSE n + 1 END;\n",
+            "  END IF;\n",
+            "END$$\n",
+            "DELIMITER ;\n",
+            "CREATE TABLE counters (n INT);\n",
+        ));
+        assert_eq!(file.functions.len(), 1);
+        assert!(file.functions[0].procedure);
+        assert_eq!(refs(&file.functions[0].sources), vec![("counters", Access::Write)]);
+        assert_eq!(file.tables[0].name, "counters");
+    }
+}
@@ -0,0 +1,562 @@
+// TypeScript and JavaScript module and import resolution
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::index::{Symbol, SymbolKind, SymbolTable};
+
+/// Maximum number of imports, re-exports and heritage clauses followed to resolve one name
+const MAX_DEPTH: usize = 8;
+
+/// Extensions tried, in order, for a module specifier without one
+const EXTENSIONS: &[&str] = &["ts", "tsx", "d.ts", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+/// JavaScript extensions a TypeScript import may name for the compiled file
+const JAVASCRIPT_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("js", &["ts", "tsx"]),
+    ("jsx", &["tsx"]),
+    ("mjs", &["mts"]),
+    ("cjs", &["cts"]),
+];
+
+/// Name bound or re-exported by an `import` or `export` statement, kept in
+/// the metadata of `Import` symbols
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportBinding {
+    /// Local name, exported name for a re-export, or `*` for `export * from`
+    pub alias: String,
+    /// Module specifier, absent for `export { a as b }` of a local name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Name taken from the module, `default` included; absent for the namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Whether the binding is exported rather than imported
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub export: bool,
+}
+
+/// What a name or member expression refers to
+#[derive(Clone)]
+enum Target<'a> {
+    /// Namespace of an indexed module file
+    Module(String),
+    Symbol(&'a Symbol),
+}
+
+/// Top-level view of one module
+struct ModuleScope<'a> {
+    file: String,
+    /// Symbols by qualified name, imports excluded
+    symbols: HashMap<&'a str, &'a Symbol>,
+    imports: HashMap<String, ImportBinding>,
+    /// Re-exports and export lists, by exported name
+    exports: HashMap<String, ImportBinding>,
+    /// Sources of `export * from`
+    star_exports: Vec<String>,
+}
+
+/// `baseUrl` and `paths` of a `tsconfig.json` or `jsconfig.json`, with
+/// those of the configs it extends merged in
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TsConfig {
+    base_url: Option<PathBuf>,
+    /// Directory `paths` targets are relative to when there is no `baseUrl`
+    paths_base: PathBuf,
+    /// Patterns with their targets, longest prefix first
+    paths: Vec<(String, Vec<String>)>,
+}
+
+impl TsConfig {
+    /// Nearest config in `directory` or one of its parents
+    pub fn find(directory: &Path) -> Option<Self> {
+        directory.ancestors()
+            .flat_map(|dir| ["tsconfig.json", "jsconfig.json"].map(|name| dir.join(name)))
+            .find(|path| path.is_file())
+            .and_then(|path| Self::load(&path, 0))
+    }
+
+    fn load(path: &Path, depth: usize) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let json: serde_json::Value = serde_json::from_str(&strip_json_comments(&content)).ok()?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        // Relative `extends` only; package configs live in node_modules
+        let mut config = json["extends"].as_str()
+            .filter(|parent| parent.starts_with('.') && depth < MAX_DEPTH)
+            .and_then(|parent| {
+                let parent = normalize(&directory.join(parent));
+                let parent = if parent.is_file() { parent } else { PathBuf::from(format!("{}.json", parent.display())) };
+                Self::load(&parent, depth + 1)
+            })
+            .unwrap_or_else(|| Self { paths_base: directory.to_path_buf(), ..Self::default() });
+
+        let options = &json["compilerOptions"];
+        if let Some(base_url) = options["baseUrl"].as_str() {
+            config.base_url = Some(normalize(&directory.join(base_url)));
+        }
+        if let Some(paths) = options["paths"].as_object() {
+            config.paths_base = directory.to_path_buf();
+            config.paths = paths.iter()
+                .map(|(pattern, targets)| {
+                    let targets = targets.as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|target| target.as_str().map(str::to_string))
+                        .collect();
+                    (pattern.clone(), targets)
+                })
+                .collect();
+            config.paths.sort_by_key(|(pattern, _)| std::cmp::Reverse(pattern.split('*').next().unwrap_or("").len()));
+        }
+        Some(config)
+    }
+
+    /// Module paths a bare specifier may map to, without extension
+    pub fn candidates(&self, specifier: &str) -> Vec<PathBuf> {
+        let base = self.base_url.as_deref().unwrap_or(&self.paths_base);
+        let mut candidates = Vec::new();
+        for (pattern, targets) in &self.paths {
+            let matched = match pattern.split_once('*') {
+                Some((prefix, suffix)) => specifier.strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_suffix(suffix)),
+                None => (pattern == specifier).then_some(""),
+            };
+            if let Some(matched) = matched {
+                candidates.extend(targets.iter().map(|target| normalize(&base.join(target.replace('*', matched)))));
+                break;
+            }
+        }
+        if let Some(base_url) = &self.base_url {
+            candidates.push(normalize(&base_url.join(specifier)));
+        }
+        candidates
+    }
+}
+
+/// Resolves ES module names against the symbol table
+///
+/// Bare names are looked up in the enclosing function scopes, then the module
+/// and its imports. Imported names are followed through the exporting module,
+/// its export lists and `export * from` re-exports. Relative specifiers resolve
+/// from the importing file, others through the nearest `tsconfig.json` `paths`
+/// and `baseUrl`. `this` and `super` resolve against the enclosing class, and
+/// members of a variable, field or parameter through its declared type.
+pub struct Resolver<'t, 'a> {
+    table: &'t SymbolTable<'a>,
+    scopes: RefCell<HashMap<String, Rc<ModuleScope<'a>>>>,
+    configs: RefCell<HashMap<PathBuf, Option<Rc<TsConfig>>>>,
+}
+
+impl<'t, 'a> Resolver<'t, 'a> {
+    pub fn new(table: &'t SymbolTable<'a>) -> Self {
+        Self {
+            table,
+            scopes: RefCell::new(HashMap::new()),
+            configs: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Symbol defined in `file` with the given qualified name
+    pub fn symbol(&self, file: &str, qualified_name: &str) -> Option<&'a Symbol> {
+        self.scope(file).symbols.get(qualified_name).copied()
+    }
+
+    /// Resolve a member expression such as `api.get` or `this.save`, used
+    /// inside the definition named by `context`
+    pub fn resolve(&self, file: &str, context: &[String], path: &[String]) -> Option<&'a Symbol> {
+        let scope = self.scope(file);
+        let (first, rest) = path.split_first()?;
+
+        let (mut target, rest) = match first.as_str() {
+            "this" => (Target::Symbol(self.enclosing_class(&scope, context)?), rest),
+            "super" => {
+                let class = self.enclosing_class(&scope, context)?;
+                let base = self.bases(class, "extends", 0).into_iter().next()?;
+                match rest.split_first() {
+                    Some((name, rest)) => (Target::Symbol(self.class_member(base, name, 1)?), rest),
+                    // `super(...)` calls the base constructor
+                    None => return Some(self.class_member(base, "constructor", 1).unwrap_or(base)),
+                }
+            }
+            name => (self.lookup_local(&scope, context, name)?, rest),
+        };
+
+        for segment in rest {
+            target = self.member(target, segment, 0)?;
+        }
+
+        match target {
+            Target::Symbol(symbol) => Some(symbol),
+            Target::Module(_) => None,
+        }
+    }
+
+    /// Resolve a type reference such as `Base` or `models.Base` written in `file`
+    pub fn resolve_type(&self, file: &str, context: &[String], type_name: &str) -> Option<&'a Symbol> {
+        let path: Vec<String> = type_name.split('<').next().unwrap_or(type_name)
+            .split('.')
+            .map(|segment| segment.trim().to_string())
+            .collect();
+        self.resolve(file, context, &path).filter(|symbol| is_type(symbol))
+    }
+
+    fn scope(&self, file: &str) -> Rc<ModuleScope<'a>> {
+        if let Some(scope) = self.scopes.borrow().get(file) {
+            return scope.clone();
+        }
+
+        let mut scope = ModuleScope {
+            file: file.to_string(),
+            symbols: HashMap::new(),
+            imports: HashMap::new(),
+            exports: HashMap::new(),
+            star_exports: Vec::new(),
+        };
+        for &symbol in self.table.in_file(file) {
+            if symbol.kind != SymbolKind::Import {
+                scope.symbols.entry(symbol.qualified_name.as_str()).or_insert(symbol);
+                continue;
+            }
+            let bindings: Vec<ImportBinding> = serde_json::from_value(symbol.metadata["bindings"].clone())
+                .unwrap_or_default();
+            for binding in bindings {
+                match (binding.export, binding.alias.as_str(), &binding.source) {
+                    (true, "*", Some(source)) => scope.star_exports.push(source.clone()),
+                    (true, _, _) => { scope.exports.insert(binding.alias.clone(), binding); }
+                    (false, _, _) => { scope.imports.insert(binding.alias.clone(), binding); }
+                }
+            }
+        }
+
+        let scope = Rc::new(scope);
+        self.scopes.borrow_mut().insert(file.to_string(), scope.clone());
+        scope
+    }
+
+    /// Look up a bare name from inside a definition: enclosing function scopes
+    /// and their parameters first, class bodies being skipped, then the module
+    fn lookup_local(&self, scope: &ModuleScope<'a>, context: &[String], name: &str) -> Option<Target<'a>> {
+        for depth in (1..=context.len()).rev() {
+            let enclosing = context[..depth].join(".");
+            let enclosing_symbol = scope.symbols.get(enclosing.as_str()).copied();
+            if enclosing_symbol.is_some_and(|s| s.kind == SymbolKind::Class) {
+                continue;
+            }
+            if let Some(&symbol) = scope.symbols.get(format!("{}.{}", enclosing, name).as_str()) {
+                return Some(Target::Symbol(symbol));
+            }
+            let parameter_type = enclosing_symbol.and_then(|function| parameter_type(function, name));
+            if let Some(type_name) = parameter_type {
+                return self.resolve_type(&scope.file, &context[..depth], &type_name).map(Target::Symbol);
+            }
+        }
+
+        self.lookup_global(scope, name, 0)
+    }
+
+    /// Look up a name at module level: declarations, then imports
+    fn lookup_global(&self, scope: &ModuleScope<'a>, name: &str, depth: usize) -> Option<Target<'a>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        if let Some(&symbol) = scope.symbols.get(name) {
+            return Some(Target::Symbol(symbol));
+        }
+
+        let binding = scope.imports.get(name)?;
+        let file = self.module_file(&scope.file, binding.source.as_deref()?)?;
+        match &binding.name {
+            Some(name) => self.export(&file, name, depth + 1),
+            None => Some(Target::Module(file)),
+        }
+    }
+
+    /// Name exported by a module
+    fn export(&self, file: &str, name: &str, depth: usize) -> Option<Target<'a>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let scope = self.scope(file);
+
+        let declared = scope.symbols.values()
+            .filter(|symbol| is_exported(symbol) && !symbol.qualified_name.contains('.'))
+            .find(|symbol| match name {
+                "default" => symbol.metadata["default"].as_bool().unwrap_or(false),
+                name => symbol.name == name && !symbol.metadata["default"].as_bool().unwrap_or(false),
+            });
+        if let Some(&symbol) = declared {
+            return Some(Target::Symbol(symbol));
+        }
+
+        if let Some(binding) = scope.exports.get(name) {
+            return match (&binding.source, &binding.name) {
+                (Some(source), Some(name)) => self.export(&self.module_file(file, source)?, name, depth + 1),
+                (Some(source), None) => self.module_file(file, source).map(Target::Module),
+                (None, Some(local)) => self.lookup_global(&scope, local, depth + 1),
+                (None, None) => None,
+            };
+        }
+
+        // `export *` never re-exports a default
+        if name == "default" {
+            return None;
+        }
+        scope.star_exports.iter()
+            .filter_map(|source| self.module_file(file, source))
+            .find_map(|module| self.export(&module, name, depth + 1))
+    }
+
+    /// Member `name` of a module namespace, class, interface, enum or
+    /// namespace, or of the declared type of a variable or field
+    fn member(&self, target: Target<'a>, name: &str, depth: usize) -> Option<Target<'a>> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        match target {
+            Target::Module(file) => self.export(&file, name, depth + 1),
+            Target::Symbol(symbol) if is_type(symbol) || symbol.kind == SymbolKind::Module => {
+                self.class_member(symbol, name, depth).map(Target::Symbol)
+            }
+            Target::Symbol(symbol) => {
+                let type_name = symbol.metadata["type"].as_str()?;
+                let context: Vec<String> = symbol.qualified_name.split('.').map(str::to_string).collect();
+                let ty = self.resolve_type(&symbol.location.file, &context[..context.len() - 1], type_name)?;
+                self.class_member(ty, name, depth + 1).map(Target::Symbol)
+            }
+        }
+    }
+
+    /// Member of a type or, following `extends` clauses, of its ancestors
+    fn class_member(&self, class: &'a Symbol, name: &str, depth: usize) -> Option<&'a Symbol> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let scope = self.scope(&class.location.file);
+        let qualified_name = format!("{}.{}", class.qualified_name, name);
+        if let Some(&member) = scope.symbols.get(qualified_name.as_str()) {
+            return Some(member);
+        }
+
+        self.bases(class, "extends", depth)
+            .into_iter()
+            .find_map(|base| self.class_member(base, name, depth + 1))
+    }
+
+    /// Types named by a heritage clause of a class or interface, in declaration order
+    fn bases(&self, class: &'a Symbol, clause: &str, depth: usize) -> Vec<&'a Symbol> {
+        if depth > MAX_DEPTH {
+            return Vec::new();
+        }
+        heritage(class, clause)
+            .filter_map(|base| self.resolve_base(class, base))
+            .collect()
+    }
+
+    /// Resolve one heritage clause entry of `class`, such as `models.Base`
+    pub fn resolve_base(&self, class: &'a Symbol, base: &str) -> Option<&'a Symbol> {
+        let context: Vec<String> = class.qualified_name.split('.').map(str::to_string).collect();
+        self.resolve_type(&class.location.file, &context[..context.len() - 1], base)
+            .filter(|base| !std::ptr::eq(*base, class))
+    }
+
+    fn enclosing_class(&self, scope: &ModuleScope<'a>, context: &[String]) -> Option<&'a Symbol> {
+        (1..=context.len()).rev()
+            .filter_map(|depth| scope.symbols.get(context[..depth].join(".").as_str()).copied())
+            .find(|symbol| symbol.kind == SymbolKind::Class)
+    }
+
+    /// Indexed file a module specifier imports from `file`
+    fn module_file(&self, file: &str, specifier: &str) -> Option<String> {
+        let directory = Path::new(file).parent().unwrap_or(Path::new(""));
+        let bases = if specifier.starts_with("./") || specifier.starts_with("../") || specifier == "." || specifier == ".." {
+            vec![normalize(&directory.join(specifier))]
+        } else {
+            self.config(directory).map(|config| config.candidates(specifier)).unwrap_or_default()
+        };
+
+        bases.iter()
+            .flat_map(|base| file_candidates(base))
+            .map(|path| path.to_string_lossy().to_string())
+            .find(|path| self.table.has_file(path))
+    }
+
+    fn config(&self, directory: &Path) -> Option<Rc<TsConfig>> {
+        if let Some(config) = self.configs.borrow().get(directory) {
+            return config.clone();
+        }
+        let config = TsConfig::find(directory).map(Rc::new);
+        self.configs.borrow_mut().insert(directory.to_path_buf(), config.clone());
+        config
+    }
+}
+
+pub fn is_type(symbol: &Symbol) -> bool {
+    matches!(symbol.kind, SymbolKind::Class | SymbolKind::Type)
+}
+
+pub fn is_exported(symbol: &Symbol) -> bool {
+    symbol.metadata["exported"].as_bool().unwrap_or(false)
+}
+
+/// Entries of the `extends` or `implements` clause recorded on a class or interface
+pub fn heritage<'s>(class: &'s Symbol, clause: &str) -> impl Iterator<Item = &'s str> {
+    class.metadata[clause].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|base| base.as_str())
+}
+
+/// Declared type of a parameter of a function symbol
+fn parameter_type(function: &Symbol, name: &str) -> Option<String> {
+    function.metadata["parameters"].as_array()?
+        .iter()
+        .find(|parameter| parameter["name"].as_str() == Some(name))
+        .and_then(|parameter| parameter["type"].as_str())
+        .map(str::to_string)
+}
+
+/// Files a module path may refer to, most likely first
+fn file_candidates(base: &Path) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    let extension = base.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if let Some((_, sources)) = JAVASCRIPT_EXTENSIONS.iter().find(|(js, _)| *js == extension) {
+        candidates.extend(sources.iter().map(|source| base.with_extension(source)));
+    }
+    if EXTENSIONS.contains(&extension) {
+        candidates.push(base.to_path_buf());
+    }
+
+    let name = base.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    candidates.extend(EXTENSIONS.iter().map(|ext| base.with_file_name(format!("{}.{}", name, ext))));
+    candidates.extend(EXTENSIONS.iter().map(|ext| base.join(format!("index.{}", ext))));
+    candidates
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// `tsconfig.json` allows comments and trailing commas
+fn strip_json_comments(content: &str) -> String {
+    let mut stripped = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            stripped.push(c);
+            match c {
+                '\\' => stripped.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                stripped.push(c);
+            }
+            ('/', Some('/')) => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == '*' && chars.next_if_eq(&'/').is_some() {
+                        break;
+                    }
+                }
+            }
+            (',', _) => {
+                // Dropped when only whitespace separates it from a closing bracket
+                let rest: String = chars.clone().take_while(|c| c.is_whitespace()).collect();
+                let next = chars.clone().nth(rest.chars().count());
+                if !matches!(next, Some('}' | ']')) {
+                    stripped.push(c);
+                }
+            }
+            _ => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Parser;
+    use crate::indexer::parser::TypeScriptParser;
+
+    fn parse(files: &[(&str, &str)]) -> Vec<Symbol> {
+        files.iter()
+            .flat_map(|(path, source)| TypeScriptParser::new().parse(source, path).unwrap().0)
+            .collect()
+    }
+
+    fn resolve(table: &SymbolTable, file: &str, context: &[&str], path: &str) -> Option<String> {
+        let context: Vec<String> = context.iter().map(|s| s.to_string()).collect();
+        let path: Vec<String> = path.split('.').map(str::to_string).collect();
+        Resolver::new(table).resolve(file, &context, &path).map(|s| s.id.clone())
+    }
+
+    #[test]
+    fn test_resolves_imports_and_reexports() {
+        let symbols = parse(&[
+            ("proj/src/main.ts", "import Api, { load as fetch } from './api';\nimport * as models from './models/index.js';\nimport { User } from './models';\n"),
+            ("proj/src/api.ts", "export default class Api {\n  get(): void {}\n}\nexport function load() {}\n"),
+            ("proj/src/models/index.ts", "export * from './user';\nexport { Base as Model } from './base';\n"),
+            ("proj/src/models/user.ts", "import { Base } from './base';\nexport class User extends Base {}\n"),
+            ("proj/src/models/base.ts", "export class Base {\n  save(): void {}\n}\nclass Hidden {}\n"),
+        ]);
+        let table = SymbolTable::new(&symbols);
+        let main = "proj/src/main.ts";
+
+        assert_eq!(resolve(&table, main, &["run"], "Api.get").as_deref(), Some("proj/src/api.ts:Api.get"));
+        assert_eq!(resolve(&table, main, &["run"], "fetch").as_deref(), Some("proj/src/api.ts:load"));
+        assert_eq!(resolve(&table, main, &["run"], "models.User").as_deref(), Some("proj/src/models/user.ts:User"));
+        assert_eq!(resolve(&table, main, &["run"], "models.Model").as_deref(), Some("proj/src/models/base.ts:Base"));
+        // Inherited through `extends`
+        assert_eq!(resolve(&table, main, &["run"], "User.save").as_deref(), Some("proj/src/models/base.ts:Base.save"));
+        // Not exported
+        assert_eq!(resolve(&table, main, &["run"], "models.Hidden"), None);
+    }
+
+    #[test]
+    fn test_tsconfig_paths_and_base_url() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("tsconfig.base.json"), r#"{
+            // Shared options
+            "compilerOptions": {
+                "baseUrl": "./src",
+                "paths": { "@app/*": ["app/*"], "@lib": ["lib/index"], },
+            },
+        }"#).unwrap();
+        std::fs::write(dir.path().join("tsconfig.json"), r#"{ "extends": "./tsconfig.base" }"#).unwrap();
+
+        let config = TsConfig::find(&dir.path().join("src/app")).unwrap();
+        let src = dir.path().join("src");
+        assert_eq!(config.candidates("@app/api/client"), vec![src.join("app/api/client"), src.join("@app/api/client")]);
+        assert_eq!(config.candidates("@lib"), vec![src.join("lib/index"), src.join("@lib")]);
+        assert_eq!(config.candidates("utils/strings"), vec![src.join("utils/strings")]);
+    }
+}
@@ -1,37 +1,40 @@
 // File watcher for incremental updates
 
-use std::collections::HashSet;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use parking_lot::Mutex;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info};
 
+use crate::indexer::scanner::ProjectScanner;
 use crate::indexer::Indexer;
 
+/// Quiet period used to coalesce the burst of events a single save produces
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Longest a burst is drained before its events are processed, so a steady
+/// stream of events, such as a build writing files, cannot hold updates back
+const MAX_BATCH_WAIT: Duration = Duration::from_secs(2);
+
 /// File watcher for automatic re-indexing
 pub struct FileWatcher {
     indexer: Arc<Indexer>,
+    scanner: ProjectScanner,
     watch_path: PathBuf,
-    extensions: HashSet<String>,
 }
 
 impl FileWatcher {
     /// Create a new file watcher
-    pub fn new(indexer: Arc<Indexer>, watch_path: PathBuf) -> Self {
-        let mut extensions = HashSet::new();
-        extensions.insert("py".to_string());
-        extensions.insert("rs".to_string());
-        extensions.insert("go".to_string());
-        extensions.insert("java".to_string());
-        extensions.insert("intent".to_string());
-
+    ///
+    /// Events are filtered with the same scanner rules used for the initial index.
+    pub fn new(indexer: Arc<Indexer>, scanner: ProjectScanner, watch_path: PathBuf) -> Self {
         Self {
             indexer,
+            scanner,
             watch_path,
-            extensions,
         }
     }
 
@@ -67,13 +70,25 @@ impl FileWatcher {
         // Keep watcher alive by moving it into the loop
         loop {
             // Use blocking recv in spawn_blocking to avoid blocking the async runtime
-            let event = match tokio::task::spawn_blocking({
+            let events = match tokio::task::spawn_blocking({
                 let rx = Arc::clone(&rx);
-                move || rx.lock().recv()
+                move || {
+                    let rx = rx.lock();
+                    let mut events = vec![rx.recv()?];
+                    // Drain the rest of the burst so each path is processed once
+                    let deadline = Instant::now() + MAX_BATCH_WAIT;
+                    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                        match rx.recv_timeout(DEBOUNCE.min(remaining)) {
+                            Ok(event) => events.push(event),
+                            Err(_) => break,
+                        }
+                    }
+                    Ok::<_, std::sync::mpsc::RecvError>(events)
+                }
             })
             .await
             {
-                Ok(Ok(event)) => event,
+                Ok(Ok(events)) => events,
                 Ok(Err(_)) => break, // Channel closed
                 Err(e) => {
                     error!("Spawn blocking error: {}", e);
@@ -81,8 +96,8 @@ impl FileWatcher {
                 }
             };
 
-            if let Err(e) = self.handle_event(event).await {
-                error!("Error handling event: {}", e);
+            if let Err(e) = self.handle_events(events).await {
+                error!("Error handling events: {}", e);
             }
         }
 
@@ -92,118 +107,100 @@ impl FileWatcher {
         Ok(())
     }
 
-    /// Handle a file system event
-    async fn handle_event(&self, event: Event) -> Result<()> {
-        debug!("File event: {:?}", event);
-
-        match event.kind {
-            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
-                // Check if any of the changed paths are files we care about
-                for path in &event.paths {
-                    if self.should_index_file(path) {
-                        self.handle_file_change(path, &event.kind).await?;
+    /// Handle a batch of file system events
+    ///
+    /// Only the last event seen for each path is acted upon.
+    async fn handle_events(&self, events: Vec<Event>) -> Result<()> {
+        let mut changes: Vec<(PathBuf, EventKind)> = Vec::new();
+
+        for event in events {
+            debug!("File event: {:?}", event);
+
+            match event.kind {
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
+                    // Check if any of the changed paths are files we care about
+                    for path in event.paths {
+                        if let Some(path) = self.scanner.accept(&path, self.indexer.registry()) {
+                            changes.retain(|(seen, _)| *seen != path);
+                            changes.push((path, event.kind));
+                        }
                     }
                 }
-            }
-            _ => {
-                // Ignore other event types
+                _ => {
+                    // Ignore other event types
+                }
             }
         }
 
-        Ok(())
-    }
-
-    /// Handle a file change event
-    async fn handle_file_change(&self, path: &Path, kind: &EventKind) -> Result<()> {
-        let path_str = path.to_string_lossy();
-
-        match kind {
-            EventKind::Create(_) => {
-                info!("File created: {}", path_str);
-                self.index_file(&path_str).await?;
-            }
-            EventKind::Modify(_) => {
-                info!("File modified: {}", path_str);
-                self.index_file(&path_str).await?;
-            }
-            EventKind::Remove(_) => {
-                info!("File removed: {}", path_str);
-                self.remove_file(&path_str).await?;
+        let mut changed = Vec::new();
+        let mut removed = Vec::new();
+        for (path, kind) in changes {
+            let path_str = path.to_string_lossy().to_string();
+            match kind {
+                EventKind::Create(_) => {
+                    info!("File created: {}", path_str);
+                    changed.push(path_str);
+                }
+                EventKind::Modify(_) if !path.exists() => {
+                    // Renames report the old path as a modification
+                    info!("File moved away: {}", path_str);
+                    removed.push(path_str);
+                }
+                EventKind::Modify(_) => {
+                    info!("File modified: {}", path_str);
+                    changed.push(path_str);
+                }
+                EventKind::Remove(_) => {
+                    info!("File removed: {}", path_str);
+                    removed.push(path_str);
+                }
+                _ => {}
             }
-            _ => {}
         }
 
-        Ok(())
+        self.update(changed, removed).await
     }
 
-    /// Check if a file should be indexed
-    fn should_index_file(&self, path: &Path) -> bool {
-        if !path.is_file() {
-            return false;
+    /// Index changed files and purge removed ones in one batch, re-resolving
+    /// the files depending on them once
+    async fn update(&self, changed: Vec<String>, removed: Vec<String>) -> Result<()> {
+        if changed.is_empty() && removed.is_empty() {
+            return Ok(());
         }
-
-        if let Some(extension) = path.extension() {
-            if let Some(ext_str) = extension.to_str() {
-                return self.extensions.contains(ext_str);
-            }
-        }
-
-        false
-    }
-
-    /// Index a single file
-    async fn index_file(&self, file_path: &str) -> Result<()> {
-        debug!("Indexing file: {}", file_path);
-
-        // Read the file content
-        let content = match std::fs::read_to_string(file_path) {
-            Ok(content) => content,
-            Err(e) => {
-                warn!("Failed to read file {}: {}", file_path, e);
-                return Ok(()); // Don't fail the watcher for read errors
-            }
-        };
-
-        // Index the file
-        match self.indexer.index_file(file_path, &content).await {
-            Ok((symbols, _relationships)) => {
-                info!("Indexed {}: {} symbols", file_path, symbols.len());
+        debug!("Updating {} changed and {} removed files", changed.len(), removed.len());
+
+        let indexer = Arc::clone(&self.indexer);
+        match tokio::task::spawn_blocking(move || indexer.update_files(&changed, &removed)).await? {
+            Ok(update) => {
+                info!(
+                    "Indexed {} files ({} unchanged, {} failed) and removed {}: {} symbols, {} relationships, {} dependents re-resolved",
+                    update.added + update.modified, update.unchanged, update.failed, update.removed,
+                    update.symbols, update.relationships, update.dependents.len()
+                );
                 Ok(())
             }
             Err(e) => {
-                error!("Failed to index {}: {}", file_path, e);
+                error!("Failed to update the index: {}", e);
                 Ok(()) // Don't fail the watcher for indexing errors
             }
         }
     }
-
-    /// Remove a file from the index
-    async fn remove_file(&self, file_path: &str) -> Result<()> {
-        debug!("Removing file from index: {}", file_path);
-
-        // For now, we'll just log this - full removal would require
-        // deleting symbols and relationships from the database
-        // TODO: Implement proper file removal
-        info!("File removal not yet implemented: {}", file_path);
-
-        Ok(())
-    }
 }
 
 /// Start the file watcher for a project
-pub async fn start_watcher(project_path: &str, watch: bool) -> Result<()> {
+///
+/// The watcher shares the caller's indexer, and so its parsers and caches.
+pub async fn start_watcher(indexer: Arc<Indexer>, project_path: &str, watch: bool) -> Result<()> {
     if !watch {
         return Ok(());
     }
 
     info!("Initializing file watcher for project: {}", project_path);
 
-    // Initialize indexer
-    let db_path = PathBuf::from(project_path).join(".codegraph.db");
-    let indexer = Arc::new(Indexer::new(&db_path)?);
+    let scanner = ProjectScanner::new(project_path, indexer.config())?;
 
     // Create and start watcher
-    let watcher = FileWatcher::new(indexer, PathBuf::from(project_path));
+    let watcher = FileWatcher::new(indexer, scanner, PathBuf::from(project_path));
 
     // Run the watcher (this will block)
     watcher.watch().await?;
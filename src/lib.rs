@@ -0,0 +1,8 @@
+//! CodeGraph: real-time semantic code index for AI agents via MCP
+
+pub mod cli;
+pub mod config;
+pub mod index;
+pub mod indexer;
+pub mod mcp;
+pub mod query;
@@ -1,14 +1,8 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use tracing::{info, Level};
-use tracing_subscriber;
 
-mod cli;
-mod config;
-mod index;
-mod indexer;
-mod mcp;
-mod query;
+use codegraph::cli;
 
 #[derive(Parser)]
 #[command(name = "codegraph")]
@@ -85,12 +79,62 @@ enum Commands {
 
     /// Query the index
     Query {
-        /// Query type: callers, callees, references, deps
+        /// Query type: callers, callees, transitive_callers, transitive_callees, supertypes, subtypes, path, references, dependencies
         query_type: String,
 
-        /// Target symbol
+        /// Target symbol, or the start of a path
         target: String,
 
+        /// End of a path query
+        #[arg(short, long)]
+        to: Option<String>,
+
+        /// Maximum depth for transitive, type hierarchy and path queries (capped by query.max_depth)
+        #[arg(long)]
+        depth: Option<usize>,
+
+        /// Maximum number of paths returned by a path query
+        #[arg(long, default_value = "10")]
+        max_paths: usize,
+
+        /// Project directory
+        #[arg(short, long, default_value = ".")]
+        project: String,
+
+        /// Output format: json, text
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Search symbols by name, with optional filters
+    Search {
+        /// Name, qualified name, prefix (`get_*`) or abbreviation to search for
+        query: String,
+
+        /// Symbol kind, such as function, method or class
+        #[arg(short, long)]
+        kind: Option<String>,
+
+        /// Language, such as python or rust
+        #[arg(short, long)]
+        language: Option<String>,
+
+        /// Glob over the file path, or a substring of it
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Visibility: public, private, internal
+        #[arg(long)]
+        visibility: Option<String>,
+
+        /// Decorator, with or without its arguments
+        #[arg(long)]
+        decorator: Option<String>,
+
+        /// Maximum number of results
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+
         /// Project directory
         #[arg(short, long, default_value = ".")]
         project: String,
@@ -117,6 +161,40 @@ enum Commands {
         project: String,
     },
 
+    /// Report functions, methods and classes that nothing calls or references
+    Deadcode {
+        /// Project directory
+        #[arg(short, long, default_value = ".")]
+        project: String,
+
+        /// Minimum confidence: low, medium, high
+        #[arg(long, default_value = "low")]
+        min_confidence: String,
+
+        /// Output format: json, text
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Find dependency cycles between symbols, files or packages
+    Cycles {
+        /// Project directory
+        #[arg(short, long, default_value = ".")]
+        project: String,
+
+        /// Scope: symbol, file, package
+        #[arg(short, long, default_value = "file")]
+        scope: String,
+
+        /// Comma-separated relationship types (default: calls,references,depends_on,extends,implements)
+        #[arg(short, long)]
+        types: Option<String>,
+
+        /// Output format: json, text
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
     /// Show index statistics
     Stats {
         /// Project directory
@@ -211,10 +289,29 @@ async fn main() -> Result<()> {
         Commands::Query {
             query_type,
             target,
+            to,
+            depth,
+            max_paths,
+            project,
+            format,
+        } => {
+            let options = cli::query::QueryOptions { to, depth, max_paths };
+            cli::query::query_index(query_type, target, options, project, format).await?;
+        }
+
+        Commands::Search {
+            query,
+            kind,
+            language,
+            file,
+            visibility,
+            decorator,
+            limit,
             project,
             format,
         } => {
-            cli::query::query_index(query_type, target, project, format).await?;
+            let filters = codegraph::index::db::SearchFilters { kind, language, file, visibility, decorator };
+            cli::search::search_symbols(query, filters, limit, project, format).await?;
         }
 
         Commands::Impact {
@@ -226,6 +323,23 @@ async fn main() -> Result<()> {
             cli::impact::analyze_impact(change_type, target, to, project).await?;
         }
 
+        Commands::Deadcode {
+            project,
+            min_confidence,
+            format,
+        } => {
+            cli::deadcode::find_dead_code(project, min_confidence, format).await?;
+        }
+
+        Commands::Cycles {
+            project,
+            scope,
+            types,
+            format,
+        } => {
+            cli::cycles::find_cycles(project, scope, types, format).await?;
+        }
+
         Commands::Stats { project, verbose } => {
             cli::stats::show_stats(project, verbose).await?;
         }
@@ -3,7 +3,8 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::io::{self, BufRead, Write};
+use std::io::{self, Write};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info};
 
@@ -60,11 +61,11 @@ struct InitializeResult {
 
 /// MCP server
 pub struct McpServer {
-    indexer: Indexer,
+    indexer: Arc<Indexer>,
 }
 
 impl McpServer {
-    pub fn new(indexer: Indexer) -> Self {
+    pub fn new(indexer: Arc<Indexer>) -> Self {
         Self { indexer }
     }
 
@@ -78,9 +79,7 @@ impl McpServer {
         let tx_clone = tx.clone();
         tokio::spawn(async move {
             let stdin = io::stdin();
-            let mut lines = stdin.lines();
-
-            while let Some(line) = lines.next() {
+            for line in stdin.lines() {
                 match line {
                     Ok(line) => {
                         if let Err(e) = tx_clone.send(line) {
@@ -222,12 +221,25 @@ impl McpServer {
                     "properties": {
                         "query_type": {
                             "type": "string",
-                            "enum": ["callers", "callees", "references", "dependencies"],
+                            "enum": ["callers", "callees", "transitive_callers", "transitive_callees", "supertypes", "subtypes", "path", "references", "dependencies"],
                             "description": "Type of query to perform"
                         },
                         "target": {
                             "type": "string",
-                            "description": "Target symbol to query"
+                            "description": "Target symbol to query, or the start of a path"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "End symbol of a path query"
+                        },
+                        "depth": {
+                            "type": "integer",
+                            "description": "Maximum depth for transitive, type hierarchy and path queries, capped by query.max_depth"
+                        },
+                        "max_paths": {
+                            "type": "integer",
+                            "default": 10,
+                            "description": "Maximum number of paths returned by a path query"
                         },
                         "format": {
                             "type": "string",
@@ -241,7 +253,7 @@ impl McpServer {
             },
             Tool {
                 name: "codegraph_search".to_string(),
-                description: "Search for symbols by name or content".to_string(),
+                description: "Ranked search for symbols by name or signature. Words match prefixes of camelCase/snake_case parts, \"quotes\" match phrases and abbreviations like usrmgr are expanded".to_string(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
@@ -251,9 +263,26 @@ impl McpServer {
                         },
                         "kind": {
                             "type": "string",
-                            "enum": ["function", "class", "variable", "method", "field"],
+                            "enum": ["function", "type", "variable", "context", "module", "class", "method", "field", "parameter", "import"],
                             "description": "Filter by symbol kind"
                         },
+                        "language": {
+                            "type": "string",
+                            "description": "Filter by language, e.g. python"
+                        },
+                        "file": {
+                            "type": "string",
+                            "description": "Filter by file path glob or substring"
+                        },
+                        "visibility": {
+                            "type": "string",
+                            "enum": ["public", "private", "internal"],
+                            "description": "Filter by visibility"
+                        },
+                        "decorator": {
+                            "type": "string",
+                            "description": "Filter by decorator or annotation, e.g. property or app.route"
+                        },
                         "limit": {
                             "type": "integer",
                             "default": 10,
@@ -263,6 +292,52 @@ impl McpServer {
                     "required": ["query"]
                 }),
             },
+            Tool {
+                name: "codegraph_dead_code".to_string(),
+                description: "List functions, methods and classes that nothing calls or references, grouped by file and kind, with a confidence level".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "min_confidence": {
+                            "type": "string",
+                            "enum": ["low", "medium", "high"],
+                            "default": "low",
+                            "description": "Only report symbols at or above this confidence"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["text", "json"],
+                            "default": "text",
+                            "description": "Output format"
+                        }
+                    }
+                }),
+            },
+            Tool {
+                name: "codegraph_cycles".to_string(),
+                description: "Find dependency cycles (strongly connected components) between symbols, files or packages, with the edges that close each cycle".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "scope": {
+                            "type": "string",
+                            "enum": ["symbol", "file", "package"],
+                            "default": "file",
+                            "description": "Granularity of the graph"
+                        },
+                        "types": {
+                            "type": "string",
+                            "description": "Comma-separated relationship types, default calls,references,depends_on,extends,implements"
+                        },
+                        "format": {
+                            "type": "string",
+                            "enum": ["text", "json"],
+                            "default": "text",
+                            "description": "Output format"
+                        }
+                    }
+                }),
+            },
             Tool {
                 name: "codegraph_stats".to_string(),
                 description: "Get index statistics".to_string(),
@@ -285,6 +360,8 @@ impl McpServer {
         match tool_name {
             "codegraph_query" => tools::query(&self.indexer, &args_hashmap).await,
             "codegraph_search" => tools::search(&self.indexer, &args_hashmap).await,
+            "codegraph_dead_code" => tools::dead_code(&self.indexer, &args_hashmap).await,
+            "codegraph_cycles" => tools::cycles(&self.indexer, &args_hashmap).await,
             "codegraph_stats" => tools::stats(&self.indexer, &args_hashmap).await,
             _ => Err(anyhow::anyhow!("Unknown tool: {}", tool_name)),
         }
@@ -3,9 +3,13 @@
 use anyhow::Result;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::str::FromStr;
 
+use crate::index::db::SearchFilters;
 use crate::indexer::Indexer;
-use crate::query::engine::QueryEngine;
+use crate::query::cycles::{parse_types, CycleScope, DEPENDENCY_TYPES};
+use crate::query::deadcode::{group_by_file_and_kind, Confidence, DeadSymbol, EntryPoints};
+use crate::query::engine::{route, PathEdge, TransitiveResult};
 
 /// Query tool handler
 pub async fn query(indexer: &Indexer, args: &HashMap<String, Value>) -> Result<Value> {
@@ -21,8 +25,34 @@ pub async fn query(indexer: &Indexer, args: &HashMap<String, Value>) -> Result<V
         .and_then(|v| v.as_str())
         .unwrap_or("text");
 
+    let depth = args.get("depth")
+        .and_then(|v| v.as_u64())
+        .map(|d| d as usize);
+
     // Execute query using the query engine
-    let query_engine = QueryEngine::new(indexer.db().clone());
+    let query_engine = indexer.query_engine();
+    let transitive = match query_type {
+        "transitive_callers" => Some(query_engine.transitive_callers(target, depth)?),
+        "transitive_callees" => Some(query_engine.transitive_callees(target, depth)?),
+        "supertypes" => Some(query_engine.supertypes(target, depth)?),
+        "subtypes" => Some(query_engine.subtypes(target, depth)?),
+        _ => None,
+    };
+    if let Some(results) = transitive {
+        return Ok(transitive_response(query_type, target, results, format));
+    }
+
+    if query_type == "path" {
+        let to = args.get("to")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing to for path query"))?;
+        let max_paths = args.get("max_paths")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(10) as usize;
+        let paths = query_engine.find_paths(target, to, depth, max_paths)?;
+        return Ok(paths_response(target, to, paths, format));
+    }
+
     let results = match query_type {
         "callers" => query_engine.find_callers(target)?,
         "callees" => query_engine.find_callees(target)?,
@@ -75,20 +105,117 @@ pub async fn query(indexer: &Indexer, args: &HashMap<String, Value>) -> Result<V
     }
 }
 
+/// Format transitive results with the depth and route of each hit
+fn transitive_response(query_type: &str, target: &str, results: Vec<TransitiveResult>, format: &str) -> Value {
+    if format == "json" {
+        let json_results: Vec<Value> = results
+            .into_iter()
+            .map(|r| {
+                json!({
+                    "symbol_id": r.symbol_id,
+                    "qualified_name": r.qualified_name,
+                    "file": r.file,
+                    "line": r.line,
+                    "kind": r.kind,
+                    "depth": r.depth,
+                    "path": r.path
+                })
+            })
+            .collect();
+
+        return json!({
+            "query_type": query_type,
+            "target": target,
+            "results": json_results
+        });
+    }
+
+    let mut text_results = Vec::new();
+    if results.is_empty() {
+        text_results.push(format!("No {} found for '{}'", query_type, target));
+    } else {
+        text_results.push(format!("Found {} {} of '{}':", results.len(), query_type, target));
+        for result in results {
+            text_results.push(format!("  [{}] {}:{} - {} ({})",
+                result.depth,
+                result.file,
+                result.line,
+                result.qualified_name,
+                result.kind
+            ));
+            if result.depth > 1 {
+                text_results.push(format!("      via {}", result.route().join(" -> ")));
+            }
+        }
+    }
+
+    json!({
+        "content": [{
+            "type": "text",
+            "text": text_results.join("\n")
+        }]
+    })
+}
+
+/// Format path results, one line per hop
+fn paths_response(from: &str, to: &str, paths: Vec<Vec<PathEdge>>, format: &str) -> Value {
+    if format == "json" {
+        return json!({
+            "query_type": "path",
+            "target": from,
+            "to": to,
+            "results": paths
+        });
+    }
+
+    let mut text_results = Vec::new();
+    if paths.is_empty() {
+        text_results.push(format!("No paths found from '{}' to '{}'", from, to));
+    } else {
+        text_results.push(format!("Found {} paths from '{}' to '{}':", paths.len(), from, to));
+        for (i, path) in paths.iter().enumerate() {
+            text_results.push(format!("  {}. {} ({} hops)", i + 1, route(path).join(" -> "), path.len()));
+            for edge in path {
+                text_results.push(format!("       {}:{} {} {} {}",
+                    edge.file,
+                    edge.line,
+                    edge.from_id,
+                    edge.relationship,
+                    edge.to_id
+                ));
+            }
+        }
+    }
+
+    json!({
+        "content": [{
+            "type": "text",
+            "text": text_results.join("\n")
+        }]
+    })
+}
+
 /// Search tool handler
 pub async fn search(indexer: &Indexer, args: &HashMap<String, Value>) -> Result<Value> {
     let query = args.get("query")
         .and_then(|v| v.as_str())
         .ok_or_else(|| anyhow::anyhow!("Missing query"))?;
 
-    let kind = args.get("kind").and_then(|v| v.as_str());
+    let filter = |name: &str| args.get(name).and_then(|v| v.as_str()).map(str::to_string);
+    let filters = SearchFilters {
+        kind: filter("kind"),
+        language: filter("language"),
+        file: filter("file"),
+        visibility: filter("visibility"),
+        decorator: filter("decorator"),
+    };
     let limit = args.get("limit")
         .and_then(|v| v.as_u64())
         .unwrap_or(10) as usize;
 
     // Execute search using the query engine
-    let query_engine = QueryEngine::new(indexer.db().clone());
-    let results = query_engine.search_symbols(query, kind, limit)?;
+    let query_engine = indexer.query_engine();
+    let results = query_engine.search_symbols(query, &filters, limit)?;
 
     let mut text_results = Vec::new();
     if results.is_empty() {
@@ -96,11 +223,12 @@ pub async fn search(indexer: &Indexer, args: &HashMap<String, Value>) -> Result<
     } else {
         text_results.push(format!("Found {} symbols matching '{}':", results.len(), query));
         for result in results {
-            text_results.push(format!("  {}:{} - {} ({})",
+            text_results.push(format!("  {}:{} - {} ({}, score {:.2})",
                 result.file,
                 result.line,
                 result.qualified_name,
-                result.kind
+                result.kind,
+                result.score
             ));
         }
     }
@@ -113,6 +241,117 @@ pub async fn search(indexer: &Indexer, args: &HashMap<String, Value>) -> Result<
     }))
 }
 
+/// Dead code tool handler
+pub async fn dead_code(indexer: &Indexer, args: &HashMap<String, Value>) -> Result<Value> {
+    let min_confidence = args.get("min_confidence")
+        .and_then(|v| v.as_str())
+        .map(Confidence::from_str)
+        .transpose()?
+        .unwrap_or(Confidence::Low);
+
+    let format = args.get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("text");
+
+    let entry_points = EntryPoints::new(&indexer.config().deadcode)?;
+    let results: Vec<DeadSymbol> = indexer.query_engine()
+        .find_dead_code(&entry_points)?
+        .into_iter()
+        .filter(|symbol| symbol.confidence >= min_confidence)
+        .collect();
+
+    if format == "json" {
+        return Ok(json!({
+            "min_confidence": min_confidence.as_str(),
+            "results": results
+        }));
+    }
+
+    let mut text_results = Vec::new();
+    if results.is_empty() {
+        text_results.push("No dead code found".to_string());
+    } else {
+        text_results.push(format!("Found {} possibly dead symbols:", results.len()));
+        for (file, kinds) in group_by_file_and_kind(&results) {
+            text_results.push(format!("  {}", file));
+            for (kind, symbols) in kinds {
+                text_results.push(format!("    {}:", kind));
+                for symbol in symbols {
+                    text_results.push(format!("      {}:{} - {} [{}] {}",
+                        symbol.file,
+                        symbol.line,
+                        symbol.qualified_name,
+                        symbol.confidence.as_str(),
+                        symbol.reason
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(json!({
+        "content": [{
+            "type": "text",
+            "text": text_results.join("\n")
+        }]
+    }))
+}
+
+/// Cycles tool handler
+pub async fn cycles(indexer: &Indexer, args: &HashMap<String, Value>) -> Result<Value> {
+    let scope = args.get("scope")
+        .and_then(|v| v.as_str())
+        .map(CycleScope::from_str)
+        .transpose()?
+        .unwrap_or(CycleScope::File);
+
+    let types = match args.get("types").and_then(|v| v.as_str()) {
+        Some(types) => parse_types(types)?,
+        None => DEPENDENCY_TYPES.to_vec(),
+    };
+
+    let format = args.get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("text");
+
+    let cycles = indexer.query_engine().find_cycles(&types, scope)?;
+
+    if format == "json" {
+        return Ok(json!({
+            "scope": scope.as_str(),
+            "results": cycles
+        }));
+    }
+
+    let mut text_results = Vec::new();
+    if cycles.is_empty() {
+        text_results.push(format!("No cycles found between {}s", scope.as_str()));
+    } else {
+        text_results.push(format!("Found {} cycles between {}s:", cycles.len(), scope.as_str()));
+        for (i, cycle) in cycles.iter().enumerate() {
+            text_results.push(format!("  {}. {} members: {}", i + 1, cycle.members.len(), cycle.members.join(", ")));
+            for edge in &cycle.edges {
+                text_results.push(format!("       {} -> {} ({}:{} {} {} {})",
+                    edge.from,
+                    edge.to,
+                    edge.via.file,
+                    edge.via.line,
+                    edge.via.from_id,
+                    edge.via.relationship,
+                    edge.via.to_id
+                ));
+            }
+        }
+    }
+
+    Ok(json!({
+        "content": [{
+            "type": "text",
+            "text": text_results.join("\n")
+        }]
+    }))
+}
+
 /// Stats tool handler
 pub async fn stats(indexer: &Indexer, _args: &HashMap<String, Value>) -> Result<Value> {
     let stats = indexer.get_stats()?;
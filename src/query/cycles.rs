@@ -0,0 +1,301 @@
+// Cycle detection with Tarjan's strongly connected components
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::index::db::RelationshipType;
+use crate::query::engine::PathEdge;
+
+/// Relationship types that make one symbol depend on another
+pub const DEPENDENCY_TYPES: &[RelationshipType] = &[
+    RelationshipType::Calls,
+    RelationshipType::References,
+    RelationshipType::DependsOn,
+    RelationshipType::Extends,
+    RelationshipType::Implements,
+];
+
+/// Parse a comma-separated list of relationship types, e.g. `calls,references`
+pub fn parse_types(types: &str) -> anyhow::Result<Vec<RelationshipType>> {
+    types.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(RelationshipType::from_str)
+        .collect()
+}
+
+/// Granularity at which dependency cycles are detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleScope {
+    /// Individual symbols
+    Symbol,
+    /// Source files, e.g. Python modules
+    File,
+    /// Directories, e.g. Go and Java packages
+    Package,
+}
+
+impl CycleScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CycleScope::Symbol => "symbol",
+            CycleScope::File => "file",
+            CycleScope::Package => "package",
+        }
+    }
+
+    /// Node of the graph at this scope for a symbol defined in `file`
+    pub fn node<'a>(&self, symbol_id: &'a str, file: &'a str) -> &'a str {
+        match self {
+            CycleScope::Symbol => symbol_id,
+            CycleScope::File => file,
+            CycleScope::Package => Path::new(file)
+                .parent()
+                .and_then(Path::to_str)
+                .filter(|dir| !dir.is_empty())
+                .unwrap_or("."),
+        }
+    }
+}
+
+impl FromStr for CycleScope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "symbol" | "symbols" => Ok(CycleScope::Symbol),
+            "file" | "files" | "module" | "modules" => Ok(CycleScope::File),
+            "package" | "packages" => Ok(CycleScope::Package),
+            _ => Err(anyhow::anyhow!("Unknown cycle scope: {}", s)),
+        }
+    }
+}
+
+/// Dependency between two nodes, witnessed by one relationship
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleEdge {
+    pub from: String,
+    pub to: String,
+    pub via: PathEdge,
+}
+
+/// Strongly connected component with more than one member
+#[derive(Debug, Clone, Serialize)]
+pub struct Cycle {
+    /// Every node of the component, sorted
+    pub members: Vec<String>,
+    /// A closed walk through the first member, one edge per hop
+    pub edges: Vec<CycleEdge>,
+}
+
+/// Directed graph between scope nodes, keeping one witness edge per pair
+#[derive(Default)]
+pub struct DependencyGraph {
+    nodes: Vec<String>,
+    index: HashMap<String, usize>,
+    edges: Vec<BTreeMap<usize, PathEdge>>,
+}
+
+impl DependencyGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a dependency, ignoring edges within a node
+    pub fn add_edge(&mut self, from: &str, to: &str, via: PathEdge) {
+        if from == to {
+            return;
+        }
+        let from = self.node(from);
+        let to = self.node(to);
+        self.edges[from].entry(to).or_insert(via);
+    }
+
+    fn node(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.index.get(name) {
+            return index;
+        }
+        self.nodes.push(name.to_string());
+        self.edges.push(BTreeMap::new());
+        self.index.insert(name.to_string(), self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    /// Components with more than one node, largest first
+    pub fn cycles(&self) -> Vec<Cycle> {
+        let mut cycles: Vec<Cycle> = self.strongly_connected_components()
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| {
+                let mut members: Vec<&String> = component.iter().map(|&n| &self.nodes[n]).collect();
+                members.sort();
+                let start = self.index[members[0]];
+                Cycle {
+                    members: members.into_iter().cloned().collect(),
+                    edges: self.closed_walk(start, &component.into_iter().collect()),
+                }
+            })
+            .collect();
+
+        cycles.sort_by(|a, b| b.members.len().cmp(&a.members.len()).then_with(|| a.members.cmp(&b.members)));
+        cycles
+    }
+
+    /// Tarjan's algorithm, iterative so that deep graphs cannot overflow the stack
+    fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let count = self.nodes.len();
+        let mut order = vec![usize::MAX; count];
+        let mut low = vec![0; count];
+        let mut on_stack = vec![false; count];
+        let mut stack = Vec::new();
+        let mut next_order = 0;
+        let mut components = Vec::new();
+
+        for root in 0..count {
+            if order[root] != usize::MAX {
+                continue;
+            }
+
+            // Each frame is a node and the iterator over its remaining successors
+            let mut frames = vec![(root, self.edges[root].keys())];
+            order[root] = next_order;
+            low[root] = next_order;
+            next_order += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some((node, successors)) = frames.last_mut() {
+                let node = *node;
+                match successors.next() {
+                    Some(&next) if order[next] == usize::MAX => {
+                        order[next] = next_order;
+                        low[next] = next_order;
+                        next_order += 1;
+                        stack.push(next);
+                        on_stack[next] = true;
+                        frames.push((next, self.edges[next].keys()));
+                    }
+                    Some(&next) => {
+                        if on_stack[next] {
+                            low[node] = low[node].min(order[next]);
+                        }
+                    }
+                    None => {
+                        frames.pop();
+                        if let Some((parent, _)) = frames.last() {
+                            low[*parent] = low[*parent].min(low[node]);
+                        }
+                        if low[node] == order[node] {
+                            let mut component = Vec::new();
+                            while let Some(member) = stack.pop() {
+                                on_stack[member] = false;
+                                component.push(member);
+                                if member == node {
+                                    break;
+                                }
+                            }
+                            components.push(component);
+                        }
+                    }
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Shortest walk from `start` back to itself within a component
+    fn closed_walk(&self, start: usize, component: &BTreeSet<usize>) -> Vec<CycleEdge> {
+        let mut parent: HashMap<usize, usize> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        let mut last = None;
+
+        'search: while let Some(node) = queue.pop_front() {
+            for &next in self.edges[node].keys() {
+                if next == start {
+                    last = Some(node);
+                    break 'search;
+                }
+                if component.contains(&next) && !parent.contains_key(&next) {
+                    parent.insert(next, node);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let Some(last) = last else {
+            return Vec::new();
+        };
+
+        let mut hops = vec![(last, start)];
+        let mut current = last;
+        while current != start {
+            let previous = parent[&current];
+            hops.push((previous, current));
+            current = previous;
+        }
+        hops.reverse();
+
+        hops.into_iter()
+            .map(|(from, to)| CycleEdge {
+                from: self.nodes[from].clone(),
+                to: self.nodes[to].clone(),
+                via: self.edges[from][&to].clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(from: &str, to: &str) -> PathEdge {
+        PathEdge {
+            from_id: from.to_string(),
+            to_id: to.to_string(),
+            relationship: "calls".to_string(),
+            file: "x".to_string(),
+            line: 1,
+        }
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut graph = DependencyGraph::new();
+        for (from, to) in [("a", "b"), ("b", "c"), ("c", "a"), ("c", "d"), ("d", "e"), ("e", "d"), ("e", "f"), ("f", "f")] {
+            graph.add_edge(from, to, edge(from, to));
+        }
+
+        let cycles = graph.cycles();
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(cycles[0].members, vec!["a", "b", "c"]);
+        let walk: Vec<(&str, &str)> = cycles[0].edges.iter().map(|e| (e.from.as_str(), e.to.as_str())).collect();
+        assert_eq!(walk, vec![("a", "b"), ("b", "c"), ("c", "a")]);
+        assert_eq!(cycles[1].members, vec!["d", "e"]);
+    }
+
+    #[test]
+    fn test_deep_chain_does_not_overflow() {
+        let mut graph = DependencyGraph::new();
+        let count = 50_000;
+        for i in 0..count {
+            let (from, to) = (i.to_string(), ((i + 1) % count).to_string());
+            graph.add_edge(&from, &to, edge(&from, &to));
+        }
+
+        let cycles = graph.cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].members.len(), count);
+        assert_eq!(cycles[0].edges.len(), count);
+    }
+
+    #[test]
+    fn test_package_scope() {
+        assert_eq!(CycleScope::Package.node("id", "pkg/api/handler.go"), "pkg/api");
+        assert_eq!(CycleScope::Package.node("id", "main.go"), ".");
+        assert_eq!(CycleScope::File.node("id", "pkg/api/handler.go"), "pkg/api/handler.go");
+    }
+}
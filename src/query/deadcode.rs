@@ -0,0 +1,135 @@
+// Dead code detection
+
+use anyhow::Result;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::Gitignore;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use crate::config::DeadCodeConfig;
+use crate::index::db::{Symbol, SymbolKind, Visibility};
+use crate::indexer::scanner::build_matcher;
+
+/// How likely a reported symbol is to be unused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        }
+    }
+}
+
+impl FromStr for Confidence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "low" => Ok(Confidence::Low),
+            "medium" => Ok(Confidence::Medium),
+            "high" => Ok(Confidence::High),
+            _ => Err(anyhow::anyhow!("Unknown confidence: {}", s)),
+        }
+    }
+}
+
+/// Function, method or class that nothing calls or references
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadSymbol {
+    pub symbol_id: String,
+    pub qualified_name: String,
+    pub file: String,
+    pub line: usize,
+    pub kind: String,
+    pub confidence: Confidence,
+    pub reason: &'static str,
+}
+
+impl DeadSymbol {
+    /// Report an unreferenced symbol, `unresolved_names` being the names of
+    /// calls that matched no symbol anywhere in the index
+    pub fn new(symbol: Symbol, unresolved_names: &HashSet<String>) -> Self {
+        let (confidence, reason) = if unresolved_names.contains(&symbol.name) {
+            (Confidence::Low, "an unresolved call uses this name")
+        } else {
+            match symbol.kind {
+                SymbolKind::Method => (Confidence::Medium, "no calls, but methods can be reached by dynamic dispatch"),
+                SymbolKind::Class => (Confidence::Medium, "no references, but classes can be used reflectively"),
+                _ => (Confidence::High, "no calls or references"),
+            }
+        };
+
+        Self {
+            symbol_id: symbol.id,
+            qualified_name: symbol.qualified_name,
+            file: symbol.file,
+            line: symbol.line,
+            kind: symbol.kind.as_str().to_string(),
+            confidence,
+            reason,
+        }
+    }
+}
+
+/// Group dead symbols by file, then by kind
+pub fn group_by_file_and_kind(results: &[DeadSymbol]) -> BTreeMap<&str, BTreeMap<&str, Vec<&DeadSymbol>>> {
+    let mut groups: BTreeMap<&str, BTreeMap<&str, Vec<&DeadSymbol>>> = BTreeMap::new();
+    for symbol in results {
+        groups.entry(symbol.file.as_str())
+            .or_default()
+            .entry(symbol.kind.as_str())
+            .or_default()
+            .push(symbol);
+    }
+    groups
+}
+
+/// Symbols excluded from dead code reports, built from `[deadcode]`
+pub struct EntryPoints {
+    names: GlobSet,
+    test_files: Gitignore,
+    public_api_languages: Vec<String>,
+}
+
+impl EntryPoints {
+    pub fn new(config: &DeadCodeConfig) -> Result<Self> {
+        let mut names = GlobSetBuilder::new();
+        for pattern in &config.entry_points {
+            names.add(Glob::new(pattern)?);
+        }
+
+        Ok(Self {
+            names: names.build()?,
+            test_files: build_matcher(&config.test_files)?,
+            public_api_languages: config.public_api_languages.clone(),
+        })
+    }
+
+    /// Whether a symbol is used from outside the index and must not be reported
+    pub fn contains(&self, symbol: &Symbol) -> bool {
+        self.names.is_match(&symbol.name)
+            || self.names.is_match(&symbol.qualified_name)
+            || self.is_test_file(&symbol.file)
+            || (symbol.visibility == Visibility::Public && self.public_api_languages.contains(&symbol.language))
+    }
+
+    fn is_test_file(&self, file: &str) -> bool {
+        // Indexed paths may be absolute, the patterns are unanchored
+        let relative: PathBuf = Path::new(file)
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .collect();
+        self.test_files.matched_path_or_any_parents(&relative, false).is_ignore()
+    }
+}
@@ -1,10 +1,17 @@
 // Query execution engine
 
 use anyhow::Result;
-use std::collections::HashSet;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::indexer::Indexer;
-use crate::index::db::{IndexDatabase, RelationshipType};
+use crate::config::Config;
+use crate::index::db::{Direction, IndexDatabase, RelationshipType, SearchFilters, Symbol, SymbolKind, TraversalStep};
+use crate::query::cycles::{Cycle, CycleScope, DependencyGraph};
+use crate::query::deadcode::{DeadSymbol, EntryPoints};
+use crate::index::search::{abbreviation_score, match_expression};
+
+/// Relationship types that make up the type hierarchy
+const TYPE_HIERARCHY: &[RelationshipType] = &[RelationshipType::Extends, RelationshipType::Implements];
 
 /// Query result
 #[derive(Debug, Clone)]
@@ -16,27 +23,116 @@ pub struct QueryResult {
     pub kind: String,
 }
 
+/// Ranked symbol search result
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub symbol_id: String,
+    pub qualified_name: String,
+    pub file: String,
+    pub line: usize,
+    pub kind: String,
+    pub language: String,
+    pub signature: Option<String>,
+    /// Relevance, higher is better
+    pub score: f64,
+}
+
+impl SearchResult {
+    fn new(symbol: Symbol, score: f64) -> Self {
+        Self {
+            symbol_id: symbol.id,
+            qualified_name: symbol.qualified_name,
+            file: symbol.file,
+            line: symbol.line,
+            kind: symbol.kind.as_str().to_string(),
+            language: symbol.language,
+            signature: symbol.signature,
+            score,
+        }
+    }
+}
+
+/// One relationship on a path through the graph, in call direction
+#[derive(Debug, Clone, Serialize)]
+pub struct PathEdge {
+    pub from_id: String,
+    pub to_id: String,
+    pub relationship: String,
+    pub file: String,
+    pub line: usize,
+}
+
+impl PathEdge {
+    fn new(step: &TraversalStep, direction: Direction) -> Self {
+        let (from_id, to_id) = match direction {
+            Direction::Incoming => (&step.id, &step.via),
+            Direction::Outgoing => (&step.via, &step.id),
+        };
+        Self {
+            from_id: from_id.clone(),
+            to_id: to_id.clone(),
+            relationship: step.type_.as_str().to_string(),
+            file: step.file.clone(),
+            line: step.line,
+        }
+    }
+}
+
+/// Symbol ids along a path, in call direction
+pub fn route(path: &[PathEdge]) -> Vec<&str> {
+    path.first()
+        .map(|edge| edge.from_id.as_str())
+        .into_iter()
+        .chain(path.iter().map(|edge| edge.to_id.as_str()))
+        .collect()
+}
+
+/// Symbol reached by a transitive query
+#[derive(Debug, Clone)]
+pub struct TransitiveResult {
+    pub symbol_id: String,
+    pub qualified_name: String,
+    pub file: String,
+    pub line: usize,
+    pub kind: String,
+    /// Number of hops from the target
+    pub depth: usize,
+    /// Edges of a shortest route between the symbol and the target
+    pub path: Vec<PathEdge>,
+}
+
+impl TransitiveResult {
+    /// Symbol ids along the path, in call direction
+    pub fn route(&self) -> Vec<&str> {
+        route(&self.path)
+    }
+}
+
 /// Query engine
 pub struct QueryEngine {
     db: IndexDatabase,
+    max_depth: usize,
 }
 
 impl QueryEngine {
     pub fn new(db: IndexDatabase) -> Self {
-        Self { db }
+        Self {
+            db,
+            max_depth: Config::default().query.max_depth,
+        }
     }
 
-    /// Find all callers of a symbol
-    pub fn find_callers(&self, target_symbol: &str) -> Result<Vec<QueryResult>> {
-        // Find all target symbols with this name
-        let symbols = self.db.find_symbols_by_name(target_symbol)?;
-        if symbols.is_empty() {
-            return Ok(Vec::new());
-        }
+    /// Bound graph traversals, `query.max_depth` in the configuration
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
 
+    /// Find all callers of a symbol, or of the generated code standing for it
+    pub fn find_callers(&self, target_symbol: &str) -> Result<Vec<QueryResult>> {
         let mut results = Vec::new();
-        for symbol in symbols {
-            let relationships = self.db.find_relationships_to(&symbol.id, Some(RelationshipType::Calls))?;
+        for id in self.with_stubs(target_symbol)? {
+            let relationships = self.db.find_relationships_to(&id, Some(RelationshipType::Calls))?;
 
             for rel in relationships {
                 if let Some(caller_symbol) = self.db.get_symbol(&rel.from_id)? {
@@ -82,6 +178,224 @@ impl QueryEngine {
         Ok(results)
     }
 
+    /// Find every symbol that eventually calls a symbol, or the generated
+    /// code standing for it
+    ///
+    /// `max_depth` is capped by `query.max_depth`, which is also the default.
+    pub fn transitive_callers(&self, target_symbol: &str, max_depth: Option<usize>) -> Result<Vec<TransitiveResult>> {
+        let start = self.with_stubs(target_symbol)?;
+        self.transitive_from(start, &[RelationshipType::Calls], Direction::Incoming, max_depth)
+    }
+
+    /// Find every symbol that a symbol eventually calls
+    ///
+    /// `max_depth` is capped by `query.max_depth`, which is also the default.
+    pub fn transitive_callees(&self, target_symbol: &str, max_depth: Option<usize>) -> Result<Vec<TransitiveResult>> {
+        self.transitive(target_symbol, &[RelationshipType::Calls], Direction::Outgoing, max_depth)
+    }
+
+    /// Find every class or interface a type extends or implements, directly or not
+    ///
+    /// `max_depth` is capped by `query.max_depth`, which is also the default.
+    pub fn supertypes(&self, target_symbol: &str, max_depth: Option<usize>) -> Result<Vec<TransitiveResult>> {
+        self.transitive(target_symbol, TYPE_HIERARCHY, Direction::Outgoing, max_depth)
+    }
+
+    /// Find every type that extends or implements a type, directly or not
+    ///
+    /// `max_depth` is capped by `query.max_depth`, which is also the default.
+    pub fn subtypes(&self, target_symbol: &str, max_depth: Option<usize>) -> Result<Vec<TransitiveResult>> {
+        self.transitive(target_symbol, TYPE_HIERARCHY, Direction::Incoming, max_depth)
+    }
+
+    /// Ids of the symbols with a qualified name, each followed by the ids of
+    /// the generated code linked to it, such as the gRPC stubs of an rpc
+    fn with_stubs(&self, target_symbol: &str) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for symbol in self.db.find_symbols_by_name(target_symbol)? {
+            let stubs: Vec<String> = self.db.find_relationships_to(&symbol.id, Some(RelationshipType::Implements))?
+                .into_iter()
+                .filter(|rel| {
+                    rel.metadata.as_deref()
+                        .and_then(|metadata| serde_json::from_str::<serde_json::Value>(metadata).ok())
+                        .is_some_and(|metadata| metadata["generated"] == true)
+                })
+                .map(|rel| rel.from_id)
+                .collect();
+            ids.push(symbol.id);
+            ids.extend(stubs);
+        }
+        Ok(ids)
+    }
+
+    fn transitive(
+        &self,
+        target_symbol: &str,
+        types: &[RelationshipType],
+        direction: Direction,
+        max_depth: Option<usize>,
+    ) -> Result<Vec<TransitiveResult>> {
+        let start: Vec<String> = self.db.find_symbols_by_name(target_symbol)?
+            .into_iter()
+            .map(|symbol| symbol.id)
+            .collect();
+        self.transitive_from(start, types, direction, max_depth)
+    }
+
+    fn transitive_from(
+        &self,
+        start: Vec<String>,
+        types: &[RelationshipType],
+        direction: Direction,
+        max_depth: Option<usize>,
+    ) -> Result<Vec<TransitiveResult>> {
+        if start.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let max_depth = max_depth.map_or(self.max_depth, |depth| depth.min(self.max_depth));
+        let steps = self.db.traverse(&start, types, direction, max_depth)?;
+        let depths = shallowest(&start, &steps);
+
+        // The step into each symbol that lies on a shortest route
+        let mut parents: HashMap<&str, &TraversalStep> = HashMap::new();
+        for step in &steps {
+            if depths[step.id.as_str()] == step.depth && depths.get(step.via.as_str()) == Some(&(step.depth - 1)) {
+                parents.entry(step.id.as_str()).or_insert(step);
+            }
+        }
+
+        let mut results = Vec::new();
+        for (&id, &depth) in &depths {
+            if depth == 0 {
+                continue;
+            }
+            let Some(symbol) = self.db.get_symbol(id)? else {
+                continue;
+            };
+
+            let mut path = Vec::with_capacity(depth);
+            let mut current = id;
+            while let Some(step) = parents.get(current) {
+                path.push(PathEdge::new(step, direction));
+                current = step.via.as_str();
+            }
+            if direction == Direction::Outgoing {
+                path.reverse();
+            }
+
+            results.push(TransitiveResult {
+                symbol_id: symbol.id,
+                qualified_name: symbol.qualified_name,
+                file: symbol.file,
+                line: symbol.line,
+                kind: symbol.kind.as_str().to_string(),
+                depth,
+                path,
+            });
+        }
+
+        results.sort_by(|a, b| a.depth.cmp(&b.depth).then_with(|| a.symbol_id.cmp(&b.symbol_id)));
+        Ok(results)
+    }
+
+    /// Find call or reference chains from one symbol to another, shortest first
+    ///
+    /// Returns at most `max_paths` paths that visit no symbol twice and take at
+    /// most `max_depth` hops, capped by `query.max_depth`.
+    pub fn find_paths(&self, from: &str, to: &str, max_depth: Option<usize>, max_paths: usize) -> Result<Vec<Vec<PathEdge>>> {
+        let ids = |name: &str| -> Result<Vec<String>> {
+            Ok(self.db.find_symbols_by_name(name)?.into_iter().map(|symbol| symbol.id).collect())
+        };
+        let sources = ids(from)?;
+        let targets = ids(to)?;
+        if sources.is_empty() || targets.is_empty() || max_paths == 0 {
+            return Ok(Vec::new());
+        }
+
+        let max_depth = max_depth.map_or(self.max_depth, |depth| depth.min(self.max_depth));
+        let types = [RelationshipType::Calls, RelationshipType::References];
+        let forward = self.db.traverse(&sources, &types, Direction::Outgoing, max_depth)?;
+        let backward = self.db.traverse(&targets, &types, Direction::Incoming, max_depth)?;
+        let from_sources = shallowest(&sources, &forward);
+        let to_targets = shallowest(&targets, &backward);
+
+        // Edges on a route short enough to matter, one per pair of symbols
+        let mut edges: BTreeMap<&str, BTreeMap<&str, &TraversalStep>> = BTreeMap::new();
+        for step in &forward {
+            let (Some(&before), Some(&after)) = (from_sources.get(step.via.as_str()), to_targets.get(step.id.as_str())) else {
+                continue;
+            };
+            if before + 1 + after <= max_depth {
+                edges.entry(step.via.as_str()).or_default().entry(step.id.as_str()).or_insert(step);
+            }
+        }
+
+        // Deepen one hop at a time so that shorter paths come first
+        let search = PathSearch { edges: &edges, to_targets: &to_targets, max_paths };
+        let mut paths = Vec::new();
+        for length in 1..=max_depth {
+            for source in &sources {
+                search.extend(&mut vec![source.as_str()], &mut Vec::new(), length, &mut paths);
+            }
+            if paths.len() >= max_paths {
+                break;
+            }
+        }
+
+        Ok(paths.into_iter()
+            .take(max_paths)
+            .map(|steps| steps.into_iter().map(|step| PathEdge::new(step, Direction::Outgoing)).collect())
+            .collect())
+    }
+
+    /// Find functions, methods and classes that nothing calls, references or defines
+    ///
+    /// Results are ordered by file, kind and line.
+    pub fn find_dead_code(&self, entry_points: &EntryPoints) -> Result<Vec<DeadSymbol>> {
+        let candidates = self.db.find_unreferenced_symbols(
+            &[SymbolKind::Function, SymbolKind::Method, SymbolKind::Class],
+            &[
+                RelationshipType::Calls,
+                RelationshipType::References,
+                RelationshipType::Extends,
+                RelationshipType::Implements,
+                RelationshipType::DependsOn,
+                // A prototype is live once something defines it
+                RelationshipType::Defines,
+            ],
+        )?;
+        let unresolved_names = self.db.unresolved_names()?;
+
+        Ok(candidates
+            .into_iter()
+            .filter(|symbol| !entry_points.contains(symbol))
+            .map(|symbol| DeadSymbol::new(symbol, &unresolved_names))
+            .collect())
+    }
+
+    /// Find dependency cycles as strongly connected components
+    ///
+    /// Relationships of the given types are lifted to `scope`, so at file
+    /// scope an edge between two symbols becomes an edge between their files
+    /// and edges within a single file are ignored.
+    pub fn find_cycles(&self, types: &[RelationshipType], scope: CycleScope) -> Result<Vec<Cycle>> {
+        let mut graph = DependencyGraph::new();
+        for (rel, from_file, to_file) in self.db.find_relationships_with_files(types)? {
+            let from = scope.node(&rel.from_id, &from_file).to_string();
+            let to = scope.node(&rel.to_id, &to_file).to_string();
+            graph.add_edge(&from, &to, PathEdge {
+                relationship: rel.type_.as_str().to_string(),
+                from_id: rel.from_id,
+                to_id: rel.to_id,
+                file: rel.file,
+                line: rel.line,
+            });
+        }
+
+        Ok(graph.cycles())
+    }
+
     /// Find all references to a symbol
     pub fn find_references(&self, target_symbol: &str) -> Result<Vec<QueryResult>> {
         // Find the target symbol first
@@ -116,61 +430,363 @@ impl QueryEngine {
         self.find_references(target_symbol)
     }
 
-    /// Search for symbols by name
-    pub fn search_symbols(&self, query: &str, kind: Option<&str>, limit: usize) -> Result<Vec<QueryResult>> {
-        // Use a simple LIKE query for now
-        // TODO: Implement full-text search
-        let conn = self.db.get_conn()?;
-        let pattern = format!("%{}%", query);
-
-        let mut stmt = conn.prepare(
-            "SELECT id, kind, name, qualified_name, file, line, column, end_line, end_column,
-                    signature, type, visibility, language, metadata, content_hash, last_indexed
-             FROM symbols
-             WHERE qualified_name LIKE ?1
-             ORDER BY qualified_name
-             LIMIT ?2",
-        )?;
+    /// Ranked full-text search for symbols
+    ///
+    /// Words are matched as prefixes of the camelCase and snake_case parts of
+    /// names, double quotes match phrases, and a single word that full-text
+    /// search cannot satisfy is retried as an abbreviation, so `usrmgr` finds
+    /// `UserManager`.
+    pub fn search_symbols(&self, query: &str, filters: &SearchFilters, limit: usize) -> Result<Vec<SearchResult>> {
+        let query = query.trim();
+        let mut results: Vec<SearchResult> = match match_expression(query) {
+            Some(expression) => self.db.search_symbols(&expression, query, filters, limit)?
+                .into_iter()
+                .map(|(symbol, score)| SearchResult::new(symbol, score))
+                .collect(),
+            None => Vec::new(),
+        };
 
-        let symbols = stmt.query_map([pattern, limit.to_string()], |row| {
-            Ok(crate::index::db::Symbol {
-                id: row.get(0)?,
-                kind: crate::index::db::SymbolKind::from_str(&row.get::<_, String>(1)?).unwrap(),
-                name: row.get(2)?,
-                qualified_name: row.get(3)?,
-                file: row.get(4)?,
-                line: row.get::<_, i64>(5)? as usize,
-                column: row.get::<_, i64>(6)? as usize,
-                end_line: row.get::<_, i64>(7)? as usize,
-                end_column: row.get::<_, i64>(8)? as usize,
-                signature: row.get(9)?,
-                type_: row.get(10)?,
-                visibility: crate::index::db::Visibility::from_str(&row.get::<_, String>(11)?).unwrap(),
-                language: row.get(12)?,
-                metadata: row.get(13)?,
-                content_hash: row.get(14)?,
-                last_indexed: row.get::<_, i64>(15)? as u64,
-            })
-        })?
-        .collect::<std::result::Result<Vec<_>, _>>()?;
+        let single_word = !query.is_empty() && query.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if results.is_empty() && single_word {
+            results = self.db.find_symbols_by_abbreviation(query, filters, limit)?
+                .into_iter()
+                .filter_map(|symbol| {
+                    let score = abbreviation_score(query, &symbol.name)?;
+                    Some(SearchResult::new(symbol, score))
+                })
+                .collect();
+            results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.qualified_name.cmp(&b.qualified_name)));
+        }
 
-        let mut results = Vec::new();
-        for symbol in symbols {
-            if let Some(kind_filter) = kind {
-                if symbol.kind.as_str() != kind_filter {
-                    continue;
-                }
+        Ok(results)
+    }
+}
+
+/// Shallowest depth at which each symbol is reached, zero for the start
+fn shallowest<'a>(start: &'a [String], steps: &'a [TraversalStep]) -> HashMap<&'a str, usize> {
+    let mut depths: HashMap<&str, usize> = start.iter().map(|id| (id.as_str(), 0)).collect();
+    for step in steps {
+        let depth = depths.entry(step.id.as_str()).or_insert(step.depth);
+        *depth = (*depth).min(step.depth);
+    }
+    depths
+}
+
+/// Depth-first enumeration of paths of an exact length over pruned edges
+struct PathSearch<'s, 'a> {
+    edges: &'s BTreeMap<&'a str, BTreeMap<&'a str, &'a TraversalStep>>,
+    /// Hops from each symbol to the nearest target, zero for the targets
+    to_targets: &'s HashMap<&'a str, usize>,
+    max_paths: usize,
+}
+
+impl<'a> PathSearch<'_, 'a> {
+    fn extend(&self, route: &mut Vec<&'a str>, steps: &mut Vec<&'a TraversalStep>, remaining: usize, paths: &mut Vec<Vec<&'a TraversalStep>>) {
+        let current = route[route.len() - 1];
+        if self.to_targets.get(current) == Some(&0) {
+            // Paths end at the first target they reach
+            if remaining == 0 && !steps.is_empty() {
+                paths.push(steps.clone());
             }
+            return;
+        }
+        if remaining == 0 {
+            return;
+        }
 
-            results.push(QueryResult {
-                symbol_id: symbol.id,
-                qualified_name: symbol.qualified_name,
-                file: symbol.file,
-                line: symbol.line,
-                kind: symbol.kind.as_str().to_string(),
-            });
+        for (&next, &step) in self.edges.get(current).into_iter().flatten() {
+            if paths.len() >= self.max_paths {
+                return;
+            }
+            if route.contains(&next) || self.to_targets.get(next).is_none_or(|&hops| hops > remaining - 1) {
+                continue;
+            }
+            route.push(next);
+            steps.push(step);
+            self.extend(route, steps, remaining - 1, paths);
+            route.pop();
+            steps.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::db::{now, Relationship, SymbolKind, Visibility};
+    use tempfile::tempdir;
+
+    fn symbol(file: &str, qualified_name: &str, kind: SymbolKind, visibility: Visibility) -> Symbol {
+        let name = qualified_name.rsplit('.').next().unwrap().to_string();
+        Symbol {
+            id: format!("{}:{}", file, qualified_name),
+            kind,
+            name,
+            qualified_name: qualified_name.to_string(),
+            file: file.to_string(),
+            line: 1,
+            column: 0,
+            end_line: 2,
+            end_column: 0,
+            signature: None,
+            type_: None,
+            visibility,
+            language: "python".to_string(),
+            metadata: None,
+            content_hash: "hash".to_string(),
+            last_indexed: now(),
         }
+    }
 
-        Ok(results)
+    #[test]
+    fn test_search_symbols_ranked() {
+        let dir = tempdir().unwrap();
+        let db = IndexDatabase::new(dir.path().join("test.db")).unwrap();
+
+        let mut symbols = vec![
+            symbol("src/auth.py", "auth.UserManager", SymbolKind::Class, Visibility::Public),
+            symbol("src/auth.py", "auth.user_manager_factory", SymbolKind::Function, Visibility::Public),
+            symbol("src/auth.py", "auth._user_cache", SymbolKind::Variable, Visibility::Private),
+        ];
+        // Enough other classes that filtering after LIMIT would drop the function
+        for i in 0..20 {
+            symbols.push(symbol("src/models.py", &format!("models.UserModel{}", i), SymbolKind::Class, Visibility::Public));
+        }
+        db.replace_file("src/auth.py", "python", "hash", &symbols[..3]).unwrap();
+        db.replace_file("src/models.py", "python", "hash", &symbols[3..]).unwrap();
+
+        let engine = QueryEngine::new(db.clone());
+        let names = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.qualified_name).collect()
+        };
+
+        // Exact names rank first, split words and prefixes match
+        let results = engine.search_symbols("UserManager", &SearchFilters::default(), 5).unwrap();
+        assert_eq!(results[0].qualified_name, "auth.UserManager");
+        assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+        assert_eq!(names(engine.search_symbols("user manager", &SearchFilters::default(), 5).unwrap()).len(), 2);
+        assert_eq!(names(engine.search_symbols("\"manager factory\"", &SearchFilters::default(), 5).unwrap()),
+            vec!["auth.user_manager_factory"]);
+        assert_eq!(names(engine.search_symbols("usrmgr", &SearchFilters::default(), 5).unwrap()),
+            vec!["auth.UserManager", "auth.user_manager_factory"]);
+        assert_eq!(names(engine.search_symbols("usrmgr", &SearchFilters::default(), 1).unwrap()), vec!["auth.UserManager"]);
+
+        // Filters apply before the limit
+        let functions = SearchFilters { kind: Some("function".to_string()), ..Default::default() };
+        assert_eq!(names(engine.search_symbols("user", &functions, 3).unwrap()), vec!["auth.user_manager_factory"]);
+        let private = SearchFilters { visibility: Some("private".to_string()), ..Default::default() };
+        assert_eq!(names(engine.search_symbols("user", &private, 3).unwrap()), vec!["auth._user_cache"]);
+        let models = SearchFilters { file: Some("src/models*".to_string()), ..Default::default() };
+        assert_eq!(engine.search_symbols("user", &models, 50).unwrap().len(), 20);
+
+        // Re-indexing a file replaces its search entries
+        db.replace_file("src/auth.py", "python", "hash2", &symbols[..1]).unwrap();
+        db.replace_file("src/auth.py", "python", "hash2", &symbols[..1]).unwrap();
+        assert!(engine.search_symbols("factory", &SearchFilters::default(), 5).unwrap().is_empty());
+        assert_eq!(engine.search_symbols("UserManager", &SearchFilters::default(), 5).unwrap().len(), 1);
+    }
+
+    fn call(file: &str, from: &str, to: &str, line: usize) -> Relationship {
+        Relationship {
+            from_id: format!("{}:{}", file, from),
+            to_id: format!("{}:{}", file, to),
+            type_: RelationshipType::Calls,
+            file: file.to_string(),
+            line,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_transitive_calls() {
+        let dir = tempdir().unwrap();
+        let db = IndexDatabase::new(dir.path().join("test.db")).unwrap();
+
+        // handler -> service -> repo -> db, handler -> repo, and a db <-> retry cycle
+        let names = ["handler", "service", "repo", "db", "retry"];
+        let symbols: Vec<Symbol> = names.iter()
+            .map(|name| symbol("app.py", name, SymbolKind::Function, Visibility::Public))
+            .collect();
+        db.replace_file("app.py", "python", "hash", &symbols).unwrap();
+        db.replace_file_relationships("app.py", &[
+            call("app.py", "handler", "service", 2),
+            call("app.py", "service", "repo", 6),
+            call("app.py", "handler", "repo", 3),
+            call("app.py", "repo", "db", 10),
+            call("app.py", "db", "retry", 14),
+            call("app.py", "retry", "db", 18),
+        ]).unwrap();
+
+        let engine = QueryEngine::new(db).with_max_depth(10);
+        let summary = |results: Vec<TransitiveResult>| -> Vec<(String, usize)> {
+            results.into_iter().map(|r| (r.qualified_name, r.depth)).collect()
+        };
+
+        let callers = engine.transitive_callers("db", None).unwrap();
+        let retry = callers.iter().find(|r| r.qualified_name == "retry").unwrap();
+        assert_eq!(retry.depth, 1);
+        let handler = callers.iter().find(|r| r.qualified_name == "handler").unwrap();
+        assert_eq!(handler.depth, 2);
+        assert_eq!(handler.route(), vec!["app.py:handler", "app.py:repo", "app.py:db"]);
+        assert_eq!(handler.path[0].line, 3);
+        assert_eq!(summary(callers), vec![
+            ("repo".to_string(), 1),
+            ("retry".to_string(), 1),
+            ("handler".to_string(), 2),
+            ("service".to_string(), 2),
+        ]);
+
+        let callees = engine.transitive_callees("handler", None).unwrap();
+        let retry = callees.iter().find(|r| r.qualified_name == "retry").unwrap();
+        assert_eq!(retry.route(), vec!["app.py:handler", "app.py:repo", "app.py:db", "app.py:retry"]);
+        assert_eq!(retry.path.iter().map(|e| e.line).collect::<Vec<_>>(), vec![3, 10, 14]);
+
+        // Requested depths are capped by the configured maximum
+        assert_eq!(summary(engine.transitive_callees("handler", Some(1)).unwrap()), vec![
+            ("repo".to_string(), 1),
+            ("service".to_string(), 1),
+        ]);
+        let shallow = QueryEngine::new(engine.db.clone()).with_max_depth(2);
+        assert_eq!(shallow.transitive_callees("handler", Some(5)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_find_paths() {
+        let dir = tempdir().unwrap();
+        let db = IndexDatabase::new(dir.path().join("test.db")).unwrap();
+
+        let names = ["handler", "service", "repo", "db", "retry", "config"];
+        let symbols: Vec<Symbol> = names.iter()
+            .map(|name| symbol("app.py", name, SymbolKind::Function, Visibility::Public))
+            .collect();
+        db.replace_file("app.py", "python", "hash", &symbols).unwrap();
+        let mut uses_config = call("app.py", "service", "config", 7);
+        uses_config.type_ = RelationshipType::References;
+        db.replace_file_relationships("app.py", &[
+            call("app.py", "handler", "service", 2),
+            call("app.py", "service", "repo", 6),
+            call("app.py", "handler", "repo", 3),
+            call("app.py", "repo", "db", 10),
+            call("app.py", "db", "retry", 14),
+            call("app.py", "retry", "db", 18),
+            uses_config,
+        ]).unwrap();
+
+        let engine = QueryEngine::new(db).with_max_depth(10);
+        let routes = |paths: Vec<Vec<PathEdge>>| -> Vec<String> {
+            paths.iter().map(|path| route(path).join(" ").replace("app.py:", "")).collect()
+        };
+
+        // Shortest first, cycles are not followed
+        assert_eq!(routes(engine.find_paths("handler", "db", None, 10).unwrap()), vec![
+            "handler repo db",
+            "handler service repo db",
+        ]);
+        assert_eq!(routes(engine.find_paths("handler", "db", None, 1).unwrap()), vec!["handler repo db"]);
+        assert_eq!(routes(engine.find_paths("handler", "db", Some(2), 10).unwrap()), vec!["handler repo db"]);
+
+        // Reference edges count as hops and keep their location
+        let paths = engine.find_paths("handler", "config", None, 10).unwrap();
+        assert_eq!(routes(paths.clone()), vec!["handler service config"]);
+        assert_eq!(paths[0][1].relationship, "references");
+        assert_eq!(paths[0][1].line, 7);
+
+        assert!(engine.find_paths("db", "handler", None, 10).unwrap().is_empty());
+        assert!(engine.find_paths("handler", "missing", None, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_dead_code() {
+        let dir = tempdir().unwrap();
+        let db = IndexDatabase::new(dir.path().join("test.db")).unwrap();
+
+        let mut symbols: Vec<Symbol> = ["main", "used", "unused", "recursive", "dynamic", "test_main", "__init__"]
+            .iter()
+            .map(|name| symbol("app.py", name, SymbolKind::Function, Visibility::Public))
+            .collect();
+        symbols.push(symbol("app.py", "Model", SymbolKind::Class, Visibility::Public));
+        symbols.push(symbol("app.py", "Model.save", SymbolKind::Method, Visibility::Public));
+        db.replace_file("app.py", "python", "hash", &symbols).unwrap();
+        db.replace_file("tests/test_app.py", "python", "hash", &[
+            symbol("tests/test_app.py", "fixture", SymbolKind::Function, Visibility::Public),
+        ]).unwrap();
+        let mut rust = symbol("lib.rs", "api", SymbolKind::Function, Visibility::Public);
+        rust.language = "rust".to_string();
+        let mut private = symbol("lib.rs", "internal", SymbolKind::Function, Visibility::Private);
+        private.language = "rust".to_string();
+        db.replace_file("lib.rs", "rust", "hash", &[rust, private]).unwrap();
+
+        let mut models = call("app.py", "main", "Model", 3);
+        models.type_ = RelationshipType::References;
+        // A call through an attribute that matched no symbol
+        let mut dynamic = call("app.py", "main", "dynamic", 4);
+        dynamic.to_id = crate::index::unresolved_id("dynamic");
+        db.replace_file_relationships("app.py", &[
+            call("app.py", "main", "used", 2),
+            call("app.py", "recursive", "recursive", 8),
+            models,
+            dynamic,
+        ]).unwrap();
+
+        let engine = QueryEngine::new(db);
+        let entry_points = EntryPoints::new(&Config::default().deadcode).unwrap();
+        let report: Vec<(String, String, &str)> = engine.find_dead_code(&entry_points).unwrap()
+            .into_iter()
+            .map(|dead| (dead.file, dead.qualified_name, dead.confidence.as_str()))
+            .collect();
+
+        let entry = |file: &str, name: &str, confidence| (file.to_string(), name.to_string(), confidence);
+        assert_eq!(report, vec![
+            entry("app.py", "dynamic", "low"),
+            entry("app.py", "recursive", "high"),
+            entry("app.py", "unused", "high"),
+            entry("app.py", "Model.save", "medium"),
+            entry("lib.rs", "internal", "high"),
+        ]);
+    }
+
+    #[test]
+    fn test_find_cycles_by_scope() {
+        let dir = tempdir().unwrap();
+        let db = IndexDatabase::new(dir.path().join("test.db")).unwrap();
+
+        for (file, names) in [("pkg/a.py", ["f", "h"]), ("pkg/b.py", ["g", "k"]), ("lib/c.py", ["x", "y"])] {
+            let symbols: Vec<Symbol> = names.iter()
+                .map(|name| symbol(file, name, SymbolKind::Function, Visibility::Public))
+                .collect();
+            db.replace_file(file, "python", "hash", &symbols).unwrap();
+        }
+        let cross = |from_file: &str, from: &str, to_file: &str, to: &str| Relationship {
+            from_id: format!("{}:{}", from_file, from),
+            to_id: format!("{}:{}", to_file, to),
+            type_: RelationshipType::Calls,
+            file: from_file.to_string(),
+            line: 1,
+            metadata: None,
+        };
+        db.replace_file_relationships("pkg/a.py", &[cross("pkg/a.py", "f", "pkg/b.py", "g")]).unwrap();
+        db.replace_file_relationships("pkg/b.py", &[
+            cross("pkg/b.py", "k", "pkg/a.py", "h"),
+            cross("pkg/b.py", "g", "lib/c.py", "x"),
+        ]).unwrap();
+        db.replace_file_relationships("lib/c.py", &[cross("lib/c.py", "y", "pkg/a.py", "f")]).unwrap();
+
+        let engine = QueryEngine::new(db);
+        let members = |scope| -> Vec<Vec<String>> {
+            engine.find_cycles(&[RelationshipType::Calls], scope).unwrap()
+                .into_iter()
+                .map(|cycle| cycle.members)
+                .collect()
+        };
+
+        // No symbol calls itself back, but the files and packages depend on each other
+        assert!(members(CycleScope::Symbol).is_empty());
+        assert_eq!(members(CycleScope::File), vec![vec!["lib/c.py", "pkg/a.py", "pkg/b.py"]]);
+        assert_eq!(members(CycleScope::Package), vec![vec!["lib", "pkg"]]);
+
+        let cycles = engine.find_cycles(&[RelationshipType::Calls], CycleScope::File).unwrap();
+        let walk: Vec<&str> = cycles[0].edges.iter().map(|edge| edge.via.from_id.as_str()).collect();
+        assert_eq!(walk, vec!["lib/c.py:y", "pkg/a.py:f", "pkg/b.py:g"]);
+        assert!(engine.find_cycles(&[RelationshipType::References], CycleScope::File).unwrap().is_empty());
     }
 }
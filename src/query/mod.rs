@@ -2,6 +2,8 @@
 
 pub mod engine;
 pub mod cache;
+pub mod deadcode;
+pub mod cycles;
 
 // TODO: Implement query engine
 // - Query execution